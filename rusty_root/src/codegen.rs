@@ -0,0 +1,44 @@
+use rusty_root_io::file::tfile::TFile;
+use rusty_root_io::streamer::codegen::generate_rust;
+use std::io;
+
+const USAGE: &str = "usage: rusty_root codegen <file.root> [--output <file.rs>] [Class...]";
+
+// rusty_root codegen <file.root> [--output <file.rs>] [Class...]
+// Writes the generated readers to the output file, or to stdout without --output.
+pub fn run(args: &[String]) -> io::Result<()> {
+    let mut path = None;
+    let mut output = None;
+    let mut classes = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-o" | "--output" => {
+                output = Some(
+                    args.next()
+                        .ok_or_else(|| usage_error("missing output file"))?,
+                );
+            }
+            _ if path.is_none() => path = Some(arg),
+            _ => classes.push(arg.as_str()),
+        }
+    }
+    let path = path.ok_or_else(|| usage_error("missing ROOT file"))?;
+
+    let file = TFile::open(path).map_err(io::Error::other)?;
+    let source = generate_rust(&file.streamer_info, &classes)?;
+    match output {
+        Some(output) => std::fs::write(output, source),
+        None => {
+            print!("{}", source);
+            Ok(())
+        }
+    }
+}
+
+fn usage_error(message: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!("{}\n{}", message, USAGE),
+    )
+}
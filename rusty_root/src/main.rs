@@ -1,3 +1,20 @@
-fn main() -> iced::Result {
-    rusty_root_gui::run()
+mod codegen;
+mod merge;
+
+use std::process::ExitCode;
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let result = match args.first().map(String::as_str) {
+        Some("codegen") => codegen::run(&args[1..]).map_err(|e| e.to_string()),
+        Some("merge") => merge::run(&args[1..]).map_err(|e| e.to_string()),
+        _ => rusty_root_gui::run().map_err(|e| e.to_string()),
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::FAILURE
+        }
+    }
 }
@@ -0,0 +1,80 @@
+use rusty_root_io::core::write_buffer::WriteObject;
+use rusty_root_io::file::merge::{merge_files, MergedFile};
+use rusty_root_io::file::writer::TFileWriter;
+use std::collections::HashMap;
+use std::io;
+use std::path::Path;
+
+const USAGE: &str = "usage: rusty_root merge [-f] <output.root> <input.root>...";
+
+// rusty_root merge [-f] <output.root> <input.root>...
+// Merges the inputs like hadd, -f overwrites an existing output file.
+pub fn run(args: &[String]) -> io::Result<()> {
+    let mut force = false;
+    let mut paths = Vec::new();
+    for arg in args {
+        match arg.as_str() {
+            "-f" | "--force" => force = true,
+            _ => paths.push(arg.as_str()),
+        }
+    }
+    let Some((output, inputs)) = paths.split_first() else {
+        return Err(usage_error("missing output file"));
+    };
+    if inputs.is_empty() {
+        return Err(usage_error("missing input files"));
+    }
+    if Path::new(output).exists() && !force {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("{} exists, use -f to overwrite it", output),
+        ));
+    }
+
+    let merged = merge_files(inputs)?;
+    for warning in &merged.warnings {
+        eprintln!("warning: {}", warning);
+    }
+    write(output, &merged)
+}
+
+// The merged directories, objects and trees, nothing is written if one of the objects cannot be
+fn write(output: &str, merged: &MergedFile) -> io::Result<()> {
+    if let Some(merged) = merged
+        .objects
+        .iter()
+        .find(|merged| !merged.object.is_writable())
+    {
+        return Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!(
+                "{} of class {} cannot be written, {} is not created",
+                merged.name, merged.class_name, output
+            ),
+        ));
+    }
+    let mut file = TFileWriter::create(output)?;
+    let mut directories = HashMap::from([(String::new(), file.top())]);
+    for directory in &merged.directories {
+        let (parent, name) = directory
+            .path
+            .rsplit_once('/')
+            .unwrap_or(("", directory.path.as_str()));
+        let id = file.mkdir(directories[parent], name, &directory.title)?;
+        directories.insert(directory.path.clone(), id);
+    }
+    for merged in &merged.objects {
+        file.put(directories[&merged.directory], &merged.name, &merged.object)?;
+    }
+    for tree in &merged.trees {
+        tree.write(&mut file, directories[&tree.directory])?;
+    }
+    file.close()
+}
+
+fn usage_error(message: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!("{}\n{}", message, USAGE),
+    )
+}
@@ -0,0 +1,267 @@
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::{parse_macro_input, Data, DeriveInput, Fields, LitInt, LitStr};
+
+/*
+ * #[derive(RootStreamer)] for structs streamed member-wise, see rusty_root_io::streamer::root_streamer.
+ *
+ * Struct attribute:
+ *     #[root(class = "TNamed", version = 1, checksum = 0xdfb74a3c)]
+ *     class defaults to the struct name, version and checksum to 0.
+ *
+ * Field attributes:
+ *     #[root(byte_count)] u32 set from the byte count of the object
+ *     #[root(version)]    u16 set from the class version of the object
+ *     #[root(base)]       base class, named after the class of the field in the schema
+ *     #[root(name = "fXaxis")] data member name, defaults to f_bar_offset -> fBarOffset
+ *     #[root(range = "[0,1,12]")] range of a Float16 or Double32 member, as in its comment
+ *     #[root(skip)]       not streamed, set with Default
+ */
+#[proc_macro_derive(RootStreamer, attributes(root))]
+pub fn derive_root_streamer(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+struct ClassAttrs {
+    class: String,
+    version: u16,
+    checksum: u32,
+}
+
+enum FieldRole {
+    ByteCount,
+    Version,
+    Base,
+    Member { name: String, range: Option<String> },
+    Skip,
+}
+
+fn expand(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    if !input.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &input.generics,
+            "RootStreamer cannot be derived for generic structs",
+        ));
+    }
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    &input.ident,
+                    "RootStreamer needs a struct with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(syn::Error::new_spanned(
+                &input.ident,
+                "RootStreamer can only be derived for structs",
+            ))
+        }
+    };
+
+    let ident = &input.ident;
+    let attrs = class_attrs(input)?;
+    let class = &attrs.class;
+    let version = attrs.version;
+    let checksum = attrs.checksum;
+
+    let mut reads = Vec::new();
+    let mut inits = Vec::new();
+    let mut schema = Vec::new();
+    for field in fields {
+        let name = field.ident.as_ref().expect("named field");
+        let ty = &field.ty;
+        let read = quote! {
+            <#ty as ::rusty_root_io::streamer::root_streamer::StreamerMember>::read_member(
+                reader, endian, &ctx,
+            )?
+        };
+        match field_role(field)? {
+            FieldRole::ByteCount => inits.push(quote! { #name: header.byte_count }),
+            FieldRole::Version => inits.push(quote! { #name: header.version }),
+            FieldRole::Skip => inits.push(quote! { #name: ::core::default::Default::default() }),
+            FieldRole::Base => {
+                reads.push(quote! { let #name = #read; });
+                inits.push(quote! { #name });
+                schema.push(quote! {
+                    ::rusty_root_io::streamer::registry::FieldSchema {
+                        name: <#ty as ::rusty_root_io::streamer::root_streamer::RootStreamer>::CLASS_NAME.to_string(),
+                        kind: ::rusty_root_io::streamer::registry::FieldKind::Base(
+                            <#ty as ::rusty_root_io::streamer::root_streamer::RootStreamer>::class_schema(),
+                        ),
+                    }
+                });
+            }
+            FieldRole::Member {
+                name: member,
+                range,
+            } => {
+                let kind = quote! {
+                    <#ty as ::rusty_root_io::streamer::root_streamer::StreamerMember>::field_kind()
+                };
+                let (read, kind) = match range {
+                    Some(range) => (
+                        quote! { <#ty>::read_with_range(reader, endian, #range)? },
+                        quote! { #kind.with_range(#range) },
+                    ),
+                    None => (read, kind),
+                };
+                reads.push(quote! { let #name = #read; });
+                inits.push(quote! { #name });
+                schema.push(quote! {
+                    ::rusty_root_io::streamer::registry::FieldSchema {
+                        name: #member.to_string(),
+                        kind: #kind,
+                    }
+                });
+            }
+        }
+    }
+
+    let reader = format_ident!("__R");
+    Ok(quote! {
+        impl ::rusty_root_io::streamer::root_streamer::RootStreamer for #ident {
+            const CLASS_NAME: &'static str = #class;
+            const CLASS_VERSION: u16 = #version;
+            const CHECKSUM: u32 = #checksum;
+
+            fn class_schema() -> ::std::sync::Arc<::rusty_root_io::streamer::registry::ClassSchema> {
+                ::rusty_root_io::streamer::root_streamer::class_schema::<Self>(|| {
+                    ::std::vec![#(#schema),*]
+                })
+            }
+        }
+
+        impl ::rusty_root_io::streamer::root_streamer::StreamerMember for #ident {
+            fn read_member<#reader: ::rusty_root_io::binrw::io::Read + ::rusty_root_io::binrw::io::Seek>(
+                reader: &mut #reader,
+                endian: ::rusty_root_io::binrw::Endian,
+                ctx: &::rusty_root_io::core::read_context::ReadContext,
+            ) -> ::rusty_root_io::binrw::BinResult<Self> {
+                <Self as ::rusty_root_io::binrw::BinRead>::read_options(reader, endian, (ctx.clone(),))
+            }
+
+            fn field_kind() -> ::rusty_root_io::streamer::registry::FieldKind {
+                ::rusty_root_io::streamer::registry::FieldKind::Object(
+                    <Self as ::rusty_root_io::streamer::root_streamer::RootStreamer>::class_schema(),
+                )
+            }
+        }
+
+        impl ::rusty_root_io::binrw::BinRead for #ident {
+            type Args<'a> = (::rusty_root_io::core::read_context::ReadContext,);
+
+            fn read_options<#reader: ::rusty_root_io::binrw::io::Read + ::rusty_root_io::binrw::io::Seek>(
+                reader: &mut #reader,
+                endian: ::rusty_root_io::binrw::Endian,
+                args: Self::Args<'_>,
+            ) -> ::rusty_root_io::binrw::BinResult<Self> {
+                #[allow(unused_variables)]
+                let (ctx,) = args;
+                let header = ::rusty_root_io::streamer::member::VersionHeader::read(reader, endian)?;
+                #(#reads)*
+                header.skip_to_end(reader)?;
+                Ok(Self {
+                    #(#inits),*
+                })
+            }
+        }
+    })
+}
+
+fn class_attrs(input: &DeriveInput) -> syn::Result<ClassAttrs> {
+    let mut attrs = ClassAttrs {
+        class: input.ident.to_string(),
+        version: 0,
+        checksum: 0,
+    };
+    for attr in input
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("root"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("class") {
+                attrs.class = meta.value()?.parse::<LitStr>()?.value();
+            } else if meta.path.is_ident("version") {
+                attrs.version = meta.value()?.parse::<LitInt>()?.base10_parse()?;
+            } else if meta.path.is_ident("checksum") {
+                attrs.checksum = meta.value()?.parse::<LitInt>()?.base10_parse()?;
+            } else {
+                return Err(meta.error("expected `class`, `version` or `checksum`"));
+            }
+            Ok(())
+        })?;
+    }
+    Ok(attrs)
+}
+
+fn field_role(field: &syn::Field) -> syn::Result<FieldRole> {
+    let ident = field.ident.as_ref().expect("named field");
+    let mut role = None;
+    let mut name = None;
+    let mut range = None;
+    for attr in field
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("root"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("byte_count") {
+                role = Some(FieldRole::ByteCount);
+            } else if meta.path.is_ident("version") {
+                role = Some(FieldRole::Version);
+            } else if meta.path.is_ident("base") {
+                role = Some(FieldRole::Base);
+            } else if meta.path.is_ident("skip") {
+                role = Some(FieldRole::Skip);
+            } else if meta.path.is_ident("name") {
+                name = Some(meta.value()?.parse::<LitStr>()?.value());
+            } else if meta.path.is_ident("range") {
+                range = Some(meta.value()?.parse::<LitStr>()?.value());
+            } else {
+                return Err(meta.error(
+                    "expected `byte_count`, `version`, `base`, `skip`, `name = \"...\"` or `range = \"...\"`",
+                ));
+            }
+            Ok(())
+        })?;
+    }
+    Ok(role.unwrap_or_else(|| FieldRole::Member {
+        name: name.unwrap_or_else(|| member_name(&ident.to_string())),
+        range,
+    }))
+}
+
+// name -> fName, f_bar_offset -> fBarOffset, like the data members of ROOT classes
+fn member_name(field: &str) -> String {
+    let field = field.strip_prefix("r#").unwrap_or(field);
+    let field = field.strip_prefix("f_").unwrap_or(field);
+    let mut name = String::from("f");
+    for part in field.split('_').filter(|part| !part.is_empty()) {
+        let mut chars = part.chars();
+        if let Some(first) = chars.next() {
+            name.extend(first.to_uppercase());
+            name.push_str(chars.as_str());
+        }
+    }
+    name
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_member_name() {
+        assert_eq!(member_name("name"), "fName");
+        assert_eq!(member_name("f_bar_offset"), "fBarOffset");
+        assert_eq!(member_name("f_ncells"), "fNcells");
+    }
+}
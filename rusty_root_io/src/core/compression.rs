@@ -1,17 +1,39 @@
 use flate2;
 use lz4_flex;
 use std::{
-    io::{self, Read},
+    io::{self, Read, Write},
     sync::Arc,
 };
+use twox_hash::XxHash64;
 use zstd;
 
+/*
+ * Compressed records of a ROOT file
+ * https://root.cern/doc/v638/classROOT_1_1RCompressionSetting.html
+ *
+ * The payload of a key is compressed in blocks of at most kMAXZIPBUF bytes, each with a 9 byte
+ * header: the algorithm ("ZL", "L4", "ZS"), a method or version byte, then the compressed and
+ * the uncompressed size of the block as 3 byte little-endian integers. An LZ4 block is followed
+ * by the big-endian XXH64 checksum of the compressed data before the data itself.
+ *
+ * The compression setting of a file (fCompress) is algorithm * 100 + level, level 0 means no
+ * compression.
+ */
 pub enum CompressionAlgorithm {
     Zlib,
     Lz4,
     Zstd,
     None,
 }
+
+// Largest block of a compressed record (kMAXZIPBUF)
+pub const K_MAX_ZIP_BUF: usize = 0xffffff;
+// Objects up to this size are not compressed, as in TKey
+pub const K_MIN_COMPRESSED_SIZE: usize = 256;
+
+const HEADER_SIZE: usize = 9;
+const LZ4_CHECKSUM_SIZE: usize = 8;
+
 impl CompressionAlgorithm {
     pub fn from_compression_level(level: i32) -> Self {
         let algo = level / 100;
@@ -48,15 +70,105 @@ impl CompressionAlgorithm {
                 Ok(Arc::from(decompressed_data))
             }
             CompressionAlgorithm::Lz4 => {
+                // an LZ4 block without its size, which is the uncompressed size of the header
+                let uncompressed_size = u32::from_le_bytes([data[6], data[7], data[8], 0]);
+                let block = data
+                    .get(HEADER_SIZE + LZ4_CHECKSUM_SIZE..)
+                    .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "LZ4 block"))?;
                 let decompressed_data =
-                    lz4_flex::decompress_size_prepended(data).map_err(|e| io::Error::other(e))?;
+                    lz4_flex::block::decompress(block, uncompressed_size as usize)
+                        .map_err(io::Error::other)?;
                 Ok(Arc::from(decompressed_data))
             }
             CompressionAlgorithm::Zstd => {
-                let decompressed_data = zstd::decode_all(data)?;
+                let decompressed_data = zstd::decode_all(&data[HEADER_SIZE..])?;
                 Ok(Arc::from(decompressed_data))
             }
             CompressionAlgorithm::None => Ok(Arc::from(data.to_vec())),
         }
     }
+
+    // The record of a payload for a compression setting (R__zipMultipleAlgorithm). The payload is
+    // kept as it is when it is small, not compressed, or does not shrink, like TKey does.
+    pub fn compress(setting: i32, data: &[u8]) -> io::Result<Vec<u8>> {
+        let level = setting.rem_euclid(100);
+        if level == 0 || data.len() <= K_MIN_COMPRESSED_SIZE {
+            return Ok(data.to_vec());
+        }
+        let algo = match Self::from_compression_level(setting) {
+            // the global default, zlib before ROOT 6.30
+            CompressionAlgorithm::None if setting < 100 => CompressionAlgorithm::Zlib,
+            algo => algo,
+        };
+        let mut record = Vec::with_capacity(data.len());
+        for block in data.chunks(K_MAX_ZIP_BUF) {
+            let (magic, compressed) = match algo {
+                CompressionAlgorithm::Zlib => {
+                    let level = flate2::Compression::new(level.min(9) as u32);
+                    let mut encoder = flate2::write::ZlibEncoder::new(Vec::new(), level);
+                    encoder.write_all(block)?;
+                    ([b'Z', b'L', 8], encoder.finish()?)
+                }
+                CompressionAlgorithm::Lz4 => {
+                    let compressed = lz4_flex::block::compress(block);
+                    let checksum = XxHash64::oneshot(0, &compressed);
+                    let mut checked = checksum.to_be_bytes().to_vec();
+                    checked.extend_from_slice(&compressed);
+                    ([b'L', b'4', 1], checked)
+                }
+                // ROOT doubles the level for zstd
+                CompressionAlgorithm::Zstd => {
+                    ([b'Z', b'S', 1], zstd::encode_all(block, 2 * level)?)
+                }
+                CompressionAlgorithm::None => return Ok(data.to_vec()),
+            };
+            if compressed.len() > K_MAX_ZIP_BUF {
+                return Ok(data.to_vec());
+            }
+            record.extend_from_slice(&magic);
+            record.extend_from_slice(&(compressed.len() as u32).to_le_bytes()[..3]);
+            record.extend_from_slice(&(block.len() as u32).to_le_bytes()[..3]);
+            record.extend_from_slice(&compressed);
+        }
+        if record.len() >= data.len() {
+            return Ok(data.to_vec());
+        }
+        Ok(record)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compress_round_trip() {
+        let data: Vec<u8> = (0..5000u32).flat_map(|i| (i % 97).to_be_bytes()).collect();
+        for setting in [1, 101, 404, 505] {
+            let record = CompressionAlgorithm::compress(setting, &data).unwrap();
+            assert!(record.len() < data.len());
+            assert_eq!(
+                &*CompressionAlgorithm::decompress(&record).unwrap(),
+                &data[..]
+            );
+        }
+        assert_eq!(&record_magic(101, &data), b"ZL");
+        assert_eq!(&record_magic(404, &data), b"L4");
+        assert_eq!(&record_magic(505, &data), b"ZS");
+        // not compressed: no compression, small objects
+        assert_eq!(CompressionAlgorithm::compress(0, &data).unwrap(), data);
+        assert_eq!(
+            CompressionAlgorithm::compress(505, &data[..256]).unwrap(),
+            &data[..256]
+        );
+
+        // the checksum of an LZ4 block is XXH64 with seed 0
+        assert_eq!(XxHash64::oneshot(0, b""), 0xEF46DB3751D8E999);
+        assert_eq!(XxHash64::oneshot(0, b"abc"), 0x44BC2CF5AD770999);
+    }
+
+    fn record_magic(setting: i32, data: &[u8]) -> [u8; 2] {
+        let record = CompressionAlgorithm::compress(setting, data).unwrap();
+        [record[0], record[1]]
+    }
 }
@@ -6,3 +6,45 @@ pub const K_NULLTAG: u32 = 0x0000_0000; // null
                                         // pub const K_MAP_OFFSET: u32 = 2;
 pub const K_MAP_OFFSET: u32 = 0x0000_0002; // offset for map in streamer info
 pub const K_IS_REFERENCED: u32 = 0x0000_0010; // bit24 in TKey::fBits, indicates the object is referenced by other objects
+pub const K_BYPASS_STREAMER: u32 = 0x0000_1000; // bit12 in TObject::fBits, a TClonesArray written member-wise
+pub const K_STREAMED_MEMBERWISE: u16 = 0x4000; // bit14 in the version of an STL collection written member-wise
+
+// Data member types of a TStreamerElement::fType (TVirtualStreamerInfo::EReadWrite)
+pub const K_BASE: u32 = 0;
+pub const K_CHAR: u32 = 1;
+pub const K_SHORT: u32 = 2;
+pub const K_INT: u32 = 3;
+pub const K_LONG: u32 = 4;
+pub const K_FLOAT: u32 = 5;
+pub const K_COUNTER: u32 = 6;
+pub const K_CHAR_STAR: u32 = 7;
+pub const K_DOUBLE: u32 = 8;
+pub const K_DOUBLE32: u32 = 9;
+pub const K_LEGACY_CHAR: u32 = 10;
+pub const K_UCHAR: u32 = 11;
+pub const K_USHORT: u32 = 12;
+pub const K_UINT: u32 = 13;
+pub const K_ULONG: u32 = 14;
+pub const K_BITS: u32 = 15;
+pub const K_LONG64: u32 = 16;
+pub const K_ULONG64: u32 = 17;
+pub const K_BOOL: u32 = 18;
+pub const K_FLOAT16: u32 = 19;
+pub const K_OFFSET_L: u32 = 20; // fixed size array: basic type + kOffsetL
+pub const K_OFFSET_P: u32 = 40; // array through a pointer with a counter: basic type + kOffsetP
+pub const K_OBJECT: u32 = 61;
+pub const K_ANY: u32 = 62;
+pub const K_OBJECTP: u32 = 63; // `TClass* fX; //->` pointer streamed in place, never null
+pub const K_OBJECT_P: u32 = 64; // `TClass* fX;` pointer streamed with a class tag
+pub const K_TSTRING: u32 = 65;
+pub const K_TOBJECT: u32 = 66;
+pub const K_TNAMED: u32 = 67;
+pub const K_ANYP: u32 = 68; // like kObjectp for classes not deriving from TObject
+pub const K_ANY_P: u32 = 69; // like kObjectP for classes not deriving from TObject
+pub const K_STL: u32 = 300;
+pub const K_STL_STRING: u32 = 365;
+pub const K_STREAMER: u32 = 500;
+pub const K_STREAM_LOOP: u32 = 501;
+
+// TStreamerSTL::fSTLtype (ROOT::ESTLType)
+pub const K_STL_VECTOR: u32 = 1;
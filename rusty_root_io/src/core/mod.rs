@@ -1,3 +1,5 @@
 pub mod compression;
 pub mod constant;
+pub mod read_context;
 pub mod utils;
+pub mod write_buffer;
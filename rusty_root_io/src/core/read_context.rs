@@ -0,0 +1,372 @@
+use crate::core::constant::{
+    K_BYTECOUNTMASK, K_HAS_BYTECOUNT, K_MAP_OFFSET, K_NEWCLASSTAG, K_NEW_CLASSBIT, K_NULLTAG,
+};
+use crate::core::utils::ClassInfo;
+use crate::streamer::registry::ClassSchema;
+use crate::streamer::streamerinfo::StreamerRegistry;
+use binrw::io::{Read, Seek, SeekFrom};
+use binrw::{BinRead, BinReaderExt, BinResult, Endian};
+use std::any::Any;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::io;
+use std::rc::Rc;
+use std::sync::Arc;
+
+/*
+ * Per-buffer read context, the reading side of TBufferFile::fMap.
+ * https://root.cern/doc/v638/classTBufferFile.html
+ *
+ * Every class name and every object streamed through a pointer is mapped under its offset in the
+ * key buffer (plus kMapOffset, so that no entry is ever kNullTag). Later class tags and object
+ * tags are such offsets and refer back to the mapped entries.
+ *
+ * The key buffer starts at the TKey header, so offsets include key_len. A reader over the combined
+ * key + payload cursor (see TKey::decompress_full) needs no displacement, a reader that only holds
+ * the payload must be displaced by key_len.
+ *
+ * The context is a cheap handle: clones share the same maps, so it can be passed down as binrw args.
+ * It can also carry the StreamerRegistry of the file, for the classes read from their streamer
+ * info rather than from a hand-written layout (TPad, TCanvas, TLegend).
+ */
+#[derive(Clone, Default)]
+pub struct ReadContext {
+    inner: Rc<RefCell<ReadMaps>>,
+}
+
+#[derive(Default)]
+struct ReadMaps {
+    displacement: u64,
+    classes: HashMap<u32, String>,
+    objects: HashMap<u32, MappedObject>,
+    schemas: Option<Arc<StreamerRegistry>>,
+}
+
+// An object mapped in the buffer, `object` stays None while the object itself is being read
+// and for objects that were skipped.
+#[derive(Clone)]
+pub struct MappedObject {
+    pub class_name: String,
+    pub object: Option<Arc<dyn Any + Send + Sync>>,
+}
+
+// What sits at the start of an object written through a pointer (TBufferFile::ReadObjectAny).
+#[derive(Debug, Clone, PartialEq)]
+pub enum ObjectHeader {
+    Null,
+    // back-reference to an object read earlier from the same buffer, `tag` is its map offset
+    Reference {
+        tag: u32,
+    },
+    // a new object, `start` is the reader position of its byte count
+    New {
+        start: u64,
+        byte_count: u32,
+        class_info: ClassInfo,
+    },
+}
+
+impl ReadContext {
+    // Reader position 0 is the start of the key buffer.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Reader position 0 is the start of the payload, i.e. key_len bytes into the key buffer.
+    pub fn with_key_len(key_len: u16) -> Self {
+        let ctx = Self::default();
+        ctx.inner.borrow_mut().displacement = key_len as u64;
+        ctx
+    }
+
+    // The same context, with the class schemas of the file the buffer comes from.
+    pub fn with_schemas(self, schemas: Arc<StreamerRegistry>) -> Self {
+        self.inner.borrow_mut().schemas = Some(schemas);
+        self
+    }
+
+    // Schema of a class in the streamer info of the file, None without registry.
+    pub fn schema(&self, class: &str) -> Option<Arc<ClassSchema>> {
+        self.inner.borrow().schemas.as_ref()?.get(class)
+    }
+
+    // Offset in the key buffer of a reader position.
+    pub fn buffer_offset(&self, pos: u64) -> u32 {
+        (pos + self.inner.borrow().displacement) as u32
+    }
+
+    // Reader position of an offset in the key buffer.
+    pub fn reader_position(&self, offset: u32) -> io::Result<u64> {
+        (offset as u64)
+            .checked_sub(self.inner.borrow().displacement)
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("buffer offset {:#X} lies before the reader start", offset),
+                )
+            })
+    }
+
+    // Map a class name read from a kNewClassTag at reader position `tag_pos`.
+    pub fn map_class(&self, tag_pos: u64, class_name: &str) {
+        let tag = self.buffer_offset(tag_pos) + K_MAP_OFFSET;
+        self.inner
+            .borrow_mut()
+            .classes
+            .insert(tag, class_name.to_string());
+    }
+
+    // Class name referred to by a class tag, with kClassMask already removed.
+    pub fn class_at(&self, tag: u32) -> Option<String> {
+        self.inner.borrow().classes.get(&tag).cloned()
+    }
+
+    // Map an object whose byte count starts at reader position `start`.
+    pub fn map_object(
+        &self,
+        start: u64,
+        class_name: &str,
+        object: Option<Arc<dyn Any + Send + Sync>>,
+    ) {
+        let tag = self.buffer_offset(start) + K_MAP_OFFSET;
+        self.inner.borrow_mut().objects.insert(
+            tag,
+            MappedObject {
+                class_name: class_name.to_string(),
+                object,
+            },
+        );
+    }
+
+    // Object referred to by an object tag.
+    pub fn object_at(&self, tag: u32) -> Option<MappedObject> {
+        self.inner.borrow().objects.get(&tag).cloned()
+    }
+
+    pub fn read_object_header<R: Read + Seek>(
+        &self,
+        reader: &mut R,
+        endian: Endian,
+    ) -> BinResult<ObjectHeader> {
+        let start = reader.stream_position()?;
+        let first: u32 = reader.read_type(endian)?;
+        if first == K_NULLTAG {
+            return Ok(ObjectHeader::Null);
+        }
+        // Objects written without byte count start directly with their class tag
+        let has_byte_count = first & K_HAS_BYTECOUNT != 0 && first != K_NEWCLASSTAG;
+        if !has_byte_count && first & K_NEW_CLASSBIT == 0 {
+            return Ok(ObjectHeader::Reference { tag: first });
+        }
+        let byte_count = if has_byte_count {
+            first & K_BYTECOUNTMASK
+        } else {
+            reader.seek(SeekFrom::Start(start))?;
+            0
+        };
+        let class_info = ClassInfo::read_class_info(reader, self)?;
+        Ok(ObjectHeader::New {
+            start,
+            byte_count,
+            class_info,
+        })
+    }
+
+    // Read an object written through a pointer: null, a back-reference to an object already read
+    // from this buffer, or a new object which T reads itself, starting at its byte count.
+    pub fn read_object<T, R>(&self, reader: &mut R, endian: Endian) -> BinResult<Option<Arc<T>>>
+    where
+        T: for<'a> BinRead<Args<'a> = (ReadContext,)> + Send + Sync + 'static,
+        R: Read + Seek,
+    {
+        self.read_pointer(reader, endian, |reader, start| {
+            reader.seek(SeekFrom::Start(start))?;
+            T::read_options(reader, endian, (self.clone(),))
+        })
+    }
+
+    // Like read_object, for types that only read the streamed object, i.e. what follows the class
+    // tag (TClass::Streamer in TBufferFile::ReadObjectAny).
+    pub fn read_streamed_object<T, R>(
+        &self,
+        reader: &mut R,
+        endian: Endian,
+    ) -> BinResult<Option<Arc<T>>>
+    where
+        T: for<'a> BinRead<Args<'a> = (ReadContext,)> + Send + Sync + 'static,
+        R: Read + Seek,
+    {
+        self.read_pointer(reader, endian, |reader, _start| {
+            T::read_options(reader, endian, (self.clone(),))
+        })
+    }
+
+    fn read_pointer<T, R, F>(
+        &self,
+        reader: &mut R,
+        endian: Endian,
+        read: F,
+    ) -> BinResult<Option<Arc<T>>>
+    where
+        T: Send + Sync + 'static,
+        R: Read + Seek,
+        F: FnOnce(&mut R, u64) -> BinResult<T>,
+    {
+        match self.read_object_header(reader, endian)? {
+            ObjectHeader::Null => Ok(None),
+            ObjectHeader::Reference { tag } => {
+                let pos = reader.stream_position()?;
+                let mapped = self.object_at(tag).ok_or_else(|| {
+                    invalid_data(format!("no object mapped at tag {:#X} ({:#X})", tag, pos))
+                })?;
+                let object = mapped.object.ok_or_else(|| {
+                    invalid_data(format!(
+                        "{} at tag {:#X} is referenced before it was read",
+                        mapped.class_name, tag
+                    ))
+                })?;
+                let object = object.downcast::<T>().map_err(|_| {
+                    invalid_data(format!(
+                        "{} at tag {:#X} is not a {}",
+                        mapped.class_name,
+                        tag,
+                        std::any::type_name::<T>()
+                    ))
+                })?;
+                Ok(Some(object))
+            }
+            ObjectHeader::New {
+                start,
+                byte_count,
+                class_info,
+            } => {
+                let class_name = class_info.get_class_name();
+                // ROOT maps the object before reading it, so self references resolve to the slot
+                self.map_object(start, &class_name, None);
+                let object = Arc::new(read(reader, start)?);
+                self.map_object(start, &class_name, Some(object.clone()));
+                if byte_count != 0 {
+                    reader.seek(SeekFrom::Start(start + 4 + byte_count as u64))?;
+                }
+                Ok(Some(object))
+            }
+        }
+    }
+}
+
+fn invalid_data(message: String) -> binrw::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message).into()
+}
+
+impl fmt::Debug for ReadContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let maps = self.inner.borrow();
+        f.debug_struct("ReadContext")
+            .field("displacement", &maps.displacement)
+            .field("classes", &maps.classes)
+            .field("objects", &maps.objects)
+            .finish()
+    }
+}
+
+impl fmt::Debug for MappedObject {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MappedObject")
+            .field("class_name", &self.class_name)
+            .field("read", &self.object.is_some())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[derive(Debug)]
+    struct TFoo {
+        class_info: ClassInfo,
+        value: u32,
+    }
+
+    impl BinRead for TFoo {
+        type Args<'a> = (ReadContext,);
+
+        fn read_options<R: Read + Seek>(
+            reader: &mut R,
+            endian: Endian,
+            args: Self::Args<'_>,
+        ) -> BinResult<Self> {
+            let (ctx,) = args;
+            let _byte_count: u32 = reader.read_type(endian)?;
+            let class_info = ClassInfo::read_class_info(reader, &ctx)?;
+            let _version: u16 = reader.read_type(endian)?;
+            let value = reader.read_type(endian)?;
+            Ok(Self { class_info, value })
+        }
+    }
+
+    // payload of a key with key_len 10: a new TFoo, a second TFoo sharing its class,
+    // a null pointer and a back-reference to the first TFoo
+    fn payload() -> Vec<u8> {
+        let mut buf = Vec::new();
+        buf.extend_from_slice(&(K_HAS_BYTECOUNT | 15).to_be_bytes());
+        buf.extend_from_slice(&K_NEWCLASSTAG.to_be_bytes());
+        buf.extend_from_slice(b"TFoo\0");
+        buf.extend_from_slice(&1u16.to_be_bytes());
+        buf.extend_from_slice(&7u32.to_be_bytes());
+        // class tag: key_len + position of the kNewClassTag + kMapOffset
+        buf.extend_from_slice(&(K_HAS_BYTECOUNT | 10).to_be_bytes());
+        buf.extend_from_slice(&(K_NEW_CLASSBIT | (10 + 4 + K_MAP_OFFSET)).to_be_bytes());
+        buf.extend_from_slice(&1u16.to_be_bytes());
+        buf.extend_from_slice(&8u32.to_be_bytes());
+        buf.extend_from_slice(&K_NULLTAG.to_be_bytes());
+        // object tag: key_len + position of the first byte count + kMapOffset
+        buf.extend_from_slice(&(10 + K_MAP_OFFSET).to_be_bytes());
+        buf
+    }
+
+    #[test]
+    fn test_read_objects_with_references() {
+        let mut cursor = Cursor::new(payload());
+        let ctx = ReadContext::with_key_len(10);
+        let first = ctx
+            .read_object::<TFoo, _>(&mut cursor, Endian::Big)
+            .expect("Failed to read first object")
+            .expect("first object is null");
+        let second = ctx
+            .read_object::<TFoo, _>(&mut cursor, Endian::Big)
+            .expect("Failed to read second object")
+            .expect("second object is null");
+        let null = ctx
+            .read_object::<TFoo, _>(&mut cursor, Endian::Big)
+            .expect("Failed to read null object");
+        let reference = ctx
+            .read_object::<TFoo, _>(&mut cursor, Endian::Big)
+            .expect("Failed to read reference")
+            .expect("reference is null");
+
+        assert_eq!(first.class_info, ClassInfo::NewClass("TFoo".to_string()));
+        assert_eq!(first.value, 7);
+        assert_eq!(
+            second.class_info,
+            ClassInfo::Offset {
+                offset: 14,
+                class_name: "TFoo".to_string()
+            }
+        );
+        assert_eq!(second.value, 8);
+        assert!(null.is_none());
+        assert!(Arc::ptr_eq(&first, &reference));
+    }
+
+    #[test]
+    fn test_reference_to_unknown_object() {
+        let mut cursor = Cursor::new(0x20u32.to_be_bytes().to_vec());
+        let ctx = ReadContext::new();
+        assert!(ctx
+            .read_object::<TFoo, _>(&mut cursor, Endian::Big)
+            .is_err());
+    }
+}
@@ -1,5 +1,6 @@
 use crate::core::constant::{K_MAP_OFFSET, K_NEWCLASSTAG, K_NEW_CLASSBIT};
-use binrw::{BinRead, BinReaderExt, BinResult, Endian};
+use crate::core::read_context::ReadContext;
+use binrw::{BinRead, BinResult, Endian};
 use byteorder::{BigEndian, ReadBytesExt};
 use std::io;
 use std::io::{Read, Seek, SeekFrom};
@@ -59,42 +60,41 @@ impl Default for ClassInfo {
 }
 
 impl ClassInfo {
-    pub fn read_class_info<R: Read + Seek>(reader: &mut R) -> io::Result<Self> {
+    // Class tags are offsets in the key buffer, resolved through the read context. A class that
+    // was skipped over is looked up directly in the buffer, the name follows its kNewClassTag.
+    pub fn read_class_info<R: Read + Seek>(reader: &mut R, ctx: &ReadContext) -> io::Result<Self> {
+        let tag_pos = reader.stream_position()?;
         let tag = reader.read_u32::<BigEndian>()?;
         if tag == K_NEWCLASSTAG {
-            let mut name = Vec::new();
-            let mut byte = [0u8; 1];
-            loop {
-                reader.read_exact(&mut byte)?;
-                if byte[0] == 0 {
-                    break;
-                }
-                name.push(byte[0]);
-            }
-            let class_name = String::from_utf8_lossy(&name).into_owned();
+            let class_name = read_class_name(reader)?;
+            ctx.map_class(tag_pos, &class_name);
             Ok(ClassInfo::NewClass(class_name))
-        } else {
-            let offset = if tag & K_NEW_CLASSBIT != 0 {
-                (tag & !K_NEW_CLASSBIT) - K_MAP_OFFSET
-            } else {
-                tag
-            };
-
-            let current_pos = reader.stream_position()?;
-            reader.seek(SeekFrom::Start(offset as u64 + 4))?; // 64 is key length, 4 is the tag we just read
-            let mut name_bytes = Vec::new();
-            let mut byte = [0u8; 1];
-            loop {
-                reader.read_exact(&mut byte)?;
-                if byte[0] == 0 {
-                    break;
+        } else if tag & K_NEW_CLASSBIT != 0 {
+            let class_tag = tag & !K_NEW_CLASSBIT;
+            let offset = class_tag.checked_sub(K_MAP_OFFSET).ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("invalid class tag {:#X} at {:#X}", tag, tag_pos),
+                )
+            })?;
+            let class_name = match ctx.class_at(class_tag) {
+                Some(class_name) => class_name,
+                None => {
+                    let current_pos = reader.stream_position()?;
+                    let new_class_pos = ctx.reader_position(offset)?;
+                    reader.seek(SeekFrom::Start(new_class_pos + 4))?; // skip the kNewClassTag
+                    let class_name = read_class_name(reader)?;
+                    reader.seek(SeekFrom::Start(current_pos))?;
+                    ctx.map_class(new_class_pos, &class_name);
+                    class_name
                 }
-                name_bytes.push(byte[0]);
-            }
-            reader.seek(SeekFrom::Start(current_pos))?;
-            let class_name = String::from_utf8_lossy(&name_bytes).into_owned();
-
+            };
             Ok(ClassInfo::Offset { offset, class_name })
+        } else {
+            Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("expected a class tag at {:#X}, found {:#X}", tag_pos, tag),
+            ))
         }
     }
 
@@ -106,53 +106,29 @@ impl ClassInfo {
     }
 }
 
+fn read_class_name<R: Read>(reader: &mut R) -> io::Result<String> {
+    let mut name = Vec::new();
+    let mut byte = [0u8; 1];
+    loop {
+        reader.read_exact(&mut byte)?;
+        if byte[0] == 0 {
+            break;
+        }
+        name.push(byte[0]);
+    }
+    Ok(String::from_utf8_lossy(&name).into_owned())
+}
+
 impl BinRead for ClassInfo {
-    type Args<'a> = ();
+    type Args<'a> = (ReadContext,);
 
     fn read_options<R: Read + Seek>(
         reader: &mut R,
-        endian: Endian,
-        _args: Self::Args<'_>,
+        _endian: Endian,
+        args: Self::Args<'_>,
     ) -> BinResult<Self> {
-        // 1. Read the 32-bit tag
-        let tag: u32 = reader.read_type(endian)?;
-
-        if tag == K_NEWCLASSTAG {
-            // 2. Handle NewClass: Read null-terminated string
-            let mut name_bytes = Vec::new();
-            loop {
-                let byte: u8 = reader.read_type(endian)?;
-                if byte == 0 {
-                    break;
-                }
-                name_bytes.push(byte);
-            }
-            let name = String::from_utf8_lossy(&name_bytes).into_owned();
-            Ok(ClassInfo::NewClass(name))
-        } else {
-            // 3. Handle Offset: Perform bitwise logic
-            let offset = if (tag & K_NEW_CLASSBIT) != 0 {
-                (tag & !K_NEW_CLASSBIT) - K_MAP_OFFSET
-            } else {
-                tag
-            };
-
-            let current_pos = reader.stream_position()?;
-            reader.seek(SeekFrom::Start(offset as u64 + 4))?;
-
-            let mut name_bytes = Vec::new();
-            loop {
-                let byte: u8 = reader.read_type(endian)?;
-                if byte == 0 {
-                    break;
-                }
-                name_bytes.push(byte);
-            }
-            reader.seek(SeekFrom::Start(current_pos))?;
-            let class_name = String::from_utf8_lossy(&name_bytes).into_owned();
-
-            Ok(ClassInfo::Offset { offset, class_name })
-        }
+        let (ctx,) = args;
+        Ok(Self::read_class_info(reader, &ctx)?)
     }
 }
 
@@ -169,6 +145,35 @@ pub fn decode_datime(datime: u32) -> String {
     )
 }
 
+// TDatime::Set, the inverse of decode_datime
+pub fn encode_datime(year: u32, month: u32, day: u32, hour: u32, minute: u32, second: u32) -> u32 {
+    (year.saturating_sub(1995) << 26)
+        | (month << 22)
+        | (day << 17)
+        | (hour << 12)
+        | (minute << 6)
+        | second
+}
+
+// The current date and time as a TDatime, in UTC rather than in local time as ROOT does
+pub fn datime_now() -> u32 {
+    let seconds = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs());
+    let (days, time) = (seconds / 86400, (seconds % 86400) as u32);
+    // civil_from_days of H. Hinnant, days since 1970-01-01 to a proleptic Gregorian date
+    let z = days as i64 + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = (yoe + era * 400 + i64::from(month <= 2)) as u32;
+    encode_datime(year, month, day, time / 3600, time / 60 % 60, time % 60)
+}
+
 pub fn debug_in_ascii(bytes: &[u8]) -> String {
     bytes
         .iter()
@@ -0,0 +1,270 @@
+use crate::core::constant::{
+    K_HAS_BYTECOUNT, K_IS_REFERENCED, K_MAP_OFFSET, K_NEWCLASSTAG, K_NEW_CLASSBIT, K_NULLTAG,
+};
+use crate::objects::tobject::TObject;
+use binrw::{BinWrite, Endian};
+use std::collections::HashMap;
+use std::io::{Cursor, Seek, SeekFrom};
+
+/*
+ * The buffer of a key being written, the writing side of TBufferFile.
+ * https://root.cern/doc/v638/classTBufferFile.html
+ *
+ * Objects written through a pointer start with a byte count and a class tag. A class is named the
+ * first time it is written (kNewClassTag and the name) and referred to afterwards by the offset of
+ * its name in the key buffer, plus kMapOffset. The key buffer starts at the TKey header, so the
+ * buffer is displaced by the length of the header, as ReadContext expects when reading it back.
+ * An object written twice, e.g. a leaf in its branch and in the leaves of the tree, is written once
+ * and referred to afterwards by the offset of its byte count, plus kMapOffset.
+ *
+ *     let mut buf = WriteBuffer::new(key.key_len as u32);
+ *     histogram.write_to(&mut buf);
+ */
+#[derive(Debug, Default)]
+pub struct WriteBuffer {
+    bytes: Vec<u8>,
+    displacement: u32,
+    classes: HashMap<String, u32>,
+    // the objects written with write_shared, by address
+    objects: HashMap<usize, u32>,
+}
+
+// The counterpart of BinRead for the objects of a key: byte count, version and members. An object
+// written in a key has no class tag, the class name is in the key.
+pub trait WriteObject {
+    fn class_name(&self) -> String;
+
+    // the title of a key holding the object
+    fn title(&self) -> &str {
+        ""
+    }
+
+    fn write_to(&self, buf: &mut WriteBuffer);
+
+    // false for the objects that are read but cannot be written back, e.g. undecoded classes
+    fn is_writable(&self) -> bool {
+        true
+    }
+}
+
+impl WriteBuffer {
+    pub fn new(displacement: u32) -> Self {
+        Self {
+            displacement,
+            ..Default::default()
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.bytes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+
+    // The classes written with a class tag, whose streamer info the file needs
+    pub fn class_names(&self) -> impl Iterator<Item = &str> {
+        self.classes.keys().map(String::as_str)
+    }
+
+    pub fn put(&mut self, bytes: &[u8]) {
+        self.bytes.extend_from_slice(bytes);
+    }
+
+    // A value in big-endian, with its BinWrite
+    pub fn write<T>(&mut self, value: &T)
+    where
+        T: BinWrite,
+        for<'a> T::Args<'a>: Default,
+    {
+        let mut cursor = Cursor::new(&mut self.bytes);
+        cursor
+            .seek(SeekFrom::End(0))
+            .and_then(|_| {
+                value
+                    .write_options(&mut cursor, Endian::Big, Default::default())
+                    .map_err(std::io::Error::other)
+            })
+            .expect("writing to memory does not fail");
+    }
+
+    // TString::Streamer: a length byte, or 255 and the length as an int, then the characters
+    pub fn string(&mut self, s: &str) {
+        if s.len() < 255 {
+            self.bytes.push(s.len() as u8);
+        } else {
+            self.bytes.push(255);
+            self.write(&(s.len() as u32));
+        }
+        self.put(s.as_bytes());
+    }
+
+    // A bool as one byte
+    pub fn bool(&mut self, value: bool) {
+        self.bytes.push(value as u8);
+    }
+
+    // `T* fArray; //[fN]`: 1 and the values, 0 for an empty array (TBuffer::WriteFastArray)
+    pub fn basic_pointer<T>(&mut self, values: &[T])
+    where
+        T: BinWrite,
+        for<'a> T::Args<'a>: Default,
+    {
+        self.bool(!values.is_empty());
+        for value in values {
+            self.write(value);
+        }
+    }
+
+    // TObject::Streamer: version, fUniqueID and fBits, without byte count
+    pub fn tobject(&mut self, tobject: &TObject) {
+        self.write(&1u16);
+        self.write(&tobject.f_uniqueid);
+        self.write(&tobject.f_bits);
+        if tobject.f_bits & K_IS_REFERENCED != 0 {
+            self.write(&tobject.pidf);
+        }
+    }
+
+    // Byte count and version, then the members written by `members` (TBufferFile::WriteVersion)
+    pub fn versioned(&mut self, version: u16, members: impl FnOnce(&mut Self)) {
+        let start = self.bytes.len();
+        self.put(&[0; 4]);
+        self.write(&version);
+        members(self);
+        self.patch_byte_count(start);
+    }
+
+    // An object written through a pointer: byte count, class tag, then the streamed object
+    pub fn object(&mut self, class: &str, streamed: impl FnOnce(&mut Self)) {
+        let start = self.bytes.len();
+        self.put(&[0; 4]);
+        match self.classes.get(class) {
+            Some(tag) => {
+                let tag = K_NEW_CLASSBIT | tag;
+                self.write(&tag);
+            }
+            None => {
+                let tag = self.displacement + self.bytes.len() as u32 + K_MAP_OFFSET;
+                self.classes.insert(class.to_string(), tag);
+                self.write(&K_NEWCLASSTAG);
+                self.put(class.as_bytes());
+                self.put(&[0]);
+            }
+        }
+        streamed(self);
+        self.patch_byte_count(start);
+    }
+
+    // TBufferFile::WriteObjectAny of an object, with its class tag
+    pub fn write_object<T: WriteObject + ?Sized>(&mut self, object: &T) {
+        self.object(&object.class_name(), |buf| object.write_to(buf));
+    }
+
+    // TBufferFile::WriteObjectAny of an object that may be written more than once in the buffer:
+    // the object the first time, its tag afterwards
+    pub fn write_shared<T: WriteObject + ?Sized>(&mut self, object: &T) {
+        let address = object as *const T as *const () as usize;
+        if let Some(tag) = self.objects.get(&address) {
+            let tag = *tag;
+            self.write(&tag);
+            return;
+        }
+        let tag = self.displacement + self.bytes.len() as u32 + K_MAP_OFFSET;
+        self.objects.insert(address, tag);
+        self.write_object(object);
+    }
+
+    // A null pointer
+    pub fn null(&mut self) {
+        self.write(&K_NULLTAG);
+    }
+
+    pub fn write_object_or_null<T: WriteObject + ?Sized>(&mut self, object: Option<&T>) {
+        match object {
+            Some(object) => self.write_object(object),
+            None => self.null(),
+        }
+    }
+
+    fn patch_byte_count(&mut self, start: usize) {
+        let count = K_HAS_BYTECOUNT | (self.bytes.len() - start - 4) as u32;
+        self.bytes[start..start + 4].copy_from_slice(&count.to_be_bytes());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::read_context::ReadContext;
+    use crate::objects::tobjstring::TObjString;
+    use crate::objects::tstring::TString;
+
+    #[test]
+    fn test_class_tags() {
+        // after a key header of 50 bytes
+        let mut buf = WriteBuffer::new(50);
+        let strings = ["first", "second"].map(|s| TObjString {
+            string: TString::new(s),
+            ..Default::default()
+        });
+        for string in &strings {
+            buf.write_object(string);
+        }
+        buf.null();
+        let bytes = buf.into_bytes();
+        assert_eq!(&bytes[4..8], &K_NEWCLASSTAG.to_be_bytes());
+        // the second object refers to the name of the class at 4 in the buffer
+        let second = 4 + 4 + 11 + 4 + 2 + 10 + 6;
+        let tag = K_NEW_CLASSBIT | (50 + 4 + K_MAP_OFFSET);
+        assert_eq!(&bytes[second + 4..second + 8], &tag.to_be_bytes());
+
+        // read back with the displacement of the key header
+        let mut cursor = Cursor::new(bytes);
+        let ctx = ReadContext::with_key_len(50);
+        for s in ["first", "second"] {
+            let object = ctx
+                .read_streamed_object::<TObjString, _>(&mut cursor, Endian::Big)
+                .unwrap()
+                .unwrap();
+            assert_eq!(object.as_str(), s);
+        }
+        let null = ctx.read_streamed_object::<TObjString, _>(&mut cursor, Endian::Big);
+        assert!(null.unwrap().is_none());
+    }
+
+    #[test]
+    fn test_shared_objects() {
+        let mut buf = WriteBuffer::new(50);
+        let string = TObjString {
+            string: TString::new("leaf"),
+            ..Default::default()
+        };
+        buf.write_shared(&string);
+        buf.write_shared(&string);
+        let bytes = buf.into_bytes();
+        // the object at 0 in the buffer
+        let tag = 50 + K_MAP_OFFSET;
+        assert_eq!(&bytes[bytes.len() - 4..], &tag.to_be_bytes());
+
+        let mut cursor = Cursor::new(bytes);
+        let ctx = ReadContext::with_key_len(50);
+        let objects: Vec<_> = (0..2)
+            .map(|_| {
+                ctx.read_streamed_object::<TObjString, _>(&mut cursor, Endian::Big)
+                    .unwrap()
+                    .unwrap()
+            })
+            .collect();
+        assert!(std::sync::Arc::ptr_eq(&objects[0], &objects[1]));
+    }
+}
@@ -1,8 +1,9 @@
 use crate::core::utils::ReaderDynWidth;
+use crate::objects::tdictionary::TDictData;
 use crate::objects::tkey::TKey;
 use crate::objects::tstring::TString;
-use binrw::io::{Read, Seek};
-use binrw::{binread, BinRead, BinReaderExt, BinResult, Endian};
+use binrw::io::{Read, Seek, Write};
+use binrw::{binread, BinRead, BinReaderExt, BinResult, BinWrite, Endian};
 
 #[binread]
 #[derive(Default, Debug)]
@@ -16,6 +17,20 @@ impl FirstRecordDict {
         Self::read_be(reader)
     }
 }
+
+impl BinWrite for FirstRecordDict {
+    type Args<'a> = ();
+
+    fn write_options<W: Write + Seek>(
+        &self,
+        writer: &mut W,
+        endian: Endian,
+        _args: Self::Args<'_>,
+    ) -> BinResult<()> {
+        self.key.write_options(writer, endian, ())?;
+        self.data.write_options(writer, endian, ())
+    }
+}
 /*
  * https://root.cern/doc/v636/tfile.html
  */
@@ -31,6 +46,8 @@ pub struct FirstRecordData {
     pub seek_dir: u64,
     pub seek_parent: u64,
     pub seek_keys: u64,
+    pub uuid_vers: u16,
+    pub uuid: [u8; 16],
 }
 
 impl BinRead for FirstRecordData {
@@ -53,6 +70,9 @@ impl BinRead for FirstRecordData {
         let seek_dir = reader_dyn_width.read_ptr(reader)?;
         let seek_parent = reader_dyn_width.read_ptr(reader)?;
         let seek_keys = reader_dyn_width.read_ptr(reader)?;
+        let uuid_vers = reader.read_type(endian)?;
+        let mut uuid = [0u8; 16];
+        reader.read_exact(&mut uuid)?;
 
         Ok(Self {
             name,
@@ -65,10 +85,63 @@ impl BinRead for FirstRecordData {
             seek_dir,
             seek_parent,
             seek_keys,
+            uuid_vers,
+            uuid,
         })
     }
 }
 
+// The name and title of the file, then the record of its top directory
+impl BinWrite for FirstRecordData {
+    type Args<'a> = ();
+
+    fn write_options<W: Write + Seek>(
+        &self,
+        writer: &mut W,
+        endian: Endian,
+        _args: Self::Args<'_>,
+    ) -> BinResult<()> {
+        self.name.write_options(writer, endian, ())?;
+        self.title.write_options(writer, endian, ())?;
+        self.directory().write_options(writer, endian, ())
+    }
+}
+
+impl FirstRecordData {
+    pub fn from_directory(name: &str, title: &str, directory: &TDictData) -> Self {
+        Self {
+            name: TString::new(name),
+            title: TString::new(title),
+            version: directory.version,
+            datime_c: directory.datime_c,
+            datime_m: directory.datime_m,
+            n_bytes_keys: directory.n_bytes_keys,
+            n_bytes_name: directory.n_bytes_name,
+            seek_dir: directory.seek_dir,
+            seek_parent: directory.seek_parent,
+            seek_keys: directory.seek_keys,
+            uuid_vers: directory.uuid_vers,
+            uuid: directory.uuid,
+        }
+    }
+
+    // The record of the top directory, like the one of a subdirectory
+    pub fn directory(&self) -> TDictData {
+        TDictData {
+            version: self.version,
+            datime_c: self.datime_c,
+            datime_m: self.datime_m,
+            n_bytes_keys: self.n_bytes_keys,
+            n_bytes_name: self.n_bytes_name,
+            seek_dir: self.seek_dir,
+            seek_parent: self.seek_parent,
+            seek_keys: self.seek_keys,
+            uuid_vers: self.uuid_vers,
+            uuid: self.uuid,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1,18 +1,19 @@
 use crate::objects::tkey::TKey;
-use binrw::binread;
+use binrw::binrw;
 use binrw::BinRead;
 
 /*
 // https://root.cern/doc/v638/keyslist.html
+// written with n_keys as it is, TKey::sizeof of the keys gives the length of the record
 */
-#[binread]
-#[br(big)]
+#[binrw]
+#[brw(big)]
 #[derive(Debug, Default)]
 pub struct KeyList {
-    key: TKey,
-    n_keys: u32,
+    pub key: TKey,
+    pub n_keys: u32,
     #[br(count = n_keys)]
-    keys: Vec<TKey>,
+    pub keys: Vec<TKey>,
 }
 
 impl KeyList {
@@ -0,0 +1,549 @@
+use crate::file::tfile::TFile;
+use crate::file::writer::{DirectoryId, TFileWriter};
+use crate::objects::any_object::AnyObject;
+use crate::objects::tgraph::{TGraph, TGraphAsymmErrors, TGraphErrors};
+use crate::objects::tkey::TKey;
+use crate::objects::tlist::{TList, TListElement};
+use crate::tree::reader::TreeInfo;
+use crate::tree::writer::TTreeWriter;
+use rayon::prelude::*;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/*
+ * Merging of files, like hadd
+ * https://root.cern/doc/v638/classTFileMerger.html
+ *
+ * The objects of the latest cycle of every key are merged with the objects of the same name in
+ * the other files, in the order of the files:
+ *   - histograms and profiles are added, they must have the same binning
+ *   - TParameter values are summed, or combined as their merge mode bits say (TParameter::Merge)
+ *   - the points of graphs are appended (TGraph::Merge)
+ *   - lists merge their elements of the same name and append the others
+ *   - objects that cannot be merged are kept from the first file, with a warning
+ *   - the entries of trees are concatenated, like the fast merge of hadd: the baskets of every
+ *     file are copied without being decompressed when the merged tree is written. The trees must
+ *     have the same branches, of the kinds TTreeWriter writes (TreeInfo). A TNtuple is written as
+ *     the TTree it derives from.
+ * Subdirectories are merged like the top directory, objects are matched by their directory and
+ * name.
+ *
+ * Files are read in parallel and merged pairwise.
+ *     let merged = merge_files(&["job_1.root", "job_2.root"])?;
+ */
+#[derive(Debug)]
+pub struct MergedObject {
+    pub directory: String, // path of the directory, "a/b", empty for the top directory
+    pub name: String,
+    pub title: String,
+    pub class_name: String,
+    pub object: AnyObject,
+}
+
+// A tree whose entries are those of the trees of the same path in the files, in their order
+#[derive(Debug)]
+pub struct MergedTree {
+    pub directory: String,
+    pub name: String,
+    pub title: String,
+    // the file of each tree and the layout of the tree in it
+    pub parts: Vec<(PathBuf, TreeInfo)>,
+}
+
+#[derive(Debug)]
+pub struct MergedDirectory {
+    pub path: String,
+    pub title: String,
+}
+
+#[derive(Debug, Default)]
+pub struct MergedFile {
+    pub directories: Vec<MergedDirectory>, // parents before their subdirectories
+    pub objects: Vec<MergedObject>,
+    pub trees: Vec<MergedTree>,
+    pub warnings: Vec<String>,
+}
+
+const DIRECTORY_CLASSES: &[&str] = &["TDirectory", "TDirectoryFile"];
+const TREE_CLASSES: &[&str] = &["TTree", "TNtuple", "TNtupleD"];
+
+impl MergedFile {
+    // The objects of the latest cycle of every key, in the top directory and its subdirectories
+    pub fn read(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref();
+        let name = path.to_string_lossy();
+        let mut file = TFile::open(&name).map_err(|error| with_path(&name, error))?;
+        let keys = file.key_list.keys.clone();
+        let mut merged = Self::default();
+        merged
+            .read_directory(&mut file, path, "", &keys)
+            .map_err(|error| io::Error::new(error.kind(), format!("{}: {}", name, error)))?;
+        Ok(merged)
+    }
+
+    fn read_directory(
+        &mut self,
+        file: &mut TFile,
+        file_path: &Path,
+        directory: &str,
+        keys: &[TKey],
+    ) -> io::Result<()> {
+        let mut latest: Vec<&TKey> = Vec::new();
+        for key in keys {
+            match latest
+                .iter_mut()
+                .find(|latest| latest.name.as_str() == key.name.as_str())
+            {
+                Some(latest) if latest.cycle < key.cycle => *latest = key,
+                Some(_) => {}
+                None => latest.push(key),
+            }
+        }
+        for key in latest {
+            let name = key.name.to_string();
+            let class_name = key.class_name.to_string();
+            let path = if directory.is_empty() {
+                name.clone()
+            } else {
+                format!("{}/{}", directory, name)
+            };
+            if DIRECTORY_CLASSES.contains(&class_name.as_str()) {
+                let (title, keys) = file.directory_keys(key).map_err(io::Error::other)?;
+                self.directories.push(MergedDirectory {
+                    path: path.clone(),
+                    title,
+                });
+                self.read_directory(file, file_path, &path, &keys)?;
+                continue;
+            }
+            if TREE_CLASSES.contains(&class_name.as_str()) {
+                let tree = TreeInfo::read(file, key)?;
+                self.trees.push(MergedTree {
+                    directory: directory.to_string(),
+                    name,
+                    title: key.title.to_string(),
+                    parts: vec![(file_path.to_path_buf(), tree)],
+                });
+                continue;
+            }
+            let object = file.read_key(key).map_err(io::Error::other)?;
+            self.objects.push(MergedObject {
+                directory: directory.to_string(),
+                name,
+                title: key.title.to_string(),
+                class_name,
+                object,
+            });
+        }
+        Ok(())
+    }
+
+    // The object of a path, "a/b/h" for the object h of directory a/b
+    pub fn get(&self, path: &str) -> Option<&MergedObject> {
+        let (directory, name) = path.rsplit_once('/').unwrap_or(("", path));
+        self.objects
+            .iter()
+            .find(|object| object.directory == directory && object.name == name)
+    }
+
+    // The tree of a path, like get
+    pub fn tree(&self, path: &str) -> Option<&MergedTree> {
+        let (directory, name) = path.rsplit_once('/').unwrap_or(("", path));
+        self.trees
+            .iter()
+            .find(|tree| tree.directory == directory && tree.name == name)
+    }
+
+    // Merges the objects of a later file
+    pub fn merge(&mut self, other: MergedFile) -> io::Result<()> {
+        for warning in other.warnings {
+            self.warn(warning);
+        }
+        for directory in other.directories {
+            if !self.directories.iter().any(|d| d.path == directory.path) {
+                self.directories.push(directory);
+            }
+        }
+        for other in other.objects {
+            let Some(merged) = self
+                .objects
+                .iter_mut()
+                .find(|object| object.directory == other.directory && object.name == other.name)
+            else {
+                self.objects.push(other);
+                continue;
+            };
+            if !merged.object.merge(&other.object)? {
+                let warning = format!(
+                    "{} {} cannot be merged with {} {}, keeping the first",
+                    merged.class_name, merged.name, other.class_name, other.name
+                );
+                self.warn(warning);
+            }
+        }
+        for other in other.trees {
+            let Some(merged) = self
+                .trees
+                .iter_mut()
+                .find(|tree| tree.directory == other.directory && tree.name == other.name)
+            else {
+                self.trees.push(other);
+                continue;
+            };
+            let (first, tree) = (&merged.parts[0], &other.parts[0]);
+            if !first.1.same_branches(&tree.1) {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!(
+                        "tree {} of {} and {} have different branches",
+                        merged.name,
+                        first.0.display(),
+                        tree.0.display()
+                    ),
+                ));
+            }
+            merged.parts.extend(other.parts);
+        }
+        Ok(())
+    }
+
+    // every warning once, not once per file
+    fn warn(&mut self, warning: String) {
+        if !self.warnings.contains(&warning) {
+            self.warnings.push(warning);
+        }
+    }
+}
+
+impl MergedTree {
+    // The tree in a directory of the file being written, the baskets of every part copied from its
+    // file; returns the cycle of its key
+    pub fn write(&self, file: &mut TFileWriter, directory: DirectoryId) -> io::Result<u16> {
+        let mut tree = TTreeWriter::new(file, directory, &self.name, &self.title);
+        for (path, part) in &self.parts {
+            let name = path.to_string_lossy();
+            let mut source = TFile::open(&name).map_err(|error| with_path(&name, error))?;
+            tree.append_tree(&mut source, part)
+                .map_err(|error| io::Error::new(error.kind(), format!("{}: {}", name, error)))?;
+        }
+        tree.write()
+    }
+}
+
+// The merged objects of the files, read in parallel
+pub fn merge_files<P: AsRef<Path> + Sync>(paths: &[P]) -> io::Result<MergedFile> {
+    paths
+        .par_iter()
+        .map(MergedFile::read)
+        .try_reduce(MergedFile::default, |mut merged, other| {
+            merged.merge(other)?;
+            Ok(merged)
+        })
+}
+
+impl AnyObject {
+    // TObject::Merge of two objects of the same name, false when they cannot be merged
+    pub fn merge(&mut self, other: &AnyObject) -> io::Result<bool> {
+        match (self, other) {
+            (Self::TH1(th1), Self::TH1(other)) => th1.add(other)?,
+            (Self::TH2(th2), Self::TH2(other)) => th2.add(other)?,
+            (Self::TH3(th3), Self::TH3(other)) => th3.add(other)?,
+            (Self::TProfile(profile), Self::TProfile(other)) => profile.add(other)?,
+            (Self::TParameterF64(parameter), Self::TParameterF64(other)) => parameter.merge(other),
+            (Self::TParameterF32(parameter), Self::TParameterF32(other)) => parameter.merge(other),
+            (Self::TParameterI32(parameter), Self::TParameterI32(other)) => parameter.merge(other),
+            (Self::TParameterI64(parameter), Self::TParameterI64(other)) => parameter.merge(other),
+            (Self::TParameterBool(parameter), Self::TParameterBool(other)) => {
+                parameter.merge(other)
+            }
+            (Self::TGraph(graph), Self::TGraph(other)) => graph.merge(other),
+            (Self::TGraphErrors(graph), Self::TGraphErrors(other)) => graph.merge(other),
+            (Self::TGraphAsymmErrors(graph), Self::TGraphAsymmErrors(other)) => graph.merge(other),
+            (Self::TList(list), Self::TList(other))
+            | (Self::THashList(list), Self::THashList(other)) => return list.merge(other),
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+}
+
+impl TList<AnyObject> {
+    // Elements of the same name are merged, the others appended; false if one cannot be merged
+    pub fn merge(&mut self, other: &TList<AnyObject>) -> io::Result<bool> {
+        let mut merged = true;
+        for element in &other.objects {
+            let name = element.object.name();
+            let Some(existing) = self
+                .objects
+                .iter_mut()
+                .find(|existing| existing.object.name() == name)
+            else {
+                self.objects.push(TListElement {
+                    object: element.object.clone(),
+                    option: element.option.clone(),
+                });
+                continue;
+            };
+            // shared elements are back-references of the buffer, they are kept as they are
+            merged &= match Arc::get_mut(&mut existing.object) {
+                Some(object) => object.merge(&element.object)?,
+                None => false,
+            };
+        }
+        self.n_objects = self.objects.len() as u32;
+        Ok(merged)
+    }
+}
+
+impl TGraph {
+    // TGraph::Merge: the points of the other graph are appended
+    pub fn merge(&mut self, other: &TGraph) {
+        self.f_x.extend_from_slice(&other.f_x);
+        self.f_y.extend_from_slice(&other.f_y);
+        self.f_npoints = self.f_x.len() as i32;
+    }
+}
+
+impl TGraphErrors {
+    pub fn merge(&mut self, other: &TGraphErrors) {
+        self.graph.merge(&other.graph);
+        self.f_ex.extend_from_slice(&other.f_ex);
+        self.f_ey.extend_from_slice(&other.f_ey);
+    }
+}
+
+impl TGraphAsymmErrors {
+    pub fn merge(&mut self, other: &TGraphAsymmErrors) {
+        self.graph.merge(&other.graph);
+        self.f_exlow.extend_from_slice(&other.f_exlow);
+        self.f_exhigh.extend_from_slice(&other.f_exhigh);
+        self.f_eylow.extend_from_slice(&other.f_eylow);
+        self.f_eyhigh.extend_from_slice(&other.f_eyhigh);
+    }
+}
+
+fn with_path(path: &str, error: impl std::fmt::Display) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("{}: {}", path, error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::file::writer::TFileWriter;
+    use crate::objects::th1::TH1;
+    use crate::objects::tnamed::TNamed;
+    use crate::objects::tparameter::{TParameter, K_MAX};
+    use crate::objects::tstring::TString;
+    use crate::tree::branch::BranchKind;
+    use crate::tree::reader::BranchInfo;
+    use crate::tree::writer::{TTreeWriter, TreeOptions};
+
+    fn file(contents: f64, parameter: f64, max_bits: u32) -> MergedFile {
+        let mut th1 = TH1::new("h", "", 2, 0.0, 2.0);
+        th1.fill(0.5, contents);
+        let sum_of_weights = TParameter::<f64> {
+            name: TString::new("sumOfWeights"),
+            value: parameter,
+            ..Default::default()
+        };
+        let mut events = TParameter::<i64> {
+            name: TString::new("maxEvents"),
+            value: parameter as i64,
+            ..Default::default()
+        };
+        events.tobject.f_bits = max_bits;
+        let mut metadata = TList::default();
+        for parameter in [
+            AnyObject::TParameterF64(sum_of_weights),
+            AnyObject::TParameterI64(events),
+        ] {
+            metadata.objects.push(TListElement {
+                object: Arc::new(parameter),
+                option: TString::default(),
+            });
+        }
+        let object = |name: &str, class_name: &str, object: AnyObject| MergedObject {
+            directory: String::new(),
+            name: name.to_string(),
+            title: String::new(),
+            class_name: class_name.to_string(),
+            object,
+        };
+        MergedFile {
+            directories: Vec::new(),
+            objects: vec![
+                object("h", "TH1D", AnyObject::TH1(Box::new(th1))),
+                object("metadata", "TList", AnyObject::TList(metadata)),
+                object("config", "TNamed", AnyObject::TNamed(TNamed::default())),
+            ],
+            trees: Vec::new(),
+            warnings: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_merge() {
+        let mut merged = file(1.0, 10.0, K_MAX);
+        merged.merge(file(2.0, 30.0, K_MAX)).unwrap();
+        merged.merge(file(3.0, 20.0, K_MAX)).unwrap();
+        assert_eq!(merged.objects.len(), 3);
+
+        let th1 = merged.get("h").unwrap().object.as_th1().unwrap();
+        assert_eq!((th1.bin_content(1), th1.entries()), (6.0, 3.0));
+        let metadata = merged.get("metadata").unwrap().object.as_list().unwrap();
+        assert_eq!(metadata.parameter("sumOfWeights"), Some(60.0));
+        assert_eq!(metadata.parameter("maxEvents"), Some(30.0));
+        assert_eq!(
+            merged.warnings,
+            ["TNamed config cannot be merged with TNamed config, keeping the first"]
+        );
+
+        // different binning is an error, as in hadd
+        let mut other = file(1.0, 1.0, 0);
+        other.objects[0].object = AnyObject::TH1(Box::new(TH1::new("h", "", 3, 0.0, 3.0)));
+        assert!(merged.merge(other).is_err());
+    }
+
+    // h in the top directory, sel and sel/jets, each filled with weight
+    fn write_file(path: &Path, weight: f64) {
+        let mut file = TFileWriter::create(path).unwrap();
+        let top = file.top();
+        let sel = file.mkdir(top, "sel", "selection").unwrap();
+        let jets = file.mkdir(sel, "jets", "").unwrap();
+        for (directory, x) in [(top, 0.5), (sel, 1.5), (jets, 0.5)] {
+            let mut th1 = TH1::new("h", "", 2, 0.0, 2.0);
+            th1.fill(x, weight);
+            file.put(directory, "h", &th1).unwrap();
+        }
+        file.close().unwrap();
+    }
+
+    #[test]
+    fn test_merge_directories() {
+        let paths: Vec<_> = (0..2)
+            .map(|index| {
+                std::env::temp_dir().join(format!(
+                    "rusty_root_test_merge_directories_{}_{}.root",
+                    std::process::id(),
+                    index
+                ))
+            })
+            .collect();
+        write_file(&paths[0], 1.0);
+        write_file(&paths[1], 2.0);
+        let merged = merge_files(&paths).unwrap();
+        for path in &paths {
+            std::fs::remove_file(path).unwrap();
+        }
+
+        let directories: Vec<(&str, &str)> = merged
+            .directories
+            .iter()
+            .map(|directory| (directory.path.as_str(), directory.title.as_str()))
+            .collect();
+        assert_eq!(directories, [("sel", "selection"), ("sel/jets", "")]);
+        assert_eq!(merged.objects.len(), 3);
+        let content = |path: &str, bin: usize| {
+            let th1 = merged.get(path).unwrap().object.as_th1().unwrap();
+            th1.bin_content(bin)
+        };
+        assert_eq!((content("h", 1), content("h", 2)), (3.0, 0.0));
+        assert_eq!((content("sel/h", 1), content("sel/h", 2)), (0.0, 3.0));
+        assert_eq!(content("sel/jets/h", 1), 3.0);
+        assert!(merged.warnings.is_empty());
+    }
+
+    // events of the entries in the range: pt and the jets of every entry, in clusters of 2
+    fn write_tree_file(path: &Path, entries: std::ops::Range<i32>) {
+        let mut file = TFileWriter::create(path).unwrap();
+        let top = file.top();
+        let options = TreeOptions::default().with_cluster_size(2);
+        let mut tree = TTreeWriter::with_options(&mut file, top, "events", "skim", options);
+        let pt = tree.branch::<f64>("pt").unwrap();
+        let jet_pt = tree.vector_branch::<f32>("jet_pt").unwrap();
+        for entry in entries {
+            tree.set(pt, entry as f64).unwrap();
+            let values: Vec<f32> = (0..entry % 3).map(|jet| jet as f32).collect();
+            tree.set_values(jet_pt, &values).unwrap();
+            tree.fill().unwrap();
+        }
+        tree.write().unwrap();
+        file.close().unwrap();
+    }
+
+    // The values of a branch of doubles, read from its baskets
+    fn read_doubles(file: &mut TFile, branch: &BranchInfo) -> Vec<f64> {
+        let mut values = Vec::new();
+        let ends = branch.baskets.iter().skip(1).map(|basket| basket.entry);
+        for (basket, end) in branch.baskets.iter().zip(ends.chain([branch.entries])) {
+            let key = TKey::read_from(file.reader_mut(), basket.seek as u64).unwrap();
+            let bytes = key.decompress_full(file.reader_mut()).unwrap().into_inner();
+            let entries = &bytes[key.key_len as usize..][..8 * (end - basket.entry) as usize];
+            values.extend(
+                entries
+                    .chunks(8)
+                    .map(|value| f64::from_be_bytes(value.try_into().unwrap())),
+            );
+        }
+        values
+    }
+
+    #[test]
+    fn test_merge_trees() {
+        let path = |name: &str| {
+            std::env::temp_dir().join(format!(
+                "rusty_root_test_merge_trees_{}_{}.root",
+                std::process::id(),
+                name
+            ))
+        };
+        let inputs = [path("0"), path("1")];
+        write_tree_file(&inputs[0], 0..5);
+        write_tree_file(&inputs[1], 5..8);
+        let merged = merge_files(&inputs).unwrap();
+        let tree = merged.tree("events").unwrap();
+        assert_eq!((tree.title.as_str(), tree.parts.len()), ("skim", 2));
+
+        let output = path("merged");
+        let mut file = TFileWriter::create(&output).unwrap();
+        let top = file.top();
+        assert_eq!(tree.write(&mut file, top).unwrap(), 1);
+        file.close().unwrap();
+
+        let mut file = TFile::open(output.to_str().unwrap()).unwrap();
+        let key = file.key("events").unwrap().clone();
+        let tree = TreeInfo::read(&mut file, &key).unwrap();
+        assert_eq!(tree.entries, 8);
+        // the clusters of each file
+        assert_eq!(tree.cluster_ends(), [2, 4, 5, 7, 8]);
+        let pt = &tree.branches[0];
+        let basket_entries: Vec<i64> = pt.baskets.iter().map(|basket| basket.entry).collect();
+        assert_eq!(basket_entries, [0, 2, 4, 5, 7]);
+        assert_eq!(
+            read_doubles(&mut file, pt),
+            (0..8).map(f64::from).collect::<Vec<_>>()
+        );
+        let jets = &tree.branches[1];
+        assert_eq!(
+            (jets.kind, jets.entries, jets.baskets.len()),
+            (BranchKind::Vector, 8, 5)
+        );
+
+        // a tree with other branches cannot be appended
+        let other = path("other");
+        let mut file = TFileWriter::create(&other).unwrap();
+        let top = file.top();
+        let mut tree = TTreeWriter::new(&mut file, top, "events", "");
+        let pt = tree.branch::<f32>("pt").unwrap();
+        tree.set(pt, 1.0f32).unwrap();
+        tree.fill().unwrap();
+        tree.write().unwrap();
+        file.close().unwrap();
+        let error = merge_files(&[&inputs[0], &other]).unwrap_err();
+        for path in inputs.iter().chain([&output, &other]) {
+            std::fs::remove_file(path).unwrap();
+        }
+        assert!(error.to_string().contains("have different branches"));
+    }
+}
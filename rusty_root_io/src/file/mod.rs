@@ -1,3 +1,6 @@
 pub mod first_record;
 pub mod keylist;
+pub mod merge;
 pub mod tfile;
+pub mod tfree;
+pub mod writer;
@@ -1,11 +1,15 @@
+use crate::core::read_context::ReadContext;
 use crate::core::utils::ReaderDynWidth;
 use crate::file::first_record::FirstRecordDict;
 use crate::file::keylist::KeyList;
-use crate::streamer::streamerinfo::StreamerInfo;
-use binrw::{BinRead, BinReaderExt, BinResult, Endian};
+use crate::objects::any_object::AnyObject;
+use crate::objects::tdictionary::TDictionary;
+use crate::objects::tkey::TKey;
+use crate::streamer::streamerinfo::{StreamerInfo, StreamerRegistry};
+use binrw::{BinRead, BinReaderExt, BinResult, BinWrite, BinWriterExt, Endian};
 use std::fs::File;
 use std::io;
-use std::io::{BufReader, Read, Seek};
+use std::io::{BufReader, Read, Seek, SeekFrom, Write};
 use std::sync::Arc;
 
 /*
@@ -38,6 +42,7 @@ pub struct TFile {
     pub key_list: KeyList,
     pub contents: Arc<[u8]>,
     pub streamer_info: StreamerInfo,
+    pub schemas: Arc<StreamerRegistry>,
     // other fields...
 }
 impl TFile {
@@ -49,6 +54,8 @@ impl TFile {
         let key_list_offset = first_data_record.data.seek_keys;
         let key_list = KeyList::read_from(&mut reader, key_list_offset)?;
         let streamer_info = StreamerInfo::read_from(&mut reader, header.f_seek_info)?;
+        let mut schemas = StreamerRegistry::new();
+        schemas.register_from_streamerinfo(&streamer_info);
         let contents = Arc::new([]);
         Ok(TFile {
             reader,
@@ -56,15 +63,63 @@ impl TFile {
             first_data_record,
             key_list,
             streamer_info,
+            schemas: Arc::new(schemas),
             contents,
         })
     }
 
+    // The name the file was created with, kept in its first key
+    pub fn name(&self) -> &str {
+        &self.first_data_record.key.name
+    }
+
+    // The key with the highest cycle of a name
+    pub fn key(&self, name: &str) -> Option<&TKey> {
+        latest_key(&self.key_list.keys, name)
+    }
+
+    // TDirectory::Get: the object of the key with the highest cycle of a name
+    pub fn get(&mut self, name: &str) -> BinResult<AnyObject> {
+        let key = self.key(name).cloned().ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("no key {} in the file", name),
+            )
+        })?;
+        self.read_key(&key)
+    }
+
+    // TKey::ReadObj: the object of a key of any directory of the file
+    pub fn read_key(&mut self, key: &TKey) -> BinResult<AnyObject> {
+        let mut buffer = key.decompress_full(&mut self.reader)?;
+        buffer.seek(SeekFrom::Start(key.key_len as u64))?;
+        // the buffer starts at the key, like the offsets of its class tags
+        let ctx = ReadContext::new().with_schemas(self.schemas.clone());
+        AnyObject::read_class(&key.class_name, &mut buffer, Endian::Big, ctx)
+    }
+
+    // The title and the keys of the subdirectory of a TDirectory key
+    pub fn directory_keys(&mut self, key: &TKey) -> BinResult<(String, Vec<TKey>)> {
+        let TDictionary { tkey, data } = TDictionary::read_from(&mut self.reader, key.seek_key)?;
+        let keys = if data.seek_keys == 0 {
+            Vec::new()
+        } else {
+            KeyList::read_from(&mut self.reader, data.seek_keys)?.keys
+        };
+        Ok((tkey.title.to_string(), keys))
+    }
+
     pub fn reader_mut(&mut self) -> &mut BufReader<File> {
         &mut self.reader
     }
 }
 
+fn latest_key<'a>(keys: &'a [TKey], name: &str) -> Option<&'a TKey> {
+    keys.iter()
+        .filter(|key| key.name.as_str() == name)
+        .max_by_key(|key| key.cycle)
+}
+
 impl BinRead for TFileHeader {
     type Args<'a> = ();
 
@@ -108,11 +163,56 @@ impl BinRead for TFileHeader {
     }
 }
 
+// TFile::WriteHeader, the counterpart of read_options. The header is followed by zeros up to fBEGIN.
+impl BinWrite for TFileHeader {
+    type Args<'a> = ();
+
+    fn write_options<W: Write + Seek>(
+        &self,
+        writer: &mut W,
+        endian: Endian,
+        _args: Self::Args<'_>,
+    ) -> BinResult<()> {
+        writer.write_all(b"root")?;
+        writer.write_type(&self.f_version, endian)?;
+        writer.write_type(&self.f_begin, endian)?;
+        if self.is_large() {
+            writer.write_type(&self.f_end, endian)?;
+            writer.write_type(&self.f_seek_free, endian)?;
+        } else {
+            writer.write_type(&(self.f_end as u32), endian)?;
+            writer.write_type(&(self.f_seek_free as u32), endian)?;
+        }
+        writer.write_type(&self.f_nbytes_free, endian)?;
+        writer.write_type(&self.n_free, endian)?;
+        writer.write_type(&self.f_nbytes_name, endian)?;
+        writer.write_type(&self.f_units, endian)?;
+        writer.write_type(&self.f_compress, endian)?;
+        if self.is_large() {
+            writer.write_type(&self.f_seek_info, endian)?;
+        } else {
+            writer.write_type(&(self.f_seek_info as u32), endian)?;
+        }
+        writer.write_type(&self.f_nbytes_info, endian)?;
+        writer.write_type(&self.f_uuid_vers, endian)?;
+        writer.write_all(&self.f_uuid)?;
+        Ok(())
+    }
+}
+
 impl TFileHeader {
     pub fn new() -> Self {
         Self::default()
     }
 
+    // Seeks are written as 64 bit integers when the file is larger than 2GB
+    pub fn is_large(&self) -> bool {
+        matches!(
+            ReaderDynWidth::from_tfile_version(self.f_version),
+            ReaderDynWidth::Off64
+        )
+    }
+
     fn parse_f_uuid<R: std::io::Read + std::io::Seek>(reader: &mut R) -> io::Result<[u8; 16]> {
         let mut uuid_buf = [0u8; 16];
         reader.read_exact(&mut uuid_buf)?;
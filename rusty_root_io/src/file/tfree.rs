@@ -0,0 +1,146 @@
+use crate::core::utils::ReaderDynWidth;
+use crate::objects::tkey::TKey;
+use binrw::io::{Read, Seek, SeekFrom, Write};
+use binrw::{BinRead, BinReaderExt, BinResult, BinWrite, BinWriterExt, Endian};
+
+/*
+ * TFree, a segment of free bytes of the file
+ * https://root.cern/doc/v638/classTFree.html
+ *
+ * The free segments list is a key of the class of the file (fSeekFree, fNbytesFree in the header)
+ * holding the TFree records, uncompressed. A file that was never updated has a single segment,
+ * from the end of the file to 2GB, or on by 1GB steps past the end of a larger file.
+ */
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct TFree {
+    pub version: u16,
+    pub first: u64,
+    pub last: u64,
+}
+
+// TFile::kStartBigFile, from which seeks are written as 64 bit integers
+pub const K_START_BIG_FILE: u64 = 2_000_000_000;
+
+impl TFree {
+    pub fn new(first: u64, last: u64) -> Self {
+        let version = if last > K_START_BIG_FILE { 1001 } else { 1 };
+        Self {
+            version,
+            first,
+            last,
+        }
+    }
+
+    // The segment that follows the end of a file
+    pub fn after_end(end: u64) -> Self {
+        let mut last = K_START_BIG_FILE;
+        while last < end {
+            last += 1_000_000_000;
+        }
+        Self::new(end, last)
+    }
+
+    pub fn is_large(&self) -> bool {
+        self.version > 1000
+    }
+
+    // TFree::Sizeof
+    pub fn sizeof(&self) -> u32 {
+        if self.is_large() {
+            18
+        } else {
+            10
+        }
+    }
+}
+
+impl BinRead for TFree {
+    type Args<'a> = ();
+
+    fn read_options<R: Read + Seek>(
+        reader: &mut R,
+        endian: Endian,
+        _args: Self::Args<'_>,
+    ) -> BinResult<Self> {
+        let version = reader.read_type(endian)?;
+        let reader_dyn_width = ReaderDynWidth::from_tkey_version(version);
+        let first = reader_dyn_width.read_ptr(reader)?;
+        let last = reader_dyn_width.read_ptr(reader)?;
+        Ok(Self {
+            version,
+            first,
+            last,
+        })
+    }
+}
+
+impl BinWrite for TFree {
+    type Args<'a> = ();
+
+    fn write_options<W: Write + Seek>(
+        &self,
+        writer: &mut W,
+        endian: Endian,
+        _args: Self::Args<'_>,
+    ) -> BinResult<()> {
+        writer.write_type(&self.version, endian)?;
+        if self.is_large() {
+            writer.write_type(&self.first, endian)?;
+            writer.write_type(&self.last, endian)?;
+        } else {
+            writer.write_type(&(self.first as u32), endian)?;
+            writer.write_type(&(self.last as u32), endian)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct FreeList {
+    pub key: TKey,
+    pub segments: Vec<TFree>,
+}
+
+impl FreeList {
+    pub fn read_from<R: Read + Seek>(reader: &mut R, offset: u64) -> BinResult<Self> {
+        reader.seek(SeekFrom::Start(offset))?;
+        Self::read_be(reader)
+    }
+}
+
+impl BinRead for FreeList {
+    type Args<'a> = ();
+
+    fn read_options<R: Read + Seek>(
+        reader: &mut R,
+        endian: Endian,
+        _args: Self::Args<'_>,
+    ) -> BinResult<Self> {
+        let key = TKey::read_options(reader, endian, ())?;
+        let end = reader.stream_position()? + key.obj_len as u64;
+        let mut segments = Vec::new();
+        // the record can end with zeros, when it was sized for more segments than it holds
+        while reader.stream_position()? + 10 <= end {
+            let segment = TFree::read_options(reader, endian, ())?;
+            if segment.version == 0 {
+                break;
+            }
+            segments.push(segment);
+        }
+        Ok(Self { key, segments })
+    }
+}
+
+impl BinWrite for FreeList {
+    type Args<'a> = ();
+
+    fn write_options<W: Write + Seek>(
+        &self,
+        writer: &mut W,
+        endian: Endian,
+        _args: Self::Args<'_>,
+    ) -> BinResult<()> {
+        self.key.write_options(writer, endian, ())?;
+        self.segments.write_options(writer, endian, ())
+    }
+}
@@ -0,0 +1,1169 @@
+use crate::core::compression::CompressionAlgorithm;
+use crate::core::utils::datime_now;
+use crate::core::write_buffer::{WriteBuffer, WriteObject};
+use crate::file::first_record::{FirstRecordData, FirstRecordDict};
+use crate::file::keylist::KeyList;
+use crate::file::tfile::{TFile, TFileHeader};
+use crate::file::tfree::{FreeList, TFree, K_START_BIG_FILE};
+use crate::objects::tdictionary::{TDictData, TDictionary};
+use crate::objects::tkey::TKey;
+use crate::objects::tlist::TListElement;
+use crate::objects::tstring::TString;
+use crate::streamer::builtin;
+use crate::streamer::streamerinfo::StreamerInfo;
+use crate::streamer::tstreamerinfo::TStreamerInfo;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufWriter, Seek, SeekFrom, Write};
+use std::path::Path;
+use std::sync::Arc;
+
+/*
+ * Writing ROOT files
+ * https://root.cern/doc/v638/header.html
+ *
+ * A new file is laid out as TFile writes it:
+ *   - the header, padded to fBEGIN = 100 bytes
+ *   - the key of the top directory: class TFile, the name and title of the file, then the record
+ *     of the directory
+ *   - the keys of the objects and of the subdirectories, in the order they are written
+ *   - at close: the StreamerInfo list, the key list of every directory and the free segments
+ * after which the header and the directory records are rewritten with the final seeks.
+ *
+ * Seeks are 32 bit integers while the file is smaller than 2GB (TFile::kStartBigFile) and 64 bit
+ * integers beyond, in the keys and records written from there on and in the header. Large offsets
+ * can also be asked for from the start, e.g. to check readers.
+ *
+ * An existing file is opened for update like TFile::Open(path, "UPDATE"): its directories, keys,
+ * streamer info and free segments are read, new keys get the next cycles of their names. At close
+ * the streamer info, the key lists and the free segments are written again, the records they
+ * replace become free segments like with TFile::MakeFree. Objects are replaced with put_overwrite
+ * and removed with delete, which free the bytes of their keys the same way.
+ *
+ * Like TKey::Create, a key goes in the first free segment of its size, else in the first one with
+ * room for it and the size of the bytes left, else at fEND.
+ *
+ *     let mut file = TFileWriter::create("out.root")?;
+ *     let plots = file.mkdir(file.top(), "plots", "")?;
+ *     file.put(plots, "config", &config)?;
+ *     file.close()?;
+ */
+#[derive(Debug, Clone)]
+pub struct WriteOptions {
+    pub title: String,
+    // fCompress, algorithm * 100 + level
+    pub compression: i32,
+    pub large_offsets: bool,
+}
+
+impl Default for WriteOptions {
+    fn default() -> Self {
+        Self {
+            title: String::new(),
+            // ZSTD level 5, the default of ROOT since 6.30
+            compression: 505,
+            large_offsets: false,
+        }
+    }
+}
+
+impl WriteOptions {
+    pub fn with_title(self, title: &str) -> Self {
+        Self {
+            title: title.to_string(),
+            ..self
+        }
+    }
+
+    pub fn with_compression(self, compression: i32) -> Self {
+        Self {
+            compression,
+            ..self
+        }
+    }
+
+    pub fn with_large_offsets(self) -> Self {
+        Self {
+            large_offsets: true,
+            ..self
+        }
+    }
+}
+
+// A directory of the file being written, TFileWriter::top() or returned by mkdir
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DirectoryId(usize);
+
+#[derive(Debug)]
+struct Directory {
+    // its key in the parent directory, the TFile key at fBEGIN for the top directory
+    key: TKey,
+    record: TDictData,
+    keys: Vec<TKey>,
+}
+
+#[derive(Debug)]
+pub struct TFileWriter {
+    file: BufWriter<File>,
+    position: u64,
+    header: TFileHeader,
+    options: WriteOptions,
+    directories: Vec<Directory>,
+    streamer_info: StreamerInfo,
+    // the free segments before fEND, the one after it is written at close
+    free: Vec<TFree>,
+    closed: bool,
+}
+
+// fVersion of the header, the files are written like ROOT 6.34/00 writes them
+const ROOT_VERSION: u32 = 63400;
+const K_BEGIN: u64 = 100;
+// class versions of TKey and TDirectoryFile, plus 1000 with 64 bit seeks
+const KEY_VERSION: u16 = 4;
+const DIRECTORY_VERSION: u16 = 5;
+
+impl TFileWriter {
+    pub fn create(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::create_with(path, WriteOptions::default())
+    }
+
+    // TFile::Init of a new file: the header and the record of the top directory
+    pub fn create_with(path: impl AsRef<Path>, options: WriteOptions) -> io::Result<Self> {
+        let path = path.as_ref();
+        let name = path
+            .file_name()
+            .map_or_else(|| path.to_string_lossy(), |name| name.to_string_lossy())
+            .into_owned();
+        let file = BufWriter::new(File::create(path)?);
+        let datime = datime_now();
+
+        let mut key = TKey {
+            datime,
+            cycle: 1,
+            seek_key: K_BEGIN,
+            class_name: TString::new("TFile"),
+            name: TString::new(&name),
+            title: TString::new(&options.title),
+            ..TKey::new()
+        };
+        key.version = key_version(&options, K_BEGIN);
+        key.key_len = key.sizeof();
+        let name_len = (key.name.sizeof() + key.title.sizeof()) as u32;
+        key.obj_len = name_len + TDictData::SIZEOF;
+        key.n_bytes = key.key_len as u32 + key.obj_len;
+        let record = TDictData {
+            version: DIRECTORY_VERSION,
+            datime_c: datime,
+            datime_m: datime,
+            n_bytes_name: key.key_len as u32 + name_len,
+            seek_dir: K_BEGIN,
+            uuid_vers: 1,
+            uuid: random_uuid(),
+            ..Default::default()
+        };
+        let mut header = TFileHeader::new();
+        header.f_version = ROOT_VERSION;
+        header.f_begin = K_BEGIN as u32;
+        header.f_end = K_BEGIN + key.n_bytes as u64;
+        header.f_nbytes_name = record.n_bytes_name;
+        header.f_units = 4;
+        header.f_compress = options.compression;
+        header.f_uuid_vers = 1;
+        header.f_uuid = record.uuid;
+
+        let mut writer = Self {
+            file,
+            position: 0,
+            header,
+            options,
+            directories: vec![Directory {
+                key,
+                record,
+                keys: Vec::new(),
+            }],
+            streamer_info: StreamerInfo::default(),
+            free: Vec::new(),
+            closed: false,
+        };
+        writer.write_header()?;
+        writer.write_directory_header(writer.top())?;
+        Ok(writer)
+    }
+
+    // TFile::Init of an existing file opened for update: the header, the directories with their
+    // keys, the streamer info and the free segments, keeping the title and compression of the file
+    pub fn update(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref();
+        let mut input = TFile::open(&path.to_string_lossy()).map_err(io::Error::other)?;
+        let header = std::mem::take(&mut input.header);
+        let first_record = std::mem::take(&mut input.first_data_record);
+        let mut directories = vec![Directory {
+            record: first_record.data.directory(),
+            key: first_record.key,
+            keys: std::mem::take(&mut input.key_list.keys),
+        }];
+        // the subdirectories, breadth first
+        let mut index = 0;
+        while index < directories.len() {
+            let subdirectories: Vec<u64> = directories[index]
+                .keys
+                .iter()
+                .filter(|key| matches!(key.class_name.as_str(), "TDirectory" | "TDirectoryFile"))
+                .map(|key| key.seek_key)
+                .collect();
+            for seek_key in subdirectories {
+                let TDictionary { tkey, data } =
+                    TDictionary::read_from(input.reader_mut(), seek_key)
+                        .map_err(io::Error::other)?;
+                let keys = if data.seek_keys == 0 {
+                    Vec::new()
+                } else {
+                    KeyList::read_from(input.reader_mut(), data.seek_keys)
+                        .map_err(io::Error::other)?
+                        .keys
+                };
+                directories.push(Directory {
+                    key: tkey,
+                    record: data,
+                    keys,
+                });
+            }
+            index += 1;
+        }
+        let free = if header.f_seek_free == 0 {
+            Vec::new()
+        } else {
+            FreeList::read_from(input.reader_mut(), header.f_seek_free)
+                .map_err(io::Error::other)?
+                .segments
+                .into_iter()
+                .filter(|segment| segment.first < header.f_end)
+                .collect()
+        };
+        let options = WriteOptions {
+            title: directories[0].key.title.to_string(),
+            compression: header.f_compress,
+            large_offsets: header.is_large(),
+        };
+        let streamer_info = std::mem::take(&mut input.streamer_info);
+        drop(input);
+
+        Ok(Self {
+            file: BufWriter::new(OpenOptions::new().write(true).open(path)?),
+            position: 0,
+            header,
+            options,
+            directories,
+            streamer_info,
+            free,
+            closed: false,
+        })
+    }
+
+    pub fn top(&self) -> DirectoryId {
+        DirectoryId(0)
+    }
+
+    // fEND, where the next key is written
+    pub fn end(&self) -> u64 {
+        self.header.f_end
+    }
+
+    // The keys written so far in a directory
+    pub fn keys(&self, directory: DirectoryId) -> &[TKey] {
+        &self.directories[directory.0].keys
+    }
+
+    // A subdirectory made by mkdir or read from the file opened for update
+    pub fn directory(&self, parent: DirectoryId, name: &str) -> Option<DirectoryId> {
+        let seek_parent = self.directories[parent.0].record.seek_dir;
+        self.directories
+            .iter()
+            .enumerate()
+            .skip(1)
+            .find(|(_, directory)| {
+                directory.record.seek_parent == seek_parent && directory.key.name.as_str() == name
+            })
+            .map(|(index, _)| DirectoryId(index))
+    }
+
+    // TDirectoryFile::mkdir: a subdirectory, whose key holds the record of the directory
+    pub fn mkdir(
+        &mut self,
+        parent: DirectoryId,
+        name: &str,
+        title: &str,
+    ) -> io::Result<DirectoryId> {
+        if self
+            .keys(parent)
+            .iter()
+            .any(|key| key.name.as_str() == name)
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("a key {} exists already", name),
+            ));
+        }
+        let datime = datime_now();
+        let seek_parent = self.directories[parent.0].record.seek_dir;
+        let mut record = TDictData {
+            version: DIRECTORY_VERSION,
+            datime_c: datime,
+            datime_m: datime,
+            seek_parent,
+            uuid_vers: 1,
+            uuid: random_uuid(),
+            ..Default::default()
+        };
+        let mut key = self.new_key(parent, "TDirectory", name, title);
+        record.n_bytes_name = key.key_len as u32;
+        // the record holds its own seek, placed before it is written
+        let mut payload = WriteBuffer::new(0);
+        payload.write(&record);
+        key.obj_len = payload.len() as u32;
+        key.n_bytes = key.key_len as u32 + key.obj_len;
+        key.seek_key = self.allocate(key.n_bytes)?;
+        record.seek_dir = key.seek_key;
+        let mut payload = WriteBuffer::new(0);
+        payload.write(&record);
+        self.write_record(&key, &[], payload.as_bytes())?;
+        self.directories[parent.0].keys.push(key.clone());
+        self.directories.push(Directory {
+            key,
+            record,
+            keys: Vec::new(),
+        });
+        Ok(DirectoryId(self.directories.len() - 1))
+    }
+
+    // TDirectory::WriteTObject: the object in a new key, with the next cycle of its name, and the
+    // streamer info of the classes it holds
+    pub fn put<T: WriteObject + ?Sized>(
+        &mut self,
+        directory: DirectoryId,
+        name: &str,
+        object: &T,
+    ) -> io::Result<u16> {
+        let class_name = object.class_name();
+        if !object.is_writable() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Cannot write {} of class {}", name, class_name),
+            ));
+        }
+        let key = self.new_key(directory, &class_name, name, object.title());
+        let mut payload = WriteBuffer::new(key.key_len as u32);
+        object.write_to(&mut payload);
+        let mut classes: Vec<String> = payload.class_names().map(String::from).collect();
+        classes.sort();
+        for class in std::iter::once(&class_name).chain(&classes) {
+            for info in builtin::streamer_infos(class) {
+                self.add_streamer_info(info);
+            }
+        }
+        let key = self.write_key(key, payload.into_bytes(), true)?;
+        let cycle = key.cycle;
+        self.directories[directory.0].keys.push(key);
+        Ok(cycle)
+    }
+
+    // TDirectory::WriteTObject with TObject::kOverwrite: the object in a key of cycle 1, replacing
+    // every cycle of its name, whose bytes are freed first so that the new key can take them
+    pub fn put_overwrite<T: WriteObject + ?Sized>(
+        &mut self,
+        directory: DirectoryId,
+        name: &str,
+        object: &T,
+    ) -> io::Result<u16> {
+        if !object.is_writable() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Cannot write {} of class {}", name, object.class_name()),
+            ));
+        }
+        if self
+            .keys(directory)
+            .iter()
+            .any(|key| key.name.as_str() == name)
+        {
+            self.delete(directory, name, None)?;
+        }
+        self.put(directory, name, object)
+    }
+
+    /*
+     * TDirectoryFile::Delete("name;cycle"), or "name;*" without a cycle: the keys are removed from
+     * the directory and their bytes freed with TFile::MakeFree. Only the key itself is freed, the
+     * baskets of a tree stay in the file; subdirectories cannot be deleted.
+     */
+    pub fn delete(
+        &mut self,
+        directory: DirectoryId,
+        name: &str,
+        cycle: Option<u16>,
+    ) -> io::Result<()> {
+        let matches =
+            |key: &TKey| key.name.as_str() == name && cycle.is_none_or(|cycle| key.cycle == cycle);
+        let deleted: Vec<TKey> = self.directories[directory.0]
+            .keys
+            .iter()
+            .filter(|key| matches(key))
+            .cloned()
+            .collect();
+        if deleted.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("no key {} to delete", name),
+            ));
+        }
+        if deleted
+            .iter()
+            .any(|key| matches!(key.class_name.as_str(), "TDirectory" | "TDirectoryFile"))
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                format!("Cannot delete the directory {}", name),
+            ));
+        }
+        self.directories[directory.0]
+            .keys
+            .retain(|key| !matches(key));
+        for key in deleted {
+            self.release(key.seek_key, key.n_bytes)?;
+        }
+        Ok(())
+    }
+
+    // fCompress of the file, the setting of its keys
+    pub fn compression(&self) -> i32 {
+        self.options.compression
+    }
+
+    // The key of data that is not listed in its directory, like a basket of a tree, with the cycle
+    // given and `header_len` bytes of its class after the TKey header
+    pub(crate) fn new_unlisted_key(
+        &self,
+        directory: DirectoryId,
+        class_name: &str,
+        name: &str,
+        title: &str,
+        cycle: u16,
+        header_len: u16,
+    ) -> TKey {
+        let mut key = self.new_key(directory, class_name, name, title);
+        key.cycle = cycle;
+        key.key_len += header_len;
+        key
+    }
+
+    // The unlisted key, its header and its compressed payload in the file
+    pub(crate) fn write_unlisted_key(
+        &mut self,
+        key: TKey,
+        header: &[u8],
+        payload: Vec<u8>,
+    ) -> io::Result<TKey> {
+        self.write_key_with_header(key, header, payload, true)
+    }
+
+    /*
+     * A copy of an unlisted key of another file in this one, like the baskets
+     * TTreeCloner copies: `data` are the bytes after the TKey header, the header of its class and
+     * its compressed payload, written as they are. Offsets in them count from the start of the key,
+     * the copy keeps the width of the seeks of the source so that its header has the same length.
+     */
+    pub(crate) fn copy_unlisted_key(
+        &mut self,
+        directory: DirectoryId,
+        source: &TKey,
+        cycle: u16,
+        data: &[u8],
+    ) -> io::Result<TKey> {
+        let header_len = source.key_len - source.sizeof();
+        let mut key = self.new_unlisted_key(
+            directory,
+            source.class_name.as_str(),
+            source.name.as_str(),
+            source.title.as_str(),
+            cycle,
+            header_len,
+        );
+        if source.is_large() && !key.is_large() {
+            key.version += 1000;
+            key.key_len = key.sizeof() + header_len;
+        }
+        if key.key_len != source.key_len {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                format!(
+                    "{} {} has 32 bit seeks and cannot be copied beyond 2GB",
+                    source.class_name.as_str(),
+                    source.name.as_str()
+                ),
+            ));
+        }
+        key.obj_len = source.obj_len;
+        key.n_bytes = key.sizeof() as u32 + data.len() as u32;
+        key.seek_key = self.allocate(key.n_bytes)?;
+        self.write_record(&key, &[], data)?;
+        Ok(key)
+    }
+
+    // Streamer info written at close, once per class and version
+    pub fn add_streamer_info(&mut self, info: Arc<TStreamerInfo>) {
+        let list = &mut self.streamer_info.tlist;
+        if list.iter().any(|other| {
+            other.tnamed.name.as_str() == info.tnamed.name.as_str()
+                && other.f_class_version == info.f_class_version
+        }) {
+            return;
+        }
+        list.objects.push(TListElement {
+            object: info,
+            option: TString::default(),
+        });
+    }
+
+    // TFile::Close: streamer info, key lists, free segments, then the header and the records
+    pub fn close(mut self) -> io::Result<()> {
+        self.finish()
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        if self.closed {
+            return Ok(());
+        }
+        self.closed = true;
+
+        self.release(self.header.f_seek_info, self.header.f_nbytes_info)?;
+        let top = self.top();
+        let key = self.new_key(top, "TList", "StreamerInfo", self.streamer_info.title());
+        let mut payload = WriteBuffer::new(key.key_len as u32);
+        self.streamer_info.write_to(&mut payload);
+        let key = self.write_key(key, payload.into_bytes(), true)?;
+        self.header.f_seek_info = key.seek_key;
+        self.header.f_nbytes_info = key.n_bytes;
+
+        // subdirectories before their parents, like TDirectoryFile::Save
+        for index in (0..self.directories.len()).rev() {
+            self.write_key_list(DirectoryId(index))?;
+        }
+        self.write_free_segments()?;
+        if self.options.large_offsets || self.header.f_end > K_START_BIG_FILE {
+            self.header.f_version = ROOT_VERSION + 1_000_000;
+            self.header.f_units = 8;
+        }
+        self.write_header()?;
+        for index in 0..self.directories.len() {
+            self.write_directory_header(DirectoryId(index))?;
+        }
+        self.file.flush()
+    }
+
+    // TDirectoryFile::WriteKeys: the number of keys then every key header, uncompressed
+    fn write_key_list(&mut self, directory: DirectoryId) -> io::Result<()> {
+        let record = &self.directories[directory.0].record;
+        self.release(record.seek_keys, record.n_bytes_keys)?;
+        let (class_name, name, title) = {
+            let key = &self.directories[directory.0].key;
+            (
+                key.class_name.to_string(),
+                key.name.to_string(),
+                key.title.to_string(),
+            )
+        };
+        let mut key = self.new_key(directory, &class_name, &name, &title);
+        key.cycle = 1;
+        key.seek_p_dir = self.directories[directory.0].record.seek_dir;
+        let keys = &self.directories[directory.0].keys;
+        let mut payload = WriteBuffer::new(0);
+        payload.write(&(keys.len() as u32));
+        for key in keys {
+            payload.write(key);
+        }
+        let key = self.write_key(key, payload.into_bytes(), false)?;
+
+        let datime = datime_now();
+        let record = &mut self.directories[directory.0].record;
+        record.seek_keys = key.seek_key;
+        record.n_bytes_keys = key.n_bytes;
+        record.datime_m = datime;
+        if self.options.large_offsets || key.seek_key > K_START_BIG_FILE {
+            record.version = DIRECTORY_VERSION + 1000;
+        }
+        Ok(())
+    }
+
+    // TFile::WriteFree: the free segments before the end of the file, then the one after the end,
+    // which the record itself moves
+    fn write_free_segments(&mut self) -> io::Result<()> {
+        self.release(self.header.f_seek_free, self.header.f_nbytes_free)?;
+        let top = self.top();
+        let (name, title) = (self.name(), self.options.title.clone());
+        let mut key = self.new_key(top, "TFile", &name, &title);
+        key.cycle = 1;
+        let gaps: u64 = self
+            .free
+            .iter()
+            .map(|segment| segment.sizeof() as u64)
+            .sum();
+        let record_end = self.end() + key.key_len as u64 + gaps;
+        let mut segment = TFree::after_end(record_end + 10);
+        if self.options.large_offsets || segment.is_large() {
+            segment = TFree::after_end(record_end + 18);
+            segment.version = 1001;
+        }
+        let mut payload = WriteBuffer::new(0);
+        for gap in &self.free {
+            payload.write(gap);
+        }
+        payload.write(&segment);
+        // at fEND, where the segment after the end starts
+        key.obj_len = payload.len() as u32;
+        key.n_bytes = key.key_len as u32 + key.obj_len;
+        self.write_record(&key, &[], payload.as_bytes())?;
+        self.header.f_seek_free = key.seek_key;
+        self.header.f_nbytes_free = key.n_bytes;
+        self.header.n_free = self.free.len() as u32 + 1;
+        debug_assert_eq!(segment.first, self.end());
+        Ok(())
+    }
+
+    // TFile::MakeFree: the bytes of a record written again elsewhere, merged with the free segments
+    // next to them. The segment starts with its size, negative, to be told apart from a key.
+    fn release(&mut self, seek: u64, n_bytes: u32) -> io::Result<()> {
+        if seek == 0 || n_bytes == 0 {
+            return Ok(());
+        }
+        let mut released = TFree::new(seek, seek + n_bytes as u64 - 1);
+        self.free.retain(|segment| {
+            let adjacent = segment.first <= released.last + 1 && released.first <= segment.last + 1;
+            if adjacent {
+                released = TFree::new(
+                    segment.first.min(released.first),
+                    segment.last.max(released.last),
+                );
+            }
+            !adjacent
+        });
+        let size = (released.last - released.first + 1).min(2_000_000_000) as i32;
+        self.write_at(released.first, &(-size).to_be_bytes())?;
+        let index = self
+            .free
+            .partition_point(|segment| segment.first < released.first);
+        self.free.insert(index, released);
+        Ok(())
+    }
+
+    /*
+     * TFree::GetBestFree: where a record of `n_bytes` goes, in the first free segment of that size,
+     * else in the first one with 4 more bytes at least, whose rest starts with its negative size
+     * like TKey::WriteFile marks it, else at fEND
+     */
+    fn allocate(&mut self, n_bytes: u32) -> io::Result<u64> {
+        let n_bytes = n_bytes as u64;
+        let size = |segment: &TFree| segment.last - segment.first + 1;
+        let Some(index) = self
+            .free
+            .iter()
+            .position(|segment| size(segment) == n_bytes)
+            .or_else(|| {
+                self.free
+                    .iter()
+                    .position(|segment| size(segment) >= n_bytes + 4)
+            })
+        else {
+            return Ok(self.end());
+        };
+        let seek = self.free[index].first;
+        if size(&self.free[index]) == n_bytes {
+            self.free.remove(index);
+        } else {
+            let left = TFree::new(seek + n_bytes, self.free[index].last);
+            let marker = size(&left).min(2_000_000_000) as i32;
+            self.write_at(left.first, &(-marker).to_be_bytes())?;
+            self.free[index] = left;
+        }
+        Ok(seek)
+    }
+
+    fn name(&self) -> String {
+        self.directories[0].key.name.to_string()
+    }
+
+    // A key at the end of the file, without its sizes, which write_key may move to a free segment
+    fn new_key(&self, directory: DirectoryId, class_name: &str, name: &str, title: &str) -> TKey {
+        let directory = &self.directories[directory.0];
+        let cycle = directory
+            .keys
+            .iter()
+            .filter(|key| key.name.as_str() == name)
+            .map(|key| key.cycle)
+            .max()
+            .unwrap_or(0)
+            + 1;
+        let seek_key = self.end();
+        let mut key = TKey {
+            version: key_version(&self.options, seek_key),
+            datime: datime_now(),
+            cycle,
+            seek_key,
+            seek_p_dir: directory.record.seek_dir,
+            class_name: TString::new(class_name),
+            name: TString::new(name),
+            title: TString::new(title),
+            ..TKey::new()
+        };
+        key.key_len = key.sizeof();
+        key
+    }
+
+    // The key and its payload in the file, compressed with the setting of the file
+    fn write_key(&mut self, key: TKey, payload: Vec<u8>, compress: bool) -> io::Result<TKey> {
+        self.write_key_with_header(key, &[], payload, compress)
+    }
+
+    // A key whose header is followed by members of its class, like TBasket, before the payload
+    fn write_key_with_header(
+        &mut self,
+        mut key: TKey,
+        header: &[u8],
+        payload: Vec<u8>,
+        compress: bool,
+    ) -> io::Result<TKey> {
+        debug_assert_eq!(key.key_len as usize, key.sizeof() as usize + header.len());
+        key.obj_len = payload.len() as u32;
+        let data = if compress {
+            CompressionAlgorithm::compress(self.options.compression, &payload)?
+        } else {
+            payload
+        };
+        key.n_bytes = key.key_len as u32 + data.len() as u32;
+        key.seek_key = self.allocate(key.n_bytes)?;
+        self.write_record(&key, header, &data)?;
+        Ok(key)
+    }
+
+    // The key, the header of its class and its data at its seek, moving fEND past them
+    fn write_record(&mut self, key: &TKey, header: &[u8], data: &[u8]) -> io::Result<()> {
+        let mut record = WriteBuffer::new(0);
+        record.write(key);
+        record.put(header);
+        record.put(data);
+        self.write_at(key.seek_key, record.as_bytes())?;
+        let end = key.seek_key + key.n_bytes as u64;
+        self.header.f_end = self.header.f_end.max(end);
+        Ok(())
+    }
+
+    fn write_header(&mut self) -> io::Result<()> {
+        let mut header = WriteBuffer::new(0);
+        header.write(&self.header);
+        header.put(&vec![0; K_BEGIN as usize - header.len()]);
+        self.write_at(0, header.as_bytes())
+    }
+
+    fn write_directory_header(&mut self, directory: DirectoryId) -> io::Result<()> {
+        let Directory { key, record, .. } = &self.directories[directory.0];
+        let mut bytes = WriteBuffer::new(0);
+        if directory == self.top() {
+            bytes.write(&FirstRecordDict {
+                key: key.clone(),
+                data: FirstRecordData::from_directory(&key.name, &key.title, record),
+            });
+        } else {
+            bytes.write(&TDictionary {
+                tkey: key.clone(),
+                data: record.clone(),
+            });
+        }
+        let seek_key = key.seek_key;
+        self.write_at(seek_key, bytes.as_bytes())
+    }
+
+    fn write_at(&mut self, offset: u64, bytes: &[u8]) -> io::Result<()> {
+        if offset != self.position {
+            self.file.seek(SeekFrom::Start(offset))?;
+        }
+        self.file.write_all(bytes)?;
+        self.position = offset + bytes.len() as u64;
+        Ok(())
+    }
+}
+
+// A writer dropped without close is closed, ignoring errors like BufWriter does
+impl Drop for TFileWriter {
+    fn drop(&mut self) {
+        let _ = self.finish();
+    }
+}
+
+fn key_version(options: &WriteOptions, seek_key: u64) -> u16 {
+    if options.large_offsets || seek_key > K_START_BIG_FILE {
+        KEY_VERSION + 1000
+    } else {
+        KEY_VERSION
+    }
+}
+
+// A random TUUID of version 4, from the random seeds of the hashers of the standard library
+fn random_uuid() -> [u8; 16] {
+    use std::collections::hash_map::RandomState;
+    use std::hash::{BuildHasher, Hasher};
+    let mut uuid = [0u8; 16];
+    for (i, chunk) in uuid.chunks_mut(8).enumerate() {
+        let mut hasher = RandomState::new().build_hasher();
+        hasher.write_usize(i);
+        chunk.copy_from_slice(&hasher.finish().to_be_bytes());
+    }
+    uuid[6] = (uuid[6] & 0x0f) | 0x40;
+    uuid[8] = (uuid[8] & 0x3f) | 0x80;
+    uuid
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::file::keylist::KeyList;
+    use crate::file::tfile::TFile;
+    use crate::file::tfree::FreeList;
+    use crate::objects::any_object::AnyObject;
+    use crate::objects::taxis::TAxis;
+    use crate::objects::tgraph::TGraphErrors;
+    use crate::objects::th1::{TH1, TH2};
+    use crate::objects::tnamed::TNamed;
+    use crate::objects::tobjstring::TObjString;
+    use crate::objects::tprofile::TProfile;
+
+    fn obj_string(s: &str) -> TObjString {
+        TObjString {
+            string: TString::new(s),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_write_file() {
+        for large in [false, true] {
+            let path = std::env::temp_dir().join(format!(
+                "rusty_root_test_write_file_{}_{}.root",
+                std::process::id(),
+                large
+            ));
+            let mut options = WriteOptions::default().with_title("written by rusty_root");
+            if large {
+                options = options.with_large_offsets();
+            }
+            let mut writer = TFileWriter::create_with(&path, options).unwrap();
+            let top = writer.top();
+            assert_eq!(writer.put(top, "note", &obj_string("first")).unwrap(), 1);
+            // compressed, larger than 256 bytes
+            let long = "long note ".repeat(100);
+            assert_eq!(writer.put(top, "note", &obj_string(&long)).unwrap(), 2);
+            let plots = writer.mkdir(top, "plots", "the plots").unwrap();
+            assert!(writer.mkdir(top, "plots", "").is_err());
+            let config = TNamed {
+                name: TString::new("config"),
+                title: TString::new("run 2"),
+                ..Default::default()
+            };
+            writer.put(plots, "config", &config).unwrap();
+            writer.close().unwrap();
+
+            let mut file = TFile::open(path.to_str().unwrap()).unwrap();
+            assert_eq!(file.header.is_large(), large);
+            assert_eq!(file.name(), path.file_name().unwrap().to_str().unwrap());
+            assert_eq!(file.first_data_record.data.title, "written by rusty_root");
+            let keys: Vec<(&str, u16)> = file
+                .key_list
+                .keys
+                .iter()
+                .map(|key| (key.name.as_str(), key.cycle))
+                .collect();
+            assert_eq!(keys, [("note", 1), ("note", 2), ("plots", 1)]);
+            match file.get("note").unwrap() {
+                AnyObject::TObjString(note) => assert_eq!(note.as_str(), long),
+                other => panic!("unexpected object {:?}", other),
+            }
+            let infos: Vec<&str> = file
+                .streamer_info
+                .tlist
+                .iter()
+                .map(|info| info.tnamed.name.as_str())
+                .collect();
+            assert_eq!(infos, ["TObjString", "TObject", "TString", "TNamed"]);
+
+            // a single free segment, from the end of the file
+            let (seek_free, end) = (file.header.f_seek_free, file.header.f_end);
+            let free = FreeList::read_from(file.reader_mut(), seek_free).unwrap();
+            assert_eq!(free.segments.len(), 1);
+            assert_eq!(
+                (free.segments[0].first, free.segments[0].last),
+                (end, K_START_BIG_FILE)
+            );
+            assert_eq!(free.segments[0].is_large(), large);
+
+            // the subdirectory and its keys
+            let seek_key = file.key("plots").unwrap().seek_key;
+            let plots = TDictionary::read_from(file.reader_mut(), seek_key).unwrap();
+            assert_eq!(plots.tkey.class_name, "TDirectory");
+            assert_eq!(plots.data.seek_dir, seek_key);
+            assert_eq!(plots.data.seek_parent, 100);
+            let key_list = KeyList::read_from(file.reader_mut(), plots.data.seek_keys).unwrap();
+            assert_eq!(key_list.keys.len(), 1);
+            let key = &key_list.keys[0];
+            assert_eq!((key.name.as_str(), key.title.as_str()), ("config", "run 2"));
+            let config: TNamed =
+                TKey::read_from_payload(file.reader_mut(), binrw::Endian::Big, (key,)).unwrap();
+            assert_eq!(config.title, "run 2");
+            std::fs::remove_file(&path).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_write_histograms() {
+        let path = std::env::temp_dir().join(format!(
+            "rusty_root_test_write_histograms_{}.root",
+            std::process::id()
+        ));
+        let mut writer = TFileWriter::create(&path).unwrap();
+        let top = writer.top();
+        let mut h1 = TH1::new("h1", "pt", 4, 0.0, 100.0);
+        h1.fill(10.0, 2.0);
+        h1.fill(60.0, 1.0);
+        writer.put(top, "h1", &h1).unwrap();
+        let mut h2 = TH2::new("h2", "eta vs phi", 2, 0.0, 1.0, 3, 0.0, 3.0);
+        h2.fill(0.2, 2.5, 1.5);
+        writer.put(top, "h2", &h2).unwrap();
+        let mut profile = TProfile::with_axis("p", "mean", TAxis::new(2, 0.0, 2.0));
+        profile.fill(0.5, 3.0, 1.0);
+        profile.fill(0.5, 5.0, 1.0);
+        writer.put(top, "p", &profile).unwrap();
+        let graph = TGraphErrors::new("g", "", &[1.0, 2.0], &[3.0, 4.0], &[0.1, 0.2], &[0.3, 0.4]);
+        writer.put(top, "g", &graph).unwrap();
+        let unknown = AnyObject::Unknown {
+            class_name: String::from("TFoo"),
+            object: Default::default(),
+        };
+        assert!(writer.put(top, "foo", &unknown).is_err());
+        writer.close().unwrap();
+
+        let mut file = TFile::open(path.to_str().unwrap()).unwrap();
+        match file.get("h1").unwrap() {
+            AnyObject::TH1(h1) => {
+                assert_eq!((h1.class_name(), h1.title()), ("TH1D", "pt"));
+                assert_eq!(h1.bin_content(1), 2.0);
+                assert_eq!(h1.bin_content(3), 1.0);
+            }
+            other => panic!("unexpected object {:?}", other),
+        }
+        match file.get("h2").unwrap() {
+            AnyObject::TH2(h2) => assert_eq!(h2.bin_content_xy(1, 3), 1.5),
+            other => panic!("unexpected object {:?}", other),
+        }
+        match file.get("p").unwrap() {
+            AnyObject::TProfile(profile) => {
+                assert_eq!(profile.bin_content(1), 4.0);
+                assert_eq!(profile.bin_entries(1), 2.0);
+            }
+            other => panic!("unexpected object {:?}", other),
+        }
+        match file.get("g").unwrap() {
+            AnyObject::TGraphErrors(graph) => {
+                assert_eq!(graph.y(), [3.0, 4.0]);
+                assert_eq!(graph.ey(), [0.3, 0.4]);
+            }
+            other => panic!("unexpected object {:?}", other),
+        }
+        for class in [
+            "TH1D",
+            "TH1",
+            "TH2D",
+            "TProfile",
+            "TAxis",
+            "TArrayD",
+            "TGraphErrors",
+        ] {
+            assert!(
+                file.streamer_info
+                    .tlist
+                    .iter()
+                    .any(|info| info.tnamed.name.as_str() == class),
+                "{}",
+                class
+            );
+        }
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_update_file() {
+        let path = std::env::temp_dir().join(format!(
+            "rusty_root_test_update_file_{}.root",
+            std::process::id()
+        ));
+        let mut writer =
+            TFileWriter::create_with(&path, WriteOptions::default().with_title("fit")).unwrap();
+        let top = writer.top();
+        writer.put(top, "note", &obj_string("prefit")).unwrap();
+        let plots = writer.mkdir(top, "plots", "").unwrap();
+        writer.put(plots, "a", &obj_string("a")).unwrap();
+        writer.close().unwrap();
+        let file = TFile::open(path.to_str().unwrap()).unwrap();
+        // the streamer info, the key lists and the free segments end the file
+        let (old_info, old_end) = (file.header.f_seek_info, file.header.f_end);
+        drop(file);
+
+        let mut writer = TFileWriter::update(&path).unwrap();
+        assert_eq!(writer.end(), old_end);
+        let top = writer.top();
+        let plots = writer.directory(top, "plots").unwrap();
+        assert!(writer.directory(top, "note").is_none());
+        assert_eq!(writer.put(top, "note", &obj_string("postfit")).unwrap(), 2);
+        assert_eq!(writer.put(plots, "a", &obj_string("a2")).unwrap(), 2);
+        let results = writer.mkdir(top, "results", "").unwrap();
+        let mut pulls = TH1::new("pulls", "", 2, -1.0, 1.0);
+        pulls.fill(0.5, 1.0);
+        writer.put(results, "pulls", &pulls).unwrap();
+        writer.close().unwrap();
+
+        let mut file = TFile::open(path.to_str().unwrap()).unwrap();
+        assert_eq!(file.first_data_record.data.title, "fit");
+        let keys: Vec<(&str, u16)> = file
+            .key_list
+            .keys
+            .iter()
+            .map(|key| (key.name.as_str(), key.cycle))
+            .collect();
+        assert_eq!(
+            keys,
+            [("note", 1), ("plots", 1), ("note", 2), ("results", 1)]
+        );
+        match file.get("note").unwrap() {
+            AnyObject::TObjString(note) => assert_eq!(note.as_str(), "postfit"),
+            other => panic!("unexpected object {:?}", other),
+        }
+        let infos: Vec<&str> = file
+            .streamer_info
+            .tlist
+            .iter()
+            .map(|info| info.tnamed.name.as_str())
+            .collect();
+        assert!(infos.starts_with(&["TObjString", "TObject", "TString"]));
+        assert!(infos.contains(&"TH1D"));
+        let seek_key = file.key("plots").unwrap().seek_key;
+        let plots = TDictionary::read_from(file.reader_mut(), seek_key).unwrap();
+        let key_list = KeyList::read_from(file.reader_mut(), plots.data.seek_keys).unwrap();
+        let cycles: Vec<u16> = key_list.keys.iter().map(|key| key.cycle).collect();
+        assert_eq!(cycles, [1, 2]);
+
+        // the records written again free the old ones, the key lists of results, plots and the top
+        // directory go at the start of the gap and the rest of it is marked by its negative size
+        let seek_key = file.key("results").unwrap().seek_key;
+        let results = TDictionary::read_from(file.reader_mut(), seek_key).unwrap();
+        let top = &file.first_data_record.data;
+        let mut gap = old_info;
+        for (seek_keys, n_bytes_keys) in [
+            (results.data.seek_keys, results.data.n_bytes_keys),
+            (plots.data.seek_keys, plots.data.n_bytes_keys),
+            (top.seek_keys, top.n_bytes_keys),
+        ] {
+            assert_eq!(seek_keys, gap);
+            gap += n_bytes_keys as u64;
+        }
+        let (seek_free, end) = (file.header.f_seek_free, file.header.f_end);
+        let (info, seek_free_before) = (file.header.f_seek_info, seek_free);
+        let free = FreeList::read_from(file.reader_mut(), seek_free).unwrap();
+        let segments: Vec<(u64, u64)> = free
+            .segments
+            .iter()
+            .map(|segment| (segment.first, segment.last))
+            .collect();
+        assert_eq!(segments, [(gap, old_end - 1), (end, K_START_BIG_FILE)]);
+        assert_eq!(file.header.n_free, 2);
+        let bytes = std::fs::read(&path).unwrap();
+        assert_eq!(bytes.len() as u64, end);
+        let at = gap as usize;
+        let marker = i32::from_be_bytes(bytes[at..at + 4].try_into().unwrap());
+        assert_eq!(marker as i64, -((old_end - gap) as i64));
+        drop(file);
+
+        // an update that writes nothing puts the records back in the bytes they free, keeps the
+        // gap and adds the one of the free segments, written again at the end
+        TFileWriter::update(&path).unwrap().close().unwrap();
+        let mut file = TFile::open(path.to_str().unwrap()).unwrap();
+        assert_eq!(file.header.f_seek_info, info);
+        let seek_free = file.header.f_seek_free;
+        let free = FreeList::read_from(file.reader_mut(), seek_free).unwrap();
+        assert_eq!(free.segments.len(), 3);
+        assert_eq!(free.segments[0].first, gap);
+        assert_eq!(free.segments[1].first, seek_free_before);
+        assert_eq!(file.key_list.keys.len(), 4);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_overwrite_and_delete() {
+        let path = std::env::temp_dir().join(format!(
+            "rusty_root_test_overwrite_and_delete_{}.root",
+            std::process::id()
+        ));
+        let mut writer = TFileWriter::create(&path).unwrap();
+        let top = writer.top();
+        writer.put(top, "fit", &obj_string("first")).unwrap();
+        writer.put(top, "fit", &obj_string("second")).unwrap();
+        writer.put(top, "note", &obj_string("note")).unwrap();
+        writer.mkdir(top, "plots", "").unwrap();
+        let first = writer.keys(top)[0].seek_key;
+
+        // both cycles are freed, the new key goes where the first one was
+        assert_eq!(
+            writer
+                .put_overwrite(top, "fit", &obj_string("third"))
+                .unwrap(),
+            1
+        );
+        let keys: Vec<(&str, u16)> = writer
+            .keys(top)
+            .iter()
+            .map(|key| (key.name.as_str(), key.cycle))
+            .collect();
+        assert_eq!(keys, [("note", 1), ("plots", 1), ("fit", 1)]);
+        assert_eq!(writer.keys(top)[2].seek_key, first);
+        assert_eq!(
+            writer
+                .put_overwrite(top, "new", &obj_string("new"))
+                .unwrap(),
+            1
+        );
+
+        let error = writer.delete(top, "note", Some(2)).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::NotFound);
+        let error = writer.delete(top, "plots", None).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::Unsupported);
+        writer.delete(top, "note", Some(1)).unwrap();
+        writer.close().unwrap();
+
+        let mut file = TFile::open(path.to_str().unwrap()).unwrap();
+        let keys: Vec<(&str, u16)> = file
+            .key_list
+            .keys
+            .iter()
+            .map(|key| (key.name.as_str(), key.cycle))
+            .collect();
+        assert_eq!(keys, [("plots", 1), ("fit", 1), ("new", 1)]);
+        match file.get("fit").unwrap() {
+            AnyObject::TObjString(fit) => assert_eq!(fit.as_str(), "third"),
+            other => panic!("unexpected object {:?}", other),
+        }
+        // every free segment before the end starts with its negative size
+        let seek_free = file.header.f_seek_free;
+        let free = FreeList::read_from(file.reader_mut(), seek_free).unwrap();
+        let bytes = std::fs::read(&path).unwrap();
+        for segment in &free.segments[..free.segments.len() - 1] {
+            let at = segment.first as usize;
+            let marker = i32::from_be_bytes(bytes[at..at + 4].try_into().unwrap());
+            assert_eq!(marker as i64, -((segment.last - segment.first + 1) as i64));
+        }
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,483 @@
+use crate::objects::th1::{TH1, TH2, TH3};
+use crate::objects::tprofile::TProfile;
+use std::io;
+
+/*
+ * Histogram arithmetic, as TH1::Add, TH1::Multiply, TH1::Divide and TH1::Scale.
+ * https://root.cern/doc/v638/classTH1.html
+ *
+ * The histograms must have the same binning. fSumw2 is created as soon as an operand has one or
+ * a coefficient differs from 1, so that the errors stay those of the sums of squared weights, and
+ * it is propagated like in ROOT:
+ *     add:      e^2 = e0^2 + c1^2 e1^2
+ *     multiply: e^2 = e0^2 c1^2 + e1^2 c0^2
+ *     divide:   e^2 = (e0^2 c1^2 + e1^2 c0^2) / c1^4
+ *     binomial: e^2 = |(1 - 2 b1 / b2) e1^2 + b1^2 e2^2 / b2^2| / b2^2
+ * Sums add up the stat sums and fEntries. Subtractions (c1 < 0), products and quotients recompute
+ * the sums from the bin contents (TH1::ResetStats).
+ */
+impl TH1 {
+    // TH1::Add(h1): self += other
+    pub fn add(&mut self, other: &TH1) -> io::Result<()> {
+        self.add_scaled(other, 1.0)
+    }
+
+    // self -= other
+    pub fn subtract(&mut self, other: &TH1) -> io::Result<()> {
+        self.add_scaled(other, -1.0)
+    }
+
+    // TH1::Add(h1, c1): self += c1 * other
+    pub fn add_scaled(&mut self, other: &TH1, c1: f64) -> io::Result<()> {
+        self.check_consistency(other)?;
+        if !self.has_sumw2() && (other.has_sumw2() || c1 != 1.0) {
+            self.sumw2();
+        }
+        for cell in 0..self.f_array.len() {
+            self.f_array[cell] += c1 * other.f_array[cell];
+            if let Some(sumw2) = self.f_sumw2.f_array.get_mut(cell) {
+                *sumw2 += c1 * c1 * other.bin_sumw2(cell);
+            }
+        }
+        // resetStats = (c1 < 0) in TH1::Add, the sums would no longer be those of the fills
+        if c1 < 0.0 {
+            self.reset_stats();
+            return Ok(());
+        }
+        self.f_entries = (self.f_entries + c1 * other.f_entries).abs();
+        self.f_tsumw += c1 * other.f_tsumw;
+        self.f_tsumw2 += c1 * c1 * other.f_tsumw2;
+        self.f_tsumwx += c1 * other.f_tsumwx;
+        self.f_tsumwx2 += c1 * other.f_tsumwx2;
+        Ok(())
+    }
+
+    // TH1::Multiply(h1): self *= other, bin by bin
+    pub fn multiply(&mut self, other: &TH1) -> io::Result<()> {
+        self.check_consistency(other)?;
+        if !self.has_sumw2() && other.has_sumw2() {
+            self.sumw2();
+        }
+        for cell in 0..self.f_array.len() {
+            let (c0, c1) = (self.f_array[cell], other.f_array[cell]);
+            let e0sq = self.bin_sumw2(cell);
+            if let Some(sumw2) = self.f_sumw2.f_array.get_mut(cell) {
+                *sumw2 = e0sq * c1 * c1 + other.bin_sumw2(cell) * c0 * c0;
+            }
+            self.f_array[cell] = c0 * c1;
+        }
+        self.reset_extrema();
+        self.reset_stats();
+        Ok(())
+    }
+
+    // TH1::Divide(h1): self /= other, bin by bin, 0 where other is empty
+    pub fn divide(&mut self, other: &TH1) -> io::Result<()> {
+        self.check_consistency(other)?;
+        if !self.has_sumw2() && other.has_sumw2() {
+            self.sumw2();
+        }
+        for cell in 0..self.f_array.len() {
+            let (c0, c1) = (self.f_array[cell], other.f_array[cell]);
+            let e0sq = self.bin_sumw2(cell);
+            self.f_array[cell] = if c1 != 0.0 { c0 / c1 } else { 0.0 };
+            if let Some(sumw2) = self.f_sumw2.f_array.get_mut(cell) {
+                *sumw2 = if c1 != 0.0 {
+                    let c1sq = c1 * c1;
+                    (e0sq * c1sq + other.bin_sumw2(cell) * c0 * c0) / (c1sq * c1sq)
+                } else {
+                    0.0
+                };
+            }
+        }
+        self.reset_extrema();
+        self.reset_stats();
+        Ok(())
+    }
+
+    // TH1::Divide(pass, total, 1, 1, "B"): the efficiency pass / total with binomial errors. The
+    // number of entries is the one of total.
+    pub fn divide_binomial(&mut self, pass: &TH1, total: &TH1) -> io::Result<()> {
+        self.check_consistency(pass)?;
+        self.check_consistency(total)?;
+        self.sumw2();
+        for cell in 0..self.f_array.len() {
+            let (b1, b2) = (pass.f_array[cell], total.f_array[cell]);
+            if b2 == 0.0 {
+                self.f_array[cell] = 0.0;
+                self.f_sumw2.f_array[cell] = 0.0;
+                continue;
+            }
+            let w = b1 / b2;
+            self.f_array[cell] = w;
+            // no error when pass == total, TEfficiency gives the asymmetric interval
+            self.f_sumw2.f_array[cell] = if b1 != b2 {
+                let b2sq = b2 * b2;
+                let e1sq = pass.bin_sumw2(cell);
+                let e2sq = total.bin_sumw2(cell);
+                ((1.0 - 2.0 * w) * e1sq + b1 * b1 * e2sq / b2sq).abs() / b2sq
+            } else {
+                0.0
+            };
+        }
+        self.reset_extrema();
+        self.reset_stats();
+        self.f_entries = total.f_entries;
+        Ok(())
+    }
+
+    // TH1::Scale(c1): contents times c1, errors times |c1|, the number of entries is kept
+    pub fn scale(&mut self, c1: f64) {
+        if !self.has_sumw2() && c1 != 1.0 {
+            self.sumw2();
+        }
+        for content in self.f_array.iter_mut() {
+            *content *= c1;
+        }
+        for sumw2 in self.f_sumw2.f_array.iter_mut() {
+            *sumw2 *= c1 * c1;
+        }
+        self.f_tsumw *= c1;
+        self.f_tsumw2 *= c1 * c1;
+        self.f_tsumwx *= c1;
+        self.f_tsumwx2 *= c1;
+        if self.f_maximum != -1111.0 {
+            self.f_maximum *= c1;
+        }
+        if self.f_minimum != -1111.0 {
+            self.f_minimum *= c1;
+        }
+    }
+
+    // TH1::CheckConsistency: same number of cells and the same bin edges on every axis
+    pub fn check_consistency(&self, other: &TH1) -> io::Result<()> {
+        let same = self.f_array.len() == other.f_array.len()
+            && self.dimension() == other.dimension()
+            && self.f_xaxis.same_binning(&other.f_xaxis)
+            && self.f_yaxis.same_binning(&other.f_yaxis)
+            && self.f_zaxis.same_binning(&other.f_zaxis);
+        if !same {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "histograms {} and {} have different binning",
+                    self.name(),
+                    other.name()
+                ),
+            ));
+        }
+        Ok(())
+    }
+
+    // TH1::ResetStats: the stat sums from the bin contents, fEntries the effective entries
+    pub fn reset_stats(&mut self) {
+        let stats = self.bin_stats();
+        self.f_tsumw = stats[0];
+        self.f_tsumw2 = stats[1];
+        self.f_tsumwx = stats[2];
+        self.f_tsumwx2 = stats[3];
+        self.f_entries = if self.has_sumw2() && stats[0] > 0.0 && stats[1] > 0.0 {
+            stats[0] * stats[0] / stats[1]
+        } else {
+            stats[0].abs()
+        };
+    }
+
+    /*
+     * The stat sums computed from the bins in range, at their centers (TH1::GetStats):
+     * sumw, sumw2, sumwx, sumwx2, sumwy, sumwy2 and sumwxy.
+     */
+    pub(crate) fn bin_stats(&self) -> [f64; 7] {
+        let nx = self.nbins_x() + 2;
+        let ny = self.f_yaxis.nbins() + 2;
+        let two_dimensional = self.dimension() >= 2;
+        let mut stats = [0.0; 7];
+        for cell in (0..self.f_array.len()).filter(|cell| self.is_in_range(*cell)) {
+            let w = self.f_array[cell];
+            let x = self.f_xaxis.bin_center(cell % nx);
+            stats[0] += w;
+            stats[1] += self.bin_sumw2(cell);
+            stats[2] += w * x;
+            stats[3] += w * x * x;
+            if two_dimensional {
+                let y = self.f_yaxis.bin_center((cell / nx) % ny);
+                stats[4] += w * y;
+                stats[5] += w * y * y;
+                stats[6] += w * x * y;
+            }
+        }
+        stats
+    }
+
+    fn reset_extrema(&mut self) {
+        self.f_maximum = -1111.0;
+        self.f_minimum = -1111.0;
+    }
+}
+
+// The same operations on two dimensional histograms, with the y sums
+impl TH2 {
+    pub fn add(&mut self, other: &TH2) -> io::Result<()> {
+        self.add_scaled(other, 1.0)
+    }
+
+    pub fn subtract(&mut self, other: &TH2) -> io::Result<()> {
+        self.add_scaled(other, -1.0)
+    }
+
+    pub fn add_scaled(&mut self, other: &TH2, c1: f64) -> io::Result<()> {
+        self.th1.add_scaled(&other.th1, c1)?;
+        if c1 < 0.0 {
+            self.reset_stats();
+            return Ok(());
+        }
+        self.f_tsumwy += c1 * other.f_tsumwy;
+        self.f_tsumwy2 += c1 * other.f_tsumwy2;
+        self.f_tsumwxy += c1 * other.f_tsumwxy;
+        Ok(())
+    }
+
+    pub fn multiply(&mut self, other: &TH2) -> io::Result<()> {
+        self.th1.multiply(&other.th1)?;
+        self.reset_stats();
+        Ok(())
+    }
+
+    pub fn divide(&mut self, other: &TH2) -> io::Result<()> {
+        self.th1.divide(&other.th1)?;
+        self.reset_stats();
+        Ok(())
+    }
+
+    pub fn divide_binomial(&mut self, pass: &TH2, total: &TH2) -> io::Result<()> {
+        self.th1.divide_binomial(&pass.th1, &total.th1)?;
+        self.reset_stats();
+        self.th1.f_entries = total.f_entries;
+        Ok(())
+    }
+
+    pub fn scale(&mut self, c1: f64) {
+        self.th1.scale(c1);
+        self.f_tsumwy *= c1;
+        self.f_tsumwy2 *= c1;
+        self.f_tsumwxy *= c1;
+    }
+
+    pub fn reset_stats(&mut self) {
+        self.th1.reset_stats();
+        let stats = self.th1.bin_stats();
+        self.f_tsumwy = stats[4];
+        self.f_tsumwy2 = stats[5];
+        self.f_tsumwxy = stats[6];
+    }
+}
+
+// Sums of three dimensional histograms, with the y and z sums
+impl TH3 {
+    pub fn add(&mut self, other: &TH3) -> io::Result<()> {
+        self.add_scaled(other, 1.0)
+    }
+
+    pub fn add_scaled(&mut self, other: &TH3, c1: f64) -> io::Result<()> {
+        self.th1.add_scaled(&other.th1, c1)?;
+        if c1 < 0.0 {
+            self.reset_stats();
+            return Ok(());
+        }
+        self.f_tsumwy += c1 * other.f_tsumwy;
+        self.f_tsumwy2 += c1 * other.f_tsumwy2;
+        self.f_tsumwxy += c1 * other.f_tsumwxy;
+        self.f_tsumwz += c1 * other.f_tsumwz;
+        self.f_tsumwz2 += c1 * other.f_tsumwz2;
+        self.f_tsumwxz += c1 * other.f_tsumwxz;
+        self.f_tsumwyz += c1 * other.f_tsumwyz;
+        Ok(())
+    }
+
+    pub fn reset_stats(&mut self) {
+        self.th1.reset_stats();
+        let th1 = &self.th1;
+        let nx = th1.nbins_x() + 2;
+        let ny = th1.f_yaxis.nbins() + 2;
+        let mut stats = [0.0; 7];
+        for cell in (0..th1.f_array.len()).filter(|cell| th1.is_in_range(*cell)) {
+            let w = th1.f_array[cell];
+            let x = th1.f_xaxis.bin_center(cell % nx);
+            let y = th1.f_yaxis.bin_center((cell / nx) % ny);
+            let z = th1.f_zaxis.bin_center(cell / nx / ny);
+            stats[0] += w * y;
+            stats[1] += w * y * y;
+            stats[2] += w * x * y;
+            stats[3] += w * z;
+            stats[4] += w * z * z;
+            stats[5] += w * x * z;
+            stats[6] += w * y * z;
+        }
+        self.f_tsumwy = stats[0];
+        self.f_tsumwy2 = stats[1];
+        self.f_tsumwxy = stats[2];
+        self.f_tsumwz = stats[3];
+        self.f_tsumwz2 = stats[4];
+        self.f_tsumwxz = stats[5];
+        self.f_tsumwyz = stats[6];
+    }
+}
+
+/*
+ * TProfile::Add(h1) of profiles filled independently, e.g. in different jobs: the sums of w y
+ * and w y^2 are the contents and fSumw2, the sums of w and w^2 the bin entries and fBinSumw2.
+ */
+impl TProfile {
+    pub fn add(&mut self, other: &TProfile) -> io::Result<()> {
+        self.th1.check_consistency(&other.th1)?;
+        // without fBinSumw2 the fills were unweighted, the sums of w^2 are the bin entries
+        if self.f_bin_sumw2.is_empty() && !other.f_bin_sumw2.is_empty() {
+            self.f_bin_sumw2 = self.f_bin_entries.clone();
+        }
+        for (cell, sumw2) in self.f_bin_sumw2.f_array.iter_mut().enumerate() {
+            *sumw2 += other
+                .f_bin_sumw2
+                .get(cell)
+                .copied()
+                .unwrap_or(other.f_bin_entries[cell]);
+        }
+        for cell in 0..self.th1.f_array.len() {
+            self.th1.f_array[cell] += other.th1.f_array[cell];
+            self.f_bin_entries.f_array[cell] += other.f_bin_entries[cell];
+            if let Some(sumwy2) = self.th1.f_sumw2.f_array.get_mut(cell) {
+                *sumwy2 += other.th1.f_sumw2.get(cell).copied().unwrap_or(0.0);
+            }
+        }
+        self.th1.f_entries += other.th1.f_entries;
+        self.th1.f_tsumw += other.th1.f_tsumw;
+        self.th1.f_tsumw2 += other.th1.f_tsumw2;
+        self.th1.f_tsumwx += other.th1.f_tsumwx;
+        self.th1.f_tsumwx2 += other.th1.f_tsumwx2;
+        self.f_tsumwy += other.f_tsumwy;
+        self.f_tsumwy2 += other.f_tsumwy2;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::objects::taxis::TAxis;
+
+    fn histogram(name: &str, fills: &[(f64, f64)]) -> TH1 {
+        let mut th1 = TH1::new(name, "", 2, 0.0, 2.0);
+        for (x, w) in fills {
+            th1.fill(*x, *w);
+        }
+        th1
+    }
+
+    #[test]
+    fn test_add_and_scale() {
+        let mut data = histogram("data", &[(0.5, 1.0), (0.5, 1.0), (1.5, 1.0)]);
+        let mc = histogram("mc", &[(0.5, 0.5), (1.5, 2.0)]);
+        assert!(mc.has_sumw2() && !data.has_sumw2());
+        data.subtract(&mc).unwrap();
+        assert!(data.has_sumw2());
+        assert_eq!(data.contents(), [0.0, 1.5, -1.0, 0.0]);
+        assert_eq!(data.bin_error(1), (2.0f64 + 0.25).sqrt());
+        assert_eq!(data.bin_error(2), 5f64.sqrt());
+        // the stats are reset after a subtraction, the entries are sumw^2 / sumw2
+        assert_eq!(
+            (data.entries(), data.f_tsumw, data.f_tsumw2),
+            (0.5 * 0.5 / 7.25, 0.5, 7.25)
+        );
+        assert_eq!(data.f_tsumwx, 1.5 * 0.5 - 1.5);
+
+        let mut scaled = histogram("h", &[(0.5, 1.0), (0.5, 1.0)]);
+        scaled.scale(0.5);
+        assert_eq!(scaled.bin_content(1), 1.0);
+        assert_eq!(scaled.bin_error(1), 0.5 * 2f64.sqrt());
+        assert_eq!((scaled.entries(), scaled.f_tsumwx), (2.0, 0.5));
+
+        let other = TH1::new("other", "", 3, 0.0, 2.0);
+        assert!(scaled.add(&other).is_err());
+        let variable = TH1::with_edges("variable", "", &[0.0, 0.5, 2.0]);
+        assert!(scaled.add(&variable).is_err());
+    }
+
+    #[test]
+    fn test_divide() {
+        let mut pass = histogram("pass", &[(0.5, 1.0), (1.5, 1.0), (1.5, 1.0)]);
+        pass.sumw2();
+        let total = histogram(
+            "total",
+            &[
+                (0.5, 1.0),
+                (0.5, 1.0),
+                (0.5, 1.0),
+                (0.5, 1.0),
+                (1.5, 1.0),
+                (1.5, 1.0),
+            ],
+        );
+
+        let mut ratio = pass.clone();
+        ratio.divide(&total).unwrap();
+        assert_eq!(ratio.bin_content(1), 0.25);
+        // (1 * 16 + 4 * 1) / 256
+        assert_eq!(ratio.bin_error(1), (20.0f64 / 256.0).sqrt());
+        assert_eq!(ratio.bin_content(0), 0.0);
+
+        let mut efficiency = TH1::new("eff", "", 2, 0.0, 2.0);
+        efficiency.divide_binomial(&pass, &total).unwrap();
+        assert_eq!(efficiency.bin_content(1), 0.25);
+        // sqrt(eff (1 - eff) / total)
+        assert!((efficiency.bin_error(1) - (0.25f64 * 0.75 / 4.0).sqrt()).abs() < 1e-15);
+        assert_eq!(
+            (efficiency.bin_content(2), efficiency.bin_error(2)),
+            (1.0, 0.0)
+        );
+        assert_eq!(efficiency.entries(), 6.0);
+
+        let mut product = pass.clone();
+        product.multiply(&total).unwrap();
+        assert_eq!(product.contents()[1..3], [4.0, 4.0]);
+        assert_eq!(product.bin_error(2), (2.0f64 * 4.0 + 2.0 * 4.0).sqrt());
+        assert_eq!(product.f_tsumw, 8.0);
+
+        let mut th2 = TH2::new("h2", "", 2, 0.0, 2.0, 2, 0.0, 2.0);
+        th2.fill(0.5, 1.5, 1.0);
+        let copy = th2.clone();
+        th2.add(&copy).unwrap();
+        assert_eq!((th2.f_tsumwy, th2.entries()), (3.0, 2.0));
+        th2.multiply(&copy).unwrap();
+        assert_eq!((th2.bin_content_xy(1, 2), th2.f_tsumwy), (2.0, 3.0));
+        th2.subtract(&copy).unwrap();
+        assert_eq!((th2.bin_content_xy(1, 2), th2.f_tsumwy), (1.0, 1.5));
+    }
+
+    #[test]
+    fn test_add_profiles() {
+        let fills = [
+            (0.5, 1.0, 1.0),
+            (0.5, 3.0, 1.0),
+            (1.5, 2.0, 2.0),
+            (1.5, 4.0, 0.5),
+        ];
+        let profile = |fills: &[(f64, f64, f64)]| {
+            let mut profile = TProfile::with_axis("p", "", TAxis::new(2, 0.0, 2.0));
+            for (x, y, w) in fills {
+                profile.fill(*x, *y, *w);
+            }
+            profile
+        };
+        let all = profile(&fills);
+        let mut merged = profile(&fills[..2]);
+        merged.add(&profile(&fills[2..])).unwrap();
+        for bin in 1..=2 {
+            assert_eq!(merged.bin_content(bin), all.bin_content(bin));
+            assert_eq!(merged.bin_error(bin), all.bin_error(bin));
+            assert_eq!(
+                merged.bin_effective_entries(bin),
+                all.bin_effective_entries(bin)
+            );
+        }
+        assert_eq!((merged.f_tsumwy, merged.entries()), (all.f_tsumwy, 4.0));
+    }
+}
@@ -0,0 +1,369 @@
+use crate::objects::th1::TH1;
+use statrs::function::gamma::gamma_ur;
+use std::io;
+
+// What the histograms of a chi2 test hold, the options "UU", "UW" and "WW" of TH1::Chi2Test
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Chi2Mode {
+    #[default]
+    Unweighted, // both are counts of events
+    UnweightedWeighted, // the first has counts, the second weights, as data against simulation
+    Weighted,           // both have weights
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Chi2Options {
+    pub mode: Chi2Mode,
+    // "NORM": unweighted histograms that were scaled, the counts are taken from the errors
+    pub normalized: bool,
+    // "UF" and "OF": include the underflow and overflow bins
+    pub underflow: bool,
+    pub overflow: bool,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Chi2Result {
+    pub chi2: f64,
+    pub ndf: usize,
+    pub p_value: f64,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct KolmogorovOptions {
+    // "N": combine the shape probability with the one of the normalisations
+    pub normalization: bool,
+    // "U" and "O": include the underflow and overflow bins
+    pub underflow: bool,
+    pub overflow: bool,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct KolmogorovResult {
+    // the largest distance of the cumulative distributions, what option "M" returns
+    pub distance: f64,
+    pub p_value: f64,
+}
+
+/*
+ * Compatibility tests of two histograms with the same binning, TH1::Chi2Test and
+ * TH1::KolmogorovTest.
+ * https://root.cern/doc/v638/classTH1.html
+ *
+ * The chi2 test follows N. Gagunashvili, "Comparison of weighted and unweighted histograms",
+ * with W1, W2 the sums of the bin contents w1, w2 and s1, s2 the errors of the bins:
+ *     UU: chi2 = sum (W2 w1 - W1 w2)^2 / (w1 + w2) / (W1 W2)
+ *     UW: chi2 = sum (w1 - W1 p)^2 / (W1 p) + (w2 - W2 p)^2 / s2^2, p the estimated probability
+ *     WW: chi2 = sum (W2 w1 - W1 w2)^2 / (W1^2 s2^2 + W2^2 s1^2)
+ * A bin empty in both histograms is skipped and lowers ndf, which starts at the number of bins
+ * minus 1. An empty weighted bin gets the mean squared weight of its histogram as error. In UW, an
+ * empty unweighted bin may estimate p = 0, it then adds w2^2 / s2^2 only.
+ */
+impl TH1 {
+    pub fn chi2_test(&self, other: &TH1, options: Chi2Options) -> io::Result<Chi2Result> {
+        self.check_consistency(other)?;
+        let cells = self.test_cells(options.underflow, options.overflow);
+        // counts from the effective entries for scaled unweighted histograms
+        let effective = |th1: &TH1, cell: usize| {
+            let (content, sumw2) = (th1.bin_content(cell), th1.bin_sumw2(cell));
+            if !(options.normalized && options.mode == Chi2Mode::Unweighted) {
+                (content, sumw2)
+            } else if sumw2 > 0.0 {
+                let count = (content * content / sumw2 + 0.5).floor();
+                (count, count)
+            } else {
+                (0.0, 0.0)
+            }
+        };
+        let bins: Vec<[f64; 4]> = cells
+            .iter()
+            .map(|cell| {
+                let (w1, e1sq) = effective(self, *cell);
+                let (w2, e2sq) = effective(other, *cell);
+                [w1, e1sq, w2, e2sq]
+            })
+            .collect();
+        let sum = |i: usize| bins.iter().map(|bin| bin[i]).sum::<f64>();
+        let (sum1, sumw1, sum2, sumw2) = (sum(0), sum(1), sum(2), sum(3));
+        if sum1 == 0.0 || sum2 == 0.0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "cannot compare {} and {}, one of them is empty",
+                    self.name(),
+                    other.name()
+                ),
+            ));
+        }
+
+        let mut chi2 = 0.0;
+        let mut ndf = cells.len().saturating_sub(1);
+        for [w1, e1sq, w2, e2sq] in bins {
+            if w1 == 0.0 && w2 == 0.0 {
+                ndf = ndf.saturating_sub(1);
+                continue;
+            }
+            let e1sq = if e1sq == 0.0 { sumw1 / sum1 } else { e1sq };
+            let e2sq = if e2sq == 0.0 { sumw2 / sum2 } else { e2sq };
+            chi2 += match options.mode {
+                Chi2Mode::Unweighted => {
+                    let delta = sum2 * w1 - sum1 * w2;
+                    delta * delta / (w1 + w2) / (sum1 * sum2)
+                }
+                Chi2Mode::UnweightedWeighted => {
+                    let var1 = sum2 * w2 - sum1 * e2sq;
+                    let var2 = (var1 * var1 + 4.0 * sum2 * sum2 * w1 * e2sq).sqrt();
+                    let probability = (var1 + var2) / (2.0 * sum2 * sum2);
+                    let (expected1, expected2) = (probability * sum1, probability * sum2);
+                    // an empty unweighted bin with var1 < 0 estimates p = 0, where the first term
+                    // tends to 0, and a weighted bin without error adds no second term
+                    let unweighted = if probability == 0.0 {
+                        0.0
+                    } else {
+                        (w1 - expected1).powi(2) / expected1
+                    };
+                    let weighted = if e2sq == 0.0 {
+                        0.0
+                    } else {
+                        (w2 - expected2).powi(2) / e2sq
+                    };
+                    unweighted + weighted
+                }
+                Chi2Mode::Weighted => {
+                    let delta = sum2 * w1 - sum1 * w2;
+                    delta * delta / (sum1 * sum1 * e2sq + sum2 * sum2 * e1sq)
+                }
+            };
+        }
+        Ok(Chi2Result {
+            chi2,
+            ndf,
+            p_value: chi2_probability(chi2, ndf),
+        })
+    }
+
+    /*
+     * The Kolmogorov-Smirnov test of the two binned distributions. The distance of the cumulative
+     * distributions is scaled with the effective entries of both histograms, a histogram without
+     * errors being taken as an exact function, and converted to a probability with the asymptotic
+     * Kolmogorov distribution, like TMath::KolmogorovProb. Binning makes the probability larger
+     * than the one of the unbinned test.
+     */
+    pub fn kolmogorov_test(
+        &self,
+        other: &TH1,
+        options: KolmogorovOptions,
+    ) -> io::Result<KolmogorovResult> {
+        self.check_consistency(other)?;
+        let cells = self.test_cells(options.underflow, options.overflow);
+        let sums = |th1: &TH1| -> (f64, f64) {
+            cells.iter().fold((0.0, 0.0), |(sum, sumw2), cell| {
+                (sum + th1.bin_content(*cell), sumw2 + th1.bin_sumw2(*cell))
+            })
+        };
+        let ((sum1, w1), (sum2, w2)) = (sums(self), sums(other));
+        if sum1 == 0.0 || sum2 == 0.0 || (w1 <= 0.0 && w2 <= 0.0) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "cannot compare {} and {}, one is empty or both have no errors",
+                    self.name(),
+                    other.name()
+                ),
+            ));
+        }
+
+        let (mut cumulative1, mut cumulative2, mut distance) = (0.0, 0.0, 0.0f64);
+        for cell in &cells {
+            cumulative1 += self.bin_content(*cell) / sum1;
+            cumulative2 += other.bin_content(*cell) / sum2;
+            distance = distance.max((cumulative1 - cumulative2).abs());
+        }
+        let esum1 = if w1 > 0.0 { sum1 * sum1 / w1 } else { 0.0 };
+        let esum2 = if w2 > 0.0 { sum2 * sum2 / w2 } else { 0.0 };
+        let z = if w1 <= 0.0 {
+            distance * esum2.sqrt()
+        } else if w2 <= 0.0 {
+            distance * esum1.sqrt()
+        } else {
+            distance * (esum1 * esum2 / (esum1 + esum2)).sqrt()
+        };
+        let mut p_value = kolmogorov_probability(z);
+        if options.normalization && w1 > 0.0 && w2 > 0.0 {
+            // Eadie et al., section 11.6.2
+            let chi2 = (esum1 - esum2).powi(2) / (esum1 + esum2);
+            let normalization = chi2_probability(chi2, 1);
+            p_value = if p_value > 0.0 && normalization > 0.0 {
+                p_value * normalization * (1.0 - (p_value * normalization).ln())
+            } else {
+                0.0
+            };
+        }
+        Ok(KolmogorovResult { distance, p_value })
+    }
+
+    // The cells of the bins on every axis, with the underflows and overflows if asked
+    fn test_cells(&self, underflow: bool, overflow: bool) -> Vec<usize> {
+        let dimension = self.dimension();
+        let axes = [&self.f_xaxis, &self.f_yaxis, &self.f_zaxis];
+        let (nx, ny) = (self.nbins_x() + 2, self.f_yaxis.nbins() + 2);
+        (0..self.f_array.len())
+            .filter(|cell| {
+                let bins = [cell % nx, (cell / nx) % ny, cell / nx / ny];
+                (0..dimension).all(|i| {
+                    let first = if underflow { 0 } else { 1 };
+                    let last = axes[i].nbins() + usize::from(overflow);
+                    (first..=last).contains(&bins[i])
+                })
+            })
+            .collect()
+    }
+}
+
+// TMath::Prob: the probability of a chi2 at least as large with ndf degrees of freedom
+pub fn chi2_probability(chi2: f64, ndf: usize) -> f64 {
+    if ndf == 0 {
+        return 0.0;
+    }
+    if chi2 <= 0.0 {
+        return 1.0;
+    }
+    gamma_ur(ndf as f64 / 2.0, chi2 / 2.0)
+}
+
+// TMath::KolmogorovProb: the probability of a scaled distance at least as large as z
+pub fn kolmogorov_probability(z: f64) -> f64 {
+    const W: f64 = 2.50662827;
+    // -pi^2 / 8 times 1, 9 and 25
+    const C1: f64 = -1.2337005501361697;
+    const C2: f64 = -11.103304951225528;
+    const C3: f64 = -30.842513753404244;
+    let u = z.abs();
+    if u < 0.2 {
+        1.0
+    } else if u < 0.755 {
+        let v = 1.0 / (u * u);
+        1.0 - W * ((C1 * v).exp() + (C2 * v).exp() + (C3 * v).exp()) / u
+    } else if u < 6.8116 {
+        let v = u * u;
+        let terms = ((3.0 / u).round() as usize).clamp(1, 4);
+        let mut r = [0.0; 4];
+        for (j, r) in r.iter_mut().enumerate().take(terms) {
+            let fj = -2.0 * ((j + 1) * (j + 1)) as f64;
+            *r = (fj * v).exp();
+        }
+        2.0 * (r[0] - r[1] + r[2] - r[3])
+    } else {
+        0.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn histogram(name: &str, contents: &[f64]) -> TH1 {
+        let mut th1 = TH1::new(name, "", contents.len(), 0.0, contents.len() as f64);
+        for (bin, content) in contents.iter().enumerate() {
+            th1.set_bin_content(bin + 1, *content);
+        }
+        th1.f_entries = contents.iter().sum();
+        th1
+    }
+
+    #[test]
+    fn test_chi2() {
+        let data = histogram("data", &[10.0, 20.0, 30.0, 0.0]);
+        let same = histogram("same", &[20.0, 40.0, 60.0, 0.0]);
+        let result = data.chi2_test(&same, Chi2Options::default()).unwrap();
+        assert_eq!((result.chi2, result.ndf, result.p_value), (0.0, 2, 1.0));
+
+        let other = histogram("other", &[30.0, 20.0, 10.0, 0.0]);
+        let result = data.chi2_test(&other, Chi2Options::default()).unwrap();
+        // (60 * 10 - 60 * 30)^2 / 40 / 3600 twice
+        assert!((result.chi2 - 20.0).abs() < 1e-12);
+        assert!((result.p_value - (-10.0f64).exp()).abs() < 1e-12);
+
+        // the same histograms as weights give the same chi2 and, scaled, the same too
+        let options = Chi2Options {
+            mode: Chi2Mode::Weighted,
+            ..Default::default()
+        };
+        let weighted = data.chi2_test(&other, options).unwrap();
+        assert!((weighted.chi2 - 20.0).abs() < 1e-12);
+        let mut scaled = other.clone();
+        scaled.scale(0.5);
+        let weighted = data.chi2_test(&scaled, options).unwrap();
+        assert!((weighted.chi2 - 20.0).abs() < 1e-12);
+        let options = Chi2Options {
+            mode: Chi2Mode::UnweightedWeighted,
+            ..Default::default()
+        };
+        let result = data.chi2_test(&scaled, options).unwrap();
+        assert_eq!(result.ndf, 2);
+        assert!(result.chi2 > 0.0 && result.p_value < 1e-3);
+        let options = Chi2Options {
+            normalized: true,
+            ..Default::default()
+        };
+        let result = data.chi2_test(&scaled, options).unwrap();
+        assert!((result.chi2 - 20.0).abs() < 1e-12);
+
+        assert!(data
+            .chi2_test(&histogram("empty", &[0.0; 4]), Chi2Options::default())
+            .is_err());
+    }
+
+    #[test]
+    fn test_chi2_empty_unweighted_bin() {
+        // the first bin is empty in data and filled in simulation, whose weights of 2 give
+        // var1 = 24 * 4 - 20 * 8 < 0 and p = 0
+        let data = histogram("data", &[0.0, 10.0, 10.0]);
+        let mut simulation = TH1::new("simulation", "", 3, 0.0, 3.0);
+        simulation.sumw2();
+        for (x, n) in [(0.5, 2), (1.5, 5), (2.5, 5)] {
+            for _ in 0..n {
+                simulation.fill(x, 2.0);
+            }
+        }
+        let options = Chi2Options {
+            mode: Chi2Mode::UnweightedWeighted,
+            ..Default::default()
+        };
+        let result = data.chi2_test(&simulation, options).unwrap();
+        assert_eq!(result.ndf, 2);
+
+        // w2^2 / s2^2 for the empty bin, the full terms for the two others
+        let (sum1, sum2, w1, w2, e2sq) = (20.0, 24.0, 10.0, 10.0, 20.0f64);
+        let var1 = sum2 * w2 - sum1 * e2sq;
+        let var2 = (var1 * var1 + 4.0 * sum2 * sum2 * w1 * e2sq).sqrt();
+        let probability = (var1 + var2) / (2.0 * sum2 * sum2);
+        let term = (w1 - probability * sum1).powi(2) / (probability * sum1)
+            + (w2 - probability * sum2).powi(2) / e2sq;
+        assert!((result.chi2 - (16.0 / 8.0 + 2.0 * term)).abs() < 1e-12);
+        assert!(result.p_value > 0.0 && result.p_value < 1.0);
+    }
+
+    #[test]
+    fn test_kolmogorov() {
+        let data = histogram("data", &[10.0, 20.0, 30.0, 40.0]);
+        let same = histogram("same", &[20.0, 40.0, 60.0, 80.0]);
+        let result = data
+            .kolmogorov_test(&same, KolmogorovOptions::default())
+            .unwrap();
+        assert_eq!((result.distance, result.p_value), (0.0, 1.0));
+
+        let other = histogram("other", &[40.0, 30.0, 20.0, 10.0]);
+        let result = data
+            .kolmogorov_test(&other, KolmogorovOptions::default())
+            .unwrap();
+        assert!((result.distance - 0.4).abs() < 1e-12);
+        // z = 0.4 sqrt(50)
+        let z = 0.4 * 50f64.sqrt();
+        let expected = 2.0 * ((-2.0 * z * z).exp() - (-8.0 * z * z).exp());
+        assert!((result.p_value - expected).abs() < 1e-15);
+
+        assert_eq!(kolmogorov_probability(0.1), 1.0);
+        assert!((kolmogorov_probability(1.0) - 0.26999967).abs() < 1e-6);
+        assert_eq!(kolmogorov_probability(7.0), 0.0);
+    }
+}
@@ -0,0 +1,350 @@
+use crate::objects::th1::TH1;
+use std::fmt;
+
+/*
+ * Cutflows
+ *
+ * A cutflow histogram (e.g. cflow_AnaMuons_Baseline_NOSYS) has one bin per cut, labelled with the
+ * name of the cut, holding the yield after it. The relative efficiency of a cut is its yield over
+ * the one of the previous cut, the cumulative efficiency its yield over the one of the first cut.
+ */
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Cut {
+    pub name: String,
+    pub bin: usize,
+    pub events: f64,
+    pub error: f64,
+    pub relative_efficiency: f64,
+    pub cumulative_efficiency: f64,
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Cutflow {
+    pub name: String,
+    pub cuts: Vec<Cut>,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum TableFormat {
+    #[default]
+    Text,
+    Markdown,
+    Csv,
+    Latex,
+}
+
+const HEADER: [&str; 5] = ["Cut", "Yield", "Error", "Rel. eff.", "Cum. eff."];
+
+impl Cutflow {
+    // The labelled bins in bin order, or every bin when the axis has no labels
+    pub fn from_th1(th1: &TH1) -> Self {
+        let axis = th1.x_axis();
+        let mut bins: Vec<(usize, String)> = axis
+            .labels()
+            .filter(|(bin, _)| (1..=axis.nbins()).contains(bin))
+            .map(|(bin, label)| (bin, label.to_string()))
+            .collect();
+        if bins.is_empty() {
+            bins = (1..=axis.nbins())
+                .map(|bin| (bin, format!("bin {bin}")))
+                .collect();
+        }
+        bins.sort_by_key(|(bin, _)| *bin);
+
+        let mut cuts: Vec<Cut> = Vec::with_capacity(bins.len());
+        for (bin, name) in bins {
+            let events = th1.bin_content(bin);
+            let first = cuts.first().map_or(events, |cut| cut.events);
+            let previous = cuts.last().map_or(events, |cut| cut.events);
+            cuts.push(Cut {
+                name,
+                bin,
+                events,
+                error: th1.bin_error(bin),
+                relative_efficiency: efficiency(events, previous),
+                cumulative_efficiency: efficiency(events, first),
+            });
+        }
+        Self {
+            name: th1.name().to_string(),
+            cuts,
+        }
+    }
+
+    pub fn cut(&self, name: &str) -> Option<&Cut> {
+        self.cuts.iter().find(|cut| cut.name == name)
+    }
+
+    // The yield after the last cut over the one of the first
+    pub fn efficiency(&self) -> f64 {
+        self.cuts
+            .last()
+            .map_or(0.0, |cut| cut.cumulative_efficiency)
+    }
+
+    pub fn to_table(&self, format: TableFormat) -> String {
+        let rows: Vec<[String; 5]> = self
+            .cuts
+            .iter()
+            .map(|cut| {
+                // CSV keeps the full precision
+                let (events, error, relative, cumulative) = if format == TableFormat::Csv {
+                    (
+                        cut.events.to_string(),
+                        cut.error.to_string(),
+                        cut.relative_efficiency.to_string(),
+                        cut.cumulative_efficiency.to_string(),
+                    )
+                } else {
+                    (
+                        format!("{:.2}", cut.events),
+                        format!("{:.2}", cut.error),
+                        format!("{:.4}", cut.relative_efficiency),
+                        format!("{:.4}", cut.cumulative_efficiency),
+                    )
+                };
+                [cut.name.clone(), events, error, relative, cumulative]
+            })
+            .collect();
+        render(&HEADER.map(String::from), &rows, format)
+    }
+
+    // The yields and cumulative efficiencies of several cutflows side by side, one row per cut of
+    // the first cutflow, e.g. to compare samples or systematic variations
+    pub fn comparison_table(cutflows: &[Cutflow], format: TableFormat) -> String {
+        let mut header = vec![String::from("Cut")];
+        for cutflow in cutflows {
+            header.push(cutflow.name.clone());
+            header.push(format!("{} eff.", cutflow.name));
+        }
+        let rows: Vec<Vec<String>> = cutflows
+            .first()
+            .map(|first| first.cuts.as_slice())
+            .unwrap_or_default()
+            .iter()
+            .map(|reference| {
+                let mut row = vec![reference.name.clone()];
+                for cutflow in cutflows {
+                    match cutflow.cut(&reference.name) {
+                        Some(cut) if format == TableFormat::Csv => {
+                            row.push(cut.events.to_string());
+                            row.push(cut.cumulative_efficiency.to_string());
+                        }
+                        Some(cut) => {
+                            row.push(format!("{:.2} ± {:.2}", cut.events, cut.error));
+                            row.push(format!("{:.4}", cut.cumulative_efficiency));
+                        }
+                        None => row.extend([String::new(), String::new()]),
+                    }
+                }
+                row
+            })
+            .collect();
+        render(&header, &rows, format)
+    }
+}
+
+impl fmt::Display for Cutflow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_table(TableFormat::Text))
+    }
+}
+
+impl TH1 {
+    pub fn cutflow(&self) -> Cutflow {
+        Cutflow::from_th1(self)
+    }
+}
+
+fn efficiency(events: f64, reference: f64) -> f64 {
+    if reference == 0.0 {
+        0.0
+    } else {
+        events / reference
+    }
+}
+
+fn render<R: AsRef<[String]>>(header: &[String], rows: &[R], format: TableFormat) -> String {
+    match format {
+        TableFormat::Text => {
+            let widths = column_widths(header, rows);
+            let line = |row: &[String]| {
+                let cells: Vec<String> = row
+                    .iter()
+                    .zip(&widths)
+                    .enumerate()
+                    // names to the left, numbers to the right
+                    .map(|(column, (cell, width))| match column {
+                        0 => format!("{cell:<width$}"),
+                        _ => format!("{cell:>width$}"),
+                    })
+                    .collect();
+                cells.join("  ").trim_end().to_string() + "\n"
+            };
+            let rule =
+                "-".repeat(widths.iter().sum::<usize>() + 2 * widths.len().saturating_sub(1));
+            let mut table = line(header) + &rule + "\n";
+            for row in rows {
+                table += &line(row.as_ref());
+            }
+            table
+        }
+        TableFormat::Markdown => {
+            let line = |row: &[String]| {
+                let cells: Vec<String> = row.iter().map(|cell| cell.replace('|', "\\|")).collect();
+                format!("| {} |\n", cells.join(" | "))
+            };
+            let alignment: Vec<&str> = (0..header.len())
+                .map(|column| if column == 0 { ":---" } else { "---:" })
+                .collect();
+            let mut table = line(header) + &format!("| {} |\n", alignment.join(" | "));
+            for row in rows {
+                table += &line(row.as_ref());
+            }
+            table
+        }
+        TableFormat::Csv => std::iter::once(header)
+            .chain(rows.iter().map(|row| row.as_ref()))
+            .map(|row| {
+                let cells: Vec<String> = row.iter().map(|cell| csv_field(cell)).collect();
+                cells.join(",") + "\n"
+            })
+            .collect(),
+        TableFormat::Latex => {
+            let line = |row: &[String]| {
+                let cells: Vec<String> = row.iter().map(|cell| latex_escape(cell)).collect();
+                format!("{} \\\\\n", cells.join(" & "))
+            };
+            let columns = String::from("l") + &"r".repeat(header.len().saturating_sub(1));
+            let mut table = format!("\\begin{{tabular}}{{{columns}}}\n\\hline\n");
+            table += &line(header);
+            table += "\\hline\n";
+            for row in rows {
+                table += &line(row.as_ref());
+            }
+            table + "\\hline\n\\end{tabular}\n"
+        }
+    }
+}
+
+fn column_widths<R: AsRef<[String]>>(header: &[String], rows: &[R]) -> Vec<usize> {
+    let mut widths: Vec<usize> = header.iter().map(|cell| cell.chars().count()).collect();
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row.as_ref()) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    widths
+}
+
+// Quoted when it holds a separator, a quote or a line break, quotes doubled (RFC 4180)
+fn csv_field(cell: &str) -> String {
+    if cell.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", cell.replace('"', "\"\""))
+    } else {
+        cell.to_string()
+    }
+}
+
+fn latex_escape(cell: &str) -> String {
+    let mut escaped = String::with_capacity(cell.len());
+    for c in cell.chars() {
+        match c {
+            '&' | '%' | '$' | '#' | '_' | '{' | '}' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '~' => escaped.push_str("\\textasciitilde{}"),
+            '^' => escaped.push_str("\\textasciicircum{}"),
+            '\\' => escaped.push_str("\\textbackslash{}"),
+            '±' => escaped.push_str("$\\pm$"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::objects::tstring::TString;
+
+    fn cutflow_histogram(name: &str, cuts: &[(&str, f64)]) -> TH1 {
+        // one more bin than cuts, like the unused bins of an extended labelled axis
+        let mut th1 = TH1::new(name, "", cuts.len() + 1, 0.0, cuts.len() as f64 + 1.0);
+        for (bin, (label, events)) in cuts.iter().enumerate() {
+            th1.f_xaxis.set_bin_label(bin + 1, label);
+            th1.set_bin_content(bin + 1, *events);
+        }
+        th1
+    }
+
+    #[test]
+    fn test_cutflow() {
+        let th1 = cutflow_histogram(
+            "cflow_AnaMuons_Baseline_NOSYS",
+            &[("all", 400.0), ("pt_eta", 100.0), ("isolation", 25.0)],
+        );
+        let cutflow = th1.cutflow();
+        assert_eq!(cutflow.cuts.len(), 3);
+        let names: Vec<&str> = cutflow.cuts.iter().map(|cut| cut.name.as_str()).collect();
+        assert_eq!(names, ["all", "pt_eta", "isolation"]);
+        let isolation = cutflow.cut("isolation").unwrap();
+        assert_eq!(
+            (isolation.bin, isolation.events, isolation.error),
+            (3, 25.0, 5.0)
+        );
+        assert_eq!(isolation.relative_efficiency, 0.25);
+        assert_eq!(isolation.cumulative_efficiency, 0.0625);
+        assert_eq!(cutflow.efficiency(), 0.0625);
+
+        // relabelling replaces the label, unlabelled histograms fall back to bin numbers
+        let mut relabelled = th1.clone();
+        relabelled.f_xaxis.set_bin_label(2, "pt, eta");
+        assert_eq!(relabelled.cutflow().cuts[1].name, "pt, eta");
+        assert_eq!(relabelled.x_axis().labels().count(), 3);
+        let unlabelled = TH1::new("h", "", 2, 0.0, 2.0).cutflow();
+        assert_eq!(unlabelled.cuts[1].name, "bin 2");
+        assert_eq!(unlabelled.cuts[1].relative_efficiency, 0.0);
+    }
+
+    #[test]
+    fn test_tables() {
+        let mut th1 = cutflow_histogram("cflow", &[("all", 400.0), ("n_mu >= 1", 100.0)]);
+        th1.f_xaxis.set_bin_label(2, "n_mu, 50% pass");
+        let cutflow = th1.cutflow();
+
+        let text = cutflow.to_string();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 4);
+        assert!(lines[1].chars().all(|c| c == '-'));
+        assert!(lines[3].starts_with("n_mu, 50% pass  "));
+        assert!(lines[3].ends_with("100.00  10.00     0.2500     0.2500"));
+
+        let markdown = cutflow.to_table(TableFormat::Markdown);
+        assert!(markdown.starts_with("| Cut | Yield | Error | Rel. eff. | Cum. eff. |\n| :--- |"));
+
+        let csv = cutflow.to_table(TableFormat::Csv);
+        assert_eq!(
+            csv.lines().nth(2),
+            Some("\"n_mu, 50% pass\",100,10,0.25,0.25")
+        );
+
+        let latex = cutflow.to_table(TableFormat::Latex);
+        assert!(latex.starts_with("\\begin{tabular}{lrrrr}\n"));
+        assert!(latex.contains("n\\_mu, 50\\% pass & 100.00 & 10.00 & 0.2500 & 0.2500 \\\\\n"));
+
+        let mut other = th1.clone();
+        other.set_bin_content(2, 200.0);
+        other.tnamed.name = TString::new("other");
+        let comparison = Cutflow::comparison_table(&[cutflow, other.cutflow()], TableFormat::Csv);
+        assert_eq!(
+            comparison.lines().next(),
+            Some("Cut,cflow,cflow eff.,other,other eff.")
+        );
+        assert_eq!(
+            comparison.lines().nth(2),
+            Some("\"n_mu, 50% pass\",100,0.25,200,0.5")
+        );
+    }
+}
@@ -0,0 +1,903 @@
+use crate::hist::compare::chi2_probability;
+use crate::objects::any_object::AnyObject;
+use crate::objects::tf1::{TF1Parameters, TF1};
+use crate::objects::tformula::{Formula, TFormula};
+use crate::objects::tgraph::{TGraph, TGraphAsymmErrors, TGraphErrors};
+use crate::objects::th1::TH1;
+use crate::objects::tlist::{TList, TListElement};
+use crate::objects::tnamed::TNamed;
+use crate::objects::tstring::TString;
+use std::collections::HashMap;
+use std::io;
+use std::sync::Arc;
+
+// The built-in functions of TF1, each with the parameters ROOT gives them
+#[derive(Debug, Clone, PartialEq)]
+pub enum Model {
+    Gaus,        // Constant, Mean, Sigma
+    Expo,        // Constant, Slope: exp(Constant + Slope x)
+    Pol(usize),  // p0 ... pN
+    Landau,      // Constant, MPV, Sigma
+    CrystalBall, // Constant, Mean, Sigma, Alpha, N
+    Sum(Vec<Model>),
+}
+
+impl Model {
+    pub fn n_parameters(&self) -> usize {
+        match self {
+            Self::Gaus | Self::Landau => 3,
+            Self::Expo => 2,
+            Self::Pol(degree) => degree + 1,
+            Self::CrystalBall => 5,
+            Self::Sum(models) => models.iter().map(Self::n_parameters).sum(),
+        }
+    }
+
+    // The names of the parameters, those repeated in a sum get the index of their model
+    pub fn parameter_names(&self) -> Vec<String> {
+        let names: Vec<&str> = match self {
+            Self::Gaus => vec!["Constant", "Mean", "Sigma"],
+            Self::Expo => vec!["Constant", "Slope"],
+            Self::Pol(degree) => {
+                return (0..=*degree).map(|i| format!("p{}", i)).collect();
+            }
+            Self::Landau => vec!["Constant", "MPV", "Sigma"],
+            Self::CrystalBall => vec!["Constant", "Mean", "Sigma", "Alpha", "N"],
+            Self::Sum(models) => {
+                let mut names: Vec<String> = Vec::new();
+                for (i, model) in models.iter().enumerate() {
+                    for name in model.parameter_names() {
+                        if names.contains(&name) {
+                            names.push(format!("{}_{}", name, i));
+                        } else {
+                            names.push(name);
+                        }
+                    }
+                }
+                return names;
+            }
+        };
+        names.into_iter().map(String::from).collect()
+    }
+
+    // The TFormula expression, with the parameters by name
+    pub fn expression(&self) -> String {
+        let names = self.parameter_names();
+        let mut expression = String::new();
+        self.write_expression(&names, &mut expression);
+        expression
+    }
+
+    fn write_expression(&self, names: &[String], out: &mut String) {
+        let p = |i: usize| format!("[{}]", names[i]);
+        let expression = match self {
+            Self::Gaus => format!(
+                "{}*exp(-0.5*((x-{})/{})*((x-{})/{}))",
+                p(0),
+                p(1),
+                p(2),
+                p(1),
+                p(2)
+            ),
+            Self::Expo => format!("exp({}+{}*x)", p(0), p(1)),
+            Self::Pol(degree) => (0..=*degree)
+                .map(|i| match i {
+                    0 => p(0),
+                    1 => format!("{}*x", p(1)),
+                    _ => format!("{}*x^{}", p(i), i),
+                })
+                .collect::<Vec<_>>()
+                .join("+"),
+            Self::Landau => format!("{}*TMath::Landau(x,{},{})", p(0), p(1), p(2)),
+            Self::CrystalBall => format!(
+                "{}*ROOT::Math::crystalball_function(x,{},{},{},{})",
+                p(0),
+                p(3),
+                p(4),
+                p(2),
+                p(1)
+            ),
+            Self::Sum(models) => {
+                let mut first = 0;
+                for (i, model) in models.iter().enumerate() {
+                    let n = model.n_parameters();
+                    if i > 0 {
+                        out.push('+');
+                    }
+                    out.push('(');
+                    model.write_expression(&names[first..first + n], out);
+                    out.push(')');
+                    first += n;
+                }
+                return;
+            }
+        };
+        out.push_str(&expression);
+    }
+
+    /*
+     * Starting values from the points, like the initialisation of TH1::Fit: the height, mean and
+     * spread of the points for the peaks, a straight line through the logarithms for expo and the
+     * mean for polynomials. The peaks of a sum share the height.
+     */
+    pub fn initial_parameters(&self, points: &[FitPoint]) -> Vec<f64> {
+        let (mut sumw, mut sumwx, mut sumwx2) = (0.0, 0.0, 0.0);
+        let (mut max_y, mut x_at_max) = (f64::MIN, 0.0);
+        for point in points {
+            let w = point.y.max(0.0);
+            sumw += w;
+            sumwx += w * point.x;
+            sumwx2 += w * point.x * point.x;
+            if point.y > max_y {
+                (max_y, x_at_max) = (point.y, point.x);
+            }
+        }
+        let mean = if sumw > 0.0 { sumwx / sumw } else { x_at_max };
+        let rms = if sumw > 0.0 {
+            (sumwx2 / sumw - mean * mean).abs().sqrt()
+        } else {
+            1.0
+        };
+        let rms = if rms > 0.0 { rms } else { 1.0 };
+        let max_y = max_y.max(0.0);
+        match self {
+            Self::Gaus => vec![max_y, mean, rms],
+            Self::Landau => vec![max_y / 0.18, x_at_max, rms / 3.0],
+            Self::CrystalBall => vec![max_y, x_at_max, rms, 1.0, 2.0],
+            Self::Expo => {
+                // least squares line through (x, ln y)
+                let logs: Vec<(f64, f64)> = points
+                    .iter()
+                    .filter(|point| point.y > 0.0)
+                    .map(|point| (point.x, point.y.ln()))
+                    .collect();
+                let n = logs.len() as f64;
+                let (sx, sy) = logs
+                    .iter()
+                    .fold((0.0, 0.0), |(sx, sy), (x, y)| (sx + x, sy + y));
+                let (sxx, sxy) = logs
+                    .iter()
+                    .fold((0.0, 0.0), |(sxx, sxy), (x, y)| (sxx + x * x, sxy + x * y));
+                let denominator = n * sxx - sx * sx;
+                if logs.len() < 2 || denominator == 0.0 {
+                    return vec![0.0, 0.0];
+                }
+                let slope = (n * sxy - sx * sy) / denominator;
+                vec![(sy - slope * sx) / n, slope]
+            }
+            Self::Pol(degree) => {
+                let mean_y =
+                    points.iter().map(|point| point.y).sum::<f64>() / points.len().max(1) as f64;
+                let mut parameters = vec![0.0; degree + 1];
+                parameters[0] = mean_y;
+                parameters
+            }
+            Self::Sum(models) => {
+                let peaks = models
+                    .iter()
+                    .filter(|model| !matches!(model, Self::Pol(_) | Self::Expo))
+                    .count()
+                    .max(1);
+                models
+                    .iter()
+                    .flat_map(|model| {
+                        let mut parameters = model.initial_parameters(points);
+                        match model {
+                            Self::Pol(_) => parameters.iter_mut().for_each(|p| *p = 0.0),
+                            Self::Expo => {}
+                            _ => parameters[0] /= peaks as f64,
+                        }
+                        parameters
+                    })
+                    .collect()
+            }
+        }
+    }
+}
+
+// Least squares or, for counts, the binned Poisson likelihood (TH1::Fit option "L")
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum FitMethod {
+    #[default]
+    Chi2,
+    Likelihood,
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct FitOptions {
+    pub method: FitMethod,
+    // only the points, or bin centers, within [xmin, xmax]
+    pub range: Option<(f64, f64)>,
+    // the starting parameters, guessed from the data for a model when missing
+    pub initial: Option<Vec<f64>>,
+}
+
+// A point to fit: the value y at x and its error, with the error of x of a graph
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct FitPoint {
+    pub x: f64,
+    pub y: f64,
+    pub error: f64,
+    pub ex: f64,
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct FitResult {
+    pub parameters: Vec<f64>,
+    pub errors: Vec<f64>,
+    pub covariance: Vec<Vec<f64>>,
+    pub parameter_names: Vec<String>,
+    // the minimum: the chi2, or twice the negative log likelihood ratio to the data (Baker-Cousins)
+    pub chi2: f64,
+    pub ndf: usize,
+    pub p_value: f64,
+    pub n_points: usize,
+    pub converged: bool,
+    // the range of the function: that of the options, else the axis of the histogram or the
+    // points of the graph with their x errors, like TGraph::ComputeRange
+    pub range: (f64, f64),
+    // the TFormula expression of a model
+    pub expression: Option<String>,
+    // the function at 101 points over the range, to keep a fitted closure in a TF1
+    pub saved: Vec<f64>,
+}
+
+impl FitResult {
+    pub fn parameter(&self, name: &str) -> Option<f64> {
+        let index = self.parameter_names.iter().position(|n| n == name)?;
+        self.parameters.get(index).copied()
+    }
+
+    pub fn parameter_error(&self, name: &str) -> Option<f64> {
+        let index = self.parameter_names.iter().position(|n| n == name)?;
+        self.errors.get(index).copied()
+    }
+
+    // The correlation of two parameters from the covariance
+    pub fn correlation(&self, i: usize, j: usize) -> f64 {
+        let c = &self.covariance;
+        c[i][j] / (c[i][i] * c[j][j]).sqrt()
+    }
+
+    /*
+     * The fit as a TF1, as TH1::Fit stores it in the list of functions: the expression of a model
+     * in fFormula, or the parameters in fParams and the values of a closure in fSave.
+     */
+    pub fn to_tf1(&self, name: &str) -> TF1 {
+        let npar = self.parameters.len();
+        let parameter_names = || self.parameter_names.iter().map(|n| TString::new(n));
+        let (f_formula, f_params, f_save) = match &self.expression {
+            Some(expression) => {
+                let formula = TFormula {
+                    version: 13,
+                    tnamed: TNamed {
+                        name: TString::new(name),
+                        title: TString::new(expression),
+                        ..Default::default()
+                    },
+                    parameters: self.parameters.clone(),
+                    all_parameters_set: true,
+                    parameter_names: self
+                        .parameter_names
+                        .iter()
+                        .enumerate()
+                        .map(|(i, name)| (name.clone(), i as i32))
+                        .collect(),
+                    formula: TString::new(expression),
+                    ndim: 1,
+                    ..Default::default()
+                };
+                (Some(Arc::new(formula)), None, Vec::new())
+            }
+            None => {
+                let params = TF1Parameters {
+                    version: 1,
+                    f_parameters: self.parameters.clone(),
+                    f_par_names: parameter_names().collect(),
+                    ..Default::default()
+                };
+                let mut save = self.saved.clone();
+                save.extend([self.range.0, self.range.1]);
+                (None, Some(Arc::new(params)), save)
+            }
+        };
+        TF1 {
+            version: 12,
+            tnamed: TNamed {
+                name: TString::new(name),
+                title: TString::new(self.expression.as_deref().unwrap_or(name)),
+                ..Default::default()
+            },
+            f_xmin: self.range.0,
+            f_xmax: self.range.1,
+            f_npar: npar as i32,
+            f_ndim: 1,
+            f_npx: 100,
+            f_type: if self.expression.is_some() { 0 } else { 1 },
+            f_npfits: self.n_points as i32,
+            f_ndf: self.ndf as i32,
+            f_chisquare: self.chi2,
+            f_minimum: -1111.0,
+            f_maximum: -1111.0,
+            f_par_errors: self.errors.clone(),
+            f_par_min: vec![0.0; npar],
+            f_par_max: vec![0.0; npar],
+            f_save,
+            f_formula,
+            f_params,
+            ..Default::default()
+        }
+    }
+}
+
+/*
+ * Fits of histograms and graphs, as TH1::Fit and TGraph::Fit.
+ * https://root.cern/doc/v638/classTH1.html
+ *
+ * A histogram is fitted at its bin centers, without the underflow and overflow. The chi2 fit
+ * skips the empty bins like ROOT, the likelihood fit uses them all and needs counts. A graph is
+ * fitted with its y errors, 1 for a TGraph, the points with zero error skipped. The x errors of a
+ * TGraphErrors or TGraphAsymmErrors enter the chi2 as the effective variance of ROOT,
+ * ey^2 + (f'(x) ex)^2, with the mean of the low and high errors for asymmetric ones.
+ *
+ * The minimiser is Levenberg-Marquardt with numerical derivatives of the function, on the
+ * Gauss-Newton approximation of the second derivatives. The covariance is twice the inverse of
+ * that matrix at the minimum, as for an error definition of 1 in Minuit.
+ */
+impl TH1 {
+    pub fn fit(&self, model: &Model, options: &FitOptions) -> io::Result<FitResult> {
+        let points = self.fit_points(options)?;
+        fit_model(&points, self.fit_range(options), model, options)
+    }
+
+    // Fit f(x, parameters) from the initial parameters
+    pub fn fit_with(
+        &self,
+        f: impl Fn(f64, &[f64]) -> f64,
+        initial: &[f64],
+        options: &FitOptions,
+    ) -> io::Result<FitResult> {
+        let points = self.fit_points(options)?;
+        let range = self.fit_range(options);
+        fit_function(&points, range, &f, initial, options.method)
+    }
+
+    // Add a function to fFunctions, replacing the one with the same name
+    pub fn add_function(&mut self, tf1: TF1) {
+        add_to_list(&mut self.f_functions, tf1);
+    }
+
+    fn fit_points(&self, options: &FitOptions) -> io::Result<Vec<FitPoint>> {
+        if self.dimension() != 1 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{} is not a one dimensional histogram", self.name()),
+            ));
+        }
+        let axis = &self.f_xaxis;
+        let points = (1..=self.nbins_x())
+            .map(|bin| FitPoint {
+                x: axis.bin_center(bin),
+                y: self.bin_content(bin),
+                error: self.bin_error(bin),
+                ex: 0.0,
+            })
+            .filter(|point| in_range(point.x, options.range))
+            .filter(|point| options.method == FitMethod::Likelihood || point.error > 0.0)
+            .collect();
+        Ok(points)
+    }
+
+    // The range of the fit, else from the low edge of the first bin to the high edge of the last
+    fn fit_range(&self, options: &FitOptions) -> (f64, f64) {
+        let axis = &self.f_xaxis;
+        options
+            .range
+            .unwrap_or_else(|| (axis.bin_low_edge(1), axis.bin_up_edge(axis.nbins())))
+    }
+}
+
+impl TGraph {
+    pub fn fit(&self, model: &Model, options: &FitOptions) -> io::Result<FitResult> {
+        let (errors, ex) = (vec![1.0; self.f_x.len()], vec![0.0; self.f_x.len()]);
+        let (points, range) = graph_points(self, &errors, &ex, options)?;
+        fit_model(&points, range, model, options)
+    }
+
+    pub fn fit_with(
+        &self,
+        f: impl Fn(f64, &[f64]) -> f64,
+        initial: &[f64],
+        options: &FitOptions,
+    ) -> io::Result<FitResult> {
+        let (errors, ex) = (vec![1.0; self.f_x.len()], vec![0.0; self.f_x.len()]);
+        let (points, range) = graph_points(self, &errors, &ex, options)?;
+        fit_function(&points, range, &f, initial, options.method)
+    }
+
+    pub fn add_function(&mut self, tf1: TF1) {
+        let functions = self.f_functions.get_or_insert_with(Default::default);
+        add_to_list(Arc::make_mut(functions), tf1);
+    }
+}
+
+// With the errors of y and of x
+impl TGraphErrors {
+    pub fn fit(&self, model: &Model, options: &FitOptions) -> io::Result<FitResult> {
+        let (points, range) = graph_points(self, &self.f_ey, &self.f_ex, options)?;
+        fit_model(&points, range, model, options)
+    }
+
+    pub fn fit_with(
+        &self,
+        f: impl Fn(f64, &[f64]) -> f64,
+        initial: &[f64],
+        options: &FitOptions,
+    ) -> io::Result<FitResult> {
+        let (points, range) = graph_points(self, &self.f_ey, &self.f_ex, options)?;
+        fit_function(&points, range, &f, initial, options.method)
+    }
+}
+
+// With the means of the low and high errors of y and of x
+impl TGraphAsymmErrors {
+    pub fn fit(&self, model: &Model, options: &FitOptions) -> io::Result<FitResult> {
+        let mean = |low: &[f64], high: &[f64]| -> Vec<f64> {
+            low.iter()
+                .zip(high)
+                .map(|(low, high)| 0.5 * (low + high))
+                .collect()
+        };
+        let errors = mean(&self.f_eylow, &self.f_eyhigh);
+        let ex = mean(&self.f_exlow, &self.f_exhigh);
+        let (points, range) = graph_points(self, &errors, &ex, options)?;
+        fit_model(&points, range, model, options)
+    }
+}
+
+// The points to fit and the range of the function
+fn graph_points(
+    graph: &TGraph,
+    errors: &[f64],
+    ex: &[f64],
+    options: &FitOptions,
+) -> io::Result<(Vec<FitPoint>, (f64, f64))> {
+    if options.method == FitMethod::Likelihood {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("graph {} has no counts for a likelihood fit", graph.name()),
+        ));
+    }
+    let points: Vec<FitPoint> = graph
+        .points()
+        .zip(errors.iter().zip(ex))
+        .map(|((x, y), (error, ex))| FitPoint {
+            x,
+            y,
+            error: *error,
+            ex: *ex,
+        })
+        .collect();
+    let range = options.range.unwrap_or_else(|| {
+        points
+            .iter()
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(xmin, xmax), point| {
+                (xmin.min(point.x - point.ex), xmax.max(point.x + point.ex))
+            })
+    });
+    let points = points
+        .into_iter()
+        .filter(|point| in_range(point.x, options.range) && point.error > 0.0)
+        .collect();
+    Ok((points, range))
+}
+
+fn in_range(x: f64, range: Option<(f64, f64)>) -> bool {
+    range.is_none_or(|(xmin, xmax)| x >= xmin && x <= xmax)
+}
+
+fn add_to_list(list: &mut TList<AnyObject>, tf1: TF1) {
+    let name = tf1.name().to_string();
+    list.objects.retain(|element| element.object.name() != name);
+    list.objects.push(TListElement {
+        object: Arc::new(AnyObject::TF1(Box::new(tf1))),
+        option: TString::default(),
+    });
+    list.n_objects = list.objects.len() as u32;
+}
+
+fn fit_model(
+    points: &[FitPoint],
+    range: (f64, f64),
+    model: &Model,
+    options: &FitOptions,
+) -> io::Result<FitResult> {
+    let names = model.parameter_names();
+    let expression = model.expression();
+    let indices: HashMap<String, usize> = names
+        .iter()
+        .enumerate()
+        .map(|(i, name)| (name.clone(), i))
+        .collect();
+    let formula = Formula::parse_with_names(&expression, &indices)?;
+    let initial = match &options.initial {
+        Some(initial) if initial.len() == names.len() => initial.clone(),
+        Some(initial) => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "{} initial parameters for the {} of {}",
+                    initial.len(),
+                    names.len(),
+                    expression
+                ),
+            ))
+        }
+        None => model.initial_parameters(points),
+    };
+    let f = |x: f64, parameters: &[f64]| formula.eval(&[x], parameters);
+    let mut result = fit_function(points, range, &f, &initial, options.method)?;
+    result.parameter_names = names;
+    result.expression = Some(expression);
+    result.saved.clear();
+    Ok(result)
+}
+
+const MAX_ITERATIONS: usize = 1000;
+const TOLERANCE: f64 = 1e-12;
+
+fn fit_function(
+    points: &[FitPoint],
+    range: (f64, f64),
+    f: &dyn Fn(f64, &[f64]) -> f64,
+    initial: &[f64],
+    method: FitMethod,
+) -> io::Result<FitResult> {
+    let npar = initial.len();
+    if points.len() <= npar {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "{} points cannot constrain {} parameters",
+                points.len(),
+                npar
+            ),
+        ));
+    }
+    let objective = |parameters: &[f64]| -> f64 {
+        points
+            .iter()
+            .map(|point| {
+                let variance = variance(point, f, parameters);
+                term(method, point, f(point.x, parameters), variance)
+            })
+            .sum()
+    };
+
+    let mut parameters = initial.to_vec();
+    let mut value = objective(&parameters);
+    if !value.is_finite() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "the function is not finite at the initial parameters",
+        ));
+    }
+    let mut lambda = 1e-3;
+    let mut converged = false;
+    let (mut gradient, mut hessian) = derivatives(points, f, &parameters, method);
+    for _ in 0..MAX_ITERATIONS {
+        let mut step = None;
+        while lambda < 1e16 {
+            let mut damped = hessian.clone();
+            for (i, row) in damped.iter_mut().enumerate() {
+                row[i] += lambda * hessian[i][i].max(1e-12);
+            }
+            let negative: Vec<f64> = gradient.iter().map(|g| -g).collect();
+            if let Some(delta) = solve(damped, negative) {
+                let trial: Vec<f64> = parameters.iter().zip(&delta).map(|(p, d)| p + d).collect();
+                let trial_value = objective(&trial);
+                if trial_value.is_finite() && trial_value <= value {
+                    step = Some((trial, trial_value));
+                    lambda = (lambda / 10.0).max(1e-12);
+                    break;
+                }
+            }
+            lambda *= 10.0;
+        }
+        let Some((trial, trial_value)) = step else {
+            // no step lowers the minimum any more
+            converged = true;
+            break;
+        };
+        let decrease = value - trial_value;
+        parameters = trial;
+        value = trial_value;
+        (gradient, hessian) = derivatives(points, f, &parameters, method);
+        if decrease <= TOLERANCE * (value.abs() + TOLERANCE) {
+            converged = true;
+            break;
+        }
+    }
+
+    let inverse = invert(hessian).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "the parameters are not all constrained by the points",
+        )
+    })?;
+    let covariance: Vec<Vec<f64>> = inverse
+        .into_iter()
+        .map(|row| row.into_iter().map(|c| 2.0 * c).collect())
+        .collect();
+    let errors = (0..npar).map(|i| covariance[i][i].abs().sqrt()).collect();
+    let ndf = points.len() - npar;
+    let saved = (0..=100)
+        .map(|i| {
+            f(
+                range.0 + (range.1 - range.0) * i as f64 / 100.0,
+                &parameters,
+            )
+        })
+        .collect();
+    Ok(FitResult {
+        parameters,
+        errors,
+        covariance,
+        parameter_names: (0..npar).map(|i| format!("p{}", i)).collect(),
+        chi2: value,
+        ndf,
+        p_value: chi2_probability(value, ndf),
+        n_points: points.len(),
+        converged,
+        range,
+        expression: None,
+        saved,
+    })
+}
+
+// The contribution of a point with the function value fx and the variance of the chi2
+fn term(method: FitMethod, point: &FitPoint, fx: f64, variance: f64) -> f64 {
+    match method {
+        FitMethod::Chi2 => (point.y - fx).powi(2) / variance,
+        FitMethod::Likelihood if fx <= 0.0 => {
+            if point.y > 0.0 {
+                f64::INFINITY
+            } else {
+                0.0
+            }
+        }
+        FitMethod::Likelihood if point.y > 0.0 => {
+            2.0 * (fx - point.y + point.y * (point.y / fx).ln())
+        }
+        FitMethod::Likelihood => 2.0 * fx,
+    }
+}
+
+// The effective variance of FitUtil::EvaluateChi2Effective, ey^2 + (f'(x) ex)^2
+fn variance(point: &FitPoint, f: &dyn Fn(f64, &[f64]) -> f64, parameters: &[f64]) -> f64 {
+    let ey2 = point.error * point.error;
+    if point.ex == 0.0 {
+        return ey2;
+    }
+    let h = 1e-6 * (point.x.abs() + point.ex);
+    let slope = (f(point.x + h, parameters) - f(point.x - h, parameters)) / (2.0 * h);
+    ey2 + (slope * point.ex).powi(2)
+}
+
+/*
+ * The gradient of the objective and its second derivatives without those of f, with the
+ * effective variance s^2 taken at the parameters:
+ *     chi2:       d/dp = -2 (y - f) / s^2 df/dp,  d2/dp2 = 2 / s^2 df/dp df/dp
+ *     likelihood: d/dp = 2 (1 - y / f) df/dp,     d2/dp2 = 2 / f df/dp df/dp (expected)
+ */
+fn derivatives(
+    points: &[FitPoint],
+    f: &dyn Fn(f64, &[f64]) -> f64,
+    parameters: &[f64],
+    method: FitMethod,
+) -> (Vec<f64>, Vec<Vec<f64>>) {
+    let npar = parameters.len();
+    let mut gradient = vec![0.0; npar];
+    let mut hessian = vec![vec![0.0; npar]; npar];
+    let mut shifted = parameters.to_vec();
+    let mut jacobian = vec![0.0; npar];
+    for point in points {
+        let fx = f(point.x, parameters);
+        for (j, derivative) in jacobian.iter_mut().enumerate() {
+            let h = 1e-6 * (parameters[j].abs() + 1e-3);
+            shifted[j] = parameters[j] + h;
+            let up = f(point.x, &shifted);
+            shifted[j] = parameters[j] - h;
+            let down = f(point.x, &shifted);
+            shifted[j] = parameters[j];
+            *derivative = (up - down) / (2.0 * h);
+        }
+        let (first, second) = match method {
+            FitMethod::Chi2 => {
+                let s2 = variance(point, f, parameters);
+                (-2.0 * (point.y - fx) / s2, 2.0 / s2)
+            }
+            FitMethod::Likelihood if fx > 0.0 => (2.0 * (1.0 - point.y / fx), 2.0 / fx),
+            FitMethod::Likelihood => (0.0, 0.0),
+        };
+        for i in 0..npar {
+            gradient[i] += first * jacobian[i];
+            for j in 0..npar {
+                hessian[i][j] += second * jacobian[i] * jacobian[j];
+            }
+        }
+    }
+    (gradient, hessian)
+}
+
+// Gaussian elimination with partial pivoting, None for a singular matrix
+fn solve(mut a: Vec<Vec<f64>>, mut b: Vec<f64>) -> Option<Vec<f64>> {
+    let n = b.len();
+    for column in 0..n {
+        let pivot =
+            (column..n).max_by(|i, j| a[*i][column].abs().total_cmp(&a[*j][column].abs()))?;
+        if a[pivot][column].abs() < 1e-300 || !a[pivot][column].is_finite() {
+            return None;
+        }
+        a.swap(column, pivot);
+        b.swap(column, pivot);
+        let (upper, lower) = a.split_at_mut(column + 1);
+        let pivot_row = &upper[column];
+        for (offset, target) in lower.iter_mut().enumerate() {
+            let factor = target[column] / pivot_row[column];
+            for (value, pivot) in target[column..].iter_mut().zip(&pivot_row[column..]) {
+                *value -= factor * pivot;
+            }
+            b[column + 1 + offset] -= factor * b[column];
+        }
+    }
+    let mut x = vec![0.0; n];
+    for row in (0..n).rev() {
+        let sum: f64 = (row + 1..n).map(|k| a[row][k] * x[k]).sum();
+        x[row] = (b[row] - sum) / a[row][row];
+    }
+    Some(x)
+}
+
+fn invert(a: Vec<Vec<f64>>) -> Option<Vec<Vec<f64>>> {
+    let n = a.len();
+    let columns = (0..n)
+        .map(|i| {
+            let unit = (0..n).map(|j| if i == j { 1.0 } else { 0.0 }).collect();
+            solve(a.clone(), unit)
+        })
+        .collect::<Option<Vec<Vec<f64>>>>()?;
+    // the inverse is symmetric, its columns are its rows
+    Some(columns)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // a gaussian peak of 1000 events on a flat background of 20 per bin, without fluctuations
+    fn peak() -> TH1 {
+        let mut th1 = TH1::new("mass", "", 60, 80.0, 110.0);
+        for bin in 1..=60 {
+            let x = th1.x_axis().bin_center(bin);
+            let gaus = 1000.0 * 0.5 / (2.0 * 2.5 * 2.5 * std::f64::consts::PI).sqrt()
+                * (-0.5 * ((x - 91.0) / 2.5f64).powi(2)).exp();
+            th1.set_bin_content(bin, (gaus + 20.0).round());
+        }
+        th1.f_entries = th1.integral();
+        th1
+    }
+
+    #[test]
+    fn test_fit_peak() {
+        let th1 = peak();
+        let model = Model::Sum(vec![Model::Gaus, Model::Pol(0)]);
+        assert_eq!(model.parameter_names(), ["Constant", "Mean", "Sigma", "p0"]);
+        let options = FitOptions {
+            initial: Some(vec![50.0, 90.0, 3.0, 10.0]),
+            ..Default::default()
+        };
+        let result = th1.fit(&model, &options).unwrap();
+        assert!(result.converged);
+        assert!((result.parameter("Mean").unwrap() - 91.0).abs() < 0.05);
+        assert!((result.parameter("Sigma").unwrap() - 2.5).abs() < 0.05);
+        assert!((result.parameter("p0").unwrap() - 20.0).abs() < 0.5);
+        assert!(result.parameter_error("Mean").unwrap() > 0.0);
+        assert_eq!((result.ndf, result.n_points), (56, 60));
+        assert!(result.p_value > 0.99);
+        // the function spans the axis, not only the bin centers
+        assert_eq!(result.range, (80.0, 110.0));
+
+        // the likelihood fit agrees with the chi2 fit on such counts
+        let options = FitOptions {
+            method: FitMethod::Likelihood,
+            initial: Some(result.parameters.clone()),
+            ..Default::default()
+        };
+        let likelihood = th1.fit(&model, &options).unwrap();
+        assert!((likelihood.parameters[1] - result.parameters[1]).abs() < 0.01);
+
+        // the TF1 evaluates to the fitted model and replaces the previous fit
+        let mut fitted = th1.clone();
+        fitted.add_function(result.to_tf1("fit"));
+        fitted.add_function(likelihood.to_tf1("fit"));
+        assert_eq!(fitted.functions().count(), 1);
+        let tf1 = fitted.functions().next().unwrap().as_function().unwrap();
+        assert_eq!(tf1.parameter("Sigma"), likelihood.parameter("Sigma"));
+        assert_eq!((tf1.f_xmin, tf1.f_xmax), (80.0, 110.0));
+        let x = 92.0;
+        let p = &likelihood.parameters;
+        let expected = p[0] * (-0.5 * ((x - p[1]) / p[2]).powi(2)).exp() + p[3];
+        assert!((tf1.eval(x).unwrap() - expected).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_fit_graph() {
+        let mut graph = TGraph {
+            f_x: (0..10).map(|i| i as f64).collect(),
+            f_y: (0..10).map(|i| 3.0 * (-0.5 * i as f64).exp()).collect(),
+            ..Default::default()
+        };
+        graph.f_npoints = 10;
+        let result = graph.fit(&Model::Expo, &FitOptions::default()).unwrap();
+        assert!((result.parameters[0] - 3f64.ln()).abs() < 1e-6);
+        assert!((result.parameters[1] + 0.5).abs() < 1e-6);
+        assert!(result.chi2 < 1e-12);
+        assert_eq!(result.range, (0.0, 9.0));
+
+        // a closure, kept in the TF1 as saved points
+        let line = |x: f64, p: &[f64]| p[0] + p[1] * x;
+        let options = FitOptions {
+            range: Some((2.0, 6.0)),
+            ..Default::default()
+        };
+        let result = graph.fit_with(line, &[0.0, 0.0], &options).unwrap();
+        assert_eq!((result.n_points, result.range), (5, (2.0, 6.0)));
+        graph.add_function(result.to_tf1("line"));
+        let tf1 = graph.functions().next().unwrap().as_function().unwrap();
+        let expected = result.parameters[0] + result.parameters[1] * 4.0;
+        assert!((tf1.eval(4.0).unwrap() - expected).abs() < 1e-9);
+
+        assert!(graph.fit(&Model::Pol(12), &FitOptions::default()).is_err());
+        let likelihood = FitOptions {
+            method: FitMethod::Likelihood,
+            ..Default::default()
+        };
+        assert!(graph.fit(&Model::Expo, &likelihood).is_err());
+    }
+
+    #[test]
+    fn test_fit_graph_errors() {
+        let x: Vec<f64> = (0..10).map(|i| i as f64).collect();
+        let mut graph = TGraphErrors {
+            f_ey: vec![1.0; 10],
+            f_ex: vec![0.0; 10],
+            ..Default::default()
+        };
+        graph.graph.f_y = x
+            .iter()
+            .enumerate()
+            .map(|(i, x)| 2.0 * x + 1.0 + if i % 2 == 0 { 0.5 } else { -0.5 })
+            .collect();
+        graph.graph.f_x = x;
+        graph.graph.f_npoints = 10;
+        let line = |x: f64, p: &[f64]| p[0] + p[1] * x;
+        let without = graph
+            .fit_with(line, &[0.0, 0.0], &FitOptions::default())
+            .unwrap();
+        assert_eq!(without.range, (0.0, 9.0));
+
+        // x errors of 0.5 on a slope near 2 about double the variance of every point
+        graph.f_ex = vec![0.5; 10];
+        let with = graph
+            .fit_with(line, &[0.0, 0.0], &FitOptions::default())
+            .unwrap();
+        assert!((with.parameters[1] - without.parameters[1]).abs() < 1e-6);
+        let variance = 1.0 + (0.5 * with.parameters[1]).powi(2);
+        assert!((with.chi2 - without.chi2 / variance).abs() < 1e-6);
+        assert!((with.errors[1] / without.errors[1] - variance.sqrt()).abs() < 1e-6);
+        assert_eq!(with.range, (-0.5, 9.5));
+        let model = graph.fit(&Model::Pol(1), &FitOptions::default()).unwrap();
+        assert!((model.chi2 - with.chi2).abs() < 1e-6);
+    }
+}
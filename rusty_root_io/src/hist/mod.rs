@@ -0,0 +1,8 @@
+pub mod arithmetic;
+pub mod compare;
+pub mod cutflow;
+pub mod fit;
+pub mod normalisation;
+pub mod rebin;
+pub mod stats;
+pub mod systematics;
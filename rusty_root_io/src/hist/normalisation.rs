@@ -0,0 +1,323 @@
+use crate::file::tfile::TFile;
+use crate::objects::any_object::AnyObject;
+use crate::objects::th1::TH1;
+use std::collections::HashMap;
+use std::io;
+use std::path::Path;
+
+/*
+ * Luminosity and cross-section normalisation
+ *
+ * A simulated sample is scaled to the data by xsec * lumi / sumw: its cross-section (times
+ * k-factor and filter efficiency) and the integrated luminosity, over the sum of the weights of
+ * every generated event, which the job writes to each file. Cross-section and luminosity must
+ * come in matching units, e.g. pb and pb^-1.
+ *
+ * Samples are identified by their DSID, the six-digit dataset number in the name of the file,
+ * e.g. 700590 in user.holau.700590.Sh_2212_llvvjj_ss.e8433_s3681_r13167_r13146_p6697...
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CrossSection {
+    pub xsec: f64,
+    pub k_factor: f64,
+    pub filter_efficiency: f64,
+}
+
+impl CrossSection {
+    pub fn new(xsec: f64) -> Self {
+        Self {
+            xsec,
+            k_factor: 1.0,
+            filter_efficiency: 1.0,
+        }
+    }
+
+    pub fn effective(&self) -> f64 {
+        self.xsec * self.k_factor * self.filter_efficiency
+    }
+}
+
+// Cross-sections by DSID
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct CrossSectionTable {
+    pub samples: HashMap<u32, CrossSection>,
+}
+
+// Column and key names, case-insensitive
+const DSID: &[&str] = &["dsid", "datasetnumber", "dataset_number"];
+const XSEC: &[&str] = &["xsec", "cross_section", "crosssection"];
+const K_FACTOR: &[&str] = &["kfactor", "k_factor"];
+const FILTER_EFFICIENCY: &[&str] = &["filter_eff", "filter_efficiency", "filtereff", "genfilteff"];
+
+impl CrossSectionTable {
+    pub fn get(&self, dsid: u32) -> Option<&CrossSection> {
+        self.samples.get(&dsid)
+    }
+
+    /*
+     * A CSV file with a header, e.g.
+     *     dsid,xsec,kfactor,filter_eff
+     *     700590,0.0276,1.0,1.0
+     * The k-factor and filter efficiency columns are optional, other columns are ignored.
+     */
+    pub fn from_csv(text: &str) -> io::Result<Self> {
+        let mut reader = csv::ReaderBuilder::new()
+            .trim(csv::Trim::All)
+            .comment(Some(b'#'))
+            .from_reader(text.as_bytes());
+        let header = reader.headers().map_err(io::Error::other)?.clone();
+        let column = |names: &[&str]| {
+            header
+                .iter()
+                .position(|column| names.contains(&column.to_lowercase().as_str()))
+        };
+        let (Some(dsid), Some(xsec)) = (column(DSID), column(XSEC)) else {
+            return Err(invalid(String::from(
+                "the cross-section table has no dsid or xsec column",
+            )));
+        };
+        let (k_factor, filter_efficiency) = (column(K_FACTOR), column(FILTER_EFFICIENCY));
+
+        let mut table = Self::default();
+        for record in reader.records() {
+            let record = record.map_err(io::Error::other)?;
+            // an empty field is the default, an error without
+            let field = |column: Option<usize>, default: Option<f64>| -> io::Result<f64> {
+                match column.and_then(|column| record.get(column)) {
+                    None | Some("") => default,
+                    Some(value) => value.parse().ok(),
+                }
+                .ok_or_else(|| invalid(format!("invalid number in {:?}", record)))
+            };
+            let dsid = record
+                .get(dsid)
+                .and_then(|dsid| dsid.parse().ok())
+                .ok_or_else(|| invalid(format!("invalid dsid in {:?}", record)))?;
+            let cross_section = CrossSection {
+                xsec: field(Some(xsec), None)?,
+                k_factor: field(k_factor, Some(1.0))?,
+                filter_efficiency: field(filter_efficiency, Some(1.0))?,
+            };
+            table.samples.insert(dsid, cross_section);
+        }
+        Ok(table)
+    }
+
+    /*
+     * A TOML file keyed by DSID, with the cross-section alone or a table
+     *     700590 = 0.0276
+     *     [700591]
+     *     xsec = 0.0181
+     *     kfactor = 1.1
+     */
+    pub fn from_toml(text: &str) -> io::Result<Self> {
+        let document: toml::Table = text
+            .parse()
+            .map_err(|error| invalid(format!("invalid cross-section table: {}", error)))?;
+        let mut table = Self::default();
+        for (key, value) in &document {
+            let dsid = key
+                .parse()
+                .map_err(|_| invalid(format!("invalid dsid {}", key)))?;
+            let cross_section = match value {
+                toml::Value::Table(entry) => toml_cross_section(entry),
+                value => toml_number(value).map(CrossSection::new),
+            }
+            .ok_or_else(|| invalid(format!("invalid cross-section of {}", key)))?;
+            table.samples.insert(dsid, cross_section);
+        }
+        Ok(table)
+    }
+
+    // A .toml file, otherwise CSV
+    pub fn read(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)?;
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("toml") => Self::from_toml(&text),
+            _ => Self::from_csv(&text),
+        }
+    }
+}
+
+fn toml_number(value: &toml::Value) -> Option<f64> {
+    match value {
+        toml::Value::Float(value) => Some(*value),
+        toml::Value::Integer(value) => Some(*value as f64),
+        _ => None,
+    }
+}
+
+fn toml_cross_section(entry: &toml::Table) -> Option<CrossSection> {
+    let value = |names: &[&str], default: Option<f64>| match entry
+        .iter()
+        .find(|(key, _)| names.contains(&key.to_lowercase().as_str()))
+    {
+        None => default,
+        Some((_, value)) => toml_number(value),
+    };
+    Some(CrossSection {
+        xsec: value(XSEC, None)?,
+        k_factor: value(K_FACTOR, Some(1.0))?,
+        filter_efficiency: value(FILTER_EFFICIENCY, Some(1.0))?,
+    })
+}
+
+// The first six-digit field of a dot-separated file name
+pub fn dsid_from_name(name: &str) -> Option<u32> {
+    let name = name.rsplit('/').next().unwrap_or(name);
+    name.split('.')
+        .find(|field| field.len() == 6 && field.bytes().all(|byte| byte.is_ascii_digit()))
+        .and_then(|field| field.parse().ok())
+}
+
+// Where a file keeps its sum of weights
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SumOfWeights {
+    // a bin of a histogram, e.g. bin 2 of a cutflow histogram of the event bookkeeper
+    Bin { histogram: String, bin: usize },
+    // a TParameter of its own key
+    Parameter { name: String },
+    // a TParameter in a TList, e.g. the job metadata
+    ListParameter { list: String, name: String },
+}
+
+impl SumOfWeights {
+    // The name of the key holding the sum of weights
+    pub fn key(&self) -> &str {
+        match self {
+            Self::Bin { histogram, .. } => histogram,
+            Self::Parameter { name } => name,
+            Self::ListParameter { list, .. } => list,
+        }
+    }
+
+    // The sum of weights in the object of the key
+    pub fn from_object(&self, object: &AnyObject) -> io::Result<f64> {
+        let sum_of_weights = match self {
+            Self::Bin { bin, .. } => object.as_th1().map(|th1| th1.bin_content(*bin)),
+            Self::Parameter { .. } => object.parameter(),
+            Self::ListParameter { name, .. } => {
+                object.as_list().and_then(|list| list.parameter(name))
+            }
+        };
+        sum_of_weights.ok_or_else(|| {
+            invalid(format!(
+                "{} {} holds no sum of weights for {:?}",
+                object.class_name(),
+                object.name(),
+                self
+            ))
+        })
+    }
+
+    pub fn read(&self, file: &mut TFile) -> io::Result<f64> {
+        let object = file.get(self.key()).map_err(io::Error::other)?;
+        self.from_object(&object)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Normalisation {
+    pub luminosity: f64,
+    pub sum_of_weights: SumOfWeights,
+    pub cross_sections: CrossSectionTable,
+}
+
+impl Normalisation {
+    // xsec * lumi / sumw of a sample
+    pub fn scale_factor_of(&self, dsid: u32, sum_of_weights: f64) -> io::Result<f64> {
+        let cross_section = self
+            .cross_sections
+            .get(dsid)
+            .ok_or_else(|| invalid(format!("no cross-section for DSID {}", dsid)))?;
+        if sum_of_weights == 0.0 || !sum_of_weights.is_finite() {
+            return Err(invalid(format!(
+                "invalid sum of weights {} for DSID {}",
+                sum_of_weights, dsid
+            )));
+        }
+        Ok(cross_section.effective() * self.luminosity / sum_of_weights)
+    }
+
+    // The scale factor of a file, its DSID taken from the name it was created with
+    pub fn scale_factor(&self, file: &mut TFile) -> io::Result<f64> {
+        let dsid = dsid_from_name(file.name())
+            .ok_or_else(|| invalid(format!("no DSID in the file name {}", file.name())))?;
+        let sum_of_weights = self.sum_of_weights.read(file)?;
+        self.scale_factor_of(dsid, sum_of_weights)
+    }
+
+    // Scales a histogram of the file and returns the scale factor
+    pub fn normalise(&self, file: &mut TFile, th1: &mut TH1) -> io::Result<f64> {
+        let scale_factor = self.scale_factor(file)?;
+        th1.scale(scale_factor);
+        Ok(scale_factor)
+    }
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cross_section_tables() {
+        let csv = "# sample cross-sections in pb\n\
+                   DSID, xsec, kfactor, filter_eff, name\n\
+                   700590, 0.5, 1.2, 0.5,\"Sh_2212_llvvjj_ss, EW\"\n\
+                   700591, 2.0, , , Sh_2212_llvvjj_os\n";
+        let table = CrossSectionTable::from_csv(csv).unwrap();
+        assert_eq!(table.samples.len(), 2);
+        assert_eq!(table.get(700590).unwrap().effective(), 0.3);
+        assert_eq!(table.get(700591), Some(&CrossSection::new(2.0)));
+        assert!(CrossSectionTable::from_csv("dsid,name\n700590,a\n").is_err());
+        assert!(CrossSectionTable::from_csv("dsid,xsec\n700590,a\n").is_err());
+        assert!(CrossSectionTable::from_csv("dsid,xsec\n700590,\n").is_err());
+
+        let toml = "700590 = 2\n\n[700591]\nxsec = 0.5\nKFactor = 2.0\n";
+        let table = CrossSectionTable::from_toml(toml).unwrap();
+        assert_eq!(table.get(700590), Some(&CrossSection::new(2.0)));
+        assert_eq!(table.get(700591).unwrap().effective(), 1.0);
+        assert!(CrossSectionTable::from_toml("[700590]\nkfactor = 1.0\n").is_err());
+
+        assert_eq!(
+            dsid_from_name("user.holau.700590.Sh_2212_llvvjj_ss.e8433_s3681_r13167_r13146_p6697.46550259._000001.output.root"),
+            Some(700590)
+        );
+        assert_eq!(dsid_from_name("/data/mc20_13TeV.364100.root"), Some(364100));
+        assert_eq!(dsid_from_name("data18_13TeV.00348885.root"), None);
+    }
+
+    #[test]
+    fn test_scale_factor() {
+        let mut cross_sections = CrossSectionTable::default();
+        cross_sections
+            .samples
+            .insert(700590, CrossSection::new(2.0));
+        let normalisation = Normalisation {
+            luminosity: 140.0,
+            sum_of_weights: SumOfWeights::Bin {
+                histogram: String::from("CutBookkeeper"),
+                bin: 2,
+            },
+            cross_sections,
+        };
+        assert_eq!(normalisation.scale_factor_of(700590, 560.0).unwrap(), 0.5);
+        assert!(normalisation.scale_factor_of(700591, 560.0).is_err());
+        assert!(normalisation.scale_factor_of(700590, 0.0).is_err());
+
+        let mut bookkeeper = TH1::new("CutBookkeeper", "", 3, 0.0, 3.0);
+        bookkeeper.set_bin_content(2, 560.0);
+        let object = AnyObject::TH1(Box::new(bookkeeper));
+        let sum_of_weights = normalisation.sum_of_weights.from_object(&object).unwrap();
+        assert_eq!(sum_of_weights, 560.0);
+        let parameter = SumOfWeights::Parameter {
+            name: String::from("sumOfWeights"),
+        };
+        assert!(parameter.from_object(&object).is_err());
+    }
+}
@@ -0,0 +1,536 @@
+use crate::objects::tarray::TArrayD;
+use crate::objects::taxis::TAxis;
+use crate::objects::th1::{TH1, TH2, TH3};
+use crate::objects::tprofile::TProfile;
+use std::io;
+use std::ops::RangeInclusive;
+
+/*
+ * Rebinning, slicing, projections and profiles, as TH1::Rebin, TH2::ProjectionX, TH2::ProfileX...
+ * https://root.cern/doc/v638/classTH1.html
+ *
+ * Every cell of the source goes to one cell of the result, so contents and sums of squared weights
+ * are only added up and the errors stay those of the source. Bins that fall out of the new range
+ * go to the underflow or the overflow of their axis, like the bins left over by TH1::Rebin when n
+ * does not divide the number of bins. fEntries is kept; the stat sums too, unless bins left the
+ * range, in which case they are computed from the bin contents.
+ */
+impl TH1 {
+    // TH1::Rebin(n): merge n neighbouring bins of the x axis into one
+    pub fn rebin(&self, n: usize) -> io::Result<TH1> {
+        let maps = [
+            merged_axis(&self.f_xaxis, n)?,
+            AxisMap::identity(&self.f_yaxis),
+            AxisMap::identity(&self.f_zaxis),
+        ];
+        Ok(self.remap_with_stats(&maps))
+    }
+
+    // TH1::Rebin(n, name, edges): merge the x bins into the bins of edges, which must be edges of
+    // the x axis too
+    pub fn rebin_to(&self, edges: &[f64]) -> io::Result<TH1> {
+        let maps = [
+            edges_axis(&self.f_xaxis, edges)?,
+            AxisMap::identity(&self.f_yaxis),
+            AxisMap::identity(&self.f_zaxis),
+        ];
+        Ok(self.remap_with_stats(&maps))
+    }
+
+    // The x bins of the range, the bins before and after it go to underflow and overflow
+    pub fn slice(&self, bins: RangeInclusive<usize>) -> io::Result<TH1> {
+        let maps = [
+            range_axis(&self.f_xaxis, bins)?,
+            AxisMap::identity(&self.f_yaxis),
+            AxisMap::identity(&self.f_zaxis),
+        ];
+        Ok(self.remap_with_stats(&maps))
+    }
+
+    fn remap_with_stats(&self, maps: &[AxisMap; 3]) -> TH1 {
+        let mut remapped = remap(self, maps);
+        if drops_bins(maps, self.dimension()) {
+            let stats = remapped.bin_stats();
+            remapped.f_tsumw = stats[0];
+            remapped.f_tsumw2 = stats[1];
+            remapped.f_tsumwx = stats[2];
+            remapped.f_tsumwx2 = stats[3];
+        }
+        remapped
+    }
+}
+
+impl TH2 {
+    // TH2::Rebin2D(nx, ny)
+    pub fn rebin(&self, nx: usize, ny: usize) -> io::Result<TH2> {
+        let maps = [
+            merged_axis(&self.th1.f_xaxis, nx)?,
+            merged_axis(&self.th1.f_yaxis, ny)?,
+            AxisMap::identity(&self.th1.f_zaxis),
+        ];
+        Ok(self.remap_with_stats(&maps))
+    }
+
+    // The bins of both ranges, the others go to the underflows and overflows
+    pub fn slice(
+        &self,
+        xbins: RangeInclusive<usize>,
+        ybins: RangeInclusive<usize>,
+    ) -> io::Result<TH2> {
+        let maps = [
+            range_axis(&self.th1.f_xaxis, xbins)?,
+            range_axis(&self.th1.f_yaxis, ybins)?,
+            AxisMap::identity(&self.th1.f_zaxis),
+        ];
+        Ok(self.remap_with_stats(&maps))
+    }
+
+    // TH2::ProjectionX(name_px, firstybin, lastybin): the sum over the y bins of the range, which
+    // may include the underflow 0 and the overflow nbins_y + 1
+    pub fn project_x(&self, ybins: RangeInclusive<usize>) -> TH1 {
+        project(&self.th1, 0, [0..=0, ybins, 0..=0], "_px")
+    }
+
+    // TH2::ProjectionY(name_py, firstxbin, lastxbin)
+    pub fn project_y(&self, xbins: RangeInclusive<usize>) -> TH1 {
+        project(&self.th1, 1, [xbins, 0..=0, 0..=0], "_py")
+    }
+
+    /*
+     * TH2::ProfileX(name_pfx, firstybin, lastybin): the mean of y in each x bin, over the y bins of
+     * the range. Each cell counts as a fill at the center of its y bin with its content as weight
+     * and its sum of squared weights, so the profile of a weighted histogram keeps fBinSumw2.
+     */
+    pub fn profile_x(&self, ybins: RangeInclusive<usize>) -> TProfile {
+        let th1 = &self.th1;
+        let mut profile = TProfile::with_axis(
+            &format!("{}_pfx", th1.name()),
+            th1.title(),
+            th1.f_xaxis.clone(),
+        );
+        let ybins = clamp_range(ybins, self.nbins_y());
+        for ix in 0..th1.nbins_x() + 2 {
+            let x = th1.f_xaxis.bin_center(ix);
+            for iy in ybins.clone() {
+                let cell = self.bin(ix, iy);
+                let (w, w2) = (th1.bin_content(cell), th1.bin_sumw2(cell));
+                if w == 0.0 && w2 == 0.0 {
+                    continue;
+                }
+                let y = th1.f_yaxis.bin_center(iy);
+                profile.fill_bin(ix, y, w, w2);
+                if (1..=th1.nbins_x()).contains(&ix) {
+                    profile.th1.f_tsumw += w;
+                    profile.th1.f_tsumw2 += w2;
+                    profile.th1.f_tsumwx += w * x;
+                    profile.th1.f_tsumwx2 += w * x * x;
+                    profile.f_tsumwy += w * y;
+                    profile.f_tsumwy2 += w * y * y;
+                }
+            }
+        }
+        profile.th1.f_entries = if covers(&ybins, self.nbins_y()) {
+            th1.f_entries
+        } else if profile.th1.f_tsumw2 > 0.0 {
+            profile.th1.f_tsumw * profile.th1.f_tsumw / profile.th1.f_tsumw2
+        } else {
+            0.0
+        };
+        profile
+    }
+
+    fn remap_with_stats(&self, maps: &[AxisMap; 3]) -> TH2 {
+        let mut remapped = TH2 {
+            byte_count: self.byte_count,
+            version: self.version,
+            th1: self.th1.remap_with_stats(maps),
+            f_scalefactor: self.f_scalefactor,
+            f_tsumwy: self.f_tsumwy,
+            f_tsumwy2: self.f_tsumwy2,
+            f_tsumwxy: self.f_tsumwxy,
+        };
+        if drops_bins(maps, 2) {
+            let stats = remapped.th1.bin_stats();
+            remapped.f_tsumwy = stats[4];
+            remapped.f_tsumwy2 = stats[5];
+            remapped.f_tsumwxy = stats[6];
+        }
+        remapped
+    }
+}
+
+// TH3::ProjectionX, ProjectionY and ProjectionZ, the sums over the bin ranges of the other axes
+impl TH3 {
+    pub fn project_x(&self, ybins: RangeInclusive<usize>, zbins: RangeInclusive<usize>) -> TH1 {
+        project(&self.th1, 0, [0..=0, ybins, zbins], "_px")
+    }
+
+    pub fn project_y(&self, xbins: RangeInclusive<usize>, zbins: RangeInclusive<usize>) -> TH1 {
+        project(&self.th1, 1, [xbins, 0..=0, zbins], "_py")
+    }
+
+    pub fn project_z(&self, xbins: RangeInclusive<usize>, ybins: RangeInclusive<usize>) -> TH1 {
+        project(&self.th1, 2, [xbins, ybins, 0..=0], "_pz")
+    }
+}
+
+impl TProfile {
+    // TProfile::Rebin(n): the sums of the merged bins are added, the stat sums are kept
+    pub fn rebin(&self, n: usize) -> io::Result<TProfile> {
+        let th1 = &self.th1;
+        let maps = [
+            merged_axis(&th1.f_xaxis, n)?,
+            AxisMap::identity(&th1.f_yaxis),
+            AxisMap::identity(&th1.f_zaxis),
+        ];
+        let remap_array = |array: &TArrayD| {
+            if array.is_empty() {
+                return TArrayD::default();
+            }
+            let cells = remap_cells(&array.f_array, th1, &maps);
+            TArrayD {
+                f_n: cells.len() as i32,
+                f_array: cells,
+            }
+        };
+        Ok(TProfile {
+            th1: remap(th1, &maps),
+            f_bin_entries: remap_array(&self.f_bin_entries),
+            f_bin_sumw2: remap_array(&self.f_bin_sumw2),
+            ..self.clone()
+        })
+    }
+}
+
+// The new axis and the new bin of every bin of the old one, underflow and overflow included
+struct AxisMap {
+    axis: TAxis,
+    bins: Vec<usize>,
+}
+
+impl AxisMap {
+    fn identity(axis: &TAxis) -> Self {
+        Self {
+            axis: axis.clone(),
+            bins: (0..axis.nbins() + 2).collect(),
+        }
+    }
+
+    // The bins before first_bin and after last_bin of the old axis go to underflow and overflow
+    fn from_fn(axis: TAxis, old_nbins: usize, new_bin: impl Fn(usize) -> usize) -> Self {
+        let overflow = axis.nbins() + 1;
+        let mut bins = vec![0];
+        bins.extend((1..=old_nbins).map(|bin| new_bin(bin).min(overflow)));
+        bins.push(overflow);
+        Self { axis, bins }
+    }
+}
+
+// Every n bins in one, the last nbins % n bins in the overflow. Like TH1::Rebin, a merged bin
+// gets the label of its first bin.
+fn merged_axis(axis: &TAxis, n: usize) -> io::Result<AxisMap> {
+    let nbins = axis.nbins().checked_div(n).unwrap_or(0);
+    if nbins == 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("cannot merge {} bins of {} into one", n, axis.nbins()),
+        ));
+    }
+    let edges: Vec<f64> = (0..=nbins)
+        .map(|bin| axis.bin_low_edge(1 + bin * n))
+        .collect();
+    let mut new_axis = sub_axis(axis, &edges, axis.is_variable_binning());
+    for (bin, label) in axis.labels() {
+        if bin <= nbins * n && (bin - 1) % n == 0 {
+            new_axis.set_bin_label((bin - 1) / n + 1, label);
+        }
+    }
+    Ok(AxisMap::from_fn(new_axis, axis.nbins(), |bin| {
+        (bin - 1) / n + 1
+    }))
+}
+
+// The bins between the given edges, each of which must be an edge of the axis, with the labels of
+// their first bins
+fn edges_axis(axis: &TAxis, edges: &[f64]) -> io::Result<AxisMap> {
+    let old_edges = axis.edges();
+    let close = |a: f64, b: f64| (a - b).abs() <= 1e-10 * a.abs().max(b.abs()).max(1.0);
+    // index of each new edge among the old ones
+    let positions: Option<Vec<usize>> = edges
+        .iter()
+        .map(|edge| old_edges.iter().position(|old| close(*old, *edge)))
+        .collect();
+    let positions = match positions {
+        Some(positions) if positions.len() >= 2 && positions.windows(2).all(|w| w[0] < w[1]) => {
+            positions
+        }
+        _ => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "the edges {:?} are not increasing edges of the axis {:?}",
+                    edges, old_edges
+                ),
+            ))
+        }
+    };
+    let mut new_axis = sub_axis(axis, edges, true);
+    for (bin, label) in axis.labels() {
+        // the old bin starts at old edge bin - 1
+        if let Some(new_bin) = positions[..positions.len() - 1]
+            .iter()
+            .position(|position| *position == bin - 1)
+        {
+            new_axis.set_bin_label(new_bin + 1, label);
+        }
+    }
+    Ok(AxisMap::from_fn(new_axis, axis.nbins(), |bin| {
+        // the old bin starts at old edge bin - 1
+        match positions.partition_point(|position| *position < bin) {
+            0 => 0,
+            new_bin if new_bin == positions.len() => usize::MAX,
+            new_bin => new_bin,
+        }
+    }))
+}
+
+// The bins of the range, which must lie within 1..=nbins
+fn range_axis(axis: &TAxis, bins: RangeInclusive<usize>) -> io::Result<AxisMap> {
+    let (first, last) = (*bins.start(), *bins.end());
+    if first < 1 || last > axis.nbins() || first > last {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "the bin range {}..={} is not within 1..={}",
+                first,
+                last,
+                axis.nbins()
+            ),
+        ));
+    }
+    let edges: Vec<f64> = (first..=last + 1)
+        .map(|bin| axis.bin_low_edge(bin))
+        .collect();
+    let mut new_axis = sub_axis(axis, &edges, axis.is_variable_binning());
+    // the bins are kept one to one, and so are their labels
+    for (bin, label) in axis.labels() {
+        if bins.contains(&bin) {
+            new_axis.set_bin_label(bin - first + 1, label);
+        }
+    }
+    Ok(AxisMap::from_fn(new_axis, axis.nbins(), |bin| {
+        if bin < first {
+            0
+        } else {
+            bin - first + 1
+        }
+    }))
+}
+
+// The axis with new edges and without the range and labels of its old bins
+fn sub_axis(axis: &TAxis, edges: &[f64], variable: bool) -> TAxis {
+    let mut new_axis = axis.clone();
+    new_axis.f_nbins = edges.len() as i32 - 1;
+    new_axis.f_xmin = edges[0];
+    new_axis.f_xmax = edges[edges.len() - 1];
+    new_axis.f_xbins = if variable {
+        TArrayD {
+            f_n: edges.len() as i32,
+            f_array: edges.to_vec(),
+        }
+    } else {
+        TArrayD::default()
+    };
+    new_axis.f_first = 0;
+    new_axis.f_last = 0;
+    new_axis.f_labels = None;
+    new_axis.f_mod_labs = None;
+    new_axis
+}
+
+// Whether a bin in range on one of the axes of the histogram goes to an underflow or overflow
+fn drops_bins(maps: &[AxisMap; 3], dimension: usize) -> bool {
+    maps.iter().take(dimension).any(|map| {
+        let overflow = map.axis.nbins() + 1;
+        map.bins[1..map.bins.len() - 1]
+            .iter()
+            .any(|bin| *bin == 0 || *bin == overflow)
+    })
+}
+
+// The histogram on the new axes, with the contents and squared weights of the old cells added up
+fn remap(th1: &TH1, maps: &[AxisMap; 3]) -> TH1 {
+    let mut remapped = th1.clone();
+    remapped.f_array = remap_cells(&th1.f_array, th1, maps);
+    if th1.has_sumw2() {
+        let sumw2 = remap_cells(&th1.f_sumw2.f_array, th1, maps);
+        remapped.f_sumw2 = TArrayD {
+            f_n: sumw2.len() as i32,
+            f_array: sumw2,
+        };
+    }
+    remapped.f_ncells = remapped.f_array.len() as i32;
+    [remapped.f_xaxis, remapped.f_yaxis, remapped.f_zaxis] =
+        [0, 1, 2].map(|i| maps[i].axis.clone());
+    remapped.f_maximum = -1111.0;
+    remapped.f_minimum = -1111.0;
+    remapped
+}
+
+// Values per cell of th1, summed into the cells of the new axes
+fn remap_cells(values: &[f64], th1: &TH1, maps: &[AxisMap; 3]) -> Vec<f64> {
+    let dimension = th1.dimension();
+    let (nx, ny) = (th1.nbins_x() + 2, th1.f_yaxis.nbins() + 2);
+    let (new_nx, new_ny) = (maps[0].axis.nbins() + 2, maps[1].axis.nbins() + 2);
+    let ncells: usize = maps
+        .iter()
+        .take(dimension)
+        .map(|map| map.axis.nbins() + 2)
+        .product();
+    let mut cells = vec![0.0; ncells];
+    for (cell, value) in values.iter().enumerate() {
+        let (ix, iy, iz) = (cell % nx, (cell / nx) % ny, cell / nx / ny);
+        let new_cell = maps[0].bins[ix] + new_nx * (maps[1].bins[iy] + new_ny * maps[2].bins[iz]);
+        cells[new_cell] += value;
+    }
+    cells
+}
+
+/*
+ * The one dimensional histogram along an axis, summing the cells whose bins on the other axes are
+ * in their ranges. The number of entries is kept when the ranges cover all the bins of the other
+ * axes, otherwise it is the effective number of entries of the projection.
+ */
+fn project(th1: &TH1, axis: usize, ranges: [RangeInclusive<usize>; 3], suffix: &str) -> TH1 {
+    let axes = [&th1.f_xaxis, &th1.f_yaxis, &th1.f_zaxis];
+    let dimension = th1.dimension();
+    let others: Vec<usize> = (0..dimension).filter(|other| *other != axis).collect();
+    let ranges = [0, 1, 2].map(|i| clamp_range(ranges[i].clone(), axes[i].nbins()));
+    let mut projection = TH1::with_axes(
+        &format!("{}{}", th1.name(), suffix),
+        th1.title(),
+        axes[axis].clone(),
+    );
+    projection.bin_type = th1.bin_type;
+    if th1.has_sumw2() {
+        projection.sumw2();
+    }
+    let (nx, ny) = (th1.nbins_x() + 2, th1.f_yaxis.nbins() + 2);
+    for cell in 0..th1.f_array.len() {
+        let bins = [cell % nx, (cell / nx) % ny, cell / nx / ny];
+        if !others.iter().all(|i| ranges[*i].contains(&bins[*i])) {
+            continue;
+        }
+        projection.f_array[bins[axis]] += th1.f_array[cell];
+        if let Some(sumw2) = projection.f_sumw2.f_array.get_mut(bins[axis]) {
+            *sumw2 += th1.bin_sumw2(cell);
+        }
+    }
+    projection.reset_stats();
+    if others.iter().all(|i| covers(&ranges[*i], axes[*i].nbins())) {
+        projection.f_entries = th1.f_entries;
+    }
+    projection
+}
+
+fn clamp_range(range: RangeInclusive<usize>, nbins: usize) -> RangeInclusive<usize> {
+    *range.start()..=(*range.end()).min(nbins + 1)
+}
+
+// Whether the range includes all the bins, the underflow and overflow aside
+fn covers(range: &RangeInclusive<usize>, nbins: usize) -> bool {
+    range.contains(&1) && range.contains(&nbins)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rebin_and_slice() {
+        let mut th1 = TH1::new("h", "", 5, 0.0, 5.0);
+        for x in [-1.0, 0.5, 1.5, 1.5, 2.5, 4.5] {
+            th1.fill(x, 1.0);
+        }
+        let rebinned = th1.rebin(2).unwrap();
+        assert_eq!(rebinned.x_axis().edges(), [0.0, 2.0, 4.0]);
+        assert!(!rebinned.x_axis().is_variable_binning());
+        // the fifth bin is left over and goes to the overflow
+        assert_eq!(rebinned.contents(), [1.0, 3.0, 1.0, 1.0]);
+        assert_eq!((rebinned.entries(), rebinned.f_tsumw), (6.0, 4.0));
+        assert!(th1.rebin(6).is_err());
+
+        th1.set_bin_error(2, 3.0);
+        let merged = th1.rebin_to(&[1.0, 2.0, 5.0]).unwrap();
+        assert_eq!(merged.contents(), [2.0, 2.0, 2.0, 0.0]);
+        assert_eq!(merged.bin_error(1), 3.0);
+        assert_eq!(merged.bin_error(0), 2f64.sqrt());
+        assert!(th1.rebin_to(&[1.0, 2.5]).is_err());
+
+        let sliced = th1.slice(2..=3).unwrap();
+        assert_eq!(sliced.x_axis().edges(), [1.0, 2.0, 3.0]);
+        assert_eq!(sliced.contents(), [2.0, 2.0, 1.0, 1.0]);
+        assert!(th1.slice(0..=3).is_err());
+
+        // the labels of the sliced bins are kept, merged bins get the label of their first bin
+        for (bin, label) in ["all", "trigger", "lepton", "jets", "met"]
+            .iter()
+            .enumerate()
+        {
+            th1.f_xaxis.set_bin_label(bin + 1, label);
+        }
+        let sliced = th1.slice(2..=4).unwrap();
+        let labels: Vec<(usize, &str)> = sliced.x_axis().labels().collect();
+        assert_eq!(labels, [(1, "trigger"), (2, "lepton"), (3, "jets")]);
+        let rebinned = th1.rebin(2).unwrap();
+        let labels: Vec<(usize, &str)> = rebinned.x_axis().labels().collect();
+        assert_eq!(labels, [(1, "all"), (2, "lepton")]);
+        let merged = th1.rebin_to(&[1.0, 2.0, 5.0]).unwrap();
+        let labels: Vec<(usize, &str)> = merged.x_axis().labels().collect();
+        assert_eq!(labels, [(1, "trigger"), (2, "lepton")]);
+    }
+
+    #[test]
+    fn test_project_and_profile() {
+        let mut th2 = TH2::new("h2", "", 2, 0.0, 2.0, 3, 0.0, 3.0);
+        for (x, y, w) in [
+            (0.5, 0.5, 1.0),
+            (0.5, 2.5, 2.0),
+            (1.5, 1.5, 1.0),
+            (1.5, 5.0, 1.0),
+        ] {
+            th2.fill(x, y, w);
+        }
+        let px = th2.project_x(1..=3);
+        assert_eq!(px.name(), "h2_px");
+        assert_eq!(px.contents(), [0.0, 3.0, 1.0, 0.0]);
+        assert_eq!(px.bin_error(1), 5f64.sqrt());
+        assert_eq!(px.entries(), 4.0);
+        let py = th2.project_y(2..=2);
+        assert_eq!(py.contents(), [0.0, 0.0, 1.0, 0.0, 1.0]);
+
+        let rebinned = th2.rebin(2, 3).unwrap();
+        assert_eq!(rebinned.bin_content_xy(1, 1), 4.0);
+        assert_eq!(rebinned.bin_content_xy(1, 2), 1.0);
+
+        let profile = th2.profile_x(1..=3);
+        assert_eq!(profile.name(), "h2_pfx");
+        assert_eq!(profile.bin_entries(1), 3.0);
+        assert_eq!(profile.bin_content(1), (0.5 + 2.0 * 2.5) / 3.0);
+        assert_eq!(profile.bin_content(2), 1.5);
+        // one weight of 2 out of three
+        assert_eq!(profile.bin_effective_entries(1), 9.0 / 5.0);
+
+        let mut th3 = TH3::with_axes(
+            "h3",
+            "",
+            TAxis::new(2, 0.0, 2.0),
+            TAxis::new(2, 0.0, 2.0),
+            TAxis::new(2, 0.0, 2.0),
+        );
+        th3.fill(0.5, 1.5, 1.5, 1.0);
+        th3.fill(1.5, 1.5, 0.5, 1.0);
+        assert_eq!(th3.project_z(0..=3, 2..=2).contents(), [0.0, 1.0, 1.0, 0.0]);
+        assert_eq!(th3.project_x(0..=3, 2..=2).contents(), [0.0, 1.0, 0.0, 0.0]);
+    }
+}
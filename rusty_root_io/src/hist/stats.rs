@@ -0,0 +1,396 @@
+use crate::objects::th1::{TH1, TH2, TH3};
+use std::ops::RangeInclusive;
+
+// The sums of weights of one axis, from which the statistics of the stat box are computed
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Moments {
+    pub sumw: f64,
+    pub sumw2: f64,
+    pub sumwx: f64,
+    pub sumwx2: f64,
+}
+
+impl Moments {
+    pub fn mean(&self) -> f64 {
+        if self.sumw == 0.0 {
+            return 0.0;
+        }
+        self.sumwx / self.sumw
+    }
+
+    pub fn std_dev(&self) -> f64 {
+        if self.sumw == 0.0 {
+            return 0.0;
+        }
+        let mean = self.mean();
+        (self.sumwx2 / self.sumw - mean * mean).abs().sqrt()
+    }
+
+    // TH1::GetEffectiveEntries: sum(w)^2 / sum(w^2)
+    pub fn effective_entries(&self) -> f64 {
+        if self.sumw2 == 0.0 {
+            return 0.0;
+        }
+        self.sumw * self.sumw / self.sumw2
+    }
+}
+
+/*
+ * Statistics, as TH1::GetMean, TH1::GetStdDev, TH1::GetSkewness, TH1::GetKurtosis...
+ * https://root.cern/doc/v638/classTH1.html
+ *
+ * The axis is 1 for x, 2 for y and 3 for z. Like TH1::GetStats, the moments come from the stat
+ * sums filled with the histogram (fTsumw, fTsumwx, ...), which keep the exact values of the filled
+ * x, unless they are empty while the histogram has entries, as when the bins were set one by one.
+ * bin_moments computes them from the bin centers instead. Only the bins in range count, underflow
+ * and overflow excluded. A TH1 has the stat sums of x only, TH2 and TH3 add those of y and z.
+ */
+impl TH1 {
+    pub fn moments(&self, axis: usize) -> Moments {
+        if axis != 1 || (self.f_tsumw == 0.0 && self.f_entries > 0.0) {
+            return self.bin_moments(axis);
+        }
+        Moments {
+            sumw: self.f_tsumw,
+            sumw2: self.f_tsumw2,
+            sumwx: self.f_tsumwx,
+            sumwx2: self.f_tsumwx2,
+        }
+    }
+
+    // The moments from the bin contents at the bin centers
+    pub fn bin_moments(&self, axis: usize) -> Moments {
+        let mut moments = Moments::default();
+        for cell in self.cells_in_range() {
+            let w = self.f_array[cell];
+            let x = self.cell_center(cell, axis);
+            moments.sumw += w;
+            moments.sumw2 += self.bin_sumw2(cell);
+            moments.sumwx += w * x;
+            moments.sumwx2 += w * x * x;
+        }
+        moments
+    }
+
+    pub fn mean(&self, axis: usize) -> f64 {
+        self.moments(axis).mean()
+    }
+
+    pub fn std_dev(&self, axis: usize) -> f64 {
+        self.moments(axis).std_dev()
+    }
+
+    pub fn skewness(&self, axis: usize) -> f64 {
+        self.skewness_with(&self.moments(axis), axis)
+    }
+
+    pub fn kurtosis(&self, axis: usize) -> f64 {
+        self.kurtosis_with(&self.moments(axis), axis)
+    }
+
+    // The third standardised moment of the bins, about the mean and std dev of the moments
+    pub fn skewness_with(&self, moments: &Moments, axis: usize) -> f64 {
+        self.standardised_moment(moments, axis, 3)
+    }
+
+    // The excess kurtosis of the bins, about the mean and std dev of the moments
+    pub fn kurtosis_with(&self, moments: &Moments, axis: usize) -> f64 {
+        self.standardised_moment(moments, axis, 4) - 3.0
+    }
+
+    // TH1::GetEffectiveEntries, the number of unweighted entries with the same statistical power
+    pub fn effective_entries(&self) -> f64 {
+        self.moments(1).effective_entries()
+    }
+
+    // TH1::Integral(binx1, binx2, option): the sum of the x bins of the range, which may include
+    // the underflow 0 and overflow nbins + 1, times the bin widths with width
+    pub fn integral_in(&self, bins: RangeInclusive<usize>, width: bool) -> f64 {
+        self.integral_cells([bins, 1..=1, 1..=1], width)
+    }
+
+    /*
+     * TH1::GetQuantiles: the x below which each fraction of the integral lies, interpolated
+     * linearly inside the bins. None when the histogram is empty or has negative bins.
+     */
+    pub fn quantiles(&self, probabilities: &[f64]) -> Option<Vec<f64>> {
+        let nbins = self.nbins_x();
+        let mut cumulative = vec![0.0; nbins + 1];
+        for bin in 1..=nbins {
+            let content = self.bin_content(bin);
+            if content < 0.0 {
+                return None;
+            }
+            cumulative[bin] = cumulative[bin - 1] + content;
+        }
+        let total = cumulative[nbins];
+        if total <= 0.0 {
+            return None;
+        }
+        for sum in cumulative.iter_mut() {
+            *sum /= total;
+        }
+        let axis = &self.f_xaxis;
+        let quantiles = probabilities
+            .iter()
+            .map(|p| {
+                // last of the first nbins sums not above p, skipping empty bins at p
+                let mut bin = cumulative[..nbins]
+                    .partition_point(|sum| sum <= p)
+                    .saturating_sub(1);
+                while bin + 2 <= nbins && cumulative[bin + 1] == *p && cumulative[bin + 2] == *p {
+                    bin += 1;
+                }
+                let step = cumulative[bin + 1] - cumulative[bin];
+                let mut x = axis.bin_low_edge(bin + 1);
+                if step > 0.0 {
+                    x += axis.bin_width(bin + 1) * (p - cumulative[bin]) / step;
+                }
+                x
+            })
+            .collect();
+        Some(quantiles)
+    }
+
+    // TH1::FindFirstBinAbove: the first bin of the axis with a cell above the threshold
+    pub fn find_first_bin_above(&self, threshold: f64, axis: usize) -> Option<usize> {
+        (1..=self.axis_nbins(axis)).find(|bin| self.bin_above(*bin, threshold, axis))
+    }
+
+    // TH1::FindLastBinAbove
+    pub fn find_last_bin_above(&self, threshold: f64, axis: usize) -> Option<usize> {
+        (1..=self.axis_nbins(axis))
+            .rev()
+            .find(|bin| self.bin_above(*bin, threshold, axis))
+    }
+
+    // Sum over the cells whose bins are in the ranges, the axes beyond the dimension ignored
+    fn integral_cells(&self, ranges: [RangeInclusive<usize>; 3], width: bool) -> f64 {
+        let dimension = self.dimension();
+        let axes = [&self.f_xaxis, &self.f_yaxis, &self.f_zaxis];
+        (0..self.f_array.len())
+            .filter_map(|cell| {
+                let bins = self.cell_bins(cell);
+                let inside = (0..dimension).all(|i| ranges[i].contains(&bins[i]));
+                inside.then(|| {
+                    let volume: f64 = if width {
+                        (0..dimension).map(|i| axes[i].bin_width(bins[i])).product()
+                    } else {
+                        1.0
+                    };
+                    self.f_array[cell] * volume
+                })
+            })
+            .sum()
+    }
+
+    fn standardised_moment(&self, moments: &Moments, axis: usize, order: i32) -> f64 {
+        let (mean, std_dev) = (moments.mean(), moments.std_dev());
+        let (mut sumw, mut sum) = (0.0, 0.0);
+        for cell in self.cells_in_range() {
+            let w = self.f_array[cell];
+            sumw += w;
+            sum += w * (self.cell_center(cell, axis) - mean).powi(order);
+        }
+        if sumw == 0.0 || std_dev == 0.0 {
+            return 0.0;
+        }
+        sum / sumw / std_dev.powi(order)
+    }
+
+    fn bin_above(&self, bin: usize, threshold: f64, axis: usize) -> bool {
+        self.cells_in_range()
+            .any(|cell| self.cell_bins(cell)[axis - 1] == bin && self.f_array[cell] > threshold)
+    }
+
+    fn cells_in_range(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.f_array.len()).filter(|cell| self.is_in_range(*cell))
+    }
+
+    // The bins of a cell on the x, y and z axes
+    fn cell_bins(&self, cell: usize) -> [usize; 3] {
+        let nx = self.nbins_x() + 2;
+        let ny = self.f_yaxis.nbins() + 2;
+        [cell % nx, (cell / nx) % ny, cell / nx / ny]
+    }
+
+    fn cell_center(&self, cell: usize, axis: usize) -> f64 {
+        let bin = self.cell_bins(cell)[axis - 1];
+        match axis {
+            1 => self.f_xaxis.bin_center(bin),
+            2 => self.f_yaxis.bin_center(bin),
+            _ => self.f_zaxis.bin_center(bin),
+        }
+    }
+
+    fn axis_nbins(&self, axis: usize) -> usize {
+        match axis {
+            1 => self.f_xaxis.nbins(),
+            2 if self.dimension() >= 2 => self.f_yaxis.nbins(),
+            3 if self.dimension() >= 3 => self.f_zaxis.nbins(),
+            _ => 0,
+        }
+    }
+}
+
+// The y stat sums of a two dimensional histogram
+impl TH2 {
+    pub fn moments(&self, axis: usize) -> Moments {
+        let th1 = &self.th1;
+        if axis != 2 || (th1.f_tsumw == 0.0 && th1.f_entries > 0.0) {
+            return th1.moments(axis);
+        }
+        Moments {
+            sumw: th1.f_tsumw,
+            sumw2: th1.f_tsumw2,
+            sumwx: self.f_tsumwy,
+            sumwx2: self.f_tsumwy2,
+        }
+    }
+
+    pub fn mean(&self, axis: usize) -> f64 {
+        self.moments(axis).mean()
+    }
+
+    pub fn std_dev(&self, axis: usize) -> f64 {
+        self.moments(axis).std_dev()
+    }
+
+    pub fn skewness(&self, axis: usize) -> f64 {
+        self.th1.skewness_with(&self.moments(axis), axis)
+    }
+
+    pub fn kurtosis(&self, axis: usize) -> f64 {
+        self.th1.kurtosis_with(&self.moments(axis), axis)
+    }
+
+    // TH2::Integral(binx1, binx2, biny1, biny2, option)
+    pub fn integral_in(
+        &self,
+        xbins: RangeInclusive<usize>,
+        ybins: RangeInclusive<usize>,
+        width: bool,
+    ) -> f64 {
+        self.th1.integral_cells([xbins, ybins, 1..=1], width)
+    }
+}
+
+// The y and z stat sums of a three dimensional histogram
+impl TH3 {
+    pub fn moments(&self, axis: usize) -> Moments {
+        let th1 = &self.th1;
+        let (sumwx, sumwx2) = match axis {
+            2 => (self.f_tsumwy, self.f_tsumwy2),
+            3 => (self.f_tsumwz, self.f_tsumwz2),
+            _ => return th1.moments(axis),
+        };
+        if th1.f_tsumw == 0.0 && th1.f_entries > 0.0 {
+            return th1.bin_moments(axis);
+        }
+        Moments {
+            sumw: th1.f_tsumw,
+            sumw2: th1.f_tsumw2,
+            sumwx,
+            sumwx2,
+        }
+    }
+
+    pub fn mean(&self, axis: usize) -> f64 {
+        self.moments(axis).mean()
+    }
+
+    pub fn std_dev(&self, axis: usize) -> f64 {
+        self.moments(axis).std_dev()
+    }
+
+    pub fn skewness(&self, axis: usize) -> f64 {
+        self.th1.skewness_with(&self.moments(axis), axis)
+    }
+
+    pub fn kurtosis(&self, axis: usize) -> f64 {
+        self.th1.kurtosis_with(&self.moments(axis), axis)
+    }
+
+    // TH3::Integral(binx1, binx2, biny1, biny2, binz1, binz2, option)
+    pub fn integral_in(
+        &self,
+        xbins: RangeInclusive<usize>,
+        ybins: RangeInclusive<usize>,
+        zbins: RangeInclusive<usize>,
+        width: bool,
+    ) -> f64 {
+        self.th1.integral_cells([xbins, ybins, zbins], width)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_moments() {
+        let mut th1 = TH1::new("h", "", 4, 0.0, 4.0);
+        for (x, w) in [(0.2, 1.0), (1.5, 2.0), (1.5, 1.0), (3.9, 1.0), (7.0, 1.0)] {
+            th1.fill(x, w);
+        }
+        // the filled values, not the bin centers
+        let mean = (0.2 + 3.0 * 1.5 + 3.9) / 5.0;
+        assert!((th1.mean(1) - mean).abs() < 1e-12);
+        let bin_mean = (0.5 + 3.0 * 1.5 + 3.5) / 5.0;
+        assert!((th1.bin_moments(1).mean() - bin_mean).abs() < 1e-12);
+        let variance =
+            (0.5f64.powi(2) + 3.0 * 1.5f64.powi(2) + 3.5f64.powi(2)) / 5.0 - bin_mean * bin_mean;
+        let bin_moments = th1.bin_moments(1);
+        assert!((bin_moments.std_dev() - variance.sqrt()).abs() < 1e-12);
+        let third = ((-1.2f64).powi(3) + 3.0 * (-0.2f64).powi(3) + 1.8f64.powi(3)) / 5.0;
+        let skewness = th1.skewness_with(&bin_moments, 1);
+        assert!((skewness - third / variance.powf(1.5)).abs() < 1e-12);
+        assert!(th1.kurtosis(1) < 0.0);
+        // 5 over 1 + 4 + 1 + 1
+        assert!((th1.effective_entries() - 25.0 / 7.0).abs() < 1e-12);
+
+        // set bin by bin, no stat sums
+        let mut set = TH1::new("set", "", 2, 0.0, 2.0);
+        set.set_bin_content(1, 1.0);
+        set.set_bin_content(2, 3.0);
+        set.f_entries = 4.0;
+        assert_eq!(set.mean(1), 1.25);
+        assert!((set.std_dev(1) - 0.1875f64.sqrt()).abs() < 1e-12);
+
+        let mut th2 = TH2::new("h2", "", 2, 0.0, 2.0, 2, 0.0, 2.0);
+        th2.fill(0.5, 0.25, 1.0);
+        th2.fill(1.5, 1.75, 1.0);
+        assert_eq!((th2.mean(1), th2.mean(2)), (1.0, 1.0));
+        assert_eq!(th2.std_dev(2), 0.75);
+        assert_eq!(th2.bin_moments(2).std_dev(), 0.5);
+    }
+
+    #[test]
+    fn test_integral_and_quantiles() {
+        let mut th1 = TH1::with_edges("h", "", &[0.0, 1.0, 3.0, 4.0]);
+        for (x, w) in [(0.5, 1.0), (2.0, 2.0), (3.5, 1.0), (-1.0, 5.0)] {
+            th1.fill(x, w);
+        }
+        assert_eq!(th1.integral_in(1..=3, false), 4.0);
+        assert_eq!(th1.integral_in(0..=2, false), 8.0);
+        assert_eq!(th1.integral_in(1..=3, true), 1.0 + 4.0 + 1.0);
+
+        let quantiles = th1.quantiles(&[0.0, 0.25, 0.5, 1.0]).unwrap();
+        assert_eq!(quantiles, [0.0, 1.0, 2.0, 4.0]);
+        assert_eq!(th1.find_first_bin_above(0.0, 1), Some(1));
+        assert_eq!(th1.find_first_bin_above(1.5, 1), Some(2));
+        assert_eq!(th1.find_last_bin_above(1.5, 1), Some(2));
+        assert_eq!(th1.find_first_bin_above(2.0, 1), None);
+        assert!(TH1::new("empty", "", 2, 0.0, 1.0)
+            .quantiles(&[0.5])
+            .is_none());
+
+        let mut th2 = TH2::new("h2", "", 2, 0.0, 2.0, 2, 0.0, 4.0);
+        th2.fill(0.5, 3.0, 1.0);
+        th2.fill(1.5, 1.0, 3.0);
+        assert_eq!(th2.integral_in(1..=2, 2..=2, false), 1.0);
+        assert_eq!(th2.integral_in(1..=2, 1..=2, true), 8.0);
+        assert_eq!(th2.find_first_bin_above(2.0, 2), Some(1));
+        assert_eq!(th2.find_first_bin_above(0.5, 1), Some(1));
+    }
+}
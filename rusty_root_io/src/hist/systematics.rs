@@ -0,0 +1,402 @@
+use crate::file::keylist::KeyList;
+use crate::objects::th1::TH1;
+use std::collections::BTreeMap;
+use std::io;
+
+/*
+ * Systematic variations
+ *
+ * The objects of a variation are named after the nominal one with the name of the variation in
+ * place of the nominal suffix, e.g. cflow_AnaMuons_Baseline_NOSYS, ..._JET_JER__1up and
+ * ..._MUON_EFF__1down. Variation names contain the separator themselves, so the base of a name is
+ * the longest base of a nominal name it starts with.
+ *
+ * A name of the base without the up or down suffix is a variation only when it is listed as a
+ * one-sided variation, other objects sharing the prefix (h_weights next to h_NOSYS) are not.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SystematicPattern {
+    pub separator: String,
+    pub nominal: String,
+    pub up: String,
+    pub down: String,
+    // variations without up or down suffix, e.g. GEN_ALT for an alternative generator
+    pub one_sided: Vec<String>,
+}
+
+impl Default for SystematicPattern {
+    fn default() -> Self {
+        Self {
+            separator: String::from("_"),
+            nominal: String::from("NOSYS"),
+            up: String::from("__1up"),
+            down: String::from("__1down"),
+            one_sided: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Up,
+    Down,
+    // a variation without up or down suffix, e.g. an alternative generator
+    OneSided,
+}
+
+// An up and a down variation, of names or of the objects read from them
+#[derive(Debug, Clone, PartialEq)]
+pub struct Variation<T> {
+    pub name: String,
+    pub up: Option<T>,
+    pub down: Option<T>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SystematicGroup {
+    pub base: String,
+    pub nominal: String,
+    pub variations: Vec<Variation<String>>,
+}
+
+impl SystematicPattern {
+    // The base of a nominal name, e.g. cflow_AnaMuons_Baseline for cflow_AnaMuons_Baseline_NOSYS
+    pub fn nominal_base<'a>(&self, name: &'a str) -> Option<&'a str> {
+        name.strip_suffix(self.nominal.as_str())?
+            .strip_suffix(self.separator.as_str())
+            .filter(|base| !base.is_empty())
+    }
+
+    // The variation and direction of a name, given the base of its nominal name
+    pub fn variation<'a>(&self, base: &str, name: &'a str) -> Option<(&'a str, Direction)> {
+        let variation = name
+            .strip_prefix(base)?
+            .strip_prefix(self.separator.as_str())?;
+        if variation == self.nominal {
+            return None;
+        }
+        let (variation, direction) = if let Some(up) = variation.strip_suffix(self.up.as_str()) {
+            (up, Direction::Up)
+        } else if let Some(down) = variation.strip_suffix(self.down.as_str()) {
+            (down, Direction::Down)
+        } else if self
+            .one_sided
+            .iter()
+            .any(|one_sided| one_sided == variation)
+        {
+            (variation, Direction::OneSided)
+        } else {
+            return None;
+        };
+        (!variation.is_empty()).then_some((variation, direction))
+    }
+
+    // Groups of a nominal name and its variations, sorted by base and variation. Names without
+    // nominal are left out; a one-sided variation is an up variation without down.
+    pub fn group<'a>(&self, names: impl IntoIterator<Item = &'a str>) -> Vec<SystematicGroup> {
+        let names: Vec<&str> = names.into_iter().collect();
+        let mut groups: BTreeMap<&str, (String, BTreeMap<String, Variation<String>>)> = names
+            .iter()
+            .filter_map(|name| {
+                Some((
+                    self.nominal_base(name)?,
+                    (name.to_string(), BTreeMap::new()),
+                ))
+            })
+            .collect();
+        // the longest base first, cflow_A_B before cflow_A
+        let mut bases: Vec<&str> = groups.keys().copied().collect();
+        bases.sort_by_key(|base| std::cmp::Reverse(base.len()));
+
+        for name in names {
+            if self.nominal_base(name).is_some() {
+                continue;
+            }
+            let Some((base, (variation, direction))) = bases
+                .iter()
+                .find_map(|base| Some((*base, self.variation(base, name)?)))
+            else {
+                continue;
+            };
+            let (_, variations) = groups.get_mut(base).expect("a base of the groups");
+            let entry = variations
+                .entry(variation.to_string())
+                .or_insert_with(|| Variation {
+                    name: variation.to_string(),
+                    up: None,
+                    down: None,
+                });
+            match direction {
+                Direction::Up | Direction::OneSided => entry.up = Some(name.to_string()),
+                Direction::Down => entry.down = Some(name.to_string()),
+            }
+        }
+
+        groups
+            .into_iter()
+            .map(|(base, (nominal, variations))| SystematicGroup {
+                base: base.to_string(),
+                nominal,
+                variations: variations.into_values().collect(),
+            })
+            .collect()
+    }
+}
+
+impl KeyList {
+    // The systematic groups of the keys of a directory, every cycle of a name counted once
+    pub fn systematic_groups(&self, pattern: &SystematicPattern) -> Vec<SystematicGroup> {
+        let mut names: Vec<&str> = self.keys.iter().map(|key| key.name.as_str()).collect();
+        names.sort_unstable();
+        names.dedup();
+        pattern.group(names)
+    }
+}
+
+impl<T> Variation<T> {
+    pub fn map<U>(self, mut f: impl FnMut(T) -> U) -> Variation<U> {
+        Variation {
+            name: self.name,
+            up: self.up.map(&mut f),
+            down: self.down.map(&mut f),
+        }
+    }
+
+    pub fn try_map<U, E>(self, mut f: impl FnMut(T) -> Result<U, E>) -> Result<Variation<U>, E> {
+        Ok(Variation {
+            name: self.name,
+            up: self.up.map(&mut f).transpose()?,
+            down: self.down.map(&mut f).transpose()?,
+        })
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum BandMethod {
+    // the largest shift of any variation
+    #[default]
+    Envelope,
+    // the shifts of the variations added in quadrature
+    Quadrature,
+}
+
+/*
+ * The upward and downward shifts from the nominal contents, per cell of the nominal histogram.
+ * Every variation shifts a cell up by the largest positive difference of its up and down
+ * histograms to the nominal one, and down by the largest negative one, so that variations with
+ * both sides on the same side of the nominal histogram only widen that side.
+ */
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct SystematicBand {
+    pub nominal: Vec<f64>,
+    pub up: Vec<f64>,
+    pub down: Vec<f64>,
+}
+
+impl SystematicBand {
+    pub fn upper(&self, cell: usize) -> f64 {
+        self.nominal[cell] + self.up[cell]
+    }
+
+    pub fn lower(&self, cell: usize) -> f64 {
+        self.nominal[cell] - self.down[cell]
+    }
+}
+
+impl TH1 {
+    pub fn systematic_band(
+        &self,
+        variations: &[Variation<&TH1>],
+        method: BandMethod,
+    ) -> io::Result<SystematicBand> {
+        let nominal = self.contents().to_vec();
+        let mut up = vec![0.0f64; nominal.len()];
+        let mut down = vec![0.0f64; nominal.len()];
+        for variation in variations {
+            for varied in variation.up.iter().chain(&variation.down) {
+                self.check_consistency(varied)?;
+            }
+            for (cell, content) in nominal.iter().enumerate() {
+                let shifts = variation
+                    .up
+                    .iter()
+                    .chain(&variation.down)
+                    .map(|varied| varied.contents()[cell] - content);
+                let shift_up = shifts.clone().fold(0.0, f64::max);
+                let shift_down = -shifts.fold(0.0, f64::min);
+                match method {
+                    BandMethod::Envelope => {
+                        up[cell] = up[cell].max(shift_up);
+                        down[cell] = down[cell].max(shift_down);
+                    }
+                    BandMethod::Quadrature => {
+                        up[cell] += shift_up * shift_up;
+                        down[cell] += shift_down * shift_down;
+                    }
+                }
+            }
+        }
+        if method == BandMethod::Quadrature {
+            up.iter_mut()
+                .chain(&mut down)
+                .for_each(|shift| *shift = shift.sqrt());
+        }
+        Ok(SystematicBand { nominal, up, down })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::objects::tkey::TKey;
+    use crate::objects::tstring::TString;
+
+    #[test]
+    fn test_group() {
+        let names = [
+            "cflow_AnaMuons_Baseline_NOSYS",
+            "cflow_AnaMuons_Baseline_JET_JER__1up",
+            "cflow_AnaMuons_Baseline_JET_JER__1down",
+            "cflow_AnaMuons_Baseline_MUON_EFF__1down",
+            "cflow_AnaMuons_Baseline_GEN_ALT",
+            "cflow_AnaMuons_NOSYS",
+            "cflow_AnaMuons_JET_JER__1up",
+            "metadata",
+        ];
+        let pattern = SystematicPattern {
+            one_sided: vec![String::from("GEN_ALT")],
+            ..Default::default()
+        };
+        let groups = pattern.group(names);
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].base, "cflow_AnaMuons");
+        assert_eq!(groups[0].variations.len(), 1);
+
+        let baseline = &groups[1];
+        assert_eq!(baseline.nominal, "cflow_AnaMuons_Baseline_NOSYS");
+        let variations: Vec<&str> = baseline
+            .variations
+            .iter()
+            .map(|v| v.name.as_str())
+            .collect();
+        assert_eq!(variations, ["GEN_ALT", "JET_JER", "MUON_EFF"]);
+        assert_eq!(
+            baseline.variations[1].down.as_deref(),
+            Some("cflow_AnaMuons_Baseline_JET_JER__1down")
+        );
+        assert!(baseline.variations[2].up.is_none());
+        assert!(baseline.variations[0].down.is_none());
+
+        // other suffixes, and every cycle of a key once
+        let pattern = SystematicPattern {
+            nominal: String::from("nominal"),
+            up: String::from("Up"),
+            down: String::from("Down"),
+            ..Default::default()
+        };
+        let key = |name: &str| TKey {
+            name: TString::new(name),
+            ..TKey::new()
+        };
+        let key_list = KeyList {
+            keys: vec![
+                key("h_nominal"),
+                key("h_pdfUp"),
+                key("h_pdfUp"),
+                key("h_pdfDown"),
+            ],
+            ..Default::default()
+        };
+        let groups = key_list.systematic_groups(&pattern);
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].variations[0].name, "pdf");
+        assert_eq!(groups[0].variations[0].up.as_deref(), Some("h_pdfUp"));
+    }
+
+    #[test]
+    fn test_group_unrelated_names() {
+        // objects sharing the prefix of a nominal name are not one-sided variations
+        let names = [
+            "h_NOSYS",
+            "h_weights",
+            "h_cutflow_raw",
+            "h_JET_JER__1up",
+            "h_GEN_ALT",
+        ];
+        let groups = SystematicPattern::default().group(names);
+        assert_eq!(groups.len(), 1);
+        let variations: Vec<&str> = groups[0]
+            .variations
+            .iter()
+            .map(|v| v.name.as_str())
+            .collect();
+        assert_eq!(variations, ["JET_JER"]);
+
+        let pattern = SystematicPattern {
+            one_sided: vec![String::from("GEN_ALT")],
+            ..Default::default()
+        };
+        let groups = pattern.group(names);
+        let variations: Vec<&str> = groups[0]
+            .variations
+            .iter()
+            .map(|v| v.name.as_str())
+            .collect();
+        assert_eq!(variations, ["GEN_ALT", "JET_JER"]);
+        assert_eq!(
+            pattern.variation("h", "h_GEN_ALT"),
+            Some(("GEN_ALT", Direction::OneSided))
+        );
+        assert_eq!(pattern.variation("h", "h_weights"), None);
+    }
+
+    #[test]
+    fn test_band() {
+        let histogram = |contents: [f64; 2]| {
+            let mut th1 = TH1::new("h", "", 2, 0.0, 2.0);
+            th1.set_bin_content(1, contents[0]);
+            th1.set_bin_content(2, contents[1]);
+            th1
+        };
+        let nominal = histogram([10.0, 10.0]);
+        let jer_up = histogram([13.0, 9.0]);
+        let jer_down = histogram([6.0, 12.0]);
+        let eff_up = histogram([14.0, 11.0]);
+        let variations = [
+            Variation {
+                name: String::from("JER"),
+                up: Some(&jer_up),
+                down: Some(&jer_down),
+            },
+            Variation {
+                name: String::from("EFF"),
+                up: Some(&eff_up),
+                down: None,
+            },
+        ];
+
+        let envelope = nominal
+            .systematic_band(&variations, BandMethod::Envelope)
+            .unwrap();
+        assert_eq!((envelope.up[1], envelope.down[1]), (4.0, 4.0));
+        assert_eq!((envelope.up[2], envelope.down[2]), (2.0, 1.0));
+        assert_eq!((envelope.upper(1), envelope.lower(2)), (14.0, 9.0));
+
+        let quadrature = nominal
+            .systematic_band(&variations, BandMethod::Quadrature)
+            .unwrap();
+        assert_eq!((quadrature.up[1], quadrature.down[1]), (5.0, 4.0));
+        assert_eq!(quadrature.up[2], 5.0f64.sqrt());
+        assert_eq!(quadrature.up[0], 0.0);
+
+        let other = TH1::new("other", "", 3, 0.0, 3.0);
+        let variations = [Variation {
+            name: String::from("other"),
+            up: Some(&other),
+            down: None,
+        }];
+        assert!(nominal
+            .systematic_band(&variations, BandMethod::Envelope)
+            .is_err());
+    }
+}
@@ -16,7 +16,15 @@
 // pub mod tstring;
 // pub mod utils;
 
+// the code generated by #[derive(RootStreamer)] refers to ::rusty_root_io, also from this crate
+extern crate self as rusty_root_io;
+
+pub use binrw;
+pub use rusty_root_derive::RootStreamer;
+
 pub mod core;
 pub mod file;
+pub mod hist;
 pub mod objects;
 pub mod streamer;
+pub mod tree;
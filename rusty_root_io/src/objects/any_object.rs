@@ -0,0 +1,490 @@
+use crate::core::constant::K_BYTECOUNTMASK;
+use crate::core::read_context::ReadContext;
+use crate::core::utils::ClassInfo;
+use crate::core::write_buffer::{WriteBuffer, WriteObject};
+use crate::objects::raw_object::RawObject;
+use crate::objects::tefficiency::TEfficiency;
+use crate::objects::tf1::TF1;
+use crate::objects::tgraph::{TGraph, TGraphAsymmErrors, TGraphErrors};
+use crate::objects::th1::{BinType, TH1, TH2, TH3};
+use crate::objects::thstack::THStack;
+use crate::objects::tlegend::{TLegend, TLegendEntry};
+use crate::objects::tlist::{THashList, TList};
+use crate::objects::tmap::TMap;
+use crate::objects::tmatrix::{TMatrixD, TMatrixDSym};
+use crate::objects::tnamed::TNamed;
+use crate::objects::tobjstring::TObjString;
+use crate::objects::tpad::{TCanvas, TPad};
+use crate::objects::tparameter::{ParameterValue, TParameter};
+use crate::objects::tprofile::TProfile;
+use crate::objects::tvector::TVectorD;
+use binrw::io::{Read, Seek, SeekFrom};
+use binrw::{BinRead, BinReaderExt, BinResult, Endian};
+
+/*
+ * An object of any class in a collection, dispatched on the class name that precedes it. Classes
+ * without a Rust type are kept as RawObject, so that a list never fails on an unknown element.
+ *
+ * Read like the elements of a TList: from the byte count, followed by the class tag.
+ *     let metadata: TList<AnyObject> = ...;
+ *     let sum_of_weights = metadata.parameter("sumOfWeights");
+ */
+#[derive(Debug)]
+pub enum AnyObject {
+    TNamed(TNamed),
+    TObjString(TObjString),
+    TParameterF64(TParameter<f64>),
+    TParameterF32(TParameter<f32>),
+    TParameterI32(TParameter<i32>),
+    TParameterI64(TParameter<i64>),
+    TParameterBool(TParameter<bool>),
+    TList(TList<AnyObject>),
+    THashList(THashList<AnyObject>),
+    TMap(TMap),
+    TVectorD(TVectorD),
+    TMatrixD(TMatrixD),
+    TMatrixDSym(TMatrixDSym),
+    TF1(Box<TF1>),
+    TH1(Box<TH1>),
+    TH2(Box<TH2>),
+    TH3(Box<TH3>),
+    TProfile(Box<TProfile>),
+    TEfficiency(Box<TEfficiency>),
+    TGraph(Box<TGraph>),
+    TGraphErrors(Box<TGraphErrors>),
+    TGraphAsymmErrors(Box<TGraphAsymmErrors>),
+    THStack(Box<THStack>),
+    TPad(Box<TPad>),
+    TCanvas(Box<TCanvas>),
+    TLegend(Box<TLegend>),
+    TLegendEntry(Box<TLegendEntry>),
+    Unknown {
+        class_name: String,
+        object: RawObject,
+    },
+}
+
+impl AnyObject {
+    pub fn class_name(&self) -> &str {
+        match self {
+            Self::TNamed(_) => "TNamed",
+            Self::TObjString(_) => "TObjString",
+            Self::TParameterF64(_) => <f64 as ParameterValue>::CLASS_NAME,
+            Self::TParameterF32(_) => <f32 as ParameterValue>::CLASS_NAME,
+            Self::TParameterI32(_) => <i32 as ParameterValue>::CLASS_NAME,
+            Self::TParameterI64(_) => <i64 as ParameterValue>::CLASS_NAME,
+            Self::TParameterBool(_) => <bool as ParameterValue>::CLASS_NAME,
+            Self::TList(_) => "TList",
+            Self::THashList(_) => "THashList",
+            Self::TMap(_) => "TMap",
+            Self::TVectorD(_) => "TVectorT<double>",
+            Self::TMatrixD(_) => "TMatrixT<double>",
+            Self::TMatrixDSym(_) => "TMatrixTSym<double>",
+            Self::TF1(_) => "TF1",
+            Self::TH1(histogram) => histogram.class_name(),
+            Self::TH2(histogram) => histogram.th1.class_name(),
+            Self::TH3(histogram) => histogram.th1.class_name(),
+            Self::TProfile(profile) => profile.class_name(),
+            Self::TEfficiency(_) => "TEfficiency",
+            Self::TGraph(_) => "TGraph",
+            Self::TGraphErrors(_) => "TGraphErrors",
+            Self::TGraphAsymmErrors(_) => "TGraphAsymmErrors",
+            Self::THStack(_) => "THStack",
+            Self::TPad(_) => "TPad",
+            Self::TCanvas(_) => "TCanvas",
+            Self::TLegend(_) => "TLegend",
+            Self::TLegendEntry(_) => "TLegendEntry",
+            Self::Unknown { class_name, .. } => class_name,
+        }
+    }
+
+    // TObject::GetName, empty for objects that are not decoded
+    pub fn name(&self) -> &str {
+        match self {
+            Self::TNamed(named) => &named.name,
+            Self::TObjString(string) => string,
+            Self::TParameterF64(parameter) => parameter.name(),
+            Self::TParameterF32(parameter) => parameter.name(),
+            Self::TParameterI32(parameter) => parameter.name(),
+            Self::TParameterI64(parameter) => parameter.name(),
+            Self::TParameterBool(parameter) => parameter.name(),
+            Self::TList(list) | Self::THashList(list) => &list.f_name,
+            Self::TMap(map) => &map.name,
+            Self::TF1(function) => function.name(),
+            Self::TH1(histogram) => histogram.name(),
+            Self::TH2(histogram) => histogram.name(),
+            Self::TH3(histogram) => histogram.name(),
+            Self::TProfile(profile) => profile.name(),
+            Self::TEfficiency(efficiency) => efficiency.name(),
+            Self::TGraph(graph) => graph.name(),
+            Self::TGraphErrors(graph) => graph.name(),
+            Self::TGraphAsymmErrors(graph) => graph.name(),
+            Self::THStack(stack) => stack.name(),
+            Self::TPad(pad) => pad.name(),
+            Self::TCanvas(canvas) => canvas.name(),
+            Self::TLegend(legend) => legend.name(),
+            Self::TVectorD(_)
+            | Self::TMatrixD(_)
+            | Self::TMatrixDSym(_)
+            | Self::TLegendEntry(_)
+            | Self::Unknown { .. } => "",
+        }
+    }
+
+    // Value of a TParameter of any value type
+    pub fn parameter(&self) -> Option<f64> {
+        match self {
+            Self::TParameterF64(parameter) => Some(parameter.value.as_f64()),
+            Self::TParameterF32(parameter) => Some(parameter.value.as_f64()),
+            Self::TParameterI32(parameter) => Some(parameter.value.as_f64()),
+            Self::TParameterI64(parameter) => Some(parameter.value.as_f64()),
+            Self::TParameterBool(parameter) => Some(parameter.value.as_f64()),
+            _ => None,
+        }
+    }
+
+    pub fn as_string(&self) -> Option<&str> {
+        match self {
+            Self::TObjString(string) => Some(string),
+            _ => None,
+        }
+    }
+
+    pub fn as_list(&self) -> Option<&TList<AnyObject>> {
+        match self {
+            Self::TList(list) | Self::THashList(list) => Some(list),
+            _ => None,
+        }
+    }
+
+    pub fn as_function(&self) -> Option<&TF1> {
+        match self {
+            Self::TF1(function) => Some(function),
+            _ => None,
+        }
+    }
+
+    // A histogram of any dimension or a profile, as its TH1 base
+    pub fn as_th1(&self) -> Option<&TH1> {
+        match self {
+            Self::TH1(histogram) => Some(histogram),
+            Self::TH2(histogram) => Some(&histogram.th1),
+            Self::TH3(histogram) => Some(&histogram.th1),
+            Self::TProfile(profile) => Some(&profile.th1),
+            _ => None,
+        }
+    }
+
+    pub fn as_th2(&self) -> Option<&TH2> {
+        match self {
+            Self::TH2(histogram) => Some(histogram),
+            _ => None,
+        }
+    }
+
+    pub fn as_th3(&self) -> Option<&TH3> {
+        match self {
+            Self::TH3(histogram) => Some(histogram),
+            _ => None,
+        }
+    }
+
+    pub fn as_profile(&self) -> Option<&TProfile> {
+        match self {
+            Self::TProfile(profile) => Some(profile),
+            _ => None,
+        }
+    }
+
+    // A graph of any kind, as its TGraph base
+    pub fn as_graph(&self) -> Option<&TGraph> {
+        match self {
+            Self::TGraph(graph) => Some(graph),
+            Self::TGraphErrors(graph) => Some(&graph.graph),
+            Self::TGraphAsymmErrors(graph) => Some(&graph.graph),
+            _ => None,
+        }
+    }
+
+    pub fn as_stack(&self) -> Option<&THStack> {
+        match self {
+            Self::THStack(stack) => Some(stack),
+            _ => None,
+        }
+    }
+
+    // A pad or a canvas, as its TPad base
+    pub fn as_pad(&self) -> Option<&TPad> {
+        match self {
+            Self::TPad(pad) => Some(pad),
+            Self::TCanvas(canvas) => Some(&canvas.pad),
+            _ => None,
+        }
+    }
+
+    pub fn as_legend(&self) -> Option<&TLegend> {
+        match self {
+            Self::TLegend(legend) => Some(legend),
+            _ => None,
+        }
+    }
+
+    pub fn as_map(&self) -> Option<&TMap> {
+        match self {
+            Self::TMap(map) => Some(map),
+            _ => None,
+        }
+    }
+}
+
+impl BinRead for AnyObject {
+    type Args<'a> = (ReadContext,);
+
+    fn read_options<R: Read + Seek>(
+        reader: &mut R,
+        endian: Endian,
+        args: Self::Args<'_>,
+    ) -> BinResult<Self> {
+        let (ctx,) = args;
+        let start = reader.stream_position()?;
+        let byte_count = reader.read_type::<u32>(endian)? & K_BYTECOUNTMASK;
+        let class_name = ClassInfo::read_class_info(reader, &ctx)?.get_class_name();
+        let object = Self::read_class(&class_name, reader, endian, ctx)?;
+        reader.seek(SeekFrom::Start(start + 4 + byte_count as u64))?;
+        Ok(object)
+    }
+}
+
+impl AnyObject {
+    // An object of a known class, e.g. the object of a key, without byte count and class tag
+    pub fn read_class<R: Read + Seek>(
+        class_name: &str,
+        reader: &mut R,
+        endian: Endian,
+        ctx: ReadContext,
+    ) -> BinResult<Self> {
+        let args = (ctx,);
+        let object = match class_name {
+            "TNamed" => Self::TNamed(TNamed::read_options(reader, endian, args)?),
+            "TObjString" => Self::TObjString(TObjString::read_options(reader, endian, args)?),
+            "TParameter<double>" => {
+                Self::TParameterF64(TParameter::read_options(reader, endian, args)?)
+            }
+            "TParameter<float>" => {
+                Self::TParameterF32(TParameter::read_options(reader, endian, args)?)
+            }
+            "TParameter<int>" => {
+                Self::TParameterI32(TParameter::read_options(reader, endian, args)?)
+            }
+            "TParameter<long>" | "TParameter<Long64_t>" | "TParameter<long long>" => {
+                Self::TParameterI64(TParameter::read_options(reader, endian, args)?)
+            }
+            "TParameter<bool>" => {
+                Self::TParameterBool(TParameter::read_options(reader, endian, args)?)
+            }
+            "TList" => Self::TList(TList::read_options(reader, endian, args)?),
+            "THashList" => Self::THashList(TList::read_options(reader, endian, args)?),
+            "TMap" => Self::TMap(TMap::read_options(reader, endian, args)?),
+            "TVectorT<double>" => Self::TVectorD(TVectorD::read_options(reader, endian, args)?),
+            "TMatrixT<double>" => Self::TMatrixD(TMatrixD::read_options(reader, endian, args)?),
+            "TMatrixTSym<double>" => {
+                Self::TMatrixDSym(TMatrixDSym::read_options(reader, endian, args)?)
+            }
+            "TF1" => Self::TF1(Box::new(TF1::read_options(reader, endian, args)?)),
+            "TEfficiency" => {
+                Self::TEfficiency(Box::new(TEfficiency::read_options(reader, endian, args)?))
+            }
+            "TGraph" => Self::TGraph(Box::new(TGraph::read_options(reader, endian, args)?)),
+            "TGraphErrors" => {
+                Self::TGraphErrors(Box::new(TGraphErrors::read_options(reader, endian, args)?))
+            }
+            "TGraphAsymmErrors" => Self::TGraphAsymmErrors(Box::new(
+                TGraphAsymmErrors::read_options(reader, endian, args)?,
+            )),
+            "THStack" => Self::THStack(Box::new(THStack::read_options(reader, endian, args)?)),
+            // classes read from the streamer info are kept raw when the context has none
+            "TPad" if args.0.schema("TPad").is_some() => {
+                Self::TPad(Box::new(TPad::read_options(reader, endian, args)?))
+            }
+            "TCanvas" if args.0.schema("TPad").is_some() => {
+                Self::TCanvas(Box::new(TCanvas::read_options(reader, endian, args)?))
+            }
+            "TLegend" if args.0.schema("TLegend").is_some() => {
+                Self::TLegend(Box::new(TLegend::read_options(reader, endian, args)?))
+            }
+            "TLegendEntry" if args.0.schema("TLegendEntry").is_some() => {
+                Self::TLegendEntry(Box::new(TLegendEntry::read_options(reader, endian, args)?))
+            }
+            "TProfile" => Self::TProfile(Box::new(TProfile::read_options(reader, endian, args)?)),
+            name if BinType::from_class_name(name).is_some() => {
+                let (dimension, bin_type) = BinType::from_class_name(name).unwrap_or_default();
+                let args = (args.0, bin_type);
+                match dimension {
+                    1 => Self::TH1(Box::new(TH1::read_options(reader, endian, args)?)),
+                    2 => Self::TH2(Box::new(TH2::read_options(reader, endian, args)?)),
+                    _ => Self::TH3(Box::new(TH3::read_options(reader, endian, args)?)),
+                }
+            }
+            _ => Self::Unknown {
+                object: RawObject::read_options(reader, endian, args)?,
+                class_name: class_name.to_string(),
+            },
+        };
+        Ok(object)
+    }
+}
+
+// The classes that have a writer; the others, e.g. undecoded objects, are not writable
+impl WriteObject for AnyObject {
+    fn class_name(&self) -> String {
+        AnyObject::class_name(self).to_string()
+    }
+
+    fn title(&self) -> &str {
+        match self {
+            Self::TNamed(named) => &named.title,
+            Self::TH1(histogram) => histogram.title(),
+            Self::TH2(histogram) => histogram.title(),
+            Self::TH3(histogram) => histogram.title(),
+            Self::TProfile(profile) => profile.title(),
+            Self::TGraph(graph) => graph.title(),
+            Self::TGraphErrors(graph) => graph.title(),
+            Self::TGraphAsymmErrors(graph) => graph.title(),
+            _ => "",
+        }
+    }
+
+    fn is_writable(&self) -> bool {
+        matches!(
+            self,
+            Self::TNamed(_)
+                | Self::TObjString(_)
+                | Self::TParameterF64(_)
+                | Self::TParameterF32(_)
+                | Self::TParameterI32(_)
+                | Self::TParameterI64(_)
+                | Self::TParameterBool(_)
+                | Self::TList(_)
+                | Self::THashList(_)
+                | Self::TH1(_)
+                | Self::TH2(_)
+                | Self::TH3(_)
+                | Self::TProfile(_)
+                | Self::TGraph(_)
+                | Self::TGraphErrors(_)
+                | Self::TGraphAsymmErrors(_)
+        )
+    }
+
+    fn write_to(&self, buf: &mut WriteBuffer) {
+        match self {
+            Self::TNamed(named) => named.write_to(buf),
+            Self::TObjString(string) => string.write_to(buf),
+            Self::TParameterF64(parameter) => parameter.write_to(buf),
+            Self::TParameterF32(parameter) => parameter.write_to(buf),
+            Self::TParameterI32(parameter) => parameter.write_to(buf),
+            Self::TParameterI64(parameter) => parameter.write_to(buf),
+            Self::TParameterBool(parameter) => parameter.write_to(buf),
+            Self::TList(list) | Self::THashList(list) => list.write_to(buf),
+            Self::TH1(histogram) => histogram.write_to(buf),
+            Self::TH2(histogram) => histogram.write_to(buf),
+            Self::TH3(histogram) => histogram.write_to(buf),
+            Self::TProfile(profile) => profile.write_to(buf),
+            Self::TGraph(graph) => graph.write_to(buf),
+            Self::TGraphErrors(graph) => graph.write_to(buf),
+            Self::TGraphAsymmErrors(graph) => graph.write_to(buf),
+            other => panic!("{} cannot be written", AnyObject::class_name(other)),
+        }
+    }
+}
+
+// Lookup by name in a heterogeneous list, like TList::FindObject
+impl TList<AnyObject> {
+    pub fn find(&self, name: &str) -> Option<&AnyObject> {
+        self.iter().find(|object| object.name() == name)
+    }
+
+    // Value of the TParameter with this name
+    pub fn parameter(&self, name: &str) -> Option<f64> {
+        self.iter()
+            .filter(|object| object.name() == name)
+            .find_map(AnyObject::parameter)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::objects::test_buffer::Buffer;
+    use std::io::Cursor;
+
+    // TList "metadata": two TParameter<double>, a TObjString, a TMap and an unknown class
+    fn metadata() -> Vec<u8> {
+        let mut buf = Buffer::default();
+        buf.versioned(5, |buf| {
+            buf.tobject();
+            buf.string("metadata");
+            buf.put(&5u32.to_be_bytes());
+            buf.parameter("sumOfWeights", 1234.5);
+            buf.string("");
+            buf.parameter("luminosity", 140.1);
+            buf.string("");
+            buf.obj_string("mc20a");
+            buf.string("");
+            buf.object("TMap", |buf| {
+                buf.versioned(3, |buf| {
+                    buf.tobject();
+                    buf.string("xsec");
+                    buf.put(&2i32.to_be_bytes());
+                    buf.obj_string("ttbar");
+                    buf.parameter("xsec", 729.8);
+                    buf.obj_string("empty");
+                    buf.put(&0u32.to_be_bytes());
+                })
+            });
+            buf.string("");
+            buf.object("TFoo", |buf| buf.versioned(1, |buf| buf.put(&[1, 2, 3])));
+            buf.string("");
+        });
+        buf.bytes
+    }
+
+    #[test]
+    fn test_read_heterogeneous_list() {
+        let list =
+            TList::<AnyObject>::read_be_args(&mut Cursor::new(metadata()), (ReadContext::new(),))
+                .expect("Failed to read TList");
+        assert_eq!(list.f_name, "metadata");
+        let classes: Vec<&str> = list.iter().map(AnyObject::class_name).collect();
+        assert_eq!(
+            classes,
+            [
+                "TParameter<double>",
+                "TParameter<double>",
+                "TObjString",
+                "TMap",
+                "TFoo"
+            ]
+        );
+        assert_eq!(list.parameter("sumOfWeights"), Some(1234.5));
+        assert_eq!(list.parameter("luminosity"), Some(140.1));
+        assert_eq!(list.parameter("mc20a"), None);
+        assert_eq!(
+            list.find("mc20a").and_then(AnyObject::as_string),
+            Some("mc20a")
+        );
+
+        let map = list.find("xsec").and_then(AnyObject::as_map).unwrap();
+        assert_eq!(map.len(), 2);
+        assert_eq!(map.get("ttbar").and_then(AnyObject::parameter), Some(729.8));
+        assert!(map.get("empty").is_none());
+
+        match list.iter().last() {
+            Some(AnyObject::Unknown { class_name, object }) => {
+                assert_eq!(class_name, "TFoo");
+                assert_eq!(object.data, [1, 2, 3]);
+            }
+            other => panic!("unexpected object {:?}", other),
+        }
+    }
+}
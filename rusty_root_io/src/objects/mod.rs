@@ -1,8 +1,29 @@
+pub mod any_object;
+pub mod raw_object;
+pub mod tarray;
+pub mod tatt;
+pub mod taxis;
+pub mod tclonesarray;
 pub mod tdictionary;
+pub mod tefficiency;
+#[cfg(test)]
+pub(crate) mod test_buffer;
+pub mod tf1;
+pub mod tformula;
+pub mod tgraph;
 pub mod th1;
+pub mod thstack;
 pub mod tkey;
+pub mod tlegend;
 pub mod tlist;
+pub mod tmap;
+pub mod tmatrix;
 pub mod tnamed;
 pub mod tobjarray;
 pub mod tobject;
+pub mod tobjstring;
+pub mod tpad;
+pub mod tparameter;
+pub mod tprofile;
 pub mod tstring;
+pub mod tvector;
@@ -0,0 +1,44 @@
+use crate::core::read_context::ReadContext;
+use crate::streamer::member::{read_remaining, VersionHeader};
+use binrw::io::{Read, Seek};
+use binrw::{BinRead, BinResult, Endian};
+use std::io;
+
+/*
+ * An object of a class without a Rust type: its version and the streamed bytes that follow,
+ * delimited by the byte count. Useful to step over members and list entries that are not decoded.
+ */
+#[derive(Debug, Default, Clone)]
+pub struct RawObject {
+    pub byte_count: u32,
+    pub version: u16,
+    pub data: Vec<u8>,
+}
+
+impl BinRead for RawObject {
+    type Args<'a> = (ReadContext,);
+
+    fn read_options<R: Read + Seek>(
+        reader: &mut R,
+        endian: Endian,
+        _args: Self::Args<'_>,
+    ) -> BinResult<Self> {
+        let header = VersionHeader::read(reader, endian)?;
+        if header.end().is_none() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "object at {:#X} has no byte count and cannot be skipped",
+                    header.start
+                ),
+            )
+            .into());
+        }
+        let data = read_remaining(reader, &header)?;
+        Ok(Self {
+            byte_count: header.byte_count,
+            version: header.version,
+            data,
+        })
+    }
+}
@@ -0,0 +1,81 @@
+use crate::core::write_buffer::WriteBuffer;
+use binrw::io::{Read, Seek};
+use binrw::{BinRead, BinReaderExt, BinResult, BinWrite, Endian};
+use std::ops::Deref;
+
+/*
+ * TArrayC, TArrayS, TArrayI, TArrayL, TArrayL64, TArrayF and TArrayD
+ * https://root.cern/doc/v638/classTArray.html
+ * The classes have a custom streamer: fN followed by fN values, no byte count and no version.
+ */
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct TArray<T> {
+    pub f_n: i32,
+    pub f_array: Vec<T>,
+}
+
+pub type TArrayC = TArray<i8>;
+pub type TArrayS = TArray<i16>;
+pub type TArrayI = TArray<i32>;
+pub type TArrayL = TArray<i64>;
+pub type TArrayL64 = TArray<i64>;
+pub type TArrayF = TArray<f32>;
+pub type TArrayD = TArray<f64>;
+
+impl<T> BinRead for TArray<T>
+where
+    T: for<'a> BinRead<Args<'a> = ()>,
+{
+    type Args<'a> = ();
+
+    fn read_options<R: Read + Seek>(
+        reader: &mut R,
+        endian: Endian,
+        _args: Self::Args<'_>,
+    ) -> BinResult<Self> {
+        let f_n: i32 = reader.read_type(endian)?;
+        let f_array = (0..f_n.max(0))
+            .map(|_| T::read_options(reader, endian, ()))
+            .collect::<BinResult<Vec<T>>>()?;
+        Ok(Self { f_n, f_array })
+    }
+}
+
+impl<T> TArray<T>
+where
+    T: BinWrite,
+    for<'a> T::Args<'a>: Default,
+{
+    // fN and the values, like TBuffer::WriteArray
+    pub fn write_to(&self, buf: &mut WriteBuffer) {
+        buf.write(&(self.f_array.len() as i32));
+        for value in &self.f_array {
+            buf.write(value);
+        }
+    }
+}
+
+impl<T> Deref for TArray<T> {
+    type Target = [T];
+
+    fn deref(&self) -> &Self::Target {
+        &self.f_array
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_read_tarrayd() {
+        let mut bytes = 3i32.to_be_bytes().to_vec();
+        for value in [0.5f64, 1.0, 2.5] {
+            bytes.extend_from_slice(&value.to_be_bytes());
+        }
+        let array = TArrayD::read_be(&mut Cursor::new(bytes)).expect("Failed to read TArrayD");
+        assert_eq!(array.f_n, 3);
+        assert_eq!(&array[..], [0.5, 1.0, 2.5]);
+    }
+}
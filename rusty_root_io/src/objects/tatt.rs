@@ -0,0 +1,123 @@
+use crate::core::write_buffer::{WriteBuffer, WriteObject};
+use crate::RootStreamer;
+
+/*
+ * TAttLine, TAttFill and TAttMarker
+ * https://root.cern/doc/v638/classTAttLine.html
+ *
+ * Graphics attributes, base classes of histograms, graphs and functions. Colors are indices in
+ * the ROOT color table, styles and sizes as in TAttLine::SetLineStyle and friends.
+ */
+#[derive(Debug, Default, Clone, RootStreamer)]
+#[root(class = "TAttLine", version = 2, checksum = 0x94074549)]
+pub struct TAttLine {
+    #[root(byte_count)]
+    pub byte_count: u32,
+    #[root(version)]
+    pub version: u16,
+    pub f_line_color: i16,
+    pub f_line_style: i16,
+    pub f_line_width: i16,
+}
+
+#[derive(Debug, Default, Clone, RootStreamer)]
+#[root(class = "TAttFill", version = 2, checksum = 0xffd92a92)]
+pub struct TAttFill {
+    #[root(byte_count)]
+    pub byte_count: u32,
+    #[root(version)]
+    pub version: u16,
+    pub f_fill_color: i16,
+    pub f_fill_style: i16,
+}
+
+#[derive(Debug, Default, Clone, RootStreamer)]
+#[root(class = "TAttMarker", version = 3, checksum = 0x291d8bec)]
+pub struct TAttMarker {
+    #[root(byte_count)]
+    pub byte_count: u32,
+    #[root(version)]
+    pub version: u16,
+    pub f_marker_color: i16,
+    pub f_marker_style: i16,
+    pub f_marker_size: f32,
+}
+
+impl TAttLine {
+    // TAttLine(color, style, width)
+    pub fn new(color: i16, style: i16, width: i16) -> Self {
+        Self {
+            version: 2,
+            f_line_color: color,
+            f_line_style: style,
+            f_line_width: width,
+            ..Default::default()
+        }
+    }
+}
+
+impl TAttFill {
+    // TAttFill(color, style)
+    pub fn new(color: i16, style: i16) -> Self {
+        Self {
+            version: 2,
+            f_fill_color: color,
+            f_fill_style: style,
+            ..Default::default()
+        }
+    }
+}
+
+impl TAttMarker {
+    // TAttMarker(color, style, size)
+    pub fn new(color: i16, style: i16, size: f32) -> Self {
+        Self {
+            version: 3,
+            f_marker_color: color,
+            f_marker_style: style,
+            f_marker_size: size,
+            ..Default::default()
+        }
+    }
+}
+
+impl WriteObject for TAttLine {
+    fn class_name(&self) -> String {
+        String::from("TAttLine")
+    }
+
+    fn write_to(&self, buf: &mut WriteBuffer) {
+        buf.versioned(2, |buf| {
+            buf.write(&self.f_line_color);
+            buf.write(&self.f_line_style);
+            buf.write(&self.f_line_width);
+        });
+    }
+}
+
+impl WriteObject for TAttFill {
+    fn class_name(&self) -> String {
+        String::from("TAttFill")
+    }
+
+    fn write_to(&self, buf: &mut WriteBuffer) {
+        buf.versioned(2, |buf| {
+            buf.write(&self.f_fill_color);
+            buf.write(&self.f_fill_style);
+        });
+    }
+}
+
+impl WriteObject for TAttMarker {
+    fn class_name(&self) -> String {
+        String::from("TAttMarker")
+    }
+
+    fn write_to(&self, buf: &mut WriteBuffer) {
+        buf.versioned(3, |buf| {
+            buf.write(&self.f_marker_color);
+            buf.write(&self.f_marker_style);
+            buf.write(&self.f_marker_size);
+        });
+    }
+}
@@ -0,0 +1,370 @@
+use crate::core::read_context::ReadContext;
+use crate::core::write_buffer::{WriteBuffer, WriteObject};
+use crate::objects::any_object::AnyObject;
+use crate::objects::tarray::TArrayD;
+use crate::objects::tlist::{THashList, TList, TListElement};
+use crate::objects::tnamed::TNamed;
+use crate::objects::tobject::TObject;
+use crate::objects::tobjstring::TObjString;
+use crate::objects::tstring::TString;
+use crate::streamer::member::{read_bool, VersionHeader};
+use crate::streamer::root_streamer::StreamerMember;
+use crate::RootStreamer;
+use binrw::io::{Read, Seek};
+use binrw::{BinRead, BinReaderExt, BinResult, Endian};
+use std::io;
+use std::sync::Arc;
+
+/*
+ * TAttAxis
+ * https://root.cern/doc/v638/classTAttAxis.html
+ */
+#[derive(Debug, Default, Clone, RootStreamer)]
+#[root(class = "TAttAxis", version = 4, checksum = 0x5c6fff3e)]
+pub struct TAttAxis {
+    #[root(byte_count)]
+    pub byte_count: u32,
+    #[root(version)]
+    pub version: u16,
+    pub f_ndivisions: i32,
+    pub f_axis_color: i16,
+    pub f_label_color: i16,
+    pub f_label_font: i16,
+    pub f_label_offset: f32,
+    pub f_label_size: f32,
+    pub f_tick_length: f32,
+    pub f_title_offset: f32,
+    pub f_title_size: f32,
+    pub f_title_color: i16,
+    pub f_title_font: i16,
+}
+
+impl TAttAxis {
+    // The attributes of TAttAxis::ResetAttAxis
+    pub fn new() -> Self {
+        Self {
+            version: 4,
+            f_ndivisions: 510,
+            f_axis_color: 1,
+            f_label_color: 1,
+            f_label_font: 42,
+            f_label_offset: 0.005,
+            f_label_size: 0.035,
+            f_tick_length: 0.03,
+            f_title_offset: 1.0,
+            f_title_size: 0.035,
+            f_title_color: 1,
+            f_title_font: 42,
+            ..Default::default()
+        }
+    }
+}
+
+impl WriteObject for TAttAxis {
+    fn class_name(&self) -> String {
+        String::from("TAttAxis")
+    }
+
+    fn write_to(&self, buf: &mut WriteBuffer) {
+        buf.versioned(4, |buf| {
+            buf.write(&self.f_ndivisions);
+            buf.write(&self.f_axis_color);
+            buf.write(&self.f_label_color);
+            buf.write(&self.f_label_font);
+            buf.write(&self.f_label_offset);
+            buf.write(&self.f_label_size);
+            buf.write(&self.f_tick_length);
+            buf.write(&self.f_title_offset);
+            buf.write(&self.f_title_size);
+            buf.write(&self.f_title_color);
+            buf.write(&self.f_title_font);
+        });
+    }
+}
+
+/*
+ * TAxis
+ * https://root.cern/doc/v638/classTAxis.html
+ *
+ * Bins are numbered like in ROOT: 0 is the underflow, 1..=fNbins the bins and fNbins + 1 the
+ * overflow. fXbins holds the fNbins + 1 edges of a variable binning and is empty otherwise.
+ * fLabels are TObjString named after the label, with the bin number in fUniqueID.
+ */
+#[derive(Debug, Default, Clone)]
+pub struct TAxis {
+    pub byte_count: u32,
+    pub version: u16,
+    pub tnamed: TNamed,
+    pub att_axis: TAttAxis,
+    pub f_nbins: i32,
+    pub f_xmin: f64,
+    pub f_xmax: f64,
+    pub f_xbins: TArrayD,
+    pub f_first: i32,
+    pub f_last: i32,
+    pub f_bits2: u16,
+    pub f_time_display: bool,
+    pub f_time_format: TString,
+    pub f_labels: Option<Arc<THashList<AnyObject>>>,
+    pub f_mod_labs: Option<Arc<TList<AnyObject>>>,
+}
+
+impl TAxis {
+    // Fixed binning, like TAxis(nbins, xmin, xmax)
+    pub fn new(nbins: i32, xmin: f64, xmax: f64) -> Self {
+        Self {
+            version: 10,
+            att_axis: TAttAxis::new(),
+            f_nbins: nbins,
+            f_xmin: xmin,
+            f_xmax: xmax,
+            ..Default::default()
+        }
+    }
+
+    // Variable binning from the nbins + 1 edges
+    pub fn with_edges(edges: &[f64]) -> Self {
+        let nbins = edges.len().saturating_sub(1);
+        Self {
+            version: 10,
+            att_axis: TAttAxis::new(),
+            f_nbins: nbins as i32,
+            f_xmin: edges.first().copied().unwrap_or(0.0),
+            f_xmax: edges.last().copied().unwrap_or(1.0),
+            f_xbins: TArrayD {
+                f_n: edges.len() as i32,
+                f_array: edges.to_vec(),
+            },
+            ..Default::default()
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.tnamed.name
+    }
+
+    pub fn title(&self) -> &str {
+        &self.tnamed.title
+    }
+
+    pub fn nbins(&self) -> usize {
+        self.f_nbins.max(0) as usize
+    }
+
+    pub fn is_variable_binning(&self) -> bool {
+        !self.f_xbins.is_empty()
+    }
+
+    // TAxis::FindFixBin, underflow 0 and overflow nbins + 1
+    pub fn find_bin(&self, x: f64) -> usize {
+        let nbins = self.nbins();
+        if x < self.f_xmin {
+            return 0;
+        }
+        if x >= self.f_xmax {
+            return nbins + 1;
+        }
+        if self.is_variable_binning() {
+            // last edge not above x
+            self.f_xbins
+                .partition_point(|edge| *edge <= x)
+                .clamp(1, nbins)
+        } else {
+            let bin = (nbins as f64 * (x - self.f_xmin) / (self.f_xmax - self.f_xmin)) as usize;
+            bin.min(nbins.saturating_sub(1)) + 1
+        }
+    }
+
+    pub fn bin_low_edge(&self, bin: usize) -> f64 {
+        if self.is_variable_binning() && bin >= 1 && bin <= self.nbins() + 1 {
+            return self.f_xbins[bin - 1];
+        }
+        let width = (self.f_xmax - self.f_xmin) / self.f_nbins as f64;
+        self.f_xmin + (bin as f64 - 1.0) * width
+    }
+
+    pub fn bin_up_edge(&self, bin: usize) -> f64 {
+        self.bin_low_edge(bin + 1)
+    }
+
+    pub fn bin_center(&self, bin: usize) -> f64 {
+        0.5 * (self.bin_low_edge(bin) + self.bin_up_edge(bin))
+    }
+
+    pub fn bin_width(&self, bin: usize) -> f64 {
+        self.bin_up_edge(bin) - self.bin_low_edge(bin)
+    }
+
+    // The nbins + 1 bin edges
+    pub fn edges(&self) -> Vec<f64> {
+        (1..=self.nbins() + 1)
+            .map(|bin| self.bin_low_edge(bin))
+            .collect()
+    }
+
+    // The label of a bin, set by TAxis::SetBinLabel
+    pub fn bin_label(&self, bin: usize) -> Option<&str> {
+        self.labels()
+            .find(|(label_bin, _)| *label_bin == bin)
+            .map(|(_, label)| label)
+    }
+
+    // TAxis::SetBinLabel, replacing the label of the bin if there is one
+    pub fn set_bin_label(&mut self, bin: usize, label: &str) {
+        let string = TObjString {
+            tobject: TObject {
+                f_uniqueid: bin as u32,
+                ..Default::default()
+            },
+            string: TString::new(label),
+            ..Default::default()
+        };
+        let labels = Arc::make_mut(self.f_labels.get_or_insert_with(Default::default));
+        labels.objects.retain(|element| {
+            !matches!(&*element.object, AnyObject::TObjString(old) if old.tobject.f_uniqueid as usize == bin)
+        });
+        labels.objects.push(TListElement {
+            object: Arc::new(AnyObject::TObjString(string)),
+            option: TString::default(),
+        });
+        labels.n_objects = labels.objects.len() as u32;
+    }
+
+    // Bin numbers and labels
+    pub fn labels(&self) -> impl Iterator<Item = (usize, &str)> {
+        self.f_labels
+            .iter()
+            .flat_map(|labels| labels.iter())
+            .filter_map(|label| match label {
+                AnyObject::TObjString(string) => {
+                    Some((string.tobject.f_uniqueid as usize, string.as_str()))
+                }
+                _ => None,
+            })
+    }
+
+    // Same number of bins and edges, as TH1::CheckConsistency requires
+    pub fn same_binning(&self, other: &TAxis) -> bool {
+        const EPSILON: f64 = 1e-10;
+        if self.f_nbins != other.f_nbins {
+            return false;
+        }
+        let close = |a: f64, b: f64| (a - b).abs() <= EPSILON * a.abs().max(b.abs()).max(1.0);
+        self.edges()
+            .iter()
+            .zip(other.edges())
+            .all(|(a, b)| close(*a, b))
+    }
+}
+
+// TAxis version 10. The labels are a THashList, the modified labels a TList.
+impl WriteObject for TAxis {
+    fn class_name(&self) -> String {
+        String::from("TAxis")
+    }
+
+    fn title(&self) -> &str {
+        &self.tnamed.title
+    }
+
+    fn write_to(&self, buf: &mut WriteBuffer) {
+        buf.versioned(10, |buf| {
+            self.tnamed.write_to(buf);
+            self.att_axis.write_to(buf);
+            buf.write(&self.f_nbins);
+            buf.write(&self.f_xmin);
+            buf.write(&self.f_xmax);
+            self.f_xbins.write_to(buf);
+            buf.write(&self.f_first);
+            buf.write(&self.f_last);
+            buf.write(&self.f_bits2);
+            buf.bool(self.f_time_display);
+            buf.string(&self.f_time_format);
+            match &self.f_labels {
+                Some(labels) => buf.object("THashList", |buf| labels.write_to(buf)),
+                None => buf.null(),
+            }
+            buf.write_object_or_null(self.f_mod_labs.as_deref());
+        });
+    }
+}
+
+impl BinRead for TAxis {
+    type Args<'a> = (ReadContext,);
+
+    fn read_options<R: Read + Seek>(
+        reader: &mut R,
+        endian: Endian,
+        args: Self::Args<'_>,
+    ) -> BinResult<Self> {
+        let (ctx,) = args;
+        let header = VersionHeader::read(reader, endian)?;
+        if header.version < 6 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("TAxis version {} is not supported", header.version),
+            )
+            .into());
+        }
+        let tnamed = TNamed::read_member(reader, endian, &ctx)?;
+        let att_axis = TAttAxis::read_member(reader, endian, &ctx)?;
+        let f_nbins = reader.read_type(endian)?;
+        let f_xmin = reader.read_type(endian)?;
+        let f_xmax = reader.read_type(endian)?;
+        let f_xbins = TArrayD::read_options(reader, endian, ())?;
+        let f_first = reader.read_type(endian)?;
+        let f_last = reader.read_type(endian)?;
+        let f_bits2 = reader.read_type(endian)?;
+        let f_time_display = read_bool(reader, endian)?;
+        let f_time_format = TString::read_options(reader, endian, ())?;
+        let f_labels = ctx.read_streamed_object(reader, endian)?;
+        let f_mod_labs = if header.version >= 10 {
+            ctx.read_streamed_object(reader, endian)?
+        } else {
+            None
+        };
+        header.skip_to_end(reader)?;
+        Ok(Self {
+            byte_count: header.byte_count,
+            version: header.version,
+            tnamed,
+            att_axis,
+            f_nbins,
+            f_xmin,
+            f_xmax,
+            f_xbins,
+            f_first,
+            f_last,
+            f_bits2,
+            f_time_display,
+            f_time_format,
+            f_labels,
+            f_mod_labs,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_axis_bins() {
+        let axis = TAxis::new(4, 0.0, 2.0);
+        assert_eq!(axis.find_bin(-0.1), 0);
+        assert_eq!(axis.find_bin(0.0), 1);
+        assert_eq!(axis.find_bin(1.2), 3);
+        assert_eq!(axis.find_bin(2.0), 5);
+        assert_eq!(axis.bin_center(2), 0.75);
+        assert_eq!(axis.edges(), [0.0, 0.5, 1.0, 1.5, 2.0]);
+
+        let variable = TAxis::with_edges(&[0.0, 1.0, 3.0, 10.0]);
+        assert_eq!(variable.find_bin(0.5), 1);
+        assert_eq!(variable.find_bin(3.0), 3);
+        assert_eq!(variable.find_bin(9.99), 3);
+        assert_eq!(variable.find_bin(10.0), 4);
+        assert_eq!(variable.bin_width(3), 7.0);
+        assert!(!variable.same_binning(&axis));
+        assert!(TAxis::with_edges(&axis.edges()).same_binning(&axis));
+    }
+}
@@ -0,0 +1,691 @@
+use crate::core::constant::{K_BYPASS_STREAMER, K_BYTECOUNTMASK, K_HAS_BYTECOUNT, K_IS_REFERENCED};
+use crate::core::read_context::ReadContext;
+use crate::objects::tobject::TObject;
+use crate::objects::tstring::TString;
+use crate::streamer::member::VersionHeader;
+use crate::streamer::registry::{ClassSchema, FieldKind, FieldSchema};
+use crate::streamer::root_streamer::{class_schema, RootStreamer, StreamerMember};
+use binrw::io::{Cursor, Read, Seek};
+use binrw::{BinRead, BinReaderExt, BinResult, Endian};
+use std::collections::HashMap;
+use std::io;
+use std::sync::Arc;
+
+/*
+ * TClonesArray
+ * https://root.cern/doc/v638/classTClonesArray.html
+ *
+ * An array of objects of one fixed class, named with its version ("THit;2") before the objects.
+ * The objects are streamed one by one (a flag byte, then the object without byte count), or
+ * member-wise when kBypassStreamer is set in fBits: every data member for all objects, then the
+ * next member (TBufferFile::ReadClones). Both are read into T through its RootStreamer schema.
+ *
+ * A tree splits a TClonesArray into one sub-branch per data member, each entry holding that member
+ * for all objects of the entry, see TClonesArray::read_split.
+ */
+#[derive(Debug)]
+pub struct TClonesArray<T> {
+    pub byte_count: u32,
+    pub version: u16,
+    pub tobject: TObject,
+    pub name: TString,
+    pub class_name: String,
+    pub class_version: u16,
+    pub f_lower_bound: i32,
+    // empty slots are written with a zero flag byte
+    pub objects: Vec<Option<T>>,
+}
+
+impl<T> Default for TClonesArray<T> {
+    fn default() -> Self {
+        Self {
+            byte_count: 0,
+            version: 0,
+            tobject: TObject::default(),
+            name: TString::default(),
+            class_name: String::new(),
+            class_version: 0,
+            f_lower_bound: 0,
+            objects: Vec::new(),
+        }
+    }
+}
+
+impl<T> TClonesArray<T>
+where
+    T: RootStreamer + for<'a> BinRead<Args<'a> = (ReadContext,)>,
+{
+    // Iterate over the filled slots
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.objects.iter().filter_map(|slot| slot.as_ref())
+    }
+
+    pub fn len(&self) -> usize {
+        self.objects.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.objects.is_empty()
+    }
+
+    pub fn is_memberwise(&self) -> bool {
+        self.tobject.f_bits & K_BYPASS_STREAMER != 0
+    }
+
+    /*
+     * The objects of one entry of a TClonesArray branch split into sub-branches. `n_objects` is
+     * the value of the count leaf (`hits_`), `branches` holds the bytes of the entry in every
+     * sub-branch by data member name (fX for `hits.fX`). Members of base classes have their own
+     * sub-branches, embedded objects must not be split further.
+     */
+    pub fn read_split(
+        n_objects: usize,
+        class_version: u16,
+        branches: &HashMap<String, Vec<u8>>,
+    ) -> BinResult<Vec<T>> {
+        let mut source = SplitSource {
+            branches: branches
+                .iter()
+                .map(|(name, bytes)| (name.as_str(), Cursor::new(bytes.as_slice())))
+                .collect(),
+        };
+        read_memberwise(n_objects, class_version, &mut source, Endian::Big)
+    }
+}
+
+impl<T> BinRead for TClonesArray<T>
+where
+    T: RootStreamer + for<'a> BinRead<Args<'a> = (ReadContext,)>,
+{
+    type Args<'a> = (ReadContext,);
+
+    fn read_options<R: Read + Seek>(
+        reader: &mut R,
+        endian: Endian,
+        args: Self::Args<'_>,
+    ) -> BinResult<Self> {
+        let (ctx,) = args;
+        let header = VersionHeader::read(reader, endian)?;
+        let tobject = if header.version > 3 {
+            TObject::read_options(reader, endian, ())?
+        } else {
+            TObject::default()
+        };
+        let name = if header.version > 1 {
+            TString::read_options(reader, endian, ())?
+        } else {
+            TString::default()
+        };
+        let class = TString::read_options(reader, endian, ())?;
+        let (class_name, class_version) = match class.split_once(';') {
+            Some((name, version)) => (name.to_string(), version.parse().unwrap_or(0)),
+            None => (class.to_string(), 0),
+        };
+        if class_name != T::CLASS_NAME {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "TClonesArray of {} cannot be read as {}",
+                    class_name,
+                    T::CLASS_NAME
+                ),
+            )
+            .into());
+        }
+        // a negative count is still accepted for backward compatibility
+        let n_objects = reader.read_type::<i32>(endian)?.unsigned_abs() as usize;
+        let f_lower_bound = reader.read_type(endian)?;
+
+        let objects = if tobject.f_bits & K_BYPASS_STREAMER != 0 {
+            let mut source = BufferSource { reader };
+            read_memberwise(n_objects, class_version, &mut source, endian)?
+                .into_iter()
+                .map(Some)
+                .collect()
+        } else {
+            let mut objects = Vec::with_capacity(n_objects);
+            for _ in 0..n_objects {
+                let filled: u8 = reader.read_type(endian)?;
+                objects.push(if filled != 0 {
+                    Some(T::read_options(reader, endian, (ctx.clone(),))?)
+                } else {
+                    None
+                });
+            }
+            objects
+        };
+        header.skip_to_end(reader)?;
+
+        Ok(Self {
+            byte_count: header.byte_count,
+            version: header.version,
+            tobject,
+            name,
+            class_name,
+            class_version,
+            f_lower_bound,
+            objects,
+        })
+    }
+}
+
+// TClonesArray has its own streamer, it has no members in the schema
+impl<T> RootStreamer for TClonesArray<T>
+where
+    T: RootStreamer + for<'a> BinRead<Args<'a> = (ReadContext,)>,
+{
+    const CLASS_NAME: &'static str = "TClonesArray";
+    const CLASS_VERSION: u16 = 4;
+    const CHECKSUM: u32 = 0;
+
+    fn class_schema() -> Arc<ClassSchema> {
+        class_schema::<Self>(Vec::new)
+    }
+}
+
+impl<T> StreamerMember for TClonesArray<T>
+where
+    T: RootStreamer + for<'a> BinRead<Args<'a> = (ReadContext,)>,
+{
+    fn read_member<R: Read + Seek>(
+        reader: &mut R,
+        endian: Endian,
+        ctx: &ReadContext,
+    ) -> BinResult<Self> {
+        Self::read_options(reader, endian, (ctx.clone(),))
+    }
+
+    fn field_kind() -> FieldKind {
+        FieldKind::Object(Self::class_schema())
+    }
+}
+
+trait ReadSeek: Read + Seek {}
+
+impl<R: Read + Seek> ReadSeek for R {}
+
+// Where the values of a data member come from when the objects are read member-wise
+trait MemberSource {
+    fn member(&mut self, name: &str) -> BinResult<&mut dyn ReadSeek>;
+
+    // split sub-branches hold TObject as fUniqueID and fBits, a buffer holds whole TObjects
+    fn is_split(&self) -> bool;
+}
+
+// All members one after the other in the buffer of the TClonesArray
+struct BufferSource<'r, R> {
+    reader: &'r mut R,
+}
+
+impl<R: Read + Seek> MemberSource for BufferSource<'_, R> {
+    fn member(&mut self, _name: &str) -> BinResult<&mut dyn ReadSeek> {
+        Ok(self.reader)
+    }
+
+    fn is_split(&self) -> bool {
+        false
+    }
+}
+
+// Every member in the entry of its own sub-branch
+struct SplitSource<'a> {
+    branches: HashMap<&'a str, Cursor<&'a [u8]>>,
+}
+
+impl MemberSource for SplitSource<'_> {
+    fn member(&mut self, name: &str) -> BinResult<&mut dyn ReadSeek> {
+        match self.branches.get_mut(name) {
+            Some(cursor) => Ok(cursor),
+            None => Err(invalid_data(format!("no sub-branch for member {}", name))),
+        }
+    }
+
+    fn is_split(&self) -> bool {
+        true
+    }
+}
+
+/*
+ * Member-wise objects are rearranged into the bytes every object has when streamed on its own,
+ * with byte counts and versions in front of the object and its base classes, and then read by T.
+ * Counter members are kept per object for the `T* fArray; //[fN]` members that follow them.
+ */
+#[derive(Default)]
+struct ObjectBytes {
+    bytes: Vec<u8>,
+    counters: HashMap<String, i64>,
+}
+
+fn read_memberwise<T>(
+    n_objects: usize,
+    class_version: u16,
+    source: &mut dyn MemberSource,
+    endian: Endian,
+) -> BinResult<Vec<T>>
+where
+    T: RootStreamer + for<'a> BinRead<Args<'a> = (ReadContext,)>,
+{
+    let schema = T::class_schema();
+    let mut objects: Vec<ObjectBytes> = (0..n_objects).map(|_| ObjectBytes::default()).collect();
+    read_members(&schema.fields, &mut objects, source, endian)?;
+    let version = if class_version != 0 {
+        class_version
+    } else {
+        T::CLASS_VERSION
+    };
+    objects
+        .into_iter()
+        .map(|object| {
+            let bytes = with_header(version, &object.bytes, endian);
+            T::read_options(&mut Cursor::new(bytes), endian, (ReadContext::new(),))
+        })
+        .collect()
+}
+
+fn read_members(
+    fields: &[FieldSchema],
+    objects: &mut [ObjectBytes],
+    source: &mut dyn MemberSource,
+    endian: Endian,
+) -> BinResult<()> {
+    for field in fields {
+        match &field.kind {
+            FieldKind::Base(class) if class.name == "TObject" && source.is_split() => {
+                let mut unique_ids = Vec::with_capacity(objects.len());
+                let mut reader = source.member("fUniqueID")?;
+                for _ in 0..objects.len() {
+                    unique_ids.push(reader.read_type::<u32>(endian)?);
+                }
+                let mut reader = source.member("fBits")?;
+                for (object, unique_id) in objects.iter_mut().zip(unique_ids) {
+                    let bits: u32 = reader.read_type(endian)?;
+                    push_value(&mut object.bytes, 1u16, endian);
+                    push_value(&mut object.bytes, unique_id, endian);
+                    push_value(&mut object.bytes, bits, endian);
+                    if bits & K_IS_REFERENCED != 0 {
+                        let pidf: u16 = reader.read_type(endian)?;
+                        push_value(&mut object.bytes, pidf, endian);
+                    }
+                }
+            }
+            // TObject and TNamed bases have their own streamer and are read object by object
+            FieldKind::Base(class)
+                if class.fields.is_empty() || class.name == "TObject" || class.name == "TNamed" =>
+            {
+                let reader = source.member(&field.name)?;
+                let kind = FieldKind::Object(class.clone());
+                for object in objects.iter_mut() {
+                    copy_value(&kind, &field.name, reader, endian, object)?;
+                }
+            }
+            FieldKind::Base(class) => {
+                let mut bases: Vec<ObjectBytes> =
+                    (0..objects.len()).map(|_| ObjectBytes::default()).collect();
+                read_members(&class.fields, &mut bases, source, endian)?;
+                for (object, base) in objects.iter_mut().zip(bases) {
+                    let bytes = with_header(class.version, &base.bytes, endian);
+                    object.bytes.extend_from_slice(&bytes);
+                }
+            }
+            kind => {
+                let reader = source.member(&field.name)?;
+                for object in objects.iter_mut() {
+                    copy_value(kind, &field.name, reader, endian, object)?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+// Copy one value of a data member of one object
+fn copy_value(
+    kind: &FieldKind,
+    name: &str,
+    mut reader: &mut dyn ReadSeek,
+    endian: Endian,
+    object: &mut ObjectBytes,
+) -> BinResult<()> {
+    match kind {
+        FieldKind::Bool | FieldKind::I8 | FieldKind::U8 => {
+            let value = reader.read_type::<i8>(endian)?;
+            copy_counter(name, value as i64, value, endian, object)
+        }
+        FieldKind::I16 => {
+            let value = reader.read_type::<i16>(endian)?;
+            copy_counter(name, value as i64, value, endian, object)
+        }
+        FieldKind::U16 => {
+            let value = reader.read_type::<u16>(endian)?;
+            copy_counter(name, value as i64, value, endian, object)
+        }
+        FieldKind::I32 => {
+            let value = reader.read_type::<i32>(endian)?;
+            copy_counter(name, value as i64, value, endian, object)
+        }
+        FieldKind::U32 => {
+            let value = reader.read_type::<u32>(endian)?;
+            copy_counter(name, value as i64, value, endian, object)
+        }
+        FieldKind::I64 | FieldKind::U64 => {
+            let value = reader.read_type::<i64>(endian)?;
+            copy_counter(name, value, value, endian, object)
+        }
+        FieldKind::F32 => copy_bytes(reader, 4, object),
+        FieldKind::F64 => copy_bytes(reader, 8, object),
+        // an UInt_t or a float, or the exponent and mantissa of a truncated value
+        FieldKind::Float16(packed) | FieldKind::Double32(packed) => {
+            copy_bytes(reader, packed.size(), object)
+        }
+        FieldKind::TString => {
+            let length: u8 = reader.read_type(endian)?;
+            object.bytes.push(length);
+            let length = if length == 255 {
+                let length: u32 = reader.read_type(endian)?;
+                push_value(&mut object.bytes, length, endian);
+                length
+            } else {
+                length as u32
+            };
+            copy_bytes(reader, length as usize, object)
+        }
+        FieldKind::CharStar => {
+            let length: i32 = reader.read_type(endian)?;
+            push_value(&mut object.bytes, length, endian);
+            copy_bytes(reader, length.max(0) as usize, object)
+        }
+        FieldKind::FixedArray { elem, count } => {
+            for _ in 0..*count {
+                copy_value(elem, name, reader, endian, object)?;
+            }
+            Ok(())
+        }
+        FieldKind::BasicPointer { elem, count } => {
+            let is_array: u8 = reader.read_type(endian)?;
+            object.bytes.push(is_array);
+            if is_array == 0 {
+                return Ok(());
+            }
+            let n = *object.counters.get(count).ok_or_else(|| {
+                invalid_data(format!(
+                    "counter {} of {} is not read before it",
+                    count, name
+                ))
+            })?;
+            for _ in 0..n.max(0) {
+                copy_value(elem, name, reader, endian, object)?;
+            }
+            Ok(())
+        }
+        FieldKind::Object(class) | FieldKind::Base(class) if class.name == "TObject" => {
+            let version: u16 = reader.read_type(endian)?;
+            let unique_id: u32 = reader.read_type(endian)?;
+            let bits: u32 = reader.read_type(endian)?;
+            push_value(&mut object.bytes, version, endian);
+            push_value(&mut object.bytes, unique_id, endian);
+            push_value(&mut object.bytes, bits, endian);
+            if bits & K_IS_REFERENCED != 0 {
+                copy_bytes(reader, 2, object)?;
+            }
+            Ok(())
+        }
+        // objects and collections streamed in place carry their byte count
+        FieldKind::Object(_)
+        | FieldKind::Base(_)
+        | FieldKind::StlVector(_)
+        | FieldKind::CountedArray { .. } => {
+            let byte_count: u32 = reader.read_type(endian)?;
+            if byte_count & K_HAS_BYTECOUNT == 0 {
+                return Err(invalid_data(format!(
+                    "member {} has no byte count and cannot be read member-wise",
+                    name
+                )));
+            }
+            push_value(&mut object.bytes, byte_count, endian);
+            copy_bytes(reader, (byte_count & K_BYTECOUNTMASK) as usize, object)
+        }
+        FieldKind::ObjectPointer(_) | FieldKind::Unknown(_) => Err(invalid_data(format!(
+            "member {} ({}) cannot be read member-wise",
+            name,
+            kind.type_name()
+        ))),
+    }
+}
+
+fn copy_counter<V: ToBytes>(
+    name: &str,
+    counter: i64,
+    value: V,
+    endian: Endian,
+    object: &mut ObjectBytes,
+) -> BinResult<()> {
+    object.counters.insert(name.to_string(), counter);
+    push_value(&mut object.bytes, value, endian);
+    Ok(())
+}
+
+fn copy_bytes(reader: &mut dyn ReadSeek, n: usize, object: &mut ObjectBytes) -> BinResult<()> {
+    let start = object.bytes.len();
+    object.bytes.resize(start + n, 0);
+    reader.read_exact(&mut object.bytes[start..])?;
+    Ok(())
+}
+
+// Byte count and version in front of the members of an object
+fn with_header(version: u16, body: &[u8], endian: Endian) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(body.len() + 6);
+    push_value(
+        &mut bytes,
+        K_HAS_BYTECOUNT | (body.len() as u32 + 2),
+        endian,
+    );
+    push_value(&mut bytes, version, endian);
+    bytes.extend_from_slice(body);
+    bytes
+}
+
+trait ToBytes {
+    fn to_bytes(self, endian: Endian) -> Vec<u8>;
+}
+
+macro_rules! to_bytes {
+    ($($rust_type:ty),*) => {
+        $(
+            impl ToBytes for $rust_type {
+                fn to_bytes(self, endian: Endian) -> Vec<u8> {
+                    match endian {
+                        Endian::Big => self.to_be_bytes().to_vec(),
+                        Endian::Little => self.to_le_bytes().to_vec(),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+to_bytes!(i8, i16, u16, i32, u32, i64);
+
+fn push_value<V: ToBytes>(bytes: &mut Vec<u8>, value: V, endian: Endian) {
+    bytes.extend_from_slice(&value.to_bytes(endian));
+}
+
+fn invalid_data(message: String) -> binrw::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::streamer::root_streamer::{Double32, Float16};
+    use crate::RootStreamer;
+
+    #[derive(Debug, RootStreamer)]
+    #[root(class = "THit", version = 2)]
+    struct THit {
+        #[root(base)]
+        tobject: TObject,
+        f_x: f32,
+        f_detector: TString,
+        f_ncells: i32,
+    }
+
+    #[derive(Debug, RootStreamer)]
+    #[root(class = "TCell", version = 1)]
+    struct TCell {
+        #[root(range = "[-1,1,8]")]
+        f_e: Float16,
+        #[root(range = "[0,0,10]")]
+        f_t: Double32,
+        f_id: i32,
+    }
+
+    fn tobject(bits: u32) -> Vec<u8> {
+        let mut bytes = 1u16.to_be_bytes().to_vec();
+        bytes.extend_from_slice(&0u32.to_be_bytes());
+        bytes.extend_from_slice(&bits.to_be_bytes());
+        bytes
+    }
+
+    fn tstring(s: &str) -> Vec<u8> {
+        let mut bytes = vec![s.len() as u8];
+        bytes.extend_from_slice(s.as_bytes());
+        bytes
+    }
+
+    const HITS: [(f32, &str, i32); 2] = [(1.5, "ecal", 3), (-2.0, "hcal", 7)];
+
+    // TClonesArray "hits" of two THit, streamed one by one or member-wise
+    fn clones_array(memberwise: bool) -> Vec<u8> {
+        let bits = if memberwise { K_BYPASS_STREAMER } else { 0 };
+        let mut body = 4u16.to_be_bytes().to_vec();
+        body.extend(tobject(bits));
+        body.extend(tstring("hits"));
+        body.extend(tstring("THit;2"));
+        body.extend_from_slice(&2i32.to_be_bytes());
+        body.extend_from_slice(&0i32.to_be_bytes());
+        if memberwise {
+            body.extend(tobject(0));
+            body.extend(tobject(0));
+            for (x, _, _) in HITS {
+                body.extend_from_slice(&x.to_be_bytes());
+            }
+            for (_, detector, _) in HITS {
+                body.extend(tstring(detector));
+            }
+            for (_, _, ncells) in HITS {
+                body.extend_from_slice(&ncells.to_be_bytes());
+            }
+        } else {
+            for (x, detector, ncells) in HITS {
+                let mut hit = tobject(0);
+                hit.extend_from_slice(&x.to_be_bytes());
+                hit.extend(tstring(detector));
+                hit.extend_from_slice(&ncells.to_be_bytes());
+                body.push(1);
+                body.extend(with_header(2, &hit, Endian::Big));
+            }
+        }
+        let mut bytes = (K_HAS_BYTECOUNT | body.len() as u32).to_be_bytes().to_vec();
+        bytes.extend(body);
+        bytes
+    }
+
+    fn values<'a>(hits: impl Iterator<Item = &'a THit>) -> Vec<(f32, &'a str, i32)> {
+        hits.map(|hit| {
+            assert_eq!(hit.tobject.version, 1);
+            (hit.f_x, hit.f_detector.as_str(), hit.f_ncells)
+        })
+        .collect()
+    }
+
+    #[test]
+    fn test_read_clones_array() {
+        for memberwise in [false, true] {
+            let array = TClonesArray::<THit>::read_be_args(
+                &mut Cursor::new(clones_array(memberwise)),
+                (ReadContext::new(),),
+            )
+            .expect("Failed to read TClonesArray");
+            assert_eq!(array.version, 4);
+            assert_eq!(array.name, "hits");
+            assert_eq!(array.class_name, "THit");
+            assert_eq!(array.class_version, 2);
+            assert_eq!(array.is_memberwise(), memberwise);
+            assert_eq!(array.len(), 2);
+            assert_eq!(values(array.iter()), HITS);
+        }
+
+        let mut bytes = clones_array(false);
+        bytes[22] = b'X';
+        assert!(
+            TClonesArray::<THit>::read_be_args(&mut Cursor::new(bytes), (ReadContext::new(),))
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_read_split_entry() {
+        let mut branches: HashMap<String, Vec<u8>> = HashMap::new();
+        for (x, detector, ncells) in HITS {
+            branches
+                .entry("fUniqueID".to_string())
+                .or_default()
+                .extend_from_slice(&0u32.to_be_bytes());
+            branches
+                .entry("fBits".to_string())
+                .or_default()
+                .extend_from_slice(&0u32.to_be_bytes());
+            branches
+                .entry("fX".to_string())
+                .or_default()
+                .extend_from_slice(&x.to_be_bytes());
+            branches
+                .entry("fDetector".to_string())
+                .or_default()
+                .extend(tstring(detector));
+            branches
+                .entry("fNcells".to_string())
+                .or_default()
+                .extend_from_slice(&ncells.to_be_bytes());
+        }
+        let hits = TClonesArray::<THit>::read_split(2, 2, &branches)
+            .expect("Failed to read split TClonesArray");
+        assert_eq!(values(hits.iter()), HITS);
+
+        branches.remove("fNcells");
+        assert!(TClonesArray::<THit>::read_split(2, 2, &branches).is_err());
+    }
+
+    #[test]
+    fn test_read_packed_memberwise() {
+        let mut body = 4u16.to_be_bytes().to_vec();
+        body.extend(tobject(K_BYPASS_STREAMER));
+        body.extend(tstring("cells"));
+        body.extend(tstring("TCell;1"));
+        body.extend_from_slice(&2i32.to_be_bytes());
+        body.extend_from_slice(&0i32.to_be_bytes());
+        // fE: 0.5 and -0.5 over [-1,1] in 8 bits, 4 bytes each
+        for scaled in [192u32, 64] {
+            body.extend_from_slice(&scaled.to_be_bytes());
+        }
+        // fT: 1.5 and -1.5 with 10 bits of mantissa, 3 bytes each
+        for sign in [0u16, 0x800] {
+            body.push(127);
+            body.extend_from_slice(&(sign | 0x200).to_be_bytes());
+        }
+        for id in [3i32, 4] {
+            body.extend_from_slice(&id.to_be_bytes());
+        }
+        let mut bytes = (K_HAS_BYTECOUNT | body.len() as u32).to_be_bytes().to_vec();
+        bytes.extend(body);
+
+        let cells =
+            TClonesArray::<TCell>::read_be_args(&mut Cursor::new(bytes), (ReadContext::new(),))
+                .expect("Failed to read TClonesArray");
+        let values: Vec<(f32, f64, i32)> = cells
+            .iter()
+            .map(|cell| (cell.f_e.0, cell.f_t.0, cell.f_id))
+            .collect();
+        assert_eq!(values, [(0.5, 1.5, 3), (-0.5, -1.5, 4)]);
+    }
+}
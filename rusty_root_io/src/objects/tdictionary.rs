@@ -1,7 +1,7 @@
 use crate::core::utils::ReaderDynWidth;
 use crate::objects::tkey::TKey;
-use binrw::io::{Read, Seek};
-use binrw::{binread, BinRead, BinReaderExt, BinResult, Endian};
+use binrw::io::{Read, Seek, Write};
+use binrw::{binread, BinRead, BinReaderExt, BinResult, BinWrite, BinWriterExt, Endian};
 
 /*
 * ----------TKey--------------
@@ -35,25 +35,25 @@ use binrw::{binread, BinRead, BinReaderExt, BinResult, Endian};
       32->47 [44->59] UUID      = Universally Unique Identifier                                 TUUID::fTimeLow through fNode[6]
       48->59          Extra space to allow SeekKeys to become 64 bit without moving this header
 */
-#[derive(Debug)]
+#[derive(Debug, Default, Clone)]
 pub struct TDictData {
-    version: u16,
-    datime_c: u32,
-    datime_m: u32,
-    n_bytes_keys: u32,
-    n_bytes_name: u32,
-    seek_dir: u64,
-    seek_parent: u64,
-    seek_keys: u64,
-    uuid_vers: u16,
-    uuid: [u8; 16],
+    pub version: u16,
+    pub datime_c: u32,
+    pub datime_m: u32,
+    pub n_bytes_keys: u32,
+    pub n_bytes_name: u32,
+    pub seek_dir: u64,
+    pub seek_parent: u64,
+    pub seek_keys: u64,
+    pub uuid_vers: u16,
+    pub uuid: [u8; 16],
 }
 
 #[binread]
 #[derive(Debug)]
 pub struct TDictionary {
-    tkey: TKey,
-    data: TDictData,
+    pub tkey: TKey,
+    pub data: TDictData,
 }
 
 impl BinRead for TDictData {
@@ -98,13 +98,70 @@ impl BinRead for TDictData {
     }
 }
 
+// TDirectoryFile::FillBuffer, always TDictData::SIZEOF bytes
+impl BinWrite for TDictData {
+    type Args<'a> = ();
+
+    fn write_options<W: Write + Seek>(
+        &self,
+        writer: &mut W,
+        endian: Endian,
+        _args: Self::Args<'_>,
+    ) -> BinResult<()> {
+        writer.write_type(&self.version, endian)?;
+        writer.write_type(&self.datime_c, endian)?;
+        writer.write_type(&self.datime_m, endian)?;
+        writer.write_type(&self.n_bytes_keys, endian)?;
+        writer.write_type(&self.n_bytes_name, endian)?;
+        let large = self.is_large();
+        for seek in [self.seek_dir, self.seek_parent, self.seek_keys] {
+            if large {
+                writer.write_type(&seek, endian)?;
+            } else {
+                writer.write_type(&(seek as u32), endian)?;
+            }
+        }
+        writer.write_type(&self.uuid_vers, endian)?;
+        writer.write_all(&self.uuid)?;
+        if !large {
+            writer.write_all(&[0u8; 12])?;
+        }
+        Ok(())
+    }
+}
+
 impl TDictData {
+    // TDirectoryFile::Sizeof, with the room for 64 bit seeks
+    pub const SIZEOF: u32 = 60;
+
+    // Seeks are written as 64 bit integers from version 1000 on
+    pub fn is_large(&self) -> bool {
+        matches!(
+            ReaderDynWidth::from_tkey_version(self.version),
+            ReaderDynWidth::Off64
+        )
+    }
+
     pub fn read_from<R: Read + Seek>(reader: &mut R, offset: u64) -> BinResult<Self> {
         reader.seek(binrw::io::SeekFrom::Start(offset))?;
         Self::read_options(reader, Endian::Big, ())
     }
 }
 
+impl BinWrite for TDictionary {
+    type Args<'a> = ();
+
+    fn write_options<W: Write + Seek>(
+        &self,
+        writer: &mut W,
+        endian: Endian,
+        _args: Self::Args<'_>,
+    ) -> BinResult<()> {
+        self.tkey.write_options(writer, endian, ())?;
+        self.data.write_options(writer, endian, ())
+    }
+}
+
 impl TDictionary {
     pub fn read_from<R: Read + Seek>(reader: &mut R, offset: u64) -> BinResult<Self> {
         reader.seek(binrw::io::SeekFrom::Start(offset))?;
@@ -0,0 +1,679 @@
+use crate::core::read_context::ReadContext;
+use crate::objects::any_object::AnyObject;
+use crate::objects::tatt::{TAttFill, TAttLine, TAttMarker};
+use crate::objects::th1::TH1;
+use crate::objects::tlist::TList;
+use crate::objects::tnamed::TNamed;
+use crate::streamer::member::{read_stl_pairs, VersionHeader};
+use crate::streamer::root_streamer::StreamerMember;
+use binrw::io::{Read, Seek};
+use binrw::{BinRead, BinReaderExt, BinResult, Endian};
+use statrs::distribution::{Beta, Binomial, ContinuousCDF, Discrete, DiscreteCDF, Normal};
+use std::sync::Arc;
+
+// TEfficiency::EStatusBits in TObject::fBits
+const K_POSTERIOR_MODE: u32 = 1 << 15;
+const K_USE_BIN_PRIOR: u32 = 1 << 17;
+const K_USE_WEIGHTS: u32 = 1 << 18;
+
+// TEfficiency::EStatOption, how the confidence intervals are computed
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum StatOption {
+    #[default]
+    ClopperPearson,
+    Normal,
+    Wilson,
+    AgrestiCoull,
+    FeldmanCousins,
+    Jeffrey,  // Bayesian with the Beta(0.5, 0.5) prior
+    Uniform,  // Bayesian with the Beta(1, 1) prior
+    Bayesian, // Bayesian with the prior of fBeta_alpha and fBeta_beta
+    MidP,
+}
+
+impl StatOption {
+    pub fn from_i32(value: i32) -> Option<Self> {
+        Some(match value {
+            0 => Self::ClopperPearson,
+            1 => Self::Normal,
+            2 => Self::Wilson,
+            3 => Self::AgrestiCoull,
+            4 => Self::FeldmanCousins,
+            5 => Self::Jeffrey,
+            6 => Self::Uniform,
+            7 => Self::Bayesian,
+            8 => Self::MidP,
+            _ => return None,
+        })
+    }
+
+    pub fn is_bayesian(self) -> bool {
+        matches!(self, Self::Jeffrey | Self::Uniform | Self::Bayesian)
+    }
+}
+
+// Efficiency of a bin with the distances to the lower and upper bounds of its interval
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct EfficiencyPoint {
+    pub efficiency: f64,
+    pub error_low: f64,
+    pub error_up: f64,
+}
+
+/*
+ * TEfficiency
+ * https://root.cern/doc/v638/classTEfficiency.html
+ *
+ * The efficiency per bin from the histograms of the passed and of all events. The interval
+ * follows fStatisticOption and fConfLevel like TEfficiency::GetEfficiencyErrorLow/Up, or any
+ * other option with point_with. Weighted efficiencies (kUseWeights) use the normal approximation
+ * or the Bayesian interval with the effective number of entries, as ROOT does. The shortest
+ * Bayesian interval is not supported, the central one is used.
+ */
+#[derive(Debug, Default)]
+pub struct TEfficiency {
+    pub byte_count: u32,
+    pub version: u16,
+    pub tnamed: TNamed,
+    pub att_line: TAttLine,
+    pub att_fill: TAttFill,
+    pub att_marker: TAttMarker,
+    pub f_beta_alpha: f64,
+    pub f_beta_beta: f64,
+    pub f_beta_bin_params: Vec<(f64, f64)>,
+    pub f_conf_level: f64,
+    pub f_functions: TList<AnyObject>,
+    pub f_passed_histogram: Option<Arc<AnyObject>>,
+    pub f_statistic_option: i32,
+    pub f_total_histogram: Option<Arc<AnyObject>>,
+    pub f_weight: f64,
+}
+
+impl TEfficiency {
+    pub fn name(&self) -> &str {
+        &self.tnamed.name
+    }
+
+    pub fn passed(&self) -> Option<&TH1> {
+        self.f_passed_histogram
+            .as_deref()
+            .and_then(AnyObject::as_th1)
+    }
+
+    pub fn total(&self) -> Option<&TH1> {
+        self.f_total_histogram
+            .as_deref()
+            .and_then(AnyObject::as_th1)
+    }
+
+    pub fn statistic_option(&self) -> StatOption {
+        StatOption::from_i32(self.f_statistic_option).unwrap_or_default()
+    }
+
+    fn has_bit(&self, bit: u32) -> bool {
+        self.tnamed.tobject.f_bits & bit != 0
+    }
+
+    pub fn is_weighted(&self) -> bool {
+        self.has_bit(K_USE_WEIGHTS)
+    }
+
+    // Parameters of the Beta prior of a bin, TEfficiency::GetBetaAlpha/GetBetaBeta
+    pub fn beta_prior(&self, bin: usize) -> (f64, f64) {
+        match self.f_beta_bin_params.get(bin) {
+            Some(params) if self.has_bit(K_USE_BIN_PRIOR) => *params,
+            _ => (self.f_beta_alpha, self.f_beta_beta),
+        }
+    }
+
+    // Number of cells, the bins of all axes with underflow and overflow
+    pub fn ncells(&self) -> usize {
+        self.total().map_or(0, |total| total.contents().len())
+    }
+
+    // TEfficiency::GetEfficiency
+    pub fn efficiency(&self, bin: usize) -> f64 {
+        self.point(bin).efficiency
+    }
+
+    pub fn efficiency_error_low(&self, bin: usize) -> f64 {
+        self.point(bin).error_low
+    }
+
+    pub fn efficiency_error_up(&self, bin: usize) -> f64 {
+        self.point(bin).error_up
+    }
+
+    // Efficiency and errors of a cell with fStatisticOption
+    pub fn point(&self, bin: usize) -> EfficiencyPoint {
+        // TEfficiency::SetStatisticOption also stores the prior of kBJeffrey and kBUniform
+        let (alpha, beta) = self.beta_prior(bin);
+        self.compute(bin, self.statistic_option(), alpha, beta)
+    }
+
+    // Efficiency and errors of a cell with another statistic option, Jeffrey and Uniform with
+    // their own prior and Bayesian with the stored one
+    pub fn point_with(&self, bin: usize, option: StatOption) -> EfficiencyPoint {
+        let (alpha, beta) = match option {
+            StatOption::Jeffrey => (0.5, 0.5),
+            StatOption::Uniform => (1.0, 1.0),
+            _ => self.beta_prior(bin),
+        };
+        self.compute(bin, option, alpha, beta)
+    }
+
+    // The points of all bins 1..=nbins of a one dimensional efficiency
+    pub fn points(&self) -> Vec<EfficiencyPoint> {
+        let nbins = self.total().map_or(0, TH1::nbins_x);
+        (1..=nbins).map(|bin| self.point(bin)).collect()
+    }
+
+    fn compute(&self, bin: usize, option: StatOption, alpha: f64, beta: f64) -> EfficiencyPoint {
+        let (Some(passed), Some(total)) = (self.passed(), self.total()) else {
+            return EfficiencyPoint::default();
+        };
+        let level = self.f_conf_level;
+        let (pw, tw) = (passed.bin_content(bin), total.bin_content(bin));
+        let mode = self.has_bit(K_POSTERIOR_MODE);
+
+        if self.is_weighted() {
+            let (pw2, tw2) = (passed.bin_sumw2(bin), total.bin_sumw2(bin));
+            if option.is_bayesian() {
+                if tw2 <= 0.0 {
+                    let efficiency = if tw != 0.0 { pw / tw } else { 0.0 };
+                    return EfficiencyPoint {
+                        efficiency,
+                        ..Default::default()
+                    };
+                }
+                // tw / tw2 renormalises the weights to the effective number of entries
+                let norm = tw / tw2;
+                let a = pw * norm + alpha;
+                let b = (tw - pw) * norm + beta;
+                let efficiency = beta_estimate(a, b, mode);
+                return EfficiencyPoint {
+                    efficiency,
+                    error_low: efficiency - beta_central_interval(level, a, b, false),
+                    error_up: beta_central_interval(level, a, b, true) - efficiency,
+                };
+            }
+            // only the normal approximation is defined for weights
+            if tw <= 0.0 {
+                return EfficiencyPoint::default();
+            }
+            let efficiency = pw / tw;
+            let variance =
+                (pw2 * (1.0 - 2.0 * efficiency) + tw2 * efficiency * efficiency) / (tw * tw);
+            let delta = standard_normal_quantile(0.5 * (1.0 + level)) * variance.sqrt();
+            return EfficiencyPoint {
+                efficiency,
+                error_low: if efficiency - delta < 0.0 {
+                    efficiency
+                } else {
+                    delta
+                },
+                error_up: if efficiency + delta > 1.0 {
+                    1.0 - efficiency
+                } else {
+                    delta
+                },
+            };
+        }
+
+        if option.is_bayesian() {
+            let a = pw + alpha;
+            let b = tw - pw + beta;
+            let efficiency = beta_estimate(a, b, mode);
+            return EfficiencyPoint {
+                efficiency,
+                error_low: efficiency - bayesian(tw, pw, level, alpha, beta, false),
+                error_up: bayesian(tw, pw, level, alpha, beta, true) - efficiency,
+            };
+        }
+        let efficiency = if tw != 0.0 { pw / tw } else { 0.0 };
+        let bound = |upper| match option {
+            StatOption::Normal => normal(tw, pw, level, upper),
+            StatOption::Wilson => wilson(tw, pw, level, upper),
+            StatOption::AgrestiCoull => agresti_coull(tw, pw, level, upper),
+            StatOption::FeldmanCousins => feldman_cousins(tw, pw, level, upper),
+            StatOption::MidP => mid_p_interval(tw, pw, level, upper),
+            _ => clopper_pearson(tw, pw, level, upper),
+        };
+        EfficiencyPoint {
+            efficiency,
+            error_low: efficiency - bound(false),
+            error_up: bound(true) - efficiency,
+        }
+    }
+}
+
+// Posterior mean, or mode with kPosteriorMode (TEfficiency::BetaMean/BetaMode)
+fn beta_estimate(a: f64, b: f64, mode: bool) -> f64 {
+    if !mode {
+        return a / (a + b);
+    }
+    if a <= 0.0 || b <= 0.0 {
+        return 0.0;
+    }
+    // the mode sits on the boundary of [0, 1], on the side of the larger parameter
+    if a <= 1.0 || b <= 1.0 {
+        return if a < b {
+            0.0
+        } else if a > b {
+            1.0
+        } else {
+            0.5
+        };
+    }
+    (a - 1.0) / (a + b - 2.0)
+}
+
+fn standard_normal_quantile(p: f64) -> f64 {
+    Normal::new(0.0, 1.0)
+        .map(|normal| normal.inverse_cdf(p))
+        .unwrap_or(f64::NAN)
+}
+
+fn beta_quantile(p: f64, a: f64, b: f64) -> f64 {
+    Beta::new(a, b)
+        .map(|beta| beta.inverse_cdf(p))
+        .unwrap_or(f64::NAN)
+}
+
+// TEfficiency::ClopperPearson, the exact frequentist interval
+pub fn clopper_pearson(total: f64, passed: f64, level: f64, upper: bool) -> f64 {
+    let alpha = (1.0 - level) / 2.0;
+    if upper {
+        if passed >= total {
+            1.0
+        } else {
+            beta_quantile(1.0 - alpha, passed + 1.0, total - passed)
+        }
+    } else if passed <= 0.0 {
+        0.0
+    } else {
+        beta_quantile(alpha, passed, total - passed + 1.0)
+    }
+}
+
+// TEfficiency::Normal, the binomial error of the efficiency
+pub fn normal(total: f64, passed: f64, level: f64, upper: bool) -> f64 {
+    if total == 0.0 {
+        return if upper { 1.0 } else { 0.0 };
+    }
+    let average = passed / total;
+    let sigma = (average * (1.0 - average) / total).sqrt();
+    let delta = standard_normal_quantile(0.5 * (1.0 + level)) * sigma;
+    if upper {
+        (average + delta).min(1.0)
+    } else {
+        (average - delta).max(0.0)
+    }
+}
+
+// TEfficiency::Wilson, the score interval
+pub fn wilson(total: f64, passed: f64, level: f64, upper: bool) -> f64 {
+    if total == 0.0 {
+        return if upper { 1.0 } else { 0.0 };
+    }
+    let average = passed / total;
+    let kappa = standard_normal_quantile(0.5 * (1.0 + level));
+    let kappa2 = kappa * kappa;
+    let mode = (passed + 0.5 * kappa2) / (total + kappa2);
+    let delta =
+        kappa / (total + kappa2) * (total * average * (1.0 - average) + kappa2 / 4.0).sqrt();
+    if upper {
+        (mode + delta).min(1.0)
+    } else {
+        (mode - delta).max(0.0)
+    }
+}
+
+// TEfficiency::AgrestiCoull, the normal interval around the Wilson center
+pub fn agresti_coull(total: f64, passed: f64, level: f64, upper: bool) -> f64 {
+    let kappa = standard_normal_quantile(0.5 * (1.0 + level));
+    let kappa2 = kappa * kappa;
+    let mode = (passed + 0.5 * kappa2) / (total + kappa2);
+    let delta = kappa * (mode * (1.0 - mode) / (total + kappa2)).sqrt();
+    if upper {
+        (mode + delta).min(1.0)
+    } else {
+        (mode - delta).max(0.0)
+    }
+}
+
+// TEfficiency::Bayesian, the central interval of the Beta(passed + alpha, total - passed + beta)
+// posterior
+pub fn bayesian(total: f64, passed: f64, level: f64, alpha: f64, beta: f64, upper: bool) -> f64 {
+    beta_central_interval(level, passed + alpha, total - passed + beta, upper)
+}
+
+// TEfficiency::BetaCentralInterval
+pub fn beta_central_interval(level: f64, a: f64, b: f64, upper: bool) -> f64 {
+    if a > 0.0 && b > 0.0 {
+        let p = if upper { 1.0 + level } else { 1.0 - level } / 2.0;
+        beta_quantile(p, a, b)
+    } else if upper {
+        1.0
+    } else {
+        0.0
+    }
+}
+
+/*
+ * TEfficiency::FeldmanCousins, the Neyman construction with the likelihood ratio ordering: a
+ * true efficiency mu is in the interval when the observed number of passed events is in its
+ * acceptance region. The bounds are searched on a grid and refined by bisection.
+ */
+pub fn feldman_cousins(total: f64, passed: f64, level: f64, upper: bool) -> f64 {
+    let n = total.round().max(0.0) as u64;
+    let k = passed.round().clamp(0.0, n as f64) as u64;
+    if n == 0 {
+        return if upper { 1.0 } else { 0.0 };
+    }
+    if (upper && k == n) || (!upper && k == 0) {
+        return if upper { 1.0 } else { 0.0 };
+    }
+    const STEPS: usize = 1000;
+    let accepted = |mu: f64| feldman_cousins_accepts(n, k, mu, level);
+    let grid: Vec<f64> = (0..=STEPS).map(|i| i as f64 / STEPS as f64).collect();
+    let bound = if upper {
+        grid.iter().rposition(|mu| accepted(*mu))
+    } else {
+        grid.iter().position(|mu| accepted(*mu))
+    };
+    let Some(index) = bound else {
+        return passed / total;
+    };
+    // the boundary lies between the last point outside and the first point inside
+    let (mut inside, mut outside) = if upper {
+        (grid[index], grid[(index + 1).min(STEPS)])
+    } else {
+        (grid[index], grid[index.saturating_sub(1)])
+    };
+    if inside == outside {
+        return inside;
+    }
+    for _ in 0..40 {
+        let mid = 0.5 * (inside + outside);
+        if accepted(mid) {
+            inside = mid;
+        } else {
+            outside = mid;
+        }
+    }
+    inside
+}
+
+// Whether k passed out of n is in the acceptance region of the efficiency mu: the outcomes with
+// the largest ratio P(x | mu) / P(x | x / n) that together cover the level
+fn feldman_cousins_accepts(n: u64, k: u64, mu: f64, level: f64) -> bool {
+    let Ok(binomial) = Binomial::new(mu, n) else {
+        return false;
+    };
+    let mut ranked: Vec<(u64, f64, f64)> = (0..=n)
+        .map(|x| {
+            let p = binomial.pmf(x);
+            let best = Binomial::new(x as f64 / n as f64, n).map_or(1.0, |b| b.pmf(x));
+            (x, if best > 0.0 { p / best } else { 0.0 }, p)
+        })
+        .collect();
+    ranked.sort_by(|a, b| b.1.total_cmp(&a.1));
+    let mut probability = 0.0;
+    for (x, _, p) in ranked {
+        if x == k {
+            return true;
+        }
+        probability += p;
+        if probability >= level {
+            return false;
+        }
+    }
+    true
+}
+
+// TEfficiency::MidPInterval, Clopper-Pearson with half of the probability of the observed value
+pub fn mid_p_interval(total: f64, passed: f64, level: f64, upper: bool) -> f64 {
+    let n = total.round().max(0.0) as u64;
+    let k = passed.round().clamp(0.0, n as f64) as u64;
+    if n == 0 || (upper && k == n) {
+        return if upper { 1.0 } else { 0.0 };
+    }
+    if !upper && k == 0 {
+        return 0.0;
+    }
+    let alpha = (1.0 - level) / 2.0;
+    // the tail probability beyond the bound, decreasing in p for the upper bound and
+    // increasing for the lower one
+    let tail = |p: f64| {
+        let Ok(binomial) = Binomial::new(p, n) else {
+            return f64::NAN;
+        };
+        let half = 0.5 * binomial.pmf(k);
+        if upper {
+            half + if k > 0 { binomial.cdf(k - 1) } else { 0.0 }
+        } else {
+            half + binomial.sf(k)
+        }
+    };
+    let (mut low, mut high) = (0.0, 1.0);
+    for _ in 0..60 {
+        let p = 0.5 * (low + high);
+        let too_low = if upper {
+            tail(p) > alpha
+        } else {
+            tail(p) < alpha
+        };
+        if too_low {
+            low = p;
+        } else {
+            high = p;
+        }
+    }
+    0.5 * (low + high)
+}
+
+impl BinRead for TEfficiency {
+    type Args<'a> = (ReadContext,);
+
+    fn read_options<R: Read + Seek>(
+        reader: &mut R,
+        endian: Endian,
+        args: Self::Args<'_>,
+    ) -> BinResult<Self> {
+        let (ctx,) = args;
+        let header = VersionHeader::read(reader, endian)?;
+        let tnamed = TNamed::read_member(reader, endian, &ctx)?;
+        let att_line = TAttLine::read_member(reader, endian, &ctx)?;
+        let att_fill = TAttFill::read_member(reader, endian, &ctx)?;
+        let att_marker = TAttMarker::read_member(reader, endian, &ctx)?;
+        let f_beta_alpha = reader.read_type(endian)?;
+        let f_beta_beta = reader.read_type(endian)?;
+        // std::vector<std::pair<Double_t, Double_t>>, added in version 2
+        let f_beta_bin_params = if header.version >= 2 {
+            read_stl_pairs(reader, endian)?
+        } else {
+            Vec::new()
+        };
+        let f_conf_level = reader.read_type(endian)?;
+        // `TList* fFunctions; //->` is streamed in place
+        let f_functions = TList::read_options(reader, endian, (ctx.clone(),))?;
+        let f_passed_histogram = ctx.read_object(reader, endian)?;
+        let f_statistic_option = reader.read_type(endian)?;
+        let f_total_histogram = ctx.read_object(reader, endian)?;
+        let f_weight = reader.read_type(endian)?;
+        header.skip_to_end(reader)?;
+        Ok(Self {
+            byte_count: header.byte_count,
+            version: header.version,
+            tnamed,
+            att_line,
+            att_fill,
+            att_marker,
+            f_beta_alpha,
+            f_beta_beta,
+            f_beta_bin_params,
+            f_conf_level,
+            f_functions,
+            f_passed_histogram,
+            f_statistic_option,
+            f_total_histogram,
+            f_weight,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::constant::K_STREAMED_MEMBERWISE;
+    use crate::objects::test_buffer::{Buffer, Hist};
+    use std::io::Cursor;
+
+    fn histogram(buf: &mut Buffer, name: &str, contents: &[f64]) {
+        buf.object("TH1D", |buf| {
+            buf.th1d(&Hist {
+                name,
+                edges: &[0.0, 1.0, 2.0],
+                contents,
+                sumw2: &[],
+                entries: contents.iter().sum(),
+                bits: 0,
+            })
+        });
+    }
+
+    // 3 of 10 events pass in the first bin, 0 of 9 in the second, Clopper-Pearson at 95%
+    fn efficiency() -> TEfficiency {
+        let mut buf = Buffer::default();
+        buf.versioned(2, |buf| {
+            buf.tnamed("eff", "trigger efficiency");
+            buf.att_line_fill_marker();
+            buf.f64s(&[1.0, 1.0]);
+            buf.versioned(K_STREAMED_MEMBERWISE | 6, |buf| {
+                buf.put(&1i16.to_be_bytes());
+                buf.put(&0i32.to_be_bytes());
+            });
+            buf.f64s(&[0.95]);
+            buf.empty_list();
+            histogram(buf, "passed", &[0.0, 3.0, 0.0, 0.0]);
+            buf.put(&0i32.to_be_bytes());
+            histogram(buf, "total", &[0.0, 10.0, 9.0, 0.0]);
+            buf.f64s(&[1.0]);
+        });
+        TEfficiency::read_be_args(&mut Cursor::new(buf.bytes), (ReadContext::new(),))
+            .expect("Failed to read TEfficiency")
+    }
+
+    fn interval(point: EfficiencyPoint) -> (f64, f64) {
+        (
+            point.efficiency - point.error_low,
+            point.efficiency + point.error_up,
+        )
+    }
+
+    fn assert_close(actual: (f64, f64), expected: (f64, f64), tolerance: f64) {
+        assert!(
+            (actual.0 - expected.0).abs() < tolerance && (actual.1 - expected.1).abs() < tolerance,
+            "{:?} != {:?}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn test_read_efficiency() {
+        let eff = efficiency();
+        assert_eq!(eff.name(), "eff");
+        assert_eq!(eff.statistic_option(), StatOption::ClopperPearson);
+        assert_eq!(eff.f_conf_level, 0.95);
+        assert_eq!(eff.passed().map(TH1::name), Some("passed"));
+        assert_eq!(eff.total().map(|total| total.bin_content(2)), Some(9.0));
+        assert!(!eff.is_weighted());
+
+        let points = eff.points();
+        assert_eq!(points.len(), 2);
+        assert_eq!(points[0].efficiency, 0.3);
+        assert_close(interval(points[0]), (0.0667395, 0.6524529), 1e-6);
+        assert_eq!(interval(points[1]).0, 0.0);
+        assert_close(
+            interval(points[1]),
+            (0.0, 1.0 - 0.025f64.powf(1.0 / 9.0)),
+            1e-9,
+        );
+    }
+
+    #[test]
+    fn test_intervals() {
+        let eff = efficiency();
+        assert_close(
+            interval(eff.point_with(1, StatOption::Wilson)),
+            (0.1078, 0.6032),
+            1e-4,
+        );
+        assert_close(
+            interval(eff.point_with(1, StatOption::AgrestiCoull)),
+            (0.1033, 0.6077),
+            1e-4,
+        );
+        assert_close(
+            interval(eff.point_with(1, StatOption::Normal)),
+            (0.0160, 0.5840),
+            1e-4,
+        );
+
+        // Beta(1, 10) posterior of 0 out of 9 with the uniform prior
+        let uniform = eff.point_with(2, StatOption::Uniform);
+        assert!((uniform.efficiency - 1.0 / 11.0).abs() < 1e-12);
+        assert_close(
+            interval(uniform),
+            (1.0 - 0.975f64.powf(0.1), 1.0 - 0.025f64.powf(0.1)),
+            1e-9,
+        );
+
+        assert_close(
+            interval(eff.point_with(1, StatOption::FeldmanCousins)),
+            (0.0873, 0.6194),
+            1e-4,
+        );
+        let (low, up) = interval(eff.point_with(2, StatOption::FeldmanCousins));
+        assert!(low == 0.0 && up > 0.0 && up < 0.35, "{} {}", low, up);
+
+        // mid-p is narrower than Clopper-Pearson
+        let (low, up) = interval(eff.point_with(1, StatOption::MidP));
+        assert!(0.0667 < low && up < 0.6525, "{} {}", low, up);
+    }
+
+    #[test]
+    fn test_beta_mode() {
+        assert_eq!(beta_estimate(3.0, 5.0, true), 2.0 / 6.0);
+        assert_eq!(beta_estimate(1.0, 5.0, true), 0.0);
+        assert_eq!(beta_estimate(5.0, 1.0, true), 1.0);
+        // both parameters at most 1 (e.g. the Jeffreys prior with 0 or 1 entries)
+        assert_eq!(beta_estimate(0.5, 1.0, true), 0.0);
+        assert_eq!(beta_estimate(1.0, 0.5, true), 1.0);
+        assert_eq!(beta_estimate(0.5, 0.5, true), 0.5);
+        assert_eq!(beta_estimate(3.0, 5.0, false), 3.0 / 8.0);
+    }
+
+    #[test]
+    fn test_weighted_empty_bin() {
+        let mut eff = efficiency();
+        eff.tnamed.tobject.f_bits |= K_USE_WEIGHTS;
+        let mut passed = TH1::new("passed", "", 2, 0.0, 2.0);
+        let mut total = TH1::new("total", "", 2, 0.0, 2.0);
+        passed.fill(0.5, 2.0);
+        total.fill(0.5, 2.0);
+        total.fill(0.5, 0.5);
+        eff.f_passed_histogram = Some(Arc::new(AnyObject::TH1(Box::new(passed))));
+        eff.f_total_histogram = Some(Arc::new(AnyObject::TH1(Box::new(total))));
+
+        let points = eff.points();
+        assert_eq!(points[0].efficiency, 0.8);
+        // (4 (1 - 1.6) + 4.25 0.64) / 2.5^2 with the 95% normal quantile
+        let delta = 1.959963984540054 * (0.32f64 / 6.25).sqrt();
+        assert!((points[0].error_low - delta).abs() < 1e-9);
+        assert_eq!(points[0].error_up, 1.0 - 0.8);
+        assert_eq!(points[1], EfficiencyPoint::default());
+    }
+}
@@ -0,0 +1,299 @@
+use crate::core::constant::{K_HAS_BYTECOUNT, K_MAP_OFFSET, K_NEWCLASSTAG, K_NEW_CLASSBIT};
+use crate::streamer::registry::{ClassSchema, FieldKind, FieldSchema};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+// A key buffer written like TBufferFile for the tests: classes are named once, later by their tag
+#[derive(Default)]
+pub struct Buffer {
+    pub bytes: Vec<u8>,
+    classes: HashMap<String, u32>,
+}
+
+// The members of a histogram written by Buffer::th1, a TH1D without sumw2 by default
+pub struct Hist<'a> {
+    pub name: &'a str,
+    pub edges: &'a [f64],
+    pub contents: &'a [f64],
+    pub sumw2: &'a [f64],
+    pub entries: f64,
+    pub bits: u32,
+}
+
+impl Buffer {
+    pub fn put(&mut self, bytes: &[u8]) {
+        self.bytes.extend_from_slice(bytes);
+    }
+
+    pub fn string(&mut self, s: &str) {
+        self.bytes.push(s.len() as u8);
+        self.put(s.as_bytes());
+    }
+
+    pub fn tobject(&mut self) {
+        self.tobject_with_bits(0);
+    }
+
+    pub fn tobject_with_bits(&mut self, bits: u32) {
+        self.put(&1u16.to_be_bytes());
+        self.put(&0u32.to_be_bytes());
+        self.put(&bits.to_be_bytes());
+    }
+
+    // Byte count and version, then the members written by `members`
+    pub fn versioned(&mut self, version: u16, members: impl FnOnce(&mut Self)) {
+        let start = self.bytes.len();
+        self.put(&[0; 4]);
+        self.put(&version.to_be_bytes());
+        members(self);
+        self.patch_byte_count(start);
+    }
+
+    // An object written through a pointer: byte count, class tag, then the streamed object
+    pub fn object(&mut self, class: &str, streamed: impl FnOnce(&mut Self)) {
+        let start = self.bytes.len();
+        self.put(&[0; 4]);
+        match self.classes.get(class) {
+            Some(tag) => {
+                let tag = K_NEW_CLASSBIT | tag;
+                self.put(&tag.to_be_bytes());
+            }
+            None => {
+                let tag = self.bytes.len() as u32 + K_MAP_OFFSET;
+                self.classes.insert(class.to_string(), tag);
+                self.put(&K_NEWCLASSTAG.to_be_bytes());
+                self.put(class.as_bytes());
+                self.put(&[0]);
+            }
+        }
+        streamed(self);
+        self.patch_byte_count(start);
+    }
+
+    pub fn null(&mut self) {
+        self.put(&0u32.to_be_bytes());
+    }
+
+    fn patch_byte_count(&mut self, start: usize) {
+        let count = K_HAS_BYTECOUNT | (self.bytes.len() - start - 4) as u32;
+        self.bytes[start..start + 4].copy_from_slice(&count.to_be_bytes());
+    }
+
+    pub fn f64s(&mut self, values: &[f64]) {
+        for value in values {
+            self.put(&value.to_be_bytes());
+        }
+    }
+
+    // std::vector<double>
+    pub fn doubles(&mut self, values: &[f64]) {
+        self.versioned(6, |buf| {
+            buf.put(&(values.len() as i32).to_be_bytes());
+            buf.f64s(values);
+        });
+    }
+
+    // TArrayD, without byte count and version
+    pub fn array_d(&mut self, values: &[f64]) {
+        self.put(&(values.len() as i32).to_be_bytes());
+        self.f64s(values);
+    }
+
+    pub fn tnamed(&mut self, name: &str, title: &str) {
+        self.tnamed_with_bits(name, title, 0);
+    }
+
+    pub fn tnamed_with_bits(&mut self, name: &str, title: &str, bits: u32) {
+        self.versioned(1, |buf| {
+            buf.tobject_with_bits(bits);
+            buf.string(name);
+            buf.string(title);
+        });
+    }
+
+    // TAttLine, TAttFill and TAttMarker
+    pub fn att_line_fill_marker(&mut self) {
+        self.versioned(2, |buf| buf.put(&[0, 1, 0, 1, 0, 2]));
+        self.versioned(2, |buf| buf.put(&[0, 0, 3, 233]));
+        self.versioned(3, |buf| buf.put(&[0, 1, 0, 1, 63, 128, 0, 0]));
+    }
+
+    pub fn empty_list(&mut self) {
+        self.versioned(5, |buf| {
+            buf.tobject();
+            buf.string("");
+            buf.put(&0u32.to_be_bytes());
+        });
+    }
+
+    pub fn parameter(&mut self, name: &str, value: f64) {
+        self.object("TParameter<double>", |buf| {
+            buf.versioned(2, |buf| {
+                buf.tobject();
+                buf.string(name);
+                buf.put(&value.to_be_bytes());
+            })
+        });
+    }
+
+    pub fn obj_string(&mut self, s: &str) {
+        self.object("TObjString", |buf| {
+            buf.versioned(1, |buf| {
+                buf.tobject();
+                buf.string(s);
+            })
+        });
+    }
+
+    // TAxis version 10 over the edges, variable binning when they are not equidistant
+    pub fn taxis(&mut self, name: &str, edges: &[f64]) {
+        let nbins = edges.len() - 1;
+        let (xmin, xmax) = (edges[0], edges[nbins]);
+        let width = (xmax - xmin) / nbins as f64;
+        let fixed = edges
+            .iter()
+            .enumerate()
+            .all(|(i, edge)| (edge - (xmin + i as f64 * width)).abs() < 1e-12);
+        self.versioned(10, |buf| {
+            buf.tnamed(name, "");
+            buf.versioned(4, |buf| {
+                buf.put(&510i32.to_be_bytes());
+                buf.put(&[0; 6]);
+                buf.put(&[0; 20]);
+                buf.put(&[0; 4]);
+            });
+            buf.put(&(nbins as i32).to_be_bytes());
+            buf.f64s(&[xmin, xmax]);
+            buf.array_d(if fixed { &[] } else { edges });
+            buf.put(&0i32.to_be_bytes());
+            buf.put(&0i32.to_be_bytes());
+            buf.put(&0u16.to_be_bytes());
+            buf.put(&[0]);
+            buf.string("");
+            buf.null();
+            buf.null();
+        });
+    }
+
+    // TH1D version 3 with the TH1 base of version 8
+    pub fn th1d(&mut self, hist: &Hist) {
+        self.versioned(3, |buf| {
+            buf.th1_base(hist, &[0.0, 1.0]);
+            buf.array_d(hist.contents);
+        });
+    }
+
+    // TH2D version 4: the TH2 base of version 5 with the y edges
+    pub fn th2d(&mut self, hist: &Hist, y_edges: &[f64]) {
+        self.versioned(4, |buf| {
+            buf.versioned(5, |buf| {
+                buf.th1_base(hist, y_edges);
+                buf.f64s(&[1.0, 0.0, 0.0, 0.0]);
+            });
+            buf.array_d(hist.contents);
+        });
+    }
+
+    fn th1_base(&mut self, hist: &Hist, y_edges: &[f64]) {
+        self.versioned(8, |buf| {
+            buf.tnamed_with_bits(hist.name, "", hist.bits);
+            buf.att_line_fill_marker();
+            buf.put(&(hist.contents.len() as i32).to_be_bytes());
+            buf.taxis("xaxis", hist.edges);
+            buf.taxis("yaxis", y_edges);
+            buf.taxis("zaxis", &[0.0, 1.0]);
+            buf.put(&0i16.to_be_bytes());
+            buf.put(&1000i16.to_be_bytes());
+            let sumw: f64 = hist.contents.iter().sum();
+            buf.f64s(&[hist.entries, sumw, sumw, 0.0, 0.0, -1111.0, -1111.0, 0.0]);
+            buf.array_d(&[]);
+            buf.array_d(hist.sumw2);
+            buf.string("");
+            buf.empty_list();
+            buf.put(&0i32.to_be_bytes());
+            buf.put(&[0]);
+            buf.put(&0i32.to_be_bytes());
+            buf.put(&2i32.to_be_bytes());
+        });
+    }
+
+    // TGraph version 4 without functions nor histogram
+    pub fn graph(&mut self, name: &str, points: &[(f64, f64)]) {
+        self.versioned(4, |buf| {
+            buf.tnamed(name, "");
+            buf.att_line_fill_marker();
+            buf.put(&(points.len() as i32).to_be_bytes());
+            buf.put(&[1]);
+            buf.f64s(&points.iter().map(|(x, _)| *x).collect::<Vec<_>>());
+            buf.put(&[1]);
+            buf.f64s(&points.iter().map(|(_, y)| *y).collect::<Vec<_>>());
+            buf.null();
+            buf.null();
+            buf.f64s(&[-1111.0, -1111.0]);
+        });
+    }
+
+    // TGraphErrors version 3
+    pub fn graph_errors(&mut self, name: &str, points: &[(f64, f64)], ex: &[f64], ey: &[f64]) {
+        self.versioned(3, |buf| {
+            buf.graph(name, points);
+            buf.put(&[1]);
+            buf.f64s(ex);
+            buf.put(&[1]);
+            buf.f64s(ey);
+        });
+    }
+
+    // A TList written through a pointer, `elements` writes the objects and their options
+    pub fn list(&mut self, n_objects: u32, elements: impl FnOnce(&mut Self)) {
+        self.object("TList", |buf| {
+            buf.versioned(5, |buf| {
+                buf.tobject();
+                buf.string("");
+                buf.put(&n_objects.to_be_bytes());
+                elements(buf);
+            })
+        });
+    }
+
+    // THStack version 2 of single bin TH1D named after `histograms`
+    pub fn stack(&mut self, name: &str, histograms: &[&str]) {
+        self.versioned(2, |buf| {
+            buf.tnamed(name, "");
+            buf.list(histograms.len() as u32, |buf| {
+                for (i, name) in histograms.iter().enumerate() {
+                    let contents = [0.0, i as f64 + 1.0, 0.0];
+                    buf.object("TH1D", |buf| {
+                        buf.th1d(&Hist {
+                            name,
+                            edges: &[0.0, 1.0],
+                            contents: &contents,
+                            sumw2: &[],
+                            entries: 1.0,
+                            bits: 0,
+                        })
+                    });
+                    buf.string("hist");
+                }
+            });
+            buf.null();
+            buf.f64s(&[-1111.0, -1111.0]);
+        });
+    }
+}
+
+// The schema of a class read with read_with_schema, as the streamer info of a file would give it
+pub fn schema(name: &str, version: u16, fields: &[(&str, FieldKind)]) -> Arc<ClassSchema> {
+    Arc::new(ClassSchema {
+        name: name.to_string(),
+        version,
+        checksum: 0,
+        fields: fields
+            .iter()
+            .map(|(name, kind)| FieldSchema {
+                name: name.to_string(),
+                kind: kind.clone(),
+            })
+            .collect(),
+    })
+}
@@ -0,0 +1,323 @@
+use crate::core::read_context::ReadContext;
+use crate::objects::raw_object::RawObject;
+use crate::objects::tatt::{TAttFill, TAttLine, TAttMarker};
+use crate::objects::tformula::{Formula, TFormula};
+use crate::objects::tnamed::TNamed;
+use crate::objects::tstring::TString;
+use crate::streamer::member::{read_bool, VersionHeader};
+use crate::streamer::root_streamer::StreamerMember;
+use crate::RootStreamer;
+use binrw::io::{Read, Seek};
+use binrw::{BinRead, BinReaderExt, BinResult, Endian};
+use std::io;
+use std::sync::Arc;
+
+/*
+ * TF1
+ * https://root.cern/doc/v638/classTF1.html
+ *
+ * A one dimensional function, e.g. the result of a fit in the fFunctions of a histogram. The
+ * expression is in fFormula with its parameters, a function defined by C++ code only has its
+ * parameters in fParams and the values saved at fNpx points (TF1::Save) in fSave.
+ * Only the ROOT 6 layout (version 8 and higher) is supported.
+ */
+#[derive(Debug, Default)]
+pub struct TF1 {
+    pub byte_count: u32,
+    pub version: u16,
+    pub tnamed: TNamed,
+    pub att_line: TAttLine,
+    pub att_fill: TAttFill,
+    pub att_marker: TAttMarker,
+    pub f_xmin: f64,
+    pub f_xmax: f64,
+    pub f_npar: i32,
+    pub f_ndim: i32,
+    pub f_npx: i32,
+    pub f_type: i32,
+    pub f_npfits: i32,
+    pub f_ndf: i32,
+    pub f_chisquare: f64,
+    pub f_minimum: f64,
+    pub f_maximum: f64,
+    pub f_par_errors: Vec<f64>,
+    pub f_par_min: Vec<f64>,
+    pub f_par_max: Vec<f64>,
+    pub f_save: Vec<f64>,
+    pub f_normalized: bool,
+    pub f_norm_integral: f64,
+    pub f_formula: Option<Arc<TFormula>>,
+    pub f_params: Option<Arc<TF1Parameters>>,
+    pub f_composition: Option<Arc<RawObject>>,
+}
+
+// Parameters of a TF1 without formula
+#[derive(Debug, Default, RootStreamer)]
+#[root(class = "TF1Parameters", version = 1)]
+pub struct TF1Parameters {
+    #[root(byte_count)]
+    pub byte_count: u32,
+    #[root(version)]
+    pub version: u16,
+    pub f_parameters: Vec<f64>,
+    pub f_par_names: Vec<TString>,
+}
+
+impl TF1 {
+    pub fn name(&self) -> &str {
+        &self.tnamed.name
+    }
+
+    pub fn expression(&self) -> Option<&str> {
+        self.f_formula
+            .as_ref()
+            .map(|formula| formula.formula.as_str())
+    }
+
+    pub fn parameters(&self) -> &[f64] {
+        match (&self.f_formula, &self.f_params) {
+            (Some(formula), _) => &formula.parameters,
+            (None, Some(params)) => &params.f_parameters,
+            (None, None) => &[],
+        }
+    }
+
+    pub fn parameter_names(&self) -> Vec<&str> {
+        match (&self.f_formula, &self.f_params) {
+            (Some(formula), _) => formula.names(),
+            (None, Some(params)) => params
+                .f_par_names
+                .iter()
+                .map(|name| name.as_str())
+                .collect(),
+            (None, None) => Vec::new(),
+        }
+    }
+
+    pub fn parameter(&self, name: &str) -> Option<f64> {
+        let index = self.parameter_names().iter().position(|n| *n == name)?;
+        self.parameters().get(index).copied()
+    }
+
+    pub fn parameter_error(&self, name: &str) -> Option<f64> {
+        let index = self.parameter_names().iter().position(|n| *n == name)?;
+        self.f_par_errors.get(index).copied()
+    }
+
+    // The compiled expression, to evaluate the function at many points
+    pub fn formula_function(&self) -> io::Result<Formula> {
+        match &self.f_formula {
+            Some(formula) => formula.compile(),
+            None => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("TF1 {} has no formula", self.name()),
+            )),
+        }
+    }
+
+    // TF1::Eval: the formula with the stored parameters, or the saved points interpolated
+    pub fn eval(&self, x: f64) -> io::Result<f64> {
+        if self.f_formula.is_some() {
+            return Ok(self.formula_function()?.eval(&[x], self.parameters()));
+        }
+        self.eval_saved(x).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("TF1 {} has neither formula nor saved points", self.name()),
+            )
+        })
+    }
+
+    // fSave holds the values at equidistant points followed by xmin and xmax (TF1::GetSave)
+    fn eval_saved(&self, x: f64) -> Option<f64> {
+        let n = self.f_save.len();
+        if n < 4 {
+            return None;
+        }
+        let (values, range) = self.f_save.split_at(n - 2);
+        let (xmin, xmax) = (range[0], range[1]);
+        if x < xmin || x > xmax {
+            return Some(0.0);
+        }
+        let dx = (xmax - xmin) / (values.len() - 1) as f64;
+        let bin = (((x - xmin) / dx) as usize).min(values.len() - 2);
+        let xlow = xmin + bin as f64 * dx;
+        Some(values[bin] + (values[bin + 1] - values[bin]) * (x - xlow) / dx)
+    }
+}
+
+impl BinRead for TF1 {
+    type Args<'a> = (ReadContext,);
+
+    fn read_options<R: Read + Seek>(
+        reader: &mut R,
+        endian: Endian,
+        args: Self::Args<'_>,
+    ) -> BinResult<Self> {
+        let (ctx,) = args;
+        let header = VersionHeader::read(reader, endian)?;
+        if header.version <= 7 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("TF1 version {} (ROOT 5) is not supported", header.version),
+            )
+            .into());
+        }
+        let tnamed = TNamed::read_member(reader, endian, &ctx)?;
+        let att_line = TAttLine::read_member(reader, endian, &ctx)?;
+        let att_fill = TAttFill::read_member(reader, endian, &ctx)?;
+        let att_marker = TAttMarker::read_member(reader, endian, &ctx)?;
+        let f_xmin = reader.read_type(endian)?;
+        let f_xmax = reader.read_type(endian)?;
+        let f_npar = reader.read_type(endian)?;
+        let f_ndim = reader.read_type(endian)?;
+        let f_npx = reader.read_type(endian)?;
+        let f_type = reader.read_type(endian)?;
+        let f_npfits = reader.read_type(endian)?;
+        let f_ndf = reader.read_type(endian)?;
+        let f_chisquare = reader.read_type(endian)?;
+        let f_minimum = reader.read_type(endian)?;
+        let f_maximum = reader.read_type(endian)?;
+        let f_par_errors = Vec::read_member(reader, endian, &ctx)?;
+        let f_par_min = Vec::read_member(reader, endian, &ctx)?;
+        let f_par_max = Vec::read_member(reader, endian, &ctx)?;
+        let f_save = Vec::read_member(reader, endian, &ctx)?;
+        let f_normalized = read_bool(reader, endian)?;
+        let f_norm_integral = reader.read_type(endian)?;
+        let f_formula = ctx.read_streamed_object(reader, endian)?;
+        let f_params = ctx.read_streamed_object(reader, endian)?;
+        // fComposition was added in version 11
+        let f_composition = match header.end() {
+            Some(end) if reader.stream_position()? < end => {
+                ctx.read_streamed_object(reader, endian)?
+            }
+            _ => None,
+        };
+        header.skip_to_end(reader)?;
+        Ok(Self {
+            byte_count: header.byte_count,
+            version: header.version,
+            tnamed,
+            att_line,
+            att_fill,
+            att_marker,
+            f_xmin,
+            f_xmax,
+            f_npar,
+            f_ndim,
+            f_npx,
+            f_type,
+            f_npfits,
+            f_ndf,
+            f_chisquare,
+            f_minimum,
+            f_maximum,
+            f_par_errors,
+            f_par_min,
+            f_par_max,
+            f_save,
+            f_normalized,
+            f_norm_integral,
+            f_formula,
+            f_params,
+            f_composition,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::constant::K_STREAMED_MEMBERWISE;
+    use crate::objects::test_buffer::Buffer;
+    use std::io::Cursor;
+
+    impl Buffer {
+        // TF1 version 12 over [0, 10], the formula written by `formula` and fParams null
+        fn tf1(&mut self, save: &[f64], formula: impl FnOnce(&mut Self)) {
+            self.versioned(12, |buf| {
+                buf.tnamed("fit", "");
+                buf.att_line_fill_marker();
+                buf.put(&0f64.to_be_bytes());
+                buf.put(&10f64.to_be_bytes());
+                for value in [3i32, 1, 100, 0, 20, 17] {
+                    buf.put(&value.to_be_bytes());
+                }
+                for value in [12.5f64, -1111.0, -1111.0] {
+                    buf.put(&value.to_be_bytes());
+                }
+                buf.doubles(&[0.5, 0.01, 0.02]);
+                buf.doubles(&[]);
+                buf.doubles(&[]);
+                buf.doubles(save);
+                buf.put(&[0]);
+                buf.put(&0f64.to_be_bytes());
+                formula(buf);
+                buf.put(&0u32.to_be_bytes());
+                buf.put(&0u32.to_be_bytes());
+            });
+        }
+    }
+
+    fn read_tf1(bytes: Vec<u8>) -> TF1 {
+        TF1::read_be_args(&mut Cursor::new(bytes), (ReadContext::new(),))
+            .expect("Failed to read TF1")
+    }
+
+    #[test]
+    fn test_read_tf1_formula() {
+        let mut buf = Buffer::default();
+        buf.tf1(&[], |buf| {
+            buf.object("TFormula", |buf| {
+                buf.versioned(13, |buf| {
+                    buf.tnamed("fit", "gaus");
+                    buf.doubles(&[10.0, 1.0, 2.0]);
+                    buf.put(&[1]);
+                    // fParams written member-wise: names, then indices
+                    buf.versioned(K_STREAMED_MEMBERWISE | 6, |buf| {
+                        buf.put(&1i16.to_be_bytes());
+                        buf.put(&3i32.to_be_bytes());
+                        for name in ["Constant", "Mean", "Sigma"] {
+                            buf.string(name);
+                        }
+                        for index in [0i32, 1, 2] {
+                            buf.put(&index.to_be_bytes());
+                        }
+                    });
+                    buf.string("[Constant]*exp(-0.5*((x-[Mean])/[Sigma])*((x-[Mean])/[Sigma]))");
+                    buf.put(&1i32.to_be_bytes());
+                    buf.put(&100i32.to_be_bytes());
+                })
+            });
+        });
+        let tf1 = read_tf1(buf.bytes);
+        assert_eq!(tf1.name(), "fit");
+        assert_eq!((tf1.f_xmin, tf1.f_xmax, tf1.f_npar), (0.0, 10.0, 3));
+        assert_eq!((tf1.f_ndf, tf1.f_chisquare), (17, 12.5));
+        assert_eq!(tf1.att_line.f_line_width, 2);
+        assert_eq!(tf1.att_marker.f_marker_size, 1.0);
+        assert_eq!(tf1.parameter_names(), ["Constant", "Mean", "Sigma"]);
+        assert_eq!(tf1.parameter("Mean"), Some(1.0));
+        assert_eq!(tf1.parameter_error("Sigma"), Some(0.02));
+        let formula = tf1.f_formula.as_ref().unwrap();
+        assert_eq!((formula.ndim, formula.number), (1, 100));
+        let expected = 10.0 * (-0.5f64 * 0.25).exp();
+        assert!((tf1.eval(2.0).unwrap() - expected).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_eval_saved_points() {
+        let mut buf = Buffer::default();
+        // 0, 10, 40 at x = 0, 5, 10
+        buf.tf1(&[0.0, 10.0, 40.0, 0.0, 10.0], |buf| {
+            buf.put(&0u32.to_be_bytes())
+        });
+        let tf1 = read_tf1(buf.bytes);
+        assert!(tf1.f_formula.is_none());
+        assert_eq!(tf1.eval(2.5).unwrap(), 5.0);
+        assert_eq!(tf1.eval(7.5).unwrap(), 25.0);
+        assert_eq!(tf1.eval(10.0).unwrap(), 40.0);
+        assert_eq!(tf1.eval(11.0).unwrap(), 0.0);
+        assert!(tf1.formula_function().is_err());
+    }
+}
@@ -0,0 +1,930 @@
+use crate::core::read_context::ReadContext;
+use crate::objects::tnamed::TNamed;
+use crate::objects::tstring::TString;
+use crate::streamer::member::{read_stl_pairs, VersionHeader};
+use crate::streamer::root_streamer::StreamerMember;
+use binrw::io::{Read, Seek};
+use binrw::{BinRead, BinReaderExt, BinResult, Endian};
+use std::collections::HashMap;
+use std::f64::consts::{LN_10, LOG10_E, PI, SQRT_2};
+use std::io;
+
+/*
+ * TFormula
+ * https://root.cern/doc/v638/classTFormula.html
+ *
+ * Only the expression and the parameters are persistent: fClingParameters (the values),
+ * fAllParametersSetted, fParams (parameter name -> index), fFormula, fNdim and fNumber. The
+ * expression is compiled here into a Formula instead of Cling. Version 8 and lower is the ROOT 5
+ * TFormula with a different layout, it is not supported.
+ */
+#[derive(Debug, Default)]
+pub struct TFormula {
+    pub byte_count: u32,
+    pub version: u16,
+    pub tnamed: TNamed,
+    pub parameters: Vec<f64>,
+    pub all_parameters_set: bool,
+    pub parameter_names: Vec<(String, i32)>,
+    pub formula: TString,
+    pub ndim: i32,
+    pub number: i32,
+}
+
+impl TFormula {
+    // Parameter names ordered by index
+    pub fn names(&self) -> Vec<&str> {
+        let mut names: Vec<(&str, i32)> = self
+            .parameter_names
+            .iter()
+            .map(|(name, index)| (name.as_str(), *index))
+            .collect();
+        names.sort_by_key(|(_, index)| *index);
+        names.into_iter().map(|(name, _)| name).collect()
+    }
+
+    pub fn compile(&self) -> io::Result<Formula> {
+        let names: HashMap<String, usize> = self
+            .parameter_names
+            .iter()
+            .filter(|(_, index)| *index >= 0)
+            .map(|(name, index)| (name.clone(), *index as usize))
+            .collect();
+        Formula::parse_with_names(&self.formula, &names)
+    }
+
+    // Value at `x` with the stored parameters
+    pub fn eval(&self, x: &[f64]) -> io::Result<f64> {
+        Ok(self.compile()?.eval(x, &self.parameters))
+    }
+}
+
+impl BinRead for TFormula {
+    type Args<'a> = (ReadContext,);
+
+    fn read_options<R: Read + Seek>(
+        reader: &mut R,
+        endian: Endian,
+        args: Self::Args<'_>,
+    ) -> BinResult<Self> {
+        let (ctx,) = args;
+        let header = VersionHeader::read(reader, endian)?;
+        if header.version <= 8 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "TFormula version {} (ROOT 5) is not supported",
+                    header.version
+                ),
+            )
+            .into());
+        }
+        let tnamed = TNamed::read_options(reader, endian, (ctx.clone(),))?;
+        let parameters = Vec::<f64>::read_member(reader, endian, &ctx)?;
+        let all_parameters_set = reader.read_type::<u8>(endian)? != 0;
+        // std::map<TString, Int_t> fParams
+        let parameter_names = read_stl_pairs::<TString, i32, _>(reader, endian)?
+            .into_iter()
+            .map(|(name, index)| (name.string, index))
+            .collect();
+        let formula = TString::read_options(reader, endian, ())?;
+        // fNdim and fNumber were added after the first ROOT 6 versions
+        let remaining = match header.end() {
+            Some(end) => end.saturating_sub(reader.stream_position()?),
+            None => 0,
+        };
+        let (ndim, number) = if remaining >= 8 {
+            (reader.read_type(endian)?, reader.read_type(endian)?)
+        } else {
+            (0, 0)
+        };
+        header.skip_to_end(reader)?;
+        Ok(Self {
+            byte_count: header.byte_count,
+            version: header.version,
+            tnamed,
+            parameters,
+            all_parameters_set,
+            parameter_names,
+            formula,
+            ndim,
+            number,
+        })
+    }
+}
+
+/*
+ * A compiled TFormula expression, evaluated at variables x (x, y, z, t or x[0], x[1], ...) with
+ * parameters p ([0], [1], ... or [name]).
+ *
+ * Supported are the arithmetic operators with ^ and ** for powers, comparisons and && || !, the
+ * predefined functions gaus, gausn, expo and polN (optionally with the index of their first
+ * parameter: gaus(3)), the constants pi, e, ln10, log10e, sqrt2, infinity, true and false, and the
+ * usual math functions, also with a TMath::, std:: or ROOT::Math:: prefix (exp, log, sqrt, pow,
+ * sin, ..., erf, TMath::Gaus, TMath::Landau, ROOT::Math::breitwigner_pdf,
+ * ROOT::Math::crystalball_function), which cover the expanded formulas ROOT saves for its
+ * predefined functions.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct Formula {
+    expression: Expr,
+    n_parameters: usize,
+    n_variables: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    Number(f64),
+    Variable(usize),
+    Parameter(usize),
+    Negate(Box<Expr>),
+    Not(Box<Expr>),
+    Binary(BinaryOp, Box<Expr>, Box<Expr>),
+    Call(usize, Vec<Expr>), // index in FUNCTIONS
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+    Pow,
+    Less,
+    Greater,
+    LessEqual,
+    GreaterEqual,
+    Equal,
+    NotEqual,
+    And,
+    Or,
+}
+
+type MathFunction = fn(&[f64]) -> f64;
+
+// name (lower case, without TMath::, std:: or ROOT::Math::), minimum and maximum number of arguments
+const FUNCTIONS: &[(&str, usize, usize, MathFunction)] = &[
+    ("sin", 1, 1, |a| a[0].sin()),
+    ("cos", 1, 1, |a| a[0].cos()),
+    ("tan", 1, 1, |a| a[0].tan()),
+    ("asin", 1, 1, |a| a[0].asin()),
+    ("acos", 1, 1, |a| a[0].acos()),
+    ("atan", 1, 1, |a| a[0].atan()),
+    ("atan2", 2, 2, |a| a[0].atan2(a[1])),
+    ("sinh", 1, 1, |a| a[0].sinh()),
+    ("cosh", 1, 1, |a| a[0].cosh()),
+    ("tanh", 1, 1, |a| a[0].tanh()),
+    ("asinh", 1, 1, |a| a[0].asinh()),
+    ("acosh", 1, 1, |a| a[0].acosh()),
+    ("atanh", 1, 1, |a| a[0].atanh()),
+    ("exp", 1, 1, |a| a[0].exp()),
+    ("log", 1, 1, |a| a[0].ln()),
+    ("log10", 1, 1, |a| a[0].log10()),
+    ("log2", 1, 1, |a| a[0].log2()),
+    ("sqrt", 1, 1, |a| a[0].sqrt()),
+    ("cbrt", 1, 1, |a| a[0].cbrt()),
+    ("sq", 1, 1, |a| a[0] * a[0]),
+    ("abs", 1, 1, |a| a[0].abs()),
+    ("fabs", 1, 1, |a| a[0].abs()),
+    ("pow", 2, 2, |a| a[0].powf(a[1])),
+    ("power", 2, 2, |a| a[0].powf(a[1])),
+    ("min", 2, 2, |a| a[0].min(a[1])),
+    ("max", 2, 2, |a| a[0].max(a[1])),
+    ("floor", 1, 1, |a| a[0].floor()),
+    ("ceil", 1, 1, |a| a[0].ceil()),
+    ("sign", 2, 2, |a| a[0].abs().copysign(a[1])),
+    ("erf", 1, 1, |a| statrs::function::erf::erf(a[0])),
+    ("erfc", 1, 1, |a| statrs::function::erf::erfc(a[0])),
+    ("gaus", 1, 4, gaus),
+    ("breitwigner", 1, 3, breit_wigner),
+    ("breitwigner_pdf", 2, 3, breit_wigner_pdf),
+    ("landau", 1, 4, landau),
+    ("crystalball_function", 5, 5, |a| {
+        crystal_ball(a[0], a[1], a[2], a[3], a[4])
+    }),
+    ("pi", 0, 0, |_| PI),
+    ("e", 0, 0, |_| std::f64::consts::E),
+];
+
+const CONSTANTS: &[(&str, f64)] = &[
+    ("pi", PI),
+    ("e", std::f64::consts::E),
+    ("ln10", LN_10),
+    ("log10e", LOG10_E),
+    ("sqrt2", SQRT_2),
+    ("infinity", f64::INFINITY),
+    ("true", 1.0),
+    ("false", 0.0),
+];
+
+// TMath::Gaus(x, mean = 0, sigma = 1, norm = false)
+fn gaus(a: &[f64]) -> f64 {
+    let mean = a.get(1).copied().unwrap_or(0.0);
+    let sigma = a.get(2).copied().unwrap_or(1.0);
+    if sigma == 0.0 {
+        return 1e30;
+    }
+    let u = (a[0] - mean) / sigma;
+    let value = (-0.5 * u * u).exp();
+    if a.get(3).is_some_and(|norm| *norm != 0.0) {
+        value / ((2.0 * PI).sqrt() * sigma)
+    } else {
+        value
+    }
+}
+
+// TMath::BreitWigner(x, mean = 0, gamma = 1)
+fn breit_wigner(a: &[f64]) -> f64 {
+    let mean = a.get(1).copied().unwrap_or(0.0);
+    let gamma = a.get(2).copied().unwrap_or(1.0);
+    let d = a[0] - mean;
+    gamma / (2.0 * PI) / (d * d + gamma * gamma / 4.0)
+}
+
+// ROOT::Math::breitwigner_pdf(x, gamma, x0 = 0), the argument order of the saved breitwigner
+fn breit_wigner_pdf(a: &[f64]) -> f64 {
+    breit_wigner(&[a[0], a.get(2).copied().unwrap_or(0.0), a[1]])
+}
+
+// TMath::Landau(x, mpv = 0, sigma = 1, norm = false)
+fn landau(a: &[f64]) -> f64 {
+    let mpv = a.get(1).copied().unwrap_or(0.0);
+    let sigma = a.get(2).copied().unwrap_or(1.0);
+    if sigma <= 0.0 {
+        return 0.0;
+    }
+    let density = landau_pdf((a[0] - mpv) / sigma);
+    if a.get(3).is_some_and(|norm| *norm != 0.0) {
+        density / sigma
+    } else {
+        density
+    }
+}
+
+/*
+ * The density of the Landau distribution with location 0 and scale 1, ROOT::Math::landau_pdf
+ * (CERNLIB DENLAN): rational approximations on intervals of the argument.
+ */
+pub fn landau_pdf(v: f64) -> f64 {
+    const P1: [f64; 5] = [
+        0.4259894875,
+        -0.1249762550,
+        0.03984243700,
+        -0.006298287635,
+        0.001511162253,
+    ];
+    const Q1: [f64; 5] = [
+        1.0,
+        -0.3388260629,
+        0.09594393323,
+        -0.01608042283,
+        0.003778942063,
+    ];
+    const P2: [f64; 5] = [
+        0.1788541609,
+        0.1173957403,
+        0.01488850518,
+        -0.001394989411,
+        0.0001283617211,
+    ];
+    const Q2: [f64; 5] = [
+        1.0,
+        0.7428795082,
+        0.3153932961,
+        0.06694219548,
+        0.008790609714,
+    ];
+    const P3: [f64; 5] = [
+        0.1788544503,
+        0.09359161662,
+        0.006325387654,
+        0.00006611667319,
+        -0.000002031049101,
+    ];
+    const Q3: [f64; 5] = [
+        1.0,
+        0.6097809921,
+        0.2560616665,
+        0.04746722384,
+        0.006957301675,
+    ];
+    const P4: [f64; 5] = [
+        0.9874054407,
+        118.6723273,
+        849.2794360,
+        -743.7792444,
+        427.0262186,
+    ];
+    const Q4: [f64; 5] = [1.0, 106.8615961, 337.6496214, 2016.712389, 1597.063511];
+    const P5: [f64; 5] = [
+        1.003675074,
+        167.5702434,
+        4789.711289,
+        21217.86767,
+        -22324.94910,
+    ];
+    const Q5: [f64; 5] = [1.0, 156.9424537, 3745.310488, 9834.698876, 66924.28357];
+    const P6: [f64; 5] = [
+        1.000827619,
+        664.9143136,
+        62972.92665,
+        475554.6998,
+        -5743609.109,
+    ];
+    const Q6: [f64; 5] = [1.0, 651.4101098, 56974.73333, 165917.4725, -2815759.939];
+    const A1: [f64; 3] = [0.04166666667, -0.01996527778, 0.02709538966];
+    const A2: [f64; 2] = [-1.845568670, -4.284640743];
+    let ratio = |p: &[f64; 5], q: &[f64; 5], t: f64| {
+        let horner = |c: &[f64; 5]| c.iter().rev().fold(0.0, |sum, c| sum * t + c);
+        horner(p) / horner(q)
+    };
+    if v < -5.5 {
+        let u = (v + 1.0).exp();
+        if u < 1e-10 {
+            return 0.0;
+        }
+        0.3989422803 * ((-1.0 / u).exp() / u.sqrt()) * (1.0 + (A1[0] + (A1[1] + A1[2] * u) * u) * u)
+    } else if v < -1.0 {
+        let u = (-v - 1.0).exp();
+        (-u).exp() * u.sqrt() * ratio(&P1, &Q1, v)
+    } else if v < 1.0 {
+        ratio(&P2, &Q2, v)
+    } else if v < 5.0 {
+        ratio(&P3, &Q3, v)
+    } else if v < 12.0 {
+        let u = 1.0 / v;
+        u * u * ratio(&P4, &Q4, u)
+    } else if v < 50.0 {
+        let u = 1.0 / v;
+        u * u * ratio(&P5, &Q5, u)
+    } else if v < 300.0 {
+        let u = 1.0 / v;
+        u * u * ratio(&P6, &Q6, u)
+    } else {
+        let u = 1.0 / (v - v * v.ln() / (v + 1.0));
+        u * u * (1.0 + (A2[0] + A2[1] * u) * u)
+    }
+}
+
+// ROOT::Math::crystalball_function(x, alpha, n, sigma, mean): a gaussian core with a power law
+// tail below mean - alpha sigma (above for a negative alpha), 1 at the mean
+pub fn crystal_ball(x: f64, alpha: f64, n: f64, sigma: f64, mean: f64) -> f64 {
+    if sigma < 0.0 {
+        return 0.0;
+    }
+    let z = if alpha < 0.0 {
+        (mean - x) / sigma
+    } else {
+        (x - mean) / sigma
+    };
+    let alpha = alpha.abs();
+    if z > -alpha {
+        (-0.5 * z * z).exp()
+    } else {
+        let n_over_alpha = n / alpha;
+        let a = (-0.5 * alpha * alpha).exp();
+        let b = n_over_alpha - alpha;
+        a * (n_over_alpha / (b - z)).powf(n)
+    }
+}
+
+impl Formula {
+    pub fn parse(expression: &str) -> io::Result<Self> {
+        Self::parse_with_names(expression, &HashMap::new())
+    }
+
+    // `names` maps named parameters to their index, other names get the next free index
+    pub fn parse_with_names(expression: &str, names: &HashMap<String, usize>) -> io::Result<Self> {
+        let tokens = tokenize(expression)?;
+        let mut parser = Parser {
+            tokens,
+            pos: 0,
+            names: names.clone(),
+            n_parameters: names.values().map(|index| index + 1).max().unwrap_or(0),
+            n_variables: 0,
+        };
+        let expression = parser.expression()?;
+        if let Some(token) = parser.tokens.get(parser.pos) {
+            return Err(parse_error(format!("unexpected {:?}", token)));
+        }
+        Ok(Self {
+            expression,
+            n_parameters: parser.n_parameters,
+            n_variables: parser.n_variables,
+        })
+    }
+
+    pub fn n_parameters(&self) -> usize {
+        self.n_parameters
+    }
+
+    pub fn n_variables(&self) -> usize {
+        self.n_variables
+    }
+
+    // Missing variables are 0, missing parameters NaN
+    pub fn eval(&self, x: &[f64], parameters: &[f64]) -> f64 {
+        eval(&self.expression, x, parameters)
+    }
+}
+
+fn eval(expr: &Expr, x: &[f64], p: &[f64]) -> f64 {
+    let truth = |value: bool| if value { 1.0 } else { 0.0 };
+    match expr {
+        Expr::Number(value) => *value,
+        Expr::Variable(index) => x.get(*index).copied().unwrap_or(0.0),
+        Expr::Parameter(index) => p.get(*index).copied().unwrap_or(f64::NAN),
+        Expr::Negate(a) => -eval(a, x, p),
+        Expr::Not(a) => truth(eval(a, x, p) == 0.0),
+        Expr::Binary(op, a, b) => {
+            let (a, b) = (eval(a, x, p), eval(b, x, p));
+            match op {
+                BinaryOp::Add => a + b,
+                BinaryOp::Sub => a - b,
+                BinaryOp::Mul => a * b,
+                BinaryOp::Div => a / b,
+                BinaryOp::Rem => a % b,
+                BinaryOp::Pow => a.powf(b),
+                BinaryOp::Less => truth(a < b),
+                BinaryOp::Greater => truth(a > b),
+                BinaryOp::LessEqual => truth(a <= b),
+                BinaryOp::GreaterEqual => truth(a >= b),
+                BinaryOp::Equal => truth(a == b),
+                BinaryOp::NotEqual => truth(a != b),
+                BinaryOp::And => truth(a != 0.0 && b != 0.0),
+                BinaryOp::Or => truth(a != 0.0 || b != 0.0),
+            }
+        }
+        Expr::Call(function, args) => {
+            let args: Vec<f64> = args.iter().map(|arg| eval(arg, x, p)).collect();
+            (FUNCTIONS[*function].3)(&args)
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    Ident(String),
+    Bracket(String), // [0], [name] or the index of x[0]
+    LParen,
+    RParen,
+    Comma,
+    Op(&'static str),
+}
+
+const OPERATORS: &[&str] = &[
+    "**", "<=", ">=", "==", "!=", "&&", "||", "+", "-", "*", "/", "%", "^", "<", ">", "!",
+];
+
+fn tokenize(expression: &str) -> io::Result<Vec<Token>> {
+    let chars: Vec<char> = expression.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if c.is_ascii_digit()
+            || (c == '.' && chars.get(i + 1).is_some_and(char::is_ascii_digit))
+        {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                i += 1;
+            }
+            // exponent, only when digits follow
+            if i < chars.len() && (chars[i] == 'e' || chars[i] == 'E') {
+                let mut j = i + 1;
+                if j < chars.len() && (chars[j] == '+' || chars[j] == '-') {
+                    j += 1;
+                }
+                if j < chars.len() && chars[j].is_ascii_digit() {
+                    i = j;
+                    while i < chars.len() && chars[i].is_ascii_digit() {
+                        i += 1;
+                    }
+                }
+            }
+            let text: String = chars[start..i].iter().collect();
+            let value = text
+                .parse()
+                .map_err(|_| parse_error(format!("invalid number {}", text)))?;
+            tokens.push(Token::Number(value));
+        } else if c.is_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len()
+                && (chars[i].is_alphanumeric()
+                    || chars[i] == '_'
+                    || (chars[i] == ':' && chars.get(i + 1) == Some(&':')))
+            {
+                i += if chars[i] == ':' { 2 } else { 1 };
+            }
+            tokens.push(Token::Ident(chars[start..i].iter().collect()));
+        } else if c == '[' {
+            let end = chars[i..]
+                .iter()
+                .position(|&c| c == ']')
+                .ok_or_else(|| parse_error("unclosed [".to_string()))?;
+            let inside: String = chars[i + 1..i + end].iter().collect();
+            tokens.push(Token::Bracket(inside.trim().to_string()));
+            i += end + 1;
+        } else if c == '(' {
+            tokens.push(Token::LParen);
+            i += 1;
+        } else if c == ')' {
+            tokens.push(Token::RParen);
+            i += 1;
+        } else if c == ',' {
+            tokens.push(Token::Comma);
+            i += 1;
+        } else {
+            let rest: String = chars[i..chars.len().min(i + 2)].iter().collect();
+            let op = OPERATORS
+                .iter()
+                .find(|op| rest.starts_with(*op))
+                .ok_or_else(|| parse_error(format!("unexpected character {:?}", c)))?;
+            tokens.push(Token::Op(op));
+            i += op.len();
+        }
+    }
+    Ok(tokens)
+}
+
+fn parse_error(message: String) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!("invalid formula: {}", message),
+    )
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    names: HashMap<String, usize>,
+    n_parameters: usize,
+    n_variables: usize,
+}
+
+// binary operators by precedence, lowest first
+const PRECEDENCE: &[&[(&str, BinaryOp)]] = &[
+    &[("||", BinaryOp::Or)],
+    &[("&&", BinaryOp::And)],
+    &[
+        ("<", BinaryOp::Less),
+        (">", BinaryOp::Greater),
+        ("<=", BinaryOp::LessEqual),
+        (">=", BinaryOp::GreaterEqual),
+        ("==", BinaryOp::Equal),
+        ("!=", BinaryOp::NotEqual),
+    ],
+    &[("+", BinaryOp::Add), ("-", BinaryOp::Sub)],
+    &[
+        ("*", BinaryOp::Mul),
+        ("/", BinaryOp::Div),
+        ("%", BinaryOp::Rem),
+    ],
+];
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn expect(&mut self, expected: Token) -> io::Result<()> {
+        match self.next() {
+            Some(token) if token == expected => Ok(()),
+            Some(token) => Err(parse_error(format!(
+                "expected {:?}, found {:?}",
+                expected, token
+            ))),
+            None => Err(parse_error(format!("expected {:?} at the end", expected))),
+        }
+    }
+
+    fn expression(&mut self) -> io::Result<Expr> {
+        self.binary(0)
+    }
+
+    fn binary(&mut self, level: usize) -> io::Result<Expr> {
+        if level == PRECEDENCE.len() {
+            return self.unary();
+        }
+        let mut lhs = self.binary(level + 1)?;
+        while let Some(Token::Op(op)) = self.peek() {
+            let Some((_, binary)) = PRECEDENCE[level].iter().find(|(name, _)| name == op) else {
+                break;
+            };
+            let binary = *binary;
+            self.pos += 1;
+            let rhs = self.binary(level + 1)?;
+            lhs = Expr::Binary(binary, Box::new(lhs), Box::new(rhs));
+        }
+        Ok(lhs)
+    }
+
+    fn unary(&mut self) -> io::Result<Expr> {
+        match self.peek() {
+            Some(Token::Op("-")) => {
+                self.pos += 1;
+                Ok(Expr::Negate(Box::new(self.unary()?)))
+            }
+            Some(Token::Op("+")) => {
+                self.pos += 1;
+                self.unary()
+            }
+            Some(Token::Op("!")) => {
+                self.pos += 1;
+                Ok(Expr::Not(Box::new(self.unary()?)))
+            }
+            _ => self.power(),
+        }
+    }
+
+    // right associative, binds tighter than unary minus: -x^2 = -(x^2)
+    fn power(&mut self) -> io::Result<Expr> {
+        let base = self.primary()?;
+        if let Some(Token::Op("^" | "**")) = self.peek() {
+            self.pos += 1;
+            let exponent = self.unary()?;
+            return Ok(Expr::Binary(
+                BinaryOp::Pow,
+                Box::new(base),
+                Box::new(exponent),
+            ));
+        }
+        Ok(base)
+    }
+
+    fn primary(&mut self) -> io::Result<Expr> {
+        match self.next() {
+            Some(Token::Number(value)) => Ok(Expr::Number(value)),
+            Some(Token::Bracket(name)) => Ok(Expr::Parameter(self.parameter(&name))),
+            Some(Token::LParen) => {
+                let expr = self.expression()?;
+                self.expect(Token::RParen)?;
+                Ok(expr)
+            }
+            Some(Token::Ident(name)) => self.identifier(&name),
+            Some(token) => Err(parse_error(format!("unexpected {:?}", token))),
+            None => Err(parse_error("unexpected end".to_string())),
+        }
+    }
+
+    fn parameter(&mut self, name: &str) -> usize {
+        let index = match name.parse::<usize>() {
+            Ok(index) => index,
+            Err(_) => match self.names.get(name) {
+                Some(index) => *index,
+                None => {
+                    let index = self.n_parameters;
+                    self.names.insert(name.to_string(), index);
+                    index
+                }
+            },
+        };
+        self.n_parameters = self.n_parameters.max(index + 1);
+        index
+    }
+
+    fn variable(&mut self, index: usize) -> Expr {
+        self.n_variables = self.n_variables.max(index + 1);
+        Expr::Variable(index)
+    }
+
+    fn identifier(&mut self, name: &str) -> io::Result<Expr> {
+        if let Some(index) = ["x", "y", "z", "t"].iter().position(|v| *v == name) {
+            // x[1] is the second variable
+            if let Some(Token::Bracket(inside)) = self.peek() {
+                let inside = inside.clone();
+                if name == "x" {
+                    self.pos += 1;
+                    let index = inside
+                        .parse()
+                        .map_err(|_| parse_error(format!("invalid variable x[{}]", inside)))?;
+                    return Ok(self.variable(index));
+                }
+            }
+            return Ok(self.variable(index));
+        }
+        if let Some(expr) = self.predefined(name)? {
+            return Ok(expr);
+        }
+
+        let short = name
+            .strip_prefix("TMath::")
+            .or_else(|| name.strip_prefix("std::"))
+            .or_else(|| name.strip_prefix("ROOT::Math::"))
+            .unwrap_or(name)
+            .to_lowercase();
+        if self.peek() == Some(&Token::LParen) {
+            let function = FUNCTIONS
+                .iter()
+                .position(|(function, ..)| *function == short)
+                .ok_or_else(|| parse_error(format!("unknown function {}", name)))?;
+            self.pos += 1;
+            let mut args = Vec::new();
+            if self.peek() != Some(&Token::RParen) {
+                loop {
+                    args.push(self.expression()?);
+                    if self.peek() == Some(&Token::Comma) {
+                        self.pos += 1;
+                    } else {
+                        break;
+                    }
+                }
+            }
+            self.expect(Token::RParen)?;
+            let (_, min, max, _) = FUNCTIONS[function];
+            if args.len() < min || args.len() > max {
+                return Err(parse_error(format!(
+                    "{} takes {} to {} arguments, found {}",
+                    name,
+                    min,
+                    max,
+                    args.len()
+                )));
+            }
+            return Ok(Expr::Call(function, args));
+        }
+        CONSTANTS
+            .iter()
+            .find(|(constant, _)| *constant == short)
+            .map(|(_, value)| Expr::Number(*value))
+            .ok_or_else(|| parse_error(format!("unknown name {}", name)))
+    }
+
+    // gaus, gausn, expo and polN, with the index of the first parameter in parentheses or
+    // following the parameters used so far
+    fn predefined(&mut self, name: &str) -> io::Result<Option<Expr>> {
+        let degree = name
+            .strip_prefix("pol")
+            .and_then(|n| n.parse::<usize>().ok());
+        if !matches!(name, "gaus" | "gausn" | "expo") && degree.is_none() {
+            return Ok(None);
+        }
+        let first = match (
+            self.peek(),
+            self.tokens.get(self.pos + 1),
+            self.tokens.get(self.pos + 2),
+        ) {
+            (Some(Token::LParen), Some(Token::Number(n)), Some(Token::RParen))
+                if n.fract() == 0.0 =>
+            {
+                let n = *n as usize;
+                self.pos += 3;
+                n
+            }
+            _ => self.n_parameters,
+        };
+        let p = |i: usize| Box::new(Expr::Parameter(first + i));
+        let x = Box::new(self.variable(0));
+        let n_parameters;
+        let expr = match name {
+            "gaus" | "gausn" => {
+                n_parameters = 3;
+                let u = Expr::Binary(
+                    BinaryOp::Div,
+                    Box::new(Expr::Binary(BinaryOp::Sub, x, p(1))),
+                    p(2),
+                );
+                let exponent = Expr::Binary(
+                    BinaryOp::Mul,
+                    Box::new(Expr::Number(-0.5)),
+                    Box::new(Expr::Binary(
+                        BinaryOp::Mul,
+                        Box::new(u.clone()),
+                        Box::new(u),
+                    )),
+                );
+                let exp = Expr::Call(function_index("exp"), vec![exponent]);
+                let gaus = Expr::Binary(BinaryOp::Mul, p(0), Box::new(exp));
+                if name == "gausn" {
+                    let norm = Expr::Binary(
+                        BinaryOp::Mul,
+                        Box::new(Expr::Number((2.0 * PI).sqrt())),
+                        p(2),
+                    );
+                    Expr::Binary(BinaryOp::Div, Box::new(gaus), Box::new(norm))
+                } else {
+                    gaus
+                }
+            }
+            "expo" => {
+                n_parameters = 2;
+                let exponent = Expr::Binary(
+                    BinaryOp::Add,
+                    p(0),
+                    Box::new(Expr::Binary(BinaryOp::Mul, p(1), x)),
+                );
+                Expr::Call(function_index("exp"), vec![exponent])
+            }
+            _ => {
+                let degree = degree.unwrap_or_default();
+                n_parameters = degree + 1;
+                (1..=degree).fold(Expr::Parameter(first), |sum, i| {
+                    let power =
+                        Expr::Binary(BinaryOp::Pow, x.clone(), Box::new(Expr::Number(i as f64)));
+                    Expr::Binary(
+                        BinaryOp::Add,
+                        Box::new(sum),
+                        Box::new(Expr::Binary(BinaryOp::Mul, p(i), Box::new(power))),
+                    )
+                })
+            }
+        };
+        self.n_parameters = self.n_parameters.max(first + n_parameters);
+        Ok(Some(expr))
+    }
+}
+
+fn function_index(name: &str) -> usize {
+    FUNCTIONS
+        .iter()
+        .position(|(function, ..)| *function == name)
+        .expect("function in the table")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eval_at(expression: &str, x: f64, p: &[f64]) -> f64 {
+        Formula::parse(expression)
+            .unwrap_or_else(|e| panic!("{}: {}", expression, e))
+            .eval(&[x], p)
+    }
+
+    #[test]
+    fn test_eval_formula() {
+        assert_eq!(eval_at("1 + 2*3 - 4/2", 0.0, &[]), 5.0);
+        assert_eq!(eval_at("-x^2", 3.0, &[]), -9.0);
+        assert_eq!(eval_at("2**3**2", 0.0, &[]), 512.0);
+        assert_eq!(eval_at("[0] + [1]*x", 2.0, &[1.0, 0.5]), 2.0);
+        assert_eq!(eval_at("(x > 1) && !(x > 3)", 2.0, &[]), 1.0);
+        assert_eq!(
+            eval_at("TMath::Exp(0) + std::sqrt(1.6e1) + pi", 0.0, &[]),
+            5.0 + PI
+        );
+        assert_eq!(eval_at("x[0]*x[1]", 2.0, &[]), 0.0);
+        assert_eq!(eval_at("max(x, 2.5) + abs(-1)", 2.0, &[]), 3.5);
+        assert!((eval_at("erf(x)", 1.0, &[]) - 0.8427007929497149).abs() < 1e-9);
+        assert!((eval_at("TMath::Gaus(x, 1, 2, 1)", 1.0, &[]) - 0.19947114020071635).abs() < 1e-12);
+        assert!((eval_at("TMath::Landau(x)", 0.0, &[]) - 0.1788541609).abs() < 1e-9);
+        assert!((eval_at("TMath::Landau(x, 1, 2, 1)", 1.0, &[]) - 0.1788541609 / 2.0).abs() < 1e-9);
+        // the tail joins the core continuously at mean - alpha sigma
+        let cb = "ROOT::Math::crystalball_function(x, 1.5, 3, 1, 0)";
+        assert!((eval_at(cb, -1.5, &[]) - (-0.5f64 * 2.25).exp()).abs() < 1e-12);
+        assert!((eval_at(cb, -1.5 - 1e-9, &[]) - eval_at(cb, -1.5, &[])).abs() < 1e-8);
+
+        let p = [10.0, 1.0, 2.0, 0.5, -0.1];
+        let gaus = 10.0 * (-0.5f64 * 0.25).exp();
+        assert!((eval_at("gaus", 2.0, &p) - gaus).abs() < 1e-12);
+        assert!((eval_at("gaus(0) + expo(3)", 2.0, &p) - gaus - 0.3f64.exp()).abs() < 1e-12);
+        assert_eq!(eval_at("pol2", 2.0, &[1.0, 2.0, 3.0]), 17.0);
+        assert_eq!(Formula::parse("gaus + pol1").unwrap().n_parameters(), 5);
+
+        // named parameters as written by TFormula
+        let expanded = "[Constant]*exp(-0.5*((x-[Mean])/[Sigma])*((x-[Mean])/[Sigma]))";
+        let formula = Formula::parse(expanded).unwrap();
+        assert_eq!(formula.n_parameters(), 3);
+        assert!((formula.eval(&[2.0], &p) - gaus).abs() < 1e-12);
+
+        assert!(Formula::parse("1 +").is_err());
+        assert!(Formula::parse("foo(x)").is_err());
+        assert!(Formula::parse("sin(1, 2)").is_err());
+    }
+
+    // fFormula as saved by TF1("f", "landau"), "landaun", "gausn" and "breitwigner"
+    #[test]
+    fn test_eval_saved_predefined() {
+        let p = [10.0, 1.0, 2.0];
+        let landau = "[Constant]*TMath::Landau(x,[MPV],[Sigma],false)";
+        let expected = 10.0 * landau_pdf(0.5);
+        assert!((eval_at(landau, 2.0, &p) - expected).abs() < 1e-12);
+        let landaun = "[Constant]*TMath::Landau(x,[MPV],[Sigma],true)";
+        assert!((eval_at(landaun, 2.0, &p) - expected / 2.0).abs() < 1e-12);
+
+        let gausn =
+            "[Constant]*exp(-0.5*((x-[Mean])/[Sigma])*((x-[Mean])/[Sigma]))/(sqrt(2*pi)*[Sigma])";
+        let expected = 10.0 * (-0.5f64 * 0.25).exp() / ((2.0 * PI).sqrt() * 2.0);
+        assert!((eval_at(gausn, 2.0, &p) - expected).abs() < 1e-12);
+
+        // gamma / 2 pi / ((x - mean)^2 + gamma^2 / 4)
+        // [Sigma] comes before [Mean], the indices are those of the saved parameter names
+        let breitwigner = "[Constant]*ROOT::Math::breitwigner_pdf(x,[Sigma],[Mean])";
+        let names = [("Constant", 0), ("Mean", 1), ("Sigma", 2)]
+            .map(|(name, index)| (name.to_string(), index))
+            .into();
+        let formula = Formula::parse_with_names(breitwigner, &names).unwrap();
+        let expected = 10.0 * 2.0 / (2.0 * PI) / (1.0 + 1.0);
+        assert!((formula.eval(&[2.0], &p) - expected).abs() < 1e-12);
+        assert_eq!(formula.n_parameters(), 3);
+    }
+}
@@ -0,0 +1,391 @@
+use crate::core::read_context::ReadContext;
+use crate::core::write_buffer::{WriteBuffer, WriteObject};
+use crate::objects::any_object::AnyObject;
+use crate::objects::tatt::{TAttFill, TAttLine, TAttMarker};
+use crate::objects::tlist::TList;
+use crate::objects::tnamed::TNamed;
+use crate::objects::tstring::TString;
+use crate::streamer::member::{read_basic_pointer, VersionHeader};
+use crate::streamer::root_streamer::StreamerMember;
+use binrw::io::{Read, Seek};
+use binrw::{BinRead, BinReaderExt, BinResult, Endian};
+use std::io;
+use std::ops::Deref;
+use std::sync::Arc;
+
+/*
+ * TGraph
+ * https://root.cern/doc/v638/classTGraph.html
+ *
+ * fNpoints points in fX and fY. fHistogram is the frame the graph was drawn in, fFunctions the fits
+ * made to the graph. Versions from 3 on are read, written by TStreamerInfo.
+ */
+#[derive(Debug, Default)]
+pub struct TGraph {
+    pub byte_count: u32,
+    pub version: u16,
+    pub tnamed: TNamed,
+    pub att_line: TAttLine,
+    pub att_fill: TAttFill,
+    pub att_marker: TAttMarker,
+    pub f_npoints: i32,
+    pub f_x: Vec<f64>,
+    pub f_y: Vec<f64>,
+    pub f_functions: Option<Arc<TList<AnyObject>>>,
+    pub f_histogram: Option<Arc<AnyObject>>,
+    pub f_minimum: f64,
+    pub f_maximum: f64,
+}
+
+impl TGraph {
+    // TGraph(n, x, y), the points of the shorter of x and y
+    pub fn new(name: &str, title: &str, x: &[f64], y: &[f64]) -> Self {
+        let n = x.len().min(y.len());
+        Self {
+            version: 4,
+            tnamed: TNamed {
+                name: TString::new(name),
+                title: TString::new(title),
+                ..Default::default()
+            },
+            att_line: TAttLine::new(1, 1, 1),
+            att_fill: TAttFill::new(0, 1000),
+            att_marker: TAttMarker::new(1, 1, 1.0),
+            f_npoints: n as i32,
+            f_x: x[..n].to_vec(),
+            f_y: y[..n].to_vec(),
+            f_minimum: -1111.0,
+            f_maximum: -1111.0,
+            ..Default::default()
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.tnamed.name
+    }
+
+    pub fn title(&self) -> &str {
+        &self.tnamed.title
+    }
+
+    pub fn npoints(&self) -> usize {
+        self.f_npoints.max(0) as usize
+    }
+
+    pub fn x(&self) -> &[f64] {
+        &self.f_x
+    }
+
+    pub fn y(&self) -> &[f64] {
+        &self.f_y
+    }
+
+    pub fn points(&self) -> impl Iterator<Item = (f64, f64)> + '_ {
+        self.f_x.iter().copied().zip(self.f_y.iter().copied())
+    }
+
+    pub fn functions(&self) -> impl Iterator<Item = &AnyObject> {
+        self.f_functions.iter().flat_map(|list| list.iter())
+    }
+}
+
+impl BinRead for TGraph {
+    type Args<'a> = (ReadContext,);
+
+    fn read_options<R: Read + Seek>(
+        reader: &mut R,
+        endian: Endian,
+        args: Self::Args<'_>,
+    ) -> BinResult<Self> {
+        let (ctx,) = args;
+        let header = VersionHeader::read(reader, endian)?;
+        check_version("TGraph", header.version, 3)?;
+        let tnamed = TNamed::read_member(reader, endian, &ctx)?;
+        let att_line = TAttLine::read_member(reader, endian, &ctx)?;
+        let att_fill = TAttFill::read_member(reader, endian, &ctx)?;
+        let att_marker = TAttMarker::read_member(reader, endian, &ctx)?;
+        let f_npoints: i32 = reader.read_type(endian)?;
+        let f_x = read_basic_pointer(reader, endian, f_npoints as i64)?;
+        let f_y = read_basic_pointer(reader, endian, f_npoints as i64)?;
+        let f_functions = ctx.read_streamed_object(reader, endian)?;
+        let f_histogram = ctx.read_object(reader, endian)?;
+        let f_minimum = reader.read_type(endian)?;
+        let f_maximum = reader.read_type(endian)?;
+        header.skip_to_end(reader)?;
+        Ok(Self {
+            byte_count: header.byte_count,
+            version: header.version,
+            tnamed,
+            att_line,
+            att_fill,
+            att_marker,
+            f_npoints,
+            f_x,
+            f_y,
+            f_functions,
+            f_histogram,
+            f_minimum,
+            f_maximum,
+        })
+    }
+}
+
+// TGraph version 4. fFunctions and fHistogram are written with their class, objects that cannot
+// be written are left out.
+impl WriteObject for TGraph {
+    fn class_name(&self) -> String {
+        String::from("TGraph")
+    }
+
+    fn title(&self) -> &str {
+        TGraph::title(self)
+    }
+
+    fn write_to(&self, buf: &mut WriteBuffer) {
+        let n = self.f_x.len().min(self.f_y.len());
+        buf.versioned(4, |buf| {
+            self.tnamed.write_to(buf);
+            self.att_line.write_to(buf);
+            self.att_fill.write_to(buf);
+            self.att_marker.write_to(buf);
+            buf.write(&(n as i32));
+            buf.basic_pointer(&self.f_x[..n]);
+            buf.basic_pointer(&self.f_y[..n]);
+            buf.write_object_or_null(self.f_functions.as_deref());
+            let histogram = self.f_histogram.as_deref();
+            buf.write_object_or_null(histogram.filter(|histogram| histogram.is_writable()));
+            buf.write(&self.f_minimum);
+            buf.write(&self.f_maximum);
+        });
+    }
+}
+
+// The errors of the points, as many as the points
+fn write_errors(buf: &mut WriteBuffer, graph: &TGraph, errors: &[f64]) {
+    let n = graph.f_x.len().min(graph.f_y.len());
+    let mut errors = errors.to_vec();
+    errors.resize(n, 0.0);
+    buf.basic_pointer(&errors);
+}
+
+/*
+ * TGraphErrors
+ * https://root.cern/doc/v638/classTGraphErrors.html
+ */
+#[derive(Debug, Default)]
+pub struct TGraphErrors {
+    pub byte_count: u32,
+    pub version: u16,
+    pub graph: TGraph,
+    pub f_ex: Vec<f64>,
+    pub f_ey: Vec<f64>,
+}
+
+impl TGraphErrors {
+    pub fn new(name: &str, title: &str, x: &[f64], y: &[f64], ex: &[f64], ey: &[f64]) -> Self {
+        let graph = TGraph::new(name, title, x, y);
+        let n = graph.npoints();
+        let errors = |errors: &[f64]| {
+            let mut errors = errors.to_vec();
+            errors.resize(n, 0.0);
+            errors
+        };
+        Self {
+            version: 3,
+            f_ex: errors(ex),
+            f_ey: errors(ey),
+            graph,
+            ..Default::default()
+        }
+    }
+
+    pub fn ex(&self) -> &[f64] {
+        &self.f_ex
+    }
+
+    pub fn ey(&self) -> &[f64] {
+        &self.f_ey
+    }
+}
+
+impl Deref for TGraphErrors {
+    type Target = TGraph;
+
+    fn deref(&self) -> &Self::Target {
+        &self.graph
+    }
+}
+
+impl BinRead for TGraphErrors {
+    type Args<'a> = (ReadContext,);
+
+    fn read_options<R: Read + Seek>(
+        reader: &mut R,
+        endian: Endian,
+        args: Self::Args<'_>,
+    ) -> BinResult<Self> {
+        let header = VersionHeader::read(reader, endian)?;
+        check_version("TGraphErrors", header.version, 3)?;
+        let graph = TGraph::read_options(reader, endian, args)?;
+        let n = graph.f_npoints as i64;
+        let f_ex = read_basic_pointer(reader, endian, n)?;
+        let f_ey = read_basic_pointer(reader, endian, n)?;
+        header.skip_to_end(reader)?;
+        Ok(Self {
+            byte_count: header.byte_count,
+            version: header.version,
+            graph,
+            f_ex,
+            f_ey,
+        })
+    }
+}
+
+impl WriteObject for TGraphErrors {
+    fn class_name(&self) -> String {
+        String::from("TGraphErrors")
+    }
+
+    fn title(&self) -> &str {
+        self.graph.title()
+    }
+
+    fn write_to(&self, buf: &mut WriteBuffer) {
+        buf.versioned(3, |buf| {
+            self.graph.write_to(buf);
+            write_errors(buf, &self.graph, &self.f_ex);
+            write_errors(buf, &self.graph, &self.f_ey);
+        });
+    }
+}
+
+/*
+ * TGraphAsymmErrors
+ * https://root.cern/doc/v638/classTGraphAsymmErrors.html
+ */
+#[derive(Debug, Default)]
+pub struct TGraphAsymmErrors {
+    pub byte_count: u32,
+    pub version: u16,
+    pub graph: TGraph,
+    pub f_exlow: Vec<f64>,
+    pub f_exhigh: Vec<f64>,
+    pub f_eylow: Vec<f64>,
+    pub f_eyhigh: Vec<f64>,
+}
+
+impl TGraphAsymmErrors {
+    pub fn ex_low(&self) -> &[f64] {
+        &self.f_exlow
+    }
+
+    pub fn ex_high(&self) -> &[f64] {
+        &self.f_exhigh
+    }
+
+    pub fn ey_low(&self) -> &[f64] {
+        &self.f_eylow
+    }
+
+    pub fn ey_high(&self) -> &[f64] {
+        &self.f_eyhigh
+    }
+}
+
+impl Deref for TGraphAsymmErrors {
+    type Target = TGraph;
+
+    fn deref(&self) -> &Self::Target {
+        &self.graph
+    }
+}
+
+impl BinRead for TGraphAsymmErrors {
+    type Args<'a> = (ReadContext,);
+
+    fn read_options<R: Read + Seek>(
+        reader: &mut R,
+        endian: Endian,
+        args: Self::Args<'_>,
+    ) -> BinResult<Self> {
+        let header = VersionHeader::read(reader, endian)?;
+        check_version("TGraphAsymmErrors", header.version, 3)?;
+        let graph = TGraph::read_options(reader, endian, args)?;
+        let n = graph.f_npoints as i64;
+        let f_exlow = read_basic_pointer(reader, endian, n)?;
+        let f_exhigh = read_basic_pointer(reader, endian, n)?;
+        let f_eylow = read_basic_pointer(reader, endian, n)?;
+        let f_eyhigh = read_basic_pointer(reader, endian, n)?;
+        header.skip_to_end(reader)?;
+        Ok(Self {
+            byte_count: header.byte_count,
+            version: header.version,
+            graph,
+            f_exlow,
+            f_exhigh,
+            f_eylow,
+            f_eyhigh,
+        })
+    }
+}
+
+impl WriteObject for TGraphAsymmErrors {
+    fn class_name(&self) -> String {
+        String::from("TGraphAsymmErrors")
+    }
+
+    fn title(&self) -> &str {
+        self.graph.title()
+    }
+
+    fn write_to(&self, buf: &mut WriteBuffer) {
+        buf.versioned(3, |buf| {
+            self.graph.write_to(buf);
+            write_errors(buf, &self.graph, &self.f_exlow);
+            write_errors(buf, &self.graph, &self.f_exhigh);
+            write_errors(buf, &self.graph, &self.f_eylow);
+            write_errors(buf, &self.graph, &self.f_eyhigh);
+        });
+    }
+}
+
+// Older versions have a hand-written streamer with another layout
+fn check_version(class: &str, version: u16, min_version: u16) -> BinResult<()> {
+    if version < min_version {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{} version {} is not supported", class, version),
+        )
+        .into());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::objects::test_buffer::Buffer;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_read_graph_errors() {
+        let mut buf = Buffer::default();
+        buf.graph_errors(
+            "efficiency",
+            &[(1.0, 0.5), (2.0, 0.75)],
+            &[0.1, 0.1],
+            &[0.05, 0.02],
+        );
+        let graph = TGraphErrors::read_be_args(&mut Cursor::new(buf.bytes), (ReadContext::new(),))
+            .expect("Failed to read TGraphErrors");
+        assert_eq!(graph.name(), "efficiency");
+        assert_eq!(graph.npoints(), 2);
+        assert_eq!(
+            graph.points().collect::<Vec<_>>(),
+            [(1.0, 0.5), (2.0, 0.75)]
+        );
+        assert_eq!(graph.ey(), [0.05, 0.02]);
+        assert_eq!(graph.functions().count(), 0);
+        assert!(graph.f_histogram.is_none());
+    }
+}
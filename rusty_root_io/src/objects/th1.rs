@@ -1,8 +1,955 @@
+use crate::core::read_context::ReadContext;
+use crate::core::write_buffer::{WriteBuffer, WriteObject};
+use crate::objects::any_object::AnyObject;
+use crate::objects::tarray::{TArray, TArrayD};
+use crate::objects::tatt::{TAttFill, TAttLine, TAttMarker};
+use crate::objects::taxis::TAxis;
+use crate::objects::tlist::TList;
+use crate::objects::tnamed::TNamed;
+use crate::objects::tstring::TString;
+use crate::streamer::member::{read_basic_pointer, VersionHeader};
+use crate::streamer::root_streamer::StreamerMember;
+use binrw::io::{Read, Seek};
+use binrw::{BinRead, BinReaderExt, BinResult, BinWrite, Endian};
+use std::io;
+use std::ops::{Deref, DerefMut};
+
+// Type of the bin contents, the last letter of the class name: TH1C, TH1S, TH1I, TH1L, TH1F, TH1D
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum BinType {
+    Char,
+    Short,
+    Int,
+    Long64,
+    Float,
+    #[default]
+    Double,
+}
+
+impl BinType {
+    // Dimension and bin type of a histogram class, None for other classes
+    pub fn from_class_name(class_name: &str) -> Option<(usize, Self)> {
+        let rest = class_name.strip_prefix("TH")?;
+        let mut chars = rest.chars();
+        let dimension = chars.next()?.to_digit(10)? as usize;
+        let bin_type = match chars.as_str() {
+            "C" => Self::Char,
+            "S" => Self::Short,
+            "I" => Self::Int,
+            "L" => Self::Long64,
+            "F" => Self::Float,
+            "D" => Self::Double,
+            _ => return None,
+        };
+        (1..=3)
+            .contains(&dimension)
+            .then_some((dimension, bin_type))
+    }
+
+    // TH1F, TH2D, ... for the dimensions 1 to 3
+    pub fn class_name(self, dimension: usize) -> &'static str {
+        const CLASSES: [[&str; 6]; 3] = [
+            ["TH1C", "TH1S", "TH1I", "TH1L", "TH1F", "TH1D"],
+            ["TH2C", "TH2S", "TH2I", "TH2L", "TH2F", "TH2D"],
+            ["TH3C", "TH3S", "TH3I", "TH3L", "TH3F", "TH3D"],
+        ];
+        CLASSES[dimension.clamp(1, 3) - 1][self as usize]
+    }
+
+    // The TArray base class holding the bin contents, converted from f64 like TH1::SetBinContent
+    fn write_array(self, values: &[f64], buf: &mut WriteBuffer) {
+        fn write<T>(values: impl Iterator<Item = T>, n: usize, buf: &mut WriteBuffer)
+        where
+            T: BinWrite,
+            for<'a> T::Args<'a>: Default,
+        {
+            buf.write(&(n as i32));
+            for value in values {
+                buf.write(&value);
+            }
+        }
+        let n = values.len();
+        let values = values.iter().copied();
+        match self {
+            Self::Char => write(values.map(|value| value as i8), n, buf),
+            Self::Short => write(values.map(|value| value as i16), n, buf),
+            Self::Int => write(values.map(|value| value as i32), n, buf),
+            Self::Long64 => write(values.map(|value| value as i64), n, buf),
+            Self::Float => write(values.map(|value| value as f32), n, buf),
+            Self::Double => write(values, n, buf),
+        }
+    }
+
+    // The TArray base class holding the bin contents
+    fn read_array<R: Read + Seek>(self, reader: &mut R, endian: Endian) -> BinResult<Vec<f64>> {
+        fn read<T, R>(reader: &mut R, endian: Endian) -> BinResult<Vec<f64>>
+        where
+            T: for<'a> BinRead<Args<'a> = ()> + Into<f64>,
+            R: Read + Seek,
+        {
+            let array = TArray::<T>::read_options(reader, endian, ())?;
+            Ok(array.f_array.into_iter().map(Into::into).collect())
+        }
+        match self {
+            Self::Char => read::<i8, R>(reader, endian),
+            Self::Short => read::<i16, R>(reader, endian),
+            Self::Int => read::<i32, R>(reader, endian),
+            Self::Long64 => {
+                let array = TArray::<i64>::read_options(reader, endian, ())?;
+                Ok(array
+                    .f_array
+                    .into_iter()
+                    .map(|value| value as f64)
+                    .collect())
+            }
+            Self::Float => read::<f32, R>(reader, endian),
+            Self::Double => read::<f64, R>(reader, endian),
+        }
+    }
+}
+
+/*
+ * TH1
+ * https://root.cern/doc/v638/classTH1.html
+ *
+ * A one dimensional histogram of any bin type, the TH1 members followed by the bin contents of
+ * the TArray base of TH1F, TH1D, ... which are kept as f64 in fArray. The cells are numbered like
+ * in ROOT: 0 is the underflow, 1..=nbins the bins and nbins + 1 the overflow. fSumw2 holds the
+ * sum of squared weights per cell once TH1::Sumw2 was called, the errors are sqrt(content)
+ * otherwise.
+ *
+ * byte_count and version are those of the TH1 base class, class_version the one of TH1F, ...
+ */
+#[derive(Debug, Default, Clone)]
+pub struct TH1 {
+    pub byte_count: u32,
+    pub version: u16,
+    pub class_version: u16,
+    pub bin_type: BinType,
+    pub tnamed: TNamed,
+    pub att_line: TAttLine,
+    pub att_fill: TAttFill,
+    pub att_marker: TAttMarker,
+    pub f_ncells: i32,
+    pub f_xaxis: TAxis,
+    pub f_yaxis: TAxis,
+    pub f_zaxis: TAxis,
+    pub f_bar_offset: i16,
+    pub f_bar_width: i16,
+    pub f_entries: f64,
+    pub f_tsumw: f64,
+    pub f_tsumw2: f64,
+    pub f_tsumwx: f64,
+    pub f_tsumwx2: f64,
+    pub f_maximum: f64,
+    pub f_minimum: f64,
+    pub f_norm_factor: f64,
+    pub f_contour: TArrayD,
+    pub f_sumw2: TArrayD,
+    pub f_option: TString,
+    pub f_functions: TList<AnyObject>,
+    pub f_buffer_size: i32,
+    pub f_buffer: Vec<f64>,
+    pub f_bin_stat_err_opt: i32,
+    pub f_stat_overflows: i32,
+    pub f_array: Vec<f64>,
+}
+
+impl TH1 {
+    // An empty TH1D with fixed binning, like TH1D(name, title, nbins, xlow, xup)
+    pub fn new(name: &str, title: &str, nbins: usize, xlow: f64, xup: f64) -> Self {
+        Self::with_axes(name, title, TAxis::new(nbins as i32, xlow, xup))
+    }
+
+    // An empty TH1D with the nbins + 1 bin edges
+    pub fn with_edges(name: &str, title: &str, edges: &[f64]) -> Self {
+        Self::with_axes(name, title, TAxis::with_edges(edges))
+    }
+
+    pub(crate) fn with_axes(name: &str, title: &str, xaxis: TAxis) -> Self {
+        let ncells = xaxis.nbins() + 2;
+        Self {
+            version: 8,
+            class_version: 3,
+            tnamed: TNamed {
+                name: TString::new(name),
+                title: TString::new(title),
+                ..Default::default()
+            },
+            // the gStyle defaults of histograms
+            att_line: TAttLine::new(602, 1, 1),
+            att_fill: TAttFill::new(0, 1001),
+            att_marker: TAttMarker::new(1, 1, 1.0),
+            f_ncells: ncells as i32,
+            f_xaxis: named_axis("xaxis", xaxis),
+            f_yaxis: named_axis("yaxis", TAxis::new(1, 0.0, 1.0)),
+            f_zaxis: named_axis("zaxis", TAxis::new(1, 0.0, 1.0)),
+            f_bar_width: 1000,
+            f_maximum: -1111.0,
+            f_minimum: -1111.0,
+            f_buffer_size: 0,
+            f_bin_stat_err_opt: 0,
+            f_stat_overflows: 2, // EStatOverflows::kNeutral
+            f_array: vec![0.0; ncells],
+            ..Default::default()
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.tnamed.name
+    }
+
+    pub fn title(&self) -> &str {
+        &self.tnamed.title
+    }
+
+    pub fn class_name(&self) -> &'static str {
+        self.bin_type.class_name(self.dimension())
+    }
+
+    pub fn x_axis(&self) -> &TAxis {
+        &self.f_xaxis
+    }
+
+    pub fn nbins_x(&self) -> usize {
+        self.f_xaxis.nbins()
+    }
+
+    pub fn entries(&self) -> f64 {
+        self.f_entries
+    }
+
+    // All cells, underflow and overflow included
+    pub fn contents(&self) -> &[f64] {
+        &self.f_array
+    }
+
+    pub fn bin_content(&self, bin: usize) -> f64 {
+        self.f_array.get(bin).copied().unwrap_or(0.0)
+    }
+
+    pub fn set_bin_content(&mut self, bin: usize, content: f64) {
+        if let Some(cell) = self.f_array.get_mut(bin) {
+            *cell = content;
+        }
+    }
+
+    pub fn has_sumw2(&self) -> bool {
+        !self.f_sumw2.is_empty()
+    }
+
+    // Sum of squared weights of a cell, the content when TH1::Sumw2 was not called
+    pub fn bin_sumw2(&self, bin: usize) -> f64 {
+        if self.has_sumw2() {
+            self.f_sumw2.get(bin).copied().unwrap_or(0.0)
+        } else {
+            self.bin_content(bin).abs()
+        }
+    }
+
+    pub fn bin_error(&self, bin: usize) -> f64 {
+        self.bin_sumw2(bin).sqrt()
+    }
+
+    pub fn set_bin_error(&mut self, bin: usize, error: f64) {
+        self.sumw2();
+        if let Some(cell) = self.f_sumw2.f_array.get_mut(bin) {
+            *cell = error * error;
+        }
+    }
+
+    // TH1::Sumw2: keep the squared weights from now on, starting from the current contents
+    pub fn sumw2(&mut self) {
+        if !self.has_sumw2() {
+            self.f_sumw2 = TArrayD {
+                f_n: self.f_array.len() as i32,
+                f_array: self.f_array.iter().map(|content| content.abs()).collect(),
+            };
+        }
+    }
+
+    // TH1::Fill(x, w) for a one dimensional histogram
+    pub fn fill(&mut self, x: f64, weight: f64) -> usize {
+        let bin = self.f_xaxis.find_bin(x);
+        self.fill_cell(bin, weight);
+        if bin >= 1 && bin <= self.nbins_x() {
+            self.f_tsumwx += weight * x;
+            self.f_tsumwx2 += weight * x * x;
+        }
+        bin
+    }
+
+    // Content, entries and the sums of weights, the moments are up to the caller
+    fn fill_cell(&mut self, cell: usize, weight: f64) {
+        if weight != 1.0 {
+            self.sumw2();
+        }
+        self.f_entries += 1.0;
+        if let Some(content) = self.f_array.get_mut(cell) {
+            *content += weight;
+        }
+        if let Some(sumw2) = self.f_sumw2.f_array.get_mut(cell) {
+            *sumw2 += weight * weight;
+        }
+        if self.is_in_range(cell) {
+            self.f_tsumw += weight;
+            self.f_tsumw2 += weight * weight;
+        }
+    }
+
+    // 1, 2 or 3, from the number of cells: axes beyond the dimension have one bin and no cells
+    pub fn dimension(&self) -> usize {
+        let nx = self.f_xaxis.nbins() + 2;
+        let ny = self.f_yaxis.nbins() + 2;
+        match self.f_array.len().max(self.f_ncells.max(0) as usize) {
+            n if n <= nx => 1,
+            n if n <= nx * ny => 2,
+            _ => 3,
+        }
+    }
+
+    // Whether a cell is a bin on every axis, not an underflow or overflow
+    pub fn is_in_range(&self, cell: usize) -> bool {
+        let nx = self.f_xaxis.nbins() + 2;
+        let ny = self.f_yaxis.nbins() + 2;
+        let indices = [cell % nx, (cell / nx) % ny, cell / nx / ny];
+        let axes = [&self.f_xaxis, &self.f_yaxis, &self.f_zaxis];
+        let dimension = self.dimension();
+        indices
+            .iter()
+            .zip(axes)
+            .enumerate()
+            .all(|(i, (index, axis))| {
+                if i < dimension {
+                    (1..=axis.nbins()).contains(index)
+                } else {
+                    *index == 0
+                }
+            })
+    }
+
+    // Sum of the bin contents, without underflow and overflow
+    pub fn integral(&self) -> f64 {
+        (0..self.f_array.len())
+            .filter(|cell| self.is_in_range(*cell))
+            .map(|cell| self.f_array[cell])
+            .sum()
+    }
+
+    // The fitted functions and other objects attached to the histogram
+    pub fn functions(&self) -> impl Iterator<Item = &AnyObject> {
+        self.f_functions.iter()
+    }
+}
+
+// The class versions written, those of ROOT 6
+const TH1_VERSION: u16 = 8;
+const TH2_VERSION: u16 = 5;
+const TH3_VERSION: u16 = 6;
+
+// The members of the TH1 base class, in the layout of version 8. Functions that cannot be written,
+// like fitted TF1, are left out of fFunctions.
+fn write_th1_base(th1: &TH1, buf: &mut WriteBuffer) {
+    buf.versioned(TH1_VERSION, |buf| {
+        th1.tnamed.write_to(buf);
+        th1.att_line.write_to(buf);
+        th1.att_fill.write_to(buf);
+        th1.att_marker.write_to(buf);
+        buf.write(&th1.f_ncells);
+        th1.f_xaxis.write_to(buf);
+        th1.f_yaxis.write_to(buf);
+        th1.f_zaxis.write_to(buf);
+        buf.write(&th1.f_bar_offset);
+        buf.write(&th1.f_bar_width);
+        buf.write(&th1.f_entries);
+        buf.write(&th1.f_tsumw);
+        buf.write(&th1.f_tsumw2);
+        buf.write(&th1.f_tsumwx);
+        buf.write(&th1.f_tsumwx2);
+        buf.write(&th1.f_maximum);
+        buf.write(&th1.f_minimum);
+        buf.write(&th1.f_norm_factor);
+        th1.f_contour.write_to(buf);
+        th1.f_sumw2.write_to(buf);
+        buf.string(&th1.f_option);
+        // `TList* fFunctions; //->` is streamed in place
+        th1.f_functions.write_to(buf);
+        let buffer = if th1.f_buffer_size > 0 {
+            &th1.f_buffer[..]
+        } else {
+            &[]
+        };
+        buf.write(&(buffer.len() as i32));
+        buf.basic_pointer(buffer);
+        buf.write(&th1.f_bin_stat_err_opt);
+        buf.write(&th1.f_stat_overflows);
+    });
+}
+
+// The class version of TH1F, TH2D, ... when it was not read
+fn class_version(th1: &TH1, default: u16) -> u16 {
+    if th1.class_version > 0 {
+        th1.class_version
+    } else {
+        default
+    }
+}
+
+fn named_axis(name: &str, mut axis: TAxis) -> TAxis {
+    axis.tnamed.name = TString::new(name);
+    axis.version = 10;
+    axis
+}
+
+// The members of the TH1 base class, from its version header
+fn read_th1_base<R: Read + Seek>(
+    reader: &mut R,
+    endian: Endian,
+    ctx: &ReadContext,
+) -> BinResult<TH1> {
+    let header = VersionHeader::read(reader, endian)?;
+    if header.version < 5 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("TH1 version {} is not supported", header.version),
+        )
+        .into());
+    }
+    let tnamed = TNamed::read_member(reader, endian, ctx)?;
+    let att_line = TAttLine::read_member(reader, endian, ctx)?;
+    let att_fill = TAttFill::read_member(reader, endian, ctx)?;
+    let att_marker = TAttMarker::read_member(reader, endian, ctx)?;
+    let f_ncells = reader.read_type(endian)?;
+    let f_xaxis = TAxis::read_options(reader, endian, (ctx.clone(),))?;
+    let f_yaxis = TAxis::read_options(reader, endian, (ctx.clone(),))?;
+    let f_zaxis = TAxis::read_options(reader, endian, (ctx.clone(),))?;
+    let f_bar_offset = reader.read_type(endian)?;
+    let f_bar_width = reader.read_type(endian)?;
+    let f_entries = reader.read_type(endian)?;
+    let f_tsumw = reader.read_type(endian)?;
+    let f_tsumw2 = reader.read_type(endian)?;
+    let f_tsumwx = reader.read_type(endian)?;
+    let f_tsumwx2 = reader.read_type(endian)?;
+    let f_maximum = reader.read_type(endian)?;
+    let f_minimum = reader.read_type(endian)?;
+    let f_norm_factor = reader.read_type(endian)?;
+    let f_contour = TArrayD::read_options(reader, endian, ())?;
+    let f_sumw2 = TArrayD::read_options(reader, endian, ())?;
+    let f_option = TString::read_options(reader, endian, ())?;
+    // `TList* fFunctions; //->` is streamed in place
+    let f_functions = TList::read_options(reader, endian, (ctx.clone(),))?;
+    let f_buffer_size: i32 = reader.read_type(endian)?;
+    let f_buffer = read_basic_pointer(reader, endian, f_buffer_size as i64)?;
+    let f_bin_stat_err_opt = if header.version >= 7 {
+        reader.read_type(endian)?
+    } else {
+        0
+    };
+    let f_stat_overflows = if header.version >= 8 {
+        reader.read_type(endian)?
+    } else {
+        2
+    };
+    header.skip_to_end(reader)?;
+    Ok(TH1 {
+        byte_count: header.byte_count,
+        version: header.version,
+        class_version: 0,
+        bin_type: BinType::default(),
+        tnamed,
+        att_line,
+        att_fill,
+        att_marker,
+        f_ncells,
+        f_xaxis,
+        f_yaxis,
+        f_zaxis,
+        f_bar_offset,
+        f_bar_width,
+        f_entries,
+        f_tsumw,
+        f_tsumw2,
+        f_tsumwx,
+        f_tsumwx2,
+        f_maximum,
+        f_minimum,
+        f_norm_factor,
+        f_contour,
+        f_sumw2,
+        f_option,
+        f_functions,
+        f_buffer_size,
+        f_buffer,
+        f_bin_stat_err_opt,
+        f_stat_overflows,
+        f_array: Vec::new(),
+    })
+}
+
+// TH1F, TH1D, ...: the TH1 base followed by the TArray base, read from the version header
+impl BinRead for TH1 {
+    type Args<'a> = (ReadContext, BinType);
+
+    fn read_options<R: Read + Seek>(
+        reader: &mut R,
+        endian: Endian,
+        args: Self::Args<'_>,
+    ) -> BinResult<Self> {
+        let (ctx, bin_type) = args;
+        let header = VersionHeader::read(reader, endian)?;
+        let mut th1 = read_th1_base(reader, endian, &ctx)?;
+        th1.f_array = bin_type.read_array(reader, endian)?;
+        th1.class_version = header.version;
+        th1.bin_type = bin_type;
+        header.skip_to_end(reader)?;
+        Ok(th1)
+    }
+}
+
+// TH1F, TH1D, ...: the TH1 base followed by the TArray base
+impl WriteObject for TH1 {
+    fn class_name(&self) -> String {
+        TH1::class_name(self).to_string()
+    }
+
+    fn title(&self) -> &str {
+        TH1::title(self)
+    }
+
+    fn write_to(&self, buf: &mut WriteBuffer) {
+        buf.versioned(class_version(self, 3), |buf| {
+            write_th1_base(self, buf);
+            self.bin_type.write_array(&self.f_array, buf);
+        });
+    }
+}
+
+/*
+ * TH2
+ * https://root.cern/doc/v638/classTH2.html
+ *
+ * A two dimensional histogram, TH1 with the sums for the y moments. The cell of bins (ix, iy) is
+ * ix + (nx + 2) * iy. Derefs to the TH1 for the contents, errors and the x axis.
+ */
+#[derive(Debug, Default, Clone)]
+pub struct TH2 {
+    pub byte_count: u32,
+    pub version: u16,
+    pub th1: TH1,
+    pub f_scalefactor: f64,
+    pub f_tsumwy: f64,
+    pub f_tsumwy2: f64,
+    pub f_tsumwxy: f64,
+}
+
+impl TH2 {
+    // TH2D, TH2F, ... as for the TH1 base
+    pub fn class_name(&self) -> &'static str {
+        self.th1.class_name()
+    }
+
+    // An empty TH2D with fixed binning
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        name: &str,
+        title: &str,
+        nbins_x: usize,
+        xlow: f64,
+        xup: f64,
+        nbins_y: usize,
+        ylow: f64,
+        yup: f64,
+    ) -> Self {
+        Self::with_axes(
+            name,
+            title,
+            TAxis::new(nbins_x as i32, xlow, xup),
+            TAxis::new(nbins_y as i32, ylow, yup),
+        )
+    }
+
+    pub fn with_axes(name: &str, title: &str, xaxis: TAxis, yaxis: TAxis) -> Self {
+        let mut th1 = TH1::with_axes(name, title, xaxis);
+        let ncells = (th1.f_xaxis.nbins() + 2) * (yaxis.nbins() + 2);
+        th1.class_version = 4;
+        th1.f_yaxis = named_axis("yaxis", yaxis);
+        th1.f_ncells = ncells as i32;
+        th1.f_array = vec![0.0; ncells];
+        Self {
+            version: 5,
+            th1,
+            f_scalefactor: 1.0,
+            ..Default::default()
+        }
+    }
+
+    pub fn y_axis(&self) -> &TAxis {
+        &self.th1.f_yaxis
+    }
+
+    pub fn nbins_y(&self) -> usize {
+        self.th1.f_yaxis.nbins()
+    }
+
+    // Cell of the bins (ix, iy), TH1::GetBin
+    pub fn bin(&self, ix: usize, iy: usize) -> usize {
+        ix + (self.th1.nbins_x() + 2) * iy
+    }
+
+    pub fn bin_content_xy(&self, ix: usize, iy: usize) -> f64 {
+        self.th1.bin_content(self.bin(ix, iy))
+    }
+
+    pub fn bin_error_xy(&self, ix: usize, iy: usize) -> f64 {
+        self.th1.bin_error(self.bin(ix, iy))
+    }
+
+    // TH2::Fill(x, y, w)
+    pub fn fill(&mut self, x: f64, y: f64, weight: f64) -> usize {
+        let ix = self.th1.f_xaxis.find_bin(x);
+        let iy = self.th1.f_yaxis.find_bin(y);
+        let cell = self.bin(ix, iy);
+        self.th1.fill_cell(cell, weight);
+        if self.th1.is_in_range(cell) {
+            self.th1.f_tsumwx += weight * x;
+            self.th1.f_tsumwx2 += weight * x * x;
+            self.f_tsumwy += weight * y;
+            self.f_tsumwy2 += weight * y * y;
+            self.f_tsumwxy += weight * x * y;
+        }
+        cell
+    }
+}
+
+impl Deref for TH2 {
+    type Target = TH1;
+
+    fn deref(&self) -> &Self::Target {
+        &self.th1
+    }
+}
+
+impl DerefMut for TH2 {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.th1
+    }
+}
+
+// TH2F, TH2D, ...: the TH2 base followed by the TArray base
+impl BinRead for TH2 {
+    type Args<'a> = (ReadContext, BinType);
+
+    fn read_options<R: Read + Seek>(
+        reader: &mut R,
+        endian: Endian,
+        args: Self::Args<'_>,
+    ) -> BinResult<Self> {
+        let (ctx, bin_type) = args;
+        let header = VersionHeader::read(reader, endian)?;
+        let th2_header = VersionHeader::read(reader, endian)?;
+        let mut th1 = read_th1_base(reader, endian, &ctx)?;
+        let f_scalefactor = reader.read_type(endian)?;
+        let f_tsumwy = reader.read_type(endian)?;
+        let f_tsumwy2 = reader.read_type(endian)?;
+        let f_tsumwxy = reader.read_type(endian)?;
+        th2_header.skip_to_end(reader)?;
+        th1.f_array = bin_type.read_array(reader, endian)?;
+        th1.class_version = header.version;
+        th1.bin_type = bin_type;
+        header.skip_to_end(reader)?;
+        Ok(Self {
+            byte_count: th2_header.byte_count,
+            version: th2_header.version,
+            th1,
+            f_scalefactor,
+            f_tsumwy,
+            f_tsumwy2,
+            f_tsumwxy,
+        })
+    }
+}
+
+// TH2F, TH2D, ...: the TH2 base followed by the TArray base
+impl WriteObject for TH2 {
+    fn class_name(&self) -> String {
+        self.th1.class_name().to_string()
+    }
+
+    fn title(&self) -> &str {
+        self.th1.title()
+    }
+
+    fn write_to(&self, buf: &mut WriteBuffer) {
+        buf.versioned(class_version(&self.th1, 4), |buf| {
+            buf.versioned(TH2_VERSION, |buf| {
+                write_th1_base(&self.th1, buf);
+                buf.write(&self.f_scalefactor);
+                buf.write(&self.f_tsumwy);
+                buf.write(&self.f_tsumwy2);
+                buf.write(&self.f_tsumwxy);
+            });
+            self.th1.bin_type.write_array(&self.th1.f_array, buf);
+        });
+    }
+}
+
+/*
+ * TH3
+ * https://root.cern/doc/v638/classTH3.html
+ *
+ * A three dimensional histogram, TH1 with the sums for the y and z moments. The cell of bins
+ * (ix, iy, iz) is ix + (nx + 2) * (iy + (ny + 2) * iz). The TAtt3D base has no members.
+ */
+#[derive(Debug, Default, Clone)]
+pub struct TH3 {
+    pub byte_count: u32,
+    pub version: u16,
+    pub th1: TH1,
+    pub f_tsumwy: f64,
+    pub f_tsumwy2: f64,
+    pub f_tsumwxy: f64,
+    pub f_tsumwz: f64,
+    pub f_tsumwz2: f64,
+    pub f_tsumwxz: f64,
+    pub f_tsumwyz: f64,
+}
+
+impl TH3 {
+    // TH3D, TH3F, ... as for the TH1 base
+    pub fn class_name(&self) -> &'static str {
+        self.th1.class_name()
+    }
+
+    // An empty TH3D over the three axes
+    pub fn with_axes(name: &str, title: &str, xaxis: TAxis, yaxis: TAxis, zaxis: TAxis) -> Self {
+        let mut th1 = TH1::with_axes(name, title, xaxis);
+        let ncells = (th1.f_xaxis.nbins() + 2) * (yaxis.nbins() + 2) * (zaxis.nbins() + 2);
+        th1.class_version = 4;
+        th1.f_yaxis = named_axis("yaxis", yaxis);
+        th1.f_zaxis = named_axis("zaxis", zaxis);
+        th1.f_ncells = ncells as i32;
+        th1.f_array = vec![0.0; ncells];
+        Self {
+            version: 6,
+            th1,
+            ..Default::default()
+        }
+    }
+
+    pub fn y_axis(&self) -> &TAxis {
+        &self.th1.f_yaxis
+    }
+
+    pub fn z_axis(&self) -> &TAxis {
+        &self.th1.f_zaxis
+    }
+
+    pub fn nbins_y(&self) -> usize {
+        self.th1.f_yaxis.nbins()
+    }
+
+    pub fn nbins_z(&self) -> usize {
+        self.th1.f_zaxis.nbins()
+    }
+
+    // Cell of the bins (ix, iy, iz), TH1::GetBin
+    pub fn bin(&self, ix: usize, iy: usize, iz: usize) -> usize {
+        ix + (self.th1.nbins_x() + 2) * (iy + (self.nbins_y() + 2) * iz)
+    }
+
+    pub fn bin_content_xyz(&self, ix: usize, iy: usize, iz: usize) -> f64 {
+        self.th1.bin_content(self.bin(ix, iy, iz))
+    }
+
+    // TH3::Fill(x, y, z, w)
+    pub fn fill(&mut self, x: f64, y: f64, z: f64, weight: f64) -> usize {
+        let ix = self.th1.f_xaxis.find_bin(x);
+        let iy = self.th1.f_yaxis.find_bin(y);
+        let iz = self.th1.f_zaxis.find_bin(z);
+        let cell = self.bin(ix, iy, iz);
+        self.th1.fill_cell(cell, weight);
+        if self.th1.is_in_range(cell) {
+            self.th1.f_tsumwx += weight * x;
+            self.th1.f_tsumwx2 += weight * x * x;
+            self.f_tsumwy += weight * y;
+            self.f_tsumwy2 += weight * y * y;
+            self.f_tsumwxy += weight * x * y;
+            self.f_tsumwz += weight * z;
+            self.f_tsumwz2 += weight * z * z;
+            self.f_tsumwxz += weight * x * z;
+            self.f_tsumwyz += weight * y * z;
+        }
+        cell
+    }
+}
+
+impl Deref for TH3 {
+    type Target = TH1;
+
+    fn deref(&self) -> &Self::Target {
+        &self.th1
+    }
+}
+
+impl DerefMut for TH3 {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.th1
+    }
+}
+
+// TH3F, TH3D, ...: the TH3 base followed by the TArray base
+impl BinRead for TH3 {
+    type Args<'a> = (ReadContext, BinType);
+
+    fn read_options<R: Read + Seek>(
+        reader: &mut R,
+        endian: Endian,
+        args: Self::Args<'_>,
+    ) -> BinResult<Self> {
+        let (ctx, bin_type) = args;
+        let header = VersionHeader::read(reader, endian)?;
+        let th3_header = VersionHeader::read(reader, endian)?;
+        let mut th1 = read_th1_base(reader, endian, &ctx)?;
+        // TAtt3D
+        VersionHeader::read(reader, endian)?.skip_to_end(reader)?;
+        let f_tsumwy = reader.read_type(endian)?;
+        let f_tsumwy2 = reader.read_type(endian)?;
+        let f_tsumwxy = reader.read_type(endian)?;
+        let f_tsumwz = reader.read_type(endian)?;
+        let f_tsumwz2 = reader.read_type(endian)?;
+        let f_tsumwxz = reader.read_type(endian)?;
+        let f_tsumwyz = reader.read_type(endian)?;
+        th3_header.skip_to_end(reader)?;
+        th1.f_array = bin_type.read_array(reader, endian)?;
+        th1.class_version = header.version;
+        th1.bin_type = bin_type;
+        header.skip_to_end(reader)?;
+        Ok(Self {
+            byte_count: th3_header.byte_count,
+            version: th3_header.version,
+            th1,
+            f_tsumwy,
+            f_tsumwy2,
+            f_tsumwxy,
+            f_tsumwz,
+            f_tsumwz2,
+            f_tsumwxz,
+            f_tsumwyz,
+        })
+    }
+}
+
+// TH3F, TH3D, ...: the TH3 base followed by the TArray base
+impl WriteObject for TH3 {
+    fn class_name(&self) -> String {
+        self.th1.class_name().to_string()
+    }
+
+    fn title(&self) -> &str {
+        self.th1.title()
+    }
+
+    fn write_to(&self, buf: &mut WriteBuffer) {
+        buf.versioned(class_version(&self.th1, 4), |buf| {
+            buf.versioned(TH3_VERSION, |buf| {
+                write_th1_base(&self.th1, buf);
+                // TAtt3D
+                buf.versioned(1, |_| {});
+                buf.write(&self.f_tsumwy);
+                buf.write(&self.f_tsumwy2);
+                buf.write(&self.f_tsumwxy);
+                buf.write(&self.f_tsumwz);
+                buf.write(&self.f_tsumwz2);
+                buf.write(&self.f_tsumwxz);
+                buf.write(&self.f_tsumwyz);
+            });
+            self.th1.bin_type.write_array(&self.th1.f_array, buf);
+        });
+    }
+}
+
 #[cfg(test)]
 mod test {
+    use super::*;
     use crate::core::utils::debug_in_ascii;
+    use crate::objects::test_buffer::{Buffer, Hist};
     use crate::objects::tkey::TKey;
-    use crate::objects::tstring::TString;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_read_th1d_and_th2d() {
+        let mut buf = Buffer::default();
+        buf.th1d(&Hist {
+            name: "pt",
+            edges: &[0.0, 10.0, 30.0],
+            contents: &[1.0, 4.0, 2.5, 0.5],
+            sumw2: &[1.0, 2.0, 1.25, 0.25],
+            entries: 8.0,
+            bits: 0,
+        });
+        let th1 = TH1::read_be_args(
+            &mut Cursor::new(buf.bytes),
+            (ReadContext::new(), BinType::Double),
+        )
+        .expect("Failed to read TH1D");
+        assert_eq!((th1.name(), th1.class_name()), ("pt", "TH1D"));
+        assert_eq!((th1.version, th1.class_version), (8, 3));
+        assert_eq!(th1.nbins_x(), 2);
+        assert_eq!(th1.x_axis().edges(), [0.0, 10.0, 30.0]);
+        assert_eq!(th1.x_axis().find_bin(12.0), 2);
+        assert_eq!(th1.bin_content(2), 2.5);
+        assert_eq!(th1.bin_error(1), 2f64.sqrt());
+        assert_eq!(th1.integral(), 6.5);
+        assert_eq!(th1.entries(), 8.0);
+        assert!(th1.is_in_range(1) && !th1.is_in_range(3));
+
+        let mut buf = Buffer::default();
+        buf.th2d(
+            &Hist {
+                name: "map",
+                edges: &[0.0, 1.0, 2.0],
+                contents: &(0..12).map(f64::from).collect::<Vec<_>>(),
+                sumw2: &[],
+                entries: 66.0,
+                bits: 0,
+            },
+            &[0.0, 5.0, 10.0],
+        );
+        let th2 = TH2::read_be_args(
+            &mut Cursor::new(buf.bytes),
+            (ReadContext::new(), BinType::Double),
+        )
+        .expect("Failed to read TH2D");
+        assert_eq!((th2.class_name(), th2.version), ("TH2D", 5));
+        assert_eq!(th2.dimension(), 2);
+        assert_eq!((th2.nbins_x(), th2.nbins_y()), (2, 2));
+        assert_eq!(th2.bin(1, 2), 9);
+        assert_eq!(th2.bin_content_xy(1, 2), 9.0);
+        assert_eq!(th2.bin_error_xy(2, 1), 6f64.sqrt());
+        // bins (1, 1), (2, 1), (1, 2) and (2, 2)
+        assert_eq!(th2.integral(), 5.0 + 6.0 + 9.0 + 10.0);
+    }
+
+    #[test]
+    fn test_fill() {
+        let mut th1 = TH1::new("h", "", 4, 0.0, 4.0);
+        th1.fill(0.5, 1.0);
+        th1.fill(2.5, 1.0);
+        th1.fill(-1.0, 1.0);
+        assert!(!th1.has_sumw2());
+        th1.fill(2.5, 2.0);
+        assert_eq!(th1.contents(), [1.0, 1.0, 0.0, 3.0, 0.0, 0.0]);
+        assert_eq!(th1.bin_error(3), 5f64.sqrt());
+        assert_eq!((th1.entries(), th1.f_tsumw, th1.f_tsumw2), (4.0, 4.0, 6.0));
+        assert_eq!(th1.f_tsumwx, 0.5 + 2.5 + 5.0);
+
+        let mut th2 = TH2::new("h2", "", 2, 0.0, 2.0, 3, 0.0, 3.0);
+        let cell = th2.fill(1.5, 2.5, 1.0);
+        assert_eq!(cell, th2.bin(2, 3));
+        assert_eq!((th2.f_tsumwy, th2.f_tsumwxy), (2.5, 3.75));
+        assert_eq!(th2.contents().len(), 20);
+    }
+
     #[test]
     fn test_th1() {
         let path =
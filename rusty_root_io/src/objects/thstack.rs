@@ -0,0 +1,117 @@
+use crate::core::read_context::ReadContext;
+use crate::objects::any_object::AnyObject;
+use crate::objects::th1::TH1;
+use crate::objects::tlist::TList;
+use crate::objects::tnamed::TNamed;
+use crate::streamer::member::VersionHeader;
+use crate::streamer::root_streamer::StreamerMember;
+use binrw::io::{Read, Seek};
+use binrw::{BinRead, BinReaderExt, BinResult, Endian};
+use std::io;
+use std::sync::Arc;
+
+/*
+ * THStack
+ * https://root.cern/doc/v638/classTHStack.html
+ *
+ * The histograms of the stack in fHists, in the order they were added. fStack, the cumulated sums
+ * drawn by THStack::Paint, is not written. fHistogram is the frame the stack was drawn in.
+ */
+#[derive(Debug, Default)]
+pub struct THStack {
+    pub byte_count: u32,
+    pub version: u16,
+    pub tnamed: TNamed,
+    pub f_hists: Option<Arc<TList<AnyObject>>>,
+    pub f_histogram: Option<Arc<AnyObject>>,
+    pub f_maximum: f64,
+    pub f_minimum: f64,
+}
+
+impl THStack {
+    pub fn name(&self) -> &str {
+        &self.tnamed.name
+    }
+
+    pub fn title(&self) -> &str {
+        &self.tnamed.title
+    }
+
+    // Every object of fHists, with the draw option it was added with
+    pub fn hists(&self) -> impl Iterator<Item = (&AnyObject, &str)> {
+        self.f_hists
+            .iter()
+            .flat_map(|list| list.objects.iter())
+            .map(|element| (&*element.object, element.option.as_str()))
+    }
+
+    // The histograms of fHists, bottom of the stack first
+    pub fn histograms(&self) -> impl Iterator<Item = &TH1> {
+        self.hists().filter_map(|(object, _)| object.as_th1())
+    }
+
+    pub fn len(&self) -> usize {
+        self.hists().count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl BinRead for THStack {
+    type Args<'a> = (ReadContext,);
+
+    fn read_options<R: Read + Seek>(
+        reader: &mut R,
+        endian: Endian,
+        args: Self::Args<'_>,
+    ) -> BinResult<Self> {
+        let (ctx,) = args;
+        let header = VersionHeader::read(reader, endian)?;
+        if header.version < 2 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("THStack version {} is not supported", header.version),
+            )
+            .into());
+        }
+        let tnamed = TNamed::read_member(reader, endian, &ctx)?;
+        let f_hists = ctx.read_streamed_object(reader, endian)?;
+        let f_histogram = ctx.read_object(reader, endian)?;
+        let f_maximum = reader.read_type(endian)?;
+        let f_minimum = reader.read_type(endian)?;
+        header.skip_to_end(reader)?;
+        Ok(Self {
+            byte_count: header.byte_count,
+            version: header.version,
+            tnamed,
+            f_hists,
+            f_histogram,
+            f_maximum,
+            f_minimum,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::objects::test_buffer::Buffer;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_read_stack() {
+        let mut buf = Buffer::default();
+        buf.stack("mll", &["ttbar", "zjets"]);
+        let stack = THStack::read_be_args(&mut Cursor::new(buf.bytes), (ReadContext::new(),))
+            .expect("Failed to read THStack");
+        assert_eq!(stack.name(), "mll");
+        assert_eq!(stack.len(), 2);
+        let names: Vec<&str> = stack.histograms().map(TH1::name).collect();
+        assert_eq!(names, ["ttbar", "zjets"]);
+        assert_eq!(stack.hists().nth(1).unwrap().1, "hist");
+        assert!(stack.f_histogram.is_none());
+        assert_eq!(stack.f_maximum, -1111.0);
+    }
+}
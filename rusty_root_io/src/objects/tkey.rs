@@ -1,15 +1,15 @@
 use crate::core::compression::CompressionAlgorithm;
 use crate::objects::tstring::TString;
-use binrw::{BinRead, BinReaderExt, BinResult, Endian};
+use binrw::{BinRead, BinReaderExt, BinResult, BinWrite, BinWriterExt, Endian};
 use std::fmt;
-use std::io::{Cursor, Read, Seek, SeekFrom};
+use std::io::{Cursor, Read, Seek, SeekFrom, Write};
 use std::sync::Arc;
 
 /*
  * https://root.cern/doc/v638/tdirectory.html
  */
 
-#[derive(Default)]
+#[derive(Default, Clone)]
 pub struct TKey {
     pub n_bytes: u32,
     pub version: u16,
@@ -112,7 +112,48 @@ impl BinRead for TKey {
     }
 }
 
+// TKey::FillBuffer, the counterpart of read_options
+impl BinWrite for TKey {
+    type Args<'a> = ();
+
+    fn write_options<W: Write + Seek>(
+        &self,
+        writer: &mut W,
+        endian: Endian,
+        _args: Self::Args<'_>,
+    ) -> BinResult<()> {
+        writer.write_type(&self.n_bytes, endian)?;
+        writer.write_type(&self.version, endian)?;
+        writer.write_type(&self.obj_len, endian)?;
+        writer.write_type(&self.datime, endian)?;
+        writer.write_type(&self.key_len, endian)?;
+        writer.write_type(&self.cycle, endian)?;
+        if self.is_large() {
+            writer.write_type(&self.seek_key, endian)?;
+            writer.write_type(&self.seek_p_dir, endian)?;
+        } else {
+            writer.write_type(&(self.seek_key as u32), endian)?;
+            writer.write_type(&(self.seek_p_dir as u32), endian)?;
+        }
+        self.class_name.write_options(writer, endian, ())?;
+        self.name.write_options(writer, endian, ())?;
+        self.title.write_options(writer, endian, ())?;
+        Ok(())
+    }
+}
+
 impl TKey {
+    // Seeks are written as 64 bit integers from version 1000 on, for keys beyond 2GB
+    pub fn is_large(&self) -> bool {
+        self.version > 1000
+    }
+
+    // TKey::Sizeof, the length of the header written by BinWrite
+    pub fn sizeof(&self) -> u16 {
+        let seeks = if self.is_large() { 16 } else { 8 };
+        (18 + seeks + self.class_name.sizeof() + self.name.sizeof() + self.title.sizeof()) as u16
+    }
+
     // the payload is deserialized, there's no way to correctly read the payload without the tkey header
     // Decompress the payload and return a Cursor over the combined key data and decompressed payload
     pub fn decompress_full<R: Read + Seek>(&self, reader: &mut R) -> BinResult<Cursor<Arc<[u8]>>> {
@@ -124,12 +165,33 @@ impl TKey {
         let mut compressed_data = vec![0u8; compressed_size as usize];
         reader.read_exact(&mut compressed_data)?;
 
-        let decompressed_payload = CompressionAlgorithm::decompress(&compressed_data)?;
-        assert_eq!(decompressed_payload.len(), self.obj_len as usize);
-
         let mut combined = key_data;
-        combined.extend_from_slice(&decompressed_payload);
-        return Ok(Cursor::new(Arc::from(combined)));
+        if compressed_size == self.obj_len {
+            // objects that do not shrink are written uncompressed
+            combined.extend_from_slice(&compressed_data);
+        } else {
+            // compressed in blocks, each with a 9 byte header holding its compressed size
+            let mut block = compressed_data.as_slice();
+            while block.len() >= 9 {
+                let size = 9 + u32::from_le_bytes([block[3], block[4], block[5], 0]) as usize;
+                let (data, rest) = block.split_at(size.min(block.len()));
+                combined.extend_from_slice(&CompressionAlgorithm::decompress(data)?);
+                block = rest;
+            }
+        }
+        if combined.len() != self.key_len as usize + self.obj_len as usize {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!(
+                    "key {} decompressed to {} bytes instead of {}",
+                    self.name.as_str(),
+                    combined.len() - self.key_len as usize,
+                    self.obj_len
+                ),
+            )
+            .into());
+        }
+        Ok(Cursor::new(Arc::from(combined)))
     }
 
     // Move the seek to the key's data and then call decompress_full
@@ -149,7 +211,8 @@ impl TKey {
         args: (&Self,), // Pass the header in so we can decompress
     ) -> BinResult<T>
     where
-        T: for<'a> BinRead<Args<'a> = ()>,
+        T: BinRead,
+        for<'a> T::Args<'a>: Default,
         R: Read + Seek,
     {
         let (header,) = args;
@@ -157,19 +220,53 @@ impl TKey {
         let mut combined_cursor = header.decompress_full(reader)?;
         combined_cursor.seek(SeekFrom::Start(header.key_len as u64))?; // Skip the key data to position at the payload
 
-        T::read_options(&mut combined_cursor, endian, ())
+        // the cursor starts at the key, so a default read context maps offsets of the key buffer
+        T::read_options(&mut combined_cursor, endian, Default::default())
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::core::utils::decode_datime;
+    use crate::core::utils::{decode_datime, encode_datime};
     #[test]
     fn test_decode_datime() {
         let mut key = TKey::new();
         key.datime = 2054579214;
         assert_eq!(decode_datime(key.datime), "2025-09-27 06:16:14");
+        assert_eq!(encode_datime(2025, 9, 27, 6, 16, 14), key.datime);
+    }
+
+    #[test]
+    fn test_decompress_blocks() {
+        use crate::core::compression::K_MAX_ZIP_BUF;
+
+        // a payload larger than one compression block is compressed in two
+        let payload: Vec<u8> = (0..K_MAX_ZIP_BUF + 4096).map(|i| (i % 251) as u8).collect();
+        let record = CompressionAlgorithm::compress(101, &payload).unwrap();
+        let first = 9 + u32::from_le_bytes([record[3], record[4], record[5], 0]) as usize;
+        assert!(first < record.len());
+        assert_eq!(&record[first..first + 2], b"ZL");
+
+        let mut key = TKey {
+            version: 4,
+            obj_len: payload.len() as u32,
+            cycle: 1,
+            class_name: TString::new("TObjString"),
+            name: TString::new("big"),
+            ..TKey::new()
+        };
+        key.key_len = key.sizeof();
+        key.n_bytes = key.key_len as u32 + record.len() as u32;
+        let mut file = Cursor::new(Vec::new());
+        key.write_be(&mut file).unwrap();
+        file.get_mut().extend_from_slice(&record);
+
+        let buffer = key.decompress_full(&mut file).unwrap();
+        assert_eq!(
+            &buffer.get_ref()[key.key_len as usize..],
+            payload.as_slice()
+        );
     }
 
     use binrw::BinRead;
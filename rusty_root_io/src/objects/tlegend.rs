@@ -0,0 +1,242 @@
+use crate::core::read_context::ReadContext;
+use crate::objects::any_object::AnyObject;
+use crate::objects::tlist::TList;
+use crate::objects::tstring::TString;
+use crate::streamer::registry::{FieldKind, FieldSchema};
+use crate::streamer::schema_reader::{read_list_member, read_with_schema, required_schema};
+use binrw::io::{Read, Seek};
+use binrw::{BinRead, BinResult, Endian};
+use std::sync::Arc;
+
+/*
+ * TLegend
+ * https://root.cern/doc/v638/classTLegend.html
+ *
+ * The entries are TLegendEntry in fPrimitives. Like TPad, TLegend and TLegendEntry are read from
+ * the streamer info of their file, which the read context must carry.
+ */
+#[derive(Debug, Default)]
+pub struct TLegend {
+    pub byte_count: u32,
+    pub version: u16,
+    pub f_name: TString,
+    pub f_primitives: Option<Arc<TList<AnyObject>>>,
+    pub f_ncolumns: i32,
+}
+
+impl TLegend {
+    pub fn name(&self) -> &str {
+        &self.f_name
+    }
+
+    pub fn entries(&self) -> impl Iterator<Item = &TLegendEntry> {
+        self.f_primitives
+            .iter()
+            .flat_map(|list| list.iter())
+            .filter_map(|object| match object {
+                AnyObject::TLegendEntry(entry) => Some(&**entry),
+                _ => None,
+            })
+    }
+}
+
+impl BinRead for TLegend {
+    type Args<'a> = (ReadContext,);
+
+    fn read_options<R: Read + Seek>(
+        reader: &mut R,
+        endian: Endian,
+        args: Self::Args<'_>,
+    ) -> BinResult<Self> {
+        let (ctx,) = args;
+        let schema = required_schema(&ctx, "TLegend")?;
+        let mut legend = Self::default();
+        let mut visit = |field: &FieldSchema, reader: &mut R| -> BinResult<bool> {
+            match (field.name.as_str(), &field.kind) {
+                // the name of the TPave base
+                ("fName", FieldKind::TString) => {
+                    legend.f_name = TString::read_options(reader, endian, ())?
+                }
+                ("fPrimitives", kind) => {
+                    legend.f_primitives = read_list_member(reader, endian, &ctx, kind)?
+                }
+                ("fNColumns", FieldKind::I32) => {
+                    legend.f_ncolumns = i32::read_options(reader, endian, ())?
+                }
+                _ => return Ok(false),
+            }
+            Ok(true)
+        };
+        let header = read_with_schema(reader, endian, &ctx, &schema, &mut visit)?;
+        legend.byte_count = header.byte_count;
+        legend.version = header.version;
+        Ok(legend)
+    }
+}
+
+/*
+ * TLegendEntry
+ * https://root.cern/doc/v638/classTLegendEntry.html
+ *
+ * fObject is the object the entry stands for, usually a reference to a primitive of the same pad.
+ * fOption tells what is drawn next to the label: "l" line, "p" marker, "f" fill, "e" error bar.
+ */
+#[derive(Debug, Default)]
+pub struct TLegendEntry {
+    pub byte_count: u32,
+    pub version: u16,
+    pub f_object: Option<Arc<AnyObject>>,
+    pub f_label: TString,
+    pub f_option: TString,
+}
+
+impl TLegendEntry {
+    pub fn object(&self) -> Option<&AnyObject> {
+        self.f_object.as_deref()
+    }
+
+    pub fn label(&self) -> &str {
+        &self.f_label
+    }
+
+    pub fn option(&self) -> &str {
+        &self.f_option
+    }
+}
+
+impl BinRead for TLegendEntry {
+    type Args<'a> = (ReadContext,);
+
+    fn read_options<R: Read + Seek>(
+        reader: &mut R,
+        endian: Endian,
+        args: Self::Args<'_>,
+    ) -> BinResult<Self> {
+        let (ctx,) = args;
+        let schema = required_schema(&ctx, "TLegendEntry")?;
+        let mut entry = Self::default();
+        let mut visit = |field: &FieldSchema, reader: &mut R| -> BinResult<bool> {
+            match field.name.as_str() {
+                "fObject" => entry.f_object = ctx.read_object(reader, endian)?,
+                "fLabel" => entry.f_label = TString::read_options(reader, endian, ())?,
+                "fOption" => entry.f_option = TString::read_options(reader, endian, ())?,
+                _ => return Ok(false),
+            }
+            Ok(true)
+        };
+        let header = read_with_schema(reader, endian, &ctx, &schema, &mut visit)?;
+        entry.byte_count = header.byte_count;
+        entry.version = header.version;
+        Ok(entry)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::objects::test_buffer::{schema, Buffer, Hist};
+    use crate::streamer::streamerinfo::StreamerRegistry;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_read_legend_entries() {
+        let tobject = FieldKind::Base(schema("TObject", 1, &[]));
+        let tlist = schema("TList", 5, &[]);
+        let tpave = schema(
+            "TPave",
+            3,
+            &[
+                ("TObject", tobject.clone()),
+                ("fX1NDC", FieldKind::F64),
+                ("fName", FieldKind::TString),
+            ],
+        );
+        let mut registry = StreamerRegistry::new();
+        registry.classes.insert(
+            "TLegend".to_string(),
+            schema(
+                "TLegend",
+                2,
+                &[
+                    ("TPave", FieldKind::Base(tpave)),
+                    ("fPrimitives", FieldKind::ObjectPointer(tlist)),
+                    ("fMargin", FieldKind::F32),
+                    ("fNColumns", FieldKind::I32),
+                ],
+            ),
+        );
+        let tobject_schema = schema("TObject", 1, &[]);
+        registry.classes.insert(
+            "TLegendEntry".to_string(),
+            schema(
+                "TLegendEntry",
+                1,
+                &[
+                    ("TObject", tobject),
+                    ("fObject", FieldKind::ObjectPointer(tobject_schema)),
+                    ("fLabel", FieldKind::TString),
+                    ("fOption", FieldKind::TString),
+                ],
+            ),
+        );
+
+        let mut buf = Buffer::default();
+        // the histogram of the pad, referenced by the first entry
+        let histogram = buf.bytes.len() as u32 + crate::core::constant::K_MAP_OFFSET;
+        buf.object("TH1D", |buf| {
+            buf.th1d(&Hist {
+                name: "data",
+                edges: &[0.0, 1.0],
+                contents: &[0.0, 5.0, 0.0],
+                sumw2: &[],
+                entries: 5.0,
+                bits: 0,
+            })
+        });
+        let legend_start = buf.bytes.len();
+        buf.versioned(2, |buf| {
+            buf.versioned(3, |buf| {
+                buf.tobject();
+                buf.f64s(&[0.6]);
+                buf.string("legend");
+            });
+            buf.list(2, |buf| {
+                for (label, option) in [("Data", "ep"), ("Empty", "")] {
+                    buf.object("TLegendEntry", |buf| {
+                        buf.versioned(1, |buf| {
+                            buf.tobject();
+                            if option.is_empty() {
+                                buf.null();
+                            } else {
+                                buf.put(&histogram.to_be_bytes());
+                            }
+                            buf.string(label);
+                            buf.string(option);
+                        })
+                    });
+                    buf.string("");
+                }
+            });
+            buf.put(&0.25f32.to_be_bytes());
+            buf.put(&2i32.to_be_bytes());
+        });
+
+        let ctx = ReadContext::new().with_schemas(Arc::new(registry));
+        let mut reader = Cursor::new(buf.bytes);
+        let data = ctx
+            .read_object::<AnyObject, _>(&mut reader, Endian::Big)
+            .expect("Failed to read the histogram");
+        assert_eq!(reader.position(), legend_start as u64);
+        let legend = TLegend::read_options(&mut reader, Endian::Big, (ctx,))
+            .expect("Failed to read TLegend");
+        assert_eq!((legend.name(), legend.f_ncolumns), ("legend", 2));
+        let entries: Vec<&TLegendEntry> = legend.entries().collect();
+        assert_eq!(entries.len(), 2);
+        assert_eq!((entries[0].label(), entries[0].option()), ("Data", "ep"));
+        assert!(Arc::ptr_eq(
+            entries[0].f_object.as_ref().unwrap(),
+            data.as_ref().unwrap()
+        ));
+        assert!(entries[1].object().is_none());
+    }
+}
@@ -1,16 +1,23 @@
+use crate::core::read_context::ReadContext;
+use crate::core::write_buffer::{WriteBuffer, WriteObject};
 use crate::objects::tobject::TObject;
 use crate::objects::tstring::TString;
-use binrw::BinRead;
+use binrw::io::{Read, Seek};
+use binrw::{BinRead, BinResult, Endian};
 use std::ops::Deref;
+use std::sync::Arc;
 
+// Read by hand like TList::Streamer, not derived, see root_streamer.rs
 #[binrw::binread]
-#[derive(Default, Debug)]
+#[br(import(ctx: ReadContext))]
+#[derive(Debug)]
 pub struct TList<T>
 where
-    // 1. T must own its data (no temporary references)
-    T: BinRead + 'static,
-    // 2. T must be readable with no arguments for any lifetime 'a
-    for<'a> T: BinRead<Args<'a> = ()>,
+    // 1. T must own its data (no temporary references) and be shareable, back-references in the
+    //    buffer resolve to the same object
+    T: BinRead + Send + Sync + 'static,
+    // 2. T must be readable with the buffer's read context for any lifetime 'a
+    for<'a> T: BinRead<Args<'a> = (ReadContext,)>,
 {
     #[br(map = |x: u32| x & crate::core::constant::K_BYTECOUNTMASK)]
     pub byte_count: u32,
@@ -18,36 +25,132 @@ where
     pub tobject: TObject,
     pub f_name: TString,
     pub n_objects: u32,
-    #[br(count = n_objects)]
+    #[br(parse_with = read_elements, args(ctx, n_objects))]
     pub objects: Vec<TListElement<T>>,
 }
 
 impl<T> TList<T>
 where
-    T: BinRead + 'static,
-    for<'a> T: BinRead<Args<'a> = ()>,
+    T: BinRead + Send + Sync + 'static,
+    for<'a> T: BinRead<Args<'a> = (ReadContext,)>,
 {
     pub fn iter(&self) -> impl Iterator<Item = &T> {
-        self.objects.iter().map(|elem| &elem.object)
+        self.objects.iter().map(|elem| &*elem.object)
     }
 }
 
+// An empty list, without requiring T: Default like the derive would
+impl<T> Default for TList<T>
+where
+    T: BinRead + Send + Sync + 'static,
+    for<'a> T: BinRead<Args<'a> = (ReadContext,)>,
+{
+    fn default() -> Self {
+        Self {
+            byte_count: 0,
+            version: 0,
+            tobject: TObject::default(),
+            f_name: TString::default(),
+            n_objects: 0,
+            objects: Vec::new(),
+        }
+    }
+}
+
+// Clones share the elements, like the back-references of a buffer
+impl<T> Clone for TList<T>
+where
+    T: BinRead + Send + Sync + 'static,
+    for<'a> T: BinRead<Args<'a> = (ReadContext,)>,
+{
+    fn clone(&self) -> Self {
+        Self {
+            byte_count: self.byte_count,
+            version: self.version,
+            tobject: self.tobject.clone(),
+            f_name: self.f_name.clone(),
+            n_objects: self.n_objects,
+            objects: self
+                .objects
+                .iter()
+                .map(|element| TListElement {
+                    object: element.object.clone(),
+                    option: element.option.clone(),
+                })
+                .collect(),
+        }
+    }
+}
+
+// THashList only adds a hash table for lookups, it is streamed by TList::Streamer
+pub type THashList<T> = TList<T>;
+
 // TListElement is needed because in TList every object is read and followed a 'l_option" and a "option_string", not written in the link and is hidden...
-#[binrw::binread]
 #[derive(Default, Debug)]
 pub struct TListElement<T>
 where
-    T: BinRead + 'static,
-    for<'a> T: BinRead<Args<'a> = ()>,
+    T: BinRead + Send + Sync + 'static,
+    for<'a> T: BinRead<Args<'a> = (ReadContext,)>,
 {
-    pub object: T,
+    pub object: Arc<T>,
     pub option: TString,
 }
 
+// Like TList::Streamer, null objects are dropped
+fn read_elements<T, R: Read + Seek>(
+    reader: &mut R,
+    endian: Endian,
+    args: (ReadContext, u32),
+) -> BinResult<Vec<TListElement<T>>>
+where
+    T: BinRead + Send + Sync + 'static,
+    for<'a> T: BinRead<Args<'a> = (ReadContext,)>,
+{
+    let (ctx, n_objects) = args;
+    let mut elements = Vec::with_capacity(n_objects as usize);
+    for _ in 0..n_objects {
+        let object = ctx.read_object(reader, endian)?;
+        let option = TString::read_options(reader, endian, ())?;
+        if let Some(object) = object {
+            elements.push(TListElement { object, option });
+        }
+    }
+    Ok(elements)
+}
+
+// TList::Streamer: every object with its class tag, followed by its option. Objects that cannot
+// be written are left out.
+impl<T> WriteObject for TList<T>
+where
+    T: BinRead + WriteObject + Send + Sync + 'static,
+    for<'a> T: BinRead<Args<'a> = (ReadContext,)>,
+{
+    fn class_name(&self) -> String {
+        String::from("TList")
+    }
+
+    fn write_to(&self, buf: &mut WriteBuffer) {
+        let elements: Vec<&TListElement<T>> = self
+            .objects
+            .iter()
+            .filter(|element| element.object.is_writable())
+            .collect();
+        buf.versioned(5, |buf| {
+            buf.tobject(&self.tobject);
+            buf.string(&self.f_name);
+            buf.write(&(elements.len() as u32));
+            for element in elements {
+                buf.write_object(&*element.object);
+                buf.string(&element.option);
+            }
+        });
+    }
+}
+
 impl<T> Deref for TListElement<T>
 where
-    T: BinRead + 'static,
-    for<'a> T: BinRead<Args<'a> = ()>,
+    T: BinRead + Send + Sync + 'static,
+    for<'a> T: BinRead<Args<'a> = (ReadContext,)>,
 {
     type Target = T;
     fn deref(&self) -> &Self::Target {
@@ -0,0 +1,95 @@
+use crate::core::read_context::ReadContext;
+use crate::objects::any_object::AnyObject;
+use crate::objects::tobject::TObject;
+use crate::objects::tstring::TString;
+use crate::streamer::member::VersionHeader;
+use binrw::io::{Read, Seek};
+use binrw::{BinRead, BinReaderExt, BinResult, Endian};
+use std::sync::Arc;
+
+/*
+ * TMap
+ * https://root.cern/doc/v638/classTMap.html
+ *
+ * Key/value pairs of objects of any class, streamed as the number of pairs followed by every key
+ * and its value, both through a pointer. Keys are usually TObjStrings, values may be null.
+ */
+#[derive(Debug, Default)]
+pub struct TMap {
+    pub byte_count: u32,
+    pub version: u16,
+    pub tobject: TObject,
+    pub name: TString,
+    pub pairs: Vec<TPair>,
+}
+
+#[derive(Debug)]
+pub struct TPair {
+    pub key: Arc<AnyObject>,
+    pub value: Option<Arc<AnyObject>>,
+}
+
+impl TMap {
+    // Value of the key with this name (the string of a TObjString key)
+    pub fn get(&self, key: &str) -> Option<&AnyObject> {
+        self.pairs
+            .iter()
+            .find(|pair| pair.key.name() == key)
+            .and_then(|pair| pair.value.as_deref())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&AnyObject, Option<&AnyObject>)> {
+        self.pairs
+            .iter()
+            .map(|pair| (&*pair.key, pair.value.as_deref()))
+    }
+
+    pub fn len(&self) -> usize {
+        self.pairs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pairs.is_empty()
+    }
+}
+
+impl BinRead for TMap {
+    type Args<'a> = (ReadContext,);
+
+    fn read_options<R: Read + Seek>(
+        reader: &mut R,
+        endian: Endian,
+        args: Self::Args<'_>,
+    ) -> BinResult<Self> {
+        let (ctx,) = args;
+        let header = VersionHeader::read(reader, endian)?;
+        let tobject = if header.version > 2 {
+            TObject::read_options(reader, endian, ())?
+        } else {
+            TObject::default()
+        };
+        let name = if header.version > 1 {
+            TString::read_options(reader, endian, ())?
+        } else {
+            TString::default()
+        };
+        let n_pairs: i32 = reader.read_type(endian)?;
+        let mut pairs = Vec::with_capacity(n_pairs.max(0) as usize);
+        for _ in 0..n_pairs {
+            let key = ctx.read_object::<AnyObject, _>(reader, endian)?;
+            let value = ctx.read_object::<AnyObject, _>(reader, endian)?;
+            // like TMap::Streamer, pairs without key are dropped
+            if let Some(key) = key {
+                pairs.push(TPair { key, value });
+            }
+        }
+        header.skip_to_end(reader)?;
+        Ok(Self {
+            byte_count: header.byte_count,
+            version: header.version,
+            tobject,
+            name,
+            pairs,
+        })
+    }
+}
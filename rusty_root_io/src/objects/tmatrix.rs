@@ -0,0 +1,459 @@
+use crate::core::read_context::ReadContext;
+use crate::objects::tobject::TObject;
+use crate::streamer::member::{read_basic_pointer, read_values, VersionHeader};
+use crate::streamer::registry::{ClassSchema, FieldKind, FieldSchema};
+use crate::streamer::root_streamer::{class_schema, RootStreamer, StreamerMember};
+use binrw::io::{Read, Seek};
+use binrw::{BinRead, BinReaderExt, BinResult, Endian};
+use std::fmt::Debug;
+use std::io;
+use std::sync::Arc;
+
+/*
+ * TMatrixT<Element> and TMatrixTSym<Element>
+ * https://root.cern/doc/v638/classTMatrixT.html
+ * https://root.cern/doc/v638/classTMatrixTSym.html
+ *
+ * Both derive from TMatrixTBase, which holds the shape: fNrows x fNcols with the row and column
+ * lower bounds fRowLwb and fColLwb. The fNelems elements are stored row by row.
+ *
+ * TMatrixT is streamed from its streamer info: the TMatrixTBase base with its own byte count, then
+ * the elements as `Element* fElements; //[fNelems]`. TMatrixTSym has a custom streamer: the members
+ * of TMatrixTBase directly after its version, then only the upper triangle, row by row.
+ */
+
+// Element types of the matrix and vector classes and the class names they are written with
+pub trait MatrixElement:
+    StreamerMember + for<'a> BinRead<Args<'a> = ()> + Copy + Debug + Default + Send + Sync + 'static
+{
+    const BASE_CLASS: &'static str;
+    const MATRIX_CLASS: &'static str;
+    const SYM_CLASS: &'static str;
+    const VECTOR_CLASS: &'static str;
+}
+
+impl MatrixElement for f64 {
+    const BASE_CLASS: &'static str = "TMatrixTBase<double>";
+    const MATRIX_CLASS: &'static str = "TMatrixT<double>";
+    const SYM_CLASS: &'static str = "TMatrixTSym<double>";
+    const VECTOR_CLASS: &'static str = "TVectorT<double>";
+}
+
+impl MatrixElement for f32 {
+    const BASE_CLASS: &'static str = "TMatrixTBase<float>";
+    const MATRIX_CLASS: &'static str = "TMatrixT<float>";
+    const SYM_CLASS: &'static str = "TMatrixTSym<float>";
+    const VECTOR_CLASS: &'static str = "TVectorT<float>";
+}
+
+#[derive(Debug, Default)]
+pub struct TMatrixTBase<T> {
+    pub byte_count: u32,
+    pub version: u16,
+    pub tobject: TObject,
+    pub n_rows: i32,
+    pub n_cols: i32,
+    pub row_lwb: i32,
+    pub col_lwb: i32,
+    pub n_elems: i32,
+    pub n_row_index: i32,
+    pub tol: T,
+}
+
+#[derive(Debug, Default)]
+pub struct TMatrixT<T> {
+    pub byte_count: u32,
+    pub version: u16,
+    pub base: TMatrixTBase<T>,
+    pub elements: Vec<T>,
+}
+
+// The elements hold the full matrix, the lower triangle is mirrored from the upper one
+#[derive(Debug, Default)]
+pub struct TMatrixTSym<T> {
+    pub byte_count: u32,
+    pub version: u16,
+    pub base: TMatrixTBase<T>,
+    pub elements: Vec<T>,
+}
+
+pub type TMatrixD = TMatrixT<f64>;
+pub type TMatrixF = TMatrixT<f32>;
+pub type TMatrixDSym = TMatrixTSym<f64>;
+pub type TMatrixFSym = TMatrixTSym<f32>;
+
+impl<T: MatrixElement> TMatrixTBase<T> {
+    // The members after the version of the class (TMatrixTBase::Streamer of the streamer info)
+    fn read_members<R: Read + Seek>(reader: &mut R, endian: Endian) -> BinResult<Self> {
+        let tobject = TObject::read_options(reader, endian, ())?;
+        let n_rows = reader.read_type(endian)?;
+        let n_cols = reader.read_type(endian)?;
+        let row_lwb = reader.read_type(endian)?;
+        let col_lwb = reader.read_type(endian)?;
+        let n_elems = reader.read_type(endian)?;
+        let n_row_index = reader.read_type(endian)?;
+        let tol = T::read_options(reader, endian, ())?;
+        Ok(Self {
+            tobject,
+            n_rows,
+            n_cols,
+            row_lwb,
+            col_lwb,
+            n_elems,
+            n_row_index,
+            tol,
+            ..Self::default()
+        })
+    }
+
+    pub fn rows(&self) -> usize {
+        self.n_rows.max(0) as usize
+    }
+
+    pub fn cols(&self) -> usize {
+        self.n_cols.max(0) as usize
+    }
+
+    // Index in the elements of (row, col), counted from the lower bounds like TMatrixT::operator()
+    fn index(&self, row: i32, col: i32) -> Option<usize> {
+        let (i, j) = (row - self.row_lwb, col - self.col_lwb);
+        if i < 0 || i >= self.n_rows || j < 0 || j >= self.n_cols {
+            return None;
+        }
+        Some((i * self.n_cols + j) as usize)
+    }
+}
+
+impl<T: MatrixElement> BinRead for TMatrixTBase<T> {
+    type Args<'a> = (ReadContext,);
+
+    fn read_options<R: Read + Seek>(
+        reader: &mut R,
+        endian: Endian,
+        _args: Self::Args<'_>,
+    ) -> BinResult<Self> {
+        let header = VersionHeader::read(reader, endian)?;
+        let base = Self::read_members(reader, endian)?;
+        header.skip_to_end(reader)?;
+        Ok(Self {
+            byte_count: header.byte_count,
+            version: header.version,
+            ..base
+        })
+    }
+}
+
+impl<T: MatrixElement> TMatrixT<T> {
+    pub fn rows(&self) -> usize {
+        self.base.rows()
+    }
+
+    pub fn cols(&self) -> usize {
+        self.base.cols()
+    }
+
+    // Element at (row, col), None outside of the row and column bounds
+    pub fn get(&self, row: i32, col: i32) -> Option<T> {
+        self.base
+            .index(row, col)
+            .and_then(|index| self.elements.get(index).copied())
+    }
+
+    pub fn row(&self, row: i32) -> Option<&[T]> {
+        let start = self.base.index(row, self.base.col_lwb)?;
+        self.elements.get(start..start + self.cols())
+    }
+}
+
+impl<T: MatrixElement> BinRead for TMatrixT<T> {
+    type Args<'a> = (ReadContext,);
+
+    fn read_options<R: Read + Seek>(
+        reader: &mut R,
+        endian: Endian,
+        args: Self::Args<'_>,
+    ) -> BinResult<Self> {
+        let header = VersionHeader::read(reader, endian)?;
+        if header.version < 3 {
+            return Err(unsupported_version(T::MATRIX_CLASS, header.version));
+        }
+        let base = TMatrixTBase::read_options(reader, endian, args)?;
+        let elements = read_basic_pointer(reader, endian, base.n_elems as i64)?;
+        header.skip_to_end(reader)?;
+        Ok(Self {
+            byte_count: header.byte_count,
+            version: header.version,
+            base,
+            elements,
+        })
+    }
+}
+
+impl<T: MatrixElement> TMatrixTSym<T> {
+    pub fn rows(&self) -> usize {
+        self.base.rows()
+    }
+
+    pub fn cols(&self) -> usize {
+        self.base.cols()
+    }
+
+    pub fn get(&self, row: i32, col: i32) -> Option<T> {
+        self.base
+            .index(row, col)
+            .and_then(|index| self.elements.get(index).copied())
+    }
+
+    pub fn row(&self, row: i32) -> Option<&[T]> {
+        let start = self.base.index(row, self.base.col_lwb)?;
+        self.elements.get(start..start + self.cols())
+    }
+}
+
+impl<T: MatrixElement> BinRead for TMatrixTSym<T> {
+    type Args<'a> = (ReadContext,);
+
+    fn read_options<R: Read + Seek>(
+        reader: &mut R,
+        endian: Endian,
+        _args: Self::Args<'_>,
+    ) -> BinResult<Self> {
+        let header = VersionHeader::read(reader, endian)?;
+        let base = TMatrixTBase::<T>::read_members(reader, endian)?;
+        let n = base.rows();
+        if base.cols() != n {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{} of {}x{} is not square", T::SYM_CLASS, n, base.cols()),
+            )
+            .into());
+        }
+        let mut elements = vec![T::default(); n * n];
+        for i in 0..n {
+            let upper = read_values::<T, _>(reader, endian, n - i)?;
+            for (k, value) in upper.into_iter().enumerate() {
+                elements[i * n + i + k] = value;
+                elements[(i + k) * n + i] = value;
+            }
+        }
+        header.skip_to_end(reader)?;
+        Ok(Self {
+            byte_count: header.byte_count,
+            version: header.version,
+            base,
+            elements,
+        })
+    }
+}
+
+fn unsupported_version(class: &str, version: u16) -> binrw::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("{} version {} is not supported", class, version),
+    )
+    .into()
+}
+
+impl<T: MatrixElement> RootStreamer for TMatrixTBase<T> {
+    const CLASS_NAME: &'static str = T::BASE_CLASS;
+    const CLASS_VERSION: u16 = 5;
+    const CHECKSUM: u32 = 0;
+
+    fn class_schema() -> Arc<ClassSchema> {
+        class_schema::<Self>(|| {
+            let mut fields = vec![FieldSchema {
+                name: "TObject".to_string(),
+                kind: FieldKind::Base(TObject::class_schema()),
+            }];
+            for name in [
+                "fNrows",
+                "fNcols",
+                "fRowLwb",
+                "fColLwb",
+                "fNelems",
+                "fNrowIndex",
+            ] {
+                fields.push(FieldSchema {
+                    name: name.to_string(),
+                    kind: FieldKind::I32,
+                });
+            }
+            fields.push(FieldSchema {
+                name: "fTol".to_string(),
+                kind: T::field_kind(),
+            });
+            fields
+        })
+    }
+}
+
+// Schema of TMatrixT and TMatrixTSym in the streamer info
+fn matrix_fields<T: MatrixElement>() -> Vec<FieldSchema> {
+    vec![
+        FieldSchema {
+            name: T::BASE_CLASS.to_string(),
+            kind: FieldKind::Base(TMatrixTBase::<T>::class_schema()),
+        },
+        FieldSchema {
+            name: "fElements".to_string(),
+            kind: FieldKind::BasicPointer {
+                elem: Box::new(T::field_kind()),
+                count: "fNelems".to_string(),
+            },
+        },
+    ]
+}
+
+impl<T: MatrixElement> RootStreamer for TMatrixT<T> {
+    const CLASS_NAME: &'static str = T::MATRIX_CLASS;
+    const CLASS_VERSION: u16 = 4;
+    const CHECKSUM: u32 = 0;
+
+    fn class_schema() -> Arc<ClassSchema> {
+        class_schema::<Self>(matrix_fields::<T>)
+    }
+}
+
+impl<T: MatrixElement> RootStreamer for TMatrixTSym<T> {
+    const CLASS_NAME: &'static str = T::SYM_CLASS;
+    const CLASS_VERSION: u16 = 2;
+    const CHECKSUM: u32 = 0;
+
+    fn class_schema() -> Arc<ClassSchema> {
+        class_schema::<Self>(matrix_fields::<T>)
+    }
+}
+
+macro_rules! matrix_member {
+    ($($class:ident),*) => {
+        $(
+            impl<T: MatrixElement> StreamerMember for $class<T> {
+                fn read_member<R: Read + Seek>(
+                    reader: &mut R,
+                    endian: Endian,
+                    ctx: &ReadContext,
+                ) -> BinResult<Self> {
+                    Self::read_options(reader, endian, (ctx.clone(),))
+                }
+
+                fn field_kind() -> FieldKind {
+                    FieldKind::Object(Self::class_schema())
+                }
+            }
+        )*
+    };
+}
+
+matrix_member!(TMatrixTBase, TMatrixT, TMatrixTSym);
+
+#[cfg(feature = "nalgebra")]
+mod nalgebra_conversion {
+    use super::*;
+    use nalgebra::{DMatrix, Scalar};
+
+    impl<T: MatrixElement + Scalar> From<&TMatrixT<T>> for DMatrix<T> {
+        fn from(matrix: &TMatrixT<T>) -> Self {
+            DMatrix::from_row_slice(matrix.rows(), matrix.cols(), &matrix.elements)
+        }
+    }
+
+    impl<T: MatrixElement + Scalar> From<&TMatrixTSym<T>> for DMatrix<T> {
+        fn from(matrix: &TMatrixTSym<T>) -> Self {
+            DMatrix::from_row_slice(matrix.rows(), matrix.cols(), &matrix.elements)
+        }
+    }
+}
+
+#[cfg(feature = "ndarray")]
+mod ndarray_conversion {
+    use super::*;
+    use ndarray::Array2;
+
+    impl<T: MatrixElement> From<&TMatrixT<T>> for Array2<T> {
+        fn from(matrix: &TMatrixT<T>) -> Self {
+            Array2::from_shape_vec((matrix.rows(), matrix.cols()), matrix.elements.clone())
+                .expect("fNelems is fNrows x fNcols")
+        }
+    }
+
+    impl<T: MatrixElement> From<&TMatrixTSym<T>> for Array2<T> {
+        fn from(matrix: &TMatrixTSym<T>) -> Self {
+            Array2::from_shape_vec((matrix.rows(), matrix.cols()), matrix.elements.clone())
+                .expect("the elements hold the full matrix")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::constant::K_HAS_BYTECOUNT;
+    use std::io::Cursor;
+
+    fn with_byte_count(version: u16, body: &[u8]) -> Vec<u8> {
+        let mut bytes = (K_HAS_BYTECOUNT | (body.len() as u32 + 2))
+            .to_be_bytes()
+            .to_vec();
+        bytes.extend_from_slice(&version.to_be_bytes());
+        bytes.extend_from_slice(body);
+        bytes
+    }
+
+    // TMatrixTBase members of a rows x cols matrix with lower bounds 1 and 0
+    fn base_members(rows: i32, cols: i32) -> Vec<u8> {
+        let mut bytes = 1u16.to_be_bytes().to_vec();
+        bytes.extend_from_slice(&[0; 8]);
+        for value in [rows, cols, 1, 0, rows * cols, rows + 1] {
+            bytes.extend_from_slice(&value.to_be_bytes());
+        }
+        bytes.extend_from_slice(&1e-16f64.to_be_bytes());
+        bytes
+    }
+
+    #[test]
+    fn test_read_matrix() {
+        let mut body = with_byte_count(5, &base_members(2, 3));
+        body.push(1);
+        for value in [1.0f64, 2.0, 3.0, 4.0, 5.0, 6.0] {
+            body.extend_from_slice(&value.to_be_bytes());
+        }
+        let bytes = with_byte_count(4, &body);
+        let matrix = TMatrixD::read_be_args(&mut Cursor::new(bytes), (ReadContext::new(),))
+            .expect("Failed to read TMatrixD");
+        assert_eq!((matrix.rows(), matrix.cols()), (2, 3));
+        assert_eq!(matrix.base.tol, 1e-16);
+        assert_eq!(matrix.get(1, 0), Some(1.0));
+        assert_eq!(matrix.get(2, 1), Some(5.0));
+        assert_eq!(matrix.get(0, 0), None);
+        assert_eq!(matrix.row(2), Some(&[4.0, 5.0, 6.0][..]));
+    }
+
+    #[test]
+    fn test_read_symmetric_matrix() {
+        let mut body = base_members(3, 3);
+        for value in [1.0f64, 0.1, 0.2, 2.0, 0.3, 3.0] {
+            body.extend_from_slice(&value.to_be_bytes());
+        }
+        let bytes = with_byte_count(2, &body);
+        let matrix = TMatrixDSym::read_be_args(&mut Cursor::new(bytes), (ReadContext::new(),))
+            .expect("Failed to read TMatrixDSym");
+        assert_eq!(
+            matrix.elements,
+            [1.0, 0.1, 0.2, 0.1, 2.0, 0.3, 0.2, 0.3, 3.0]
+        );
+        assert_eq!(matrix.get(3, 1), matrix.get(2, 2));
+
+        #[cfg(feature = "nalgebra")]
+        {
+            let covariance = nalgebra::DMatrix::from(&matrix);
+            assert_eq!(covariance[(2, 1)], 0.3);
+            assert_eq!(covariance, covariance.transpose());
+        }
+        #[cfg(feature = "ndarray")]
+        {
+            let covariance = ndarray::Array2::from(&matrix);
+            assert_eq!(covariance[[0, 2]], 0.2);
+            assert_eq!(covariance, covariance.t());
+        }
+    }
+}
@@ -1,8 +1,9 @@
-use crate::core::constant::K_BYTECOUNTMASK;
+use crate::core::write_buffer::{WriteBuffer, WriteObject};
 use crate::objects::tobject::TObject;
 use crate::objects::tstring::TString;
+use crate::RootStreamer;
 use binrw::io::{Read, Seek, SeekFrom};
-use binrw::{binread, BinRead, BinResult};
+use binrw::{BinRead, BinResult};
 
 /*
 * -Begin TNamed object (Base class of TStreamerInfo)
@@ -19,13 +20,14 @@ use binrw::{binread, BinRead, BinResult};
                       |  (Class title may be zero length)
  -End TNamed object
 */
-#[binread]
-#[br(big)]
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone, RootStreamer)]
+#[root(class = "TNamed", version = 1, checksum = 0xdfb74a3c)]
 pub struct TNamed {
-    #[br(map = |x: u32| x & K_BYTECOUNTMASK)]
+    #[root(byte_count)]
     pub byte_count: u32,
+    #[root(version)]
     pub version: u16,
+    #[root(base)]
     pub tobject: TObject,
     pub name: TString,
     pub title: TString,
@@ -34,6 +36,24 @@ pub struct TNamed {
 impl TNamed {
     pub fn read_from<R: Read + Seek>(reader: &mut R, offset: u64) -> BinResult<Self> {
         reader.seek(SeekFrom::Start(offset))?;
-        Self::read_options(reader, binrw::Endian::Big, ())
+        Self::read_options(reader, binrw::Endian::Big, Default::default())
+    }
+}
+
+impl WriteObject for TNamed {
+    fn class_name(&self) -> String {
+        String::from("TNamed")
+    }
+
+    fn title(&self) -> &str {
+        &self.title
+    }
+
+    fn write_to(&self, buf: &mut WriteBuffer) {
+        buf.versioned(1, |buf| {
+            buf.tobject(&self.tobject);
+            buf.string(&self.name);
+            buf.string(&self.title);
+        });
     }
 }
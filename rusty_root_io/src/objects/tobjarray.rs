@@ -1,22 +1,29 @@
 use crate::core::constant::K_BYTECOUNTMASK;
+use crate::core::read_context::ReadContext;
 use crate::core::utils::ClassInfo;
+use crate::core::write_buffer::{WriteBuffer, WriteObject};
 use crate::objects::tobject::TObject;
 use crate::objects::tstring::TString;
-use binrw::{binread, BinRead};
+use binrw::io::{Read, Seek};
+use binrw::{binread, BinRead, BinResult, Endian};
 use std::default::Default;
+use std::sync::Arc;
 
+// Read by hand like TObjArray::Streamer, not derived, see root_streamer.rs
 #[binread]
-#[br(big)]
+#[br(big, import(ctx: ReadContext))]
 #[derive(Debug)]
 pub struct TObjArray<T>
 where
-    // 1. T must own its data (no temporary references)
-    T: BinRead + 'static,
-    // 2. T must be readable with no arguments for any lifetime 'a
-    for<'a> T: BinRead<Args<'a> = ()>,
+    // 1. T must own its data (no temporary references) and be shareable, back-references in the
+    //    buffer resolve to the same object
+    T: BinRead + Send + Sync + 'static,
+    // 2. T must be readable with the buffer's read context for any lifetime 'a
+    for<'a> T: BinRead<Args<'a> = (ReadContext,)>,
 {
     #[br(map = |x: u32| x & K_BYTECOUNTMASK)]
     pub byte_count: u32,
+    #[br(args(ctx.clone()))]
     pub class_info: ClassInfo,
     #[br(map = |x: u32| x & K_BYTECOUNTMASK)]
     pub remaining_bytes: u32,
@@ -25,14 +32,30 @@ where
     pub name: TString,
     pub n_objects: u32,
     pub f_lower_bound: i32,
-    #[br(count = n_objects)]
-    pub objects: Vec<T>,
+    // empty slots are written as kNullTag
+    #[br(parse_with = read_slots, args(ctx, n_objects))]
+    pub objects: Vec<Option<Arc<T>>>,
+}
+
+fn read_slots<T, R: Read + Seek>(
+    reader: &mut R,
+    endian: Endian,
+    args: (ReadContext, u32),
+) -> BinResult<Vec<Option<Arc<T>>>>
+where
+    T: BinRead + Send + Sync + 'static,
+    for<'a> T: BinRead<Args<'a> = (ReadContext,)>,
+{
+    let (ctx, n_objects) = args;
+    (0..n_objects)
+        .map(|_| ctx.read_object(reader, endian))
+        .collect()
 }
 
 impl<T> Default for TObjArray<T>
 where
-    T: BinRead + 'static,
-    for<'a> T: BinRead<Args<'a> = ()>,
+    T: BinRead + Send + Sync + 'static,
+    for<'a> T: BinRead<Args<'a> = (ReadContext,)>,
 {
     fn default() -> Self {
         Self {
@@ -51,10 +74,34 @@ where
 
 impl<T> TObjArray<T>
 where
-    T: BinRead + 'static,
-    for<'a> T: BinRead<Args<'a> = ()>,
+    T: BinRead + Send + Sync + 'static,
+    for<'a> T: BinRead<Args<'a> = (ReadContext,)>,
 {
+    // Iterate over the filled slots
     pub fn iter(&self) -> impl Iterator<Item = &T> {
-        self.objects.iter()
+        self.objects.iter().filter_map(|slot| slot.as_deref())
+    }
+}
+
+// TObjArray::Streamer, empty slots are written as kNullTag
+impl<T> WriteObject for TObjArray<T>
+where
+    T: BinRead + WriteObject + Send + Sync + 'static,
+    for<'a> T: BinRead<Args<'a> = (ReadContext,)>,
+{
+    fn class_name(&self) -> String {
+        String::from("TObjArray")
+    }
+
+    fn write_to(&self, buf: &mut WriteBuffer) {
+        buf.versioned(3, |buf| {
+            buf.tobject(&self.tobject);
+            buf.string(&self.name);
+            buf.write(&(self.objects.len() as u32));
+            buf.write(&self.f_lower_bound);
+            for slot in &self.objects {
+                buf.write_object_or_null(slot.as_deref());
+            }
+        });
     }
 }
@@ -7,7 +7,7 @@ use crate::core::constant::K_IS_REFERENCED;
 * https://root.cern/doc/v638/tobject.html
 */
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone)]
 pub struct TObject {
     pub version: u16,
     pub f_uniqueid: u32,
@@ -0,0 +1,46 @@
+use crate::core::write_buffer::{WriteBuffer, WriteObject};
+use crate::objects::tobject::TObject;
+use crate::objects::tstring::TString;
+use crate::RootStreamer;
+use std::ops::Deref;
+
+/*
+ * TObjString
+ * https://root.cern/doc/v638/classTObjString.html
+ *
+ * A TString that can be put in collections, e.g. the bin labels of an axis (TAxis::fLabels) or
+ * the keys of a TMap. Its name is the string itself.
+ */
+#[derive(Debug, Default, RootStreamer)]
+#[root(class = "TObjString", version = 1)]
+pub struct TObjString {
+    #[root(byte_count)]
+    pub byte_count: u32,
+    #[root(version)]
+    pub version: u16,
+    #[root(base)]
+    pub tobject: TObject,
+    #[root(name = "fString")]
+    pub string: TString,
+}
+
+impl Deref for TObjString {
+    type Target = String;
+
+    fn deref(&self) -> &Self::Target {
+        &self.string
+    }
+}
+
+impl WriteObject for TObjString {
+    fn class_name(&self) -> String {
+        String::from("TObjString")
+    }
+
+    fn write_to(&self, buf: &mut WriteBuffer) {
+        buf.versioned(1, |buf| {
+            buf.tobject(&self.tobject);
+            buf.string(&self.string);
+        });
+    }
+}
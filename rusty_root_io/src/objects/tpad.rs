@@ -0,0 +1,364 @@
+use crate::core::read_context::ReadContext;
+use crate::objects::any_object::AnyObject;
+use crate::objects::tgraph::TGraph;
+use crate::objects::th1::TH1;
+use crate::objects::thstack::THStack;
+use crate::objects::tlegend::TLegend;
+use crate::objects::tlist::TList;
+use crate::objects::tstring::TString;
+use crate::streamer::member::VersionHeader;
+use crate::streamer::registry::{FieldKind, FieldSchema};
+use crate::streamer::schema_reader::{read_list_member, read_with_schema, required_schema};
+use binrw::io::{Read, Seek, SeekFrom};
+use binrw::{BinRead, BinReaderExt, BinResult, Endian};
+use std::io;
+use std::ops::Deref;
+use std::sync::Arc;
+
+/*
+ * TPad
+ * https://root.cern/doc/v638/classTPad.html
+ *
+ * The objects drawn in the pad are in fPrimitives with their draw options, sub-pads (TCanvas::Divide)
+ * are TPad primitives themselves. The layout of TPad changes with almost every ROOT release, so it
+ * is read from the streamer info of the file: the read context must carry its StreamerRegistry.
+ *     let mut registry = StreamerRegistry::new();
+ *     registry.register_from_streamerinfo(&file.streamer_info);
+ *     let ctx = ReadContext::new().with_schemas(Arc::new(registry));
+ *     let canvas = TCanvas::read_options(&mut payload, Endian::Big, (ctx,))?;
+ *     for histogram in canvas.histograms() { ... }
+ */
+#[derive(Debug, Default)]
+pub struct TPad {
+    pub byte_count: u32,
+    pub version: u16,
+    pub f_name: TString,
+    pub f_title: TString,
+    pub f_x1: f64,
+    pub f_y1: f64,
+    pub f_x2: f64,
+    pub f_y2: f64,
+    pub f_logx: i32,
+    pub f_logy: i32,
+    pub f_logz: i32,
+    pub f_primitives: Option<Arc<TList<AnyObject>>>,
+}
+
+impl TPad {
+    pub fn name(&self) -> &str {
+        &self.f_name
+    }
+
+    pub fn title(&self) -> &str {
+        &self.f_title
+    }
+
+    // The objects drawn in this pad, with their draw options
+    pub fn primitives(&self) -> impl Iterator<Item = (&AnyObject, &str)> {
+        self.f_primitives
+            .iter()
+            .flat_map(|list| list.objects.iter())
+            .map(|element| (&*element.object, element.option.as_str()))
+    }
+
+    pub fn pads(&self) -> impl Iterator<Item = &TPad> {
+        self.primitives().filter_map(|(object, _)| object.as_pad())
+    }
+
+    // The primitives of this pad and of its sub-pads, depth first
+    pub fn all_primitives(&self) -> Vec<(&AnyObject, &str)> {
+        let mut primitives = Vec::new();
+        for (object, option) in self.primitives() {
+            primitives.push((object, option));
+            if let Some(pad) = object.as_pad() {
+                primitives.extend(pad.all_primitives());
+            }
+        }
+        primitives
+    }
+
+    // Like TPad::FindObject, also in the sub-pads
+    pub fn find(&self, name: &str) -> Option<&AnyObject> {
+        self.all_primitives()
+            .into_iter()
+            .map(|(object, _)| object)
+            .find(|object| object.name() == name)
+    }
+
+    // Histograms drawn in the pad and its sub-pads, not those of stacks
+    pub fn histograms(&self) -> Vec<&TH1> {
+        self.all_primitives()
+            .into_iter()
+            .filter_map(|(object, _)| object.as_th1())
+            .collect()
+    }
+
+    pub fn graphs(&self) -> Vec<&TGraph> {
+        self.all_primitives()
+            .into_iter()
+            .filter_map(|(object, _)| object.as_graph())
+            .collect()
+    }
+
+    pub fn stacks(&self) -> Vec<&THStack> {
+        self.all_primitives()
+            .into_iter()
+            .filter_map(|(object, _)| object.as_stack())
+            .collect()
+    }
+
+    pub fn legends(&self) -> Vec<&TLegend> {
+        self.all_primitives()
+            .into_iter()
+            .filter_map(|(object, _)| object.as_legend())
+            .collect()
+    }
+}
+
+impl BinRead for TPad {
+    type Args<'a> = (ReadContext,);
+
+    fn read_options<R: Read + Seek>(
+        reader: &mut R,
+        endian: Endian,
+        args: Self::Args<'_>,
+    ) -> BinResult<Self> {
+        let (ctx,) = args;
+        let schema = required_schema(&ctx, "TPad")?;
+        // versions up to 5 have a hand-written streamer, which the schema does not describe
+        let start = reader.stream_position()?;
+        let version = VersionHeader::read(reader, endian)?.version;
+        if version <= 5 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("TPad version {} is not supported", version),
+            )
+            .into());
+        }
+        reader.seek(SeekFrom::Start(start))?;
+        let mut pad = Self::default();
+        let mut visit = |field: &FieldSchema, reader: &mut R| -> BinResult<bool> {
+            let value = match (field.name.as_str(), &field.kind) {
+                ("fName", FieldKind::TString) => &mut pad.f_name,
+                ("fTitle", FieldKind::TString) => &mut pad.f_title,
+                ("fX1", FieldKind::F64) => return read_into(reader, endian, &mut pad.f_x1),
+                ("fY1", FieldKind::F64) => return read_into(reader, endian, &mut pad.f_y1),
+                ("fX2", FieldKind::F64) => return read_into(reader, endian, &mut pad.f_x2),
+                ("fY2", FieldKind::F64) => return read_into(reader, endian, &mut pad.f_y2),
+                ("fLogx", FieldKind::I32) => return read_into(reader, endian, &mut pad.f_logx),
+                ("fLogy", FieldKind::I32) => return read_into(reader, endian, &mut pad.f_logy),
+                ("fLogz", FieldKind::I32) => return read_into(reader, endian, &mut pad.f_logz),
+                ("fPrimitives", kind) => {
+                    pad.f_primitives = read_list_member(reader, endian, &ctx, kind)?;
+                    return Ok(true);
+                }
+                _ => return Ok(false),
+            };
+            *value = TString::read_options(reader, endian, ())?;
+            Ok(true)
+        };
+        let header = read_with_schema(reader, endian, &ctx, &schema, &mut visit)?;
+        pad.byte_count = header.byte_count;
+        pad.version = header.version;
+        Ok(pad)
+    }
+}
+
+fn read_into<T, R>(reader: &mut R, endian: Endian, value: &mut T) -> BinResult<bool>
+where
+    T: for<'a> BinRead<Args<'a> = ()>,
+    R: Read + Seek,
+{
+    *value = reader.read_type(endian)?;
+    Ok(true)
+}
+
+/*
+ * TCanvas
+ * https://root.cern/doc/v638/classTCanvas.html
+ *
+ * The top level pad. The members TCanvas::Streamer writes after the TPad base (window geometry,
+ * display, ...) are of no use once the canvas is read back and are skipped.
+ */
+#[derive(Debug, Default)]
+pub struct TCanvas {
+    pub byte_count: u32,
+    pub version: u16,
+    pub pad: TPad,
+}
+
+impl Deref for TCanvas {
+    type Target = TPad;
+
+    fn deref(&self) -> &Self::Target {
+        &self.pad
+    }
+}
+
+impl BinRead for TCanvas {
+    type Args<'a> = (ReadContext,);
+
+    fn read_options<R: Read + Seek>(
+        reader: &mut R,
+        endian: Endian,
+        args: Self::Args<'_>,
+    ) -> BinResult<Self> {
+        let header = VersionHeader::read(reader, endian)?;
+        let pad = TPad::read_options(reader, endian, args)?;
+        header.skip_to_end(reader)?;
+        Ok(Self {
+            byte_count: header.byte_count,
+            version: header.version,
+            pad,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::objects::test_buffer::{schema, Buffer, Hist};
+    use crate::streamer::streamerinfo::StreamerRegistry;
+    use std::io::Cursor;
+
+    // A reduced TPad: the TVirtualPad base, a base that is not streamed, then the pad members
+    fn registry() -> StreamerRegistry {
+        let tobject = schema("TObject", 1, &[]);
+        let tatt_line = schema("TAttLine", 2, &[]);
+        let tvirtual_pad = schema(
+            "TVirtualPad",
+            2,
+            &[
+                ("TObject", FieldKind::Base(tobject)),
+                ("TAttLine", FieldKind::Base(tatt_line)),
+            ],
+        );
+        let tlist = schema("TList", 5, &[]);
+        let tpad = schema(
+            "TPad",
+            13,
+            &[
+                ("TVirtualPad", FieldKind::Base(tvirtual_pad)),
+                ("TAttBBox2D", FieldKind::Base(schema("TAttBBox2D", 0, &[]))),
+                ("fX1", FieldKind::F64),
+                ("fY1", FieldKind::F64),
+                ("fX2", FieldKind::F64),
+                ("fY2", FieldKind::F64),
+                ("fNumber", FieldKind::I32),
+                (
+                    "fCollide",
+                    FieldKind::FixedArray {
+                        elem: Box::new(FieldKind::Bool),
+                        count: 2,
+                    },
+                ),
+                ("fLogx", FieldKind::I32),
+                ("fLogy", FieldKind::I32),
+                ("fLogz", FieldKind::I32),
+                ("fPrimitives", FieldKind::Object(tlist.clone())),
+                ("fExecs", FieldKind::ObjectPointer(tlist)),
+                ("fName", FieldKind::TString),
+                ("fTitle", FieldKind::TString),
+            ],
+        );
+        let mut registry = StreamerRegistry::new();
+        registry.classes.insert("TPad".to_string(), tpad);
+        registry
+    }
+
+    impl Buffer {
+        fn pad(&mut self, name: &str, logy: i32, primitives: u32, write: impl FnOnce(&mut Self)) {
+            self.versioned(13, |buf| {
+                buf.versioned(2, |buf| {
+                    buf.tobject();
+                    buf.versioned(2, |buf| buf.put(&[0, 1, 0, 1, 0, 1]));
+                });
+                buf.f64s(&[0.0, 0.0, 1.0, 1.0]);
+                buf.put(&0i32.to_be_bytes());
+                buf.put(&[0, 1]);
+                for log in [0, logy, 0] {
+                    buf.put(&log.to_be_bytes());
+                }
+                buf.versioned(5, |buf| {
+                    buf.tobject();
+                    buf.string("");
+                    buf.put(&primitives.to_be_bytes());
+                    write(buf);
+                });
+                buf.null();
+                buf.string(name);
+                buf.string("");
+            });
+        }
+    }
+
+    #[test]
+    fn test_read_canvas_primitives() {
+        let mut buf = Buffer::default();
+        buf.versioned(8, |buf| {
+            buf.pad("c1", 1, 3, |buf| {
+                buf.object("TH1D", |buf| {
+                    buf.th1d(&Hist {
+                        name: "data",
+                        edges: &[0.0, 1.0],
+                        contents: &[0.0, 5.0, 0.0],
+                        sumw2: &[],
+                        entries: 5.0,
+                        bits: 0,
+                    })
+                });
+                buf.string("E");
+                buf.object("TPad", |buf| {
+                    buf.pad("c1_1", 0, 2, |buf| {
+                        buf.object("TGraph", |buf| buf.graph("ratio", &[(0.5, 1.1)]));
+                        buf.string("P");
+                        buf.object("THStack", |buf| buf.stack("mc", &["ttbar"]));
+                        buf.string("hist");
+                    })
+                });
+                buf.string("");
+                buf.obj_string("label");
+                buf.string("");
+            });
+            // members of TCanvas after the pad
+            buf.string(":0");
+            buf.put(&[0; 9]);
+        });
+        let ctx = ReadContext::new().with_schemas(Arc::new(registry()));
+        let canvas = TCanvas::read_be_args(&mut Cursor::new(buf.bytes), (ctx,))
+            .expect("Failed to read TCanvas");
+        assert_eq!((canvas.version, canvas.name()), (8, "c1"));
+        assert_eq!((canvas.f_logy, canvas.f_x2), (1, 1.0));
+        let options: Vec<&str> = canvas.primitives().map(|(_, option)| option).collect();
+        assert_eq!(options, ["E", "", ""]);
+        assert_eq!(canvas.pads().map(TPad::name).collect::<Vec<_>>(), ["c1_1"]);
+        assert_eq!(canvas.all_primitives().len(), 5);
+        assert_eq!(canvas.histograms()[0].name(), "data");
+        assert_eq!(
+            canvas.graphs()[0].points().collect::<Vec<_>>(),
+            [(0.5, 1.1)]
+        );
+        let stacks = canvas.stacks();
+        assert_eq!(stacks[0].histograms().next().unwrap().name(), "ttbar");
+        assert_eq!(canvas.find("ratio").unwrap().class_name(), "TGraph");
+        assert!(canvas.legends().is_empty());
+    }
+
+    #[test]
+    fn test_pad_needs_schema() {
+        let mut buf = Buffer::default();
+        buf.pad("c1", 0, 0, |_| {});
+        assert!(TPad::read_be_args(&mut Cursor::new(buf.bytes), (ReadContext::new(),)).is_err());
+    }
+
+    #[test]
+    fn test_old_pad_version() {
+        let mut buf = Buffer::default();
+        buf.versioned(5, |buf| buf.put(&[0; 16]));
+        let ctx = ReadContext::new().with_schemas(Arc::new(registry()));
+        let error = TPad::read_be_args(&mut Cursor::new(buf.bytes), (ctx,)).unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("TPad version 5 is not supported"));
+    }
+}
@@ -0,0 +1,256 @@
+use crate::core::read_context::ReadContext;
+use crate::core::write_buffer::{WriteBuffer, WriteObject};
+use crate::objects::tobject::TObject;
+use crate::objects::tstring::TString;
+use crate::streamer::member::VersionHeader;
+use crate::streamer::registry::{ClassSchema, FieldKind, FieldSchema};
+use crate::streamer::root_streamer::{class_schema, RootStreamer, StreamerMember};
+use binrw::io::{Read, Seek};
+use binrw::{BinRead, BinResult, Endian};
+use std::fmt::Debug;
+use std::ops::{Add, Mul};
+use std::sync::Arc;
+
+/*
+ * TParameter<T>
+ * https://root.cern/doc/v638/classTParameter.html
+ *
+ * A named value, how job metadata like the sum of weights or the cross-section is usually stored
+ * in a TList. The class name depends on the value type: TParameter<double>, TParameter<int>, ...
+ */
+#[derive(Debug, Default)]
+pub struct TParameter<T> {
+    pub byte_count: u32,
+    pub version: u16,
+    pub tobject: TObject,
+    pub name: TString,
+    pub value: T,
+}
+
+// Value types of a TParameter and the class name they are written with
+pub trait ParameterValue: StreamerMember + Copy + Debug + Default + Send + Sync + 'static {
+    const CLASS_NAME: &'static str;
+
+    fn as_f64(self) -> f64;
+
+    // TParameter::Merge of two values, with the merge mode bits of the first parameter
+    fn merge(self, other: Self, bits: u32) -> Self;
+
+    fn write_value(self, buf: &mut WriteBuffer);
+}
+
+// TParameter::EStatusBits, how values are merged, the sum without any
+pub const K_MULTIPLY: u32 = 1 << 16;
+pub const K_MAX: u32 = 1 << 17;
+pub const K_MIN: u32 = 1 << 18;
+pub const K_FIRST: u32 = 1 << 19;
+pub const K_LAST: u32 = 1 << 20;
+pub const K_IS_CONST: u32 = 1 << 21;
+
+fn merge_numbers<T>(value: T, other: T, bits: u32) -> T
+where
+    T: Copy + PartialOrd + Add<Output = T> + Mul<Output = T>,
+{
+    if bits & K_MULTIPLY != 0 {
+        value * other
+    } else if bits & K_MAX != 0 {
+        if other > value {
+            other
+        } else {
+            value
+        }
+    } else if bits & K_MIN != 0 {
+        if other < value {
+            other
+        } else {
+            value
+        }
+    } else if bits & K_LAST != 0 {
+        other
+    } else if bits & K_FIRST != 0 {
+        value
+    } else {
+        value + other
+    }
+}
+
+impl ParameterValue for f64 {
+    const CLASS_NAME: &'static str = "TParameter<double>";
+
+    fn as_f64(self) -> f64 {
+        self
+    }
+
+    fn merge(self, other: Self, bits: u32) -> Self {
+        merge_numbers(self, other, bits)
+    }
+
+    fn write_value(self, buf: &mut WriteBuffer) {
+        buf.write(&self);
+    }
+}
+
+impl ParameterValue for f32 {
+    const CLASS_NAME: &'static str = "TParameter<float>";
+
+    fn as_f64(self) -> f64 {
+        self as f64
+    }
+
+    fn merge(self, other: Self, bits: u32) -> Self {
+        merge_numbers(self, other, bits)
+    }
+
+    fn write_value(self, buf: &mut WriteBuffer) {
+        buf.write(&self);
+    }
+}
+
+impl ParameterValue for i32 {
+    const CLASS_NAME: &'static str = "TParameter<int>";
+
+    fn as_f64(self) -> f64 {
+        self as f64
+    }
+
+    fn merge(self, other: Self, bits: u32) -> Self {
+        merge_numbers(self, other, bits)
+    }
+
+    fn write_value(self, buf: &mut WriteBuffer) {
+        buf.write(&self);
+    }
+}
+
+impl ParameterValue for i64 {
+    const CLASS_NAME: &'static str = "TParameter<Long64_t>";
+
+    fn as_f64(self) -> f64 {
+        self as f64
+    }
+
+    fn merge(self, other: Self, bits: u32) -> Self {
+        merge_numbers(self, other, bits)
+    }
+
+    fn write_value(self, buf: &mut WriteBuffer) {
+        buf.write(&self);
+    }
+}
+
+impl ParameterValue for bool {
+    const CLASS_NAME: &'static str = "TParameter<bool>";
+
+    fn as_f64(self) -> f64 {
+        self as u8 as f64
+    }
+
+    // the product and the minimum are the logical and, the sum and the maximum the logical or
+    fn merge(self, other: Self, bits: u32) -> Self {
+        if bits & (K_MULTIPLY | K_MIN) != 0 {
+            self && other
+        } else if bits & K_LAST != 0 {
+            other
+        } else if bits & K_FIRST != 0 {
+            self
+        } else {
+            self || other
+        }
+    }
+
+    fn write_value(self, buf: &mut WriteBuffer) {
+        buf.bool(self);
+    }
+}
+
+impl<T: ParameterValue> TParameter<T> {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    // TParameter::Merge: kIsConst is kept while the values are equal
+    pub fn merge(&mut self, other: &TParameter<T>) {
+        if self.value.as_f64() != other.value.as_f64() {
+            self.tobject.f_bits &= !K_IS_CONST;
+        }
+        self.value = self.value.merge(other.value, self.tobject.f_bits);
+    }
+}
+
+// TParameter version 2
+impl<T: ParameterValue> WriteObject for TParameter<T> {
+    fn class_name(&self) -> String {
+        T::CLASS_NAME.to_string()
+    }
+
+    fn write_to(&self, buf: &mut WriteBuffer) {
+        buf.versioned(2, |buf| {
+            buf.tobject(&self.tobject);
+            buf.string(&self.name);
+            self.value.write_value(buf);
+        });
+    }
+}
+
+impl<T: ParameterValue> BinRead for TParameter<T> {
+    type Args<'a> = (ReadContext,);
+
+    fn read_options<R: Read + Seek>(
+        reader: &mut R,
+        endian: Endian,
+        args: Self::Args<'_>,
+    ) -> BinResult<Self> {
+        let (ctx,) = args;
+        let header = VersionHeader::read(reader, endian)?;
+        let tobject = TObject::read_options(reader, endian, ())?;
+        let name = TString::read_options(reader, endian, ())?;
+        let value = T::read_member(reader, endian, &ctx)?;
+        header.skip_to_end(reader)?;
+        Ok(Self {
+            byte_count: header.byte_count,
+            version: header.version,
+            tobject,
+            name,
+            value,
+        })
+    }
+}
+
+impl<T: ParameterValue> RootStreamer for TParameter<T> {
+    const CLASS_NAME: &'static str = T::CLASS_NAME;
+    const CLASS_VERSION: u16 = 2;
+    const CHECKSUM: u32 = 0;
+
+    fn class_schema() -> Arc<ClassSchema> {
+        class_schema::<Self>(|| {
+            vec![
+                FieldSchema {
+                    name: "TObject".to_string(),
+                    kind: FieldKind::Base(TObject::class_schema()),
+                },
+                FieldSchema {
+                    name: "fName".to_string(),
+                    kind: FieldKind::TString,
+                },
+                FieldSchema {
+                    name: "fVal".to_string(),
+                    kind: T::field_kind(),
+                },
+            ]
+        })
+    }
+}
+
+impl<T: ParameterValue> StreamerMember for TParameter<T> {
+    fn read_member<R: Read + Seek>(
+        reader: &mut R,
+        endian: Endian,
+        ctx: &ReadContext,
+    ) -> BinResult<Self> {
+        Self::read_options(reader, endian, (ctx.clone(),))
+    }
+
+    fn field_kind() -> FieldKind {
+        FieldKind::Object(Self::class_schema())
+    }
+}
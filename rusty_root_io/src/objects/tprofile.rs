@@ -0,0 +1,276 @@
+use crate::core::read_context::ReadContext;
+use crate::core::write_buffer::{WriteBuffer, WriteObject};
+use crate::objects::tarray::TArrayD;
+use crate::objects::taxis::TAxis;
+use crate::objects::th1::{BinType, TH1};
+use crate::streamer::member::VersionHeader;
+use binrw::io::{Read, Seek};
+use binrw::{BinRead, BinReaderExt, BinResult, Endian};
+use std::io;
+use std::ops::Deref;
+
+// TProfile::EErrorType, set with the option of the constructor or TProfile::SetErrorOption
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorMode {
+    #[default]
+    Mean, // error on the mean, spread / sqrt(effective entries)
+    Spread,  // "s": the spread of y
+    SpreadI, // "i": like Mean, for integer y values
+    SpreadG, // "g": 1 / sqrt(sum of weights)
+}
+
+impl ErrorMode {
+    pub fn from_i32(value: i32) -> Self {
+        match value {
+            1 => Self::Spread,
+            2 => Self::SpreadI,
+            3 => Self::SpreadG,
+            _ => Self::Mean,
+        }
+    }
+}
+
+/*
+ * TProfile
+ * https://root.cern/doc/v638/classTProfile.html
+ *
+ * The mean of y in bins of x. The TH1D base holds the sum of w * y per bin in fArray and the sum
+ * of w * y^2 in fSumw2, fBinEntries the sum of weights and fBinSumw2 the sum of squared weights
+ * (empty while every weight is 1). bin_content is the mean, bin_error follows fErrorMode.
+ */
+#[derive(Debug, Default, Clone)]
+pub struct TProfile {
+    pub byte_count: u32,
+    pub version: u16,
+    pub th1: TH1,
+    pub f_bin_entries: TArrayD,
+    pub f_error_mode: ErrorMode,
+    pub f_ymin: f64,
+    pub f_ymax: f64,
+    pub f_tsumwy: f64,
+    pub f_tsumwy2: f64,
+    pub f_bin_sumw2: TArrayD,
+}
+
+impl TProfile {
+    // An empty profile over the x axis
+    pub fn with_axis(name: &str, title: &str, xaxis: TAxis) -> Self {
+        let mut th1 = TH1::with_axes(name, title, xaxis);
+        th1.sumw2();
+        let ncells = th1.f_array.len();
+        Self {
+            version: 7,
+            th1,
+            f_bin_entries: TArrayD {
+                f_n: ncells as i32,
+                f_array: vec![0.0; ncells],
+            },
+            ..Default::default()
+        }
+    }
+
+    pub fn class_name(&self) -> &'static str {
+        "TProfile"
+    }
+
+    // Sum of the weights of a cell
+    pub fn bin_entries(&self, bin: usize) -> f64 {
+        self.f_bin_entries.get(bin).copied().unwrap_or(0.0)
+    }
+
+    // TProfile::GetBinEffectiveEntries: sum(w)^2 / sum(w^2)
+    pub fn bin_effective_entries(&self, bin: usize) -> f64 {
+        let sum = self.bin_entries(bin);
+        let sumw2 = match self.f_bin_sumw2.get(bin) {
+            Some(sumw2) => *sumw2,
+            None => sum,
+        };
+        if sumw2 > 0.0 {
+            sum * sum / sumw2
+        } else {
+            0.0
+        }
+    }
+
+    // The mean of y in a cell, 0 without entries
+    pub fn bin_content(&self, bin: usize) -> f64 {
+        let sum = self.bin_entries(bin);
+        if sum == 0.0 {
+            return 0.0;
+        }
+        self.th1.bin_content(bin) / sum
+    }
+
+    // TProfileHelper::GetBinError, without the approximations for empty spreads
+    pub fn bin_error(&self, bin: usize) -> f64 {
+        let sum = self.bin_entries(bin);
+        if sum == 0.0 {
+            return 0.0;
+        }
+        let mean = self.th1.bin_content(bin) / sum;
+        let sumwy2 = self.th1.f_sumw2.get(bin).copied().unwrap_or(0.0);
+        let spread = (sumwy2 / sum - mean * mean).abs().sqrt();
+        let neff = self.bin_effective_entries(bin);
+        match self.f_error_mode {
+            ErrorMode::SpreadI if spread == 0.0 => 1.0 / (12.0 * neff).sqrt(),
+            ErrorMode::SpreadG => 1.0 / sum.sqrt(),
+            ErrorMode::Spread => spread,
+            ErrorMode::Mean | ErrorMode::SpreadI => spread / neff.sqrt(),
+        }
+    }
+
+    // TProfile::Fill(x, y, w)
+    pub fn fill(&mut self, x: f64, y: f64, weight: f64) -> usize {
+        let bin = self.th1.f_xaxis.find_bin(x);
+        self.fill_bin(bin, y, weight, weight * weight);
+        self.th1.f_entries += 1.0;
+        if self.th1.is_in_range(bin) {
+            self.th1.f_tsumw += weight;
+            self.th1.f_tsumw2 += weight * weight;
+            self.th1.f_tsumwx += weight * x;
+            self.th1.f_tsumwx2 += weight * x * x;
+            self.f_tsumwy += weight * y;
+            self.f_tsumwy2 += weight * y * y;
+        }
+        bin
+    }
+
+    // Add y with the weight w and the squared weight w2 to a cell, without the stat sums
+    pub(crate) fn fill_bin(&mut self, bin: usize, y: f64, weight: f64, weight2: f64) {
+        let Some(sumwy) = self.th1.f_array.get_mut(bin) else {
+            return;
+        };
+        *sumwy += weight * y;
+        self.th1.f_sumw2.f_array[bin] += weight * y * y;
+        // fBinSumw2 is only needed once a weight differs from its square
+        if self.f_bin_sumw2.is_empty() && weight2 != weight {
+            self.f_bin_sumw2 = self.f_bin_entries.clone();
+        }
+        self.f_bin_entries.f_array[bin] += weight;
+        if let Some(sumw2) = self.f_bin_sumw2.f_array.get_mut(bin) {
+            *sumw2 += weight2;
+        }
+    }
+}
+
+impl Deref for TProfile {
+    type Target = TH1;
+
+    fn deref(&self) -> &Self::Target {
+        &self.th1
+    }
+}
+
+// The TH1D base, then the profile members of version 7
+impl WriteObject for TProfile {
+    fn class_name(&self) -> String {
+        String::from("TProfile")
+    }
+
+    fn title(&self) -> &str {
+        self.th1.title()
+    }
+
+    fn write_to(&self, buf: &mut WriteBuffer) {
+        buf.versioned(7, |buf| {
+            self.th1.write_to(buf);
+            self.f_bin_entries.write_to(buf);
+            buf.write(&(self.f_error_mode as i32));
+            buf.write(&self.f_ymin);
+            buf.write(&self.f_ymax);
+            buf.write(&self.f_tsumwy);
+            buf.write(&self.f_tsumwy2);
+            self.f_bin_sumw2.write_to(buf);
+        });
+    }
+}
+
+// The TH1D base, then the profile members
+impl BinRead for TProfile {
+    type Args<'a> = (ReadContext,);
+
+    fn read_options<R: Read + Seek>(
+        reader: &mut R,
+        endian: Endian,
+        args: Self::Args<'_>,
+    ) -> BinResult<Self> {
+        let (ctx,) = args;
+        let header = VersionHeader::read(reader, endian)?;
+        if header.version < 4 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("TProfile version {} is not supported", header.version),
+            )
+            .into());
+        }
+        let th1 = TH1::read_options(reader, endian, (ctx, BinType::Double))?;
+        let f_bin_entries = TArrayD::read_options(reader, endian, ())?;
+        let f_error_mode = ErrorMode::from_i32(reader.read_type(endian)?);
+        let f_ymin = reader.read_type(endian)?;
+        let f_ymax = reader.read_type(endian)?;
+        let f_tsumwy = reader.read_type(endian)?;
+        let f_tsumwy2 = reader.read_type(endian)?;
+        let f_bin_sumw2 = if header.version >= 7 {
+            TArrayD::read_options(reader, endian, ())?
+        } else {
+            TArrayD::default()
+        };
+        header.skip_to_end(reader)?;
+        Ok(Self {
+            byte_count: header.byte_count,
+            version: header.version,
+            th1,
+            f_bin_entries,
+            f_error_mode,
+            f_ymin,
+            f_ymax,
+            f_tsumwy,
+            f_tsumwy2,
+            f_bin_sumw2,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::objects::test_buffer::{Buffer, Hist};
+    use std::io::Cursor;
+
+    #[test]
+    fn test_profile() {
+        let mut profile = TProfile::with_axis("p", "", TAxis::new(2, 0.0, 2.0));
+        for y in [1.0, 2.0, 3.0] {
+            profile.fill(0.5, y, 1.0);
+        }
+        assert_eq!(profile.bin_content(1), 2.0);
+        assert_eq!(profile.bin_entries(1), 3.0);
+        // spread sqrt(2/3) over sqrt(3)
+        assert!((profile.bin_error(1) - (2.0f64 / 9.0).sqrt()).abs() < 1e-12);
+        profile.f_error_mode = ErrorMode::Spread;
+        assert!((profile.bin_error(1) - (2.0f64 / 3.0).sqrt()).abs() < 1e-12);
+        assert_eq!((profile.bin_content(2), profile.bin_error(2)), (0.0, 0.0));
+        assert_eq!((profile.entries(), profile.f_tsumwy), (3.0, 6.0));
+
+        let mut buf = Buffer::default();
+        buf.versioned(7, |buf| {
+            buf.th1d(&Hist {
+                name: "p",
+                edges: &[0.0, 1.0],
+                contents: &[0.0, 6.0, 0.0],
+                sumw2: &[0.0, 14.0, 0.0],
+                entries: 3.0,
+                bits: 0,
+            });
+            buf.array_d(&[0.0, 3.0, 0.0]);
+            buf.put(&1i32.to_be_bytes());
+            buf.f64s(&[0.0, 0.0, 6.0, 14.0]);
+            buf.array_d(&[]);
+        });
+        let read = TProfile::read_be_args(&mut Cursor::new(buf.bytes), (ReadContext::new(),))
+            .expect("Failed to read TProfile");
+        assert_eq!(read.f_error_mode, ErrorMode::Spread);
+        assert_eq!(read.bin_content(1), 2.0);
+        assert!((read.bin_error(1) - (2.0f64 / 3.0).sqrt()).abs() < 1e-12);
+    }
+}
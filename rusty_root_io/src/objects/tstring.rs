@@ -1,8 +1,8 @@
-use binrw::io::{Read, Seek};
-use binrw::{BinRead, BinReaderExt, Endian};
+use binrw::io::{Read, Seek, Write};
+use binrw::{BinRead, BinReaderExt, BinWrite, BinWriterExt, Endian};
 use std::ops::Deref;
 
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Clone)]
 pub struct TString {
     pub l_string: u8,
     pub string: String,
@@ -47,6 +47,38 @@ impl BinRead for TString {
     }
 }
 
+impl TString {
+    // Bytes written by TString::Streamer, a long string has its length after a 255 byte
+    pub fn sizeof(&self) -> usize {
+        if self.string.len() < 255 {
+            1 + self.string.len()
+        } else {
+            5 + self.string.len()
+        }
+    }
+}
+
+impl BinWrite for TString {
+    type Args<'a> = ();
+
+    fn write_options<W: Write + Seek>(
+        &self,
+        writer: &mut W,
+        endian: Endian,
+        _args: Self::Args<'_>,
+    ) -> binrw::BinResult<()> {
+        // the length is recomputed, l_string only holds what was read
+        if self.string.len() < 255 {
+            writer.write_type(&(self.string.len() as u8), endian)?;
+        } else {
+            writer.write_type(&255u8, endian)?;
+            writer.write_type(&(self.string.len() as u32), endian)?;
+        }
+        writer.write_all(self.string.as_bytes())?;
+        Ok(())
+    }
+}
+
 impl Deref for TString {
     type Target = String;
 
@@ -0,0 +1,171 @@
+use crate::core::read_context::ReadContext;
+use crate::objects::tmatrix::MatrixElement;
+use crate::objects::tobject::TObject;
+use crate::streamer::member::{read_basic_pointer, read_values, VersionHeader};
+use crate::streamer::registry::{ClassSchema, FieldKind, FieldSchema};
+use crate::streamer::root_streamer::{class_schema, RootStreamer, StreamerMember};
+use binrw::io::{Read, Seek};
+use binrw::{BinRead, BinReaderExt, BinResult, Endian};
+use std::sync::Arc;
+
+/*
+ * TVectorT<Element>
+ * https://root.cern/doc/v638/classTVectorT.html
+ *
+ * fNrows elements indexed from fRowLwb. Version 1 was written by hand (fRowLwb, then the elements
+ * as an array with its size), later versions from the streamer info with `Element* fElements; //[fNrows]`.
+ */
+#[derive(Debug, Default)]
+pub struct TVectorT<T> {
+    pub byte_count: u32,
+    pub version: u16,
+    pub tobject: TObject,
+    pub n_rows: i32,
+    pub row_lwb: i32,
+    pub elements: Vec<T>,
+}
+
+pub type TVectorD = TVectorT<f64>;
+pub type TVectorF = TVectorT<f32>;
+
+impl<T: MatrixElement> TVectorT<T> {
+    pub fn len(&self) -> usize {
+        self.elements.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.elements.is_empty()
+    }
+
+    // Element at index `row`, counted from fRowLwb like TVectorT::operator()
+    pub fn get(&self, row: i32) -> Option<T> {
+        let index = usize::try_from(row - self.row_lwb).ok()?;
+        self.elements.get(index).copied()
+    }
+}
+
+impl<T: MatrixElement> BinRead for TVectorT<T> {
+    type Args<'a> = (ReadContext,);
+
+    fn read_options<R: Read + Seek>(
+        reader: &mut R,
+        endian: Endian,
+        _args: Self::Args<'_>,
+    ) -> BinResult<Self> {
+        let header = VersionHeader::read(reader, endian)?;
+        let tobject = TObject::read_options(reader, endian, ())?;
+        let (n_rows, row_lwb, elements) = if header.version > 1 {
+            let n_rows: i32 = reader.read_type(endian)?;
+            let row_lwb = reader.read_type(endian)?;
+            let elements = read_basic_pointer(reader, endian, n_rows as i64)?;
+            (n_rows, row_lwb, elements)
+        } else {
+            let row_lwb = reader.read_type(endian)?;
+            let n_rows: i32 = reader.read_type(endian)?;
+            let elements = read_values(reader, endian, n_rows.max(0) as usize)?;
+            (n_rows, row_lwb, elements)
+        };
+        header.skip_to_end(reader)?;
+        Ok(Self {
+            byte_count: header.byte_count,
+            version: header.version,
+            tobject,
+            n_rows,
+            row_lwb,
+            elements,
+        })
+    }
+}
+
+impl<T: MatrixElement> RootStreamer for TVectorT<T> {
+    const CLASS_NAME: &'static str = T::VECTOR_CLASS;
+    const CLASS_VERSION: u16 = 4;
+    const CHECKSUM: u32 = 0;
+
+    fn class_schema() -> Arc<ClassSchema> {
+        class_schema::<Self>(|| {
+            vec![
+                FieldSchema {
+                    name: "TObject".to_string(),
+                    kind: FieldKind::Base(TObject::class_schema()),
+                },
+                FieldSchema {
+                    name: "fNrows".to_string(),
+                    kind: FieldKind::I32,
+                },
+                FieldSchema {
+                    name: "fRowLwb".to_string(),
+                    kind: FieldKind::I32,
+                },
+                FieldSchema {
+                    name: "fElements".to_string(),
+                    kind: FieldKind::BasicPointer {
+                        elem: Box::new(T::field_kind()),
+                        count: "fNrows".to_string(),
+                    },
+                },
+            ]
+        })
+    }
+}
+
+impl<T: MatrixElement> StreamerMember for TVectorT<T> {
+    fn read_member<R: Read + Seek>(
+        reader: &mut R,
+        endian: Endian,
+        ctx: &ReadContext,
+    ) -> BinResult<Self> {
+        Self::read_options(reader, endian, (ctx.clone(),))
+    }
+
+    fn field_kind() -> FieldKind {
+        FieldKind::Object(Self::class_schema())
+    }
+}
+
+#[cfg(feature = "nalgebra")]
+impl<T: MatrixElement + nalgebra::Scalar> From<&TVectorT<T>> for nalgebra::DVector<T> {
+    fn from(vector: &TVectorT<T>) -> Self {
+        nalgebra::DVector::from_column_slice(&vector.elements)
+    }
+}
+
+#[cfg(feature = "ndarray")]
+impl<T: MatrixElement> From<&TVectorT<T>> for ndarray::Array1<T> {
+    fn from(vector: &TVectorT<T>) -> Self {
+        ndarray::Array1::from_vec(vector.elements.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::constant::K_HAS_BYTECOUNT;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_read_vector() {
+        let mut body = 4u16.to_be_bytes().to_vec();
+        body.extend_from_slice(&1u16.to_be_bytes());
+        body.extend_from_slice(&[0; 8]);
+        body.extend_from_slice(&3i32.to_be_bytes());
+        body.extend_from_slice(&1i32.to_be_bytes());
+        body.push(1);
+        for value in [0.5f64, 1.5, 2.5] {
+            body.extend_from_slice(&value.to_be_bytes());
+        }
+        let mut bytes = (K_HAS_BYTECOUNT | body.len() as u32).to_be_bytes().to_vec();
+        bytes.extend(body);
+
+        let vector = TVectorD::read_be_args(&mut Cursor::new(bytes), (ReadContext::new(),))
+            .expect("Failed to read TVectorD");
+        assert_eq!(vector.n_rows, 3);
+        assert_eq!(vector.elements, [0.5, 1.5, 2.5]);
+        assert_eq!(vector.get(1), Some(0.5));
+        assert_eq!(vector.get(0), None);
+        #[cfg(feature = "nalgebra")]
+        assert_eq!(nalgebra::DVector::from(&vector).sum(), 4.5);
+        #[cfg(feature = "ndarray")]
+        assert_eq!(ndarray::Array1::from(&vector).sum(), 4.5);
+    }
+}
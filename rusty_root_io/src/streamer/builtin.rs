@@ -0,0 +1,1299 @@
+use crate::core::constant::{
+    K_ANY, K_BASE, K_BITS, K_BOOL, K_CHAR, K_COUNTER, K_DOUBLE, K_FLOAT, K_INT, K_LEGACY_CHAR,
+    K_LONG, K_LONG64, K_OBJECT, K_OBJECTP, K_OBJECT_P, K_OFFSET_P, K_SHORT, K_STL, K_STL_VECTOR,
+    K_TNAMED, K_TOBJECT, K_TSTRING, K_UCHAR, K_UINT, K_ULONG, K_ULONG64, K_USHORT,
+};
+use crate::core::utils::ClassInfo;
+use crate::objects::tnamed::TNamed;
+use crate::objects::tobjarray::TObjArray;
+use crate::objects::tobject::TObject;
+use crate::objects::tstring::TString;
+use crate::streamer::tstreamer_element::{TStreamerElement, TStreamerElementBase, TStreamerType};
+use crate::streamer::tstreamerinfo::TStreamerInfo;
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock};
+
+/*
+ * The TStreamerInfo of the classes the crate writes
+ * https://root.cern/doc/v638/classTStreamerInfo.html
+ *
+ * A file holds the streamer info of every class written in it, with which ROOT reads the objects
+ * whose class or version it does not have. The records are built from the declarations of the
+ * classes in ROOT 6 below, with the checksum of TStreamerInfo::GetCheckSum. Classes of version 0
+ * are not streamed and their checksum includes transient members, it is given as ROOT writes it.
+ *
+ *     for info in streamer_infos("TH1D") {
+ *         file.add_streamer_info(info);
+ *     }
+ */
+enum Member {
+    // a base class of the table
+    Base(&'static str),
+    // name, title, fType and type name
+    Basic(&'static str, &'static str, u32, &'static str),
+    // a TString: name and title
+    Str(&'static str, &'static str),
+    // kObject or kAny: name, title, fType, class and the size of the class
+    Object(&'static str, &'static str, u32, &'static str, u32),
+    // kObjectp or kObjectP: name, title, fType and class
+    Pointer(&'static str, &'static str, u32, &'static str),
+    // `T* fX; //[fN]`: name, title, fType of T, type name, size and counter
+    BasicPointer(
+        &'static str,
+        &'static str,
+        u32,
+        &'static str,
+        u32,
+        &'static str,
+    ),
+    // an STL collection: name, title, type name, kind of collection and fType of its elements
+    Stl(&'static str, &'static str, &'static str, u32, u32),
+}
+
+struct Class {
+    name: String,
+    title: &'static str,
+    version: u32,
+    // the checksum of a class of version 0
+    checksum: Option<u32>,
+    members: Vec<Member>,
+}
+
+impl Class {
+    fn new(name: &str, title: &'static str, version: u32, members: Vec<Member>) -> Self {
+        Self {
+            name: name.to_string(),
+            title,
+            version,
+            checksum: None,
+            members,
+        }
+    }
+}
+
+use Member::*;
+
+fn classes() -> Vec<Class> {
+    let mut classes = vec![
+        Class::new(
+            "TObject",
+            "Basic ROOT object",
+            1,
+            vec![
+                Basic(
+                    "fUniqueID",
+                    "object unique identifier",
+                    K_UINT,
+                    "unsigned int",
+                ),
+                Basic("fBits", "bit field status word", K_BITS, "unsigned int"),
+            ],
+        ),
+        Class::new(
+            "TNamed",
+            "The basis for a named object (name, title)",
+            1,
+            vec![
+                Base("TObject"),
+                Str("fName", "object identifier"),
+                Str("fTitle", "object title"),
+            ],
+        ),
+        Class::new(
+            "TObjString",
+            "Collectable string class",
+            1,
+            vec![Base("TObject"), Str("fString", "wrapped TString")],
+        ),
+        Class::new("TString", "Basic string class", 2, vec![]),
+        Class::new(
+            "TCollection",
+            "Collection abstract base class",
+            3,
+            vec![
+                Base("TObject"),
+                Str("fName", "name of the collection"),
+                Basic("fSize", "number of elements in collection", K_INT, "int"),
+            ],
+        ),
+        Class {
+            checksum: Some(4234951622),
+            ..Class::new(
+                "TSeqCollection",
+                "Sequenceable collection ABC",
+                0,
+                vec![Base("TCollection")],
+            )
+        },
+        Class::new(
+            "TList",
+            "Doubly linked list",
+            5,
+            vec![Base("TSeqCollection")],
+        ),
+        Class {
+            checksum: Some(3430828481),
+            ..Class::new(
+                "THashList",
+                "Doubly linked list with hashtable for lookup",
+                0,
+                vec![Base("TList")],
+            )
+        },
+        Class::new(
+            "TAttLine",
+            "Line attributes",
+            2,
+            vec![
+                Basic("fLineColor", "Line color", K_SHORT, "short"),
+                Basic("fLineStyle", "Line style", K_SHORT, "short"),
+                Basic("fLineWidth", "Line width", K_SHORT, "short"),
+            ],
+        ),
+        Class::new(
+            "TAttFill",
+            "Fill area attributes",
+            2,
+            vec![
+                Basic("fFillColor", "Fill area color", K_SHORT, "short"),
+                Basic("fFillStyle", "Fill area style", K_SHORT, "short"),
+            ],
+        ),
+        Class::new(
+            "TAttMarker",
+            "Marker attributes",
+            3,
+            vec![
+                Basic("fMarkerColor", "Marker color", K_SHORT, "short"),
+                Basic("fMarkerStyle", "Marker style", K_SHORT, "short"),
+                Basic("fMarkerSize", "Marker size", K_FLOAT, "float"),
+            ],
+        ),
+        Class::new("TAtt3D", "3D attributes", 1, vec![]),
+        Class::new(
+            "TAttAxis",
+            "Axis attributes",
+            4,
+            vec![
+                Basic(
+                    "fNdivisions",
+                    "Number of divisions(10000*n3 + 100*n2 + n1)",
+                    K_INT,
+                    "int",
+                ),
+                Basic("fAxisColor", "Color of the line axis", K_SHORT, "short"),
+                Basic("fLabelColor", "Color of labels", K_SHORT, "short"),
+                Basic("fLabelFont", "Font for labels", K_SHORT, "short"),
+                Basic("fLabelOffset", "Offset of labels", K_FLOAT, "float"),
+                Basic("fLabelSize", "Size of labels", K_FLOAT, "float"),
+                Basic("fTickLength", "Length of tick marks", K_FLOAT, "float"),
+                Basic("fTitleOffset", "Offset of axis title", K_FLOAT, "float"),
+                Basic("fTitleSize", "Size of axis title", K_FLOAT, "float"),
+                Basic("fTitleColor", "Color of axis title", K_SHORT, "short"),
+                Basic("fTitleFont", "Font for axis title", K_SHORT, "short"),
+            ],
+        ),
+        Class::new(
+            "TAxis",
+            "Axis class",
+            10,
+            vec![
+                Base("TNamed"),
+                Base("TAttAxis"),
+                Basic("fNbins", "Number of bins", K_INT, "int"),
+                Basic("fXmin", "Low edge of first bin", K_DOUBLE, "double"),
+                Basic("fXmax", "Upper edge of last bin", K_DOUBLE, "double"),
+                Object("fXbins", "Bin edges array in X", K_ANY, "TArrayD", 24),
+                Basic("fFirst", "First bin to display", K_INT, "int"),
+                Basic("fLast", "Last bin to display", K_INT, "int"),
+                Basic(
+                    "fBits2",
+                    "Second bit status word",
+                    K_USHORT,
+                    "unsigned short",
+                ),
+                Basic(
+                    "fTimeDisplay",
+                    "On/off displaying time values instead of numerics",
+                    K_BOOL,
+                    "bool",
+                ),
+                Str("fTimeFormat", "Date&time format, ex: 09/12/99 12:34:00"),
+                Pointer("fLabels", "List of labels", K_OBJECT_P, "THashList"),
+                Pointer("fModLabs", "List of modified labels", K_OBJECT_P, "TList"),
+            ],
+        ),
+        Class::new(
+            "TArray",
+            "Abstract array base class",
+            1,
+            vec![Basic("fN", "Number of array elements", K_COUNTER, "int")],
+        ),
+        Class::new(
+            "TH1",
+            "1-Dim histogram base class",
+            8,
+            vec![
+                Base("TNamed"),
+                Base("TAttLine"),
+                Base("TAttFill"),
+                Base("TAttMarker"),
+                Basic(
+                    "fNcells",
+                    "Number of bins(1D), cells (2D) +U/Overflows",
+                    K_INT,
+                    "int",
+                ),
+                Object("fXaxis", "X axis descriptor", K_OBJECT, "TAxis", 216),
+                Object("fYaxis", "Y axis descriptor", K_OBJECT, "TAxis", 216),
+                Object("fZaxis", "Z axis descriptor", K_OBJECT, "TAxis", 216),
+                Basic(
+                    "fBarOffset",
+                    "(1000*offset) for bar charts or legos",
+                    K_SHORT,
+                    "short",
+                ),
+                Basic(
+                    "fBarWidth",
+                    "(1000*width) for bar charts or legos",
+                    K_SHORT,
+                    "short",
+                ),
+                Basic("fEntries", "Number of entries", K_DOUBLE, "double"),
+                Basic("fTsumw", "Total Sum of weights", K_DOUBLE, "double"),
+                Basic(
+                    "fTsumw2",
+                    "Total Sum of squares of weights",
+                    K_DOUBLE,
+                    "double",
+                ),
+                Basic("fTsumwx", "Total Sum of weight*X", K_DOUBLE, "double"),
+                Basic("fTsumwx2", "Total Sum of weight*X*X", K_DOUBLE, "double"),
+                Basic("fMaximum", "Maximum value for plotting", K_DOUBLE, "double"),
+                Basic("fMinimum", "Minimum value for plotting", K_DOUBLE, "double"),
+                Basic("fNormFactor", "Normalization factor", K_DOUBLE, "double"),
+                Object(
+                    "fContour",
+                    "Array to display contour levels",
+                    K_ANY,
+                    "TArrayD",
+                    24,
+                ),
+                Object(
+                    "fSumw2",
+                    "Array of sum of squares of weights",
+                    K_ANY,
+                    "TArrayD",
+                    24,
+                ),
+                Str("fOption", "Histogram options"),
+                Pointer(
+                    "fFunctions",
+                    "->Pointer to list of functions (fits and user)",
+                    K_OBJECTP,
+                    "TList",
+                ),
+                Basic("fBufferSize", "fBuffer size", K_COUNTER, "int"),
+                BasicPointer(
+                    "fBuffer",
+                    "[fBufferSize] entry buffer",
+                    K_DOUBLE,
+                    "double*",
+                    8,
+                    "fBufferSize",
+                ),
+                Basic(
+                    "fBinStatErrOpt",
+                    "Option for bin statistical errors",
+                    K_INT,
+                    "TH1::EBinErrorOpt",
+                ),
+                Basic(
+                    "fStatOverflows",
+                    "Per object flag to use under/overflows in statistics",
+                    K_INT,
+                    "TH1::EStatOverflows",
+                ),
+            ],
+        ),
+        Class::new(
+            "TH2",
+            "2-Dim histogram base class",
+            5,
+            vec![
+                Base("TH1"),
+                Basic("fScalefactor", "Scale factor", K_DOUBLE, "double"),
+                Basic("fTsumwy", "Total Sum of weight*Y", K_DOUBLE, "double"),
+                Basic("fTsumwy2", "Total Sum of weight*Y*Y", K_DOUBLE, "double"),
+                Basic("fTsumwxy", "Total Sum of weight*X*Y", K_DOUBLE, "double"),
+            ],
+        ),
+        Class::new(
+            "TH3",
+            "3-Dim histogram base class",
+            6,
+            vec![
+                Base("TH1"),
+                Base("TAtt3D"),
+                Basic("fTsumwy", "Total Sum of weight*Y", K_DOUBLE, "double"),
+                Basic("fTsumwy2", "Total Sum of weight*Y*Y", K_DOUBLE, "double"),
+                Basic("fTsumwxy", "Total Sum of weight*X*Y", K_DOUBLE, "double"),
+                Basic("fTsumwz", "Total Sum of weight*Z", K_DOUBLE, "double"),
+                Basic("fTsumwz2", "Total Sum of weight*Z*Z", K_DOUBLE, "double"),
+                Basic("fTsumwxz", "Total Sum of weight*X*Z", K_DOUBLE, "double"),
+                Basic("fTsumwyz", "Total Sum of weight*Y*Z", K_DOUBLE, "double"),
+            ],
+        ),
+        Class::new(
+            "TProfile",
+            "Profile histogram class",
+            7,
+            vec![
+                Base("TH1D"),
+                Object(
+                    "fBinEntries",
+                    "number of entries per bin",
+                    K_ANY,
+                    "TArrayD",
+                    24,
+                ),
+                Basic(
+                    "fErrorMode",
+                    "Option to compute errors",
+                    K_INT,
+                    "EErrorType",
+                ),
+                Basic("fYmin", "Lower limit in Y (if set)", K_DOUBLE, "double"),
+                Basic("fYmax", "Upper limit in Y (if set)", K_DOUBLE, "double"),
+                Basic("fTsumwy", "Total Sum of weight*Y", K_DOUBLE, "double"),
+                Basic("fTsumwy2", "Total Sum of weight*Y*Y", K_DOUBLE, "double"),
+                Object(
+                    "fBinSumw2",
+                    "Array of sum of squares of weights per bin",
+                    K_ANY,
+                    "TArrayD",
+                    24,
+                ),
+            ],
+        ),
+        Class::new(
+            "TGraph",
+            "Graph graphics class",
+            4,
+            vec![
+                Base("TNamed"),
+                Base("TAttLine"),
+                Base("TAttFill"),
+                Base("TAttMarker"),
+                Basic("fNpoints", "Number of points <= fMaxSize", K_COUNTER, "int"),
+                BasicPointer(
+                    "fX",
+                    "[fNpoints] array of X points",
+                    K_DOUBLE,
+                    "double*",
+                    8,
+                    "fNpoints",
+                ),
+                BasicPointer(
+                    "fY",
+                    "[fNpoints] array of Y points",
+                    K_DOUBLE,
+                    "double*",
+                    8,
+                    "fNpoints",
+                ),
+                Pointer(
+                    "fFunctions",
+                    "Pointer to list of functions (fits and user)",
+                    K_OBJECT_P,
+                    "TList",
+                ),
+                Pointer(
+                    "fHistogram",
+                    "Pointer to histogram used for drawing axis",
+                    K_OBJECT_P,
+                    "TH1F",
+                ),
+                Basic(
+                    "fMinimum",
+                    "Minimum value for plotting along y",
+                    K_DOUBLE,
+                    "double",
+                ),
+                Basic(
+                    "fMaximum",
+                    "Maximum value for plotting along y",
+                    K_DOUBLE,
+                    "double",
+                ),
+            ],
+        ),
+        Class::new(
+            "TObjArray",
+            "An array of objects",
+            3,
+            vec![
+                Base("TSeqCollection"),
+                Basic("fLowerBound", "Lower bound of the array", K_INT, "int"),
+                Basic(
+                    "fLast",
+                    "Last element in array containing an object",
+                    K_INT,
+                    "int",
+                ),
+            ],
+        ),
+        Class::new(
+            "ROOT::TIOFeatures",
+            "",
+            1,
+            vec![Basic("fIOBits", "", K_UCHAR, "unsigned char")],
+        ),
+        Class::new(
+            "TTree",
+            "Tree descriptor (the main ROOT I/O class)",
+            20,
+            vec![
+                Base("TNamed"),
+                Base("TAttLine"),
+                Base("TAttFill"),
+                Base("TAttMarker"),
+                Basic("fEntries", "Number of entries", K_LONG64, "Long64_t"),
+                Basic(
+                    "fTotBytes",
+                    "Total number of bytes in all branches before compression",
+                    K_LONG64,
+                    "Long64_t",
+                ),
+                Basic(
+                    "fZipBytes",
+                    "Total number of bytes in all branches after compression",
+                    K_LONG64,
+                    "Long64_t",
+                ),
+                Basic("fSavedBytes", "Number of autosaved bytes", K_LONG64, "Long64_t"),
+                Basic(
+                    "fFlushedBytes",
+                    "Number of auto-flushed bytes",
+                    K_LONG64,
+                    "Long64_t",
+                ),
+                Basic(
+                    "fWeight",
+                    "Tree weight (see TTree::SetWeight)",
+                    K_DOUBLE,
+                    "double",
+                ),
+                Basic(
+                    "fTimerInterval",
+                    "Timer interval in milliseconds",
+                    K_INT,
+                    "int",
+                ),
+                Basic(
+                    "fScanField",
+                    "Number of runs before prompting in Scan",
+                    K_INT,
+                    "int",
+                ),
+                Basic("fUpdate", "Update frequency for EntryLoop", K_INT, "int"),
+                Basic(
+                    "fDefaultEntryOffsetLen",
+                    "Initial Length of fEntryOffset table in the basket buffers",
+                    K_INT,
+                    "int",
+                ),
+                Basic(
+                    "fNClusterRange",
+                    "Number of Cluster range in addition to the one defined by 'AutoFlush'",
+                    K_COUNTER,
+                    "int",
+                ),
+                Basic(
+                    "fMaxEntries",
+                    "Maximum number of entries in case of circular buffers",
+                    K_LONG64,
+                    "Long64_t",
+                ),
+                Basic(
+                    "fMaxEntryLoop",
+                    "Maximum number of entries to process",
+                    K_LONG64,
+                    "Long64_t",
+                ),
+                Basic(
+                    "fMaxVirtualSize",
+                    "Maximum total size of buffers kept in memory",
+                    K_LONG64,
+                    "Long64_t",
+                ),
+                Basic(
+                    "fAutoSave",
+                    "Autosave tree when fAutoSave entries written or -fAutoSave (compressed) bytes produced",
+                    K_LONG64,
+                    "Long64_t",
+                ),
+                Basic(
+                    "fAutoFlush",
+                    "Auto-flush tree when fAutoFlush entries written or -fAutoFlush (compressed) bytes produced",
+                    K_LONG64,
+                    "Long64_t",
+                ),
+                Basic(
+                    "fEstimate",
+                    "Number of entries to estimate histogram limits",
+                    K_LONG64,
+                    "Long64_t",
+                ),
+                BasicPointer(
+                    "fClusterRangeEnd",
+                    "[fNClusterRange] Last entry of a cluster range.",
+                    K_LONG64,
+                    "Long64_t*",
+                    8,
+                    "fNClusterRange",
+                ),
+                BasicPointer(
+                    "fClusterSize",
+                    "[fNClusterRange] Number of entries in each cluster for a given range.",
+                    K_LONG64,
+                    "Long64_t*",
+                    8,
+                    "fNClusterRange",
+                ),
+                Object(
+                    "fIOFeatures",
+                    "IO features to define for newly-written baskets and branches.",
+                    K_ANY,
+                    "ROOT::TIOFeatures",
+                    1,
+                ),
+                Object("fBranches", "List of Branches", K_OBJECT, "TObjArray", 64),
+                Object(
+                    "fLeaves",
+                    "Direct pointers to individual branch leaves",
+                    K_OBJECT,
+                    "TObjArray",
+                    64,
+                ),
+                Pointer(
+                    "fAliases",
+                    "List of aliases for expressions based on the tree branches.",
+                    K_OBJECT_P,
+                    "TList",
+                ),
+                Object("fIndexValues", "Sorted index values", K_ANY, "TArrayD", 24),
+                Object("fIndex", "Index of sorted values", K_ANY, "TArrayI", 24),
+                Pointer(
+                    "fTreeIndex",
+                    "Pointer to the tree Index (if any)",
+                    K_OBJECT_P,
+                    "TVirtualIndex",
+                ),
+                Pointer(
+                    "fFriends",
+                    "pointer to list of friend elements",
+                    K_OBJECT_P,
+                    "TList",
+                ),
+                Pointer(
+                    "fUserInfo",
+                    "pointer to a list of user objects associated to this Tree",
+                    K_OBJECT_P,
+                    "TList",
+                ),
+                Pointer(
+                    "fBranchRef",
+                    "Branch supporting the TRefTable (if any)",
+                    K_OBJECT_P,
+                    "TBranchRef",
+                ),
+            ],
+        ),
+        Class::new(
+            "TBranch",
+            "Branch descriptor",
+            13,
+            vec![
+                Base("TNamed"),
+                Base("TAttFill"),
+                Basic("fCompress", "Compression level and algorithm", K_INT, "int"),
+                Basic("fBasketSize", "Initial Size of  Basket Buffer", K_INT, "int"),
+                Basic(
+                    "fEntryOffsetLen",
+                    "Initial Length of fEntryOffset table in the basket buffers",
+                    K_INT,
+                    "int",
+                ),
+                Basic("fWriteBasket", "Last basket number written", K_INT, "int"),
+                Basic(
+                    "fEntryNumber",
+                    "Current entry number (last one filled in this branch)",
+                    K_LONG64,
+                    "Long64_t",
+                ),
+                Object(
+                    "fIOFeatures",
+                    "IO features for newly-created baskets.",
+                    K_ANY,
+                    "ROOT::TIOFeatures",
+                    1,
+                ),
+                Basic("fOffset", "Offset of this branch", K_INT, "int"),
+                Basic(
+                    "fMaxBaskets",
+                    "Maximum number of Baskets so far",
+                    K_COUNTER,
+                    "int",
+                ),
+                Basic("fSplitLevel", "Branch split level", K_INT, "int"),
+                Basic("fEntries", "Number of entries", K_LONG64, "Long64_t"),
+                Basic(
+                    "fFirstEntry",
+                    "Number of the first entry in this branch",
+                    K_LONG64,
+                    "Long64_t",
+                ),
+                Basic(
+                    "fTotBytes",
+                    "Total number of bytes in all leaves before compression",
+                    K_LONG64,
+                    "Long64_t",
+                ),
+                Basic(
+                    "fZipBytes",
+                    "Total number of bytes in all leaves after compression",
+                    K_LONG64,
+                    "Long64_t",
+                ),
+                Object(
+                    "fBranches",
+                    "-> List of Branches of this branch",
+                    K_OBJECT,
+                    "TObjArray",
+                    64,
+                ),
+                Object(
+                    "fLeaves",
+                    "-> List of leaves of this branch",
+                    K_OBJECT,
+                    "TObjArray",
+                    64,
+                ),
+                Object(
+                    "fBaskets",
+                    "-> List of baskets of this branch",
+                    K_OBJECT,
+                    "TObjArray",
+                    64,
+                ),
+                BasicPointer(
+                    "fBasketBytes",
+                    "[fMaxBaskets] Length of baskets on file",
+                    K_INT,
+                    "int*",
+                    8,
+                    "fMaxBaskets",
+                ),
+                BasicPointer(
+                    "fBasketEntry",
+                    "[fMaxBaskets] Table of first entry in each basket",
+                    K_LONG64,
+                    "Long64_t*",
+                    8,
+                    "fMaxBaskets",
+                ),
+                BasicPointer(
+                    "fBasketSeek",
+                    "[fMaxBaskets] Addresses of baskets on file",
+                    K_LONG64,
+                    "Long64_t*",
+                    8,
+                    "fMaxBaskets",
+                ),
+                Str(
+                    "fFileName",
+                    "Name of file where buffers are stored (\"\" if in same file as Tree header)",
+                ),
+            ],
+        ),
+        Class::new(
+            "TBranchElement",
+            "Branch in case of an object",
+            10,
+            vec![
+                Base("TBranch"),
+                Str("fClassName", "Class name of referenced object"),
+                Str("fParentName", "Name of parent class"),
+                Str("fClonesName", "Name of class in TClonesArray (if any)"),
+                Basic("fCheckSum", "CheckSum of class", K_UINT, "unsigned int"),
+                Basic("fClassVersion", "Version number of class", K_SHORT, "short"),
+                Basic("fID", "element serial number in fInfo", K_INT, "int"),
+                Basic("fType", "branch type", K_INT, "int"),
+                Basic("fStreamerType", "branch streamer type", K_INT, "int"),
+                Basic(
+                    "fMaximum",
+                    "Maximum entries for a TClonesArray or variable array",
+                    K_INT,
+                    "int",
+                ),
+                Pointer(
+                    "fBranchCount",
+                    "pointer to primary branchcount branch",
+                    K_OBJECT_P,
+                    "TBranchElement",
+                ),
+                Pointer(
+                    "fBranchCount2",
+                    "pointer to secondary branchcount branch",
+                    K_OBJECT_P,
+                    "TBranchElement",
+                ),
+            ],
+        ),
+        Class::new(
+            "TLeaf",
+            "Leaf: description of a Branch data type",
+            2,
+            vec![
+                Base("TNamed"),
+                Basic("fLen", "Number of fixed length elements", K_INT, "int"),
+                Basic("fLenType", "Number of bytes for this data type", K_INT, "int"),
+                Basic("fOffset", "Offset in ClonesArray object (if one)", K_INT, "int"),
+                Basic(
+                    "fIsRange",
+                    "(=kTRUE if leaf has a range, kFALSE otherwise)",
+                    K_BOOL,
+                    "bool",
+                ),
+                Basic(
+                    "fIsUnsigned",
+                    "(=kTRUE if unsigned, kFALSE otherwise)",
+                    K_BOOL,
+                    "bool",
+                ),
+                Pointer(
+                    "fLeafCount",
+                    "Pointer to Leaf count if variable length (we do not own the counter)",
+                    K_OBJECT_P,
+                    "TLeaf",
+                ),
+            ],
+        ),
+        Class::new(
+            "TLeafElement",
+            "Leaf for a general object derived from TObject.",
+            1,
+            vec![
+                Base("TLeaf"),
+                Basic("fID", "element serial number in fInfo", K_INT, "int"),
+                Basic("fType", "leaf type", K_INT, "int"),
+            ],
+        ),
+    ];
+
+    // the graphs with errors, TGraph and an array per error
+    for (class, title, errors) in [
+        (
+            "TGraphErrors",
+            "A graph with error bars",
+            &[("fEX", "X errors"), ("fEY", "Y errors")][..],
+        ),
+        (
+            "TGraphAsymmErrors",
+            "A graph with asymmetric error bars",
+            &[
+                ("fEXlow", "X low errors"),
+                ("fEXhigh", "X high errors"),
+                ("fEYlow", "Y low errors"),
+                ("fEYhigh", "Y high errors"),
+            ][..],
+        ),
+    ] {
+        let mut members = vec![Base("TGraph")];
+        for (name, what) in errors {
+            let title: &'static str = Box::leak(format!("[fNpoints] array of {}", what).into());
+            members.push(BasicPointer(
+                name, title, K_DOUBLE, "double*", 8, "fNpoints",
+            ));
+        }
+        classes.push(Class::new(class, title, 3, members));
+    }
+
+    // the arrays and the histograms of every bin type
+    for (suffix, array_title, what, f_type, type_name, size) in [
+        ("C", "Array of chars", "chars", K_CHAR, "char*", 1),
+        ("S", "Array of shorts", "shorts", K_SHORT, "short*", 2),
+        ("I", "Array of ints", "32 bit integers", K_INT, "int*", 4),
+        (
+            "L64",
+            "Array of long64s",
+            "long64s",
+            K_LONG64,
+            "Long64_t*",
+            8,
+        ),
+        ("F", "Array of floats", "floats", K_FLOAT, "float*", 4),
+        ("D", "Array of doubles", "doubles", K_DOUBLE, "double*", 8),
+    ] {
+        let array = format!("TArray{}", suffix);
+        let title: &'static str = Box::leak(format!("[fN] Array of fN {}", what).into());
+        classes.push(Class::new(
+            &array,
+            array_title,
+            1,
+            vec![
+                Base("TArray"),
+                BasicPointer("fArray", title, f_type, type_name, size, "fN"),
+            ],
+        ));
+        let array: &'static str = Box::leak(array.into());
+        let (suffix, per_channel) = match suffix {
+            "L64" => ("L", "long64"),
+            "C" => ("C", "char"),
+            "S" => ("S", "short"),
+            "I" => ("I", "int"),
+            "F" => ("F", "float"),
+            _ => ("D", "double"),
+        };
+        for (dimension, version) in [(1, 3), (2, 4), (3, 4)] {
+            let title: &'static str = Box::leak(
+                format!(
+                    "{}-Dim histograms (one {} per channel)",
+                    dimension, per_channel
+                )
+                .into(),
+            );
+            let base: &'static str = Box::leak(format!("TH{}", dimension).into());
+            classes.push(Class::new(
+                &format!("TH{}{}", dimension, suffix),
+                title,
+                version,
+                vec![Base(base), Base(array)],
+            ));
+        }
+    }
+
+    for (value_type, f_type) in [
+        ("double", K_DOUBLE),
+        ("float", K_FLOAT),
+        ("int", K_INT),
+        ("Long64_t", K_LONG64),
+        ("bool", K_BOOL),
+    ] {
+        classes.push(Class::new(
+            &format!("TParameter<{}>", value_type),
+            "Named templated parameter type",
+            2,
+            vec![
+                Base("TObject"),
+                Str("fName", ""),
+                Basic("fVal", "", f_type, value_type),
+            ],
+        ));
+    }
+    // the leaves of the basic types, whose range has the type of the leaf
+    for (suffix, title, f_type, type_name) in [
+        (
+            "B",
+            "A TLeaf for an 8 bit Integer data type.",
+            K_CHAR,
+            "char",
+        ),
+        (
+            "S",
+            "A TLeaf for a 16 bit Integer data type.",
+            K_SHORT,
+            "short",
+        ),
+        ("I", "A TLeaf for an Integer data type.", K_INT, "int"),
+        (
+            "L",
+            "A TLeaf for a 64 bit Integer data type.",
+            K_LONG64,
+            "Long64_t",
+        ),
+        (
+            "F",
+            "A TLeaf for a 32 bit floating point data type.",
+            K_FLOAT,
+            "float",
+        ),
+        (
+            "D",
+            "A TLeaf for a 64 bit floating point data type.",
+            K_DOUBLE,
+            "double",
+        ),
+        ("O", "A TLeaf for a bool data type.", K_BOOL, "bool"),
+    ] {
+        classes.push(Class::new(
+            &format!("TLeaf{}", suffix),
+            title,
+            1,
+            vec![
+                Base("TLeaf"),
+                Basic(
+                    "fMinimum",
+                    "Minimum value if leaf range is specified",
+                    f_type,
+                    type_name,
+                ),
+                Basic(
+                    "fMaximum",
+                    "Maximum value if leaf range is specified",
+                    f_type,
+                    type_name,
+                ),
+            ],
+        ));
+    }
+
+    // std::vector of the basic types, streamed by their collection proxy. The checksum of a
+    // collection is that of its name, it has no bases nor data members (TClass::GetCheckSum).
+    for (f_type, type_name) in [
+        (K_CHAR, "char"),
+        (K_UCHAR, "unsigned char"),
+        (K_SHORT, "short"),
+        (K_USHORT, "unsigned short"),
+        (K_INT, "int"),
+        (K_UINT, "unsigned int"),
+        (K_LONG64, "Long64_t"),
+        (K_ULONG64, "ULong64_t"),
+        (K_FLOAT, "float"),
+        (K_DOUBLE, "double"),
+        (K_BOOL, "bool"),
+    ] {
+        let name: &'static str = Box::leak(format!("vector<{}>", type_name).into());
+        let title: &'static str =
+            Box::leak(format!("<{}> Used to call the proper TStreamerInfo case", type_name).into());
+        let checksum = TStreamerInfo {
+            tnamed: TNamed {
+                name: TString::new(name),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+        .compute_checksum();
+        classes.push(Class {
+            checksum: Some(checksum),
+            ..Class::new(
+                name,
+                "",
+                6,
+                vec![Stl("This", title, name, K_STL_VECTOR, f_type)],
+            )
+        });
+    }
+    classes
+}
+
+fn basic_size(f_type: u32) -> u32 {
+    match f_type {
+        K_CHAR | K_BOOL | K_LEGACY_CHAR | K_UCHAR => 1,
+        K_SHORT | K_USHORT => 2,
+        K_DOUBLE | K_LONG | K_LONG64 | K_ULONG | K_ULONG64 => 8,
+        _ => 4,
+    }
+}
+
+fn element(
+    class_name: &str,
+    name: &str,
+    title: &str,
+    f_type: u32,
+    f_size: u32,
+    type_name: &str,
+    tstreamer_type: TStreamerType,
+) -> TStreamerElement {
+    TStreamerElement {
+        byte_count: 0,
+        class_info: ClassInfo::NewClass(class_name.to_string()),
+        remaining_bytes: 0,
+        version: TStreamerType::class_version(class_name),
+        tstreamer_element_base: TStreamerElementBase {
+            version: 4,
+            tnamed: TNamed {
+                name: TString::new(name),
+                title: TString::new(title),
+                ..Default::default()
+            },
+            f_type,
+            f_size,
+            type_name: TString::new(type_name),
+            ..Default::default()
+        },
+        tstreamer_type,
+    }
+}
+
+// The class declaring the counter of an array, the class itself or one of its bases
+fn counter_owner<'a>(
+    class: &'a Class,
+    counter: &str,
+    classes: &HashMap<&str, &'a Class>,
+) -> Option<&'a Class> {
+    for member in &class.members {
+        match *member {
+            Basic(name, ..) if name == counter => return Some(class),
+            Base(base) => {
+                if let Some(owner) = counter_owner(classes.get(base)?, counter, classes) {
+                    return Some(owner);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+// The info of a class, after those of its bases, whose checksum and version it holds
+fn build(
+    name: &str,
+    classes: &HashMap<&str, &Class>,
+    infos: &mut HashMap<String, Arc<TStreamerInfo>>,
+) -> Option<Arc<TStreamerInfo>> {
+    if let Some(info) = infos.get(name) {
+        return Some(info.clone());
+    }
+    let class = classes.get(name)?;
+    let mut elements = Vec::new();
+    for member in &class.members {
+        let element = match *member {
+            Base(base) => {
+                let base_info = build(base, classes, infos)?;
+                let f_type = match base {
+                    "TObject" => K_TOBJECT,
+                    "TNamed" => K_TNAMED,
+                    _ => K_BASE,
+                };
+                let mut element = element(
+                    "TStreamerBase",
+                    base,
+                    classes[base].title,
+                    f_type,
+                    0,
+                    "BASE",
+                    TStreamerType::TStreamerBase {
+                        base_version: base_info.f_class_version,
+                    },
+                );
+                // the checksum of the base, TStreamerBase::fBaseCheckSum
+                element.tstreamer_element_base.f_max_index[1] = base_info.f_checksum;
+                element
+            }
+            Basic(name, title, f_type, type_name) => element(
+                "TStreamerBasicType",
+                name,
+                title,
+                f_type,
+                basic_size(f_type),
+                type_name,
+                TStreamerType::TStreamerBasicType,
+            ),
+            Str(name, title) => element(
+                "TStreamerString",
+                name,
+                title,
+                K_TSTRING,
+                24,
+                "TString",
+                TStreamerType::TStreamerString,
+            ),
+            Object(name, title, f_type, object_class, size) => {
+                let (element_class, tstreamer_type) = if f_type == K_ANY {
+                    ("TStreamerObjectAny", TStreamerType::TStreamerObjectAny)
+                } else {
+                    ("TStreamerObject", TStreamerType::TStreamerObject)
+                };
+                element(
+                    element_class,
+                    name,
+                    title,
+                    f_type,
+                    size,
+                    object_class,
+                    tstreamer_type,
+                )
+            }
+            Pointer(name, title, f_type, object_class) => element(
+                "TStreamerObjectPointer",
+                name,
+                title,
+                f_type,
+                8,
+                &format!("{}*", object_class),
+                TStreamerType::TStreamerObjectPointer,
+            ),
+            Stl(name, title, type_name, stl_type, c_type) => element(
+                "TStreamerSTL",
+                name,
+                title,
+                K_STL,
+                24,
+                type_name,
+                TStreamerType::TStreamerSTL { stl_type, c_type },
+            ),
+            BasicPointer(name, title, f_type, type_name, size, counter) => {
+                let owner = counter_owner(class, counter, classes)?;
+                element(
+                    "TStreamerBasicPointer",
+                    name,
+                    title,
+                    K_OFFSET_P + f_type,
+                    size,
+                    type_name,
+                    TStreamerType::TStreamerBasicPointer {
+                        count_version: owner.version,
+                        name: TString::new(counter),
+                        class_name: TString::new(&owner.name),
+                    },
+                )
+            }
+        };
+        elements.push(Some(Arc::new(element)));
+    }
+
+    let mut info = TStreamerInfo {
+        class_info: ClassInfo::NewClass(String::from("TStreamerInfo")),
+        version: 9,
+        tnamed: TNamed {
+            version: 1,
+            tobject: TObject {
+                f_bits: 0x10000, // kIsCompiled
+                ..Default::default()
+            },
+            name: TString::new(&class.name),
+            ..Default::default()
+        },
+        f_class_version: class.version,
+        tobjarray: TObjArray {
+            class_info: ClassInfo::NewClass(String::from("TObjArray")),
+            version: 3,
+            n_objects: elements.len() as u32,
+            objects: elements,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    info.f_checksum = class.checksum.unwrap_or_else(|| info.compute_checksum());
+    let info = Arc::new(info);
+    infos.insert(class.name.clone(), info.clone());
+    Some(info)
+}
+
+fn builtin_infos() -> &'static HashMap<String, Arc<TStreamerInfo>> {
+    static INFOS: OnceLock<HashMap<String, Arc<TStreamerInfo>>> = OnceLock::new();
+    INFOS.get_or_init(|| {
+        let classes = classes();
+        let by_name: HashMap<&str, &Class> = classes
+            .iter()
+            .map(|class| (class.name.as_str(), class))
+            .collect();
+        let mut infos = HashMap::new();
+        for class in &classes {
+            build(&class.name, &by_name, &mut infos);
+        }
+        infos
+    })
+}
+
+// The info of a class written by the crate
+pub fn streamer_info(class_name: &str) -> Option<Arc<TStreamerInfo>> {
+    builtin_infos().get(class_name).cloned()
+}
+
+// The info of a class followed by those of the classes it streams: bases, members and pointees.
+// Empty for a class that is not built in.
+pub fn streamer_infos(class_name: &str) -> Vec<Arc<TStreamerInfo>> {
+    fn visit(class_name: &str, infos: &mut Vec<Arc<TStreamerInfo>>) {
+        let Some(info) = streamer_info(class_name) else {
+            return;
+        };
+        if infos.iter().any(|other| Arc::ptr_eq(other, &info)) {
+            return;
+        }
+        infos.push(info.clone());
+        for element in info.iter() {
+            let class_name = if element.is_base() {
+                element.name()
+            } else {
+                element.type_name().trim_end_matches('*')
+            };
+            visit(class_name, infos);
+        }
+    }
+    let mut infos = Vec::new();
+    visit(class_name, &mut infos);
+    infos
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::objects::tkey::TKey;
+    use crate::objects::tlist::TList;
+    use binrw::BinRead;
+
+    #[test]
+    fn test_builtin_streamer_infos() {
+        // the classes ROOT wrote in the test file
+        let path =
+            "/Users/kylelau519/Programming/rusty_root/rusty_root_io/testfiles/streamer_info.bin";
+        let bytes = std::fs::read(path).expect("Failed to read streamer info file");
+        let mut reader = std::io::Cursor::new(&bytes);
+        TKey::read_be(&mut reader).expect("Failed to read TKey");
+        let tlist: TList<TStreamerInfo> =
+            BinRead::read_be(&mut reader).expect("Failed to read TList");
+        let mut compared = 0;
+        for expected in tlist.iter().filter(|info| info.version > 0) {
+            let Some(info) = streamer_info(&expected.tnamed.name) else {
+                continue;
+            };
+            let name = expected.tnamed.name.as_str();
+            assert_eq!(info.f_class_version, expected.f_class_version, "{}", name);
+            assert_eq!(info.f_checksum, expected.f_checksum, "{}", name);
+            let elements: Vec<&TStreamerElement> = info.iter().collect();
+            let expected_elements: Vec<&TStreamerElement> = expected.iter().collect();
+            assert_eq!(elements.len(), expected_elements.len(), "{}", name);
+            for (element, expected) in elements.iter().zip(expected_elements) {
+                let (base, expected_base) = (
+                    &element.tstreamer_element_base,
+                    &expected.tstreamer_element_base,
+                );
+                assert_eq!(element.class_name(), expected.class_name());
+                assert_eq!(base.name(), expected_base.name());
+                assert_eq!(base.title(), expected_base.title(), "{}", name);
+                assert_eq!(base.f_type, expected_base.f_type, "{}", base.name());
+                assert_eq!(element.type_name(), expected.type_name());
+                assert_eq!(base.f_max_index, expected_base.f_max_index);
+                // the base versions are those of the classes written, not of the file
+                if !element.is_base() {
+                    assert_eq!(
+                        format!("{:?}", element.tstreamer_type),
+                        format!("{:?}", expected.tstreamer_type)
+                    );
+                }
+            }
+            compared += 1;
+        }
+        assert_eq!(compared, 34);
+
+        let names: Vec<String> = streamer_infos("TProfile")
+            .iter()
+            .map(|info| info.tnamed.name.to_string())
+            .collect();
+        for class in [
+            "TProfile",
+            "TH1D",
+            "TH1",
+            "TAxis",
+            "TAttAxis",
+            "TArrayD",
+            "THashList",
+        ] {
+            assert!(names.iter().any(|name| name == class), "{}", class);
+        }
+        assert!(streamer_infos("TFoo").is_empty());
+    }
+}
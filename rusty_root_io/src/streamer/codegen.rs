@@ -0,0 +1,811 @@
+use crate::core::constant::*;
+use crate::streamer::member::PackedFloat;
+use crate::streamer::registry::FieldKind;
+use crate::streamer::streamerinfo::StreamerInfo;
+use crate::streamer::tstreamer_element::{TStreamerElement, TStreamerType};
+use crate::streamer::tstreamerinfo::TStreamerInfo;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fmt::Write;
+use std::io;
+
+/*
+ * Rust source generation from the streamer info of a file, the counterpart of TFile::MakeProject.
+ * Every class becomes a struct with a BinRead implementation reading the streamed object, i.e. the
+ * byte count and version followed by the members in streamer order, the same layout as TNamed.
+ *
+ * TObject, TNamed, TString and the TArray family map to the types of this crate, other classes
+ * with a hand-written ROOT streamer (collections, ...) and classes missing from the streamer info
+ * are kept as RawObject. Members that cannot be decoded end the struct, the bytes from there to
+ * the end of the object are kept in `unread`.
+ *
+ * The output for TH1D is checked in as tests/codegen/th1d.rs, which tests/codegen.rs compiles and
+ * keeps in step with the generator.
+ */
+
+// Generate the source for `classes` and the classes they derive from or embed,
+// for every class in the streamer info when `classes` is empty.
+pub fn generate_rust(streamer_info: &StreamerInfo, classes: &[&str]) -> io::Result<String> {
+    let mut infos: HashMap<&str, &TStreamerInfo> = HashMap::new();
+    for info in streamer_info.iter() {
+        if !info.tnamed.name.is_empty() {
+            infos.entry(info.tnamed.name.as_str()).or_insert(info);
+        }
+    }
+
+    let mut selected = HashSet::new();
+    if classes.is_empty() {
+        selected.extend(infos.keys().copied().filter(|name| is_generated(name)));
+    } else {
+        for class in classes {
+            if !infos.contains_key(class) {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("class {} is not in the streamer info", class),
+                ));
+            }
+            if !is_generated(class) {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("class {} is provided by rusty_root_io", class),
+                ));
+            }
+            add_with_dependencies(class, &infos, &mut selected);
+        }
+    }
+
+    let generator = Generator {
+        infos: &infos,
+        selected: &selected,
+    };
+    let mut imports = BTreeSet::new();
+    let mut body = String::new();
+    // keep the order of the streamer info, bases and members come first. A class can be listed
+    // more than once, the first entry is the one the map above refers to
+    let mut generated = HashSet::new();
+    for info in streamer_info.iter() {
+        let class = info.tnamed.name.as_str();
+        if selected.contains(class) && generated.insert(class) {
+            body.push('\n');
+            body.push_str(&generator.class_source(info, &mut imports));
+        }
+    }
+
+    let mut source = String::new();
+    source.push_str("// Generated by rusty_root codegen from the streamer info of a ROOT file.\n");
+    source.push_str("// Do not edit, regenerate instead.\n\n");
+    for import in imports {
+        writeln!(source, "use {};", import).unwrap();
+    }
+    source.push_str(&body);
+    Ok(source)
+}
+
+fn add_with_dependencies<'a>(
+    class: &'a str,
+    infos: &HashMap<&'a str, &'a TStreamerInfo>,
+    selected: &mut HashSet<&'a str>,
+) {
+    if !is_generated(class) || !selected.insert(class) {
+        return;
+    }
+    for element in infos[class].iter() {
+        let base = &element.tstreamer_element_base;
+        let dependency = match element.tstreamer_type {
+            TStreamerType::TStreamerBase { .. } => base.tnamed.name.as_str(),
+            TStreamerType::TStreamerObject | TStreamerType::TStreamerObjectAny => {
+                base.type_name.as_str()
+            }
+            TStreamerType::TStreamerObjectPointer | TStreamerType::TStreamerObjectAnyPointer
+                if base.f_type == K_OBJECTP || base.f_type == K_ANYP =>
+            {
+                base.type_name.trim_end_matches('*')
+            }
+            _ => continue,
+        };
+        if let Some((name, _)) = infos.get_key_value(dependency) {
+            add_with_dependencies(name, infos, selected);
+        }
+    }
+}
+
+// Classes mapped to the types of this crate
+fn builtin_type(class: &str) -> Option<&'static str> {
+    match class {
+        "TObject" => Some("TObject"),
+        "TNamed" => Some("TNamed"),
+        "TString" => Some("TString"),
+        "TArrayC" => Some("TArrayC"),
+        "TArrayS" => Some("TArrayS"),
+        "TArrayI" => Some("TArrayI"),
+        "TArrayL" => Some("TArrayL"),
+        "TArrayL64" => Some("TArrayL64"),
+        "TArrayF" => Some("TArrayF"),
+        "TArrayD" => Some("TArrayD"),
+        _ => None,
+    }
+}
+
+fn builtin_import(rust_type: &str) -> String {
+    match rust_type {
+        "TObject" => "rusty_root_io::objects::tobject::TObject".to_string(),
+        "TNamed" => "rusty_root_io::objects::tnamed::TNamed".to_string(),
+        "TString" => "rusty_root_io::objects::tstring::TString".to_string(),
+        array => format!("rusty_root_io::objects::tarray::{}", array),
+    }
+}
+
+// Classes whose ROOT streamer is written by hand, their streamer info does not describe the bytes
+fn has_custom_streamer(class: &str) -> bool {
+    matches!(
+        class,
+        "TArray"
+            | "TCollection"
+            | "TSeqCollection"
+            | "TList"
+            | "THashList"
+            | "TObjArray"
+            | "TClonesArray"
+            | "TMap"
+            | "TRefArray"
+            | "TRef"
+    )
+}
+
+fn is_generated(class: &str) -> bool {
+    builtin_type(class).is_none() && !has_custom_streamer(class)
+}
+
+pub fn type_ident(class: &str) -> String {
+    let ident: String = class
+        .replace("::", "_")
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    ident.trim_end_matches('_').to_string()
+}
+
+// fNcells -> f_ncells, fXaxis -> f_xaxis, fBarOffset -> f_bar_offset
+pub fn field_ident(member: &str) -> String {
+    let chars: Vec<char> = member.chars().collect();
+    let mut ident = String::new();
+    for (i, c) in chars.iter().enumerate() {
+        if c.is_ascii_uppercase() {
+            if i > 0 && (chars[i - 1].is_ascii_lowercase() || chars[i - 1].is_ascii_digit()) {
+                ident.push('_');
+            }
+            ident.push(c.to_ascii_lowercase());
+        } else if c.is_ascii_alphanumeric() {
+            ident.push(*c);
+        } else {
+            ident.push('_');
+        }
+    }
+    if ident.starts_with(|c: char| c.is_ascii_digit()) {
+        ident.insert(0, '_');
+    }
+    if is_keyword(&ident) {
+        ident.insert_str(0, "r#");
+    }
+    ident
+}
+
+// Base classes are named like the hand-written objects: TNamed -> tnamed
+fn base_ident(class: &str) -> String {
+    let ident = type_ident(class).to_ascii_lowercase();
+    if is_keyword(&ident) {
+        format!("r#{}", ident)
+    } else {
+        ident
+    }
+}
+
+fn is_keyword(ident: &str) -> bool {
+    matches!(
+        ident,
+        "as" | "break"
+            | "const"
+            | "continue"
+            | "crate"
+            | "else"
+            | "enum"
+            | "extern"
+            | "false"
+            | "fn"
+            | "for"
+            | "if"
+            | "impl"
+            | "in"
+            | "let"
+            | "loop"
+            | "match"
+            | "mod"
+            | "move"
+            | "mut"
+            | "pub"
+            | "ref"
+            | "return"
+            | "self"
+            | "static"
+            | "struct"
+            | "super"
+            | "trait"
+            | "true"
+            | "type"
+            | "unsafe"
+            | "use"
+            | "where"
+            | "while"
+            | "async"
+            | "await"
+            | "dyn"
+    )
+}
+
+// Rust type of a basic fType, without the kOffsetL/kOffsetP offsets
+fn basic_type(f_type: u32) -> Option<&'static str> {
+    FieldKind::from_basic_type(f_type)?.rust_type()
+}
+
+// How a Double32_t or Float16_t member is packed, see TStreamerElement::GetRange
+fn packed_float_expr(element: &TStreamerElement) -> String {
+    let base = &element.tstreamer_element_base;
+    let packed = if base.is_float16() {
+        PackedFloat::float16(base)
+    } else {
+        PackedFloat::double32(base)
+    };
+    match packed {
+        PackedFloat::Float => "PackedFloat::Float".to_string(),
+        PackedFloat::Scaled { xmin, factor } => format!(
+            "PackedFloat::Scaled {{ xmin: {:?}, factor: {:?} }}",
+            xmin, factor
+        ),
+        PackedFloat::Truncated { nbits } => {
+            format!("PackedFloat::Truncated {{ nbits: {} }}", nbits)
+        }
+    }
+}
+
+// Type and read expression of a Double32_t or Float16_t member, or of a fixed array of them
+fn packed_float(
+    element: &TStreamerElement,
+    length: u32,
+    imports: &mut BTreeSet<String>,
+) -> (String, String) {
+    imports.insert("rusty_root_io::streamer::member::PackedFloat".to_string());
+    let packed = packed_float_expr(element);
+    let (rust_type, cast) = if element.tstreamer_element_base.is_float16() {
+        ("f32", " as f32")
+    } else {
+        ("f64", "")
+    };
+    if length == 0 {
+        (
+            rust_type.to_string(),
+            format!("{}.read(reader, endian)?{}", packed, cast),
+        )
+    } else {
+        (
+            format!("[{}; {}]", rust_type, length),
+            format!(
+                "{{ let mut values = [0{}; {}]; for value in values.iter_mut() {{ *value = {}.read(reader, endian)?{}; }} values }}",
+                rust_type, length, packed, cast
+            ),
+        )
+    }
+}
+
+struct Generator<'a> {
+    infos: &'a HashMap<&'a str, &'a TStreamerInfo>,
+    selected: &'a HashSet<&'a str>,
+}
+
+// How one data member is declared and read
+struct Member {
+    field: String,
+    rust_type: String,
+    read: String,
+    comment: String,
+    needs_ctx: bool,
+}
+
+impl Generator<'_> {
+    fn class_source(&self, info: &TStreamerInfo, imports: &mut BTreeSet<String>) -> String {
+        let class = info.tnamed.name.as_str();
+        let ident = type_ident(class);
+        imports.insert("binrw::io::{Read, Seek}".to_string());
+        imports.insert("binrw::{BinRead, BinResult, Endian}".to_string());
+        imports.insert("rusty_root_io::core::read_context::ReadContext".to_string());
+        imports.insert("rusty_root_io::streamer::member::VersionHeader".to_string());
+
+        let mut members = Vec::new();
+        let mut fields = HashSet::new();
+        let mut unread = None;
+        // artificial members are set by I/O rules, they are not in the buffer
+        for element in info.iter().filter(|element| !element.is_artificial()) {
+            match self.member(info, element, imports) {
+                Ok(mut member) => {
+                    while !fields.insert(member.field.clone()) {
+                        member.field.push('_');
+                    }
+                    members.push(member);
+                }
+                Err(reason) => {
+                    unread = Some(reason);
+                    break;
+                }
+            }
+        }
+        if let Some(reason) = &unread {
+            imports.insert("rusty_root_io::streamer::member::read_remaining".to_string());
+            members.push(Member {
+                field: "unread".to_string(),
+                rust_type: "Vec<u8>".to_string(),
+                read: "read_remaining(reader, &header)?".to_string(),
+                comment: format!("{}, it and the members after it are not decoded", reason),
+                needs_ctx: false,
+            });
+        }
+        let needs_ctx = members.iter().any(|member| member.needs_ctx);
+
+        let mut source = String::new();
+        writeln!(
+            source,
+            "// {} version {}, checksum {:#010x}",
+            class, info.f_class_version, info.f_checksum
+        )
+        .unwrap();
+        if !info.tnamed.title.is_empty() {
+            writeln!(source, "// {}", info.tnamed.title.as_str()).unwrap();
+        }
+        if ident.contains('_') {
+            source.push_str("#[allow(non_camel_case_types)]\n");
+        }
+        source.push_str("#[derive(Debug)]\n");
+        writeln!(source, "pub struct {} {{", ident).unwrap();
+        source.push_str("    pub byte_count: u32,\n");
+        source.push_str("    pub version: u16,\n");
+        for member in &members {
+            if !member.comment.is_empty() {
+                writeln!(source, "    // {}", member.comment).unwrap();
+            }
+            writeln!(source, "    pub {}: {},", member.field, member.rust_type).unwrap();
+        }
+        source.push_str("}\n\n");
+
+        writeln!(source, "impl BinRead for {} {{", ident).unwrap();
+        source.push_str("    type Args<'a> = (ReadContext,);\n\n");
+        source.push_str("    fn read_options<R: Read + Seek>(\n");
+        source.push_str("        reader: &mut R,\n");
+        source.push_str("        endian: Endian,\n");
+        if needs_ctx {
+            source.push_str("        args: Self::Args<'_>,\n");
+        } else {
+            source.push_str("        _args: Self::Args<'_>,\n");
+        }
+        source.push_str("    ) -> BinResult<Self> {\n");
+        if needs_ctx {
+            source.push_str("        let (ctx,) = args;\n");
+        }
+        source.push_str("        let header = VersionHeader::read(reader, endian)?;\n");
+        writeln!(
+            source,
+            "        if header.version != {} {{",
+            info.f_class_version
+        )
+        .unwrap();
+        source.push_str("            return Err(binrw::Error::AssertFail {\n");
+        source.push_str("                pos: header.start,\n");
+        writeln!(
+            source,
+            "                message: format!(\"{} version {{}} differs from the generated version {}\", header.version),",
+            class, info.f_class_version
+        )
+        .unwrap();
+        source.push_str("            });\n");
+        source.push_str("        }\n");
+        for member in &members {
+            writeln!(source, "        let {} = {};", member.field, member.read).unwrap();
+        }
+        if unread.is_none() {
+            source.push_str("        header.skip_to_end(reader)?;\n");
+        }
+        source.push_str("        Ok(Self {\n");
+        source.push_str("            byte_count: header.byte_count,\n");
+        source.push_str("            version: header.version,\n");
+        for member in &members {
+            writeln!(source, "            {},", member.field).unwrap();
+        }
+        source.push_str("        })\n");
+        source.push_str("    }\n");
+        source.push_str("}\n");
+        source
+    }
+
+    fn member(
+        &self,
+        info: &TStreamerInfo,
+        element: &TStreamerElement,
+        imports: &mut BTreeSet<String>,
+    ) -> Result<Member, String> {
+        let base = &element.tstreamer_element_base;
+        let name = base.tnamed.name.as_str();
+        let type_name = base.type_name.as_str();
+        let comment = base.tnamed.title.trim().to_string();
+        let unsupported = || format!("{} ({}, type {})", name, type_name, base.f_type);
+
+        match &element.tstreamer_type {
+            TStreamerType::TStreamerBase { .. } => {
+                let (rust_type, read, needs_ctx) = self.embedded(name, imports);
+                Ok(Member {
+                    field: base_ident(name),
+                    rust_type,
+                    read,
+                    comment: String::new(),
+                    needs_ctx,
+                })
+            }
+            TStreamerType::TStreamerBasicType => {
+                let (f_type, length) = if base.f_type > K_OFFSET_L && base.f_type < K_OFFSET_P {
+                    (base.f_type - K_OFFSET_L, base.f_array_length)
+                } else {
+                    (base.f_type, 0)
+                };
+                if f_type == K_CHAR_STAR {
+                    imports.insert("rusty_root_io::streamer::member::read_char_star".to_string());
+                    return Ok(Member {
+                        field: field_ident(name),
+                        rust_type: "String".to_string(),
+                        read: "read_char_star(reader, endian)?".to_string(),
+                        comment,
+                        needs_ctx: false,
+                    });
+                }
+                if f_type == K_FLOAT16 || f_type == K_DOUBLE32 {
+                    let (rust_type, read) = packed_float(element, length, imports);
+                    return Ok(Member {
+                        field: field_ident(name),
+                        rust_type,
+                        read,
+                        comment,
+                        needs_ctx: false,
+                    });
+                }
+                let rust_type = basic_type(f_type).ok_or_else(unsupported)?;
+                let (rust_type, read) = match (f_type, length) {
+                    (K_BOOL, 0) => {
+                        imports.insert("rusty_root_io::streamer::member::read_bool".to_string());
+                        ("bool".to_string(), "read_bool(reader, endian)?".to_string())
+                    }
+                    (K_BOOL, n) => (
+                        format!("[bool; {}]", n),
+                        format!(
+                            "<[u8; {}]>::read_options(reader, endian, ())?.map(|b| b != 0)",
+                            n
+                        ),
+                    ),
+                    (_, 0) => (
+                        rust_type.to_string(),
+                        format!("{}::read_options(reader, endian, ())?", rust_type),
+                    ),
+                    (_, n) => (
+                        format!("[{}; {}]", rust_type, n),
+                        format!(
+                            "<[{}; {}]>::read_options(reader, endian, ())?",
+                            rust_type, n
+                        ),
+                    ),
+                };
+                Ok(Member {
+                    field: field_ident(name),
+                    rust_type,
+                    read,
+                    comment,
+                    needs_ctx: false,
+                })
+            }
+            TStreamerType::TStreamerBasicPointer { name: count, .. } => {
+                let f_type = base.f_type.wrapping_sub(K_OFFSET_P);
+                let count = self
+                    .counter_path(info, count.as_str())
+                    .ok_or_else(unsupported)?;
+                if f_type == K_FLOAT16 || f_type == K_DOUBLE32 {
+                    imports.insert("rusty_root_io::streamer::member::PackedFloat".to_string());
+                    let packed = packed_float_expr(element);
+                    let read =
+                        format!("{}.read_pointer(reader, endian, {} as i64)?", packed, count);
+                    let (rust_type, read) = if f_type == K_FLOAT16 {
+                        (
+                            "Vec<f32>".to_string(),
+                            format!("{}.into_iter().map(|v| v as f32).collect()", read),
+                        )
+                    } else {
+                        ("Vec<f64>".to_string(), read)
+                    };
+                    return Ok(Member {
+                        field: field_ident(name),
+                        rust_type,
+                        read,
+                        comment,
+                        needs_ctx: false,
+                    });
+                }
+                let rust_type = basic_type(f_type).ok_or_else(unsupported)?;
+                imports.insert("rusty_root_io::streamer::member::read_basic_pointer".to_string());
+                let (rust_type, read) = match f_type {
+                    K_BOOL => (
+                        "Vec<bool>".to_string(),
+                        format!("read_basic_pointer::<u8, _>(reader, endian, {} as i64)?.into_iter().map(|b| b != 0).collect()", count),
+                    ),
+                    _ => (
+                        format!("Vec<{}>", rust_type),
+                        format!("read_basic_pointer::<{}, _>(reader, endian, {} as i64)?", rust_type, count),
+                    ),
+                };
+                Ok(Member {
+                    field: field_ident(name),
+                    rust_type,
+                    read,
+                    comment,
+                    needs_ctx: false,
+                })
+            }
+            TStreamerType::TStreamerString | TStreamerType::TStreamerSTLString { .. } => {
+                imports.insert(builtin_import("TString"));
+                let (rust_type, read) = self.repeat(
+                    "TString".to_string(),
+                    "TString::read_options(reader, endian, ())?".to_string(),
+                    base.f_array_length,
+                );
+                Ok(Member {
+                    field: field_ident(name),
+                    rust_type,
+                    read,
+                    comment,
+                    needs_ctx: false,
+                })
+            }
+            TStreamerType::TStreamerObject | TStreamerType::TStreamerObjectAny => {
+                let (rust_type, read, needs_ctx) = self.embedded(type_name, imports);
+                let (rust_type, read) = self.repeat(rust_type, read, base.f_array_length);
+                Ok(Member {
+                    field: field_ident(name),
+                    rust_type,
+                    read,
+                    comment,
+                    needs_ctx,
+                })
+            }
+            TStreamerType::TStreamerObjectPointer | TStreamerType::TStreamerObjectAnyPointer
+                if base.f_type == K_OBJECTP || base.f_type == K_ANYP =>
+            {
+                let (rust_type, read, needs_ctx) =
+                    self.embedded(type_name.trim_end_matches('*'), imports);
+                let (rust_type, read) = self.repeat(rust_type, read, base.f_array_length);
+                Ok(Member {
+                    field: field_ident(name),
+                    rust_type,
+                    read,
+                    comment,
+                    needs_ctx,
+                })
+            }
+            TStreamerType::TStreamerObjectPointer | TStreamerType::TStreamerObjectAnyPointer => {
+                let class = type_name.trim_end_matches('*');
+                let pointee = if self.selected.contains(class) {
+                    type_ident(class)
+                } else {
+                    imports.insert("rusty_root_io::objects::raw_object::RawObject".to_string());
+                    "RawObject".to_string()
+                };
+                imports.insert("std::sync::Arc".to_string());
+                let (rust_type, read) = self.repeat(
+                    format!("Option<Arc<{}>>", pointee),
+                    format!(
+                        "ctx.read_streamed_object::<{}, _>(reader, endian)?",
+                        pointee
+                    ),
+                    base.f_array_length,
+                );
+                Ok(Member {
+                    field: field_ident(name),
+                    rust_type,
+                    read,
+                    comment,
+                    needs_ctx: true,
+                })
+            }
+            TStreamerType::TStreamerSTL { stl_type, .. } => {
+                let element_type = match FieldKind::from_type_name(type_name) {
+                    Some(FieldKind::StlVector(elem)) if *stl_type == K_STL_VECTOR => {
+                        elem.rust_type().map(|rust_type| (*elem, rust_type))
+                    }
+                    _ => None,
+                };
+                let (rust_type, read, needs_ctx) = match element_type {
+                    Some((FieldKind::Bool, _)) => {
+                        imports
+                            .insert("rusty_root_io::streamer::member::read_stl_vector".to_string());
+                        (
+                            "Vec<bool>".to_string(),
+                            "read_stl_vector::<u8, _>(reader, endian)?.into_iter().map(|b| b != 0).collect()".to_string(),
+                            false,
+                        )
+                    }
+                    // written as float by the collection proxy, which has no range for the elements
+                    Some((FieldKind::Double32(_), _)) => {
+                        imports
+                            .insert("rusty_root_io::streamer::member::read_stl_vector".to_string());
+                        (
+                            "Vec<f64>".to_string(),
+                            "read_stl_vector::<f32, _>(reader, endian)?.into_iter().map(f64::from).collect()".to_string(),
+                            false,
+                        )
+                    }
+                    Some((FieldKind::Float16(_), _)) | None => {
+                        imports.insert("rusty_root_io::objects::raw_object::RawObject".to_string());
+                        (
+                            "RawObject".to_string(),
+                            "RawObject::read_options(reader, endian, (ctx.clone(),))?".to_string(),
+                            true,
+                        )
+                    }
+                    Some((_, element_type)) => {
+                        imports
+                            .insert("rusty_root_io::streamer::member::read_stl_vector".to_string());
+                        if element_type == "TString" {
+                            imports.insert(builtin_import("TString"));
+                        }
+                        (
+                            format!("Vec<{}>", element_type),
+                            format!("read_stl_vector::<{}, _>(reader, endian)?", element_type),
+                            false,
+                        )
+                    }
+                };
+                Ok(Member {
+                    field: field_ident(name),
+                    rust_type,
+                    read,
+                    comment,
+                    needs_ctx,
+                })
+            }
+            TStreamerType::TStreamerLoop { .. } => {
+                imports.insert("rusty_root_io::objects::raw_object::RawObject".to_string());
+                Ok(Member {
+                    field: field_ident(name),
+                    rust_type: "RawObject".to_string(),
+                    read: "RawObject::read_options(reader, endian, (ctx.clone(),))?".to_string(),
+                    comment,
+                    needs_ctx: true,
+                })
+            }
+            TStreamerType::TStreamerArtificial | TStreamerType::Unknown(_) => Err(unsupported()),
+        }
+    }
+
+    // An object streamed in place: type, read expression and whether it needs the read context
+    fn embedded(&self, class: &str, imports: &mut BTreeSet<String>) -> (String, String, bool) {
+        if let Some(rust_type) = builtin_type(class) {
+            imports.insert(builtin_import(rust_type));
+            // TNamed is a derived RootStreamer and takes the read context like generated classes
+            let needs_ctx = rust_type == "TNamed";
+            let args = if needs_ctx { "(ctx.clone(),)" } else { "()" };
+            (
+                rust_type.to_string(),
+                format!("{}::read_options(reader, endian, {})?", rust_type, args),
+                needs_ctx,
+            )
+        } else if self.selected.contains(class) {
+            let ident = type_ident(class);
+            let read = format!("{}::read_options(reader, endian, (ctx.clone(),))?", ident);
+            (ident, read, true)
+        } else {
+            imports.insert("rusty_root_io::objects::raw_object::RawObject".to_string());
+            (
+                "RawObject".to_string(),
+                "RawObject::read_options(reader, endian, (ctx.clone(),))?".to_string(),
+                true,
+            )
+        }
+    }
+
+    // Fixed size arrays of objects are read one after the other
+    fn repeat(&self, rust_type: String, read: String, length: u32) -> (String, String) {
+        if length == 0 {
+            (rust_type, read)
+        } else {
+            (
+                format!("Vec<{}>", rust_type),
+                format!(
+                    "(0..{}).map(|_| Ok({})).collect::<BinResult<Vec<_>>>()?",
+                    length, read
+                ),
+            )
+        }
+    }
+
+    // Expression for the counter of a `//[fN]` array, a member of the class or of one of its bases
+    fn counter_path(&self, info: &TStreamerInfo, count: &str) -> Option<String> {
+        for element in info.iter() {
+            let base = &element.tstreamer_element_base;
+            match element.tstreamer_type {
+                TStreamerType::TStreamerBase { .. } => {
+                    let class = base.tnamed.name.as_str();
+                    if !self.selected.contains(class) {
+                        continue;
+                    }
+                    if let Some(path) = self
+                        .infos
+                        .get(class)
+                        .and_then(|base_info| self.counter_path(base_info, count))
+                    {
+                        return Some(format!("{}.{}", base_ident(class), path));
+                    }
+                }
+                _ if base.tnamed.name == count => return Some(field_ident(count)),
+                _ => {}
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::objects::tkey::TKey;
+    use binrw::BinRead;
+    use std::fs::File;
+    use std::io::BufReader;
+
+    fn read_streamer_info() -> StreamerInfo {
+        let path =
+            "/Users/kylelau519/Programming/rusty_root/rusty_root_io/testfiles/streamer_info.bin";
+        let file = File::open(path).expect("Failed to open streamer info file");
+        let mut reader = BufReader::new(file);
+        let streamer_info_header = TKey::read_be(&mut reader).expect("Failed to read TKey");
+        let tlist = BinRead::read_be(&mut reader).expect("Failed to read TList");
+        StreamerInfo {
+            streamer_info_header,
+            tlist,
+        }
+    }
+
+    #[test]
+    fn test_idents() {
+        assert_eq!(field_ident("fNcells"), "f_ncells");
+        assert_eq!(field_ident("fBarOffset"), "f_bar_offset");
+        assert_eq!(field_ident("fIOBits"), "f_iobits");
+        assert_eq!(type_ident("ROOT::TIOFeatures"), "ROOT_TIOFeatures");
+        assert_eq!(type_ident("TParameter<double>"), "TParameter_double");
+        assert_eq!(base_ident("TAttLine"), "tattline");
+    }
+
+    #[test]
+    fn test_generate_selected_class_with_bases() {
+        let streamer_info = read_streamer_info();
+        let source = generate_rust(&streamer_info, &["TH1D"]).expect("Failed to generate TH1D");
+        assert!(source.contains("pub struct TH1D {"));
+        // bases and embedded objects are generated too
+        assert!(source.contains("pub struct TH1 {"));
+        assert!(source.contains("pub struct TAxis {"));
+        assert!(source.contains("pub struct TAttLine {"));
+        assert!(source.contains("    pub th1: TH1,\n"));
+        assert!(source.contains("    pub tarrayd: TArrayD,\n"));
+        assert!(source.contains("    pub f_xaxis: TAxis,\n"));
+        // provided by this crate
+        assert!(!source.contains("pub struct TNamed {"));
+        assert!(source.contains("use rusty_root_io::objects::tnamed::TNamed;"));
+        // unrelated classes are left out
+        assert!(!source.contains("pub struct TTree {"));
+    }
+
+    #[test]
+    fn test_generate_unknown_class() {
+        let streamer_info = read_streamer_info();
+        assert!(generate_rust(&streamer_info, &["TNoSuchClass"]).is_err());
+        assert!(generate_rust(&streamer_info, &["TList"]).is_err());
+    }
+}
@@ -0,0 +1,417 @@
+use crate::streamer::registry::{ClassSchema, FieldKind};
+use crate::streamer::root_streamer::RootStreamer;
+use crate::streamer::streamerinfo::StreamerRegistry;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::io;
+
+/*
+ * Consistency check of the ClassSchema a Rust type was written for against the streamer info of a
+ * file: class version, checksum, member names, types and order. Base classes and embedded objects
+ * are checked as well. A version or checksum of 0 in the expected schema is not checked.
+ */
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SchemaDifference {
+    MissingClass {
+        class: String,
+    },
+    Version {
+        class: String,
+        expected: u16,
+        found: u16,
+    },
+    Checksum {
+        class: String,
+        version: u16,
+        expected: u32,
+        found: u32,
+    },
+    // in the Rust type, not in the file
+    MissingMember {
+        class: String,
+        version: u16,
+        member: String,
+    },
+    // in the file, not in the Rust type
+    ExtraMember {
+        class: String,
+        version: u16,
+        member: String,
+        type_name: String,
+    },
+    MemberType {
+        class: String,
+        version: u16,
+        member: String,
+        expected: String,
+        found: String,
+    },
+    MemberOrder {
+        class: String,
+        version: u16,
+        member: String,
+        expected: usize,
+        found: usize,
+    },
+}
+
+impl fmt::Display for SchemaDifference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingClass { class } => {
+                write!(f, "class {} is not in the streamer info", class)
+            }
+            Self::Version {
+                class,
+                expected,
+                found,
+            } => write!(
+                f,
+                "class {} has version {} in the file, expected version {}",
+                class, found, expected
+            ),
+            Self::Checksum {
+                class,
+                version,
+                expected,
+                found,
+            } => write!(
+                f,
+                "class {} has checksum {:#010x} in version {}, expected {:#010x}",
+                class, found, version, expected
+            ),
+            Self::MissingMember {
+                class,
+                version,
+                member,
+            } => write!(
+                f,
+                "member {} of {} was removed in version {}",
+                member, class, version
+            ),
+            Self::ExtraMember {
+                class,
+                version,
+                member,
+                type_name,
+            } => write!(
+                f,
+                "member {} ({}) of {} was added in version {}",
+                member, type_name, class, version
+            ),
+            Self::MemberType {
+                class,
+                version,
+                member,
+                expected,
+                found,
+            } => write!(
+                f,
+                "member {} of {} changed from {} to {} in version {}",
+                member, class, expected, found, version
+            ),
+            Self::MemberOrder {
+                class,
+                version,
+                member,
+                expected,
+                found,
+            } => write!(
+                f,
+                "member {} of {} moved from position {} to {} in version {}",
+                member, class, expected, found, version
+            ),
+        }
+    }
+}
+
+// Differences between the schema of a Rust type and the schema of the same class in a file
+pub fn compare_schemas(expected: &ClassSchema, found: &ClassSchema) -> Vec<SchemaDifference> {
+    let mut differences = Vec::new();
+    compare_class(expected, found, &mut HashSet::new(), &mut differences);
+    differences
+}
+
+// Differences between the schema of a Rust type and the streamer info registered from a file
+pub fn check_schema(expected: &ClassSchema, registry: &StreamerRegistry) -> Vec<SchemaDifference> {
+    match registry.get(&expected.name) {
+        Some(found) => compare_schemas(expected, &found),
+        None => vec![SchemaDifference::MissingClass {
+            class: expected.name.clone(),
+        }],
+    }
+}
+
+// Check a RootStreamer type before reading it from a file, every difference is listed in the error
+pub fn check_class<T: RootStreamer>(registry: &StreamerRegistry) -> io::Result<()> {
+    let differences = check_schema(&T::class_schema(), registry);
+    if differences.is_empty() {
+        return Ok(());
+    }
+    let messages: Vec<String> = differences.iter().map(|d| d.to_string()).collect();
+    Err(io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!(
+            "{} does not match the streamer info: {}",
+            T::CLASS_NAME,
+            messages.join("; ")
+        ),
+    ))
+}
+
+fn compare_class(
+    expected: &ClassSchema,
+    found: &ClassSchema,
+    visited: &mut HashSet<String>,
+    differences: &mut Vec<SchemaDifference>,
+) {
+    if !visited.insert(expected.name.clone()) {
+        return;
+    }
+    let class = expected.name.clone();
+    let version = found.version;
+    if expected.version != 0 && expected.version != found.version {
+        differences.push(SchemaDifference::Version {
+            class: class.clone(),
+            expected: expected.version,
+            found: found.version,
+        });
+    }
+    if expected.checksum != 0 && expected.checksum != found.checksum {
+        differences.push(SchemaDifference::Checksum {
+            class: class.clone(),
+            version,
+            expected: expected.checksum,
+            found: found.checksum,
+        });
+    }
+
+    let found_fields: HashMap<&str, &FieldKind> = found
+        .fields
+        .iter()
+        .map(|field| (field.name.as_str(), &field.kind))
+        .collect();
+    let expected_names: HashSet<&str> = expected.fields.iter().map(|f| f.name.as_str()).collect();
+
+    for field in &expected.fields {
+        match found_fields.get(field.name.as_str()) {
+            None => differences.push(SchemaDifference::MissingMember {
+                class: class.clone(),
+                version,
+                member: field.name.clone(),
+            }),
+            Some(found_kind) => {
+                if !same_kind(&field.kind, found_kind) {
+                    differences.push(SchemaDifference::MemberType {
+                        class: class.clone(),
+                        version,
+                        member: field.name.clone(),
+                        expected: field.kind.type_name(),
+                        found: found_kind.type_name(),
+                    });
+                } else {
+                    compare_nested(&field.kind, found_kind, visited, differences);
+                }
+            }
+        }
+    }
+    for field in &found.fields {
+        if !expected_names.contains(field.name.as_str()) {
+            differences.push(SchemaDifference::ExtraMember {
+                class: class.clone(),
+                version,
+                member: field.name.clone(),
+                type_name: field.kind.type_name(),
+            });
+        }
+    }
+
+    // order of the members both sides have, added and removed members are reported above
+    let expected_order = expected
+        .fields
+        .iter()
+        .filter(|field| found_fields.contains_key(field.name.as_str()));
+    let found_order = found
+        .fields
+        .iter()
+        .filter(|field| expected_names.contains(field.name.as_str()));
+    let found_positions: HashMap<&str, usize> = found_order
+        .enumerate()
+        .map(|(i, field)| (field.name.as_str(), i))
+        .collect();
+    for (i, field) in expected_order.enumerate() {
+        let position = found_positions[field.name.as_str()];
+        if position != i {
+            differences.push(SchemaDifference::MemberOrder {
+                class: class.clone(),
+                version,
+                member: field.name.clone(),
+                expected: i,
+                found: position,
+            });
+        }
+    }
+}
+
+// Same type on disk, classes are compared by name here and member-wise by compare_nested
+fn same_kind(expected: &FieldKind, found: &FieldKind) -> bool {
+    match (expected, found) {
+        (FieldKind::Base(a), FieldKind::Base(b))
+        | (FieldKind::Object(a), FieldKind::Object(b))
+        | (FieldKind::ObjectPointer(a), FieldKind::ObjectPointer(b)) => a.name == b.name,
+        (
+            FieldKind::FixedArray { elem: a, count: n },
+            FieldKind::FixedArray { elem: b, count: m },
+        ) => n == m && same_kind(a, b),
+        (
+            FieldKind::BasicPointer { elem: a, count: n },
+            FieldKind::BasicPointer { elem: b, count: m },
+        )
+        | (
+            FieldKind::CountedArray { elem: a, count: n },
+            FieldKind::CountedArray { elem: b, count: m },
+        ) => n == m && same_kind(a, b),
+        (FieldKind::StlVector(a), FieldKind::StlVector(b)) => same_kind(a, b),
+        (a, b) => a == b,
+    }
+}
+
+fn compare_nested(
+    expected: &FieldKind,
+    found: &FieldKind,
+    visited: &mut HashSet<String>,
+    differences: &mut Vec<SchemaDifference>,
+) {
+    match (expected, found) {
+        // classes with a hand-written streamer have no members to compare
+        (FieldKind::Base(a), FieldKind::Base(b)) | (FieldKind::Object(a), FieldKind::Object(b))
+            if !a.fields.is_empty() && !b.fields.is_empty() =>
+        {
+            compare_class(a, b, visited, differences);
+        }
+        (FieldKind::FixedArray { elem: a, .. }, FieldKind::FixedArray { elem: b, .. })
+        | (FieldKind::CountedArray { elem: a, .. }, FieldKind::CountedArray { elem: b, .. })
+        | (FieldKind::StlVector(a), FieldKind::StlVector(b)) => {
+            compare_nested(a, b, visited, differences)
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::objects::tkey::TKey;
+    use crate::objects::tnamed::TNamed;
+    use crate::streamer::registry::FieldSchema;
+    use crate::streamer::streamerinfo::StreamerInfo;
+    use binrw::BinRead;
+    use std::fs::File;
+    use std::io::BufReader;
+    use std::sync::Arc;
+
+    fn read_registry() -> StreamerRegistry {
+        let path =
+            "/Users/kylelau519/Programming/rusty_root/rusty_root_io/testfiles/streamer_info.bin";
+        let file = File::open(path).expect("Failed to open streamer info file");
+        let mut reader = BufReader::new(file);
+        let streamer_info_header = TKey::read_be(&mut reader).expect("Failed to read TKey");
+        let tlist = BinRead::read_be(&mut reader).expect("Failed to read TList");
+        let streamer_info = StreamerInfo {
+            streamer_info_header,
+            tlist,
+        };
+        let mut registry = StreamerRegistry::new();
+        registry.register_from_streamerinfo(&streamer_info);
+        registry
+    }
+
+    #[test]
+    fn test_tnamed_matches_streamer_info() {
+        let registry = read_registry();
+        assert!(check_schema(&TNamed::class_schema(), &registry).is_empty());
+        check_class::<TNamed>(&registry).expect("TNamed does not match");
+    }
+
+    #[test]
+    fn test_report_differences() {
+        let registry = read_registry();
+        let tattline = registry.get("TAttLine").expect("TAttLine is missing");
+        let mut expected = (*tattline).clone();
+        expected.version = 1;
+        // fLineStyle removed, fLineWidth changed from short to float, fLineColor moved last
+        expected.fields = vec![
+            FieldSchema {
+                name: "fLineWidth".to_string(),
+                kind: FieldKind::F32,
+            },
+            FieldSchema {
+                name: "fLineColor".to_string(),
+                kind: FieldKind::I16,
+            },
+            FieldSchema {
+                name: "fLineAlpha".to_string(),
+                kind: FieldKind::F32,
+            },
+        ];
+
+        let messages: Vec<String> = check_schema(&expected, &registry)
+            .iter()
+            .map(|d| d.to_string())
+            .collect();
+        assert_eq!(
+            messages,
+            [
+                "class TAttLine has version 2 in the file, expected version 1",
+                "member fLineWidth of TAttLine changed from float to short in version 2",
+                "member fLineAlpha of TAttLine was removed in version 2",
+                "member fLineStyle (short) of TAttLine was added in version 2",
+                "member fLineWidth of TAttLine moved from position 0 to 1 in version 2",
+                "member fLineColor of TAttLine moved from position 1 to 0 in version 2",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_nested_and_missing_classes() {
+        let registry = read_registry();
+        let th1 = registry.get("TH1").expect("TH1 is missing");
+        assert_eq!(th1.fields[0].kind.type_name(), "TNamed");
+        assert_eq!(th1.fields[5].kind.type_name(), "TAxis");
+        assert_eq!(
+            th1.fields.last().map(|f| f.name.as_str()),
+            Some("fStatOverflows")
+        );
+        let buffer = th1.fields.iter().find(|f| f.name == "fBuffer").unwrap();
+        assert_eq!(buffer.kind.type_name(), "double*[fBufferSize]");
+
+        // a difference in an embedded class is reported against that class
+        let mut expected = (*th1).clone();
+        let mut taxis = (*registry.get("TAxis").unwrap()).clone();
+        taxis.fields.retain(|f| f.name != "fTimeFormat");
+        expected.fields[5].kind = FieldKind::Object(Arc::new(taxis));
+        assert_eq!(
+            check_schema(&expected, &registry),
+            [SchemaDifference::ExtraMember {
+                class: "TAxis".to_string(),
+                version: 10,
+                member: "fTimeFormat".to_string(),
+                type_name: "TString".to_string(),
+            }]
+        );
+
+        let unknown = ClassSchema {
+            name: "TNoSuchClass".to_string(),
+            version: 1,
+            checksum: 0,
+            fields: Vec::new(),
+        };
+        assert_eq!(
+            check_schema(&unknown, &registry),
+            [SchemaDifference::MissingClass {
+                class: "TNoSuchClass".to_string()
+            }]
+        );
+    }
+}
@@ -0,0 +1,330 @@
+use crate::core::constant::{K_BYTECOUNTMASK, K_HAS_BYTECOUNT, K_STREAMED_MEMBERWISE};
+use crate::streamer::tstreamer_element::{get_range, TStreamerElementBase};
+use binrw::io::{Read, Seek, SeekFrom};
+use binrw::{BinRead, BinReaderExt, BinResult, Endian};
+
+/*
+ * Readers for the data members of a streamed class, in the layout TStreamerInfo::ReadBuffer expects.
+ * They are used by code generated from streamer info and by derived streamers, so that both
+ * decode members the same way as the hand-written objects.
+ */
+
+// Byte count and class version at the start of a streamed object (TBufferFile::ReadVersion)
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct VersionHeader {
+    pub start: u64,
+    pub byte_count: u32,
+    pub version: u16,
+}
+
+impl VersionHeader {
+    // The byte count is only present when kByteCountMask is set, otherwise the version comes first
+    pub fn read<R: Read + Seek>(reader: &mut R, endian: Endian) -> BinResult<Self> {
+        let start = reader.stream_position()?;
+        let first: u32 = reader.read_type(endian)?;
+        if first & K_HAS_BYTECOUNT != 0 {
+            let version = reader.read_type(endian)?;
+            Ok(Self {
+                start,
+                byte_count: first & K_BYTECOUNTMASK,
+                version,
+            })
+        } else {
+            reader.seek(SeekFrom::Start(start))?;
+            let version = reader.read_type(endian)?;
+            Ok(Self {
+                start,
+                byte_count: 0,
+                version,
+            })
+        }
+    }
+
+    // Position right after the object, None when it was written without byte count
+    pub fn end(&self) -> Option<u64> {
+        (self.byte_count != 0).then(|| self.start + 4 + self.byte_count as u64)
+    }
+
+    // Continue after the object whatever its members consumed (TBufferFile::CheckByteCount)
+    pub fn skip_to_end<R: Seek>(&self, reader: &mut R) -> BinResult<()> {
+        if let Some(end) = self.end() {
+            reader.seek(SeekFrom::Start(end))?;
+        }
+        Ok(())
+    }
+}
+
+// The members of an object that are not decoded, up to the end given by its byte count
+pub fn read_remaining<R: Read + Seek>(
+    reader: &mut R,
+    header: &VersionHeader,
+) -> BinResult<Vec<u8>> {
+    let Some(end) = header.end() else {
+        return Ok(Vec::new());
+    };
+    let pos = reader.stream_position()?;
+    let mut bytes = vec![0u8; end.saturating_sub(pos) as usize];
+    reader.read_exact(&mut bytes)?;
+    Ok(bytes)
+}
+
+pub fn read_bool<R: Read + Seek>(reader: &mut R, endian: Endian) -> BinResult<bool> {
+    Ok(reader.read_type::<u8>(endian)? != 0)
+}
+
+// char* member: the length as Int_t followed by the characters
+pub fn read_char_star<R: Read + Seek>(reader: &mut R, endian: Endian) -> BinResult<String> {
+    let length: i32 = reader.read_type(endian)?;
+    let mut bytes = vec![0u8; length.max(0) as usize];
+    reader.read_exact(&mut bytes)?;
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+// `T* fArray; //[fN]` member: a flag byte telling whether the array was allocated, then `count`
+// values where count is the value of the counter member
+pub fn read_basic_pointer<T, R>(reader: &mut R, endian: Endian, count: i64) -> BinResult<Vec<T>>
+where
+    T: for<'a> BinRead<Args<'a> = ()>,
+    R: Read + Seek,
+{
+    let is_array: u8 = reader.read_type(endian)?;
+    if is_array == 0 {
+        return Ok(Vec::new());
+    }
+    read_values(reader, endian, count.max(0) as usize)
+}
+
+// std::vector<T> member of a basic type or std::string: version header, size, then the values
+pub fn read_stl_vector<T, R>(reader: &mut R, endian: Endian) -> BinResult<Vec<T>>
+where
+    T: for<'a> BinRead<Args<'a> = ()>,
+    R: Read + Seek,
+{
+    let header = VersionHeader::read(reader, endian)?;
+    let size: i32 = reader.read_type(endian)?;
+    let values = read_values(reader, endian, size.max(0) as usize)?;
+    header.skip_to_end(reader)?;
+    Ok(values)
+}
+
+// std::map<K, V> or std::vector<std::pair<K, V>>: version header and size, then the pairs one by
+// one, or member-wise all the keys followed by all the values
+pub fn read_stl_pairs<K, V, R>(reader: &mut R, endian: Endian) -> BinResult<Vec<(K, V)>>
+where
+    K: for<'a> BinRead<Args<'a> = ()>,
+    V: for<'a> BinRead<Args<'a> = ()>,
+    R: Read + Seek,
+{
+    let header = VersionHeader::read(reader, endian)?;
+    let memberwise = header.version & K_STREAMED_MEMBERWISE != 0;
+    if memberwise {
+        // version of the pair class, followed by its checksum when it has none
+        let pair_version: i16 = reader.read_type(endian)?;
+        if pair_version <= 0 {
+            let _checksum: u32 = reader.read_type(endian)?;
+        }
+    }
+    let size = reader.read_type::<i32>(endian)?.max(0) as usize;
+    let pairs = if memberwise {
+        let keys: Vec<K> = read_values(reader, endian, size)?;
+        let values: Vec<V> = read_values(reader, endian, size)?;
+        keys.into_iter().zip(values).collect()
+    } else {
+        (0..size)
+            .map(|_| {
+                Ok((
+                    K::read_options(reader, endian, ())?,
+                    V::read_options(reader, endian, ())?,
+                ))
+            })
+            .collect::<BinResult<Vec<_>>>()?
+    };
+    header.skip_to_end(reader)?;
+    Ok(pairs)
+}
+
+pub fn read_values<T, R>(reader: &mut R, endian: Endian, count: usize) -> BinResult<Vec<T>>
+where
+    T: for<'a> BinRead<Args<'a> = ()>,
+    R: Read + Seek,
+{
+    (0..count)
+        .map(|_| T::read_options(reader, endian, ()))
+        .collect()
+}
+
+/*
+ * How a Float16_t or Double32_t value is written, from the range of its streamer element
+ * (TBufferFile::ReadFloat16 and ReadDouble32):
+ *   Scaled:    "[xmin,xmax(,nbits)]" in the title, an UInt_t holding (value - xmin) * factor
+ *   Truncated: nbits of mantissa, an UChar_t exponent and an UShort_t sign and mantissa
+ *   Float:     Double32_t without range, written as a float
+ * Float16_t without range is truncated to 12 bits.
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PackedFloat {
+    Float,
+    Scaled { xmin: f64, factor: f64 },
+    Truncated { nbits: u32 },
+}
+
+impl PackedFloat {
+    pub fn float16(element: &TStreamerElementBase) -> Self {
+        Self::from_range(element.f_xmin, element.f_factor, 12)
+    }
+
+    pub fn double32(element: &TStreamerElementBase) -> Self {
+        Self::from_range(element.f_xmin, element.f_factor, 0)
+    }
+
+    // For a member described only by its title, like TStreamerElement::GetRange does
+    pub fn from_title(title: &str, is_float16: bool) -> Self {
+        let (xmin, _, factor) = get_range(title);
+        Self::from_range(xmin, factor, if is_float16 { 12 } else { 0 })
+    }
+
+    fn from_range(xmin: f64, factor: f64, default_nbits: u32) -> Self {
+        if factor != 0.0 {
+            return Self::Scaled { xmin, factor };
+        }
+        match xmin as u32 {
+            0 if default_nbits == 0 => Self::Float,
+            0 => Self::Truncated {
+                nbits: default_nbits,
+            },
+            nbits => Self::Truncated { nbits },
+        }
+    }
+
+    // Bytes of a value: an UInt_t or a float, or the exponent and mantissa of a truncated value
+    pub fn size(&self) -> usize {
+        match self {
+            Self::Float | Self::Scaled { .. } => 4,
+            Self::Truncated { .. } => 3,
+        }
+    }
+
+    pub fn read<R: Read + Seek>(&self, reader: &mut R, endian: Endian) -> BinResult<f64> {
+        match *self {
+            Self::Float => Ok(reader.read_type::<f32>(endian)? as f64),
+            Self::Scaled { xmin, factor } => {
+                Ok(reader.read_type::<u32>(endian)? as f64 / factor + xmin)
+            }
+            Self::Truncated { nbits } => {
+                let exponent: u8 = reader.read_type(endian)?;
+                let mantissa: u16 = reader.read_type(endian)?;
+                let mut bits = (exponent as u32) << 23;
+                bits |= (mantissa as u32 & ((1 << (nbits + 1)) - 1)) << (23 - nbits);
+                let value = f32::from_bits(bits);
+                if (1 << (nbits + 1)) & mantissa as u32 != 0 {
+                    Ok(-value as f64)
+                } else {
+                    Ok(value as f64)
+                }
+            }
+        }
+    }
+
+    pub fn read_values<R: Read + Seek>(
+        &self,
+        reader: &mut R,
+        endian: Endian,
+        count: usize,
+    ) -> BinResult<Vec<f64>> {
+        (0..count).map(|_| self.read(reader, endian)).collect()
+    }
+
+    // `Double32_t* fArray; //[fN]`: a flag byte, then `count` packed values
+    pub fn read_pointer<R: Read + Seek>(
+        &self,
+        reader: &mut R,
+        endian: Endian,
+        count: i64,
+    ) -> BinResult<Vec<f64>> {
+        let is_array: u8 = reader.read_type(endian)?;
+        if is_array == 0 {
+            return Ok(Vec::new());
+        }
+        self.read_values(reader, endian, count.max(0) as usize)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_version_header_with_and_without_byte_count() {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&(K_HAS_BYTECOUNT | 6).to_be_bytes());
+        bytes.extend_from_slice(&3u16.to_be_bytes());
+        bytes.extend_from_slice(&[0xAA; 4]);
+        bytes.extend_from_slice(&2u16.to_be_bytes());
+        bytes.extend_from_slice(&1i32.to_be_bytes());
+        let mut cursor = Cursor::new(bytes);
+
+        let header = VersionHeader::read(&mut cursor, Endian::Big).expect("Failed to read header");
+        assert_eq!(header.version, 3);
+        assert_eq!(header.end(), Some(10));
+        header.skip_to_end(&mut cursor).expect("Failed to skip");
+
+        let header = VersionHeader::read(&mut cursor, Endian::Big).expect("Failed to read header");
+        assert_eq!(header.version, 2);
+        assert_eq!(header.end(), None);
+    }
+
+    #[test]
+    fn test_read_stl_vector_and_basic_pointer() {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&(K_HAS_BYTECOUNT | 14).to_be_bytes());
+        bytes.extend_from_slice(&9u16.to_be_bytes());
+        bytes.extend_from_slice(&2i32.to_be_bytes());
+        bytes.extend_from_slice(&1.5f32.to_be_bytes());
+        bytes.extend_from_slice(&(-2.0f32).to_be_bytes());
+        bytes.push(1);
+        bytes.extend_from_slice(&7i16.to_be_bytes());
+        bytes.extend_from_slice(&8i16.to_be_bytes());
+        bytes.push(0);
+        let mut cursor = Cursor::new(bytes);
+
+        let vector: Vec<f32> = read_stl_vector(&mut cursor, Endian::Big).expect("vector");
+        assert_eq!(vector, [1.5, -2.0]);
+        let array: Vec<i16> = read_basic_pointer(&mut cursor, Endian::Big, 2).expect("array");
+        assert_eq!(array, [7, 8]);
+        let empty: Vec<i16> = read_basic_pointer(&mut cursor, Endian::Big, 2).expect("empty");
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn test_read_packed_floats() {
+        let mut bytes = Vec::new();
+        // Double32_t without range: a float
+        bytes.extend_from_slice(&1.25f32.to_be_bytes());
+        // [0,10,16]: (3.5 - 0) * 65536 / 10
+        bytes.extend_from_slice(&22937u32.to_be_bytes());
+        // 12 bits of mantissa for -1.5: exponent 127, sign bit 13, mantissa 0x800
+        bytes.push(127);
+        bytes.extend_from_slice(&((1u16 << 13) | 0x800).to_be_bytes());
+        let mut cursor = Cursor::new(bytes);
+
+        let plain = PackedFloat::from_title("no range", false);
+        assert_eq!(plain, PackedFloat::Float);
+        assert_eq!(plain.read(&mut cursor, Endian::Big).unwrap(), 1.25);
+
+        let scaled = PackedFloat::from_title("[0,10,16] energy", false);
+        assert_eq!(
+            scaled,
+            PackedFloat::Scaled {
+                xmin: 0.0,
+                factor: 6553.6
+            }
+        );
+        let value = scaled.read(&mut cursor, Endian::Big).unwrap();
+        assert!((value - 3.5).abs() < 10.0 / 65536.0);
+
+        let truncated = PackedFloat::from_title("Float16_t without range", true);
+        assert_eq!(truncated, PackedFloat::Truncated { nbits: 12 });
+        assert_eq!(truncated.read(&mut cursor, Endian::Big).unwrap(), -1.5);
+    }
+}
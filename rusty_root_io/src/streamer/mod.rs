@@ -1,4 +1,10 @@
+pub mod builtin;
+pub mod codegen;
+pub mod consistency;
+pub mod member;
 pub mod registry;
+pub mod root_streamer;
+pub mod schema_reader;
 pub mod streamerinfo;
 pub mod tstreamer_element;
 pub mod tstreamerinfo;
@@ -1,6 +1,9 @@
+use crate::core::constant::*;
+use crate::streamer::member::PackedFloat;
+use crate::streamer::tstreamer_element::TStreamerElementBase;
 use std::sync::Arc;
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct ClassSchema {
     pub name: String,
     pub version: u16,
@@ -8,12 +11,12 @@ pub struct ClassSchema {
     pub fields: Vec<FieldSchema>, // ordered, base class fields first
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct FieldSchema {
     pub name: String,
     pub kind: FieldKind,
 }
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum FieldKind {
     Bool,
     I8,
@@ -26,10 +29,147 @@ pub enum FieldKind {
     U64,
     F32,
     F64,
+    Float16(PackedFloat),  // Float16_t, packed on disk
+    Double32(PackedFloat), // Double32_t, written as float or packed on disk
+    CharStar,
     TString,
     Base(Arc<ClassSchema>),   // recurse into base class fields
     Object(Arc<ClassSchema>), // embedded object
     ObjectPointer(Arc<ClassSchema>),
     FixedArray { elem: Box<FieldKind>, count: usize },
+    BasicPointer { elem: Box<FieldKind>, count: String }, // `T* fArray; //[fN]`
+    CountedArray { elem: Box<FieldKind>, count: String }, // `TClass* fArray; //[fN]`, a TStreamerLoop
     StlVector(Box<FieldKind>),
+    Unknown(String), // members without a schema representation, by type name
+}
+
+impl FieldKind {
+    // Kind of a basic TStreamerElement::fType, without the kOffsetL/kOffsetP offsets
+    pub fn from_basic_type(f_type: u32) -> Option<Self> {
+        Some(match f_type {
+            K_CHAR | K_LEGACY_CHAR => Self::I8,
+            K_SHORT => Self::I16,
+            K_INT | K_COUNTER => Self::I32,
+            K_LONG | K_LONG64 => Self::I64,
+            K_FLOAT => Self::F32,
+            K_DOUBLE => Self::F64,
+            K_DOUBLE32 => Self::Double32(PackedFloat::Float),
+            K_FLOAT16 => Self::Float16(PackedFloat::Truncated { nbits: 12 }),
+            K_UCHAR => Self::U8,
+            K_USHORT => Self::U16,
+            K_UINT | K_BITS => Self::U32,
+            K_ULONG | K_ULONG64 => Self::U64,
+            K_BOOL => Self::Bool,
+            K_CHAR_STAR => Self::CharStar,
+            _ => return None,
+        })
+    }
+
+    // Kind of a basic streamer element, a Float16_t or Double32_t packed after the range of its
+    // title like TStreamerElement::GetRange
+    pub fn from_element(element: &TStreamerElementBase) -> Option<Self> {
+        Some(match Self::from_basic_type(element.basic_type())? {
+            Self::Float16(_) => Self::Float16(PackedFloat::float16(element)),
+            Self::Double32(_) => Self::Double32(PackedFloat::double32(element)),
+            kind => kind,
+        })
+    }
+
+    // The kind with the packing of a range, "[xmin,xmax(,nbits)]", for Float16_t and Double32_t
+    // members and arrays of them
+    pub fn with_range(self, range: &str) -> Self {
+        match self {
+            Self::Float16(_) => Self::Float16(PackedFloat::from_title(range, true)),
+            Self::Double32(_) => Self::Double32(PackedFloat::from_title(range, false)),
+            Self::FixedArray { elem, count } => Self::FixedArray {
+                elem: Box::new(elem.with_range(range)),
+                count,
+            },
+            kind => kind,
+        }
+    }
+
+    // Kind of a basic C++ type name, as found in the element type of std::vector
+    pub fn from_cpp_type(type_name: &str) -> Option<Self> {
+        Some(match type_name.trim() {
+            "char" | "Char_t" => Self::I8,
+            "unsigned char" | "UChar_t" => Self::U8,
+            "short" | "Short_t" => Self::I16,
+            "unsigned short" | "UShort_t" => Self::U16,
+            "int" | "Int_t" => Self::I32,
+            "unsigned int" | "UInt_t" => Self::U32,
+            "long" | "Long_t" | "long long" | "Long64_t" => Self::I64,
+            "unsigned long" | "ULong_t" | "unsigned long long" | "ULong64_t" => Self::U64,
+            "float" | "Float_t" => Self::F32,
+            "double" | "Double_t" => Self::F64,
+            "Double32_t" => Self::Double32(PackedFloat::Float),
+            "Float16_t" => Self::Float16(PackedFloat::Truncated { nbits: 12 }),
+            "bool" | "Bool_t" => Self::Bool,
+            "string" | "std::string" | "TString" => Self::TString,
+            _ => return None,
+        })
+    }
+
+    // Kind of a basic C++ type name or of a std::vector of one, as in the type name of a streamer
+    // element
+    pub fn from_type_name(type_name: &str) -> Option<Self> {
+        let type_name = type_name.trim();
+        match type_name
+            .strip_prefix("vector<")
+            .or_else(|| type_name.strip_prefix("std::vector<"))
+            .and_then(|inner| inner.strip_suffix('>'))
+        {
+            Some(inner) => Some(Self::StlVector(Box::new(Self::from_cpp_type(inner)?))),
+            None => Self::from_cpp_type(type_name),
+        }
+    }
+
+    // Rust type of a basic value or string of the kind, used by the code generator. Double32_t
+    // and Float16_t are unpacked to the type of their C++ typedef, char* is read by read_char_star
+    pub fn rust_type(&self) -> Option<&'static str> {
+        Some(match self {
+            Self::Bool => "bool",
+            Self::I8 => "i8",
+            Self::I16 => "i16",
+            Self::I32 => "i32",
+            Self::I64 => "i64",
+            Self::U8 => "u8",
+            Self::U16 => "u16",
+            Self::U32 => "u32",
+            Self::U64 => "u64",
+            Self::F32 | Self::Float16(_) => "f32",
+            Self::F64 | Self::Double32(_) => "f64",
+            Self::TString => "TString",
+            _ => return None,
+        })
+    }
+
+    // C++ spelling of the kind, used in reports
+    pub fn type_name(&self) -> String {
+        match self {
+            Self::Bool => "bool".to_string(),
+            Self::I8 => "char".to_string(),
+            Self::I16 => "short".to_string(),
+            Self::I32 => "int".to_string(),
+            Self::I64 => "Long64_t".to_string(),
+            Self::U8 => "unsigned char".to_string(),
+            Self::U16 => "unsigned short".to_string(),
+            Self::U32 => "unsigned int".to_string(),
+            Self::U64 => "ULong64_t".to_string(),
+            Self::F32 => "float".to_string(),
+            Self::F64 => "double".to_string(),
+            Self::Float16(_) => "Float16_t".to_string(),
+            Self::Double32(_) => "Double32_t".to_string(),
+            Self::CharStar => "char*".to_string(),
+            Self::TString => "TString".to_string(),
+            Self::Base(class) | Self::Object(class) => class.name.clone(),
+            Self::ObjectPointer(class) => format!("{}*", class.name),
+            Self::FixedArray { elem, count } => format!("{}[{}]", elem.type_name(), count),
+            Self::BasicPointer { elem, count } | Self::CountedArray { elem, count } => {
+                format!("{}*[{}]", elem.type_name(), count)
+            }
+            Self::StlVector(elem) => format!("vector<{}>", elem.type_name()),
+            Self::Unknown(type_name) => type_name.clone(),
+        }
+    }
 }
@@ -0,0 +1,440 @@
+use crate::core::read_context::ReadContext;
+use crate::objects::tobject::TObject;
+use crate::objects::tstring::TString;
+use crate::streamer::member::{PackedFloat, VersionHeader};
+use crate::streamer::registry::{ClassSchema, FieldKind, FieldSchema};
+use binrw::io::{Read, Seek};
+use binrw::{BinRead, BinReaderExt, BinResult, Endian};
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::sync::Arc;
+
+/*
+ * Traits implemented by #[derive(RootStreamer)] (crate rusty_root_derive).
+ *
+ * A derived struct is read like a class streamed member-wise by TStreamerInfo: byte count and
+ * version, then every data member in declaration order, bases first as ROOT writes them.
+ *
+ *     #[derive(RootStreamer)]
+ *     #[root(class = "TNamed", version = 1, checksum = 0xdfb74a3c)]
+ *     pub struct TNamed {
+ *         #[root(byte_count)]
+ *         pub byte_count: u32,
+ *         #[root(version)]
+ *         pub version: u16,
+ *         #[root(base)]
+ *         pub tobject: TObject,
+ *         pub name: TString,   // member fName, #[root(name = "...")] to name it otherwise
+ *         pub title: TString,
+ *     }
+ *
+ * Members can be any StreamerMember: basic types, bool, Float16, Double32, TString, TObject, other
+ * derived classes, fixed arrays `[T; N]`, std::vector as `Vec<T>` and pointers as `Option<Arc<T>>`.
+ *
+ * Collections are not derived. TList, TObjArray and the other classes of has_custom_streamer in
+ * codegen.rs have a hand-written ROOT Streamer: their elements are not data members, but a count
+ * followed by objects with class tags (and an option string per element in a TList), which their
+ * streamer info does not describe. They keep their hand-written readers, generic over the element.
+ */
+
+// A class with a member-wise streamer and the schema it is written with.
+pub trait RootStreamer {
+    const CLASS_NAME: &'static str;
+    const CLASS_VERSION: u16;
+    const CHECKSUM: u32;
+
+    fn class_schema() -> Arc<ClassSchema>;
+}
+
+// A data member of a streamed class: how it is read and how it appears in the ClassSchema.
+pub trait StreamerMember: Sized {
+    fn read_member<R: Read + Seek>(
+        reader: &mut R,
+        endian: Endian,
+        ctx: &ReadContext,
+    ) -> BinResult<Self>;
+
+    fn field_kind() -> FieldKind;
+}
+
+macro_rules! basic_member {
+    ($($rust_type:ty => $kind:ident),* $(,)?) => {
+        $(
+            impl StreamerMember for $rust_type {
+                fn read_member<R: Read + Seek>(
+                    reader: &mut R,
+                    endian: Endian,
+                    _ctx: &ReadContext,
+                ) -> BinResult<Self> {
+                    reader.read_type(endian)
+                }
+
+                fn field_kind() -> FieldKind {
+                    FieldKind::$kind
+                }
+            }
+        )*
+    };
+}
+
+basic_member!(
+    i8 => I8,
+    i16 => I16,
+    i32 => I32,
+    i64 => I64,
+    u8 => U8,
+    u16 => U16,
+    u32 => U32,
+    u64 => U64,
+    f32 => F32,
+    f64 => F64,
+);
+
+impl StreamerMember for bool {
+    fn read_member<R: Read + Seek>(
+        reader: &mut R,
+        endian: Endian,
+        _ctx: &ReadContext,
+    ) -> BinResult<Self> {
+        Ok(reader.read_type::<u8>(endian)? != 0)
+    }
+
+    fn field_kind() -> FieldKind {
+        FieldKind::Bool
+    }
+}
+
+impl StreamerMember for TString {
+    fn read_member<R: Read + Seek>(
+        reader: &mut R,
+        endian: Endian,
+        _ctx: &ReadContext,
+    ) -> BinResult<Self> {
+        TString::read_options(reader, endian, ())
+    }
+
+    fn field_kind() -> FieldKind {
+        FieldKind::TString
+    }
+}
+
+// TObject has its own streamer: version, fUniqueID and fBits, no byte count
+impl RootStreamer for TObject {
+    const CLASS_NAME: &'static str = "TObject";
+    const CLASS_VERSION: u16 = 1;
+    const CHECKSUM: u32 = 0x901bc02d;
+
+    fn class_schema() -> Arc<ClassSchema> {
+        class_schema::<Self>(|| {
+            vec![
+                FieldSchema {
+                    name: "fUniqueID".to_string(),
+                    kind: FieldKind::U32,
+                },
+                FieldSchema {
+                    name: "fBits".to_string(),
+                    kind: FieldKind::U32,
+                },
+            ]
+        })
+    }
+}
+
+impl StreamerMember for TObject {
+    fn read_member<R: Read + Seek>(
+        reader: &mut R,
+        endian: Endian,
+        _ctx: &ReadContext,
+    ) -> BinResult<Self> {
+        TObject::read_options(reader, endian, ())
+    }
+
+    fn field_kind() -> FieldKind {
+        FieldKind::Object(Self::class_schema())
+    }
+}
+
+/*
+ * Float16_t and Double32_t members, written packed (see PackedFloat). As members they use the
+ * packing of a title without range, a range is given with #[root(range = "[xmin,xmax,nbits]")].
+ */
+#[derive(Debug, Default, Clone, Copy, PartialEq, PartialOrd)]
+pub struct Float16(pub f32);
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, PartialOrd)]
+pub struct Double32(pub f64);
+
+impl Float16 {
+    pub fn read_with_range<R: Read + Seek>(
+        reader: &mut R,
+        endian: Endian,
+        range: &str,
+    ) -> BinResult<Self> {
+        let packed = PackedFloat::from_title(range, true);
+        Ok(Self(packed.read(reader, endian)? as f32))
+    }
+}
+
+impl Double32 {
+    pub fn read_with_range<R: Read + Seek>(
+        reader: &mut R,
+        endian: Endian,
+        range: &str,
+    ) -> BinResult<Self> {
+        let packed = PackedFloat::from_title(range, false);
+        Ok(Self(packed.read(reader, endian)?))
+    }
+}
+
+impl StreamerMember for Float16 {
+    fn read_member<R: Read + Seek>(
+        reader: &mut R,
+        endian: Endian,
+        _ctx: &ReadContext,
+    ) -> BinResult<Self> {
+        Self::read_with_range(reader, endian, "")
+    }
+
+    fn field_kind() -> FieldKind {
+        FieldKind::Float16(PackedFloat::from_title("", true))
+    }
+}
+
+impl StreamerMember for Double32 {
+    fn read_member<R: Read + Seek>(
+        reader: &mut R,
+        endian: Endian,
+        _ctx: &ReadContext,
+    ) -> BinResult<Self> {
+        Self::read_with_range(reader, endian, "")
+    }
+
+    fn field_kind() -> FieldKind {
+        FieldKind::Double32(PackedFloat::from_title("", false))
+    }
+}
+
+// `T fArray[N]`
+impl<T: StreamerMember, const N: usize> StreamerMember for [T; N] {
+    fn read_member<R: Read + Seek>(
+        reader: &mut R,
+        endian: Endian,
+        ctx: &ReadContext,
+    ) -> BinResult<Self> {
+        let values = (0..N)
+            .map(|_| T::read_member(reader, endian, ctx))
+            .collect::<BinResult<Vec<T>>>()?;
+        Ok(values
+            .try_into()
+            .unwrap_or_else(|_| unreachable!("read exactly {} values", N)))
+    }
+
+    fn field_kind() -> FieldKind {
+        FieldKind::FixedArray {
+            elem: Box::new(T::field_kind()),
+            count: N,
+        }
+    }
+}
+
+// std::vector<T>: version header, size, then the elements
+impl<T: StreamerMember> StreamerMember for Vec<T> {
+    fn read_member<R: Read + Seek>(
+        reader: &mut R,
+        endian: Endian,
+        ctx: &ReadContext,
+    ) -> BinResult<Self> {
+        let header = VersionHeader::read(reader, endian)?;
+        let size: i32 = reader.read_type(endian)?;
+        let values = (0..size.max(0))
+            .map(|_| T::read_member(reader, endian, ctx))
+            .collect::<BinResult<Vec<T>>>()?;
+        header.skip_to_end(reader)?;
+        Ok(values)
+    }
+
+    fn field_kind() -> FieldKind {
+        FieldKind::StlVector(Box::new(T::field_kind()))
+    }
+}
+
+// `T* fObject`, written through TBufferFile::WriteObjectAny
+impl<T> StreamerMember for Option<Arc<T>>
+where
+    T: RootStreamer + for<'a> BinRead<Args<'a> = (ReadContext,)> + Send + Sync + 'static,
+{
+    fn read_member<R: Read + Seek>(
+        reader: &mut R,
+        endian: Endian,
+        ctx: &ReadContext,
+    ) -> BinResult<Self> {
+        ctx.read_streamed_object(reader, endian)
+    }
+
+    fn field_kind() -> FieldKind {
+        FieldKind::ObjectPointer(T::class_schema())
+    }
+}
+
+thread_local! {
+    // schemas being built on this thread, a class pointing to itself refers to a schema without
+    // fields instead of recursing
+    static BUILDING: RefCell<HashSet<&'static str>> = RefCell::new(HashSet::new());
+}
+
+// Build the schema of T from its fields, used by the derived RootStreamer::class_schema
+pub fn class_schema<T: RootStreamer>(
+    fields: impl FnOnce() -> Vec<FieldSchema>,
+) -> Arc<ClassSchema> {
+    let recursive = BUILDING.with(|building| !building.borrow_mut().insert(T::CLASS_NAME));
+    let fields = if recursive {
+        Vec::new()
+    } else {
+        let fields = fields();
+        BUILDING.with(|building| building.borrow_mut().remove(T::CLASS_NAME));
+        fields
+    };
+    Arc::new(ClassSchema {
+        name: T::CLASS_NAME.to_string(),
+        version: T::CLASS_VERSION,
+        checksum: T::CHECKSUM,
+        fields,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::constant::K_HAS_BYTECOUNT;
+    use crate::objects::tnamed::TNamed;
+    use crate::RootStreamer;
+    use std::io::Cursor;
+
+    #[derive(Debug, RootStreamer)]
+    #[root(class = "TEvent", version = 2, checksum = 0x1234)]
+    struct TEvent {
+        #[root(version)]
+        version: u16,
+        #[root(base)]
+        tnamed: TNamed,
+        f_run: i32,
+        #[root(name = "fIsMC")]
+        is_mc: bool,
+        f_pos: [f32; 3],
+        f_weights: Vec<f64>,
+        f_parent: Option<Arc<TEvent>>,
+        #[root(skip)]
+        cache: Vec<u8>,
+    }
+
+    #[derive(Debug, RootStreamer)]
+    #[root(class = "TTrack", version = 1)]
+    struct TTrack {
+        #[root(range = "[0,10,16]")]
+        f_pt: Float16,
+        f_eta: Double32,
+    }
+
+    fn with_byte_count(version: u16, body: &[u8]) -> Vec<u8> {
+        let mut bytes = (K_HAS_BYTECOUNT | (body.len() as u32 + 2))
+            .to_be_bytes()
+            .to_vec();
+        bytes.extend_from_slice(&version.to_be_bytes());
+        bytes.extend_from_slice(body);
+        bytes
+    }
+
+    #[test]
+    fn test_derived_schema() {
+        let schema = TEvent::class_schema();
+        assert_eq!(schema.name, "TEvent");
+        assert_eq!(schema.version, 2);
+        assert_eq!(schema.checksum, 0x1234);
+        let names: Vec<&str> = schema.fields.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(
+            names,
+            ["TNamed", "fRun", "fIsMC", "fPos", "fWeights", "fParent"]
+        );
+        assert_eq!(
+            schema.fields[0].kind,
+            FieldKind::Base(TNamed::class_schema())
+        );
+        assert_eq!(
+            schema.fields[3].kind,
+            FieldKind::FixedArray {
+                elem: Box::new(FieldKind::F32),
+                count: 3
+            }
+        );
+        // the pointer to its own class does not recurse
+        match &schema.fields[5].kind {
+            FieldKind::ObjectPointer(parent) => {
+                assert_eq!(parent.name, "TEvent");
+                assert!(parent.fields.is_empty());
+            }
+            kind => panic!("unexpected kind {:?}", kind),
+        }
+
+        let tnamed = TNamed::class_schema();
+        let names: Vec<&str> = tnamed.fields.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, ["TObject", "fName", "fTitle"]);
+    }
+
+    #[test]
+    fn test_read_derived() {
+        let mut tnamed = Vec::new();
+        tnamed.extend_from_slice(&1u16.to_be_bytes());
+        tnamed.extend_from_slice(&0u32.to_be_bytes());
+        tnamed.extend_from_slice(&0x0300_0000u32.to_be_bytes());
+        tnamed.extend_from_slice(b"\x03evt\x05title");
+
+        let mut weights = 2i32.to_be_bytes().to_vec();
+        weights.extend_from_slice(&0.5f64.to_be_bytes());
+        weights.extend_from_slice(&2.0f64.to_be_bytes());
+
+        let mut body = with_byte_count(1, &tnamed);
+        body.extend_from_slice(&7i32.to_be_bytes());
+        body.push(1);
+        for value in [1.0f32, 2.0, 3.0] {
+            body.extend_from_slice(&value.to_be_bytes());
+        }
+        body.extend_from_slice(&with_byte_count(9, &weights));
+        body.extend_from_slice(&0u32.to_be_bytes());
+        let bytes = with_byte_count(2, &body);
+
+        let event = TEvent::read_be(&mut Cursor::new(bytes)).expect("Failed to read TEvent");
+        assert_eq!(event.version, 2);
+        assert_eq!(event.tnamed.name, "evt");
+        assert_eq!(event.tnamed.title, "title");
+        assert_eq!(event.f_run, 7);
+        assert!(event.is_mc);
+        assert_eq!(event.f_pos, [1.0, 2.0, 3.0]);
+        assert_eq!(event.f_weights, [0.5, 2.0]);
+        assert!(event.f_parent.is_none());
+        assert!(event.cache.is_empty());
+    }
+
+    #[test]
+    fn test_read_packed_members() {
+        let schema = TTrack::class_schema();
+        assert_eq!(
+            schema.fields[0].kind,
+            FieldKind::Float16(PackedFloat::Scaled {
+                xmin: 0.0,
+                factor: 6553.6
+            })
+        );
+        assert_eq!(
+            schema.fields[1].kind,
+            FieldKind::Double32(PackedFloat::Float)
+        );
+
+        // fPt scaled to 16 bits over [0, 10], fEta without range is written as a float
+        let mut body = 32768u32.to_be_bytes().to_vec();
+        body.extend_from_slice(&2.5f32.to_be_bytes());
+        let bytes = with_byte_count(1, &body);
+        let track = TTrack::read_be(&mut Cursor::new(bytes)).expect("Failed to read TTrack");
+        assert_eq!(track.f_pt, Float16(5.0));
+        assert_eq!(track.f_eta, Double32(2.5));
+    }
+}
@@ -0,0 +1,466 @@
+use crate::core::read_context::ReadContext;
+use crate::objects::any_object::AnyObject;
+use crate::objects::tlist::TList;
+use crate::objects::tobject::TObject;
+use crate::objects::tstring::TString;
+use crate::streamer::member::{read_char_star, VersionHeader};
+use crate::streamer::registry::{ClassSchema, FieldKind, FieldSchema};
+use binrw::io::{Read, Seek, SeekFrom};
+use binrw::{BinRead, BinReaderExt, BinResult, Endian};
+use std::collections::HashMap;
+use std::io;
+use std::sync::Arc;
+
+/*
+ * Reading of a streamed object from its ClassSchema, the way TStreamerInfo::ReadBuffer walks the
+ * streamer elements. Used for classes whose layout changes from one ROOT version to the next and
+ * is always in the streamer info of the files that contain them (TPad, TCanvas, TLegend).
+ *
+ * The caller decodes the members it is interested in through `visit`, which returns true when it
+ * read the member. Every other member is stepped over, objects through their byte count, and
+ * members of base classes are visited like the members of the class itself.
+ */
+pub type MemberVisitor<'v, R> = dyn FnMut(&FieldSchema, &mut R) -> BinResult<bool> + 'v;
+
+// Read an object from its version header, returning the header
+pub fn read_with_schema<R: Read + Seek>(
+    reader: &mut R,
+    endian: Endian,
+    ctx: &ReadContext,
+    schema: &ClassSchema,
+    visit: &mut MemberVisitor<R>,
+) -> BinResult<VersionHeader> {
+    let header = VersionHeader::read(reader, endian)?;
+    let mut counters = HashMap::new();
+    for field in &schema.fields {
+        if visit(field, reader)? {
+            continue;
+        }
+        skip_member(reader, endian, ctx, field, &mut counters, visit)?;
+    }
+    header.skip_to_end(reader)?;
+    Ok(header)
+}
+
+// Schema of a class read by read_with_schema, from the registry of the context
+pub fn required_schema(ctx: &ReadContext, class: &str) -> BinResult<Arc<ClassSchema>> {
+    ctx.schema(class).ok_or_else(|| {
+        invalid_data(format!(
+            "{} is read from the streamer info of its file, which the read context lacks",
+            class
+        ))
+    })
+}
+
+// A TList member, streamed in place (`//->`) or through a pointer
+pub fn read_list_member<R: Read + Seek>(
+    reader: &mut R,
+    endian: Endian,
+    ctx: &ReadContext,
+    kind: &FieldKind,
+) -> BinResult<Option<Arc<TList<AnyObject>>>> {
+    match kind {
+        FieldKind::Object(_) => Ok(Some(Arc::new(TList::read_options(
+            reader,
+            endian,
+            (ctx.clone(),),
+        )?))),
+        _ => ctx.read_streamed_object(reader, endian),
+    }
+}
+
+fn skip_member<R: Read + Seek>(
+    reader: &mut R,
+    endian: Endian,
+    ctx: &ReadContext,
+    field: &FieldSchema,
+    counters: &mut HashMap<String, i64>,
+    visit: &mut MemberVisitor<R>,
+) -> BinResult<()> {
+    match &field.kind {
+        FieldKind::Base(class) if class.name == "TObject" => {
+            TObject::read_options(reader, endian, ())?;
+        }
+        // a base class of version 0 (TAttBBox2D) is not streamed, nor one missing from the info
+        FieldKind::Base(class) if class.version == 0 => {}
+        FieldKind::Base(class) if class.fields.is_empty() => {
+            skip_opaque_base(reader, endian, class)?
+        }
+        FieldKind::Base(class) => {
+            read_with_schema(reader, endian, ctx, class, visit)?;
+        }
+        kind => skip_value(reader, endian, ctx, &field.name, kind, counters)?,
+    }
+    Ok(())
+}
+
+fn skip_value<R: Read + Seek>(
+    reader: &mut R,
+    endian: Endian,
+    ctx: &ReadContext,
+    name: &str,
+    kind: &FieldKind,
+    counters: &mut HashMap<String, i64>,
+) -> BinResult<()> {
+    let counter = match kind {
+        FieldKind::Bool | FieldKind::I8 | FieldKind::U8 => reader.read_type::<i8>(endian)? as i64,
+        FieldKind::I16 => reader.read_type::<i16>(endian)? as i64,
+        FieldKind::U16 => reader.read_type::<u16>(endian)? as i64,
+        FieldKind::I32 => reader.read_type::<i32>(endian)? as i64,
+        FieldKind::U32 => reader.read_type::<u32>(endian)? as i64,
+        FieldKind::I64 | FieldKind::U64 => reader.read_type::<i64>(endian)?,
+        FieldKind::F32 => return skip_bytes(reader, 4),
+        FieldKind::F64 => return skip_bytes(reader, 8),
+        FieldKind::Float16(packed) | FieldKind::Double32(packed) => {
+            return skip_bytes(reader, packed.size() as i64)
+        }
+        FieldKind::TString => {
+            TString::read_options(reader, endian, ())?;
+            return Ok(());
+        }
+        FieldKind::CharStar => {
+            read_char_star(reader, endian)?;
+            return Ok(());
+        }
+        FieldKind::FixedArray { elem, count } => {
+            for _ in 0..*count {
+                skip_value(reader, endian, ctx, name, elem, counters)?;
+            }
+            return Ok(());
+        }
+        FieldKind::BasicPointer { elem, count } => {
+            let is_array: u8 = reader.read_type(endian)?;
+            if is_array == 0 {
+                return Ok(());
+            }
+            let n = *counters.get(count).ok_or_else(|| {
+                invalid_data(format!(
+                    "counter {} of {} is not read before it",
+                    count, name
+                ))
+            })?;
+            for _ in 0..n.max(0) {
+                skip_value(reader, endian, ctx, name, elem, counters)?;
+            }
+            return Ok(());
+        }
+        FieldKind::Object(class) if class.name == "TObject" => {
+            TObject::read_options(reader, endian, ())?;
+            return Ok(());
+        }
+        // the objects of a TStreamerLoop are wrapped in one byte count
+        FieldKind::Object(_)
+        | FieldKind::Base(_)
+        | FieldKind::StlVector(_)
+        | FieldKind::CountedArray { .. } => return skip_object(reader, endian),
+        // objects behind a pointer are read, later members may refer back to them
+        FieldKind::ObjectPointer(_) => {
+            ctx.read_object::<AnyObject, _>(reader, endian)?;
+            return Ok(());
+        }
+        FieldKind::Unknown(type_name) => {
+            return skip_object(reader, endian).map_err(|_| {
+                invalid_data(format!("member {} ({}) cannot be skipped", name, type_name))
+            })
+        }
+    };
+    counters.insert(name.to_string(), counter);
+    Ok(())
+}
+
+fn skip_object<R: Read + Seek>(reader: &mut R, endian: Endian) -> BinResult<()> {
+    let header = VersionHeader::read(reader, endian)?;
+    if header.end().is_none() {
+        return Err(invalid_data(format!(
+            "object at {:#X} has no byte count and cannot be skipped",
+            header.start
+        )));
+    }
+    header.skip_to_end(reader)
+}
+
+// A base class with a hand-written streamer is skipped by its byte count, unless its version does
+// not follow (TQObject streams nothing)
+fn skip_opaque_base<R: Read + Seek>(
+    reader: &mut R,
+    endian: Endian,
+    class: &ClassSchema,
+) -> BinResult<()> {
+    let start = reader.stream_position()?;
+    let header = VersionHeader::read(reader, endian)?;
+    if header.end().is_some() && header.version == class.version {
+        return header.skip_to_end(reader);
+    }
+    reader.seek(SeekFrom::Start(start))?;
+    Ok(())
+}
+
+fn skip_bytes<R: Seek>(reader: &mut R, n: i64) -> BinResult<()> {
+    reader.seek(SeekFrom::Current(n))?;
+    Ok(())
+}
+
+fn invalid_data(message: String) -> binrw::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::constant::{
+        K_COUNTER, K_DOUBLE32, K_FLOAT, K_FLOAT16, K_HAS_BYTECOUNT, K_INT, K_STREAM_LOOP,
+    };
+    use crate::core::utils::ClassInfo;
+    use crate::objects::tlist::TListElement;
+    use crate::objects::tnamed::TNamed;
+    use crate::streamer::member::PackedFloat;
+    use crate::streamer::streamerinfo::{StreamerInfo, StreamerRegistry};
+    use crate::streamer::tstreamer_element::{
+        get_range, TStreamerElement, TStreamerElementBase, TStreamerType, K_HAS_RANGE,
+    };
+    use crate::streamer::tstreamerinfo::TStreamerInfo;
+    use std::io::Cursor;
+
+    // A basic member as read from the streamer info, with the range of its title
+    fn basic_element(name: &str, title: &str, f_type: u32, type_name: &str) -> TStreamerElement {
+        let mut tnamed = TNamed {
+            name: TString::new(name),
+            title: TString::new(title),
+            ..Default::default()
+        };
+        let (f_xmin, f_xmax, f_factor) = get_range(title);
+        if title.starts_with('[') {
+            tnamed.tobject.f_bits |= K_HAS_RANGE;
+        }
+        TStreamerElement {
+            byte_count: 0,
+            class_info: ClassInfo::NewClass(String::from("TStreamerBasicType")),
+            remaining_bytes: 0,
+            version: 2,
+            tstreamer_element_base: TStreamerElementBase {
+                version: 4,
+                tnamed,
+                f_type,
+                f_size: 4,
+                type_name: TString::new(type_name),
+                f_xmin,
+                f_xmax,
+                f_factor,
+                ..Default::default()
+            },
+            tstreamer_type: TStreamerType::TStreamerBasicType,
+        }
+    }
+
+    // A TStreamerLoop member, `TClass* fArray; //[fN]`
+    fn loop_element(name: &str, title: &str, type_name: &str, count: &str) -> TStreamerElement {
+        let mut element = basic_element(name, title, K_STREAM_LOOP, type_name);
+        element.class_info = ClassInfo::NewClass(String::from("TStreamerLoop"));
+        element.tstreamer_type = TStreamerType::TStreamerLoop {
+            count_version: 1,
+            name: TString::new(count),
+            class_name: TString::new("TEvent"),
+        };
+        element
+    }
+
+    fn register(classes: Vec<(&str, Vec<TStreamerElement>)>) -> StreamerRegistry {
+        let mut streamer_info = StreamerInfo::default();
+        for (name, elements) in classes {
+            let mut info = TStreamerInfo {
+                tnamed: TNamed {
+                    name: TString::new(name),
+                    ..Default::default()
+                },
+                f_class_version: 1,
+                ..Default::default()
+            };
+            for element in elements {
+                info.tobjarray.objects.push(Some(Arc::new(element)));
+            }
+            streamer_info.tlist.objects.push(TListElement {
+                object: Arc::new(info),
+                option: TString::default(),
+            });
+        }
+        let mut registry = StreamerRegistry::new();
+        registry.register_from_streamerinfo(&streamer_info);
+        registry
+    }
+
+    // TPacked { Double32_t fScaled; //[0,10,16]  Double32_t fBits; //[0,0,10]
+    //           Float16_t fRanged; //[-1,1,8]  Int_t fN; }
+    fn packed_schema() -> Arc<ClassSchema> {
+        let registry = register(vec![(
+            "TPacked",
+            vec![
+                basic_element("fScaled", "[0,10,16]", K_DOUBLE32, "Double32_t"),
+                basic_element("fBits", "[0,0,10]", K_DOUBLE32, "Double32_t"),
+                basic_element("fRanged", "[-1,1,8]", K_FLOAT16, "Float16_t"),
+                basic_element("fN", "", K_INT, "int"),
+            ],
+        )]);
+        registry.get("TPacked").unwrap()
+    }
+
+    fn packed_bytes() -> Vec<u8> {
+        let mut body = 1u16.to_be_bytes().to_vec();
+        // 2.5 over [0,10] in 16 bits
+        body.extend_from_slice(&16384u32.to_be_bytes());
+        // -1.5 with 10 bits of mantissa: exponent, then sign bit 11 and mantissa
+        body.push(127);
+        body.extend_from_slice(&(0x800u16 | 0x200).to_be_bytes());
+        // 0.5 over [-1,1] in 8 bits
+        body.extend_from_slice(&192u32.to_be_bytes());
+        body.extend_from_slice(&7i32.to_be_bytes());
+        let mut bytes = (K_HAS_BYTECOUNT | body.len() as u32).to_be_bytes().to_vec();
+        bytes.extend_from_slice(&body);
+        bytes
+    }
+
+    #[test]
+    fn test_packed_members_from_titles() {
+        let schema = packed_schema();
+        let kinds: Vec<&FieldKind> = schema.fields.iter().map(|field| &field.kind).collect();
+        assert_eq!(
+            kinds,
+            [
+                &FieldKind::Double32(PackedFloat::Scaled {
+                    xmin: 0.0,
+                    factor: 6553.6
+                }),
+                &FieldKind::Double32(PackedFloat::Truncated { nbits: 10 }),
+                &FieldKind::Float16(PackedFloat::Scaled {
+                    xmin: -1.0,
+                    factor: 128.0
+                }),
+                &FieldKind::I32,
+            ]
+        );
+
+        let mut values = Vec::new();
+        let mut n = 0;
+        let ctx = ReadContext::new();
+        let mut reader = Cursor::new(packed_bytes());
+        read_with_schema(
+            &mut reader,
+            Endian::Big,
+            &ctx,
+            &schema,
+            &mut |field, reader| {
+                match &field.kind {
+                    FieldKind::Double32(packed) | FieldKind::Float16(packed) => {
+                        values.push(packed.read(reader, Endian::Big)?)
+                    }
+                    _ => n = reader.read_type::<i32>(Endian::Big)?,
+                }
+                Ok(true)
+            },
+        )
+        .unwrap();
+        assert_eq!(values, [2.5, -1.5, 0.5]);
+        assert_eq!(n, 7);
+    }
+
+    #[test]
+    fn test_skip_packed_members() {
+        let schema = packed_schema();
+        let (mut ranged, mut n) = (0.0, 0);
+        let ctx = ReadContext::new();
+        let mut reader = Cursor::new(packed_bytes());
+        // fScaled and fBits are skipped by their sizes, 4 and 3 bytes
+        read_with_schema(
+            &mut reader,
+            Endian::Big,
+            &ctx,
+            &schema,
+            &mut |field, reader| {
+                match (field.name.as_str(), &field.kind) {
+                    ("fRanged", FieldKind::Float16(packed)) => {
+                        ranged = packed.read(reader, Endian::Big)?
+                    }
+                    ("fN", _) => n = reader.read_type::<i32>(Endian::Big)?,
+                    _ => return Ok(false),
+                }
+                Ok(true)
+            },
+        )
+        .unwrap();
+        assert_eq!((ranged, n), (0.5, 7));
+    }
+
+    // Version and byte count in front of a streamed object
+    fn with_header(body: &[u8]) -> Vec<u8> {
+        let mut bytes = (K_HAS_BYTECOUNT | (body.len() as u32 + 2))
+            .to_be_bytes()
+            .to_vec();
+        bytes.extend_from_slice(&1u16.to_be_bytes());
+        bytes.extend_from_slice(body);
+        bytes
+    }
+
+    #[test]
+    fn test_skip_streamer_loop() {
+        // TEvent { Int_t fN; THit* fHits; //[fN]  THit** fRefs; //[fN]  Int_t fTag; }
+        let registry = register(vec![
+            ("THit", vec![basic_element("fX", "", K_FLOAT, "float")]),
+            (
+                "TEvent",
+                vec![
+                    basic_element("fN", "", K_COUNTER, "int"),
+                    loop_element("fHits", "[fN]", "THit*", "fN"),
+                    loop_element("fRefs", "[fN]", "THit**", "fN"),
+                    basic_element("fTag", "", K_INT, "int"),
+                ],
+            ),
+        ]);
+        let schema = registry.get("TEvent").unwrap();
+        let hit = registry.get("THit").unwrap();
+        assert_eq!(
+            schema.fields[1].kind,
+            FieldKind::CountedArray {
+                elem: Box::new(FieldKind::Object(hit.clone())),
+                count: String::from("fN"),
+            }
+        );
+        assert_eq!(
+            schema.fields[2].kind,
+            FieldKind::CountedArray {
+                elem: Box::new(FieldKind::ObjectPointer(hit)),
+                count: String::from("fN"),
+            }
+        );
+        assert_eq!(schema.fields[1].kind.type_name(), "THit*[fN]");
+
+        let mut body = 2i32.to_be_bytes().to_vec();
+        // the objects of each loop are wrapped in one byte count, null pointers are 0 tags
+        let hits = [
+            with_header(&1.5f32.to_be_bytes()),
+            with_header(&2.5f32.to_be_bytes()),
+        ]
+        .concat();
+        body.extend_from_slice(&with_header(&hits));
+        body.extend_from_slice(&with_header(&[0u8; 8]));
+        body.extend_from_slice(&11i32.to_be_bytes());
+
+        let mut tag = 0;
+        let ctx = ReadContext::new();
+        let mut reader = Cursor::new(with_header(&body));
+        read_with_schema(
+            &mut reader,
+            Endian::Big,
+            &ctx,
+            &schema,
+            &mut |field, reader| {
+                if field.name != "fTag" {
+                    return Ok(false);
+                }
+                tag = reader.read_type::<i32>(Endian::Big)?;
+                Ok(true)
+            },
+        )
+        .unwrap();
+        assert_eq!(tag, 11);
+        assert_eq!(reader.position() as usize, reader.get_ref().len());
+    }
+}
@@ -1,11 +1,14 @@
+use crate::core::constant::{K_ANYP, K_OBJECTP, K_STL_VECTOR};
+use crate::core::write_buffer::{WriteBuffer, WriteObject};
 use crate::objects::tkey::TKey;
 use crate::objects::tlist::TList;
 use crate::streamer::registry::{ClassSchema, FieldKind, FieldSchema};
+use crate::streamer::tstreamer_element::{TStreamerElement, TStreamerType};
 use crate::streamer::tstreamerinfo::TStreamerInfo;
 use binrw::io::{Read, Seek, SeekFrom};
 use binrw::BinRead;
 use core::iter::IntoIterator;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 
 // https://root.cern/doc/v638/streamerinfo.html
@@ -26,22 +29,187 @@ impl StreamerInfo {
 
     pub fn iter(&self) -> impl Iterator<Item = &TStreamerInfo> {
         self.tlist.iter()
+    }
+}
+
+// TFile::WriteStreamerInfo: the list of the TStreamerInfo of the classes written in the file
+impl WriteObject for StreamerInfo {
+    fn class_name(&self) -> String {
+        String::from("TList")
+    }
+
+    fn title(&self) -> &str {
+        "Doubly linked list"
+    }
+
+    fn write_to(&self, buf: &mut WriteBuffer) {
+        self.tlist.write_to(buf);
+    }
+}
+
+impl<'a> IntoIterator for &'a StreamerInfo {
+    type Item = &'a TStreamerInfo;
+    type IntoIter = Box<dyn Iterator<Item = &'a TStreamerInfo> + 'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        Box::new(self.iter())
+    }
 }
 
+/*
+ * ClassSchema of every class in the streamer info of a file. Base classes and embedded objects
+ * refer to the schema of their class, classes without streamer info (or with a hand-written
+ * streamer and no members, like TList) get a schema without fields.
+ */
+#[derive(Debug, Default)]
 pub struct StreamerRegistry {
     pub classes: HashMap<String, Arc<ClassSchema>>,
 }
 
 impl StreamerRegistry {
     pub fn new() -> Self {
-        Self {
-            classes: HashMap::new(),
-        }
+        Self::default()
     }
 
     pub fn register_from_streamerinfo(&mut self, streamerinfo: &StreamerInfo) {
-        for t_streamerinfo in &streamerinfo {
-            
+        let mut infos: HashMap<&str, &TStreamerInfo> = HashMap::new();
+        for info in streamerinfo {
+            if !info.tnamed.name.is_empty() {
+                infos.entry(info.tnamed.name.as_str()).or_insert(info);
+            }
+        }
+        let mut builder = SchemaBuilder {
+            infos: &infos,
+            classes: &mut self.classes,
+            building: HashSet::new(),
+        };
+        for class in infos.keys() {
+            builder.schema(class);
+        }
+    }
+
+    pub fn get(&self, class: &str) -> Option<Arc<ClassSchema>> {
+        self.classes.get(class).cloned()
+    }
+}
+
+struct SchemaBuilder<'a> {
+    infos: &'a HashMap<&'a str, &'a TStreamerInfo>,
+    classes: &'a mut HashMap<String, Arc<ClassSchema>>,
+    building: HashSet<String>,
+}
+
+impl SchemaBuilder<'_> {
+    fn schema(&mut self, class: &str) -> Arc<ClassSchema> {
+        if let Some(schema) = self.classes.get(class) {
+            return schema.clone();
+        }
+        let Some(info) = self.infos.get(class).copied() else {
+            return Arc::new(ClassSchema {
+                name: class.to_string(),
+                version: 0,
+                checksum: 0,
+                fields: Vec::new(),
+            });
+        };
+        // a class pointing to itself refers to a schema without fields
+        if !self.building.insert(class.to_string()) {
+            return Arc::new(ClassSchema {
+                name: class.to_string(),
+                version: info.f_class_version as u16,
+                checksum: info.f_checksum,
+                fields: Vec::new(),
+            });
+        }
+        // artificial members are set by I/O rules, they are not in the buffer
+        let fields = info
+            .iter()
+            .filter(|element| !element.is_artificial())
+            .map(|element| FieldSchema {
+                name: element.tstreamer_element_base.tnamed.name.to_string(),
+                kind: self.kind(element),
+            })
+            .collect();
+        self.building.remove(class);
+        let schema = Arc::new(ClassSchema {
+            name: class.to_string(),
+            version: info.f_class_version as u16,
+            checksum: info.f_checksum,
+            fields,
+        });
+        self.classes.insert(class.to_string(), schema.clone());
+        schema
+    }
+
+    fn kind(&mut self, element: &TStreamerElement) -> FieldKind {
+        let base = &element.tstreamer_element_base;
+        let type_name = base.type_name.as_str();
+        let unknown = || FieldKind::Unknown(type_name.to_string());
+        let kind = match &element.tstreamer_type {
+            TStreamerType::TStreamerBase { .. } => {
+                return FieldKind::Base(self.schema(base.tnamed.name.as_str()))
+            }
+            TStreamerType::TStreamerBasicType => {
+                FieldKind::from_element(base).unwrap_or_else(unknown)
+            }
+            TStreamerType::TStreamerBasicPointer { name, .. } => {
+                match FieldKind::from_element(base) {
+                    Some(elem) => FieldKind::BasicPointer {
+                        elem: Box::new(elem),
+                        count: name.to_string(),
+                    },
+                    None => unknown(),
+                }
+            }
+            TStreamerType::TStreamerString | TStreamerType::TStreamerSTLString { .. } => {
+                FieldKind::TString
+            }
+            TStreamerType::TStreamerObject | TStreamerType::TStreamerObjectAny => {
+                FieldKind::Object(self.schema(type_name))
+            }
+            // `//->` pointers are streamed in place
+            TStreamerType::TStreamerObjectPointer | TStreamerType::TStreamerObjectAnyPointer
+                if base.f_type == K_OBJECTP || base.f_type == K_ANYP =>
+            {
+                FieldKind::Object(self.schema(type_name.trim_end_matches('*')))
+            }
+            TStreamerType::TStreamerObjectPointer | TStreamerType::TStreamerObjectAnyPointer => {
+                FieldKind::ObjectPointer(self.schema(type_name.trim_end_matches('*')))
+            }
+            TStreamerType::TStreamerSTL { stl_type, .. } if *stl_type == K_STL_VECTOR => {
+                match FieldKind::from_type_name(type_name) {
+                    Some(kind @ FieldKind::StlVector(_)) => kind,
+                    _ => unknown(),
+                }
+            }
+            // `TClass* fArray; //[fN]` streams fN objects in place, `TClass** fArray; //[fN]` fN
+            // pointers to them unless the title marks them preallocated with `->`
+            TStreamerType::TStreamerLoop { name, .. } => {
+                let class = self.schema(type_name.trim_end_matches('*'));
+                let elem = if type_name.ends_with("**") && !base.tnamed.title.contains("->") {
+                    FieldKind::ObjectPointer(class)
+                } else {
+                    FieldKind::Object(class)
+                };
+                FieldKind::CountedArray {
+                    elem: Box::new(elem),
+                    count: name.to_string(),
+                }
+            }
+            _ => unknown(),
+        };
+        if base.f_array_length > 0
+            && !matches!(
+                kind,
+                FieldKind::BasicPointer { .. } | FieldKind::CountedArray { .. }
+            )
+        {
+            FieldKind::FixedArray {
+                elem: Box::new(kind),
+                count: base.f_array_length as usize,
+            }
+        } else {
+            kind
         }
     }
 }
@@ -76,7 +244,7 @@ mod tests {
         let obj_len: usize = 30257;
         let n_bytes: usize = 8360;
         let key_len: usize = 64;
-        let mut data = vec![0u8; n_bytes - key_len as usize];
+        let mut data = vec![0u8; n_bytes - key_len];
         reader
             .seek(SeekFrom::Start(streamer_info_offset + key_len as u64))
             .expect("Failed to seek to compressed data");
@@ -89,7 +257,7 @@ mod tests {
             CompressionAlgorithm::decompress(&data).expect("Failed to decompress data");
         dbg!(&decompressed_data.len());
 
-        assert_eq!(decompressed_data.len(), obj_len as usize);
+        assert_eq!(decompressed_data.len(), obj_len);
     }
 
     use crate::objects::tlist::TList;
@@ -162,4 +330,38 @@ mod tests {
             .expect("Failed to read TList of TStreamerInfo from decompressed data");
         dbg!(&tlist);
     }
+
+    use crate::core::read_context::ReadContext;
+    #[test]
+    fn test_read_streamer_info_payload_with_key_len() {
+        let path =
+            "/Users/kylelau519/Programming/rusty_root/rusty_root_io/testfiles/streamer_info.bin";
+        let combined_data = std::fs::read(path).expect("Failed to read streamer info file");
+        let mut cursor = std::io::Cursor::new(&combined_data[..]);
+        let tkey = TKey::read_be(&mut cursor).expect("Failed to read TKey");
+
+        // only the payload, class tags still count from the start of the key buffer
+        let mut payload_cursor = std::io::Cursor::new(&combined_data[tkey.key_len as usize..]);
+        let tlist = TList::<TStreamerInfo>::read_options(
+            &mut payload_cursor,
+            binrw::Endian::Big,
+            (ReadContext::with_key_len(tkey.key_len),),
+        )
+        .expect("Failed to read TList of TStreamerInfo from the payload");
+
+        assert_eq!(tlist.n_objects, 46);
+        let tnamed = tlist
+            .iter()
+            .find(|info| info.tnamed.name == "TNamed")
+            .expect("TNamed streamer info is missing");
+        assert_eq!(tnamed.f_checksum, 3753331260);
+        let element_classes: Vec<String> = tnamed
+            .iter()
+            .map(|element| element.class_info.get_class_name())
+            .collect();
+        assert_eq!(
+            element_classes,
+            ["TStreamerBase", "TStreamerString", "TStreamerString"]
+        );
+    }
 }
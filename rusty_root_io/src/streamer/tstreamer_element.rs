@@ -1,15 +1,28 @@
-use crate::core::constant::K_BYTECOUNTMASK;
+use crate::core::constant::{K_BYTECOUNTMASK, K_DOUBLE32, K_FLOAT16, K_OFFSET_L, K_OFFSET_P};
+use crate::core::read_context::ReadContext;
 use crate::core::utils::ClassInfo;
+use crate::core::write_buffer::{WriteBuffer, WriteObject};
 use crate::objects::tnamed::TNamed;
 use crate::objects::tstring::TString;
-use binrw::io::{Read, Seek};
-use binrw::{binread, BinRead, BinReaderExt, BinResult, Endian};
+use crate::streamer::member::VersionHeader;
+use binrw::io::{Read, Seek, SeekFrom};
+use binrw::{BinRead, BinReaderExt, BinResult, Endian};
 
-#[binread]
-#[br(big)]
+// TStreamerElement::kHasRange, set in fBits when the title of a Float16_t or Double32_t member
+// holds a range
+pub const K_HAS_RANGE: u32 = 1 << 6;
+
+/*
+ * TStreamerElement, the members shared by all streamer element classes
+ * https://root.cern/doc/v638/classTStreamerElement.html
+ *
+ * The layout depends on the class version (TStreamerElement::Streamer):
+ *   v1:  fMaxIndex is written as a counted array
+ *   v3:  fXmin, fXmax and fFactor follow the members
+ *   v4+: they are not written, but computed from the title when kHasRange is set
+ */
 #[derive(Debug, Default)]
 pub struct TStreamerElementBase {
-    #[br(map = |x: u32| x & K_BYTECOUNTMASK)]
     pub byte_count: u32,
     pub version: u16,
     pub tnamed: TNamed,
@@ -19,11 +32,208 @@ pub struct TStreamerElementBase {
     pub f_array_dim: u32,
     pub f_max_index: [u32; 5],
     pub type_name: TString,
+    // range of a packed Float16_t or Double32_t, see TStreamerElement::GetRange
+    pub f_xmin: f64,
+    pub f_xmax: f64,
+    pub f_factor: f64,
+}
+
+impl BinRead for TStreamerElementBase {
+    type Args<'a> = (ReadContext,);
+
+    fn read_options<R: Read + Seek>(
+        reader: &mut R,
+        endian: Endian,
+        args: Self::Args<'_>,
+    ) -> BinResult<Self> {
+        let (ctx,) = args;
+        let header = VersionHeader::read(reader, endian)?;
+        let tnamed = TNamed::read_options(reader, endian, (ctx,))?;
+        let f_type = reader.read_type(endian)?;
+        let f_size = reader.read_type(endian)?;
+        let f_array_length = reader.read_type(endian)?;
+        let f_array_dim = reader.read_type(endian)?;
+        let mut f_max_index = [0u32; 5];
+        if header.version == 1 {
+            // TBuffer::ReadStaticArray
+            let n: i32 = reader.read_type(endian)?;
+            for i in 0..n.max(0) as usize {
+                let index: u32 = reader.read_type(endian)?;
+                if let Some(slot) = f_max_index.get_mut(i) {
+                    *slot = index;
+                }
+            }
+        } else {
+            f_max_index = reader.read_type(endian)?;
+        }
+        let type_name = TString::read_options(reader, endian, ())?;
+
+        let mut element = Self {
+            byte_count: header.byte_count,
+            version: header.version,
+            tnamed,
+            f_type,
+            f_size,
+            f_array_length,
+            f_array_dim,
+            f_max_index,
+            type_name,
+            ..Default::default()
+        };
+        if header.version == 3 {
+            element.f_xmin = reader.read_type(endian)?;
+            element.f_xmax = reader.read_type(endian)?;
+            element.f_factor = reader.read_type(endian)?;
+        } else if header.version > 3 && element.tnamed.tobject.f_bits & K_HAS_RANGE != 0 {
+            (element.f_xmin, element.f_xmax, element.f_factor) = get_range(&element.tnamed.title);
+        }
+        header.skip_to_end(reader)?;
+        Ok(element)
+    }
 }
 
+impl TStreamerElementBase {
+    // TStreamerElement::Streamer of version 4, the range is kept in the title
+    pub fn write_to(&self, buf: &mut WriteBuffer) {
+        buf.versioned(4, |buf| {
+            self.tnamed.write_to(buf);
+            buf.write(&self.f_type);
+            buf.write(&self.f_size);
+            buf.write(&self.f_array_length);
+            buf.write(&self.f_array_dim);
+            buf.write(&self.f_max_index);
+            buf.string(&self.type_name);
+        });
+    }
+
+    pub fn name(&self) -> &str {
+        &self.tnamed.name
+    }
+
+    pub fn title(&self) -> &str {
+        &self.tnamed.title
+    }
+
+    // fType without the kOffsetL/kOffsetP offsets of arrays
+    pub fn basic_type(&self) -> u32 {
+        if self.f_type > K_OFFSET_L && self.f_type < K_OFFSET_P {
+            self.f_type - K_OFFSET_L
+        } else if self.f_type > K_OFFSET_P && self.f_type < K_OFFSET_P + K_OFFSET_L {
+            self.f_type - K_OFFSET_P
+        } else {
+            self.f_type
+        }
+    }
+
+    pub fn is_float16(&self) -> bool {
+        self.basic_type() == K_FLOAT16
+    }
+
+    pub fn is_double32(&self) -> bool {
+        self.basic_type() == K_DOUBLE32
+    }
+
+    // A Float16_t or Double32_t with a range or a number of bits, i.e. not written as a plain float
+    pub fn has_range(&self) -> bool {
+        self.f_factor > 0.0 || self.f_xmin > 0.0
+    }
+}
+
+/*
+ * TStreamerElement::GetRange: parse "[xmin,xmax]" or "[xmin,xmax,nbits]" in the title of a member.
+ * With xmin < xmax, values are stored as integers of nbits and factor = 2^nbits / (xmax - xmin).
+ * Otherwise factor is 0 and, for nbits < 15, xmin = nbits + 0.1 asks for a truncated mantissa.
+ * A first bracket without comma is an array dimension and is skipped.
+ */
+pub fn get_range(title: &str) -> (f64, f64, f64) {
+    let find_range = |from: usize| -> Option<(usize, usize, usize)> {
+        let left = from + title[from..].find('[')?;
+        let right = left + title[left..].find(']')?;
+        let comma = left + title[left..].find(',')?;
+        (comma < right).then_some((left, comma, right))
+    };
+    let range = find_range(0).or_else(|| {
+        let right = title.find(']')?;
+        find_range(right)
+    });
+    let Some((left, comma, mut right)) = range else {
+        return (0.0, 0.0, 0.0);
+    };
+
+    let mut nbits = 32;
+    if let Some(comma2) = title[comma + 1..right].find(',').map(|i| i + comma + 1) {
+        nbits = leading_int(&title[comma2 + 1..right]).unwrap_or(32);
+        if !(2..=32).contains(&nbits) {
+            nbits = 32;
+        }
+        right = comma2;
+    }
+    let mut xmin = parse_bound(&title[left + 1..comma]);
+    let xmax = parse_bound(&title[comma + 1..right]);
+
+    let bigint = if nbits < 32 {
+        (1u32 << nbits) as f64
+    } else {
+        u32::MAX as f64
+    };
+    let factor = if xmin < xmax {
+        bigint / (xmax - xmin)
+    } else {
+        0.0
+    };
+    if xmin >= xmax && nbits < 15 {
+        xmin = nbits as f64 + 0.1;
+    }
+    (xmin, xmax, factor)
+}
+
+// sscanf("%d")
+fn leading_int(s: &str) -> Option<i32> {
+    let s = s.trim_start();
+    let end = s
+        .char_indices()
+        .find(|&(i, c)| !(c.is_ascii_digit() || (i == 0 && (c == '-' || c == '+'))))
+        .map_or(s.len(), |(i, _)| i);
+    s[..end].parse().ok()
+}
+
+// A bound is a number or one of the multiples of pi ROOT understands
+fn parse_bound(s: &str) -> f64 {
+    use std::f64::consts::PI;
+    let s: String = s.to_lowercase().chars().filter(|c| *c != ' ').collect();
+    if s.contains("pi") {
+        let value = if s.contains("2pi") || s.contains("2*pi") || s.contains("twopi") {
+            2.0 * PI
+        } else if s.contains("pi/2") {
+            PI / 2.0
+        } else if s.contains("pi/4") {
+            PI / 4.0
+        } else {
+            PI
+        };
+        if s.contains('-') {
+            -value
+        } else {
+            value
+        }
+    } else {
+        // sscanf("%lg"): the longest prefix that is a number
+        (1..=s.len())
+            .rev()
+            .filter(|&end| s.is_char_boundary(end))
+            .find_map(|end| s[..end].parse().ok())
+            .unwrap_or(0.0)
+    }
+}
+
+/*
+ * The members the streamer element classes add to TStreamerElement, by class name.
+ * https://root.cern/doc/v638/classTStreamerElement.html (inheritance diagram)
+ */
 #[derive(Debug)]
 pub enum TStreamerType {
     TStreamerBase {
+        // not written before version 3
         base_version: u32,
     },
     TStreamerBasicType,
@@ -41,28 +251,39 @@ pub enum TStreamerType {
         class_name: TString,
     },
     TStreamerObjectAny,
+    TStreamerObjectAnyPointer,
     TStreamerSTL {
         stl_type: u32,
         c_type: u32,
     },
-    TStreamerSTLString,
+    // TStreamerSTLstring derives from TStreamerSTL
+    TStreamerSTLString {
+        stl_type: u32,
+        c_type: u32,
+    },
+    // members added by I/O customization rules, not read from the buffer
+    TStreamerArtificial,
     Unknown(String),
 }
 
 impl BinRead for TStreamerType {
-    // We define that this enum NEEDS a String argument to be parsed
-    type Args<'a> = (String,);
+    // class name and version of the streamer element
+    type Args<'a> = (String, u16);
 
     fn read_options<R: Read + Seek>(
         reader: &mut R,
         endian: Endian,
         args: Self::Args<'_>,
     ) -> BinResult<Self> {
-        let (class_name,) = args;
+        let (class_name, version) = args;
 
         match class_name.as_str() {
             "TStreamerBase" => Ok(TStreamerType::TStreamerBase {
-                base_version: reader.read_type(endian)?,
+                base_version: if version > 2 {
+                    reader.read_type(endian)?
+                } else {
+                    0
+                },
             }),
             "TStreamerBasicType" => Ok(TStreamerType::TStreamerBasicType),
             "TStreamerString" => Ok(TStreamerType::TStreamerString),
@@ -89,32 +310,238 @@ impl BinRead for TStreamerType {
                 })
             }
             "TStreamerObjectAny" => Ok(TStreamerType::TStreamerObjectAny),
+            "TStreamerObjectAnyPointer" => Ok(TStreamerType::TStreamerObjectAnyPointer),
             "TStreamerSTL" => Ok(TStreamerType::TStreamerSTL {
                 stl_type: reader.read_type(endian)?,
                 c_type: reader.read_type(endian)?,
             }),
-            "TStreamerSTLString" => Ok(TStreamerType::TStreamerSTLString),
-            _ => {
-                let pos = reader.stream_position().unwrap_or(0);
-                eprintln!(
-                    "⚠️ WARNING: Unknown TStreamerType '{}' at offset {:#X}",
-                    class_name, pos
-                );
-                Ok(TStreamerType::Unknown(class_name.to_string()))
-            }
+            "TStreamerSTLstring" => Ok(TStreamerType::TStreamerSTLString {
+                stl_type: reader.read_type(endian)?,
+                c_type: reader.read_type(endian)?,
+            }),
+            "TStreamerArtificial" => Ok(TStreamerType::TStreamerArtificial),
+            // skipped with the byte count of the element
+            _ => Ok(TStreamerType::Unknown(class_name)),
         }
     }
 }
-#[binread]
+
 #[derive(Debug)]
 pub struct TStreamerElement {
-    #[br(map = |x: u32| x & K_BYTECOUNTMASK)]
     pub byte_count: u32,
     pub class_info: ClassInfo,
-    #[br(map = |x: u32| x & K_BYTECOUNTMASK)]
     pub remaining_bytes: u32,
     pub version: u16,
     pub tstreamer_element_base: TStreamerElementBase,
-    #[br(args(class_info.get_class_name()))]
     pub tstreamer_type: TStreamerType,
 }
+
+impl BinRead for TStreamerElement {
+    type Args<'a> = (ReadContext,);
+
+    fn read_options<R: Read + Seek>(
+        reader: &mut R,
+        endian: Endian,
+        args: Self::Args<'_>,
+    ) -> BinResult<Self> {
+        let (ctx,) = args;
+        let start = reader.stream_position()?;
+        let byte_count = reader.read_type::<u32>(endian)? & K_BYTECOUNTMASK;
+        let class_info = ClassInfo::read_class_info(reader, &ctx)?;
+        let class_name = class_info.get_class_name();
+        let remaining_bytes = reader.read_type::<u32>(endian)? & K_BYTECOUNTMASK;
+        let version = reader.read_type(endian)?;
+
+        // TStreamerSTLstring wraps a TStreamerSTL, whose version decides its own members
+        let stl_header = if class_name == "TStreamerSTLstring" {
+            Some(VersionHeader::read(reader, endian)?)
+        } else {
+            None
+        };
+        let tstreamer_element_base = TStreamerElementBase::read_options(reader, endian, (ctx,))?;
+        let type_version = stl_header.map_or(version, |header| header.version);
+        let tstreamer_type =
+            TStreamerType::read_options(reader, endian, (class_name, type_version))?;
+
+        reader.seek(SeekFrom::Start(start + 4 + byte_count as u64))?;
+        Ok(Self {
+            byte_count,
+            class_info,
+            remaining_bytes,
+            version,
+            tstreamer_element_base,
+            tstreamer_type,
+        })
+    }
+}
+
+impl TStreamerType {
+    // The members of the element class after those of TStreamerElement
+    pub fn write_to(&self, buf: &mut WriteBuffer) {
+        match self {
+            TStreamerType::TStreamerBase { base_version } => buf.write(base_version),
+            TStreamerType::TStreamerBasicPointer {
+                count_version,
+                name,
+                class_name,
+            }
+            | TStreamerType::TStreamerLoop {
+                count_version,
+                name,
+                class_name,
+            } => {
+                buf.write(count_version);
+                buf.string(name);
+                buf.string(class_name);
+            }
+            TStreamerType::TStreamerSTL { stl_type, c_type }
+            | TStreamerType::TStreamerSTLString { stl_type, c_type } => {
+                buf.write(stl_type);
+                buf.write(c_type);
+            }
+            _ => {}
+        }
+    }
+
+    // The current version of the element classes, the layout write_to follows
+    pub fn class_version(class_name: &str) -> u16 {
+        match class_name {
+            "TStreamerBase" | "TStreamerSTL" => 3,
+            _ => 2,
+        }
+    }
+}
+
+impl WriteObject for TStreamerElement {
+    fn class_name(&self) -> String {
+        self.class_info.get_class_name()
+    }
+
+    fn write_to(&self, buf: &mut WriteBuffer) {
+        let class_name = self.class_info.get_class_name();
+        let members = |buf: &mut WriteBuffer| {
+            self.tstreamer_element_base.write_to(buf);
+            self.tstreamer_type.write_to(buf);
+        };
+        buf.versioned(TStreamerType::class_version(&class_name), |buf| {
+            // TStreamerSTLstring wraps a TStreamerSTL
+            if class_name == "TStreamerSTLstring" {
+                buf.versioned(TStreamerType::class_version("TStreamerSTL"), members);
+            } else {
+                members(buf);
+            }
+        });
+    }
+}
+
+impl TStreamerElement {
+    pub fn name(&self) -> &str {
+        self.tstreamer_element_base.name()
+    }
+
+    pub fn type_name(&self) -> &str {
+        &self.tstreamer_element_base.type_name
+    }
+
+    pub fn class_name(&self) -> String {
+        self.class_info.get_class_name()
+    }
+
+    pub fn is_base(&self) -> bool {
+        matches!(self.tstreamer_type, TStreamerType::TStreamerBase { .. })
+    }
+
+    pub fn is_artificial(&self) -> bool {
+        matches!(self.tstreamer_type, TStreamerType::TStreamerArtificial)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::objects::tkey::TKey;
+    use crate::streamer::streamerinfo::StreamerInfo;
+    use std::fs::File;
+    use std::io::BufReader;
+
+    #[test]
+    fn test_get_range() {
+        assert_eq!(get_range("no range"), (0.0, 0.0, 0.0));
+        let (xmin, xmax, factor) = get_range("[-1,1,16] calibrated energy");
+        assert_eq!((xmin, xmax), (-1.0, 1.0));
+        assert_eq!(factor, 65536.0 / 2.0);
+        // the first bracket is an array dimension
+        let (xmin, xmax, factor) = get_range("[fN][0,2pi]");
+        assert_eq!(xmin, 0.0);
+        assert_eq!(xmax, 2.0 * std::f64::consts::PI);
+        assert_eq!(factor, u32::MAX as f64 / xmax);
+        // truncated mantissa, the number of bits is kept in xmin
+        assert_eq!(get_range("[0,0,12]"), (12.1, 0.0, 0.0));
+        assert_eq!(
+            get_range("[ -pi/2 , pi/2 ]").0,
+            -std::f64::consts::FRAC_PI_2
+        );
+    }
+
+    #[test]
+    fn test_element_classes_and_members() {
+        let path =
+            "/Users/kylelau519/Programming/rusty_root/rusty_root_io/testfiles/streamer_info.bin";
+        let file = File::open(path).expect("Failed to open streamer info file");
+        let mut reader = BufReader::new(file);
+        let streamer_info_header = TKey::read_be(&mut reader).expect("Failed to read TKey");
+        let tlist = BinRead::read_be(&mut reader).expect("Failed to read TList");
+        let streamer_info = StreamerInfo {
+            streamer_info_header,
+            tlist,
+        };
+
+        let elements: Vec<&TStreamerElement> =
+            streamer_info.iter().flat_map(|info| info.iter()).collect();
+        assert!(elements
+            .iter()
+            .all(|e| !matches!(e.tstreamer_type, TStreamerType::Unknown(_))));
+
+        let th1 = streamer_info
+            .iter()
+            .find(|info| info.tnamed.name == "TH1")
+            .expect("TH1 is missing");
+        let buffer = th1.iter().find(|e| e.name() == "fBuffer").unwrap();
+        assert_eq!(buffer.type_name(), "double*");
+        assert_eq!(buffer.tstreamer_element_base.basic_type(), 8);
+        match &buffer.tstreamer_type {
+            TStreamerType::TStreamerBasicPointer {
+                count_version,
+                name,
+                class_name,
+            } => {
+                assert_eq!(*count_version, 8);
+                assert_eq!(*name, "fBufferSize");
+                assert_eq!(*class_name, "TH1");
+            }
+            other => panic!("unexpected element {:?}", other),
+        }
+        let base = th1.iter().next().unwrap();
+        assert!(matches!(
+            base.tstreamer_type,
+            TStreamerType::TStreamerBase { base_version: 1 }
+        ));
+        assert!(!base.tstreamer_element_base.has_range());
+
+        let ref_table = streamer_info
+            .iter()
+            .find(|info| info.tnamed.name == "TRefTable")
+            .expect("TRefTable is missing");
+        let guids = ref_table
+            .iter()
+            .find(|e| e.name() == "fProcessGUIDs")
+            .unwrap();
+        assert!(matches!(
+            guids.tstreamer_type,
+            TStreamerType::TStreamerSTL {
+                stl_type: 1,
+                c_type: 61
+            }
+        ));
+    }
+}
@@ -1,7 +1,10 @@
-use crate::core::constant::K_BYTECOUNTMASK;
+use crate::core::constant::{K_BYTECOUNTMASK, K_INT};
+use crate::core::read_context::ReadContext;
 use crate::core::utils::ClassInfo;
+use crate::core::write_buffer::{WriteBuffer, WriteObject};
 use crate::objects::tnamed::TNamed;
 use crate::objects::tobjarray::TObjArray;
+use crate::streamer::registry::FieldKind;
 use crate::streamer::tstreamer_element::TStreamerElement;
 use binrw::{BinRead, BinReaderExt, BinResult, Endian};
 use std::io::{Read, Seek, SeekFrom};
@@ -19,24 +22,25 @@ pub struct TStreamerInfo {
 }
 
 impl BinRead for TStreamerInfo {
-    type Args<'a> = ();
+    type Args<'a> = (ReadContext,);
 
     fn read_options<R: Read + Seek>(
         reader: &mut R,
         endian: Endian,
-        _args: Self::Args<'_>,
+        args: Self::Args<'_>,
     ) -> BinResult<Self> {
+        let (ctx,) = args;
         let start_pos = reader.stream_position()?;
         let byte_count = reader.read_type::<u32>(endian)? & K_BYTECOUNTMASK;
-        let class_info = ClassInfo::read_options(reader, endian, ())?;
+        let class_info = ClassInfo::read_class_info(reader, &ctx)?;
 
         if class_info.get_class_name() == "TStreamerInfo" {
             let remaining_bytes = reader.read_type::<u32>(endian)? & K_BYTECOUNTMASK;
             let version = reader.read_type::<u16>(endian)?;
-            let tnamed = TNamed::read_options(reader, endian, ())?;
+            let tnamed = TNamed::read_options(reader, endian, (ctx.clone(),))?;
             let f_checksum = reader.read_type::<u32>(endian)?;
             let f_class_version = reader.read_type::<u32>(endian)?;
-            let tobjarray = TObjArray::<TStreamerElement>::read_options(reader, endian, ())?;
+            let tobjarray = TObjArray::<TStreamerElement>::read_options(reader, endian, (ctx,))?;
             Ok(Self {
                 byte_count,
                 class_info,
@@ -58,10 +62,69 @@ impl BinRead for TStreamerInfo {
         }
     }
 }
+// TStreamerInfo::Streamer, in the layout of the current class version
+impl WriteObject for TStreamerInfo {
+    fn class_name(&self) -> String {
+        String::from("TStreamerInfo")
+    }
+
+    fn write_to(&self, buf: &mut WriteBuffer) {
+        buf.versioned(10, |buf| {
+            self.tnamed.write_to(buf);
+            buf.write(&self.f_checksum);
+            buf.write(&self.f_class_version);
+            buf.write_object(&self.tobjarray);
+        });
+    }
+}
+
 impl TStreamerInfo {
     pub fn iter(&self) -> impl Iterator<Item = &TStreamerElement> {
         self.tobjarray.iter()
     }
+
+    /*
+     * TStreamerInfo::GetCheckSum of the elements, with the default TClass::kCurrentCheckSum:
+     * id = id * 3 + c over the class name, then the name and checksum of every base, then the
+     * members: a 1 for enums, the name, the type name, the array dimensions and the counter of a
+     * basic pointer ("[fN]" at the start of the title). The base checksums are those the base
+     * elements hold.
+     */
+    pub fn compute_checksum(&self) -> u32 {
+        fn hash(id: &mut u32, s: &str) {
+            for c in s.bytes() {
+                *id = id.wrapping_mul(3).wrapping_add(c as i8 as u32);
+            }
+        }
+        let mut id = 0u32;
+        hash(&mut id, &self.tnamed.name);
+        for element in self.iter().filter(|element| element.is_base()) {
+            hash(&mut id, element.name());
+            let base = &element.tstreamer_element_base;
+            id = id.wrapping_mul(3).wrapping_add(base.f_max_index[1]);
+        }
+        for element in self.iter().filter(|element| !element.is_base()) {
+            let base = &element.tstreamer_element_base;
+            // an enum is an int member whose type is not a basic type
+            if base.f_type == K_INT && FieldKind::from_cpp_type(&base.type_name).is_none() {
+                id = id.wrapping_mul(3).wrapping_add(1);
+            }
+            hash(&mut id, element.name());
+            hash(&mut id, &base.type_name);
+            for max_index in &base.f_max_index[..(base.f_array_dim as usize).min(5)] {
+                id = id.wrapping_mul(3).wrapping_add(*max_index);
+            }
+            // TVirtualStreamerInfo::GetElementCounterStart
+            let title = base.title();
+            let start = title.trim_start_matches(|c: char| c.is_whitespace() || c == '*');
+            if let Some(counter) = start.strip_prefix('[') {
+                if let Some(end) = counter.find(']') {
+                    hash(&mut id, &counter[..end]);
+                }
+            }
+        }
+        id
+    }
 }
 
 #[cfg(test)]
@@ -84,11 +147,69 @@ mod tests {
             TStreamerInfo::read_be(&mut reader).expect("Failed to read TStreamerInfo");
         assert_eq!(tstreamer_info.f_checksum, 3753331260);
         tstreamers_info.push(tstreamer_info);
-        assert_eq!(tstreamers_info.is_empty(), false);
+        assert!(!tstreamers_info.is_empty());
     }
+    #[test]
+    fn test_write_streamer_info() {
+        use crate::core::write_buffer::WriteBuffer;
+        use crate::objects::tlist::{TList, TListElement};
+
+        let path =
+            "/Users/kylelau519/Programming/rusty_root/rusty_root_io/testfiles/streamer_info.bin";
+        let bytes = std::fs::read(path).expect("Failed to read streamer info file");
+        let mut reader = std::io::Cursor::new(&bytes);
+        let key = TKey::read_be(&mut reader).expect("Failed to read TKey");
+        let tlist: TList<TStreamerInfo> =
+            BinRead::read_be(&mut reader).expect("Failed to read TList");
+
+        // the list of rules that ends the list is not a TStreamerInfo
+        let mut infos = TList::default();
+        for element in &tlist.objects {
+            if element.class_info.get_class_name() == "TStreamerInfo" {
+                infos.objects.push(TListElement {
+                    object: element.object.clone(),
+                    option: element.option.clone(),
+                });
+            }
+        }
+        assert_eq!(infos.objects.len(), 45);
+        let mut buf = WriteBuffer::new(key.key_len as u32);
+        infos.write_to(&mut buf);
+
+        // every TStreamerInfo is written as ROOT wrote it, class tags included; the list header
+        // differs in its byte count and number of objects
+        let payload = &bytes[key.key_len as usize..];
+        let written = buf.as_bytes();
+        assert_eq!(&written[4..17], &payload[4..17]);
+        assert_eq!(&written[21..], &payload[21..written.len()]);
+    }
+
+    #[test]
+    fn test_compute_checksum() {
+        let path =
+            "/Users/kylelau519/Programming/rusty_root/rusty_root_io/testfiles/streamer_info.bin";
+        let bytes = std::fs::read(path).expect("Failed to read streamer info file");
+        let mut reader = std::io::Cursor::new(&bytes);
+        TKey::read_be(&mut reader).expect("Failed to read TKey");
+        let tlist: crate::objects::tlist::TList<TStreamerInfo> =
+            BinRead::read_be(&mut reader).expect("Failed to read TList");
+        let infos: Vec<&TStreamerInfo> = tlist.iter().filter(|info| info.version > 0).collect();
+        assert_eq!(infos.len(), 45);
+        // classes of version 0 are not streamed, their checksum includes the transient members
+        for info in infos.into_iter().filter(|info| info.f_class_version > 0) {
+            assert_eq!(
+                info.compute_checksum(),
+                info.f_checksum,
+                "{}",
+                info.tnamed.name.as_str()
+            );
+        }
+    }
+
     // Define a test-only struct that mirrors the fields of TList before the objects vector, so we can read just those fields and skip the rest.
     #[binrw::binread]
     #[derive(Debug)]
+    #[allow(dead_code)]
     struct TestTListHeader {
         #[br(map = |x: u32| x & crate::core::constant::K_BYTECOUNTMASK)]
         pub byte_count: u32,
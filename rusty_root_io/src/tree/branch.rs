@@ -0,0 +1,425 @@
+use crate::core::constant::K_HAS_BYTECOUNT;
+use crate::core::write_buffer::{WriteBuffer, WriteObject};
+use crate::file::tfile::TFile;
+use crate::file::writer::{DirectoryId, TFileWriter};
+use crate::objects::tatt::TAttFill;
+use crate::objects::tkey::TKey;
+use crate::objects::tobject::TObject;
+use crate::streamer::builtin;
+use crate::tree::leaf::{Leaf, LeafType};
+use crate::tree::reader::BranchInfo;
+use std::io::{self, Read, Seek, SeekFrom};
+
+/*
+ * The branches written by TTreeWriter and their baskets
+ * https://root.cern/doc/v638/classTBranch.html
+ * https://root.cern/doc/v638/classTBasket.html
+ *
+ * The entries of a branch are filled in a basket, written in a key of class TBasket when it is full
+ * or at the end of a cluster. The key is not listed in the directory, the branch keeps the seek,
+ * the size and the first entry of each of its baskets. The header of the key is followed by that of
+ * the basket:
+ *   - version (3), fBufferSize, fNevBufSize, fNevBuf and fLast as int, and a flag byte (0)
+ * then by the entries. The entries of a std::vector branch have different sizes, the basket ends
+ * with their offsets in the key buffer (fEntryOffset), from fLast on, like TBuffer::WriteArray of
+ * fNevBuf + 1 offsets. The baskets of the same branch in another file are copied with their
+ * compressed data, the offsets hold as long as the key header keeps its length.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BranchKind {
+    // one value per entry, `pt/D`
+    Scalar,
+    // a fixed number of values per entry, `p4[4]/D`
+    Array(usize),
+    // a std::vector per entry, in a TBranchElement
+    Vector,
+}
+
+// The bytes of the TBasket header after the TKey header
+const BASKET_HEADER_LEN: u16 = 19;
+const BASKET_VERSION: u16 = 3;
+// Collections are streamed with the version of TStreamerInfo (TStreamerInfo::WriteBufferAux)
+const STL_VERSION: u16 = 9;
+// Version of the TClass of a std::vector
+const STL_CLASS_VERSION: i16 = 6;
+// TTree::fDefaultEntryOffsetLen, the initial size of the offset table of a basket
+pub(crate) const ENTRY_OFFSET_LEN: i32 = 1000;
+
+// A basket on file: fBasketBytes, fBasketEntry and fBasketSeek of the branch
+#[derive(Debug, Clone, Copy)]
+struct Basket {
+    bytes: i32,
+    entry: i64,
+    seek: i64,
+}
+
+#[derive(Debug)]
+pub(crate) struct Branch {
+    pub name: String,
+    pub kind: BranchKind,
+    pub leaf: Leaf,
+    pub entries: u64,
+    // the value of the next entry, set before TTreeWriter::fill
+    pub pending: Option<Vec<u8>>,
+    compression: i32,
+    basket_size: i32,
+    // the basket being filled: its entries and their offsets in it
+    data: Vec<u8>,
+    offsets: Vec<i32>,
+    first_entry: u64,
+    baskets: Vec<Basket>,
+    tot_bytes: i64,
+    zip_bytes: i64,
+}
+
+impl Branch {
+    pub fn new(
+        name: &str,
+        leaf_type: LeafType,
+        kind: BranchKind,
+        compression: i32,
+        basket_size: i32,
+    ) -> Self {
+        let leaf_title = match kind {
+            BranchKind::Array(len) => format!("{}[{}]", name, len),
+            _ => name.to_string(),
+        };
+        Self {
+            name: name.to_string(),
+            kind,
+            leaf: Leaf {
+                name: name.to_string(),
+                title: leaf_title,
+                leaf_type,
+                len: match kind {
+                    BranchKind::Array(len) => len,
+                    _ => 1,
+                },
+                is_element: kind == BranchKind::Vector,
+            },
+            entries: 0,
+            pending: None,
+            compression,
+            basket_size,
+            data: Vec::new(),
+            offsets: Vec::new(),
+            first_entry: 0,
+            baskets: Vec::new(),
+            tot_bytes: 0,
+            zip_bytes: 0,
+        }
+    }
+
+    // The leaf list of a TBranch, `pt/D`, the name of a TBranchElement
+    pub fn title(&self) -> String {
+        match self.kind {
+            BranchKind::Vector => self.name.clone(),
+            _ => format!("{}/{}", self.leaf.title, self.leaf.leaf_type.code()),
+        }
+    }
+
+    // vector<float> for a TBranchElement
+    pub fn collection_class(&self) -> Option<String> {
+        (self.kind == BranchKind::Vector)
+            .then(|| format!("vector<{}>", self.leaf.leaf_type.type_name()))
+    }
+
+    pub fn tot_bytes(&self) -> i64 {
+        self.tot_bytes
+    }
+
+    pub fn zip_bytes(&self) -> i64 {
+        self.zip_bytes
+    }
+
+    pub fn has_basket(&self) -> bool {
+        self.entries > self.first_entry
+    }
+
+    pub fn is_basket_full(&self) -> bool {
+        self.data.len() >= self.basket_size as usize
+    }
+
+    // The streamed values of an entry: the values, after the byte count, the version and the size
+    // of the collection for a std::vector
+    pub fn entry_bytes(&self, values: &[u8], count: usize) -> Vec<u8> {
+        match self.kind {
+            BranchKind::Vector => {
+                let mut bytes = Vec::with_capacity(10 + values.len());
+                let byte_count = K_HAS_BYTECOUNT | (6 + values.len()) as u32;
+                bytes.extend_from_slice(&byte_count.to_be_bytes());
+                bytes.extend_from_slice(&STL_VERSION.to_be_bytes());
+                bytes.extend_from_slice(&(count as i32).to_be_bytes());
+                bytes.extend_from_slice(values);
+                bytes
+            }
+            _ => values.to_vec(),
+        }
+    }
+
+    // TBranch::Fill: the entry at the end of the basket
+    pub fn append(&mut self, entry: &[u8]) {
+        if self.kind == BranchKind::Vector {
+            self.offsets.push(self.data.len() as i32);
+        }
+        self.data.extend_from_slice(entry);
+        self.entries += 1;
+    }
+
+    // TBranch::WriteBasket: the basket in a key of the file
+    pub fn write_basket(
+        &mut self,
+        file: &mut TFileWriter,
+        directory: DirectoryId,
+        tree_name: &str,
+    ) -> io::Result<()> {
+        if !self.has_basket() {
+            return Ok(());
+        }
+        let key = file.new_unlisted_key(
+            directory,
+            "TBasket",
+            &self.name,
+            tree_name,
+            self.baskets.len() as u16,
+            BASKET_HEADER_LEN,
+        );
+        let key_len = key.key_len as i32;
+        let nev_buf = (self.entries - self.first_entry) as i32;
+        let mut payload = std::mem::take(&mut self.data);
+        let last = key_len + payload.len() as i32;
+        let nev_buf_size = if self.kind == BranchKind::Vector {
+            // WriteArray(fEntryOffset, fNevBuf + 1), the slot after the last entry is left at 0
+            payload.extend_from_slice(&(nev_buf + 1).to_be_bytes());
+            for offset in self.offsets.drain(..) {
+                payload.extend_from_slice(&(key_len + offset).to_be_bytes());
+            }
+            payload.extend_from_slice(&0i32.to_be_bytes());
+            // the offset table grows by doubling, like TBasket::Update
+            let mut size = ENTRY_OFFSET_LEN;
+            while size <= nev_buf {
+                size *= 2;
+            }
+            size
+        } else {
+            // the size of an entry
+            (last - key_len) / nev_buf
+        };
+
+        let mut header = WriteBuffer::new(0);
+        header.write(&BASKET_VERSION);
+        header.write(&self.basket_size.max(last));
+        header.write(&nev_buf_size);
+        header.write(&nev_buf);
+        header.write(&last);
+        header.put(&[0]);
+        let key = file.write_unlisted_key(key, header.as_bytes(), payload)?;
+
+        self.baskets.push(Basket {
+            bytes: key.n_bytes as i32,
+            entry: self.first_entry as i64,
+            seek: key.seek_key as i64,
+        });
+        self.tot_bytes += key.obj_len as i64 + key.key_len as i64;
+        self.zip_bytes += key.n_bytes as i64;
+        self.first_entry = self.entries;
+        Ok(())
+    }
+
+    // TTreeCloner::CopyBaskets: the baskets of the branch of a tree of another file appended after
+    // the entries of the branch, which has no pending basket. Their keys are copied with the data
+    // as they are, without decompressing them.
+    pub fn copy_baskets(
+        &mut self,
+        file: &mut TFileWriter,
+        directory: DirectoryId,
+        source: &mut TFile,
+        branch: &BranchInfo,
+    ) -> io::Result<()> {
+        debug_assert!(!self.has_basket());
+        for basket in &branch.baskets {
+            let reader = source.reader_mut();
+            let key = TKey::read_from(reader, basket.seek as u64).map_err(io::Error::other)?;
+            if key.class_name.as_str() != "TBasket" || key.n_bytes as i32 != basket.bytes {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!(
+                        "no basket of {} bytes of branch {} at {}",
+                        basket.bytes, branch.name, basket.seek
+                    ),
+                ));
+            }
+            let mut data = vec![0; (key.n_bytes - key.sizeof() as u32) as usize];
+            reader.seek(SeekFrom::Start(basket.seek as u64 + key.sizeof() as u64))?;
+            reader.read_exact(&mut data)?;
+            let cycle = self.baskets.len() as u16;
+            let key = file.copy_unlisted_key(directory, &key, cycle, &data)?;
+            self.baskets.push(Basket {
+                bytes: key.n_bytes as i32,
+                entry: self.entries as i64 + basket.entry,
+                seek: key.seek_key as i64,
+            });
+            self.tot_bytes += key.obj_len as i64 + key.key_len as i64;
+            self.zip_bytes += key.n_bytes as i64;
+        }
+        self.entries += branch.entries as u64;
+        self.first_entry = self.entries;
+        Ok(())
+    }
+
+    // TBranch::Streamer once the baskets are written: fBaskets is empty and the tables of the
+    // baskets have at least 10 slots, fBasketEntry ending with the number of entries
+    fn write_tbranch(&self, buf: &mut WriteBuffer) {
+        buf.versioned(13, |buf| {
+            buf.versioned(1, |buf| {
+                buf.tobject(&TObject::default());
+                buf.string(&self.name);
+                buf.string(&self.title());
+            });
+            TAttFill::new(0, 1001).write_to(buf);
+            buf.write(&self.compression);
+            buf.write(&self.basket_size);
+            let entry_offset_len = match self.kind {
+                BranchKind::Vector => ENTRY_OFFSET_LEN,
+                _ => 0,
+            };
+            buf.write(&entry_offset_len);
+            buf.write(&(self.baskets.len() as i32));
+            buf.write(&(self.entries as i64));
+            // fIOFeatures
+            buf.versioned(1, |buf| buf.put(&[0]));
+            // fOffset
+            buf.write(&0i32);
+            let max_baskets = (self.baskets.len() + 1).max(10);
+            buf.write(&(max_baskets as i32));
+            // fSplitLevel
+            buf.write(&0i32);
+            buf.write(&(self.entries as i64));
+            // fFirstEntry
+            buf.write(&0i64);
+            buf.write(&self.tot_bytes);
+            buf.write(&self.zip_bytes);
+            // fBranches, fLeaves and fBaskets
+            write_obj_array(buf, 0, |_| {});
+            write_obj_array(buf, 1, |buf| buf.write_shared(&self.leaf));
+            write_obj_array(buf, 0, |_| {});
+
+            let mut bytes = vec![0i32; max_baskets];
+            let mut entries = vec![0i64; max_baskets];
+            let mut seeks = vec![0i64; max_baskets];
+            for (i, basket) in self.baskets.iter().enumerate() {
+                bytes[i] = basket.bytes;
+                entries[i] = basket.entry;
+                seeks[i] = basket.seek;
+            }
+            entries[self.baskets.len()] = self.entries as i64;
+            buf.basic_pointer(&bytes);
+            buf.basic_pointer(&entries);
+            buf.basic_pointer(&seeks);
+            // fFileName, the baskets are in the file of the tree
+            buf.string("");
+        });
+    }
+}
+
+// A TBranch with a leaf list, or a TBranchElement of a std::vector
+impl WriteObject for Branch {
+    fn class_name(&self) -> String {
+        match self.kind {
+            BranchKind::Vector => String::from("TBranchElement"),
+            _ => String::from("TBranch"),
+        }
+    }
+
+    fn write_to(&self, buf: &mut WriteBuffer) {
+        let Some(class) = self.collection_class() else {
+            self.write_tbranch(buf);
+            return;
+        };
+        let checksum = builtin::streamer_info(&class).map_or(0, |info| info.f_checksum);
+        buf.versioned(10, |buf| {
+            self.write_tbranch(buf);
+            // fClassName, fParentName and fClonesName
+            buf.string(&class);
+            buf.string("");
+            buf.string("");
+            buf.write(&checksum);
+            buf.write(&STL_CLASS_VERSION);
+            // fID and fType of a top level branch, fStreamerType and fMaximum
+            buf.write(&-1i32);
+            buf.write(&0i32);
+            buf.write(&-1i32);
+            buf.write(&0i32);
+            // fBranchCount and fBranchCount2
+            buf.null();
+            buf.null();
+        });
+    }
+}
+
+// TObjArray::Streamer of `len` objects written by `objects`
+pub(crate) fn write_obj_array(
+    buf: &mut WriteBuffer,
+    len: usize,
+    objects: impl FnOnce(&mut WriteBuffer),
+) {
+    buf.versioned(3, |buf| {
+        buf.tobject(&TObject::default());
+        buf.string("");
+        buf.write(&(len as i32));
+        // fLowerBound
+        buf.write(&0i32);
+        objects(buf);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::file::writer::WriteOptions;
+
+    // The bytes after the TKey header of a basket of two vector<float> entries, {1.0} and {}, laid
+    // out like TBasket::Streamer and TBasket::WriteBuffer write them, uncompressed
+    #[rustfmt::skip]
+    const VECTOR_BASKET: [u8; 59] = [
+        // version, fBufferSize, fNevBufSize, fNevBuf, fLast = 65 + 24, flag
+        0x00, 0x03,
+        0x00, 0x00, 0x7d, 0x00,
+        0x00, 0x00, 0x03, 0xe8,
+        0x00, 0x00, 0x00, 0x02,
+        0x00, 0x00, 0x00, 0x59,
+        0x00,
+        // {1.0}: byte count, version, size and values
+        0x40, 0x00, 0x00, 0x0a, 0x00, 0x09, 0x00, 0x00, 0x00, 0x01, 0x3f, 0x80, 0x00, 0x00,
+        // {}
+        0x40, 0x00, 0x00, 0x06, 0x00, 0x09, 0x00, 0x00, 0x00, 0x00,
+        // fEntryOffset: fNevBuf + 1, the entries at fKeylen = 65 and 79, the unset last slot
+        0x00, 0x00, 0x00, 0x03,
+        0x00, 0x00, 0x00, 0x41,
+        0x00, 0x00, 0x00, 0x4f,
+        0x00, 0x00, 0x00, 0x00,
+    ];
+
+    #[test]
+    fn test_vector_basket_layout() {
+        let path = std::env::temp_dir().join(format!(
+            "rusty_root_test_vector_basket_{}.root",
+            std::process::id()
+        ));
+        let mut file =
+            TFileWriter::create_with(&path, WriteOptions::default().with_compression(0)).unwrap();
+        let top = file.top();
+        let mut branch = Branch::new("jets", LeafType::F32, BranchKind::Vector, 0, 32000);
+        branch.append(&branch.entry_bytes(&1.0f32.to_be_bytes(), 1));
+        branch.append(&branch.entry_bytes(&[], 0));
+        branch.write_basket(&mut file, top, "events").unwrap();
+        let basket = branch.baskets[0];
+        file.close().unwrap();
+
+        let bytes = std::fs::read(&path).unwrap();
+        let record = &bytes[basket.seek as usize..][..basket.bytes as usize];
+        // the TKey header of class TBasket, jets and events is 46 bytes
+        assert_eq!(record[46..], VECTOR_BASKET);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,223 @@
+use crate::core::write_buffer::{WriteBuffer, WriteObject};
+use crate::objects::tobject::TObject;
+
+/*
+ * The leaves of the branches written by TTreeWriter
+ * https://root.cern/doc/v638/classTLeaf.html
+ *
+ * A leaf describes the values of its branch: their type, given by the class of the leaf (TLeafD for
+ * double, ...) and fIsUnsigned, and their number in an entry. A branch of a std::vector has a
+ * TLeafElement, the values are described by the class of the branch.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LeafType {
+    Bool,
+    I8,
+    U8,
+    I16,
+    U16,
+    I32,
+    U32,
+    I64,
+    U64,
+    F32,
+    F64,
+}
+
+impl LeafType {
+    // The type code of a leaf list, as in TTree::Branch("pt", &pt, "pt/D")
+    pub fn code(self) -> char {
+        match self {
+            Self::Bool => 'O',
+            Self::I8 => 'B',
+            Self::U8 => 'b',
+            Self::I16 => 'S',
+            Self::U16 => 's',
+            Self::I32 => 'I',
+            Self::U32 => 'i',
+            Self::I64 => 'L',
+            Self::U64 => 'l',
+            Self::F32 => 'F',
+            Self::F64 => 'D',
+        }
+    }
+
+    pub fn size(self) -> usize {
+        match self {
+            Self::Bool | Self::I8 | Self::U8 => 1,
+            Self::I16 | Self::U16 => 2,
+            Self::I32 | Self::U32 | Self::F32 => 4,
+            Self::I64 | Self::U64 | Self::F64 => 8,
+        }
+    }
+
+    pub fn is_unsigned(self) -> bool {
+        matches!(self, Self::U8 | Self::U16 | Self::U32 | Self::U64)
+    }
+
+    // The class of the leaf, the same for the signed and unsigned types
+    pub fn leaf_class(self) -> &'static str {
+        match self {
+            Self::Bool => "TLeafO",
+            Self::I8 | Self::U8 => "TLeafB",
+            Self::I16 | Self::U16 => "TLeafS",
+            Self::I32 | Self::U32 => "TLeafI",
+            Self::I64 | Self::U64 => "TLeafL",
+            Self::F32 => "TLeafF",
+            Self::F64 => "TLeafD",
+        }
+    }
+
+    // The type of the values of a TLeafX, None for the other leaves (TLeafC, TLeafElement, ...)
+    pub fn from_leaf_class(class: &str, is_unsigned: bool) -> Option<Self> {
+        let (signed, unsigned) = match class {
+            "TLeafO" => return Some(Self::Bool),
+            "TLeafF" => return Some(Self::F32),
+            "TLeafD" => return Some(Self::F64),
+            "TLeafB" => (Self::I8, Self::U8),
+            "TLeafS" => (Self::I16, Self::U16),
+            "TLeafI" => (Self::I32, Self::U32),
+            "TLeafL" => (Self::I64, Self::U64),
+            _ => return None,
+        };
+        Some(if is_unsigned { unsigned } else { signed })
+    }
+
+    // The type of the elements of vector<float> and the like
+    pub fn from_type_name(type_name: &str) -> Option<Self> {
+        match type_name {
+            "Long64_t" | "long" | "long long" => Some(Self::I64),
+            "ULong64_t" | "unsigned long" | "unsigned long long" => Some(Self::U64),
+            _ => [
+                Self::Bool,
+                Self::I8,
+                Self::U8,
+                Self::I16,
+                Self::U16,
+                Self::I32,
+                Self::U32,
+                Self::F32,
+                Self::F64,
+            ]
+            .into_iter()
+            .find(|leaf_type| leaf_type.type_name() == type_name),
+        }
+    }
+
+    // The C++ type, as in the name of vector<float>
+    pub fn type_name(self) -> &'static str {
+        match self {
+            Self::Bool => "bool",
+            Self::I8 => "char",
+            Self::U8 => "unsigned char",
+            Self::I16 => "short",
+            Self::U16 => "unsigned short",
+            Self::I32 => "int",
+            Self::U32 => "unsigned int",
+            Self::I64 => "Long64_t",
+            Self::U64 => "ULong64_t",
+            Self::F32 => "float",
+            Self::F64 => "double",
+        }
+    }
+}
+
+// The values a branch holds, written big-endian in the baskets
+pub trait LeafValue: Copy {
+    const LEAF_TYPE: LeafType;
+
+    fn put(self, bytes: &mut Vec<u8>);
+}
+
+macro_rules! leaf_value {
+    ($($type:ty => $leaf_type:ident),* $(,)?) => {
+        $(
+            impl LeafValue for $type {
+                const LEAF_TYPE: LeafType = LeafType::$leaf_type;
+
+                fn put(self, bytes: &mut Vec<u8>) {
+                    bytes.extend_from_slice(&self.to_be_bytes());
+                }
+            }
+        )*
+    };
+}
+
+leaf_value!(
+    i8 => I8,
+    u8 => U8,
+    i16 => I16,
+    u16 => U16,
+    i32 => I32,
+    u32 => U32,
+    i64 => I64,
+    u64 => U64,
+    f32 => F32,
+    f64 => F64,
+);
+
+impl LeafValue for bool {
+    const LEAF_TYPE: LeafType = LeafType::Bool;
+
+    fn put(self, bytes: &mut Vec<u8>) {
+        bytes.push(self as u8);
+    }
+}
+
+// TLeafX, or TLeafElement for a branch of a collection
+#[derive(Debug, Clone)]
+pub(crate) struct Leaf {
+    pub name: String,
+    pub title: String,
+    pub leaf_type: LeafType,
+    // fLen: the number of values of a fixed size array, 1 otherwise
+    pub len: usize,
+    pub is_element: bool,
+}
+
+impl WriteObject for Leaf {
+    fn class_name(&self) -> String {
+        if self.is_element {
+            String::from("TLeafElement")
+        } else {
+            self.leaf_type.leaf_class().to_string()
+        }
+    }
+
+    fn title(&self) -> &str {
+        &self.title
+    }
+
+    fn write_to(&self, buf: &mut WriteBuffer) {
+        buf.versioned(1, |buf| {
+            buf.versioned(2, |buf| {
+                buf.versioned(1, |buf| {
+                    buf.tobject(&TObject::default());
+                    buf.string(&self.name);
+                    buf.string(&self.title);
+                });
+                buf.write(&(self.len as i32));
+                // fLenType, left at 0 by TLeafElement for a collection
+                let len_type = if self.is_element {
+                    0
+                } else {
+                    self.leaf_type.size() as i32
+                };
+                buf.write(&len_type);
+                // fOffset, fIsRange, fIsUnsigned and fLeafCount
+                buf.write(&0i32);
+                buf.bool(false);
+                buf.bool(!self.is_element && self.leaf_type.is_unsigned());
+                buf.null();
+            });
+            if self.is_element {
+                // fID and fType of the top level branch of a collection
+                buf.write(&-1i32);
+                buf.write(&-1i32);
+            } else {
+                // fMinimum and fMaximum, no range
+                buf.put(&vec![0; 2 * self.leaf_type.size()]);
+            }
+        });
+    }
+}
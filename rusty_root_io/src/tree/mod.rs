@@ -0,0 +1,4 @@
+pub mod branch;
+pub mod leaf;
+pub mod reader;
+pub mod writer;
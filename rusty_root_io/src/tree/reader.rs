@@ -0,0 +1,477 @@
+use crate::core::read_context::{ObjectHeader, ReadContext};
+use crate::file::tfile::TFile;
+use crate::objects::tkey::TKey;
+use crate::objects::tobject::TObject;
+use crate::objects::tstring::TString;
+use crate::streamer::member::{read_basic_pointer, VersionHeader};
+use crate::streamer::schema_reader::{read_with_schema, required_schema};
+use crate::tree::branch::BranchKind;
+use crate::tree::leaf::LeafType;
+use binrw::io::{Read, Seek, SeekFrom};
+use binrw::{BinRead, BinReaderExt, BinResult, Endian};
+use std::io;
+
+/*
+ * Reading of the layout of trees: their branches, their clusters and where the baskets of every
+ * branch are in the file
+ * https://root.cern/doc/v638/classTTree.html
+ *
+ * The TTree (or TNtuple) and its branches are walked with the streamer info of their file, the
+ * members of interest are decoded and the others stepped over. Only the branches TTreeWriter
+ * writes are described: numbers, fixed size arrays of numbers and std::vector of numbers, with
+ * one leaf of the name of the branch. Other branches are an Unsupported error. The entries are not
+ * decoded, TTreeWriter::append_tree copies the baskets as they are.
+ *
+ *     let key = file.key("events").unwrap().clone();
+ *     let tree = TreeInfo::read(&mut file, &key)?;
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct TreeInfo {
+    pub name: String,
+    pub title: String,
+    pub entries: i64,
+    // fAutoFlush, fClusterRangeEnd and fClusterSize
+    pub auto_flush: i64,
+    pub cluster_range_end: Vec<i64>,
+    pub cluster_size: Vec<i64>,
+    pub branches: Vec<BranchInfo>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BranchInfo {
+    pub name: String,
+    pub leaf_type: LeafType,
+    pub kind: BranchKind,
+    // fCompress and fBasketSize
+    pub compression: i32,
+    pub basket_size: i32,
+    pub entries: i64,
+    pub baskets: Vec<BasketInfo>,
+}
+
+// A basket on file: fBasketBytes, fBasketEntry and fBasketSeek of its branch
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BasketInfo {
+    pub bytes: i32,
+    pub entry: i64,
+    pub seek: i64,
+}
+
+// The members of a branch as they are streamed, checked by into_info
+#[derive(Debug, Default)]
+struct RawBranch {
+    class: String,
+    name: String,
+    // fClassName of a TBranchElement
+    element_class: String,
+    compression: i32,
+    basket_size: i32,
+    write_basket: i32,
+    entries: i64,
+    subbranches: usize,
+    leaves: Vec<RawLeaf>,
+    basket_bytes: Vec<i32>,
+    basket_entry: Vec<i64>,
+    basket_seek: Vec<i64>,
+    file_name: String,
+}
+
+#[derive(Debug, Default)]
+struct RawLeaf {
+    class: String,
+    name: String,
+    len: i32,
+    is_unsigned: bool,
+    has_count: bool,
+}
+
+impl TreeInfo {
+    // The tree of a key of the file
+    pub fn read(file: &mut TFile, key: &TKey) -> io::Result<Self> {
+        let (mut tree, branches) = read_tree(file, key).map_err(|error| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("tree {}: {}", key.name.as_str(), error),
+            )
+        })?;
+        tree.branches = branches
+            .into_iter()
+            .map(|branch| branch.into_info(&tree.name))
+            .collect::<io::Result<_>>()?;
+        Ok(tree)
+    }
+
+    // Whether the trees have branches of the same names and types, in the same order
+    pub fn same_branches(&self, other: &TreeInfo) -> bool {
+        self.branches.len() == other.branches.len()
+            && self
+                .branches
+                .iter()
+                .zip(&other.branches)
+                .all(|(a, b)| (&a.name, a.leaf_type, a.kind) == (&b.name, b.leaf_type, b.kind))
+    }
+
+    // The entries after the clusters of the tree, as TTree::GetClusterIterator steps through
+    // them: the ranges of clusters of the same size, then clusters of fAutoFlush entries. A size
+    // that is not positive makes one cluster of the rest of its range.
+    pub fn cluster_ends(&self) -> Vec<u64> {
+        let ranges = self
+            .cluster_range_end
+            .iter()
+            .zip(&self.cluster_size)
+            .map(|(&last, &size)| (last + 1, size));
+        let mut ends = Vec::new();
+        let mut start = 0;
+        for (end, size) in ranges.chain(std::iter::once((self.entries, self.auto_flush))) {
+            let end = end.min(self.entries);
+            while start < end {
+                start = if size > 0 {
+                    (start + size).min(end)
+                } else {
+                    end
+                };
+                ends.push(start as u64);
+            }
+        }
+        ends
+    }
+}
+
+impl RawBranch {
+    // The branch if TTreeWriter can write it, with the baskets written to the file
+    fn into_info(self, tree: &str) -> io::Result<BranchInfo> {
+        let unsupported = |reason: String| {
+            io::Error::new(
+                io::ErrorKind::Unsupported,
+                format!("branch {} of tree {} {}", self.name, tree, reason),
+            )
+        };
+        if self.subbranches > 0 {
+            return Err(unsupported(String::from("has subbranches")));
+        }
+        if !self.file_name.is_empty() {
+            return Err(unsupported(format!(
+                "has its baskets in {}",
+                self.file_name
+            )));
+        }
+        let [leaf] = self.leaves.as_slice() else {
+            return Err(unsupported(format!("has {} leaves", self.leaves.len())));
+        };
+        if leaf.name != self.name {
+            return Err(unsupported(format!("has a leaf named {}", leaf.name)));
+        }
+        let (leaf_type, kind) = match self.class.as_str() {
+            "TBranch" => {
+                let leaf_type = LeafType::from_leaf_class(&leaf.class, leaf.is_unsigned)
+                    .ok_or_else(|| unsupported(format!("has a leaf of class {}", leaf.class)))?;
+                let kind = match leaf.len {
+                    _ if leaf.has_count => {
+                        return Err(unsupported(String::from("holds arrays of variable size")))
+                    }
+                    1 => BranchKind::Scalar,
+                    len if len > 1 => BranchKind::Array(len as usize),
+                    len => return Err(unsupported(format!("holds arrays of {} values", len))),
+                };
+                (leaf_type, kind)
+            }
+            "TBranchElement" if leaf.class == "TLeafElement" => {
+                let leaf_type = self
+                    .element_class
+                    .strip_prefix("vector<")
+                    .and_then(|element| element.strip_suffix('>'))
+                    .and_then(|element| LeafType::from_type_name(element.trim()))
+                    .ok_or_else(|| unsupported(format!("holds {}", self.element_class)))?;
+                (leaf_type, BranchKind::Vector)
+            }
+            class => return Err(unsupported(format!("is a {}", class))),
+        };
+
+        let baskets: Vec<BasketInfo> = self
+            .basket_bytes
+            .iter()
+            .zip(&self.basket_entry)
+            .zip(&self.basket_seek)
+            .take(self.write_basket.max(0) as usize)
+            .take_while(|(_, &seek)| seek != 0)
+            .map(|((&bytes, &entry), &seek)| BasketInfo { bytes, entry, seek })
+            .collect();
+        // the entries of a basket not yet written are kept in the tree (fBaskets) by
+        // TTree::AutoSave, a tree written by TTree::Write has all its baskets on file
+        let on_file = self.basket_entry.get(baskets.len()).copied().unwrap_or(0);
+        if on_file != self.entries {
+            return Err(unsupported(format!(
+                "has {} of its {} entries in baskets on file",
+                on_file, self.entries
+            )));
+        }
+        Ok(BranchInfo {
+            name: self.name,
+            leaf_type,
+            kind,
+            compression: self.compression,
+            basket_size: self.basket_size,
+            entries: self.entries,
+            baskets,
+        })
+    }
+}
+
+// The members of the tree and its branches, read from the streamer info of the file
+fn read_tree(file: &mut TFile, key: &TKey) -> BinResult<(TreeInfo, Vec<RawBranch>)> {
+    let mut reader = key.decompress_full(file.reader_mut())?;
+    reader.seek(SeekFrom::Start(key.key_len as u64))?;
+    // the buffer starts at the key, like the offsets of its class tags
+    let ctx = ReadContext::new().with_schemas(file.schemas.clone());
+    let schema = required_schema(&ctx, key.class_name.as_str())?;
+    let mut tree = TreeInfo {
+        name: String::new(),
+        title: String::new(),
+        entries: 0,
+        auto_flush: 0,
+        cluster_range_end: Vec::new(),
+        cluster_size: Vec::new(),
+        branches: Vec::new(),
+    };
+    let mut branches = Vec::new();
+    let mut ranges = 0;
+    read_with_schema(
+        &mut reader,
+        Endian::Big,
+        &ctx,
+        &schema,
+        &mut |field, reader| {
+            match field.name.as_str() {
+                "fName" => tree.name = TString::read_be(reader)?.to_string(),
+                "fTitle" => tree.title = TString::read_be(reader)?.to_string(),
+                "fEntries" => tree.entries = reader.read_be()?,
+                "fAutoFlush" => tree.auto_flush = reader.read_be()?,
+                "fNClusterRange" => ranges = reader.read_be::<i32>()? as i64,
+                "fClusterRangeEnd" => {
+                    tree.cluster_range_end = read_basic_pointer(reader, Endian::Big, ranges)?
+                }
+                "fClusterSize" => {
+                    tree.cluster_size = read_basic_pointer(reader, Endian::Big, ranges)?
+                }
+                "fBranches" => read_obj_array(reader, |reader| {
+                    branches.extend(read_branch(reader, &ctx)?);
+                    Ok(())
+                })?,
+                // TArray members have no byte count
+                "fIndexValues" => skip_array(reader, 8)?,
+                "fIndex" => skip_array(reader, 4)?,
+                "fAliases" | "fTreeIndex" | "fFriends" | "fUserInfo" | "fBranchRef" => {
+                    skip_pointer(reader, &ctx)?;
+                }
+                _ => return Ok(false),
+            }
+            Ok(true)
+        },
+    )?;
+    Ok((tree, branches))
+}
+
+// A TBranch or TBranchElement element of a TObjArray
+fn read_branch<R: Read + Seek>(reader: &mut R, ctx: &ReadContext) -> BinResult<Option<RawBranch>> {
+    let Some((class, end)) = new_object(reader, ctx)? else {
+        return Ok(None);
+    };
+    let schema = required_schema(ctx, &class)?;
+    let mut branch = RawBranch {
+        class,
+        ..Default::default()
+    };
+    let mut max_baskets = 0;
+    read_with_schema(reader, Endian::Big, ctx, &schema, &mut |field, reader| {
+        match field.name.as_str() {
+            "fName" => branch.name = TString::read_be(reader)?.to_string(),
+            "fClassName" => branch.element_class = TString::read_be(reader)?.to_string(),
+            "fCompress" => branch.compression = reader.read_be()?,
+            "fBasketSize" => branch.basket_size = reader.read_be()?,
+            "fWriteBasket" => branch.write_basket = reader.read_be()?,
+            "fEntries" => branch.entries = reader.read_be()?,
+            "fMaxBaskets" => max_baskets = reader.read_be::<i32>()? as i64,
+            "fBasketBytes" => {
+                branch.basket_bytes = read_basic_pointer(reader, Endian::Big, max_baskets)?
+            }
+            "fBasketEntry" => {
+                branch.basket_entry = read_basic_pointer(reader, Endian::Big, max_baskets)?
+            }
+            "fBasketSeek" => {
+                branch.basket_seek = read_basic_pointer(reader, Endian::Big, max_baskets)?
+            }
+            "fFileName" => branch.file_name = TString::read_be(reader)?.to_string(),
+            "fBranches" => read_obj_array(reader, |reader| {
+                if let Some((_, end)) = new_object(reader, ctx)? {
+                    branch.subbranches += 1;
+                    reader.seek(SeekFrom::Start(end))?;
+                }
+                Ok(())
+            })?,
+            "fLeaves" => read_obj_array(reader, |reader| {
+                branch.leaves.extend(read_leaf(reader, ctx)?);
+                Ok(())
+            })?,
+            "fBranchCount" | "fBranchCount2" => {
+                skip_pointer(reader, ctx)?;
+            }
+            _ => return Ok(false),
+        }
+        Ok(true)
+    })?;
+    reader.seek(SeekFrom::Start(end))?;
+    Ok(Some(branch))
+}
+
+// A TLeaf element of the fLeaves of a branch
+fn read_leaf<R: Read + Seek>(reader: &mut R, ctx: &ReadContext) -> BinResult<Option<RawLeaf>> {
+    let Some((class, end)) = new_object(reader, ctx)? else {
+        return Ok(None);
+    };
+    let schema = required_schema(ctx, &class)?;
+    let mut leaf = RawLeaf {
+        class,
+        ..Default::default()
+    };
+    read_with_schema(reader, Endian::Big, ctx, &schema, &mut |field, reader| {
+        match field.name.as_str() {
+            "fName" => leaf.name = TString::read_be(reader)?.to_string(),
+            "fLen" => leaf.len = reader.read_be()?,
+            "fIsUnsigned" => leaf.is_unsigned = reader.read_be::<u8>()? != 0,
+            "fLeafCount" => leaf.has_count = skip_pointer(reader, ctx)?,
+            _ => return Ok(false),
+        }
+        Ok(true)
+    })?;
+    reader.seek(SeekFrom::Start(end))?;
+    Ok(Some(leaf))
+}
+
+// TObjArray::Streamer, `read` reads each element from its object header on
+fn read_obj_array<R: Read + Seek>(
+    reader: &mut R,
+    mut read: impl FnMut(&mut R) -> BinResult<()>,
+) -> BinResult<()> {
+    let header = VersionHeader::read(reader, Endian::Big)?;
+    TObject::read_be(reader)?;
+    TString::read_be(reader)?;
+    let len: i32 = reader.read_be()?;
+    let _lower_bound: i32 = reader.read_be()?;
+    for _ in 0..len {
+        read(reader)?;
+    }
+    header.skip_to_end(reader)
+}
+
+// The class of a new object and the position of its end, None for null and references. The
+// object is mapped for the references to it.
+fn new_object<R: Read + Seek>(
+    reader: &mut R,
+    ctx: &ReadContext,
+) -> BinResult<Option<(String, u64)>> {
+    match ctx.read_object_header(reader, Endian::Big)? {
+        ObjectHeader::New {
+            start,
+            byte_count,
+            class_info,
+        } => {
+            let class = class_info.get_class_name();
+            ctx.map_object(start, &class, None);
+            Ok(Some((class, start + 4 + byte_count as u64)))
+        }
+        _ => Ok(None),
+    }
+}
+
+// A pointer member stepped over, false when it is null
+fn skip_pointer<R: Read + Seek>(reader: &mut R, ctx: &ReadContext) -> BinResult<bool> {
+    match ctx.read_object_header(reader, Endian::Big)? {
+        ObjectHeader::Null => Ok(false),
+        ObjectHeader::Reference { .. } => Ok(true),
+        ObjectHeader::New {
+            start,
+            byte_count,
+            class_info,
+        } => {
+            ctx.map_object(start, &class_info.get_class_name(), None);
+            reader.seek(SeekFrom::Start(start + 4 + byte_count as u64))?;
+            Ok(true)
+        }
+    }
+}
+
+// A TArray member: its size then the values
+fn skip_array<R: Read + Seek>(reader: &mut R, size: i64) -> BinResult<()> {
+    let len: i32 = reader.read_be()?;
+    reader.seek(SeekFrom::Current(len.max(0) as i64 * size))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::file::writer::TFileWriter;
+    use crate::tree::writer::{TTreeWriter, TreeOptions};
+
+    #[test]
+    fn test_read_tree_layout() {
+        let path = std::env::temp_dir().join(format!(
+            "rusty_root_test_read_tree_layout_{}.root",
+            std::process::id()
+        ));
+        let mut file = TFileWriter::create(&path).unwrap();
+        let top = file.top();
+        let options = TreeOptions::default().with_cluster_size(4);
+        let mut tree = TTreeWriter::with_options(&mut file, top, "events", "skim", options);
+        let pt = tree.branch::<f64>("pt").unwrap();
+        let p4 = tree.array_branch::<u32>("p4", 3).unwrap();
+        let jets = tree.vector_branch::<f32>("jets").unwrap();
+        tree.fill_column(pt, &[1.0; 6]).unwrap();
+        tree.fill_column(p4, &[1u32; 18]).unwrap();
+        tree.fill_vector_column(jets, &vec![vec![1.5f32]; 6])
+            .unwrap();
+        tree.flush().unwrap();
+        tree.fill_column(pt, &[2.0; 5]).unwrap();
+        tree.fill_column(p4, &[2u32; 15]).unwrap();
+        tree.fill_vector_column(jets, &vec![Vec::<f32>::new(); 5])
+            .unwrap();
+        tree.write().unwrap();
+        file.close().unwrap();
+
+        let mut file = TFile::open(path.to_str().unwrap()).unwrap();
+        let key = file.key("events").unwrap().clone();
+        let tree = TreeInfo::read(&mut file, &key).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(
+            (tree.name.as_str(), tree.title.as_str()),
+            ("events", "skim")
+        );
+        assert_eq!(tree.entries, 11);
+        // clusters of 4, 2, 4 and 1 entries
+        assert_eq!(tree.cluster_ends(), [4, 6, 10, 11]);
+
+        let layout: Vec<(&str, LeafType, BranchKind)> = tree
+            .branches
+            .iter()
+            .map(|branch| (branch.name.as_str(), branch.leaf_type, branch.kind))
+            .collect();
+        assert_eq!(
+            layout,
+            [
+                ("pt", LeafType::F64, BranchKind::Scalar),
+                ("p4", LeafType::U32, BranchKind::Array(3)),
+                ("jets", LeafType::F32, BranchKind::Vector),
+            ]
+        );
+        for branch in &tree.branches {
+            assert_eq!((branch.entries, branch.compression), (11, 505));
+            let entries: Vec<i64> = branch.baskets.iter().map(|basket| basket.entry).collect();
+            assert_eq!(entries, [0, 4, 6, 10]);
+            assert!(branch.baskets.iter().all(|basket| basket.seek > 0));
+        }
+        assert!(tree.same_branches(&tree.clone()));
+        let mut other = tree.clone();
+        other.branches[1].kind = BranchKind::Array(4);
+        assert!(!tree.same_branches(&other));
+    }
+}
@@ -0,0 +1,811 @@
+use crate::core::write_buffer::{WriteBuffer, WriteObject};
+use crate::file::tfile::TFile;
+use crate::file::writer::{DirectoryId, TFileWriter};
+use crate::objects::tatt::{TAttFill, TAttLine, TAttMarker};
+use crate::objects::tobject::TObject;
+use crate::streamer::builtin;
+use crate::tree::branch::{write_obj_array, Branch, BranchKind, ENTRY_OFFSET_LEN};
+use crate::tree::leaf::LeafValue;
+use crate::tree::reader::TreeInfo;
+use std::io;
+
+/*
+ * Writing of trees
+ * https://root.cern/doc/v638/classTTree.html
+ *
+ * A tree of flat branches: numbers, fixed size arrays of numbers and std::vector of numbers. The
+ * entries are filled one at a time, setting every branch then calling fill like TTree::Fill, or
+ * a column at a time for one branch. The baskets of the branches are written to the file when
+ * they hold `basket_size` bytes and at the end of every cluster. Clusters end every
+ * `cluster_size` entries (fAutoFlush) and where flush is called, the clusters of other sizes are
+ * described by the cluster ranges of the tree. write flushes the remaining baskets and writes the
+ * tree in a key of its directory. The entries of a tree of another file can also be appended with
+ * its baskets copied as they are (append_tree), which is how merged trees are written.
+ *
+ *     let mut tree = TTreeWriter::new(&mut file, top, "events", "skimmed events");
+ *     let pt = tree.branch::<f64>("pt")?;
+ *     let jets = tree.vector_branch::<f32>("jet_pt")?;
+ *     tree.set(pt, 41.5)?;
+ *     tree.set_values(jets, &[50.2, 31.0])?;
+ *     tree.fill()?;
+ *     tree.write()?;
+ */
+#[derive(Debug, Clone)]
+pub struct TreeOptions {
+    // bytes of entries in a basket before it is written
+    pub basket_size: i32,
+    // entries in a cluster, fAutoFlush
+    pub cluster_size: u64,
+}
+
+impl Default for TreeOptions {
+    fn default() -> Self {
+        Self {
+            basket_size: 32000,
+            cluster_size: 100_000,
+        }
+    }
+}
+
+impl TreeOptions {
+    pub fn with_basket_size(self, basket_size: i32) -> Self {
+        Self {
+            basket_size,
+            ..self
+        }
+    }
+
+    pub fn with_cluster_size(self, cluster_size: u64) -> Self {
+        Self {
+            cluster_size: cluster_size.max(1),
+            ..self
+        }
+    }
+}
+
+// A branch of the tree being written, returned by TTreeWriter::branch and the like
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BranchId(usize);
+
+pub struct TTreeWriter<'f> {
+    file: &'f mut TFileWriter,
+    directory: DirectoryId,
+    tree: Tree,
+}
+
+// The TTree streamed in its key
+#[derive(Debug)]
+struct Tree {
+    name: String,
+    title: String,
+    options: TreeOptions,
+    branches: Vec<Branch>,
+    // the entries at which clusters were ended by flush
+    flushes: Vec<u64>,
+}
+
+impl<'f> TTreeWriter<'f> {
+    pub fn new(file: &'f mut TFileWriter, directory: DirectoryId, name: &str, title: &str) -> Self {
+        Self::with_options(file, directory, name, title, TreeOptions::default())
+    }
+
+    pub fn with_options(
+        file: &'f mut TFileWriter,
+        directory: DirectoryId,
+        name: &str,
+        title: &str,
+        options: TreeOptions,
+    ) -> Self {
+        Self {
+            file,
+            directory,
+            tree: Tree {
+                name: name.to_string(),
+                title: title.to_string(),
+                options,
+                branches: Vec::new(),
+                flushes: Vec::new(),
+            },
+        }
+    }
+
+    // TTree::Branch("pt", &pt, "pt/D"): one value per entry
+    pub fn branch<T: LeafValue>(&mut self, name: &str) -> io::Result<BranchId> {
+        self.add_branch::<T>(name, BranchKind::Scalar)
+    }
+
+    // TTree::Branch("p4", p4, "p4[4]/D"): `len` values per entry
+    pub fn array_branch<T: LeafValue>(&mut self, name: &str, len: usize) -> io::Result<BranchId> {
+        if len == 0 {
+            return Err(invalid_input(format!(
+                "array branch {} has no values",
+                name
+            )));
+        }
+        self.add_branch::<T>(name, BranchKind::Array(len))
+    }
+
+    // TTree::Branch("jet_pt", &jet_pt) of a std::vector<T>
+    pub fn vector_branch<T: LeafValue>(&mut self, name: &str) -> io::Result<BranchId> {
+        self.add_branch::<T>(name, BranchKind::Vector)
+    }
+
+    pub fn entries(&self) -> u64 {
+        self.tree.entries()
+    }
+
+    // The value of a scalar branch in the next entry
+    pub fn set<T: LeafValue>(&mut self, branch: BranchId, value: T) -> io::Result<()> {
+        self.set_values(branch, &[value])
+    }
+
+    // The values of an array or vector branch in the next entry
+    pub fn set_values<T: LeafValue>(&mut self, branch: BranchId, values: &[T]) -> io::Result<()> {
+        let entry = self.entry_bytes(branch, values)?;
+        self.tree.branches[branch.0].pending = Some(entry);
+        Ok(())
+    }
+
+    // TTree::Fill: the values set in every branch as a new entry
+    pub fn fill(&mut self) -> io::Result<()> {
+        let entries = self.aligned_entries()?;
+        if let Some(branch) = self.tree.branches.iter().find(|b| b.pending.is_none()) {
+            return Err(invalid_input(format!(
+                "branch {} is not set for entry {}",
+                branch.name, entries
+            )));
+        }
+        for index in 0..self.tree.branches.len() {
+            let entry = self.tree.branches[index].pending.take().unwrap_or_default();
+            self.append(index, &entry)?;
+        }
+        Ok(())
+    }
+
+    // Entries of a scalar or array branch, one per value or per `len` values of an array
+    pub fn fill_column<T: LeafValue>(&mut self, branch: BranchId, values: &[T]) -> io::Result<()> {
+        let len = match self.tree.branches[branch.0].kind {
+            BranchKind::Scalar => 1,
+            BranchKind::Array(len) => len,
+            BranchKind::Vector => {
+                return Err(invalid_input(format!(
+                    "branch {} holds vectors, fill it with fill_vector_column",
+                    self.tree.branches[branch.0].name
+                )))
+            }
+        };
+        if !values.len().is_multiple_of(len) {
+            return Err(invalid_input(format!(
+                "{} values do not make entries of {} values",
+                values.len(),
+                len
+            )));
+        }
+        for entry in values.chunks(len) {
+            let entry = self.entry_bytes(branch, entry)?;
+            self.append(branch.0, &entry)?;
+        }
+        Ok(())
+    }
+
+    // Entries of a vector branch, one per vector
+    pub fn fill_vector_column<T: LeafValue, V: AsRef<[T]>>(
+        &mut self,
+        branch: BranchId,
+        vectors: &[V],
+    ) -> io::Result<()> {
+        for vector in vectors {
+            let entry = self.entry_bytes(branch, vector.as_ref())?;
+            self.append(branch.0, &entry)?;
+        }
+        Ok(())
+    }
+
+    /*
+     * TTreeCloner, the fast merge of hadd: the entries of the tree of another file appended after
+     * those filled so far, its baskets copied without being decompressed and its clusters kept.
+     * The first tree appended to a writer without branches gives it its branches, the others must
+     * have the same branches.
+     */
+    pub fn append_tree(&mut self, source: &mut TFile, tree: &TreeInfo) -> io::Result<()> {
+        if self.tree.branches.is_empty() {
+            for branch in &tree.branches {
+                self.tree.branches.push(Branch::new(
+                    &branch.name,
+                    branch.leaf_type,
+                    branch.kind,
+                    branch.compression,
+                    branch.basket_size,
+                ));
+            }
+        }
+        let same_branches = self.tree.branches.len() == tree.branches.len()
+            && self.tree.branches.iter().zip(&tree.branches).all(|(a, b)| {
+                (&a.name, a.leaf.leaf_type, a.kind) == (&b.name, b.leaf_type, b.kind)
+            });
+        if !same_branches || tree.branches.iter().any(|b| b.entries != tree.entries) {
+            return Err(invalid_input(format!(
+                "tree {} does not have the branches of tree {}",
+                tree.name, self.tree.name
+            )));
+        }
+        // the entries filled so far end a cluster
+        self.flush()?;
+        let start = self.tree.entries();
+        for (branch, info) in self.tree.branches.iter_mut().zip(&tree.branches) {
+            branch.copy_baskets(self.file, self.directory, source, info)?;
+        }
+        let mut cluster_start = start;
+        for end in tree.cluster_ends() {
+            let size = start + end - cluster_start;
+            let options = &mut self.tree.options;
+            options.cluster_size = options.cluster_size.max(size);
+            self.tree.flushes.push(start + end);
+            cluster_start = start + end;
+        }
+        Ok(())
+    }
+
+    // TTree::FlushBaskets: ends the cluster at the current entry, writing the baskets
+    pub fn flush(&mut self) -> io::Result<()> {
+        let entries = self.aligned_entries()?;
+        for branch in &mut self.tree.branches {
+            branch.write_basket(self.file, self.directory, &self.tree.name)?;
+        }
+        if entries > self.tree.flushes.last().copied().unwrap_or(0) {
+            self.tree.flushes.push(entries);
+        }
+        Ok(())
+    }
+
+    // TTree::Write: the remaining baskets, then the tree in a key of its directory, returning
+    // the cycle of the key
+    pub fn write(mut self) -> io::Result<u16> {
+        self.flush()?;
+        for branch in &self.tree.branches {
+            if let Some(class) = branch.collection_class() {
+                for info in builtin::streamer_infos(&class) {
+                    self.file.add_streamer_info(info);
+                }
+            }
+        }
+        self.file.put(self.directory, &self.tree.name, &self.tree)
+    }
+
+    fn add_branch<T: LeafValue>(&mut self, name: &str, kind: BranchKind) -> io::Result<BranchId> {
+        if self.tree.branches.iter().any(|branch| branch.name == name) {
+            return Err(invalid_input(format!(
+                "tree {} already has a branch {}",
+                self.tree.name, name
+            )));
+        }
+        if self.tree.entries() > 0 {
+            return Err(invalid_input(format!(
+                "branch {} is added after entries were filled",
+                name
+            )));
+        }
+        let branch = Branch::new(
+            name,
+            T::LEAF_TYPE,
+            kind,
+            self.file.compression(),
+            self.tree.options.basket_size,
+        );
+        self.tree.branches.push(branch);
+        Ok(BranchId(self.tree.branches.len() - 1))
+    }
+
+    // The streamed entry of values of the type and number the branch holds
+    fn entry_bytes<T: LeafValue>(&self, branch: BranchId, values: &[T]) -> io::Result<Vec<u8>> {
+        let branch = &self.tree.branches[branch.0];
+        if T::LEAF_TYPE != branch.leaf.leaf_type {
+            return Err(invalid_input(format!(
+                "branch {} holds {}, not {}",
+                branch.name,
+                branch.leaf.leaf_type.type_name(),
+                T::LEAF_TYPE.type_name()
+            )));
+        }
+        let expected = match branch.kind {
+            BranchKind::Scalar => Some(1),
+            BranchKind::Array(len) => Some(len),
+            BranchKind::Vector => None,
+        };
+        if let Some(expected) = expected.filter(|&expected| expected != values.len()) {
+            return Err(invalid_input(format!(
+                "branch {} holds {} values per entry, not {}",
+                branch.name,
+                expected,
+                values.len()
+            )));
+        }
+        let mut bytes = Vec::with_capacity(values.len() * T::LEAF_TYPE.size());
+        for value in values {
+            value.put(&mut bytes);
+        }
+        Ok(branch.entry_bytes(&bytes, values.len()))
+    }
+
+    // An entry at the end of a branch, whose basket is written when full or at the end of a cluster
+    fn append(&mut self, index: usize, entry: &[u8]) -> io::Result<()> {
+        let last_flush = self.tree.flushes.last().copied().unwrap_or(0);
+        let cluster_size = self.tree.options.cluster_size;
+        let branch = &mut self.tree.branches[index];
+        branch.append(entry);
+        if branch.is_basket_full() || (branch.entries - last_flush).is_multiple_of(cluster_size) {
+            branch.write_basket(self.file, self.directory, &self.tree.name)?;
+        }
+        Ok(())
+    }
+
+    // The entries of the tree, when every branch has as many
+    fn aligned_entries(&self) -> io::Result<u64> {
+        let entries = self.tree.entries();
+        match self.tree.branches.iter().find(|b| b.entries != entries) {
+            Some(branch) => Err(invalid_input(format!(
+                "branch {} has {} entries and branch {} {}",
+                self.tree.branches[0].name, entries, branch.name, branch.entries
+            ))),
+            None => Ok(entries),
+        }
+    }
+}
+
+impl Tree {
+    fn entries(&self) -> u64 {
+        self.branches.first().map_or(0, |branch| branch.entries)
+    }
+
+    // The sizes of the clusters, ended every cluster_size entries and at the flushes
+    fn cluster_sizes(&self) -> Vec<u64> {
+        let cluster_size = self.options.cluster_size;
+        let mut sizes = Vec::new();
+        let mut start = 0;
+        for &end in self.flushes.iter().chain(std::iter::once(&self.entries())) {
+            while start < end {
+                let size = cluster_size.min(end - start);
+                sizes.push(size);
+                start += size;
+            }
+        }
+        sizes
+    }
+
+    // fClusterRangeEnd, fClusterSize and fAutoFlush: the runs of clusters of the same size, the
+    // last run given by fAutoFlush, which also covers a smaller last cluster
+    fn cluster_ranges(&self) -> (Vec<i64>, Vec<i64>, i64) {
+        let sizes = self.cluster_sizes();
+        // the entry after each run and the size of its clusters
+        let mut runs: Vec<(u64, u64)> = Vec::new();
+        let mut start = 0;
+        for (i, &size) in sizes.iter().enumerate() {
+            let is_last = i + 1 == sizes.len();
+            match runs.last_mut() {
+                Some((end, run_size)) if *run_size == size || (is_last && size < *run_size) => {
+                    *end += size
+                }
+                _ => runs.push((start + size, size)),
+            }
+            start += size;
+        }
+        let auto_flush = runs
+            .pop()
+            .map_or(self.options.cluster_size, |(_, size)| size);
+        let ends = runs.iter().map(|&(end, _)| end as i64 - 1).collect();
+        let sizes = runs.iter().map(|&(_, size)| size as i64).collect();
+        (ends, sizes, auto_flush as i64)
+    }
+}
+
+// TTree::Streamer, once the baskets are written
+impl WriteObject for Tree {
+    fn class_name(&self) -> String {
+        String::from("TTree")
+    }
+
+    fn title(&self) -> &str {
+        &self.title
+    }
+
+    fn write_to(&self, buf: &mut WriteBuffer) {
+        let tot_bytes: i64 = self.branches.iter().map(Branch::tot_bytes).sum();
+        let zip_bytes: i64 = self.branches.iter().map(Branch::zip_bytes).sum();
+        let (range_ends, range_sizes, auto_flush) = self.cluster_ranges();
+        buf.versioned(20, |buf| {
+            buf.versioned(1, |buf| {
+                buf.tobject(&TObject::default());
+                buf.string(&self.name);
+                buf.string(&self.title);
+            });
+            TAttLine::new(602, 1, 1).write_to(buf);
+            TAttFill::new(0, 1001).write_to(buf);
+            TAttMarker::new(1, 1, 1.0).write_to(buf);
+            buf.write(&(self.entries() as i64));
+            buf.write(&tot_bytes);
+            buf.write(&zip_bytes);
+            // fSavedBytes and fFlushedBytes
+            buf.write(&zip_bytes);
+            buf.write(&zip_bytes);
+            // fWeight, fTimerInterval, fScanField, fUpdate
+            buf.write(&1.0f64);
+            buf.write(&0i32);
+            buf.write(&25i32);
+            buf.write(&0i32);
+            buf.write(&ENTRY_OFFSET_LEN);
+            buf.write(&(range_ends.len() as i32));
+            // fMaxEntries, fMaxEntryLoop, fMaxVirtualSize, fAutoSave, fAutoFlush, fEstimate
+            buf.write(&1_000_000_000_000i64);
+            buf.write(&1_000_000_000_000i64);
+            buf.write(&0i64);
+            buf.write(&-300_000_000i64);
+            buf.write(&auto_flush);
+            buf.write(&1_000_000i64);
+            buf.basic_pointer(&range_ends);
+            buf.basic_pointer(&range_sizes);
+            // fIOFeatures
+            buf.versioned(1, |buf| buf.put(&[0]));
+            write_obj_array(buf, self.branches.len(), |buf| {
+                for branch in &self.branches {
+                    buf.write_object(branch);
+                }
+            });
+            // the leaves written in their branches
+            write_obj_array(buf, self.branches.len(), |buf| {
+                for branch in &self.branches {
+                    buf.write_shared(&branch.leaf);
+                }
+            });
+            // fAliases, fIndexValues, fIndex, fTreeIndex, fFriends, fUserInfo, fBranchRef
+            buf.null();
+            buf.write(&0i32);
+            buf.write(&0i32);
+            for _ in 0..4 {
+                buf.null();
+            }
+        });
+    }
+}
+
+fn invalid_input(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::read_context::{ObjectHeader, ReadContext};
+    use crate::file::tfile::TFile;
+    use crate::objects::tkey::TKey;
+    use crate::objects::tstring::TString;
+    use crate::streamer::member::{
+        read_basic_pointer, read_stl_vector, read_values, VersionHeader,
+    };
+    use crate::streamer::schema_reader::{read_with_schema, required_schema};
+    use binrw::{BinRead, BinReaderExt, BinResult, Endian};
+    use std::io::{Cursor, Seek, SeekFrom};
+    use std::sync::Arc;
+
+    #[derive(Debug, Default)]
+    struct ReadBranch {
+        name: String,
+        title: String,
+        class_name: String,
+        entries: i64,
+        leaves: Vec<String>,
+        basket_entry: Vec<i64>,
+        basket_seek: Vec<i64>,
+        basket_bytes: Vec<i32>,
+    }
+
+    type Buffer = Cursor<Arc<[u8]>>;
+
+    // A TObjArray member, `read` reads each of its elements
+    fn read_obj_array(
+        reader: &mut Buffer,
+        mut read: impl FnMut(&mut Buffer) -> BinResult<()>,
+    ) -> BinResult<()> {
+        let header = VersionHeader::read(reader, Endian::Big)?;
+        TObject::read_be(reader)?;
+        TString::read_be(reader)?;
+        let len: i32 = reader.read_be()?;
+        let _lower_bound: i32 = reader.read_be()?;
+        for _ in 0..len {
+            read(reader)?;
+        }
+        header.skip_to_end(reader)
+    }
+
+    // The class of the elements of a TObjArray, "ref" for a reference to an object read before
+    fn element_classes(reader: &mut Buffer, ctx: &ReadContext) -> BinResult<Vec<String>> {
+        let mut classes = Vec::new();
+        read_obj_array(reader, |reader| {
+            match ctx.read_object_header(reader, Endian::Big)? {
+                ObjectHeader::New {
+                    start,
+                    byte_count,
+                    class_info,
+                } => {
+                    let class = class_info.get_class_name();
+                    ctx.map_object(start, &class, None);
+                    classes.push(class);
+                    reader.seek(SeekFrom::Start(start + 4 + byte_count as u64))?;
+                }
+                ObjectHeader::Reference { .. } => classes.push(String::from("ref")),
+                ObjectHeader::Null => classes.push(String::from("null")),
+            }
+            Ok(())
+        })?;
+        Ok(classes)
+    }
+
+    fn read_branch(reader: &mut Buffer, ctx: &ReadContext) -> BinResult<ReadBranch> {
+        let ObjectHeader::New { class_info, .. } = ctx.read_object_header(reader, Endian::Big)?
+        else {
+            panic!("a branch is not a new object");
+        };
+        let schema = required_schema(ctx, &class_info.get_class_name())?;
+        let mut branch = ReadBranch::default();
+        let mut max_baskets = 0;
+        read_with_schema(reader, Endian::Big, ctx, &schema, &mut |field, reader| {
+            match field.name.as_str() {
+                "fName" => branch.name = TString::read_be(reader)?.to_string(),
+                "fTitle" => branch.title = TString::read_be(reader)?.to_string(),
+                "fClassName" => branch.class_name = TString::read_be(reader)?.to_string(),
+                "fEntries" => branch.entries = reader.read_be()?,
+                "fMaxBaskets" => max_baskets = reader.read_be::<i32>()? as i64,
+                "fBasketBytes" => {
+                    branch.basket_bytes = read_basic_pointer(reader, Endian::Big, max_baskets)?
+                }
+                "fBasketEntry" => {
+                    branch.basket_entry = read_basic_pointer(reader, Endian::Big, max_baskets)?
+                }
+                "fBasketSeek" => {
+                    branch.basket_seek = read_basic_pointer(reader, Endian::Big, max_baskets)?
+                }
+                "fLeaves" => branch.leaves = element_classes(reader, ctx)?,
+                "fBranches" | "fBaskets" => assert!(element_classes(reader, ctx)?.is_empty()),
+                _ => return Ok(false),
+            }
+            Ok(true)
+        })?;
+        Ok(branch)
+    }
+
+    // The entries of the baskets of a branch, each as its bytes
+    fn read_entries(file: &mut TFile, branch: &ReadBranch) -> Vec<Vec<u8>> {
+        let mut entries = Vec::new();
+        let baskets = branch.basket_seek.iter().take_while(|&&seek| seek != 0);
+        for (i, &seek) in baskets.enumerate() {
+            let key = TKey::read_from(file.reader_mut(), seek as u64).unwrap();
+            assert_eq!(key.n_bytes as i32, branch.basket_bytes[i]);
+            assert_eq!((key.class_name.as_str(), key.cycle), ("TBasket", i as u16));
+            let bytes = key.decompress_full(file.reader_mut()).unwrap().into_inner();
+            let mut header = Cursor::new(&bytes[key.sizeof() as usize..]);
+            let version: u16 = header.read_be().unwrap();
+            let [_buffer_size, _nev_buf_size, nev_buf, last]: [i32; 4] = header.read_be().unwrap();
+            assert_eq!(version, 3);
+            assert_eq!(
+                nev_buf as i64,
+                branch.basket_entry[i + 1] - branch.basket_entry[i]
+            );
+            let last = last as usize;
+            let mut starts: Vec<usize> = if branch.class_name.is_empty() {
+                let size = (last - key.key_len as usize) / nev_buf as usize;
+                (0..nev_buf as usize)
+                    .map(|entry| key.key_len as usize + entry * size)
+                    .collect()
+            } else {
+                let mut offsets = Cursor::new(&bytes[last..]);
+                let count: i32 = offsets.read_be().unwrap();
+                let offsets: Vec<i32> =
+                    read_values(&mut offsets, Endian::Big, count as usize).unwrap();
+                // the slot after the last entry is not an offset
+                assert_eq!((count, offsets[nev_buf as usize]), (nev_buf + 1, 0));
+                offsets[..nev_buf as usize]
+                    .iter()
+                    .map(|&offset| offset as usize)
+                    .collect()
+            };
+            starts.push(last);
+            for entry in starts.windows(2) {
+                entries.push(bytes[entry[0]..entry[1]].to_vec());
+            }
+        }
+        entries
+    }
+
+    fn values<T: for<'a> BinRead<Args<'a> = ()>>(entry: &[u8]) -> Vec<T> {
+        let mut reader = Cursor::new(entry);
+        let mut values = Vec::new();
+        while (reader.position() as usize) < entry.len() {
+            values.push(reader.read_be().unwrap());
+        }
+        values
+    }
+
+    #[test]
+    fn test_write_tree() {
+        let path = std::env::temp_dir().join(format!(
+            "rusty_root_test_write_tree_{}.root",
+            std::process::id()
+        ));
+        let mut file = TFileWriter::create(&path).unwrap();
+        let top = file.top();
+        let options = TreeOptions::default().with_cluster_size(4);
+        let mut tree = TTreeWriter::with_options(&mut file, top, "events", "skim", options);
+        let pt = tree.branch::<f64>("pt").unwrap();
+        let p4 = tree.array_branch::<i32>("p4", 3).unwrap();
+        let jets = tree.vector_branch::<f32>("jets").unwrap();
+        let run = tree.branch::<u16>("run").unwrap();
+        assert!(tree.branch::<f64>("pt").is_err());
+
+        // entry by entry, then a cluster ended after 6 entries
+        for entry in 0..6 {
+            tree.set(pt, entry as f64 * 1.5).unwrap();
+            tree.set_values(p4, &[entry, entry + 1, entry + 2]).unwrap();
+            let jet_pt: Vec<f32> = (0..entry % 3).map(|jet| jet as f32 + 0.5).collect();
+            tree.set_values(jets, &jet_pt).unwrap();
+            assert!(tree.fill().is_err());
+            tree.set(run, 7u16).unwrap();
+            tree.fill().unwrap();
+        }
+        assert!(tree.set(pt, 1.0f32).is_err());
+        assert!(tree.set_values(p4, &[1, 2]).is_err());
+        tree.flush().unwrap();
+        // then column by column
+        let pts: Vec<f64> = (6..11).map(|entry| entry as f64 * 1.5).collect();
+        tree.fill_column(pt, &pts).unwrap();
+        assert!(tree.flush().is_err());
+        let p4s: Vec<i32> = (6..11)
+            .flat_map(|entry| [entry, entry + 1, entry + 2])
+            .collect();
+        tree.fill_column(p4, &p4s).unwrap();
+        let jet_pts: Vec<Vec<f32>> = (6..11)
+            .map(|entry| (0..entry % 3).map(|jet| jet as f32 + 0.5).collect())
+            .collect();
+        tree.fill_vector_column(jets, &jet_pts).unwrap();
+        tree.fill_column(run, &[7u16; 5]).unwrap();
+        assert_eq!(tree.entries(), 11);
+        assert_eq!(tree.write().unwrap(), 1);
+        file.close().unwrap();
+
+        let mut file = TFile::open(path.to_str().unwrap()).unwrap();
+        for class in ["TTree", "TBranch", "TBranchElement", "TLeafD", "TLeafI"] {
+            assert!(
+                file.streamer_info
+                    .tlist
+                    .iter()
+                    .any(|info| info.tnamed.name.as_str() == class),
+                "{}",
+                class
+            );
+        }
+        assert!(file
+            .streamer_info
+            .tlist
+            .iter()
+            .any(|info| info.tnamed.name.as_str() == "vector<float>"));
+
+        // the tree, walked with the streamer info of the file
+        let key = file.key("events").unwrap().clone();
+        assert_eq!(
+            (key.class_name.as_str(), key.title.as_str()),
+            ("TTree", "skim")
+        );
+        let mut reader = key.decompress_full(file.reader_mut()).unwrap();
+        reader.seek(SeekFrom::Start(key.key_len as u64)).unwrap();
+        let ctx = ReadContext::new().with_schemas(file.schemas.clone());
+        let schema = required_schema(&ctx, "TTree").unwrap();
+        let mut branches = Vec::new();
+        let mut tree_leaves = Vec::new();
+        let (mut entries, mut auto_flush, mut ranges) = (0i64, 0i64, 0i32);
+        let (mut range_ends, mut range_sizes) = (Vec::<i64>::new(), Vec::<i64>::new());
+        read_with_schema(
+            &mut reader,
+            Endian::Big,
+            &ctx,
+            &schema,
+            &mut |field, reader| {
+                match field.name.as_str() {
+                    "fEntries" => entries = reader.read_be()?,
+                    "fAutoFlush" => auto_flush = reader.read_be()?,
+                    "fNClusterRange" => ranges = reader.read_be()?,
+                    "fClusterRangeEnd" => {
+                        range_ends = read_basic_pointer(reader, Endian::Big, ranges as i64)?
+                    }
+                    "fClusterSize" => {
+                        range_sizes = read_basic_pointer(reader, Endian::Big, ranges as i64)?
+                    }
+                    "fBranches" => read_obj_array(reader, |reader| {
+                        branches.push(read_branch(reader, &ctx)?);
+                        Ok(())
+                    })?,
+                    "fLeaves" => tree_leaves = element_classes(reader, &ctx)?,
+                    // TArray members have no byte count
+                    "fIndexValues" | "fIndex" => assert_eq!(reader.read_be::<i32>()?, 0),
+                    _ => return Ok(false),
+                }
+                Ok(true)
+            },
+        )
+        .unwrap();
+        assert_eq!(entries, 11);
+        // clusters of 4, 2, 4 and 1 entries
+        assert_eq!(
+            (range_ends, range_sizes, auto_flush),
+            (vec![3, 5], vec![4, 2], 4)
+        );
+        assert_eq!(tree_leaves, ["ref"; 4]);
+
+        let summary: Vec<(&str, &str, &str, &str)> = branches
+            .iter()
+            .map(|branch| {
+                (
+                    branch.name.as_str(),
+                    branch.title.as_str(),
+                    branch.class_name.as_str(),
+                    branch.leaves[0].as_str(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("pt", "pt/D", "", "TLeafD"),
+                ("p4", "p4[3]/I", "", "TLeafI"),
+                ("jets", "jets", "vector<float>", "TLeafElement"),
+                ("run", "run/s", "", "TLeafS"),
+            ]
+        );
+        for branch in &branches {
+            assert_eq!(branch.entries, 11);
+            assert_eq!(branch.basket_entry[..5], [0, 4, 6, 10, 11]);
+        }
+
+        let pts: Vec<f64> = read_entries(&mut file, &branches[0])
+            .iter()
+            .flat_map(|entry| values::<f64>(entry))
+            .collect();
+        assert_eq!(
+            pts,
+            (0..11).map(|entry| entry as f64 * 1.5).collect::<Vec<_>>()
+        );
+        let p4s = read_entries(&mut file, &branches[1]);
+        assert_eq!(values::<i32>(&p4s[10]), [10, 11, 12]);
+        let jet_pts: Vec<Vec<f32>> = read_entries(&mut file, &branches[2])
+            .iter()
+            .map(|entry| read_stl_vector(&mut Cursor::new(entry), Endian::Big).unwrap())
+            .collect();
+        assert_eq!(jet_pts.len(), 11);
+        assert_eq!(jet_pts[0], Vec::<f32>::new());
+        assert_eq!(jet_pts[8], [0.5, 1.5]);
+        let runs = read_entries(&mut file, &branches[3]);
+        assert_eq!(values::<u16>(&runs.concat()), [7; 11]);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_basket_size() {
+        let path = std::env::temp_dir().join(format!(
+            "rusty_root_test_basket_size_{}.root",
+            std::process::id()
+        ));
+        let mut file = TFileWriter::create(&path).unwrap();
+        let top = file.top();
+        let options = TreeOptions::default().with_basket_size(16);
+        let mut tree = TTreeWriter::with_options(&mut file, top, "small", "", options);
+        let x = tree.branch::<f64>("x").unwrap();
+        tree.fill_column(x, &[1.0, 2.0, 3.0, 4.0, 5.0]).unwrap();
+        // full baskets of two entries are written, the last entry is pending
+        let branch = &tree.tree.branches[0];
+        assert!(branch.zip_bytes() > 0 && branch.has_basket());
+        let written = branch.tot_bytes();
+        tree.write().unwrap();
+        file.close().unwrap();
+        let file = TFile::open(path.to_str().unwrap()).unwrap();
+        assert!(file.key("small").is_some());
+        assert!(written > 0);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,57 @@
+// The code generator output for TH1D, checked in under codegen/ and compiled here as a module
+use binrw::{BinRead, Endian};
+use rusty_root_io::core::read_context::ReadContext;
+use rusty_root_io::core::write_buffer::{WriteBuffer, WriteObject};
+use rusty_root_io::objects::th1::TH1;
+use rusty_root_io::objects::tkey::TKey;
+use rusty_root_io::streamer::codegen::generate_rust;
+use rusty_root_io::streamer::streamerinfo::StreamerInfo;
+use std::fs::File;
+use std::io::{BufReader, Cursor};
+
+#[rustfmt::skip]
+#[allow(dead_code)]
+#[path = "codegen/th1d.rs"]
+mod th1d;
+
+fn read_streamer_info() -> StreamerInfo {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/testfiles/streamer_info.bin");
+    let file = File::open(path).expect("Failed to open streamer info file");
+    let mut reader = BufReader::new(file);
+    let streamer_info_header = TKey::read_be(&mut reader).expect("Failed to read TKey");
+    let tlist = BinRead::read_be(&mut reader).expect("Failed to read TList");
+    StreamerInfo {
+        streamer_info_header,
+        tlist,
+    }
+}
+
+#[test]
+fn test_generated_source_is_up_to_date() {
+    let source = generate_rust(&read_streamer_info(), &["TH1D"]).expect("Failed to generate TH1D");
+    assert_eq!(source, include_str!("codegen/th1d.rs"));
+}
+
+#[test]
+fn test_read_with_generated_source() {
+    let mut h = TH1::new("h", "generated", 4, 0.0, 4.0);
+    h.fill(0.5, 1.0);
+    h.fill(2.5, 2.0);
+    h.fill(9.0, 1.0);
+    let mut buf = WriteBuffer::new(0);
+    h.write_to(&mut buf);
+
+    let mut reader = Cursor::new(buf.into_bytes());
+    let generated = th1d::TH1D::read_options(&mut reader, Endian::Big, (ReadContext::new(),))
+        .expect("Failed to read TH1D with the generated source");
+    assert_eq!(reader.position() as usize, reader.get_ref().len());
+    assert_eq!(generated.th1.tnamed.name, "h");
+    assert_eq!(generated.th1.f_ncells, 6);
+    assert_eq!(generated.th1.f_xaxis.f_nbins, 4);
+    assert_eq!(
+        (generated.th1.f_xaxis.f_xmin, generated.th1.f_xaxis.f_xmax),
+        (0.0, 4.0)
+    );
+    assert_eq!(generated.th1.f_entries, 3.0);
+    assert_eq!(generated.tarrayd.f_array, [0.0, 1.0, 0.0, 2.0, 0.0, 1.0]);
+}
@@ -0,0 +1,459 @@
+// Generated by rusty_root codegen from the streamer info of a ROOT file.
+// Do not edit, regenerate instead.
+
+use binrw::io::{Read, Seek};
+use binrw::{BinRead, BinResult, Endian};
+use rusty_root_io::core::read_context::ReadContext;
+use rusty_root_io::objects::raw_object::RawObject;
+use rusty_root_io::objects::tarray::TArrayD;
+use rusty_root_io::objects::tnamed::TNamed;
+use rusty_root_io::objects::tstring::TString;
+use rusty_root_io::streamer::member::VersionHeader;
+use rusty_root_io::streamer::member::read_basic_pointer;
+use rusty_root_io::streamer::member::read_bool;
+use std::sync::Arc;
+
+// TAttLine version 2, checksum 0x94074549
+#[derive(Debug)]
+pub struct TAttLine {
+    pub byte_count: u32,
+    pub version: u16,
+    // Line color
+    pub f_line_color: i16,
+    // Line style
+    pub f_line_style: i16,
+    // Line width
+    pub f_line_width: i16,
+}
+
+impl BinRead for TAttLine {
+    type Args<'a> = (ReadContext,);
+
+    fn read_options<R: Read + Seek>(
+        reader: &mut R,
+        endian: Endian,
+        _args: Self::Args<'_>,
+    ) -> BinResult<Self> {
+        let header = VersionHeader::read(reader, endian)?;
+        if header.version != 2 {
+            return Err(binrw::Error::AssertFail {
+                pos: header.start,
+                message: format!("TAttLine version {} differs from the generated version 2", header.version),
+            });
+        }
+        let f_line_color = i16::read_options(reader, endian, ())?;
+        let f_line_style = i16::read_options(reader, endian, ())?;
+        let f_line_width = i16::read_options(reader, endian, ())?;
+        header.skip_to_end(reader)?;
+        Ok(Self {
+            byte_count: header.byte_count,
+            version: header.version,
+            f_line_color,
+            f_line_style,
+            f_line_width,
+        })
+    }
+}
+
+// TAttFill version 2, checksum 0xffd92a92
+#[derive(Debug)]
+pub struct TAttFill {
+    pub byte_count: u32,
+    pub version: u16,
+    // Fill area color
+    pub f_fill_color: i16,
+    // Fill area style
+    pub f_fill_style: i16,
+}
+
+impl BinRead for TAttFill {
+    type Args<'a> = (ReadContext,);
+
+    fn read_options<R: Read + Seek>(
+        reader: &mut R,
+        endian: Endian,
+        _args: Self::Args<'_>,
+    ) -> BinResult<Self> {
+        let header = VersionHeader::read(reader, endian)?;
+        if header.version != 2 {
+            return Err(binrw::Error::AssertFail {
+                pos: header.start,
+                message: format!("TAttFill version {} differs from the generated version 2", header.version),
+            });
+        }
+        let f_fill_color = i16::read_options(reader, endian, ())?;
+        let f_fill_style = i16::read_options(reader, endian, ())?;
+        header.skip_to_end(reader)?;
+        Ok(Self {
+            byte_count: header.byte_count,
+            version: header.version,
+            f_fill_color,
+            f_fill_style,
+        })
+    }
+}
+
+// TAttMarker version 3, checksum 0x291d8bec
+#[derive(Debug)]
+pub struct TAttMarker {
+    pub byte_count: u32,
+    pub version: u16,
+    // Marker color
+    pub f_marker_color: i16,
+    // Marker style
+    pub f_marker_style: i16,
+    // Marker size
+    pub f_marker_size: f32,
+}
+
+impl BinRead for TAttMarker {
+    type Args<'a> = (ReadContext,);
+
+    fn read_options<R: Read + Seek>(
+        reader: &mut R,
+        endian: Endian,
+        _args: Self::Args<'_>,
+    ) -> BinResult<Self> {
+        let header = VersionHeader::read(reader, endian)?;
+        if header.version != 3 {
+            return Err(binrw::Error::AssertFail {
+                pos: header.start,
+                message: format!("TAttMarker version {} differs from the generated version 3", header.version),
+            });
+        }
+        let f_marker_color = i16::read_options(reader, endian, ())?;
+        let f_marker_style = i16::read_options(reader, endian, ())?;
+        let f_marker_size = f32::read_options(reader, endian, ())?;
+        header.skip_to_end(reader)?;
+        Ok(Self {
+            byte_count: header.byte_count,
+            version: header.version,
+            f_marker_color,
+            f_marker_style,
+            f_marker_size,
+        })
+    }
+}
+
+// TH1D version 3, checksum 0xf9b1569f
+#[derive(Debug)]
+pub struct TH1D {
+    pub byte_count: u32,
+    pub version: u16,
+    pub th1: TH1,
+    pub tarrayd: TArrayD,
+}
+
+impl BinRead for TH1D {
+    type Args<'a> = (ReadContext,);
+
+    fn read_options<R: Read + Seek>(
+        reader: &mut R,
+        endian: Endian,
+        args: Self::Args<'_>,
+    ) -> BinResult<Self> {
+        let (ctx,) = args;
+        let header = VersionHeader::read(reader, endian)?;
+        if header.version != 3 {
+            return Err(binrw::Error::AssertFail {
+                pos: header.start,
+                message: format!("TH1D version {} differs from the generated version 3", header.version),
+            });
+        }
+        let th1 = TH1::read_options(reader, endian, (ctx.clone(),))?;
+        let tarrayd = TArrayD::read_options(reader, endian, ())?;
+        header.skip_to_end(reader)?;
+        Ok(Self {
+            byte_count: header.byte_count,
+            version: header.version,
+            th1,
+            tarrayd,
+        })
+    }
+}
+
+// TH1 version 8, checksum 0x1c3740c4
+#[derive(Debug)]
+pub struct TH1 {
+    pub byte_count: u32,
+    pub version: u16,
+    pub tnamed: TNamed,
+    pub tattline: TAttLine,
+    pub tattfill: TAttFill,
+    pub tattmarker: TAttMarker,
+    // Number of bins(1D), cells (2D) +U/Overflows
+    pub f_ncells: i32,
+    // X axis descriptor
+    pub f_xaxis: TAxis,
+    // Y axis descriptor
+    pub f_yaxis: TAxis,
+    // Z axis descriptor
+    pub f_zaxis: TAxis,
+    // (1000*offset) for bar charts or legos
+    pub f_bar_offset: i16,
+    // (1000*width) for bar charts or legos
+    pub f_bar_width: i16,
+    // Number of entries
+    pub f_entries: f64,
+    // Total Sum of weights
+    pub f_tsumw: f64,
+    // Total Sum of squares of weights
+    pub f_tsumw2: f64,
+    // Total Sum of weight*X
+    pub f_tsumwx: f64,
+    // Total Sum of weight*X*X
+    pub f_tsumwx2: f64,
+    // Maximum value for plotting
+    pub f_maximum: f64,
+    // Minimum value for plotting
+    pub f_minimum: f64,
+    // Normalization factor
+    pub f_norm_factor: f64,
+    // Array to display contour levels
+    pub f_contour: TArrayD,
+    // Array of sum of squares of weights
+    pub f_sumw2: TArrayD,
+    // Histogram options
+    pub f_option: TString,
+    // ->Pointer to list of functions (fits and user)
+    pub f_functions: RawObject,
+    // fBuffer size
+    pub f_buffer_size: i32,
+    // [fBufferSize] entry buffer
+    pub f_buffer: Vec<f64>,
+    // Option for bin statistical errors
+    pub f_bin_stat_err_opt: i32,
+    // Per object flag to use under/overflows in statistics
+    pub f_stat_overflows: i32,
+}
+
+impl BinRead for TH1 {
+    type Args<'a> = (ReadContext,);
+
+    fn read_options<R: Read + Seek>(
+        reader: &mut R,
+        endian: Endian,
+        args: Self::Args<'_>,
+    ) -> BinResult<Self> {
+        let (ctx,) = args;
+        let header = VersionHeader::read(reader, endian)?;
+        if header.version != 8 {
+            return Err(binrw::Error::AssertFail {
+                pos: header.start,
+                message: format!("TH1 version {} differs from the generated version 8", header.version),
+            });
+        }
+        let tnamed = TNamed::read_options(reader, endian, (ctx.clone(),))?;
+        let tattline = TAttLine::read_options(reader, endian, (ctx.clone(),))?;
+        let tattfill = TAttFill::read_options(reader, endian, (ctx.clone(),))?;
+        let tattmarker = TAttMarker::read_options(reader, endian, (ctx.clone(),))?;
+        let f_ncells = i32::read_options(reader, endian, ())?;
+        let f_xaxis = TAxis::read_options(reader, endian, (ctx.clone(),))?;
+        let f_yaxis = TAxis::read_options(reader, endian, (ctx.clone(),))?;
+        let f_zaxis = TAxis::read_options(reader, endian, (ctx.clone(),))?;
+        let f_bar_offset = i16::read_options(reader, endian, ())?;
+        let f_bar_width = i16::read_options(reader, endian, ())?;
+        let f_entries = f64::read_options(reader, endian, ())?;
+        let f_tsumw = f64::read_options(reader, endian, ())?;
+        let f_tsumw2 = f64::read_options(reader, endian, ())?;
+        let f_tsumwx = f64::read_options(reader, endian, ())?;
+        let f_tsumwx2 = f64::read_options(reader, endian, ())?;
+        let f_maximum = f64::read_options(reader, endian, ())?;
+        let f_minimum = f64::read_options(reader, endian, ())?;
+        let f_norm_factor = f64::read_options(reader, endian, ())?;
+        let f_contour = TArrayD::read_options(reader, endian, ())?;
+        let f_sumw2 = TArrayD::read_options(reader, endian, ())?;
+        let f_option = TString::read_options(reader, endian, ())?;
+        let f_functions = RawObject::read_options(reader, endian, (ctx.clone(),))?;
+        let f_buffer_size = i32::read_options(reader, endian, ())?;
+        let f_buffer = read_basic_pointer::<f64, _>(reader, endian, f_buffer_size as i64)?;
+        let f_bin_stat_err_opt = i32::read_options(reader, endian, ())?;
+        let f_stat_overflows = i32::read_options(reader, endian, ())?;
+        header.skip_to_end(reader)?;
+        Ok(Self {
+            byte_count: header.byte_count,
+            version: header.version,
+            tnamed,
+            tattline,
+            tattfill,
+            tattmarker,
+            f_ncells,
+            f_xaxis,
+            f_yaxis,
+            f_zaxis,
+            f_bar_offset,
+            f_bar_width,
+            f_entries,
+            f_tsumw,
+            f_tsumw2,
+            f_tsumwx,
+            f_tsumwx2,
+            f_maximum,
+            f_minimum,
+            f_norm_factor,
+            f_contour,
+            f_sumw2,
+            f_option,
+            f_functions,
+            f_buffer_size,
+            f_buffer,
+            f_bin_stat_err_opt,
+            f_stat_overflows,
+        })
+    }
+}
+
+// TAxis version 10, checksum 0x5a496e70
+#[derive(Debug)]
+pub struct TAxis {
+    pub byte_count: u32,
+    pub version: u16,
+    pub tnamed: TNamed,
+    pub tattaxis: TAttAxis,
+    // Number of bins
+    pub f_nbins: i32,
+    // Low edge of first bin
+    pub f_xmin: f64,
+    // Upper edge of last bin
+    pub f_xmax: f64,
+    // Bin edges array in X
+    pub f_xbins: TArrayD,
+    // First bin to display
+    pub f_first: i32,
+    // Last bin to display
+    pub f_last: i32,
+    // Second bit status word
+    pub f_bits2: u16,
+    // On/off displaying time values instead of numerics
+    pub f_time_display: bool,
+    // Date&time format, ex: 09/12/99 12:34:00
+    pub f_time_format: TString,
+    // List of labels
+    pub f_labels: Option<Arc<RawObject>>,
+    // List of modified labels
+    pub f_mod_labs: Option<Arc<RawObject>>,
+}
+
+impl BinRead for TAxis {
+    type Args<'a> = (ReadContext,);
+
+    fn read_options<R: Read + Seek>(
+        reader: &mut R,
+        endian: Endian,
+        args: Self::Args<'_>,
+    ) -> BinResult<Self> {
+        let (ctx,) = args;
+        let header = VersionHeader::read(reader, endian)?;
+        if header.version != 10 {
+            return Err(binrw::Error::AssertFail {
+                pos: header.start,
+                message: format!("TAxis version {} differs from the generated version 10", header.version),
+            });
+        }
+        let tnamed = TNamed::read_options(reader, endian, (ctx.clone(),))?;
+        let tattaxis = TAttAxis::read_options(reader, endian, (ctx.clone(),))?;
+        let f_nbins = i32::read_options(reader, endian, ())?;
+        let f_xmin = f64::read_options(reader, endian, ())?;
+        let f_xmax = f64::read_options(reader, endian, ())?;
+        let f_xbins = TArrayD::read_options(reader, endian, ())?;
+        let f_first = i32::read_options(reader, endian, ())?;
+        let f_last = i32::read_options(reader, endian, ())?;
+        let f_bits2 = u16::read_options(reader, endian, ())?;
+        let f_time_display = read_bool(reader, endian)?;
+        let f_time_format = TString::read_options(reader, endian, ())?;
+        let f_labels = ctx.read_streamed_object::<RawObject, _>(reader, endian)?;
+        let f_mod_labs = ctx.read_streamed_object::<RawObject, _>(reader, endian)?;
+        header.skip_to_end(reader)?;
+        Ok(Self {
+            byte_count: header.byte_count,
+            version: header.version,
+            tnamed,
+            tattaxis,
+            f_nbins,
+            f_xmin,
+            f_xmax,
+            f_xbins,
+            f_first,
+            f_last,
+            f_bits2,
+            f_time_display,
+            f_time_format,
+            f_labels,
+            f_mod_labs,
+        })
+    }
+}
+
+// TAttAxis version 4, checksum 0x5c6fff3e
+#[derive(Debug)]
+pub struct TAttAxis {
+    pub byte_count: u32,
+    pub version: u16,
+    // Number of divisions(10000*n3 + 100*n2 + n1)
+    pub f_ndivisions: i32,
+    // Color of the line axis
+    pub f_axis_color: i16,
+    // Color of labels
+    pub f_label_color: i16,
+    // Font for labels
+    pub f_label_font: i16,
+    // Offset of labels
+    pub f_label_offset: f32,
+    // Size of labels
+    pub f_label_size: f32,
+    // Length of tick marks
+    pub f_tick_length: f32,
+    // Offset of axis title
+    pub f_title_offset: f32,
+    // Size of axis title
+    pub f_title_size: f32,
+    // Color of axis title
+    pub f_title_color: i16,
+    // Font for axis title
+    pub f_title_font: i16,
+}
+
+impl BinRead for TAttAxis {
+    type Args<'a> = (ReadContext,);
+
+    fn read_options<R: Read + Seek>(
+        reader: &mut R,
+        endian: Endian,
+        _args: Self::Args<'_>,
+    ) -> BinResult<Self> {
+        let header = VersionHeader::read(reader, endian)?;
+        if header.version != 4 {
+            return Err(binrw::Error::AssertFail {
+                pos: header.start,
+                message: format!("TAttAxis version {} differs from the generated version 4", header.version),
+            });
+        }
+        let f_ndivisions = i32::read_options(reader, endian, ())?;
+        let f_axis_color = i16::read_options(reader, endian, ())?;
+        let f_label_color = i16::read_options(reader, endian, ())?;
+        let f_label_font = i16::read_options(reader, endian, ())?;
+        let f_label_offset = f32::read_options(reader, endian, ())?;
+        let f_label_size = f32::read_options(reader, endian, ())?;
+        let f_tick_length = f32::read_options(reader, endian, ())?;
+        let f_title_offset = f32::read_options(reader, endian, ())?;
+        let f_title_size = f32::read_options(reader, endian, ())?;
+        let f_title_color = i16::read_options(reader, endian, ())?;
+        let f_title_font = i16::read_options(reader, endian, ())?;
+        header.skip_to_end(reader)?;
+        Ok(Self {
+            byte_count: header.byte_count,
+            version: header.version,
+            f_ndivisions,
+            f_axis_color,
+            f_label_color,
+            f_label_font,
+            f_label_offset,
+            f_label_size,
+            f_tick_length,
+            f_title_offset,
+            f_title_size,
+            f_title_color,
+            f_title_font,
+        })
+    }
+}